@@ -1,15 +1,27 @@
 use crate::{
-    bot::Bot, character::Character, level::UpdateContext, message::Message, player::Player,
+    bot::Bot,
+    character::{Character, Team, MAX_OVERHEAL},
+    level::UpdateContext,
+    message::Message,
+    player::Player,
+    spatial_grid::SpatialGrid,
+    weapon::WeaponContainer,
 };
 use fyrox::{
     core::{
         algebra::Vector3,
+        color::Color,
+        math::ray::Ray,
         pool::{Handle, Pool},
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::Scene,
+    scene::{node::Node, Scene},
+    utils::log::{Log, MessageKind},
+};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
 };
-use std::ops::{Deref, DerefMut};
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Visit)]
@@ -33,7 +45,43 @@ macro_rules! static_dispatch {
     };
 }
 
+/// Actors never move faster than this, in meters per second.
+const MAX_TRACKED_SPEED: f32 = 50.0;
+
+// Velocity from the movement since the previous frame. Moves faster than anyone can run are
+// teleports, like respawns.
+fn tracked_velocity(
+    previous_position: Option<Vector3<f32>>,
+    position: Vector3<f32>,
+    dt: f32,
+) -> Vector3<f32> {
+    previous_position
+        .map(|previous| (position - previous).scale(1.0 / dt))
+        .filter(|velocity| velocity.norm() <= MAX_TRACKED_SPEED)
+        .unwrap_or_default()
+}
+
+/// Players are humans, they bleed red.
+const PLAYER_BLOOD_COLOR: Color = Color::opaque(140, 0, 0);
+
 impl Actor {
+    /// Color of the blood of the actor, `None` if the actor does not bleed.
+    pub fn blood_color(&self) -> Option<Color> {
+        match self {
+            Actor::Bot(bot) => bot.definition().blood_color,
+            Actor::Player(_) => Some(PLAYER_BLOOD_COLOR),
+        }
+    }
+
+    /// Medkits heal the actor up to this, bots never get more than the health of their kind
+    /// while players can overheal.
+    pub fn max_health(&self) -> f32 {
+        match self {
+            Actor::Bot(bot) => bot.max_health(),
+            Actor::Player(_) => MAX_OVERHEAL,
+        }
+    }
+
     pub fn can_be_removed(&self) -> bool {
         static_dispatch!(self, can_be_removed,)
     }
@@ -71,6 +119,17 @@ pub struct TargetDescriptor {
     pub handle: Handle<Actor>,
     pub health: f32,
     pub position: Vector3<f32>,
+    pub team: Team,
+    /// Game time when the actor hurt somebody last time.
+    pub last_damage_dealt_time: f64,
+    /// Where the current weapon of the actor points, `None` for unarmed actors.
+    pub aim: Option<Ray>,
+    /// Velocity from the movement since the previous frame.
+    pub velocity: Vector3<f32>,
+    /// Radius of the body, bots keep this far from each other.
+    pub radius: f32,
+    /// Rigid body of the actor, rays that hit its colliders hit the actor.
+    pub body: Handle<Node>,
 }
 
 #[derive(Default, Visit)]
@@ -78,6 +137,12 @@ pub struct ActorContainer {
     pool: Pool<Actor>,
     #[visit(skip)]
     target_descriptors: Vec<TargetDescriptor>,
+    // Indices of `target_descriptors`, rebuilt with them every frame.
+    #[visit(skip)]
+    target_grid: SpatialGrid<usize>,
+    // Positions of actors on the previous frame, velocities of descriptors come from them.
+    #[visit(skip)]
+    previous_positions: HashMap<Handle<Actor>, Vector3<f32>>,
 }
 
 impl ActorContainer {
@@ -85,6 +150,8 @@ impl ActorContainer {
         Self {
             pool: Default::default(),
             target_descriptors: Default::default(),
+            target_grid: Default::default(),
+            previous_positions: Default::default(),
         }
     }
 
@@ -104,6 +171,15 @@ impl ActorContainer {
         self.pool.borrow_mut(actor)
     }
 
+    /// Velocity of the actor from its movement since the previous frame, zero for unknown
+    /// actors. Rigid bodies of bots don't tell it, they're pushed much faster than they walk.
+    pub fn velocity(&self, actor: Handle<Actor>) -> Vector3<f32> {
+        self.target_descriptors
+            .iter()
+            .find(|desc| desc.handle == actor)
+            .map_or_else(Vector3::default, |desc| desc.velocity)
+    }
+
     pub fn free(&mut self, actor_handle: Handle<Actor>) {
         for actor in self.pool.iter_mut() {
             if let Actor::Bot(bot) = actor {
@@ -114,28 +190,101 @@ impl ActorContainer {
         self.pool.free(actor_handle);
     }
 
+    /// Checks invariants that must hold before serialization and repairs broken state:
+    /// dangling weapon handles are dropped and bots forget targets that no longer exist.
+    pub fn validate(&mut self, weapons: &WeaponContainer) {
+        let alive = self
+            .pool
+            .pair_iter()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        for actor in self.pool.iter_mut() {
+            let dropped = actor.validate_weapons(weapons);
+            if dropped > 0 {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "{} had {} invalid weapon handle(s), dropped.",
+                        actor.name, dropped
+                    ),
+                );
+            }
+
+            if let Actor::Bot(bot) = actor {
+                if let Some(target) = bot.target_handle() {
+                    if !alive.contains(&target) {
+                        Log::writeln(
+                            MessageKind::Warning,
+                            format!("{} had invalid target handle, dropped.", bot.name),
+                        );
+                        bot.on_actor_removed(target);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn count(&self) -> u32 {
         self.pool.alive_count()
     }
 
     pub fn update(&mut self, context: &mut UpdateContext) {
         self.target_descriptors.clear();
+        self.target_grid.clear();
         for (handle, actor) in self.pool.pair_iter() {
+            let position = actor.position(&context.scene.graph);
+            let velocity = tracked_velocity(
+                self.previous_positions.get(&handle).copied(),
+                position,
+                context.time.delta,
+            );
+            let weapon = actor.current_weapon();
+            let aim = if context.weapons.contains(weapon) {
+                let weapon = &context.weapons[weapon];
+                Some(Ray::new(
+                    weapon.get_shot_position(&context.scene.graph),
+                    weapon.get_shot_direction(&context.scene.graph),
+                ))
+            } else {
+                None
+            };
+            self.target_grid
+                .insert(self.target_descriptors.len(), position);
             self.target_descriptors.push(TargetDescriptor {
                 handle,
                 health: actor.health,
-                position: actor.position(&context.scene.graph),
+                position,
+                team: actor.team(),
+                last_damage_dealt_time: actor.last_damage_dealt_time(),
+                aim,
+                velocity,
+                radius: actor.radius(&context.scene.graph),
+                body: actor.body,
             });
         }
+        self.previous_positions.clear();
+        self.previous_positions.extend(
+            self.target_descriptors
+                .iter()
+                .map(|desc| (desc.handle, desc.position)),
+        );
 
         for (handle, actor) in self.pool.pair_iter_mut() {
             let is_dead = actor.is_dead();
+            // Player with manual pickup picks items up with the "use" key.
+            let walk_over_pickup = match actor {
+                Actor::Bot(_) => true,
+                Actor::Player(player) => !player.is_manual_pickup(),
+            };
 
             match actor {
-                Actor::Bot(bot) => bot.update(handle, context, &self.target_descriptors),
-                Actor::Player(player) => player.update(context),
+                Actor::Bot(bot) => {
+                    bot.update(handle, context, &self.target_descriptors, &self.target_grid)
+                }
+                Actor::Player(player) => player.update(handle, context, &self.target_descriptors),
             }
-            if !is_dead {
+            if !is_dead && walk_over_pickup {
                 for (item_handle, item) in context.items.pair_iter() {
                     let distance = (context.scene.graph[item.get_pivot()].global_position()
                         - actor.position(&context.scene.graph))
@@ -211,3 +360,30 @@ impl ActorContainer {
         self.pool.iter_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracked_velocity_follows_movement() {
+        let dt = 1.0 / 60.0;
+        let position = Vector3::new(4.0, 10.0, -2.0);
+        assert_eq!(
+            tracked_velocity(Some(position), position, dt),
+            Vector3::default()
+        );
+        // Falling actor moves down by 0.2 m in a frame.
+        let fallen = position - Vector3::new(0.0, 0.2, 0.0);
+        assert!(
+            (tracked_velocity(Some(position), fallen, dt) - Vector3::new(0.0, -12.0, 0.0)).norm()
+                < 1.0e-3
+        );
+        // Newly spawned and teleported actors don't move.
+        assert_eq!(tracked_velocity(None, position, dt), Vector3::default());
+        assert_eq!(
+            tracked_velocity(Some(Vector3::default()), position, dt),
+            Vector3::default()
+        );
+    }
+}
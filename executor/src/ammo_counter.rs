@@ -0,0 +1,215 @@
+//! Ammo counter of the HUD. It shows icon and ammo of the current weapon of the player in the
+//! bottom-right corner of the screen, weapons that overheat show their heat next to it.
+
+use crate::{hud::WeaponHudData, weapon::WeaponKind};
+use fyrox::{
+    core::{color::Color, parking_lot::Mutex, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        brush::Brush,
+        image::{ImageBuilder, ImageMessage},
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    utils,
+};
+use std::{path::Path, sync::Arc};
+
+/// Counter starts flashing when ammo is below this fraction of the weapon's full ammo.
+const LOW_AMMO_FRACTION: f32 = 0.2;
+/// Duration of the slide-in animation that is played on weapon switch.
+const SWITCH_ANIMATION_DURATION: f32 = 0.25;
+const SWITCH_ANIMATION_DISTANCE: f32 = 30.0;
+const MARGIN: Thickness = Thickness {
+    left: 0.0,
+    top: 0.0,
+    right: 30.0,
+    bottom: 55.0,
+};
+const NORMAL_COLOR: Color = Color {
+    r: 79,
+    g: 79,
+    b: 255,
+    a: 255,
+};
+const LOW_AMMO_COLOR: Color = Color {
+    r: 200,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+pub struct AmmoCounter {
+    pub root: Handle<UiNode>,
+    icon: Handle<UiNode>,
+    count: Handle<UiNode>,
+    resource_manager: ResourceManager,
+    // Kind of weapon that is currently shown, None when counter is hidden.
+    kind: Option<WeaponKind>,
+    switch_animation: f32,
+    low_ammo: bool,
+    overheated: bool,
+    flash_time: f32,
+}
+
+impl AmmoCounter {
+    pub fn new(
+        ctx: &mut BuildContext,
+        resource_manager: ResourceManager,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        // Default char set has no infinity sign which is shown for melee weapons.
+        let font = fyrox::core::futures::executor::block_on(Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            35.0,
+            &[0x20..0x7F, 0x221E..0x221F],
+        ))
+        .unwrap();
+        let font = SharedFont(Arc::new(Mutex::new(font)));
+
+        let icon;
+        let count;
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .on_row(row)
+                .on_column(column)
+                .with_margin(MARGIN)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_child({
+                    icon = ImageBuilder::new(
+                        WidgetBuilder::new()
+                            .with_width(35.0)
+                            .with_height(35.0)
+                            .with_margin(Thickness::right(10.0)),
+                    )
+                    .build(ctx);
+                    icon
+                })
+                .with_child({
+                    count = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_foreground(Brush::Solid(NORMAL_COLOR))
+                            .with_height(35.0),
+                    )
+                    .with_font(font)
+                    .build(ctx);
+                    count
+                }),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        Self {
+            root,
+            icon,
+            count,
+            resource_manager,
+            kind: None,
+            switch_animation: 0.0,
+            low_ammo: false,
+            overheated: false,
+            flash_time: 0.0,
+        }
+    }
+
+    /// Shows state of given weapon, `None` hides the counter (player is dead or has no weapon
+    /// in hands).
+    pub fn set_weapon(&mut self, ui: &mut UserInterface, weapon: Option<&WeaponHudData>) {
+        let weapon = match weapon {
+            Some(weapon) => weapon,
+            None => {
+                if self.kind.take().is_some() {
+                    ui.send_message(WidgetMessage::visibility(
+                        self.root,
+                        MessageDirection::ToWidget,
+                        false,
+                    ));
+                }
+                return;
+            }
+        };
+
+        let kind = weapon.kind;
+        if self.kind != Some(kind) {
+            if self.kind.is_none() {
+                ui.send_message(WidgetMessage::visibility(
+                    self.root,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            }
+            ui.send_message(ImageMessage::texture(
+                self.icon,
+                MessageDirection::ToWidget,
+                Some(utils::into_gui_texture(
+                    self.resource_manager.request_texture(weapon.icon),
+                )),
+            ));
+            self.kind = Some(kind);
+            self.switch_animation = SWITCH_ANIMATION_DURATION;
+        }
+
+        let text = if weapon.is_melee {
+            "\u{221E}".to_owned()
+        } else if weapon.is_reloading {
+            format!("- / {}", weapon.reserve_ammo)
+        } else {
+            format!("{} / {}", weapon.ammo_in_magazine, weapon.reserve_ammo)
+        };
+        let text = match weapon.heat {
+            Some(heat) => format!("{}  {}%", text, (heat * 100.0).round()),
+            None => text,
+        };
+        ui.send_message(TextMessage::text(
+            self.count,
+            MessageDirection::ToWidget,
+            text,
+        ));
+
+        self.low_ammo =
+            !weapon.is_melee && (weapon.ammo as f32) < weapon.max_ammo as f32 * LOW_AMMO_FRACTION;
+        self.overheated = weapon.is_overheated;
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        if self.switch_animation > 0.0 {
+            // Counter slides up from below on weapon switch.
+            self.switch_animation = (self.switch_animation - dt).max(0.0);
+            let k = self.switch_animation / SWITCH_ANIMATION_DURATION;
+            ui.send_message(WidgetMessage::margin(
+                self.root,
+                MessageDirection::ToWidget,
+                Thickness {
+                    bottom: MARGIN.bottom - SWITCH_ANIMATION_DISTANCE * k,
+                    ..MARGIN
+                },
+            ));
+        }
+
+        // Overheated weapon flashes the same way until it has cooled down.
+        let color = if self.low_ammo || self.overheated {
+            self.flash_time += dt;
+            if (self.flash_time * 3.0).fract() < 0.5 {
+                LOW_AMMO_COLOR
+            } else {
+                NORMAL_COLOR
+            }
+        } else {
+            self.flash_time = 0.0;
+            NORMAL_COLOR
+        };
+        ui.send_message(WidgetMessage::foreground(
+            self.count,
+            MessageDirection::ToWidget,
+            Brush::Solid(color),
+        ));
+    }
+}
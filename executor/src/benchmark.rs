@@ -0,0 +1,272 @@
+//! Benchmark mode is a stress test for performance regressions: it starts a match with
+//! lots of bots, runs simulation for fixed amount of time and reports tick time statistics.
+//!
+//! Usage: `executor --benchmark [--bots N] [--duration SECONDS] [--budget MILLISECONDS]`.
+//! When budget is specified, process exits with non-zero code if 99th percentile of tick
+//! time exceeds it, so it can be used on CI.
+//!
+//! Match is played on the first level of the level list, there is no dedicated arena. Bots
+//! make their random choices as in a normal match, so tick times of two runs differ a bit,
+//! budget on CI needs some headroom for that.
+
+use crate::bot::BotKind;
+use std::{fmt::Write as _, fs::File, io::Write};
+
+pub struct BenchmarkOptions {
+    pub bot_count: usize,
+    /// Duration of simulation in seconds of game time.
+    pub duration: f32,
+    /// Maximum allowed 99th percentile of tick time in milliseconds.
+    pub budget: Option<f32>,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            bot_count: 50,
+            duration: 30.0,
+            budget: None,
+        }
+    }
+}
+
+impl BenchmarkOptions {
+    /// Parses benchmark options from command line, returns `None` if benchmark mode is not
+    /// requested.
+    pub fn from_args() -> Option<Self> {
+        Self::parse(&std::env::args().collect::<Vec<_>>())
+    }
+
+    fn parse(args: &[String]) -> Option<Self> {
+        if !args.iter().any(|arg| arg == "--benchmark") {
+            return None;
+        }
+
+        let value_of = |name: &str| {
+            args.iter()
+                .position(|arg| arg == name)
+                .and_then(|i| args.get(i + 1))
+                .and_then(|value| value.parse::<f32>().ok())
+        };
+
+        let mut options = Self::default();
+        if let Some(bot_count) = value_of("--bots") {
+            options.bot_count = bot_count as usize;
+        }
+        if let Some(duration) = value_of("--duration") {
+            options.duration = duration;
+        }
+        options.budget = value_of("--budget");
+        Some(options)
+    }
+}
+
+pub struct BenchmarkReport {
+    pub bot_count: usize,
+    pub ticks: usize,
+    pub average: f32,
+    pub percentile_99: f32,
+    pub max: f32,
+    pub profiler: String,
+}
+
+impl BenchmarkReport {
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write!(
+            json,
+            "{{\n  \"bot_count\": {},\n  \"ticks\": {},\n  \"average_ms\": {:.4},\n  \
+             \"p99_ms\": {:.4},\n  \"max_ms\": {:.4},\n  \"profiler\": \"{}\"\n}}\n",
+            self.bot_count,
+            self.ticks,
+            self.average,
+            self.percentile_99,
+            self.max,
+            self.profiler
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )
+        .unwrap();
+        json
+    }
+}
+
+pub struct Benchmark {
+    pub options: BenchmarkOptions,
+    // Tick times in milliseconds.
+    tick_times: Vec<f32>,
+    elapsed: f32,
+    bots_spawned: bool,
+}
+
+impl Benchmark {
+    pub fn new(options: BenchmarkOptions) -> Self {
+        Self {
+            tick_times: Vec::with_capacity((options.duration * crate::FIXED_FPS) as usize),
+            options,
+            elapsed: 0.0,
+            bots_spawned: false,
+        }
+    }
+
+    /// Returns list of bots that must be added to the level, returns empty list on every
+    /// call except first one.
+    pub fn take_bots(&mut self) -> Vec<(BotKind, String)> {
+        if self.bots_spawned {
+            return Vec::new();
+        }
+        self.bots_spawned = true;
+
+        (0..self.options.bot_count)
+            .map(|i| {
+                let kind = BotKind::ALL[i % BotKind::ALL.len()];
+                (kind, format!("{} {}", kind.description(), i))
+            })
+            .collect()
+    }
+
+    pub fn record(&mut self, delta: f32, tick_time: f32) {
+        self.elapsed += delta;
+        self.tick_times.push(tick_time);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.options.duration
+    }
+
+    pub fn report(&self) -> BenchmarkReport {
+        let mut sorted = self.tick_times.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let ticks = sorted.len();
+        let average = if ticks > 0 {
+            sorted.iter().sum::<f32>() / ticks as f32
+        } else {
+            0.0
+        };
+        // Nearest rank, the smallest tick time that at least 99% of ticks don't exceed.
+        let percentile_99 = if ticks > 0 {
+            sorted[(ticks * 99 + 99) / 100 - 1]
+        } else {
+            0.0
+        };
+
+        BenchmarkReport {
+            bot_count: self.options.bot_count,
+            ticks,
+            average,
+            percentile_99,
+            max: sorted.last().copied().unwrap_or_default(),
+            profiler: fyrox::core::profiler::print().unwrap_or_default(),
+        }
+    }
+
+    /// Prints report, writes it to `benchmark.json` and returns `true` if tick time fits
+    /// into the budget.
+    pub fn finish(&self) -> bool {
+        let report = self.report();
+
+        println!(
+            "Benchmark finished: {} bots, {} ticks, average {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+            report.bot_count, report.ticks, report.average, report.percentile_99, report.max
+        );
+        println!("{}", report.profiler);
+
+        if let Ok(mut file) = File::create("benchmark.json") {
+            let _ = file.write_all(report.to_json().as_bytes());
+        }
+
+        match self.options.budget {
+            Some(budget) if report.percentile_99 > budget => {
+                println!(
+                    "Tick time budget exceeded: p99 {:.3} ms > {:.3} ms",
+                    report.percentile_99, budget
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Option<BenchmarkOptions> {
+        BenchmarkOptions::parse(
+            &args
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn options_are_parsed_from_args() {
+        assert!(parse("executor").is_none());
+        assert!(parse("executor --bots 10").is_none());
+
+        let options = parse("executor --benchmark").unwrap();
+        assert_eq!(options.bot_count, 50);
+        assert_eq!(options.duration, 30.0);
+        assert_eq!(options.budget, None);
+
+        let options = parse("executor --bots 10 --benchmark --duration 5 --budget 8.5").unwrap();
+        assert_eq!(options.bot_count, 10);
+        assert_eq!(options.duration, 5.0);
+        assert_eq!(options.budget, Some(8.5));
+
+        // Malformed or missing values keep the defaults.
+        let options = parse("executor --benchmark --bots many --duration").unwrap();
+        assert_eq!(options.bot_count, 50);
+        assert_eq!(options.duration, 30.0);
+    }
+
+    fn benchmark_with(tick_times: impl IntoIterator<Item = f32>) -> Benchmark {
+        let mut benchmark = Benchmark::new(BenchmarkOptions::default());
+        for tick_time in tick_times {
+            benchmark.record(1.0 / crate::FIXED_FPS, tick_time);
+        }
+        benchmark
+    }
+
+    #[test]
+    fn report_has_nearest_rank_percentile() {
+        // Recorded out of order, the report sorts them.
+        let report = benchmark_with((1..=100).rev().map(|i| i as f32)).report();
+        assert_eq!(report.ticks, 100);
+        assert_eq!(report.average, 50.5);
+        assert_eq!(report.percentile_99, 99.0);
+        assert_eq!(report.max, 100.0);
+
+        let report = benchmark_with((1..=1000).map(|i| i as f32)).report();
+        assert_eq!(report.percentile_99, 990.0);
+
+        // Single spike of a short run is the percentile.
+        let report = benchmark_with([1.0, 1.0, 1.0, 9.0]).report();
+        assert_eq!(report.percentile_99, 9.0);
+        assert_eq!(report.average, 3.0);
+
+        let report = benchmark_with(Vec::new()).report();
+        assert_eq!(report.ticks, 0);
+        assert_eq!(report.percentile_99, 0.0);
+        assert_eq!(report.max, 0.0);
+    }
+
+    #[test]
+    fn benchmark_finishes_after_duration() {
+        let mut benchmark = Benchmark::new(BenchmarkOptions {
+            duration: 1.0,
+            ..Default::default()
+        });
+        for _ in 0..59 {
+            benchmark.record(1.0 / 60.0, 1.0);
+        }
+        assert!(!benchmark.is_finished());
+        benchmark.record(1.0 / 60.0, 1.0);
+        benchmark.record(1.0 / 60.0, 1.0);
+        assert!(benchmark.is_finished());
+    }
+}
@@ -0,0 +1,346 @@
+//! Health bar of a boss at the top of the HUD. Level tells which boss is engaged and the bar
+//! follows its health: the fill drains smoothly after big hits, flashes when the boss enters
+//! next phase and shatters when the boss dies.
+
+use crate::{actor::Actor, hud::BossBarData};
+use fyrox::{
+    core::{color::Color, pool::Handle, rand::Rng},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        grid::{Column, GridBuilder, Row},
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    rand,
+};
+
+const BAR_WIDTH: f32 = 600.0;
+const BAR_HEIGHT: f32 = 18.0;
+/// Speed of the fill following actual health, fraction of the difference per second.
+const DRAIN_SPEED: f32 = 4.0;
+const FLASH_DURATION: f32 = 0.4;
+/// Most phases a boss can have, dividers are made up front.
+const MAX_DIVIDERS: usize = 4;
+const SHARD_COUNT: usize = 12;
+const SHATTER_DURATION: f32 = 0.8;
+/// Space under the bar where shards fall, shards would be clipped outside of it.
+const SHATTER_DROP: f32 = 60.0;
+const FILL_COLOR: Color = Color::opaque(190, 20, 20);
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum State {
+    Hidden,
+    Visible,
+    /// Time since the boss died.
+    Shattering(f32),
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+    Color::from_rgba(
+        lerp(a.r, b.r),
+        lerp(a.g, b.g),
+        lerp(a.b, b.b),
+        lerp(a.a, b.a),
+    )
+}
+
+/// Phase is the amount of thresholds the health has dropped below.
+fn phase(phases: &[f32], fraction: f32) -> usize {
+    phases
+        .iter()
+        .filter(|threshold| fraction < **threshold)
+        .count()
+}
+
+pub struct BossBar {
+    pub root: Handle<UiNode>,
+    name: Handle<UiNode>,
+    frame: Handle<UiNode>,
+    fill: Handle<UiNode>,
+    dividers: Vec<Handle<UiNode>>,
+    shard_panel: Handle<UiNode>,
+    shards: Vec<Handle<UiNode>>,
+    // Fall speed of every shard, randomized on each shatter.
+    shard_speeds: Vec<f32>,
+    state: State,
+    actor: Handle<Actor>,
+    health: f32,
+    displayed_health: f32,
+    phase: usize,
+    flash_time: f32,
+}
+
+impl BossBar {
+    pub fn new(ctx: &mut BuildContext, row: usize, column: usize) -> Self {
+        let name = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+
+        let fill = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(BAR_WIDTH)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_background(Brush::Solid(FILL_COLOR)),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        let dividers = (0..MAX_DIVIDERS)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_width(2.0)
+                        .with_horizontal_alignment(HorizontalAlignment::Left)
+                        .with_background(Brush::Solid(Color::opaque(20, 20, 20))),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let frame = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_height(BAR_HEIGHT)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                .with_foreground(Brush::Solid(Color::opaque(120, 120, 120)))
+                .with_child(fill)
+                .with_children(dividers.iter().copied()),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        let shards = (0..SHARD_COUNT)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(BAR_WIDTH / SHARD_COUNT as f32)
+                        .with_height(BAR_HEIGHT)
+                        .with_vertical_alignment(VerticalAlignment::Top)
+                        .with_background(Brush::Solid(FILL_COLOR)),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let shard_panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_children(shards.iter().copied()),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_width(BAR_WIDTH)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::top(50.0))
+                .with_child(name)
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(frame)
+                            .with_child(shard_panel),
+                    )
+                    .add_row(Row::strict(BAR_HEIGHT + SHATTER_DROP))
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            name,
+            frame,
+            fill,
+            dividers,
+            shard_panel,
+            shards,
+            shard_speeds: vec![0.0; SHARD_COUNT],
+            state: State::Hidden,
+            actor: Handle::NONE,
+            health: 0.0,
+            displayed_health: 0.0,
+            phase: 0,
+            flash_time: 0.0,
+        }
+    }
+
+    fn set_visibility(ui: &mut UserInterface, widget: Handle<UiNode>, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            widget,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    /// Syncs the bar with the boss the player fights, `None` when there is no boss fight.
+    pub fn set_data(&mut self, ui: &mut UserInterface, data: Option<&BossBarData>) {
+        let data = match data {
+            Some(data) => data,
+            None => {
+                // Shatter is played to the end even if the boss is gone.
+                if self.state == State::Visible {
+                    self.state = State::Hidden;
+                    Self::set_visibility(ui, self.root, false);
+                }
+                return;
+            }
+        };
+
+        let health = (data.health / data.max_health).max(0.0).min(1.0);
+        let is_new_boss = data.actor != self.actor || self.state == State::Hidden;
+        if is_new_boss {
+            // Dead boss stays hidden after its shatter.
+            if health <= 0.0 || matches!(self.state, State::Shattering(_)) {
+                return;
+            }
+            self.show(ui, data, health);
+            return;
+        }
+
+        if self.state != State::Visible {
+            return;
+        }
+
+        self.health = health;
+        let phase = phase(data.phases, health);
+        if phase > self.phase {
+            self.flash_time = FLASH_DURATION;
+        }
+        self.phase = phase;
+
+        if health <= 0.0 {
+            self.shatter(ui);
+        }
+    }
+
+    fn show(&mut self, ui: &mut UserInterface, data: &BossBarData, health: f32) {
+        self.state = State::Visible;
+        self.actor = data.actor;
+        self.health = health;
+        self.displayed_health = health;
+        self.phase = phase(data.phases, health);
+        self.flash_time = 0.0;
+
+        ui.send_message(TextMessage::text(
+            self.name,
+            MessageDirection::ToWidget,
+            data.name.clone(),
+        ));
+        for (i, divider) in self.dividers.iter().enumerate() {
+            let threshold = data.phases.get(i);
+            Self::set_visibility(ui, *divider, threshold.is_some());
+            if let Some(threshold) = threshold {
+                ui.send_message(WidgetMessage::margin(
+                    *divider,
+                    MessageDirection::ToWidget,
+                    Thickness::left(BAR_WIDTH * threshold - 1.0),
+                ));
+            }
+        }
+        ui.send_message(WidgetMessage::foreground(
+            self.name,
+            MessageDirection::ToWidget,
+            Brush::Solid(Color::WHITE),
+        ));
+        Self::set_visibility(ui, self.frame, true);
+        Self::set_visibility(ui, self.shard_panel, false);
+        Self::set_visibility(ui, self.root, true);
+        self.sync_fill(ui);
+    }
+
+    fn shatter(&mut self, ui: &mut UserInterface) {
+        self.state = State::Shattering(0.0);
+        let mut rng = rand::thread_rng();
+        for speed in self.shard_speeds.iter_mut() {
+            *speed = rng.gen_range(0.5..1.0);
+        }
+        for shard in self.shards.iter() {
+            ui.send_message(WidgetMessage::margin(
+                *shard,
+                MessageDirection::ToWidget,
+                Thickness::zero(),
+            ));
+        }
+        Self::set_visibility(ui, self.frame, false);
+        Self::set_visibility(ui, self.shard_panel, true);
+    }
+
+    fn sync_fill(&self, ui: &mut UserInterface) {
+        ui.send_message(WidgetMessage::width(
+            self.fill,
+            MessageDirection::ToWidget,
+            BAR_WIDTH * self.displayed_health,
+        ));
+        ui.send_message(WidgetMessage::background(
+            self.fill,
+            MessageDirection::ToWidget,
+            Brush::Solid(lerp_color(
+                FILL_COLOR,
+                Color::WHITE,
+                self.flash_time / FLASH_DURATION,
+            )),
+        ));
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        match self.state {
+            State::Hidden => (),
+            State::Visible => {
+                self.displayed_health +=
+                    (self.health - self.displayed_health) * (DRAIN_SPEED * dt).min(1.0);
+                self.flash_time = (self.flash_time - dt).max(0.0);
+                self.sync_fill(ui);
+            }
+            State::Shattering(time) => {
+                let time = time + dt;
+                if time >= SHATTER_DURATION {
+                    self.state = State::Hidden;
+                    Self::set_visibility(ui, self.root, false);
+                    return;
+                }
+                self.state = State::Shattering(time);
+
+                let t = time / SHATTER_DURATION;
+                let mut color = FILL_COLOR;
+                color.a = (255.0 * (1.0 - t)) as u8;
+                for (shard, speed) in self.shards.iter().zip(self.shard_speeds.iter()) {
+                    // Shards accelerate as they fall.
+                    ui.send_message(WidgetMessage::margin(
+                        *shard,
+                        MessageDirection::ToWidget,
+                        Thickness::top(SHATTER_DROP * speed * t * t),
+                    ));
+                    ui.send_message(WidgetMessage::background(
+                        *shard,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(color),
+                    ));
+                }
+                color = Color::WHITE;
+                color.a = (255.0 * (1.0 - t)) as u8;
+                ui.send_message(WidgetMessage::foreground(
+                    self.name,
+                    MessageDirection::ToWidget,
+                    Brush::Solid(color),
+                ));
+            }
+        }
+    }
+}
@@ -1,14 +1,22 @@
 use crate::{
     actor::{Actor, TargetDescriptor},
-    character::Character,
-    item::ItemContainer,
+    character::{Character, FOOTSTEP_SOUNDS},
+    effects::EffectKind,
+    error::GameError,
+    item::{Item, ItemContainer, ItemKind},
     level::UpdateContext,
-    message::Message,
-    weapon::WeaponContainer,
+    message::{DamageHit, Message},
+    patrol::PatrolRoute,
+    projectile::{Projectile, ProjectileDescriptor},
+    settings::SoundCategory,
+    spatial_grid::{GridQuery, SpatialGrid},
+    tunables,
+    weapon::{spread_direction, AmmoType, Weapon, WeaponAttack, WeaponContainer, WeaponKind},
     GameTime,
 };
+use bitflags::bitflags;
 use fyrox::scene::graph::physics::CoefficientCombineRule;
-use fyrox::utils::navmesh::NavmeshAgent;
+use fyrox::utils::navmesh::{Navmesh, NavmeshAgent};
 use fyrox::{
     animation::{
         machine::{self, Machine, PoseNode, State},
@@ -20,6 +28,7 @@ use fyrox::{
         math::{frustum::Frustum, ray::Ray, SmoothAngle, Vector3Ext},
         pool::Handle,
         rand::Rng,
+        scope_profile,
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
@@ -30,28 +39,296 @@ use fyrox::{
         base::BaseBuilder,
         collider::{ColliderBuilder, ColliderShape, InteractionGroups},
         debug::SceneDrawingContext,
-        graph::{physics::RayCastOptions, Graph},
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
         node::Node,
         pivot::PivotBuilder,
-        rigidbody::RigidBodyBuilder,
+        rigidbody::{RigidBody, RigidBodyBuilder},
         transform::TransformBuilder,
         Scene,
     },
     utils::log::{Log, MessageKind},
 };
+use serde::Deserialize;
 use std::{
     ops::{Deref, DerefMut},
     path::Path,
     sync::mpsc::Sender,
 };
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit)]
+bitflags! {
+    /// Parts of the debug drawing of bots, see `Bot::debug_draw`.
+    pub struct BotDebugFlags: u32 {
+        /// Path, navmesh agent and detour of a stuck bot.
+        const PATH = 1 << 0;
+        const FRUSTUM = 1 << 1;
+        /// Line to the current target, colored by its threat.
+        const TARGET = 1 << 2;
+        const POINT_OF_INTEREST = 1 << 3;
+        /// Names and active states of the animation machines, as text above the bot.
+        const STATE_LABELS = 1 << 4;
+        /// Sphere above a stuck bot, the more attempts to get unstuck the larger.
+        const STUCK = 1 << 5;
+        const DODGE = 1 << 6;
+        const PATROL = 1 << 7;
+    }
+}
+
+impl Default for BotDebugFlags {
+    fn default() -> Self {
+        Self::PATH | Self::FRUSTUM | Self::TARGET | Self::STUCK | Self::DODGE | Self::PATROL
+    }
+}
+
+/// Near plane of the vision frustum of bots, the frustum is square and the rest of it comes
+/// from the definition of a bot.
+const FRUSTUM_NEAR: f32 = 0.1;
+/// Items closer than this are looked up in the item grid, the whole level is scanned only
+/// when there is nothing around.
+const POI_SEARCH_RADIUS: f32 = 32.0;
+/// Weapon runs low on ammo when it has less than its initial ammo divided by this, bots go for
+/// ammo boxes of it then.
+const LOW_AMMO_DIVISOR: u32 = 4;
+/// Bot with no ammo left in any weapon bashes its targets. Bots that have no whip of their own
+/// bash this far, this wide and this hard.
+const DRY_CLOSE_COMBAT_DISTANCE: f32 = 2.0;
+const DRY_MELEE_HIT_RADIUS: f32 = 0.6;
+const DRY_MELEE_DAMAGE: f32 = 10.0;
+
+/// Bot hears a sound of unit loudness this far, louder sounds are heard proportionally
+/// farther.
+pub const HEARING_DISTANCE: f32 = 4.0;
+/// Bot goes to check a heard sound out for this long at most, in seconds.
+const INVESTIGATION_TIME: f32 = 6.0;
+/// Sounds this close are where the bot already is, like its own steps.
+const INVESTIGATION_ARRIVAL_DISTANCE: f32 = 1.0;
+
+/// Bot keeps in mind who has hurt it for this long, in seconds.
+const ATTACKER_MEMORY: f32 = 3.0;
+/// Actor aims at the bot when its weapon points within this angle of the bot, in degrees.
+const AIM_THREAT_ANGLE: f32 = 10.0;
+/// Threat of a candidate target is added up from these, the closeness of the candidate adds
+/// up to one more.
+const ATTACKER_THREAT: f32 = 2.0;
+const AIMING_THREAT: f32 = 1.0;
+const DAMAGE_DEALER_THREAT: f32 = 0.5;
+const MAX_THREAT: f32 = 1.0 + ATTACKER_THREAT + AIMING_THREAT + DAMAGE_DEALER_THREAT;
+
+/// Projectiles at least this fast, in meters per second, are shot right at the target.
+const NO_LEAD_PROJECTILE_SPEED: f32 = 40.0;
+/// Bots lead their targets by the distance the target walks in this time at most, in seconds.
+const MAX_LEAD_TIME: f32 = 1.0;
+/// Bot of this aim error doesn't lead its targets at all, in degrees.
+const NO_LEAD_AIM_ERROR: f32 = 12.0;
+
+/// Bot can't aim for this long after it's staggered by a hit, in seconds.
+const HIT_REACTION_TIME: f32 = 0.8;
+/// Hit of at least this damage staggers a bot whatever its resistance, like a rocket does.
+const HEAVY_HIT_DAMAGE: f32 = 50.0;
+
+/// Spine chain never bends farther than this either way, even if the weapon doesn't follow
+/// it, in degrees.
+const MAX_SPINE_PITCH: f32 = 70.0;
+
+/// Bot hurt from behind has turned to the attacker when it faces it within this angle, in
+/// degrees.
+const FACING_ANGLE: f32 = 15.0;
+
+/// Bot remembers where it has seen its target last time for this long, in seconds.
+const LAST_KNOWN_TARGET_MEMORY: f32 = 10.0;
+/// Bot starts to look around when it's this close to where it has seen its target.
+const SEARCH_ARRIVAL_DISTANCE: f32 = 1.0;
+/// Searching bot turns this far to each side of its heading, in degrees.
+const SEARCH_SWEEP_ANGLE: f32 = 75.0;
+/// Time of a full sweep to one side, the other side and back, in seconds.
+const SEARCH_SWEEP_PERIOD: f32 = 3.0;
+
+/// Fleeing bot runs for a point this far from its target, in meters.
+const FLEE_DISTANCE: f32 = 12.0;
+const FLEE_SPEED_FACTOR: f32 = 1.5;
+/// Fleeing bot that hasn't seen its target for this long, in seconds, comes back to fight.
+const FLEE_CALM_TIME: f32 = 3.0;
+
+/// Bot in ranged combat steps this far to a side of its target, in meters.
+const STRAFE_DISTANCE: f32 = 2.0;
+/// Bot keeps strafing to the same point for a random time in this range, in seconds.
+const STRAFE_TIME: std::ops::Range<f32> = 1.0..2.0;
+/// Strafe and detour points are taken only if the navmesh has a vertex this close to them, so
+/// bots don't walk into walls or off ledges.
+const NAVMESH_TOLERANCE: f32 = 1.0;
+
+/// Navmesh agent walks this far below the center of the body, at the feet of the bot.
+const AGENT_HEIGHT_OFFSET: f32 = 0.45;
+/// Agent runs ahead of the body by a step at most, the body doesn't follow an agent that is
+/// farther than this since it hasn't been placed yet.
+const MAX_AGENT_OFFSET: f32 = 4.0;
+
+/// Bot that wants to walk somewhere but has moved less than this in [`STUCK_TIME`] is stuck,
+/// in meters.
+const STUCK_DISTANCE: f32 = 0.3;
+const STUCK_TIME: f32 = 1.5;
+/// Bot is where it wants to be, and doesn't need to move, when its destination is this close.
+const STUCK_ARRIVAL_DISTANCE: f32 = 1.0;
+/// Stuck bot tries to walk to a random point this far away for this long.
+const DETOUR_DISTANCE: f32 = 2.0;
+const DETOUR_TIME: f32 = 2.0;
+
+/// Bots push away from neighbors whose bodies are closer than this, in meters.
+const SEPARATION_DISTANCE: f32 = 1.0;
+/// Push from neighbors is at most this part of a step along the path, so a crowd slows a bot
+/// down but never turns it back.
+const MAX_SEPARATION: f32 = 0.5;
+
+/// Bot dodges a projectile that is going to pass closer than this, in meters.
+const DODGE_RADIUS: f32 = 1.5;
+/// Bot only reacts to projectiles that reach it within this time, in seconds.
+const DODGE_LOOKAHEAD: f32 = 1.0;
+/// Duration of a sidestep, in seconds.
+const DODGE_TIME: f32 = 0.5;
+/// Time between two sidesteps, a barrage of rockets catches the bot eventually.
+const DODGE_COOLDOWN: f32 = 1.5;
+
+/// Dead bot stays on the level for this long by default, in seconds.
+pub const CORPSE_LIFETIME: f32 = 30.0;
+/// Line of fire from the muzzle is checked this often while the bot wants to shoot, the
+/// result is kept in between, in seconds.
+const LINE_OF_FIRE_CHECK_INTERVAL: f32 = 0.1;
+/// Item picked as the point of interest is forgotten after this time unless it's picked
+/// again, in seconds.
+const ITEM_POI_TIME: f32 = 5.0;
+/// Time a bot keeps the point it was hurt from as its point of interest, in seconds.
+pub const DAMAGE_POI_TIME: f32 = 10.0;
+/// Time between two idle sounds of a bot without a target, in seconds.
+const IDLE_SOUND_INTERVAL: std::ops::Range<f32> = 6.0..12.0;
+/// Bot that keeps getting hurt cries out at most this often, in seconds.
+const PAIN_SOUND_INTERVAL: f32 = 1.0;
+
+/// Bots at full rate look for targets this often, in seconds. Target is extrapolated in
+/// between.
+pub const TARGET_PERCEPTION_INTERVAL: f32 = 0.1;
+
+/// Bots at reduced rate look around and plan their path this often, in seconds.
+const REDUCED_PERCEPTION_INTERVAL: f32 = 0.5;
+/// Bots at reduced rate evaluate their animation machines this often, in seconds.
+const REDUCED_ANIMATION_INTERVAL: f32 = 0.1;
+
+/// How well a bot shoots. Presets are picked by the difficulty of the settings, but every bot
+/// has a profile of its own.
+#[derive(Copy, Clone, PartialEq, Debug, Visit)]
+pub struct BotDifficulty {
+    /// Delay between acquiring a target and the first shot at it.
+    pub reaction_time_seconds: f32,
+    /// Shots go in a random cone of this half-angle around the target.
+    pub aim_error_degrees: f32,
+    /// Shots fired in a row before a pause.
+    pub shoot_burst_length: u32,
+    /// Pause between bursts in seconds.
+    pub pause_between_bursts: f32,
+}
+
+impl BotDifficulty {
+    pub const EASY: Self = Self {
+        reaction_time_seconds: 0.8,
+        aim_error_degrees: 8.0,
+        shoot_burst_length: 3,
+        pause_between_bursts: 1.2,
+    };
+    pub const NORMAL: Self = Self {
+        reaction_time_seconds: 0.5,
+        aim_error_degrees: 4.0,
+        shoot_burst_length: 5,
+        pause_between_bursts: 0.8,
+    };
+    pub const HARD: Self = Self {
+        reaction_time_seconds: 0.25,
+        aim_error_degrees: 1.5,
+        shoot_burst_length: 8,
+        pause_between_bursts: 0.5,
+    };
+}
+
+impl BotDifficulty {
+    /// Part of the target movement that shots are led by, the more aim error the less lead.
+    pub fn lead_factor(&self) -> f32 {
+        (1.0 - self.aim_error_degrees / NO_LEAD_AIM_ERROR)
+            .max(0.0)
+            .min(1.0)
+    }
+}
+
+impl Default for BotDifficulty {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// Direction from the body to its navmesh agent, `None` when the agent is too far to be
+/// followed.
+fn agent_direction(to_agent: Vector3<f32>) -> Option<Vector3<f32>> {
+    to_agent
+        .try_normalize(f32::EPSILON)
+        .filter(|_| to_agent.norm() <= MAX_AGENT_OFFSET)
+}
+
+// Points that are off the navmesh are in walls or beyond ledges.
+fn is_on_navmesh(navmesh: &Navmesh, point: Vector3<f32>) -> bool {
+    navmesh.query_closest(point).map_or(false, |index| {
+        let offset = navmesh.vertices()[index].position() - point;
+        Vector3::new(offset.x, 0.0, offset.z).norm() <= NAVMESH_TOLERANCE
+    })
+}
+
+// Angle of a direction above the horizon, in radians.
+fn elevation(direction: Vector3<f32>) -> f32 {
+    direction
+        .try_normalize(f32::EPSILON)
+        .map_or(0.0, |direction| direction.y.max(-1.0).min(1.0).asin())
+}
+
+// Smallest angle between two yaws, in radians.
+fn angle_between(a: f32, b: f32) -> f32 {
+    let diff = (a - b).rem_euclid(std::f32::consts::TAU);
+    diff.min(std::f32::consts::TAU - diff)
+}
+
+/// Thresholds of AI level of detail, bots that are far from every player and out of combat
+/// are updated at reduced rate.
+#[derive(Copy, Clone, Debug, Visit)]
+pub struct AiLodOptions {
+    /// Bots closer than this to a player always run at full rate.
+    pub full_rate_distance: f32,
+    /// Bots stay at full rate for this many seconds after they had a target or were hit.
+    pub combat_cooldown: f32,
+}
+
+impl Default for AiLodOptions {
+    fn default() -> Self {
+        Self {
+            full_rate_distance: 35.0,
+            combat_cooldown: 5.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AiLod {
+    Full,
+    /// Perception at 2 Hz, animations at 10 Hz, no frustum and no target ray casts.
+    Reduced,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Deserialize)]
 pub enum BotKind {
     // Beasts
     Mutant,
     Parasite,
     Maw,
+    Hound,
+    Behemoth,
     // Humans
+    Soldier,
 }
 
 impl Default for BotKind {
@@ -61,11 +338,23 @@ impl Default for BotKind {
 }
 
 impl BotKind {
+    pub const ALL: [BotKind; 6] = [
+        BotKind::Mutant,
+        BotKind::Parasite,
+        BotKind::Maw,
+        BotKind::Hound,
+        BotKind::Behemoth,
+        BotKind::Soldier,
+    ];
+
     pub fn description(self) -> &'static str {
         match self {
             BotKind::Mutant => "Mutant",
             BotKind::Parasite => "Parasite",
             BotKind::Maw => "Maw",
+            BotKind::Hound => "Hound",
+            BotKind::Behemoth => "Behemoth",
+            BotKind::Soldier => "Soldier",
         }
     }
 }
@@ -74,6 +363,120 @@ impl BotKind {
 pub struct Target {
     position: Vector3<f32>,
     handle: Handle<Actor>,
+    // Threat of the target when it was selected, it's only drawn for debugging.
+    #[visit(skip)]
+    threat: f32,
+    // Where the target was seen by last perception and how fast it was moving, `position` is
+    // extrapolated from them between perceptions and shots lead the target by the velocity.
+    seen_position: Vector3<f32>,
+    velocity: Vector3<f32>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Attacker {
+    handle: Handle<Actor>,
+    time: f64,
+}
+
+/// Importance of a point of interest, a bot walks to the most important one it has.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Visit)]
+pub enum PoiPriority {
+    /// Item the bot wants to pick up.
+    Item,
+    /// Something the bot has heard or has been hurt by.
+    Sound,
+    /// Point given by a level script.
+    Script,
+}
+
+impl Default for PoiPriority {
+    fn default() -> Self {
+        Self::Item
+    }
+}
+
+#[derive(Default, Visit)]
+struct PointOfInterest {
+    position: Vector3<f32>,
+    priority: PoiPriority,
+    time_left: f32,
+}
+
+#[derive(Default, Visit)]
+struct Investigation {
+    position: Vector3<f32>,
+    time_left: f32,
+}
+
+#[derive(Default, Visit)]
+struct DamageSource {
+    position: Vector3<f32>,
+    time_left: f32,
+    // Bot has turned to the source and looked for the attacker there.
+    turned: bool,
+}
+
+#[derive(Default, Visit)]
+struct LastKnownTarget {
+    position: Vector3<f32>,
+    handle: Handle<Actor>,
+    // Time when the target was seen last time.
+    time: f64,
+    // Time the bot has been looking around at the position, `None` until it gets there.
+    search_time: Option<f32>,
+    // Direction the bot has come from, the sweep goes around it.
+    heading: Vector3<f32>,
+}
+
+#[derive(Default, Visit)]
+struct Flee {
+    /// Point away from the target, it moves with the target while the target is seen.
+    point: Vector3<f32>,
+    // Time since the target was seen last time.
+    unseen_time: f32,
+}
+
+// Stuck bot tries these one after another, then starts over.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Unstuck {
+    Repath,
+    Detour,
+    Jump,
+}
+
+/// Leap lasts at least this long, the bot is still on the ground right after the take off.
+const LUNGE_TAKE_OFF_TIME: f32 = 0.2;
+/// Leap that never lands, off a ledge for example, is over after this many seconds.
+const MAX_LUNGE_TIME: f32 = 2.0;
+
+// Leap at a target, it's over once the bot lands.
+#[derive(Copy, Clone, Debug)]
+struct Lunge {
+    target: Handle<Actor>,
+    // Time in the air, the bot has landed when it is on the ground after the take off.
+    time: f32,
+}
+
+// Sidestep out of the path of a projectile.
+#[derive(Copy, Clone, Debug)]
+struct Dodge {
+    direction: Vector3<f32>,
+    time_left: f32,
+    // Where the projectile would have hit the bot, for debugging.
+    impact: Vector3<f32>,
+}
+
+// Damage taken since the last stagger, it's dropped once the window is over.
+#[derive(Default, Visit)]
+struct StaggerWindow {
+    damage: f32,
+    time_left: f32,
+}
+
+#[derive(Default, Visit)]
+struct Strafe {
+    point: Vector3<f32>,
+    time_left: f32,
 }
 
 #[derive(Visit)]
@@ -86,16 +489,111 @@ pub struct Bot {
     combat_machine: CombatMachine,
     dying_machine: DyingMachine,
     last_health: f32,
+    // Time left until the bot can aim after it was staggered.
     restoration_time: f32,
+    // Time left until the bot can be staggered again.
+    stagger_cooldown: f32,
+    stagger_window: Option<StaggerWindow>,
+    // Time left until the bot can slam the ground again.
+    slam_cooldown: f32,
+    // Slam has started and hasn't hit yet.
+    #[visit(skip)]
+    slam_pending: bool,
     #[visit(skip)]
     navmesh_agent: NavmeshAgent,
     frustum: Frustum,
     last_poi_update_time: f64,
-    point_of_interest: Vector3<f32>,
+    // At most one point per priority, expired points are removed every update.
+    points_of_interest: Vec<PointOfInterest>,
     last_move_dir: Vector3<f32>,
-    spine: Handle<Node>,
+    // Bones that bend to aim vertically, from the lowest one up.
+    spine_chain: Vec<Handle<Node>>,
     yaw: SmoothAngle,
     pitch: SmoothAngle,
+    // Level of detail is decided every frame, so none of it is saved.
+    #[visit(skip)]
+    lod: AiLod,
+    // Time since the bot had a target or was hit.
+    #[visit(skip)]
+    calm_time: f32,
+    // Time since last perception and animation updates at reduced rate.
+    #[visit(skip)]
+    perception_time: f32,
+    #[visit(skip)]
+    animation_time: f32,
+    /// Time between looks for targets, zero makes the bot look every frame.
+    #[visit(skip)]
+    pub perception_interval: f32,
+    // Number of the last perception tick and its game time.
+    #[visit(skip)]
+    perception_tick: i64,
+    #[visit(skip)]
+    last_perception_time: f64,
+    patrol: PatrolRoute,
+    // Sound that the bot has heard and walks to, it's dropped once the bot sees a target.
+    investigation: Option<Investigation>,
+    // Actors that have hurt the bot recently, they are the first ones to fight back.
+    #[visit(skip)]
+    attackers: Vec<Attacker>,
+    // Where the bot was hurt from while it had no target, it turns there once the hit reaction
+    // is over and walks there.
+    damage_source: Option<DamageSource>,
+    // Where the target was seen last time, the bot searches there once it loses the target.
+    last_known_target: Option<LastKnownTarget>,
+    flee: Option<Flee>,
+    // Bot has already fled at this health, it fights until it's healed.
+    fled: bool,
+    // Point to a side of the target that the bot steps to while it shoots.
+    strafe: Option<Strafe>,
+    // Stuck detection: where the bot was when the current check has started, time since then
+    // and the number of attempts to get unstuck in a row.
+    #[visit(skip)]
+    stuck_check_position: Vector3<f32>,
+    #[visit(skip)]
+    stuck_check_time: f32,
+    #[visit(skip)]
+    unstuck_attempts: u32,
+    // Point off the path that a stuck bot walks to, it's a strafe point of its own kind.
+    #[visit(skip)]
+    detour: Option<Strafe>,
+    #[visit(skip)]
+    repath_requested: bool,
+    /// Number of times the bot has got unstuck, for debugging.
+    #[visit(skip)]
+    pub unstuck_count: u32,
+    // Leaps are short, there is no need to save them.
+    #[visit(skip)]
+    lunge: Option<Lunge>,
+    #[visit(skip)]
+    lunge_cooldown: f32,
+    #[visit(skip)]
+    dodge: Option<Dodge>,
+    #[visit(skip)]
+    dodge_cooldown: f32,
+    difficulty: BotDifficulty,
+    // Time left until the bot reacts to its new target.
+    reaction_time_left: f32,
+    // Shots of current burst and the pause after the last one.
+    burst_shots: u32,
+    burst_pause_left: f32,
+    last_shot_time: f64,
+    // Every weapon of the bot is empty, it's checked every frame.
+    #[visit(skip)]
+    out_of_ammo: bool,
+    // Last result of the line of fire check and the time left until the next one.
+    #[visit(skip)]
+    line_of_fire_clear: bool,
+    #[visit(skip)]
+    line_of_fire_check_time_left: f32,
+    /// Dead bot is removed this many seconds after its dying animation is over.
+    pub corpse_lifetime: f32,
+    // Time since the dying animation is over, it's saved so corpses don't linger after a load.
+    corpse_time: f32,
+    // Vocal timers and states are saved, so a loaded bot doesn't burst into sounds.
+    idle_sound_time_left: f32,
+    pain_sound_cooldown: f32,
+    had_target: bool,
+    death_sound_played: bool,
 }
 
 impl Deref for Bot {
@@ -124,12 +622,16 @@ impl Default for Bot {
             dying_machine: Default::default(),
             last_health: 0.0,
             restoration_time: 0.0,
+            stagger_cooldown: 0.0,
+            stagger_window: None,
+            slam_cooldown: 0.0,
+            slam_pending: false,
             navmesh_agent: Default::default(),
             frustum: Default::default(),
             last_poi_update_time: -10.0,
-            point_of_interest: Default::default(),
+            points_of_interest: Default::default(),
             last_move_dir: Default::default(),
-            spine: Default::default(),
+            spine_chain: Default::default(),
             yaw: SmoothAngle {
                 angle: 0.0,
                 target: 0.0,
@@ -140,6 +642,45 @@ impl Default for Bot {
                 target: 0.0,
                 speed: 260.0f32.to_radians(), // rad/s
             },
+            lod: AiLod::Full,
+            calm_time: 0.0,
+            perception_time: 0.0,
+            animation_time: 0.0,
+            perception_interval: TARGET_PERCEPTION_INTERVAL,
+            perception_tick: i64::MIN,
+            last_perception_time: 0.0,
+            patrol: Default::default(),
+            investigation: None,
+            attackers: Default::default(),
+            damage_source: None,
+            last_known_target: None,
+            flee: None,
+            fled: false,
+            strafe: None,
+            stuck_check_position: Default::default(),
+            stuck_check_time: 0.0,
+            unstuck_attempts: 0,
+            detour: None,
+            repath_requested: false,
+            unstuck_count: 0,
+            lunge: None,
+            lunge_cooldown: 0.0,
+            dodge: None,
+            dodge_cooldown: 0.0,
+            difficulty: Default::default(),
+            reaction_time_left: 0.0,
+            burst_shots: 0,
+            burst_pause_left: 0.0,
+            last_shot_time: 0.0,
+            out_of_ammo: false,
+            line_of_fire_clear: false,
+            line_of_fire_check_time_left: 0.0,
+            corpse_lifetime: CORPSE_LIFETIME,
+            corpse_time: 0.0,
+            idle_sound_time_left: IDLE_SOUND_INTERVAL.end,
+            pain_sound_cooldown: 0.0,
+            had_target: false,
+            death_sound_played: false,
         }
     }
 }
@@ -158,29 +699,230 @@ pub struct BotDefinition {
     pub jump_animation: &'static str,
     pub falling_animation: &'static str,
     pub hit_reaction_animation: &'static str,
+    /// Walks to the sides, they are blended with the forward walk and the backward one by the
+    /// direction the bot moves in. Backward walk is the forward one played in reverse. Bots
+    /// without side walks play the forward walk when they step aside.
+    pub strafe_animations: Option<StrafeAnimations>,
     pub dying_animation: &'static str,
     pub dead_animation: &'static str,
     pub weapon_hand_name: &'static str,
     pub left_leg_name: &'static str,
     pub right_leg_name: &'static str,
-    pub spine: &'static str,
+    /// Bones that bend to aim vertically, from the lowest one up. Pitch is shared between
+    /// them evenly.
+    pub spine_chain: &'static [&'static str],
+    /// Pitch correction of models that have a single spine bone, in degrees. Chains of
+    /// several bones are aimed along the actual weapon direction and need none.
     pub v_aim_angle_hack: f32,
+    /// Bot runs away from its target when its health drops below this, zero for bots that
+    /// fight to the death.
+    pub flee_health_threshold: f32,
+    /// Damage of a whip hit.
+    pub melee_damage: f32,
+    /// Bot fights in close combat with targets this close, it whips them instead of shooting.
+    pub close_combat_distance: f32,
+    /// Half-width of a whip swing, targets that are farther to a side of the bot are missed.
+    pub melee_hit_radius: f32,
+    /// Bot is staggered by hits at most once in this time, in seconds.
+    pub hit_reaction_cooldown: f32,
+    /// Damage the bot has to take within the cooldown to be staggered, chip damage below it
+    /// doesn't interrupt the bot. Heavy hits stagger regardless.
+    pub stagger_threshold: f32,
+    /// Bot looks around for this long where it has lost its target, in seconds.
+    pub search_time: f32,
+    /// Bot limps when its health drops below this part of the full health, it walks slower the
+    /// more it's hurt.
+    pub limp_health_fraction: f32,
+    /// Part of the walk speed that a bot on its last breath walks at.
+    pub min_limp_speed_fraction: f32,
+    /// Field of view of the bot, both vertical and horizontal, in degrees.
+    pub view_fov_degrees: f32,
+    /// Bot sees nothing farther than this, in meters.
+    pub view_distance: f32,
+    /// Height of the eyes of the bot above its body, in meters.
+    pub head_offset: f32,
+    /// Weapons that the bot spawns with, the first one is held. Beasts that only whip may have
+    /// none.
+    pub loadout: &'static [WeaponKind],
+    /// Ranged bots keep their targets in this range instead of chasing them, they never get
+    /// into close combat. `None` for bots that chase and whip.
+    pub engagement: Option<EngagementRange>,
+    /// Bots with this leap at their targets and bite on contact, they never aim or whip.
+    pub lunge: Option<LungeDefinition>,
+    /// Bots with this change their behavior once they're hurt badly enough.
+    pub second_phase: Option<PhaseDefinition>,
+    /// Radius of the body capsule, in meters.
+    pub body_radius: f32,
+    /// Bots with this are bosses, they get a health bar on the HUD when they engage the player.
+    pub boss: Option<BossDefinition>,
+    /// Color of blood on hits, `None` for bots that don't bleed.
+    pub blood_color: Option<Color>,
+    /// Growls while the bot has no target, one of them is picked at random every few seconds.
+    /// Voice lists may be empty, the bot stays silent then.
+    pub idle_sounds: &'static [&'static str],
+    /// Bark when the bot spots a target.
+    pub aggro_sounds: &'static [&'static str],
+    pub pain_sounds: &'static [&'static str],
+    pub death_sound: Option<&'static str>,
+}
+
+impl BotDefinition {
+    /// Distance from the body of the bot to the farthest corner of its frustum, nothing
+    /// farther can be seen.
+    pub fn view_radius(&self) -> f32 {
+        let half_size = self.view_distance * (self.view_fov_degrees.to_radians() * 0.5).tan();
+        Vector3::new(half_size, half_size, self.view_distance).norm() + self.head_offset
+    }
+
+    /// Square view cone from the head of a bot at `position`, looking along `look`.
+    pub fn view_frustum(
+        &self,
+        position: Vector3<f32>,
+        look: Vector3<f32>,
+        up: Vector3<f32>,
+    ) -> Frustum {
+        let head_pos = position + Vector3::new(0.0, self.head_offset, 0.0);
+        let view_matrix =
+            Matrix4::look_at_rh(&Point3::from(head_pos), &Point3::from(head_pos + look), &up);
+        let projection_matrix = Matrix4::new_perspective(
+            1.0,
+            self.view_fov_degrees.to_radians(),
+            FRUSTUM_NEAR,
+            self.view_distance,
+        );
+        Frustum::from(projection_matrix * view_matrix).unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct EngagementRange {
+    /// Bot backs away from a target that is closer than this, in meters.
+    pub min_distance: f32,
+    /// Bot walks to a target that is farther than this, in meters.
+    pub max_distance: f32,
+}
+
+impl EngagementRange {
+    fn contains(&self, distance: f32) -> bool {
+        distance >= self.min_distance && distance <= self.max_distance
+    }
+
+    // Point to walk to, so the target at `target` gets in range of the bot at `position`.
+    fn destination(&self, position: Vector3<f32>, target: Vector3<f32>) -> Vector3<f32> {
+        let offset = position - target;
+        let distance = offset.norm();
+        if distance < self.min_distance {
+            let away = offset
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::x);
+            // Bot backs off to the middle of the range, so it doesn't stop right at its edge.
+            target + away.scale((self.min_distance + self.max_distance) * 0.5)
+        } else {
+            target
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct StrafeAnimations {
+    pub left: &'static str,
+    pub right: &'static str,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LungeDefinition {
+    /// Bot on the ground leaps at a target that is this close, in meters.
+    pub distance: f32,
+    /// Horizontal and vertical speed of the leap, in meters per second.
+    pub speed: f32,
+    pub lift: f32,
+    pub damage: f32,
+    /// Leap hits a target that gets this close to the bot, in meters.
+    pub hit_distance: f32,
+    /// Time between two leaps, in seconds.
+    pub cooldown: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PhaseDefinition {
+    /// Bot enters this phase when its health drops below this fraction of the maximum.
+    pub health_fraction: f32,
+    /// Replace the values of the definition while the phase is on.
+    pub walk_speed: f32,
+    pub hit_reaction_cooldown: f32,
+    pub stagger_threshold: f32,
+    pub slam: Option<SlamDefinition>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SlamDefinition {
+    pub animation: &'static str,
+    /// Time from the start of the slam animation to the impact, in seconds.
+    pub impact_time: f32,
+    /// Bot slams the ground when its target is this close, in meters.
+    pub distance: f32,
+    /// Everyone within this distance from the bot gets hit by the shockwave, in meters.
+    pub radius: f32,
+    pub damage: f32,
+    /// Time between two slams, in seconds.
+    pub cooldown: f32,
+}
+
+pub struct BossDefinition {
+    pub name: &'static str,
+    /// Fractions of health at which the boss enters next phase, in descending order. Boss
+    /// health bar is split into segments at these points.
+    pub phases: &'static [f32],
+}
+
+async fn request_model(resource_manager: &ResourceManager, path: &str) -> Result<Model, GameError> {
+    resource_manager
+        .request_model(path)
+        .await
+        .map_err(|e| GameError::resource_load(path, e))
+}
+
+// Animation that failed to load is replaced with the idle animation of the bot.
+async fn or_idle(
+    animation: Result<Model, GameError>,
+    resource_manager: &ResourceManager,
+    definition: &BotDefinition,
+) -> Result<Model, GameError> {
+    match animation {
+        Ok(animation) => Ok(animation),
+        Err(e) => {
+            Log::writeln(
+                MessageKind::Warning,
+                format!("{}. Idle animation is used instead.", e),
+            );
+            request_model(resource_manager, definition.idle_animation).await
+        }
+    }
 }
 
 fn prepare_animation(
     scene: &mut Scene,
     model: Model,
     root: Handle<Node>,
-    spine: Handle<Node>,
+    spine_chain: &[Handle<Node>],
 ) -> Handle<Animation> {
     let animation = model.retarget_animations(root, scene)[0];
-    scene
-        .animations
-        .get_mut(animation)
-        .set_node_track_enabled(spine, false);
+    for &bone in spine_chain {
+        scene
+            .animations
+            .get_mut(animation)
+            .set_node_track_enabled(bone, false);
+    }
     animation
 }
 
+fn state_name(machine: &Machine) -> &str {
+    machine
+        .states()
+        .try_borrow(machine.active_state())
+        .map_or("None", |state| state.name())
+}
+
 fn disable_leg_tracks(
     animation: &mut Animation,
     root: Handle<Node>,
@@ -190,10 +932,33 @@ fn disable_leg_tracks(
     animation.set_tracks_enabled_from(graph.find_by_name(root, leg_name), false, graph)
 }
 
+/// Weights of the forward, left, right and back walks. Walks are weighted by how much of the
+/// move goes their way, `move_dir` is in the space of the model: X is to the right and Z is
+/// forward. Without side walks their share goes to the forward walk.
+fn walk_weights(move_dir: Vector3<f32>, has_side_walks: bool) -> [f32; 4] {
+    let mut forward = move_dir.z.max(0.0);
+    let back = (-move_dir.z).max(0.0);
+    let mut right = move_dir.x.max(0.0);
+    let mut left = (-move_dir.x).max(0.0);
+    if !has_side_walks {
+        forward += std::mem::take(&mut right) + std::mem::take(&mut left);
+    }
+    let total = forward + back + right + left;
+    if total > f32::EPSILON {
+        [forward / total, left / total, right / total, back / total]
+    } else {
+        [1.0, 0.0, 0.0, 0.0]
+    }
+}
+
 #[derive(Visit)]
 struct LocomotionMachine {
     machine: Machine,
     walk_animation: Handle<Animation>,
+    // Forward walk played in reverse.
+    walk_back_animation: Handle<Animation>,
+    // Left and right walks, empty when the bot has none of them.
+    strafe_animations: Vec<Handle<Animation>>,
     walk_state: Handle<State>,
 }
 
@@ -202,6 +967,8 @@ impl Default for LocomotionMachine {
         Self {
             machine: Default::default(),
             walk_animation: Default::default(),
+            walk_back_animation: Default::default(),
+            strafe_animations: Default::default(),
             walk_state: Default::default(),
         }
     }
@@ -209,6 +976,8 @@ impl Default for LocomotionMachine {
 
 impl LocomotionMachine {
     pub const STEP_SIGNAL: u64 = 1;
+    /// Times of the steps within the walk animation, in seconds.
+    const STEP_TIMES: [f32; 2] = [0.4, 0.8];
 
     const WALK_TO_IDLE_PARAM: &'static str = "WalkToIdle";
     const WALK_TO_JUMP_PARAM: &'static str = "WalkToJump";
@@ -216,31 +985,79 @@ impl LocomotionMachine {
     const IDLE_TO_JUMP_PARAM: &'static str = "IdleToJump";
     const JUMP_TO_FALLING_PARAM: &'static str = "JumpToFalling";
     const FALLING_TO_IDLE_PARAM: &'static str = "FallingToIdle";
+    const WALK_FORWARD_WEIGHT: &'static str = "WalkForward";
+    const WALK_LEFT_WEIGHT: &'static str = "WalkLeft";
+    const WALK_RIGHT_WEIGHT: &'static str = "WalkRight";
+    const WALK_BACK_WEIGHT: &'static str = "WalkBack";
 
     async fn new(
         resource_manager: ResourceManager,
         definition: &BotDefinition,
         model: Handle<Node>,
         scene: &mut Scene,
-        spine: Handle<Node>,
-    ) -> Self {
+        spine_chain: &[Handle<Node>],
+    ) -> Result<Self, GameError> {
         let (idle_animation, walk_animation, jump_animation, falling_animation) = fyrox::core::futures::join!(
-            resource_manager.request_model(definition.idle_animation,),
-            resource_manager.request_model(definition.walk_animation,),
-            resource_manager.request_model(definition.jump_animation,),
-            resource_manager.request_model(definition.falling_animation,)
+            request_model(&resource_manager, definition.idle_animation),
+            request_model(&resource_manager, definition.walk_animation),
+            request_model(&resource_manager, definition.jump_animation),
+            request_model(&resource_manager, definition.falling_animation)
+        );
+        // Everything is loaded before anything is added to the scene, so a failure leaves
+        // nothing behind.
+        let (idle_animation, walk_animation, jump_animation, falling_animation) = (
+            idle_animation?,
+            walk_animation?,
+            jump_animation?,
+            falling_animation?,
         );
 
-        let idle_animation = prepare_animation(scene, idle_animation.unwrap(), model, spine);
-        let walk_animation = prepare_animation(scene, walk_animation.unwrap(), model, spine);
-        let jump_animation = prepare_animation(scene, jump_animation.unwrap(), model, spine);
-        let falling_animation = prepare_animation(scene, falling_animation.unwrap(), model, spine);
-
+        let idle_animation = prepare_animation(scene, idle_animation, model, spine_chain);
+        let walk_back_animation =
+            prepare_animation(scene, walk_animation.clone(), model, spine_chain);
         scene
             .animations
-            .get_mut(walk_animation)
-            .add_signal(AnimationSignal::new(Self::STEP_SIGNAL, 0.4))
-            .add_signal(AnimationSignal::new(Self::STEP_SIGNAL, 0.8));
+            .get_mut(walk_back_animation)
+            .set_speed(-1.0);
+        let walk_animation = prepare_animation(scene, walk_animation, model, spine_chain);
+        let jump_animation = prepare_animation(scene, jump_animation, model, spine_chain);
+        let falling_animation = prepare_animation(scene, falling_animation, model, spine_chain);
+
+        for time in Self::STEP_TIMES {
+            scene
+                .animations
+                .get_mut(walk_animation)
+                .add_signal(AnimationSignal::new(Self::STEP_SIGNAL, time));
+        }
+
+        // Strafe animations are optional, a bot that misses any of them walks forward when it
+        // steps aside.
+        let mut strafe_animations = Vec::new();
+        if let Some(strafe) = definition.strafe_animations {
+            let (left, right) = fyrox::core::futures::join!(
+                resource_manager.request_model(strafe.left),
+                resource_manager.request_model(strafe.right)
+            );
+            match (left, right) {
+                (Ok(left), Ok(right)) => {
+                    for animation in [left, right] {
+                        strafe_animations.push(prepare_animation(
+                            scene,
+                            animation,
+                            model,
+                            spine_chain,
+                        ));
+                    }
+                }
+                _ => Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "Strafe animations of {:?} failed to load, it won't step aside.",
+                        definition.kind
+                    ),
+                ),
+            }
+        }
 
         let mut machine = Machine::new(model);
 
@@ -251,7 +1068,32 @@ impl LocomotionMachine {
             machine.add_node(machine::PoseNode::make_play_animation(falling_animation));
         let falling_state = machine.add_state(State::new("Falling", falling_node));
 
-        let walk_node = machine.add_node(machine::PoseNode::make_play_animation(walk_animation));
+        let forward_node = machine.add_node(machine::PoseNode::make_play_animation(walk_animation));
+        let back_node =
+            machine.add_node(machine::PoseNode::make_play_animation(walk_back_animation));
+        let mut poses = vec![
+            machine::BlendPose::new(
+                machine::PoseWeight::Parameter(Self::WALK_FORWARD_WEIGHT.to_owned()),
+                forward_node,
+            ),
+            machine::BlendPose::new(
+                machine::PoseWeight::Parameter(Self::WALK_BACK_WEIGHT.to_owned()),
+                back_node,
+            ),
+        ];
+        if let [left, right] = strafe_animations[..] {
+            for (animation, weight) in [
+                (left, Self::WALK_LEFT_WEIGHT),
+                (right, Self::WALK_RIGHT_WEIGHT),
+            ] {
+                let node = machine.add_node(machine::PoseNode::make_play_animation(animation));
+                poses.push(machine::BlendPose::new(
+                    machine::PoseWeight::Parameter(weight.to_owned()),
+                    node,
+                ));
+            }
+        }
+        let walk_node = machine.add_node(machine::PoseNode::make_blend_animations(poses));
         let walk_state = machine.add_state(State::new("Walk", walk_node));
 
         let idle_node = machine.add_node(machine::PoseNode::make_play_animation(idle_animation));
@@ -302,11 +1144,25 @@ impl LocomotionMachine {
 
         machine.set_entry_state(idle_state);
 
-        Self {
+        Ok(Self {
             walk_animation,
+            walk_back_animation,
+            strafe_animations,
             walk_state,
             machine,
+        })
+    }
+
+    /// Playback speed of the walk animation, limping bots walk slower. Steps are signals at
+    /// fixed times of the animation, so they follow the speed.
+    fn set_walk_speed(&self, scene: &mut Scene, speed: f32) {
+        for &animation in std::iter::once(&self.walk_animation).chain(&self.strafe_animations) {
+            scene.animations.get_mut(animation).set_speed(speed);
         }
+        scene
+            .animations
+            .get_mut(self.walk_back_animation)
+            .set_speed(-speed);
     }
 
     fn is_walking(&self) -> bool {
@@ -327,8 +1183,19 @@ impl LocomotionMachine {
         in_close_combat: bool,
         need_jump: bool,
         has_ground_contact: bool,
+        move_dir: Vector3<f32>,
     ) {
+        let [forward, left, right, back] =
+            walk_weights(move_dir, !self.strafe_animations.is_empty());
+
         self.machine
+            .set_parameter(
+                Self::WALK_FORWARD_WEIGHT,
+                machine::Parameter::Weight(forward),
+            )
+            .set_parameter(Self::WALK_LEFT_WEIGHT, machine::Parameter::Weight(left))
+            .set_parameter(Self::WALK_RIGHT_WEIGHT, machine::Parameter::Weight(right))
+            .set_parameter(Self::WALK_BACK_WEIGHT, machine::Parameter::Weight(back))
             .set_parameter(
                 Self::IDLE_TO_WALK_PARAM,
                 machine::Parameter::Rule(!in_close_combat),
@@ -385,15 +1252,16 @@ impl DyingMachine {
         definition: &BotDefinition,
         model: Handle<Node>,
         scene: &mut Scene,
-        spine: Handle<Node>,
-    ) -> Self {
+        spine_chain: &[Handle<Node>],
+    ) -> Result<Self, GameError> {
         let (dying_animation, dead_animation) = fyrox::core::futures::join!(
-            resource_manager.request_model(definition.dying_animation,),
-            resource_manager.request_model(definition.dead_animation,)
+            request_model(&resource_manager, definition.dying_animation),
+            request_model(&resource_manager, definition.dead_animation)
         );
+        let (dying_animation, dead_animation) = (dying_animation?, dead_animation?);
 
-        let dying_animation = prepare_animation(scene, dying_animation.unwrap(), model, spine);
-        let dead_animation = prepare_animation(scene, dead_animation.unwrap(), model, spine);
+        let dying_animation = prepare_animation(scene, dying_animation, model, spine_chain);
+        let dead_animation = prepare_animation(scene, dead_animation, model, spine_chain);
 
         scene
             .animations
@@ -437,6 +1305,13 @@ impl DyingMachine {
         clean_machine(&self.machine, scene);
     }
 
+    // Bot is in the dead state and its last animation is over.
+    fn is_settled(&self, scene: &Scene) -> bool {
+        self.machine.active_state() == self.dead_state
+            && self.machine.active_transition().is_none()
+            && scene.animations.get(self.dead_animation).has_ended()
+    }
+
     fn apply(&mut self, scene: &mut Scene, time: GameTime, is_dead: bool) {
         scene
             .animations
@@ -460,6 +1335,9 @@ struct CombatMachine {
     hit_reaction_animation: Handle<Animation>,
     whip_animation: Handle<Animation>,
     aim_state: Handle<State>,
+    // Both are none for bots that can't slam.
+    slam_animation: Handle<Animation>,
+    slam_state: Handle<State>,
 }
 
 impl Default for CombatMachine {
@@ -469,36 +1347,47 @@ impl Default for CombatMachine {
             hit_reaction_animation: Default::default(),
             whip_animation: Default::default(),
             aim_state: Default::default(),
+            slam_animation: Default::default(),
+            slam_state: Default::default(),
         }
     }
 }
 
 impl CombatMachine {
     pub const HIT_SIGNAL: u64 = 1;
+    pub const SLAM_SIGNAL: u64 = 2;
 
     const AIM_TO_WHIP_PARAM: &'static str = "AimToWhip";
     const WHIP_TO_AIM_PARAM: &'static str = "WhipToAim";
     const HIT_REACTION_TO_AIM_PARAM: &'static str = "HitReactionToAim";
     const AIM_TO_HIT_REACTION_PARAM: &'static str = "AimToHitReaction";
     const WHIP_TO_HIT_REACTION_PARAM: &'static str = "WhipToHitReaction";
+    const AIM_TO_SLAM_PARAM: &'static str = "AimToSlam";
+    const WHIP_TO_SLAM_PARAM: &'static str = "WhipToSlam";
+    const SLAM_TO_AIM_PARAM: &'static str = "SlamToAim";
 
     async fn new(
         resource_manager: ResourceManager,
         definition: &BotDefinition,
         model: Handle<Node>,
         scene: &mut Scene,
-        spine: Handle<Node>,
-    ) -> Self {
+        spine_chain: &[Handle<Node>],
+    ) -> Result<Self, GameError> {
         let (aim_animation, whip_animation, hit_reaction_animation) = fyrox::core::futures::join!(
-            resource_manager.request_model(definition.aim_animation,),
-            resource_manager.request_model(definition.whip_animation,),
-            resource_manager.request_model(definition.hit_reaction_animation,)
+            request_model(&resource_manager, definition.aim_animation),
+            request_model(&resource_manager, definition.whip_animation),
+            request_model(&resource_manager, definition.hit_reaction_animation)
         );
+        // Bot without aim or whip animations stands idle instead, so a partially broken asset
+        // pack still runs.
+        let aim_animation = or_idle(aim_animation, &resource_manager, definition).await?;
+        let whip_animation = or_idle(whip_animation, &resource_manager, definition).await?;
+        let hit_reaction_animation = hit_reaction_animation?;
 
-        let aim_animation = prepare_animation(scene, aim_animation.unwrap(), model, spine);
-        let whip_animation = prepare_animation(scene, whip_animation.unwrap(), model, spine);
+        let aim_animation = prepare_animation(scene, aim_animation, model, spine_chain);
+        let whip_animation = prepare_animation(scene, whip_animation, model, spine_chain);
         let hit_reaction_animation =
-            prepare_animation(scene, hit_reaction_animation.unwrap(), model, spine);
+            prepare_animation(scene, hit_reaction_animation, model, spine_chain);
 
         scene
             .animations
@@ -600,12 +1489,75 @@ impl CombatMachine {
             Self::HIT_REACTION_TO_AIM_PARAM,
         ));
 
-        Self {
+        let mut slam_animation = Handle::NONE;
+        let mut slam_state = Handle::NONE;
+        let slam = match definition.second_phase.and_then(|phase| phase.slam) {
+            Some(slam) => match request_model(&resource_manager, slam.animation).await {
+                Ok(animation) => Some((slam, animation)),
+                Err(e) => {
+                    Log::writeln(
+                        MessageKind::Warning,
+                        format!("{}. {:?} won't slam.", e, definition.kind),
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some((slam, animation)) = slam {
+            slam_animation = prepare_animation(scene, animation, model, spine_chain);
+
+            scene
+                .animations
+                .get_mut(slam_animation)
+                .set_loop(false)
+                .add_signal(AnimationSignal::new(Self::SLAM_SIGNAL, slam.impact_time));
+
+            // Slam is done while standing, so it may drive the legs.
+            let slam_node =
+                machine.add_node(machine::PoseNode::make_play_animation(slam_animation));
+            slam_state = machine.add_state(State::new("Slam", slam_node));
+
+            machine.add_transition(machine::Transition::new(
+                "Aim->Slam",
+                aim_state,
+                slam_state,
+                0.2,
+                Self::AIM_TO_SLAM_PARAM,
+            ));
+            machine.add_transition(machine::Transition::new(
+                "Whip->Slam",
+                whip_state,
+                slam_state,
+                0.2,
+                Self::WHIP_TO_SLAM_PARAM,
+            ));
+            machine.add_transition(machine::Transition::new(
+                "Slam->Aim",
+                slam_state,
+                aim_state,
+                0.5,
+                Self::SLAM_TO_AIM_PARAM,
+            ));
+        }
+
+        Ok(Self {
             machine,
             hit_reaction_animation,
             whip_animation,
             aim_state,
-        }
+            slam_animation,
+            slam_state,
+        })
+    }
+
+    // Slam animation may be missing, the bot just won't slam then.
+    fn can_slam(&self) -> bool {
+        self.slam_state.is_some()
+    }
+
+    fn is_slamming(&self) -> bool {
+        self.slam_state.is_some() && self.machine.active_state() == self.slam_state
     }
 
     fn clean_up(&mut self, scene: &mut Scene) {
@@ -619,7 +1571,11 @@ impl CombatMachine {
         in_close_combat: bool,
         was_damaged: bool,
         can_aim: bool,
+        slam: bool,
     ) {
+        let slam_over =
+            self.slam_animation.is_none() || scene.animations.get(self.slam_animation).has_ended();
+
         self.machine
             .set_parameter(
                 Self::WHIP_TO_AIM_PARAM,
@@ -641,6 +1597,12 @@ impl CombatMachine {
                 Self::HIT_REACTION_TO_AIM_PARAM,
                 machine::Parameter::Rule(can_aim),
             )
+            .set_parameter(Self::AIM_TO_SLAM_PARAM, machine::Parameter::Rule(slam))
+            .set_parameter(Self::WHIP_TO_SLAM_PARAM, machine::Parameter::Rule(slam))
+            .set_parameter(
+                Self::SLAM_TO_AIM_PARAM,
+                machine::Parameter::Rule(!slam && slam_over),
+            )
             .evaluate_pose(&scene.animations, time.delta)
             .apply(&mut scene.graph);
     }
@@ -662,15 +1624,39 @@ impl Bot {
                     dying_animation: "data/animations/mutant/dying.fbx",
                     dead_animation: "data/animations/mutant/dead.fbx",
                     hit_reaction_animation: "data/animations/mutant/hit_reaction.fbx",
+                    strafe_animations: None,
+                    idle_sounds: &[],
+                    aggro_sounds: &[],
+                    pain_sounds: &[],
+                    death_sound: None,
                     weapon_hand_name: "Mutant:RightHand",
                     left_leg_name: "Mutant:LeftUpLeg",
                     right_leg_name: "Mutant:RightUpLeg",
-                    spine: "Mutant:Spine",
+                    spine_chain: &["Mutant:Spine", "Mutant:Spine1", "Mutant:Spine2"],
                     walk_speed: 2.0,
                     scale: 0.0085,
                     weapon_scale: 2.6,
                     health: 100.0,
-                    v_aim_angle_hack: -2.0,
+                    v_aim_angle_hack: 0.0,
+                    boss: None,
+                    blood_color: Some(Color::opaque(120, 0, 0)),
+                    flee_health_threshold: 25.0,
+                    melee_damage: 20.0,
+                    close_combat_distance: 2.0,
+                    melee_hit_radius: 0.6,
+                    hit_reaction_cooldown: 1.5,
+                    stagger_threshold: 10.0,
+                    search_time: 5.0,
+                    limp_health_fraction: 0.5,
+                    min_limp_speed_fraction: 0.4,
+                    view_fov_degrees: 90.0,
+                    view_distance: 20.0,
+                    head_offset: 0.8,
+                    loadout: &[WeaponKind::BattleAxe],
+                    engagement: None,
+                    lunge: None,
+                    second_phase: None,
+                    body_radius: 0.28,
                 };
                 &DEFINITION
             }
@@ -687,15 +1673,39 @@ impl Bot {
                     dying_animation: "data/animations/parasite/dying.fbx",
                     dead_animation: "data/animations/parasite/dead.fbx",
                     hit_reaction_animation: "data/animations/parasite/hit_reaction.fbx",
+                    strafe_animations: None,
+                    idle_sounds: &[],
+                    aggro_sounds: &[],
+                    pain_sounds: &[],
+                    death_sound: None,
                     weapon_hand_name: "RightHand",
                     left_leg_name: "LeftUpLeg",
                     right_leg_name: "RightUpLeg",
-                    spine: "Spine",
+                    spine_chain: &["Spine", "Spine1", "Spine2"],
                     walk_speed: 2.0,
                     scale: 0.0085,
                     weapon_scale: 2.5,
                     health: 100.0,
-                    v_aim_angle_hack: 12.0,
+                    v_aim_angle_hack: 0.0,
+                    boss: None,
+                    blood_color: Some(Color::opaque(90, 130, 0)),
+                    flee_health_threshold: 20.0,
+                    melee_damage: 15.0,
+                    close_combat_distance: 1.8,
+                    melee_hit_radius: 0.5,
+                    hit_reaction_cooldown: 1.5,
+                    stagger_threshold: 10.0,
+                    search_time: 4.0,
+                    limp_health_fraction: 0.5,
+                    min_limp_speed_fraction: 0.4,
+                    view_fov_degrees: 90.0,
+                    view_distance: 20.0,
+                    head_offset: 0.8,
+                    loadout: &[WeaponKind::BattleAxe],
+                    engagement: None,
+                    lunge: None,
+                    second_phase: None,
+                    body_radius: 0.28,
                 };
                 &DEFINITION
             }
@@ -712,15 +1722,219 @@ impl Bot {
                     dying_animation: "data/animations/maw/dying.fbx",
                     dead_animation: "data/animations/maw/dead.fbx",
                     hit_reaction_animation: "data/animations/maw/hit_reaction.fbx",
+                    strafe_animations: None,
+                    idle_sounds: &[],
+                    aggro_sounds: &[],
+                    pain_sounds: &[],
+                    death_sound: None,
+                    weapon_hand_name: "RightHand",
+                    left_leg_name: "LeftUpLeg",
+                    right_leg_name: "RightUpLeg",
+                    spine_chain: &["Spine", "Spine1", "Spine2"],
+                    walk_speed: 2.0,
+                    scale: 0.0085,
+                    weapon_scale: 2.5,
+                    health: 100.0,
+                    v_aim_angle_hack: 0.0,
+                    boss: Some(BossDefinition {
+                        name: "The Maw",
+                        phases: &[0.5],
+                    }),
+                    blood_color: Some(Color::opaque(100, 0, 20)),
+                    flee_health_threshold: 0.0,
+                    melee_damage: 40.0,
+                    close_combat_distance: 2.6,
+                    melee_hit_radius: 0.9,
+                    hit_reaction_cooldown: 3.0,
+                    stagger_threshold: 40.0,
+                    search_time: 6.0,
+                    limp_health_fraction: 0.5,
+                    min_limp_speed_fraction: 0.4,
+                    view_fov_degrees: 70.0,
+                    view_distance: 12.0,
+                    head_offset: 1.2,
+                    loadout: &[WeaponKind::BattleAxe],
+                    engagement: None,
+                    lunge: None,
+                    second_phase: None,
+                    body_radius: 0.28,
+                };
+                &DEFINITION
+            }
+            // Behemoth has no model of its own yet, it's a huge maw that slams the ground once
+            // it is enraged.
+            BotKind::Behemoth => {
+                static DEFINITION: BotDefinition = BotDefinition {
+                    kind: BotKind::Behemoth,
+                    model: "data/models/maw.fbx",
+                    idle_animation: "data/animations/maw/idle.fbx",
+                    walk_animation: "data/animations/maw/walk.fbx",
+                    aim_animation: "data/animations/maw/aim.fbx",
+                    whip_animation: "data/animations/maw/whip.fbx",
+                    jump_animation: "data/animations/maw/jump.fbx",
+                    falling_animation: "data/animations/maw/falling.fbx",
+                    dying_animation: "data/animations/maw/dying.fbx",
+                    dead_animation: "data/animations/maw/dead.fbx",
+                    hit_reaction_animation: "data/animations/maw/hit_reaction.fbx",
+                    strafe_animations: None,
+                    idle_sounds: &[],
+                    aggro_sounds: &[],
+                    pain_sounds: &[],
+                    death_sound: None,
+                    weapon_hand_name: "RightHand",
+                    left_leg_name: "LeftUpLeg",
+                    right_leg_name: "RightUpLeg",
+                    spine_chain: &["Spine", "Spine1", "Spine2"],
+                    walk_speed: 1.6,
+                    scale: 0.013,
+                    weapon_scale: 3.5,
+                    health: 400.0,
+                    v_aim_angle_hack: 0.0,
+                    boss: Some(BossDefinition {
+                        name: "The Behemoth",
+                        phases: &[0.5],
+                    }),
+                    blood_color: Some(Color::opaque(100, 0, 20)),
+                    flee_health_threshold: 0.0,
+                    melee_damage: 40.0,
+                    close_combat_distance: 3.2,
+                    melee_hit_radius: 1.2,
+                    hit_reaction_cooldown: 3.0,
+                    stagger_threshold: 40.0,
+                    search_time: 8.0,
+                    limp_health_fraction: 0.0,
+                    min_limp_speed_fraction: 1.0,
+                    view_fov_degrees: 70.0,
+                    view_distance: 16.0,
+                    head_offset: 1.8,
+                    loadout: &[WeaponKind::BattleAxe],
+                    engagement: None,
+                    lunge: None,
+                    second_phase: Some(PhaseDefinition {
+                        health_fraction: 0.5,
+                        walk_speed: 3.0,
+                        hit_reaction_cooldown: 6.0,
+                        stagger_threshold: 80.0,
+                        slam: Some(SlamDefinition {
+                            animation: "data/animations/maw/jump.fbx",
+                            impact_time: 0.6,
+                            distance: 4.0,
+                            radius: 5.0,
+                            damage: 35.0,
+                            cooldown: 6.0,
+                        }),
+                    }),
+                    body_radius: 0.6,
+                };
+                &DEFINITION
+            }
+            // Hound has no model of its own yet, it's a small and quick mutant.
+            BotKind::Hound => {
+                static DEFINITION: BotDefinition = BotDefinition {
+                    kind: BotKind::Hound,
+                    model: "data/models/mutant.FBX",
+                    idle_animation: "data/animations/mutant/idle.fbx",
+                    walk_animation: "data/animations/mutant/walk.fbx",
+                    aim_animation: "data/animations/mutant/aim.fbx",
+                    whip_animation: "data/animations/mutant/whip.fbx",
+                    jump_animation: "data/animations/mutant/jump.fbx",
+                    falling_animation: "data/animations/mutant/falling.fbx",
+                    dying_animation: "data/animations/mutant/dying.fbx",
+                    dead_animation: "data/animations/mutant/dead.fbx",
+                    hit_reaction_animation: "data/animations/mutant/hit_reaction.fbx",
+                    strafe_animations: None,
+                    idle_sounds: &[],
+                    aggro_sounds: &[],
+                    pain_sounds: &[],
+                    death_sound: None,
+                    weapon_hand_name: "Mutant:RightHand",
+                    left_leg_name: "Mutant:LeftUpLeg",
+                    right_leg_name: "Mutant:RightUpLeg",
+                    spine_chain: &["Mutant:Spine", "Mutant:Spine1", "Mutant:Spine2"],
+                    walk_speed: 4.0,
+                    scale: 0.006,
+                    weapon_scale: 2.6,
+                    health: 60.0,
+                    v_aim_angle_hack: 0.0,
+                    boss: None,
+                    blood_color: Some(Color::opaque(120, 0, 0)),
+                    flee_health_threshold: 0.0,
+                    melee_damage: 0.0,
+                    close_combat_distance: 0.0,
+                    melee_hit_radius: 0.0,
+                    hit_reaction_cooldown: 1.0,
+                    stagger_threshold: 10.0,
+                    search_time: 4.0,
+                    limp_health_fraction: 0.5,
+                    min_limp_speed_fraction: 0.4,
+                    view_fov_degrees: 110.0,
+                    view_distance: 18.0,
+                    head_offset: 0.5,
+                    loadout: &[WeaponKind::BattleAxe],
+                    engagement: None,
+                    lunge: Some(LungeDefinition {
+                        distance: 4.0,
+                        speed: 9.0,
+                        lift: 3.0,
+                        damage: 25.0,
+                        hit_distance: 1.2,
+                        cooldown: 2.0,
+                    }),
+                    second_phase: None,
+                    body_radius: 0.28,
+                };
+                &DEFINITION
+            }
+            // Soldier has no model of its own yet, it borrows the humanoid rig of the parasite.
+            BotKind::Soldier => {
+                static DEFINITION: BotDefinition = BotDefinition {
+                    kind: BotKind::Soldier,
+                    model: "data/models/parasite.FBX",
+                    idle_animation: "data/animations/parasite/idle.fbx",
+                    walk_animation: "data/animations/parasite/walk.fbx",
+                    aim_animation: "data/animations/parasite/aim.fbx",
+                    whip_animation: "data/animations/parasite/whip.fbx",
+                    jump_animation: "data/animations/parasite/jump.fbx",
+                    falling_animation: "data/animations/parasite/falling.fbx",
+                    dying_animation: "data/animations/parasite/dying.fbx",
+                    dead_animation: "data/animations/parasite/dead.fbx",
+                    hit_reaction_animation: "data/animations/parasite/hit_reaction.fbx",
+                    strafe_animations: None,
+                    idle_sounds: &[],
+                    aggro_sounds: &[],
+                    pain_sounds: &[],
+                    death_sound: None,
                     weapon_hand_name: "RightHand",
                     left_leg_name: "LeftUpLeg",
                     right_leg_name: "RightUpLeg",
-                    spine: "Spine",
+                    spine_chain: &["Spine", "Spine1", "Spine2"],
                     walk_speed: 2.0,
                     scale: 0.0085,
                     weapon_scale: 2.5,
                     health: 100.0,
-                    v_aim_angle_hack: 16.0,
+                    v_aim_angle_hack: 0.0,
+                    boss: None,
+                    blood_color: Some(Color::opaque(140, 0, 0)),
+                    flee_health_threshold: 30.0,
+                    melee_damage: 0.0,
+                    close_combat_distance: 0.0,
+                    melee_hit_radius: 0.0,
+                    hit_reaction_cooldown: 1.5,
+                    stagger_threshold: 15.0,
+                    search_time: 8.0,
+                    limp_health_fraction: 0.5,
+                    min_limp_speed_fraction: 0.4,
+                    view_fov_degrees: 70.0,
+                    view_distance: 35.0,
+                    head_offset: 0.8,
+                    loadout: &[WeaponKind::Ak47],
+                    engagement: Some(EngagementRange {
+                        min_distance: 5.0,
+                        max_distance: 15.0,
+                    }),
+                    lunge: None,
+                    second_phase: None,
+                    body_radius: 0.28,
                 };
                 &DEFINITION
             }
@@ -733,15 +1947,16 @@ impl Bot {
         scene: &mut Scene,
         position: Vector3<f32>,
         sender: Sender<Message>,
-    ) -> Self {
+        difficulty: BotDifficulty,
+        loadout: &[WeaponKind],
+        weapons: &mut WeaponContainer,
+    ) -> Result<Self, GameError> {
         let definition = Self::get_definition(kind);
 
         let body_height = 1.25;
 
-        let model = resource_manager
-            .request_model(Path::new(definition.model))
-            .await
-            .unwrap()
+        let model = request_model(&resource_manager, definition.model)
+            .await?
             .instantiate_geometry(scene);
 
         scene.graph[model]
@@ -753,11 +1968,22 @@ impl Bot {
                 definition.scale,
             ));
 
-        let spine = scene.graph.find_by_name(model, definition.spine);
-        if spine.is_none() {
+        let mut spine_chain = Vec::new();
+        for name in definition.spine_chain {
+            let bone = scene.graph.find_by_name(model, name);
+            if bone.is_some() {
+                spine_chain.push(bone);
+            } else {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Spine bone {} not found, it won't bend to aim.", name),
+                );
+            }
+        }
+        if spine_chain.is_empty() {
             Log::writeln(
                 MessageKind::Warning,
-                "Spine bone not found, bot won't aim vertically!".to_owned(),
+                "No spine bones found, bot won't aim vertically!".to_owned(),
             );
         }
 
@@ -772,7 +1998,10 @@ impl Bot {
                 .with_children(&[
                     {
                         collider = ColliderBuilder::new(BaseBuilder::new())
-                            .with_shape(ColliderShape::capsule_y(body_height * 0.5, 0.28))
+                            .with_shape(ColliderShape::capsule_y(
+                                body_height * 0.5,
+                                definition.body_radius,
+                            ))
                             .with_friction_combine_rule(CoefficientCombineRule::Min)
                             .build(&mut scene.graph);
                         collider
@@ -806,56 +2035,471 @@ impl Bot {
 
         scene.graph.link_nodes(weapon_pivot, hand);
 
-        let locomotion_machine =
-            LocomotionMachine::new(resource_manager.clone(), &definition, model, scene, spine)
-                .await;
-        let combat_machine =
-            CombatMachine::new(resource_manager.clone(), definition, model, scene, spine).await;
-        let dying_machine =
-            DyingMachine::new(resource_manager, definition, model, scene, spine).await;
+        // Bot that fails to load takes its nodes and animations with it.
+        let mut locomotion_machine = match LocomotionMachine::new(
+            resource_manager.clone(),
+            &definition,
+            model,
+            scene,
+            &spine_chain,
+        )
+        .await
+        {
+            Ok(machine) => machine,
+            Err(e) => {
+                scene.graph.remove_node(body);
+                return Err(e);
+            }
+        };
+        let mut combat_machine = match CombatMachine::new(
+            resource_manager.clone(),
+            definition,
+            model,
+            scene,
+            &spine_chain,
+        )
+        .await
+        {
+            Ok(machine) => machine,
+            Err(e) => {
+                locomotion_machine.clean_up(scene);
+                scene.graph.remove_node(body);
+                return Err(e);
+            }
+        };
+        let dying_machine = match DyingMachine::new(
+            resource_manager.clone(),
+            definition,
+            model,
+            scene,
+            &spine_chain,
+        )
+        .await
+        {
+            Ok(machine) => machine,
+            Err(e) => {
+                locomotion_machine.clean_up(scene);
+                combat_machine.clean_up(scene);
+                scene.graph.remove_node(body);
+                return Err(e);
+            }
+        };
+
+        let max_health = tunables::bot(kind).health.unwrap_or(definition.health);
+        let mut character = Character {
+            body,
+            collider,
+            weapon_pivot,
+            health: max_health,
+            sender: Some(sender.clone()),
+            name: format!("{:?}", kind),
+            ..Default::default()
+        };
+        // Weapons are owned by the bot once it gets its handle, see `Bot::set_weapons_owner`.
+        // Bot fights without a weapon that fails to load.
+        for &weapon_kind in loadout {
+            let mut weapon =
+                match Weapon::new(weapon_kind, resource_manager.clone(), scene, sender.clone())
+                    .await
+                {
+                    Ok(weapon) => weapon,
+                    Err(e) => {
+                        Log::writeln(
+                            MessageKind::Error,
+                            format!("{}. {:?} is left without it.", e, kind),
+                        );
+                        continue;
+                    }
+                };
+            scene.graph.link_nodes(weapon.get_model(), weapon_pivot);
+            // Only the first weapon is in hands, the rest are holstered.
+            weapon.set_visibility(character.weapons().is_empty(), &mut scene.graph);
+            let definition = weapon.definition();
+            let reserve_ammo = weapon.load(definition.ammo);
+            if let Some(ammo_type) = definition.ammo_type {
+                character.give_ammo(ammo_type, reserve_ammo);
+            }
+            character.add_weapon(weapons.add(weapon));
+        }
+        character.set_current_weapon(0);
 
-        Self {
-            character: Character {
-                body,
-                collider,
-                weapon_pivot,
-                health: definition.health,
-                sender: Some(sender),
-                name: format!("{:?}", kind),
-                ..Default::default()
-            },
-            spine,
-            last_health: definition.health,
+        Ok(Self {
+            character,
+            spine_chain,
+            last_health: max_health,
             model,
             kind,
             locomotion_machine,
             combat_machine,
             dying_machine,
+            difficulty,
             ..Default::default()
+        })
+    }
+
+    fn is_corpse(&self) -> bool {
+        self.dying_machine.machine.active_state() == self.dying_machine.dead_state
+    }
+
+    /// Weapons of a new bot are made before it's added to the actors, they get their owner
+    /// once the bot has a handle.
+    pub fn set_weapons_owner(&self, self_handle: Handle<Actor>, weapons: &mut WeaponContainer) {
+        for weapon in self.character.weapons() {
+            weapons[*weapon].set_owner(self_handle);
         }
     }
 
     pub fn can_be_removed(&self) -> bool {
-        self.dying_machine.machine.active_state() == self.dying_machine.dead_state
+        self.is_corpse() && self.corpse_time >= self.corpse_lifetime
     }
 
     pub fn can_shoot(&self) -> bool {
         self.combat_machine.machine.active_state() == self.combat_machine.aim_state
     }
 
+    /// Actors within given radius of the bot, in the same order as `targets`. `found` holds
+    /// the result of the query.
+    fn actors_within<'a>(
+        &self,
+        graph: &Graph,
+        targets: &'a [TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
+        radius: f32,
+        found: &'a mut GridQuery<usize>,
+    ) -> impl Iterator<Item = &'a TargetDescriptor> + 'a {
+        target_grid.query(self.character.position(graph), radius, found);
+        found.iter().map(move |index| &targets[index])
+    }
+
+    // How dangerous a candidate target is for the bot at `position`: attackers of the bot come
+    // first, then actors that aim at the bot, then ones who hurt somebody else. Closer ones are
+    // a bit more dangerous than farther ones.
+    fn threat_of(&self, desc: &TargetDescriptor, position: Vector3<f32>, time: f64) -> f32 {
+        let definition = self.definition();
+        let mut threat =
+            1.0 - (desc.position.metric_distance(&position) / definition.view_distance).min(1.0);
+        if self
+            .attackers
+            .iter()
+            .any(|attacker| attacker.handle == desc.handle)
+        {
+            threat += ATTACKER_THREAT;
+        }
+        let is_aiming = desc.aim.as_ref().map_or(false, |aim| {
+            let eyes = position + Vector3::new(0.0, definition.head_offset, 0.0);
+            match (
+                (eyes - aim.origin).try_normalize(f32::EPSILON),
+                aim.dir.try_normalize(f32::EPSILON),
+            ) {
+                (Some(to_bot), Some(dir)) => {
+                    to_bot.dot(&dir) >= AIM_THREAT_ANGLE.to_radians().cos()
+                }
+                _ => false,
+            }
+        });
+        if is_aiming {
+            threat += AIMING_THREAT;
+        }
+        if time - desc.last_damage_dealt_time < ATTACKER_MEMORY as f64 {
+            threat += DAMAGE_DEALER_THREAT;
+        }
+        threat
+    }
+
+    /// Remembers who has hurt the bot, the bot prefers to fight its attackers for a while. Bot
+    /// without a target turns to the attacker and goes there.
+    pub fn on_damaged(&mut self, who: Handle<Actor>, who_position: Vector3<f32>, time: f64) {
+        self.attackers.retain(|attacker| attacker.handle != who);
+        self.attackers.push(Attacker { handle: who, time });
+        if self.target.is_none() {
+            self.damage_source = Some(DamageSource {
+                position: who_position,
+                time_left: INVESTIGATION_TIME,
+                turned: false,
+            });
+        }
+    }
+
+    // Direction from the weapon to where the target will be when a projectile of the weapon
+    // gets there. `None` for melee weapons and projectiles that are fast enough to hit
+    // without a lead.
+    fn lead_direction(&self, weapon: &Weapon, graph: &Graph) -> Option<Vector3<f32>> {
+        let target = self.target.as_ref()?;
+        let kind = match weapon.definition().attack {
+            WeaponAttack::Projectile(kind) => kind,
+            WeaponAttack::Melee { .. } => return None,
+        };
+        let speed = Projectile::get_definition(kind).speed_per_second();
+        if speed >= NO_LEAD_PROJECTILE_SPEED {
+            return None;
+        }
+        let origin = weapon.get_shot_position(graph);
+        let to_target = target.position - origin;
+        // Flight time to where the target is now and then to where it will be, one step is
+        // close enough for targets that are slower than projectiles.
+        let time = (to_target.norm() / speed).min(MAX_LEAD_TIME);
+        let time = ((to_target + target.velocity.scale(time)).norm() / speed).min(MAX_LEAD_TIME);
+        Some(to_target + target.velocity.scale(time * self.difficulty.lead_factor()))
+    }
+
+    // Push away from living actors that are too close, stronger the closer they are. Bodies
+    // that are right inside each other are pushed apart along a side of their own.
+    fn separation(
+        &self,
+        self_handle: Handle<Actor>,
+        graph: &Graph,
+        targets: &[TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
+        found: &mut GridQuery<usize>,
+    ) -> Vector3<f32> {
+        let position = self.character.position(graph);
+        let radius = self.character.radius(graph);
+        let mut push = Vector3::default();
+        for desc in self.actors_within(
+            graph,
+            targets,
+            target_grid,
+            SEPARATION_DISTANCE * 2.0,
+            found,
+        ) {
+            if desc.handle == self_handle || desc.health <= 0.0 {
+                continue;
+            }
+            let offset = position - desc.position;
+            let offset = Vector3::new(offset.x, 0.0, offset.z);
+            let gap = offset.norm() - radius - desc.radius;
+            if gap >= SEPARATION_DISTANCE {
+                continue;
+            }
+            let away = offset.try_normalize(f32::EPSILON).unwrap_or_else(|| {
+                let angle = self_handle.index() as f32 * std::f32::consts::FRAC_PI_3;
+                Vector3::new(angle.cos(), 0.0, angle.sin())
+            });
+            push += away.scale(1.0 - gap.max(0.0) / SEPARATION_DISTANCE);
+        }
+        let strength = push.norm();
+        if strength > MAX_SEPARATION {
+            push.scale(MAX_SEPARATION / strength)
+        } else {
+            push
+        }
+    }
+
+    // Shockwave of a slam hits every living enemy within its radius, the closer the harder.
+    // It goes through walls, it's the ground that shakes.
+    fn slam(
+        &self,
+        self_handle: Handle<Actor>,
+        graph: &Graph,
+        targets: &[TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
+        found: &mut GridQuery<usize>,
+    ) {
+        let slam = match self.phase().and_then(|phase| phase.slam) {
+            Some(slam) => slam,
+            None => return,
+        };
+        let sender = self.character.sender.as_ref().unwrap();
+        let position = self.character.position(graph);
+        for desc in self.actors_within(graph, targets, target_grid, slam.radius, found) {
+            let offset = desc.position - position;
+            let distance = offset.norm();
+            if desc.handle == self_handle
+                || desc.health <= 0.0
+                || self.character.team.is_ally_of(desc.team)
+                || distance > slam.radius
+            {
+                continue;
+            }
+            sender
+                .send(Message::DamageActor {
+                    actor: desc.handle,
+                    who: self_handle,
+                    amount: slam.damage * (1.0 - 0.5 * distance / slam.radius),
+                    hit: offset
+                        .try_normalize(f32::EPSILON)
+                        .map(|direction| DamageHit {
+                            position: desc.position,
+                            direction,
+                            normal: -direction,
+                        }),
+                })
+                .unwrap();
+        }
+        // Model is placed at the feet of the bot.
+        sender
+            .send(Message::CreateEffect {
+                kind: EffectKind::Shockwave,
+                position: graph[self.model].global_position(),
+                direction: Vector3::y(),
+            })
+            .unwrap();
+    }
+
+    // Hit signal of the whip damages the target if it's still in the swing.
+    fn whip(
+        &self,
+        self_handle: Handle<Actor>,
+        graph: &Graph,
+        targets: &[TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
+        found: &mut GridQuery<usize>,
+        target: &Target,
+    ) {
+        if !self.is_in_swing(graph, target.position)
+            || self.is_ally_in_swing(
+                self_handle,
+                graph,
+                targets,
+                target_grid,
+                found,
+                target.position,
+            )
+        {
+            return;
+        }
+        self.character
+            .sender
+            .as_ref()
+            .unwrap()
+            .send(Message::DamageActor {
+                actor: target.handle,
+                who: self_handle,
+                amount: self.melee_damage(),
+                hit: (target.position - self.character.position(graph))
+                    .try_normalize(f32::EPSILON)
+                    .map(|direction| DamageHit {
+                        position: target.position,
+                        direction,
+                        normal: -direction,
+                    }),
+            })
+            .unwrap();
+    }
+
+    // Whip hits the first body on its way, a swing that would go through a teammate is held
+    // back.
+    fn is_ally_in_swing(
+        &self,
+        self_handle: Handle<Actor>,
+        graph: &Graph,
+        targets: &[TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
+        found: &mut GridQuery<usize>,
+        target_position: Vector3<f32>,
+    ) -> bool {
+        let position = self.character.position(graph);
+        let swing = target_position - position;
+        let length = swing.norm();
+        let direction = match swing.try_normalize(f32::EPSILON) {
+            Some(direction) => direction,
+            None => return false,
+        };
+        self.actors_within(graph, targets, target_grid, length, found)
+            .filter(|desc| desc.handle != self_handle && self.character.team.is_ally_of(desc.team))
+            .any(|desc| {
+                let offset = desc.position - position;
+                let along = offset.dot(&direction);
+                along > 0.0
+                    && along < length
+                    && (offset - direction.scale(along)).norm() <= self.melee_hit_radius()
+            })
+    }
+
+    // Whip sweeps forward of the bot as far as the close combat reaches and as wide as the hit
+    // radius. A target that has dodged out of it during the wind-up is missed.
+    fn is_in_swing(&self, graph: &Graph, point: Vector3<f32>) -> bool {
+        let offset = point - self.character.position(graph);
+        if offset.norm() > self.close_combat_distance() {
+            return false;
+        }
+        let look = graph[self.model].look_vector();
+        let forward = match Vector3::new(look.x, 0.0, look.z).try_normalize(f32::EPSILON) {
+            Some(forward) => forward,
+            None => return false,
+        };
+        let horizontal = Vector3::new(offset.x, 0.0, offset.z);
+        let along = horizontal.dot(&forward);
+        along >= 0.0 && (horizontal - forward.scale(along)).norm() <= self.melee_hit_radius()
+    }
+
+    // Bot out of ammo bashes with its empty weapon if it has no whip of its own.
+    fn close_combat_distance(&self) -> f32 {
+        let distance = self.definition().close_combat_distance;
+        if self.out_of_ammo {
+            distance.max(DRY_CLOSE_COMBAT_DISTANCE)
+        } else {
+            distance
+        }
+    }
+
+    fn melee_hit_radius(&self) -> f32 {
+        let radius = self.definition().melee_hit_radius;
+        if self.out_of_ammo {
+            radius.max(DRY_MELEE_HIT_RADIUS)
+        } else {
+            radius
+        }
+    }
+
+    fn melee_damage(&self) -> f32 {
+        let damage = self.definition().melee_damage;
+        if self.out_of_ammo {
+            damage.max(DRY_MELEE_DAMAGE)
+        } else {
+            damage
+        }
+    }
+
+    /// Items within given radius of the bot that can be picked up, in the same order as
+    /// `items.iter()`. `found` holds the result of the query.
+    fn items_within<'a>(
+        &self,
+        graph: &Graph,
+        items: &'a ItemContainer,
+        radius: f32,
+        found: &'a mut GridQuery<Handle<Item>>,
+    ) -> impl Iterator<Item = &'a Item> + 'a {
+        items.query(self.character.position(graph), radius, found);
+        found
+            .iter()
+            .map(move |handle| items.get(handle))
+            .filter(|item| !item.is_picked_up())
+    }
+
     fn select_target(
         &mut self,
         self_handle: Handle<Actor>,
         scene: &mut Scene,
         targets: &[TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
+        found: &mut GridQuery<usize>,
+        query_buffer: &mut Vec<Intersection>,
+        time: f64,
     ) {
+        scope_profile!();
+
+        let prev_target = self.target_handle();
         self.target = None;
+        self.last_perception_time = time;
         let position = self.character.position(&scene.graph);
-        let mut closest_distance = std::f32::MAX;
+        let mut best = (f32::MIN, f32::MAX);
+        self.attackers
+            .retain(|attacker| time - attacker.time < ATTACKER_MEMORY as f64);
 
-        let mut query_buffer = Vec::default();
-        'target_loop: for desc in targets {
-            if desc.handle != self_handle && self.frustum.is_contains_point(desc.position) {
+        // Nothing outside of the frustum can be seen, so only actors around are checked.
+        let candidates = self.actors_within(
+            &scene.graph,
+            targets,
+            target_grid,
+            self.definition().view_radius(),
+            found,
+        );
+        'target_loop: for desc in candidates {
+            if desc.handle != self_handle
+                && !self.character.team.is_ally_of(desc.team)
+                && self.frustum.is_contains_point(desc.position)
+            {
                 let ray = Ray::from_two_points(desc.position, position);
                 scene.graph.physics.cast_ray(
                     RayCastOptions {
@@ -865,7 +2509,7 @@ impl Bot {
                         max_len: ray.dir.norm(),
                         sort_results: true,
                     },
-                    &mut query_buffer,
+                    query_buffer,
                 );
 
                 'hit_loop: for hit in query_buffer.iter() {
@@ -873,7 +2517,8 @@ impl Bot {
                     let body = collider.parent();
 
                     if matches!(collider.shape(), ColliderShape::Trimesh(_)) {
-                        // Target is behind something.
+                        // Target is behind something. A target that was seen before is still
+                        // remembered where it was seen last time, others are never seen.
                         continue 'target_loop;
                     } else {
                         // Prevent setting self as target.
@@ -883,94 +2528,377 @@ impl Bot {
                     }
                 }
 
+                // The most threatening target wins, ties go to the closest one.
                 let sqr_d = position.sqr_distance(&desc.position);
-                if sqr_d < closest_distance {
+                let threat = self.threat_of(desc, position, time);
+                if threat > best.0 || threat == best.0 && sqr_d < best.1 {
                     self.target = Some(Target {
                         position: desc.position,
                         handle: desc.handle,
+                        threat,
+                        seen_position: desc.position,
+                        velocity: desc.velocity,
                     });
-                    closest_distance = sqr_d;
+                    best = (threat, sqr_d);
                 }
             }
         }
+
+        let new_target = self.target_handle();
+        if new_target != prev_target {
+            // Every new target takes a moment to react to, the one that was seen a moment ago
+            // is shot at right away.
+            let recently_seen = self
+                .last_known_target
+                .as_ref()
+                .map_or(false, |last| Some(last.handle) == new_target);
+            if !recently_seen {
+                self.reaction_time_left = self.difficulty.reaction_time_seconds;
+            }
+            self.burst_shots = 0;
+            if let Some(sender) = self.character.sender.as_ref() {
+                let message = match new_target {
+                    Some(target) => Message::TargetAcquired {
+                        bot: self_handle,
+                        target,
+                    },
+                    None => Message::TargetLost { bot: self_handle },
+                };
+                sender.send(message).unwrap();
+            }
+        }
+
+        if let Some(target) = self.target.as_ref() {
+            self.last_known_target = Some(LastKnownTarget {
+                position: target.position,
+                handle: target.handle,
+                time,
+                search_time: None,
+                heading: Default::default(),
+            });
+        }
+    }
+
+    // Bots look for targets at a lower rate than they're updated. Every bot is shifted within
+    // the interval by its handle, so ray casts of many bots are spread over frames.
+    fn is_perception_due(&mut self, self_handle: Handle<Actor>, time: f64) -> bool {
+        if self.perception_interval <= 0.0 {
+            return true;
+        }
+        let phase = (self_handle.index() as f64 * 0.618_034).fract();
+        let tick = (time / self.perception_interval as f64 + phase).floor() as i64;
+        if tick != self.perception_tick {
+            self.perception_tick = tick;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Target moves on as it was moving when it was seen, the bot doesn't aim at where the
+    // target was a few frames ago. Extrapolation never goes farther than one interval.
+    fn extrapolate_target(&mut self, time: f64) {
+        let age = ((time - self.last_perception_time) as f32).min(self.perception_interval);
+        if let Some(target) = self.target.as_mut() {
+            target.position = target.seen_position + target.velocity.scale(age);
+        }
+    }
+
+    /// Ammo of the weapon that the bot holds if it is running out of it, melee weapons never
+    /// do.
+    fn low_ammo_type(&self, weapons: &WeaponContainer) -> Option<AmmoType> {
+        let weapon = self.character.current_weapon();
+        if weapon.is_none() {
+            return None;
+        }
+        let weapon = &weapons[weapon];
+        let definition = weapon.definition();
+        if self.character.weapon_ammo(weapon) < definition.ammo / LOW_AMMO_DIVISOR {
+            definition.ammo_type
+        } else {
+            None
+        }
     }
 
-    fn select_point_of_interest(&mut self, items: &ItemContainer, scene: &Scene, time: &GameTime) {
+    fn select_point_of_interest(
+        &mut self,
+        items: &ItemContainer,
+        weapons: &WeaponContainer,
+        scene: &Scene,
+        found: &mut GridQuery<Handle<Item>>,
+        time: &GameTime,
+    ) {
         if time.elapsed - self.last_poi_update_time >= 1.25 {
-            // Select closest non-despawned item as point of interest.
+            scope_profile!();
+
+            // Medkits are only good for hurt bots and ammo for the weapon that runs out,
+            // weapons are always worth a walk.
+            let is_hurt = self.character.health < self.max_health();
+            let low_ammo_type = self.low_ammo_type(weapons);
+            let is_needed = |item: &Item| match item.get_kind() {
+                ItemKind::Medkit => is_hurt,
+                kind => kind
+                    .ammo_type()
+                    .map_or(true, |ammo_type| Some(ammo_type) == low_ammo_type),
+            };
+            let carried = if self.out_of_ammo {
+                self.carried_ammo_types(weapons)
+            } else {
+                Vec::new()
+            };
+            let is_ammo_for_carried = |item: &Item| {
+                item.get_kind()
+                    .ammo_type()
+                    .map_or(false, |ammo_type| carried.contains(&ammo_type))
+            };
+
+            // Select closest non-despawned item as point of interest. Closest item around is
+            // the closest one on the whole level, so all items are checked only when there
+            // is nothing around.
             let self_position = self.position(&scene.graph);
-            let mut closest_distance = std::f32::MAX;
-            for item in items.iter() {
-                if !item.is_picked_up() {
-                    let item_position = item.position(&scene.graph);
-                    let sqr_d = item_position.sqr_distance(&self_position);
-                    if sqr_d < closest_distance {
-                        closest_distance = sqr_d;
-                        self.point_of_interest = item_position;
-                    }
-                }
+            let closest_of = |candidates: &mut dyn Iterator<Item = &Item>| {
+                candidates
+                    .map(|item| item.position(&scene.graph))
+                    .min_by(|a, b| {
+                        a.sqr_distance(&self_position)
+                            .total_cmp(&b.sqr_distance(&self_position))
+                    })
+            };
+            // Fleeing bot goes for a medkit wherever it is and a bot out of ammo goes for ammo
+            // of its weapons, other items are only good when there are none.
+            let mut closest = if self.flee.is_some() {
+                closest_of(
+                    &mut items
+                        .iter()
+                        .filter(|item| item.get_kind() == ItemKind::Medkit && !item.is_picked_up()),
+                )
+            } else if self.out_of_ammo {
+                closest_of(
+                    &mut items
+                        .iter()
+                        .filter(|item| is_ammo_for_carried(*item) && !item.is_picked_up()),
+                )
+            } else {
+                None
+            };
+            if closest.is_none() {
+                closest = closest_of(
+                    &mut self
+                        .items_within(&scene.graph, items, POI_SEARCH_RADIUS, found)
+                        .filter(|item| is_needed(*item)),
+                );
+            }
+            if closest.is_none() {
+                closest = closest_of(
+                    &mut items
+                        .iter()
+                        .filter(|item| !item.is_picked_up() && is_needed(*item)),
+                );
+            }
+            if let Some(item_position) = closest {
+                self.push_point_of_interest(item_position, PoiPriority::Item, ITEM_POI_TIME);
             }
             self.last_poi_update_time = time.elapsed;
         }
     }
 
-    fn select_weapon(&mut self, weapons: &WeaponContainer) {
+    /// Switches to a weapon with ammo when the current one has run dry.
+    pub fn select_weapon(&mut self, weapons: &WeaponContainer) {
         if self.character.current_weapon().is_some()
-            && weapons[self.character.current_weapon()].ammo() == 0
+            && !self
+                .character
+                .has_ammo_for(&weapons[self.character.current_weapon()])
         {
-            for (i, handle) in self.character.weapons().iter().enumerate() {
-                if weapons[*handle].ammo() > 0 {
-                    self.character.set_current_weapon(i);
-                    break;
-                }
+            let loaded = self
+                .character
+                .weapons()
+                .iter()
+                .position(|handle| self.character.has_ammo_for(&weapons[*handle]));
+            if let Some(i) = loaded {
+                self.character.set_current_weapon(i);
             }
         }
+
+        // Lunging bots never shoot and unarmed beasts only whip, so they never run dry.
+        let out_of_ammo = self.definition().lunge.is_none()
+            && !self.character.weapons().is_empty()
+            && self
+                .character
+                .weapons()
+                .iter()
+                .all(|handle| !self.character.has_ammo_for(&weapons[*handle]));
+        if out_of_ammo && !self.out_of_ammo {
+            // Ammo box is picked on the next perception update.
+            self.last_poi_update_time = f64::MIN;
+        }
+        self.out_of_ammo = out_of_ammo;
+    }
+
+    /// Ammo types of the weapons that the bot carries, the bot out of ammo goes for ammo of
+    /// any of them.
+    fn carried_ammo_types(&self, weapons: &WeaponContainer) -> Vec<AmmoType> {
+        self.character
+            .weapons()
+            .iter()
+            .filter_map(|handle| weapons[*handle].definition().ammo_type)
+            .collect()
     }
 
-    pub fn debug_draw(&self, context: &mut SceneDrawingContext) {
-        for pts in self.navmesh_agent.path().windows(2) {
-            let a = pts[0];
-            let b = pts[1];
+    /// Draws the parts of the bot given by `flags`, state labels are pushed to `labels` as
+    /// text at a point in the world for the caller to show.
+    pub fn debug_draw(
+        &self,
+        context: &mut SceneDrawingContext,
+        flags: BotDebugFlags,
+        labels: &mut Vec<(Vector3<f32>, String)>,
+    ) {
+        let position = self.navmesh_agent.position();
+
+        if flags.contains(BotDebugFlags::PATH) {
+            for pts in self.navmesh_agent.path().windows(2) {
+                let a = pts[0];
+                let b = pts[1];
+                context.add_line(scene::debug::Line {
+                    begin: a,
+                    end: b,
+                    color: Color::RED,
+                });
+            }
+
+            context.draw_sphere(position, 10, 10, 0.25, Color::RED);
+
+            if let Some(detour) = self.detour.as_ref() {
+                context.add_line(scene::debug::Line {
+                    begin: position,
+                    end: detour.point,
+                    color: Color::opaque(255, 120, 0),
+                });
+            }
+        }
+
+        if flags.contains(BotDebugFlags::FRUSTUM) {
+            context.draw_frustum(&self.frustum, Color::from_rgba(0, 200, 0, 255));
+        }
+
+        // Stuck bot is marked by a sphere above it.
+        if flags.contains(BotDebugFlags::STUCK) && self.unstuck_attempts > 0 {
+            context.draw_sphere(
+                position + Vector3::new(0.0, 2.0, 0.0),
+                10,
+                10,
+                0.2 * self.unstuck_attempts as f32,
+                Color::opaque(255, 120, 0),
+            );
+        }
+
+        // Green for targets that are just near, red for the most threatening ones.
+        if let Some(target) = self
+            .target
+            .as_ref()
+            .filter(|_| flags.contains(BotDebugFlags::TARGET))
+        {
+            let t = (target.threat / MAX_THREAT).max(0.0).min(1.0);
+            context.add_line(scene::debug::Line {
+                begin: position,
+                end: target.position,
+                color: Color::opaque((255.0 * t) as u8, (255.0 * (1.0 - t)) as u8, 0),
+            });
+        }
+
+        if let Some(poi) = self
+            .point_of_interest()
+            .filter(|_| flags.contains(BotDebugFlags::POINT_OF_INTEREST))
+        {
+            context.draw_sphere(poi, 10, 10, 0.3, Color::opaque(0, 120, 255));
+        }
+
+        // Predicted impact of the projectile the bot is dodging.
+        if let Some(dodge) = self
+            .dodge
+            .as_ref()
+            .filter(|_| flags.contains(BotDebugFlags::DODGE))
+        {
+            context.draw_sphere(dodge.impact, 10, 10, 0.2, Color::opaque(255, 0, 255));
             context.add_line(scene::debug::Line {
-                begin: a,
-                end: b,
-                color: Color::RED,
+                begin: position,
+                end: position + dodge.direction,
+                color: Color::opaque(255, 0, 255),
             });
         }
 
-        context.draw_sphere(self.navmesh_agent.position(), 10, 10, 0.25, Color::RED);
+        if flags.contains(BotDebugFlags::PATROL) {
+            for pts in self.patrol.points().windows(2) {
+                context.add_line(scene::debug::Line {
+                    begin: pts[0],
+                    end: pts[1],
+                    color: Color::opaque(255, 200, 0),
+                });
+            }
+        }
 
-        context.draw_frustum(&self.frustum, Color::from_rgba(0, 200, 0, 255));
+        if flags.contains(BotDebugFlags::STATE_LABELS) {
+            let mut text = format!(
+                "{} ({:?}): {} / {}",
+                self.character.name,
+                self.kind,
+                state_name(&self.locomotion_machine.machine),
+                state_name(&self.combat_machine.machine),
+            );
+            if self.unstuck_attempts > 0 {
+                text += &format!(", stuck x{}", self.unstuck_attempts);
+            }
+            labels.push((
+                position + Vector3::new(0.0, self.definition().head_offset + 0.5, 0.0),
+                text,
+            ));
+        }
     }
 
     fn update_frustum(&mut self, position: Vector3<f32>, graph: &Graph) {
-        let head_pos = position + Vector3::new(0.0, 0.8, 0.0);
-        let up = graph[self.model].up_vector();
-        let look_at = head_pos + graph[self.model].look_vector();
-        let view_matrix = Matrix4::look_at_rh(&Point3::from(head_pos), &Point3::from(look_at), &up);
-        let projection_matrix =
-            Matrix4::new_perspective(16.0 / 9.0, 90.0f32.to_radians(), 0.1, 20.0);
-        let view_projection_matrix = projection_matrix * view_matrix;
-        self.frustum = Frustum::from(view_projection_matrix).unwrap();
+        self.frustum = self.definition().view_frustum(
+            position,
+            graph[self.model].look_vector(),
+            graph[self.model].up_vector(),
+        );
     }
 
     pub fn definition(&self) -> &BotDefinition {
         Self::get_definition(self.kind)
     }
 
-    fn aim_vertically(&mut self, look_dir: Vector3<f32>, graph: &mut Graph, time: GameTime) {
-        let angle = self.pitch.angle();
-        self.pitch
-            .set_target(
-                look_dir.dot(&Vector3::y()).acos() - std::f32::consts::PI / 2.0
-                    + self.definition().v_aim_angle_hack.to_radians(),
-            )
-            .update(time.delta);
+    // Spine chain bends until the weapon points at the target, the pitch is corrected by how
+    // far the weapon is off every frame. Models with a single spine bone and unarmed bots bend
+    // by the angle of the look direction instead.
+    fn aim_vertically(
+        &mut self,
+        look_dir: Vector3<f32>,
+        weapons: &WeaponContainer,
+        graph: &mut Graph,
+        time: GameTime,
+    ) {
+        let weapon = self.character.current_weapon();
+        let target = if self.spine_chain.len() > 1 && weapons.contains(weapon) {
+            let weapon = &weapons[weapon];
+            let aim_dir = self.target.as_ref().map_or(look_dir, |target| {
+                target.position - weapon.get_shot_position(graph)
+            });
+            let error = elevation(aim_dir) - elevation(weapon.get_shot_direction(graph));
+            // Pitch goes down as the weapon goes up.
+            let max_pitch = MAX_SPINE_PITCH.to_radians();
+            (self.pitch.angle() - error).clamp(-max_pitch, max_pitch)
+        } else {
+            look_dir.dot(&Vector3::y()).acos() - std::f32::consts::PI / 2.0
+                + self.definition().v_aim_angle_hack.to_radians()
+        };
+        self.pitch.set_target(target).update(time.delta);
 
-        if self.spine.is_some() {
-            graph[self.spine]
+        // Bones are bent by the updated pitch, so the weapon direction of the next frame is
+        // the result of it.
+        let share = self.pitch.angle() / self.spine_chain.len().max(1) as f32;
+        for &bone in self.spine_chain.iter() {
+            graph[bone]
                 .local_transform_mut()
-                .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::x_axis(), angle));
+                .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::x_axis(), share));
         }
     }
 
@@ -985,58 +2913,480 @@ impl Bot {
             .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle));
     }
 
+    /// Part of the walk speed that the bot walks at, it goes down from one to the minimum as
+    /// health drops from the limp threshold to zero. Attacks are never slowed down.
+    fn limp_factor(&self) -> f32 {
+        let definition = self.definition();
+        let threshold = self.max_health() * definition.limp_health_fraction;
+        if threshold <= 0.0 || self.character.health >= threshold {
+            return 1.0;
+        }
+        let t = (self.character.health / threshold).max(0.0);
+        definition.min_limp_speed_fraction + (1.0 - definition.min_limp_speed_fraction) * t
+    }
+
+    // Fraction of the walk speed the bot moves at, wounded bots limp and fleeing ones run.
+    fn speed_factor(&self) -> f32 {
+        let flee_factor = if self.flee.is_some() {
+            FLEE_SPEED_FACTOR
+        } else {
+            1.0
+        };
+        self.limp_factor() * flee_factor
+    }
+
+    // Speed the navmesh agent walks at, the walk animation plays at the limp factor alongside
+    // so a limping bot takes slower steps of the same length.
+    fn agent_speed(&self) -> f32 {
+        self.walk_speed() * self.speed_factor()
+    }
+
+    /// Second phase of the bot once its health has dropped low enough, `None` before that and
+    /// for bots without one.
+    fn phase(&self) -> Option<&'static PhaseDefinition> {
+        Self::get_definition(self.kind)
+            .second_phase
+            .as_ref()
+            .filter(|phase| self.character.health < self.max_health() * phase.health_fraction)
+    }
+
+    fn walk_speed(&self) -> f32 {
+        self.phase().map_or_else(
+            || {
+                tunables::bot(self.kind)
+                    .walk_speed
+                    .unwrap_or(self.definition().walk_speed)
+            },
+            |phase| phase.walk_speed,
+        )
+    }
+
+    /// Health the bot spawns with, medkits don't heal it any further.
+    pub fn max_health(&self) -> f32 {
+        tunables::bot(self.kind)
+            .health
+            .unwrap_or(self.definition().health)
+    }
+
+    /// Picks up tunables that were changed on disk. Health is only ever clamped to the new
+    /// maximum, a reload never heals a bot in the middle of a fight.
+    pub fn on_definitions_reloaded(&mut self) {
+        let max_health = self.max_health();
+        self.character.health = self.character.health.min(max_health);
+        // Clamped health must not look like damage.
+        self.last_health = self.last_health.min(max_health);
+    }
+
+    pub fn lod(&self) -> AiLod {
+        self.lod
+    }
+
+    /// Bots in combat or near a player run at full rate, others drop to reduced rate. Bot is
+    /// promoted back on the very frame a player comes close or it gets hit.
+    fn update_lod(
+        &mut self,
+        nearest_player_distance: Option<f32>,
+        options: &AiLodOptions,
+        dt: f32,
+    ) {
+        if self.target.is_some() || self.character.health < self.last_health {
+            self.calm_time = 0.0;
+        } else {
+            self.calm_time += dt;
+        }
+        let is_near_player = nearest_player_distance
+            .map_or(false, |distance| distance <= options.full_rate_distance);
+        self.lod = if is_near_player || self.calm_time < options.combat_cooldown {
+            AiLod::Full
+        } else {
+            AiLod::Reduced
+        };
+    }
+
     pub fn update(
         &mut self,
         self_handle: Handle<Actor>,
         context: &mut UpdateContext,
         targets: &[TargetDescriptor],
+        target_grid: &SpatialGrid<usize>,
     ) {
+        scope_profile!();
+
+        // None when there is no living player on the level.
+        let position = self.character.position(&context.scene.graph);
+        let nearest_player_distance = context
+            .player_positions
+            .iter()
+            .map(|player_position| player_position.metric_distance(&position))
+            .min_by(|a, b| a.total_cmp(b));
+
         if self.character.is_dead() {
-            self.dying_machine
-                .apply(context.scene, context.time, self.character.is_dead());
+            if !self.death_sound_played {
+                self.death_sound_played = true;
+                if let Some(death_sound) = self.definition().death_sound {
+                    self.play_voice(&[death_sound], position);
+                }
+            }
+            // Pose of a settled corpse doesn't change, so nobody sees it isn't applied.
+            let is_far = nearest_player_distance.map_or(true, |distance| {
+                distance > context.ai_lod.full_rate_distance
+            });
+            if !is_far || !self.dying_machine.is_settled(context.scene) {
+                self.dying_machine
+                    .apply(context.scene, context.time, self.character.is_dead());
+            }
 
-            // Lock dead bot in-place.
             let body = context.scene.graph[self.body].as_rigid_body_mut();
-            let mut lin_vel = body.lin_vel();
-            lin_vel.x = 0.0;
-            lin_vel.z = 0.0;
-            body.set_lin_vel(lin_vel);
+            if self.is_corpse() {
+                // Corpse lies still, it's let to fall asleep so it costs no simulation time.
+                body.set_lin_vel(Vector3::default());
+                body.set_can_sleep(true);
+
+                let was_expired = self.corpse_time >= self.corpse_lifetime;
+                self.corpse_time += context.time.delta;
+                if !was_expired && self.corpse_time >= self.corpse_lifetime {
+                    let position = body.global_position();
+                    self.character
+                        .sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::CreateEffect {
+                            kind: EffectKind::CorpseDissolve,
+                            position,
+                            direction: Vector3::y(),
+                        })
+                        .unwrap();
+                }
+            } else {
+                // Lock dead bot in-place.
+                let mut lin_vel = body.lin_vel();
+                lin_vel.x = 0.0;
+                lin_vel.z = 0.0;
+                body.set_lin_vel(lin_vel);
+            }
         } else {
-            self.select_target(self_handle, context.scene, targets);
+            self.update_lod(nearest_player_distance, &context.ai_lod, context.time.delta);
+            let is_full_rate = self.lod == AiLod::Full;
+            // At full rate the accumulated times are just the frame delta.
+            self.perception_time += context.time.delta;
+            self.animation_time += context.time.delta;
+            let perception_time =
+                if is_full_rate || self.perception_time >= REDUCED_PERCEPTION_INTERVAL {
+                    Some(std::mem::take(&mut self.perception_time))
+                } else {
+                    None
+                };
+            let animation_time =
+                if is_full_rate || self.animation_time >= REDUCED_ANIMATION_INTERVAL {
+                    Some(std::mem::take(&mut self.animation_time))
+                } else {
+                    None
+                };
+
+            // Bot at reduced rate has no target by definition, and nobody is close enough to
+            // be seen anyway.
+            let perceives =
+                is_full_rate && self.is_perception_due(self_handle, context.time.elapsed);
+            if perceives {
+                // Head barely moves between perceptions, the frustum is only needed by them.
+                self.update_frustum(position, &context.scene.graph);
+                self.select_target(
+                    self_handle,
+                    context.scene,
+                    targets,
+                    target_grid,
+                    context.actor_query,
+                    context.query_buffer,
+                    context.time.elapsed,
+                );
+            } else {
+                self.extrapolate_target(context.time.elapsed);
+            }
             self.select_weapon(context.weapons);
-            self.select_point_of_interest(context.items, context.scene, &context.time);
+            if perception_time.is_some()
+                && (self.patrol.is_empty() || self.flee.is_some() || self.out_of_ammo)
+            {
+                self.select_point_of_interest(
+                    context.items,
+                    context.weapons,
+                    context.scene,
+                    context.item_query,
+                    &context.time,
+                );
+            }
+
+            // Dodge sideways when hit, dash is the same as the one used by player.
+            if self.character.health < self.last_health
+                && self.target.is_some()
+                && self.character.can_dash()
+                && rand::thread_rng().gen_bool(0.5)
+            {
+                let side = context.scene.graph[self.character.body].side_vector();
+                let side = if rand::thread_rng().gen_bool(0.5) {
+                    side
+                } else {
+                    -side
+                };
+                self.character.dash(side, &context.scene.graph);
+            }
+            let is_dashing = self
+                .character
+                .update_dash(&mut context.scene.graph, context.time.delta);
 
             let has_ground_contact = self.character.has_ground_contact(&context.scene.graph);
             let body = context.scene.graph[self.character.body].as_rigid_body_mut();
-            let (in_close_combat, look_dir) = match self.target.as_ref() {
-                None => (false, self.point_of_interest - body.global_position()),
+            let position = body.global_position();
+            self.update_investigation(position, context.time.delta);
+            self.update_points_of_interest(context.time.delta);
+            self.update_flee(context.time.delta);
+            let (in_close_combat, look_dir, destination) = match self.target.as_ref() {
+                None => {
+                    // Bot without a target searches where it has lost one, checks out what
+                    // it has heard, then walks its patrol route. Bots without a route go for
+                    // items, fleeing bots go for medkits.
+                    self.strafe = None;
+                    if self.flee.is_some() {
+                        self.last_known_target = None;
+                        self.damage_source = None;
+                    }
+                    // Bot turns to where it was hurt from once the hit reaction is over, and
+                    // looks for the attacker as soon as it faces it.
+                    let is_reacting =
+                        self.restoration_time > 0.0 || self.character.health < self.last_health;
+                    let damage_source = self.damage_source.as_mut().filter(|_| !is_reacting);
+                    if let Some(source) = damage_source {
+                        let d = source.position - position;
+                        if !source.turned
+                            && angle_between(self.yaw.angle(), d.x.atan2(d.z))
+                                <= FACING_ANGLE.to_radians()
+                        {
+                            source.turned = true;
+                            self.perception_tick = i64::MIN;
+                        }
+                        let destination = source.position;
+                        self.patrol.pause();
+                        (false, d, destination)
+                    } else {
+                        match self.update_search(position, context.time) {
+                            Some((look_dir, destination)) => {
+                                self.patrol.pause();
+                                (false, look_dir, destination)
+                            }
+                            None => {
+                                // Fleeing bot and bot out of ammo only care for items, points
+                                // given by scripts and sounds take over patrols.
+                                let urgent = self.point_of_interest_above(PoiPriority::Item);
+                                let item = self.item_point_of_interest();
+                                let destination = if self.flee.is_some() || self.out_of_ammo {
+                                    item.unwrap_or(position)
+                                } else if let Some(investigation) = self.investigation.as_ref() {
+                                    self.patrol.pause();
+                                    investigation.position
+                                } else if let Some(poi) = urgent {
+                                    self.patrol.pause();
+                                    poi
+                                } else {
+                                    self.patrol.advance(position).or(item).unwrap_or(position)
+                                };
+                                (false, destination - position, destination)
+                            }
+                        }
+                    }
+                }
                 Some(target) => {
-                    let d = target.position - body.global_position();
-                    let close_combat_threshold = 2.0;
-                    (d.norm() <= close_combat_threshold, d)
+                    self.investigation = None;
+                    self.damage_source = None;
+                    self.patrol.pause();
+                    let d = target.position - position;
+                    match self.flee.as_mut() {
+                        // Fleeing bot runs away but keeps aiming at the target, so it never
+                        // gets close enough to whip and shoots back if it can.
+                        Some(flee) => {
+                            self.strafe = None;
+                            let away = (position - target.position)
+                                .try_normalize(f32::EPSILON)
+                                .unwrap_or_else(Vector3::x);
+                            flee.point = position + away.scale(FLEE_DISTANCE);
+                            (false, d, flee.point)
+                        }
+                        None => {
+                            // Bot out of ammo has nothing to keep the range for.
+                            let engagement = self
+                                .definition()
+                                .engagement
+                                .filter(|range| !self.out_of_ammo && !range.contains(d.norm()));
+                            if let Some(range) = engagement {
+                                // Ranged bot gets its target back in range first, then it
+                                // strafes like everyone else.
+                                self.strafe = None;
+                                (false, d, range.destination(position, target.position))
+                            } else if d.norm() <= self.close_combat_distance() {
+                                self.strafe = None;
+                                (true, d, target.position)
+                            } else if self.out_of_ammo {
+                                // Bot out of ammo walks right up to its target to bash it.
+                                self.strafe = None;
+                                (false, d, target.position)
+                            } else {
+                                let strafe_point = Self::update_strafe(
+                                    &mut self.strafe,
+                                    position,
+                                    d,
+                                    context.scene.navmeshes.at(0),
+                                    context.time.delta,
+                                );
+                                // Ranged bot with nowhere to step holds its ground.
+                                let fallback = if self.definition().engagement.is_some() {
+                                    position
+                                } else {
+                                    target.position
+                                };
+                                (false, d, strafe_point.unwrap_or(fallback))
+                            }
+                        }
+                    }
                 }
             };
+            let limp_factor = self.limp_factor();
+            let speed_factor = self.speed_factor();
 
-            let position = body.global_position();
+            if let Some(detour) = self.detour.as_mut() {
+                detour.time_left -= context.time.delta;
+                let offset = detour.point - position;
+                if detour.time_left <= 0.0
+                    || Vector3::new(offset.x, 0.0, offset.z).norm() <= STUCK_ARRIVAL_DISTANCE
+                {
+                    self.detour = None;
+                }
+            }
+            let destination = self
+                .detour
+                .as_ref()
+                .map_or(destination, |detour| detour.point);
+            // Bot that stands still on purpose, in close combat or at its destination, is
+            // never stuck.
+            let to_destination = destination - position;
+            let wants_to_move = !in_close_combat
+                && !is_dashing
+                && has_ground_contact
+                && !self.navmesh_agent.path().is_empty()
+                && Vector3::new(to_destination.x, 0.0, to_destination.z).norm()
+                    > STUCK_ARRIVAL_DISTANCE;
+            let unstuck = self.update_stuck(position, wants_to_move, context.time.delta);
+            match unstuck {
+                Some(Unstuck::Repath) => self.repath_requested = true,
+                Some(Unstuck::Detour) => {
+                    let angle = rand::thread_rng().gen_range(0.0..std::f32::consts::TAU);
+                    let point = position
+                        + Vector3::new(angle.cos(), 0.0, angle.sin()).scale(DETOUR_DISTANCE);
+                    if context
+                        .scene
+                        .navmeshes
+                        .at(0)
+                        .map_or(false, |navmesh| is_on_navmesh(navmesh, point))
+                    {
+                        self.detour = Some(Strafe {
+                            point,
+                            time_left: DETOUR_TIME,
+                        });
+                    }
+                }
+                Some(Unstuck::Jump) | None => (),
+            }
 
-            if let Some(navmesh) = context.scene.navmeshes.at_mut(0) {
+            // Path is followed in coarse steps at reduced rate, the body still moves every frame.
+            if let (Some(navmesh), Some(navmesh_time)) =
+                (context.scene.navmeshes.at_mut(0), perception_time)
+            {
                 self.navmesh_agent
-                    .set_position(position - Vector3::new(0.0, 0.45, 0.0));
-                if let Some(target) = self.target.as_ref() {
-                    self.navmesh_agent.set_target(target.position);
-                } else {
-                    self.navmesh_agent.set_target(self.point_of_interest);
+                    .set_position(position - Vector3::new(0.0, AGENT_HEIGHT_OFFSET, 0.0));
+                if std::mem::take(&mut self.repath_requested) {
+                    // Agent recalculates its path when its target moves, so it's moved back
+                    // and forth.
+                    self.navmesh_agent.set_target(position);
                 }
-                self.navmesh_agent.set_speed(self.definition().walk_speed);
-                let _ = self.navmesh_agent.update(context.time.delta, navmesh);
+                self.navmesh_agent.set_target(destination);
+                self.navmesh_agent.set_speed(self.agent_speed());
+                let _ = self.navmesh_agent.update(navmesh_time, navmesh);
             }
 
-            let need_jump = look_dir.y >= 0.3 && has_ground_contact && in_close_combat;
+            let need_jump = has_ground_contact
+                && (look_dir.y >= 0.3 && in_close_combat || unstuck == Some(Unstuck::Jump));
             if need_jump {
                 body.set_lin_vel(Vector3::new(body.lin_vel().x, 0.08, body.lin_vel().z));
             }
-            let was_damaged = self.character.health < self.last_health;
-            if was_damaged {
+
+            // Leap takes the place of the jump in the locomotion machine.
+            self.lunge_cooldown = (self.lunge_cooldown - context.time.delta).max(0.0);
+            let mut lunge_started = false;
+            if let (Some(lunge), Some(target)) = (self.definition().lunge, self.target.as_ref()) {
+                let d = target.position - position;
+                if self.lunge.is_none()
+                    && self.lunge_cooldown <= 0.0
+                    && has_ground_contact
+                    && d.norm() <= lunge.distance
+                {
+                    if let Some(direction) = Vector3::new(d.x, 0.0, d.z).try_normalize(f32::EPSILON)
+                    {
+                        let mut velocity = direction.scale(lunge.speed);
+                        velocity.y = lunge.lift;
+                        body.set_lin_vel(velocity);
+                        self.lunge = Some(Lunge {
+                            target: target.handle,
+                            time: 0.0,
+                        });
+                        self.lunge_cooldown = lunge.cooldown;
+                        lunge_started = true;
+                    }
+                }
+            }
+            // Enraged bot slams the ground when its target gets close, the shockwave hits
+            // everyone around.
+            self.slam_cooldown = (self.slam_cooldown - context.time.delta).max(0.0);
+            let is_slamming = self.combat_machine.is_slamming();
+            if let (Some(slam), Some(target)) = (
+                self.phase().and_then(|phase| phase.slam),
+                self.target.as_ref(),
+            ) {
+                if !is_slamming
+                    && self.combat_machine.can_slam()
+                    && !self.slam_pending
+                    && self.slam_cooldown <= 0.0
+                    && has_ground_contact
+                    && target.position.metric_distance(&position) <= slam.distance
+                {
+                    self.slam_cooldown = slam.cooldown;
+                    self.slam_pending = true;
+                }
+            }
+            // Slam waits at its first frame until the machine gets to it, a hit reaction may
+            // hold it back for a while.
+            if self.slam_pending && !is_slamming {
+                context
+                    .scene
+                    .animations
+                    .get_mut(self.combat_machine.slam_animation)
+                    .rewind();
+            }
+            self.update_dodge(
+                self_handle,
+                position,
+                context.projectiles,
+                has_ground_contact && !is_dashing && self.lunge.is_none(),
+                context.time.delta,
+            );
+            let lunge_hit = self.update_lunge(
+                body,
+                position,
+                targets,
+                has_ground_contact,
+                context.time.delta,
+            );
+            let staggered = self.update_stagger(
+                (self.last_health - self.character.health).max(0.0),
+                context.time.delta,
+            );
+            if staggered {
                 let hit_reaction = context
                     .scene
                     .animations
@@ -1044,17 +3394,46 @@ impl Bot {
                 if hit_reaction.has_ended() {
                     hit_reaction.rewind();
                 }
-                self.restoration_time = 0.8;
             }
-            let can_aim = self.restoration_time <= 0.0;
+            self.update_vocalizations(
+                position,
+                self.character.health < self.last_health,
+                context.time.delta,
+            );
+            let can_aim = self.can_aim();
             self.last_health = self.character.health;
 
-            if !in_close_combat {
+            // Bots that chase the same target don't walk inside each other, the push is never
+            // taken off the navmesh.
+            let separation = if !in_close_combat && self.lunge.is_none() {
+                let push = self.separation(
+                    self_handle,
+                    &context.scene.graph,
+                    targets,
+                    target_grid,
+                    context.actor_query,
+                );
+                let navmesh = context.scene.navmeshes.at(0);
+                Some(push).filter(|push| {
+                    push.norm() > f32::EPSILON
+                        && navmesh.map_or(true, |navmesh| is_on_navmesh(navmesh, position + push))
+                })
+            } else {
+                None
+            };
+            let body = context.scene.graph[self.character.body].as_rigid_body_mut();
+
+            // Bot stands still while it slams.
+            if !in_close_combat && !is_dashing && self.lunge.is_none() && !is_slamming {
+                let to_agent = self.navmesh_agent.position() - position;
                 if has_ground_contact {
-                    if let Some(move_dir) =
-                        (self.navmesh_agent.position() - position).try_normalize(f32::EPSILON)
-                    {
-                        let mut vel = move_dir.scale(1.0 / context.time.delta);
+                    let sidestep = self.dodge.as_ref().map(|dodge| dodge.direction);
+                    if let Some(move_dir) = sidestep.or_else(|| {
+                        agent_direction(to_agent).and_then(|path_dir| {
+                            (path_dir + separation.unwrap_or_default()).try_normalize(f32::EPSILON)
+                        })
+                    }) {
+                        let mut vel = move_dir.scale(speed_factor / context.time.delta);
                         vel.y = body.lin_vel().y;
                         body.set_lin_vel(vel);
                         self.last_move_dir = move_dir;
@@ -1063,52 +3442,101 @@ impl Bot {
                     // A bit of air control. This helps jump of ledges when there is jump pad below bot.
                     let mut vel = self
                         .last_move_dir
-                        .scale(self.definition().walk_speed / context.time.delta);
+                        .scale(self.walk_speed() / context.time.delta);
                     vel.y = body.lin_vel().y;
                     body.set_lin_vel(vel);
                 }
             }
 
-            self.update_frustum(position, &context.scene.graph);
-
             if let Some(look_dir) = look_dir.try_normalize(f32::EPSILON) {
-                self.aim_vertically(look_dir, &mut context.scene.graph, context.time);
+                self.aim_vertically(
+                    look_dir,
+                    context.weapons,
+                    &mut context.scene.graph,
+                    context.time,
+                );
                 self.aim_horizontally(look_dir, &mut context.scene.graph, context.time);
             }
 
-            self.locomotion_machine.apply(
-                context.scene,
-                context.time,
-                in_close_combat,
-                need_jump,
-                has_ground_contact,
-            );
-            self.combat_machine.apply(
-                context.scene,
-                context.time,
-                in_close_combat,
-                was_damaged,
-                can_aim,
-            );
-
-            let sender = self.character.sender.as_ref().unwrap();
+            if let Some(animation_time) = animation_time {
+                let time = GameTime {
+                    delta: animation_time,
+                    ..context.time
+                };
+                self.locomotion_machine
+                    .set_walk_speed(context.scene, limp_factor);
+                let move_dir = self.local_move_dir(&context.scene.graph);
+                self.locomotion_machine.apply(
+                    context.scene,
+                    time,
+                    in_close_combat,
+                    need_jump || lunge_started,
+                    has_ground_contact,
+                    move_dir,
+                );
+                self.combat_machine.apply(
+                    context.scene,
+                    time,
+                    in_close_combat,
+                    staggered,
+                    can_aim,
+                    self.slam_pending,
+                );
+            }
 
-            if !in_close_combat && can_aim && self.can_shoot() && self.target.is_some() {
-                if let Some(weapon) = self
+            let ready_to_fire = self.update_fire_readiness(context.time.delta);
+            if !in_close_combat && ready_to_fire && self.can_shoot() {
+                let weapon = self
                     .character
                     .weapons
                     .get(self.character.current_weapon as usize)
-                {
-                    sender
-                        .send(Message::ShootWeapon {
-                            weapon: *weapon,
-                            initial_velocity: Vector3::default(),
-                            direction: Some(look_dir),
-                        })
-                        .unwrap();
+                    .copied();
+                // Reloading or overheated weapon is held on to, the bot just holds fire until
+                // it's ready.
+                if let Some(weapon) = weapon.filter(|weapon| {
+                    !context.weapons[*weapon].is_reloading()
+                        && !context.weapons[*weapon].is_overheated()
+                        && self.update_line_of_fire(
+                            &context.weapons[*weapon],
+                            &context.scene.graph,
+                            targets,
+                            context.query_buffer,
+                            look_dir,
+                            context.time.delta,
+                        )
+                }) {
+                    let shoot_interval = context.weapons[weapon].attack_interval();
+                    if self.take_shot(context.time.elapsed, shoot_interval) {
+                        self.character
+                            .sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::ShootWeapon {
+                                weapon,
+                                direction: Some(spread_direction(
+                                    self.lead_direction(
+                                        &context.weapons[weapon],
+                                        &context.scene.graph,
+                                    )
+                                    .unwrap_or(look_dir),
+                                    self.difficulty.aim_error_degrees,
+                                )),
+                            })
+                            .unwrap();
+                        // Every shot of a bot is a separate pull of the trigger, so
+                        // semi-automatic weapons keep up with the shoot interval.
+                        self.character
+                            .sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::ReleaseTrigger { weapon })
+                            .unwrap();
+                    }
                 }
             }
 
+            let sender = self.character.sender.as_ref().unwrap();
+
             // Apply damage to target from melee attack
             if let Some(target) = self.target.as_ref() {
                 while let Some(event) = context
@@ -1117,18 +3545,56 @@ impl Bot {
                     .get_mut(self.combat_machine.whip_animation)
                     .pop_event()
                 {
-                    if event.signal_id == CombatMachine::HIT_SIGNAL && in_close_combat {
-                        sender
-                            .send(Message::DamageActor {
-                                actor: target.handle,
-                                who: Default::default(),
-                                amount: 20.0,
-                            })
-                            .unwrap();
+                    if event.signal_id == CombatMachine::HIT_SIGNAL {
+                        self.whip(
+                            self_handle,
+                            &context.scene.graph,
+                            targets,
+                            target_grid,
+                            context.actor_query,
+                            target,
+                        );
                     }
                 }
             }
 
+            while let Some(event) = context
+                .scene
+                .animations
+                .get_mut(self.combat_machine.slam_animation)
+                .pop_event()
+            {
+                if event.signal_id == CombatMachine::SLAM_SIGNAL
+                    && self.combat_machine.is_slamming()
+                    && std::mem::take(&mut self.slam_pending)
+                {
+                    self.slam(
+                        self_handle,
+                        &context.scene.graph,
+                        targets,
+                        target_grid,
+                        context.actor_query,
+                    );
+                }
+            }
+
+            if let Some((target, target_position)) = lunge_hit {
+                sender
+                    .send(Message::DamageActor {
+                        actor: target,
+                        who: self_handle,
+                        amount: self.definition().lunge.map_or(0.0, |lunge| lunge.damage),
+                        hit: (target_position - position)
+                            .try_normalize(std::f32::EPSILON)
+                            .map(|direction| DamageHit {
+                                position: target_position,
+                                direction,
+                                normal: -direction,
+                            }),
+                    })
+                    .unwrap();
+            }
+
             // Emit step sounds from walking animation.
             if self.locomotion_machine.is_walking() {
                 while let Some(event) = context
@@ -1138,27 +3604,21 @@ impl Bot {
                     .pop_event()
                 {
                     if event.signal_id == LocomotionMachine::STEP_SIGNAL && has_ground_contact {
-                        let footsteps = [
-                            "data/sounds/footsteps/FootStep_shoe_stone_step1.wav",
-                            "data/sounds/footsteps/FootStep_shoe_stone_step2.wav",
-                            "data/sounds/footsteps/FootStep_shoe_stone_step3.wav",
-                            "data/sounds/footsteps/FootStep_shoe_stone_step4.wav",
-                        ];
                         sender
                             .send(Message::PlaySound {
-                                path: footsteps[rand::thread_rng().gen_range(0..footsteps.len())]
-                                    .into(),
+                                path: FOOTSTEP_SOUNDS
+                                    [rand::thread_rng().gen_range(0..FOOTSTEP_SOUNDS.len())]
+                                .into(),
                                 position,
                                 gain: 1.0,
                                 rolloff_factor: 2.0,
                                 radius: 3.0,
+                                category: SoundCategory::Sfx,
                             })
                             .unwrap();
                     }
                 }
             }
-
-            self.restoration_time -= context.time.delta;
         }
     }
 
@@ -1169,6 +3629,10 @@ impl Bot {
         self.character.clean_up(scene);
     }
 
+    pub fn target_handle(&self) -> Option<Handle<Actor>> {
+        self.target.as_ref().map(|target| target.handle)
+    }
+
     pub fn on_actor_removed(&mut self, handle: Handle<Actor>) {
         if let Some(target) = self.target.as_ref() {
             if target.handle == handle {
@@ -1177,9 +3641,541 @@ impl Bot {
         }
     }
 
-    pub fn set_point_of_interest(&mut self, poi: Vector3<f32>, time: GameTime) {
-        self.point_of_interest = poi;
-        self.last_poi_update_time = time.elapsed;
+    /// Bot without a target turns to a sound it can hear and goes to check it out. Sound is
+    /// heard within `loudness * HEARING_DISTANCE`, dead and dying bots hear nothing.
+    pub fn on_sound_heard(&mut self, graph: &Graph, position: Vector3<f32>, loudness: f32) {
+        if self.character.is_dead() || self.target.is_some() {
+            return;
+        }
+        let distance = self.position(graph).metric_distance(&position);
+        if distance > loudness * HEARING_DISTANCE || distance <= INVESTIGATION_ARRIVAL_DISTANCE {
+            return;
+        }
+        self.investigation = Some(Investigation {
+            position,
+            time_left: INVESTIGATION_TIME,
+        });
+        // A distant bot at reduced rate doesn't look for targets, the sound wakes it up.
+        self.calm_time = 0.0;
+    }
+
+    // Bot starts to flee when it's hurt in a fight and stops once it's healed or hasn't seen
+    // its target for a while, then it fights until it's healed.
+    fn update_flee(&mut self, dt: f32) {
+        if self.character.health >= self.definition().flee_health_threshold {
+            self.flee = None;
+            self.fled = false;
+            return;
+        }
+        match self.flee.as_mut() {
+            None => {
+                if !self.fled && self.target.is_some() {
+                    self.flee = Some(Flee::default());
+                    // Medkit is picked on the next perception update.
+                    self.last_poi_update_time = f64::MIN;
+                }
+            }
+            Some(flee) => {
+                if self.target.is_some() {
+                    flee.unseen_time = 0.0;
+                } else {
+                    flee.unseen_time += dt;
+                }
+                if flee.unseen_time >= FLEE_CALM_TIME {
+                    self.flee = None;
+                    self.fled = true;
+                }
+            }
+        }
+    }
+
+    // Bot in ranged combat steps to a side of its target and picks another side every now and
+    // then, so it's harder to hit. Sides that are off the navmesh are not taken, `None` means
+    // that the bot has nowhere to step and walks to its target.
+    fn update_strafe(
+        strafe: &mut Option<Strafe>,
+        position: Vector3<f32>,
+        look_dir: Vector3<f32>,
+        navmesh: Option<&Navmesh>,
+        dt: f32,
+    ) -> Option<Vector3<f32>> {
+        if let Some(current) = strafe.as_mut() {
+            current.time_left -= dt;
+            if current.time_left > 0.0 {
+                return Some(current.point);
+            }
+        }
+        *strafe = None;
+
+        let side = look_dir.cross(&Vector3::y()).try_normalize(f32::EPSILON)?;
+        let mut rng = rand::thread_rng();
+        let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+        let navmesh = navmesh?;
+        let point = [sign, -sign]
+            .into_iter()
+            .map(|sign| position + side.scale(sign * STRAFE_DISTANCE))
+            .find(|point| is_on_navmesh(navmesh, *point))?;
+        *strafe = Some(Strafe {
+            point,
+            time_left: rng.gen_range(STRAFE_TIME),
+        });
+        Some(point)
+    }
+
+    // Bot walks to where it has seen its target last time and sweeps its yaw there, so the
+    // target is seen again if it's around. Returns look direction and destination of the
+    // search, `None` once the memory has expired or the search is over.
+    fn update_search(
+        &mut self,
+        position: Vector3<f32>,
+        time: GameTime,
+    ) -> Option<(Vector3<f32>, Vector3<f32>)> {
+        let duration = self.definition().search_time;
+        let last = self.last_known_target.as_mut()?;
+        if let Some(search_time) = last.search_time.as_mut() {
+            *search_time += time.delta;
+        }
+        let expired = time.elapsed - last.time >= LAST_KNOWN_TARGET_MEMORY as f64;
+        if expired || last.search_time.map_or(false, |t| t >= duration) {
+            self.last_known_target = None;
+            return None;
+        }
+
+        let offset = last.position - position;
+        match last.search_time {
+            None => {
+                if Vector3::new(offset.x, 0.0, offset.z).norm() <= SEARCH_ARRIVAL_DISTANCE {
+                    last.search_time = Some(0.0);
+                    last.heading = Vector3::new(self.last_move_dir.x, 0.0, self.last_move_dir.z)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::z);
+                }
+                Some((offset, last.position))
+            }
+            Some(search_time) => {
+                let phase = search_time * std::f32::consts::TAU / SEARCH_SWEEP_PERIOD;
+                let angle = SEARCH_SWEEP_ANGLE.to_radians() * phase.sin();
+                let look_dir = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle)
+                    .transform_vector(&last.heading);
+                Some((look_dir, position))
+            }
+        }
+    }
+
+    // Bot on the ground sidesteps a projectile that flies at it, perpendicular to its path
+    // and away from the point where it would pass.
+    fn update_dodge(
+        &mut self,
+        self_handle: Handle<Actor>,
+        position: Vector3<f32>,
+        projectiles: &[ProjectileDescriptor],
+        can_dodge: bool,
+        dt: f32,
+    ) {
+        self.dodge_cooldown = (self.dodge_cooldown - dt).max(0.0);
+        if let Some(dodge) = self.dodge.as_mut() {
+            dodge.time_left -= dt;
+            if dodge.time_left <= 0.0 || !can_dodge {
+                self.dodge = None;
+            }
+            return;
+        }
+        if !can_dodge || self.dodge_cooldown > 0.0 {
+            return;
+        }
+        for projectile in projectiles {
+            if projectile.shooter == self_handle {
+                continue;
+            }
+            let sqr_speed = projectile.velocity.norm_squared();
+            if sqr_speed <= f32::EPSILON {
+                continue;
+            }
+            // Time of the closest approach, negative for projectiles that fly away.
+            let time = (position - projectile.position).dot(&projectile.velocity) / sqr_speed;
+            if time <= 0.0 || time > DODGE_LOOKAHEAD {
+                continue;
+            }
+            let impact = projectile.position + projectile.velocity.scale(time);
+            let miss = position - impact;
+            if miss.norm() >= DODGE_RADIUS {
+                continue;
+            }
+            let side = match projectile
+                .velocity
+                .cross(&Vector3::y())
+                .try_normalize(f32::EPSILON)
+            {
+                Some(side) => side,
+                // Projectile falls straight down, any way is out.
+                None => Vector3::x(),
+            };
+            let direction = if side.dot(&miss) >= 0.0 { side } else { -side };
+            self.dodge = Some(Dodge {
+                direction,
+                time_left: DODGE_TIME,
+                impact,
+            });
+            self.dodge_cooldown = DODGE_COOLDOWN;
+            break;
+        }
+    }
+
+    // Shots leave the muzzle, not the eyes, so a target that is seen may still be out of the
+    // line of fire behind a low cover or under the floor. The first thing hit along the aim up
+    // to the target must be the target itself. Blocked bot picks another point to step to.
+    fn update_line_of_fire(
+        &mut self,
+        weapon: &Weapon,
+        graph: &Graph,
+        targets: &[TargetDescriptor],
+        query_buffer: &mut Vec<Intersection>,
+        look_dir: Vector3<f32>,
+        dt: f32,
+    ) -> bool {
+        self.line_of_fire_check_time_left -= dt;
+        if self.line_of_fire_check_time_left > 0.0 {
+            return self.line_of_fire_clear;
+        }
+        self.line_of_fire_check_time_left = LINE_OF_FIRE_CHECK_INTERVAL;
+
+        let target = match self
+            .target
+            .as_ref()
+            .and_then(|target| targets.iter().find(|desc| desc.handle == target.handle))
+        {
+            Some(target) => target,
+            None => {
+                self.line_of_fire_clear = false;
+                return false;
+            }
+        };
+        let origin = weapon.get_shot_position(graph);
+        let direction = match look_dir.try_normalize(f32::EPSILON) {
+            Some(direction) => direction,
+            None => {
+                self.line_of_fire_clear = false;
+                return false;
+            }
+        };
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(origin),
+                ray_direction: direction,
+                groups: InteractionGroups::default(),
+                max_len: origin.metric_distance(&target.position),
+                sort_results: true,
+            },
+            query_buffer,
+        );
+        self.line_of_fire_clear = query_buffer
+            .iter()
+            .map(|hit| graph[hit.collider].parent())
+            .find(|&body| body != self.character.body)
+            .map_or(true, |body| body == target.body);
+        if !self.line_of_fire_clear {
+            self.strafe = None;
+        }
+        self.line_of_fire_clear
+    }
+
+    // Idle growls while there is no target, a bark when one is spotted and cries of pain that
+    // don't come faster than the pain interval.
+    fn update_vocalizations(&mut self, position: Vector3<f32>, was_damaged: bool, dt: f32) {
+        let definition = self.definition();
+        self.pain_sound_cooldown = (self.pain_sound_cooldown - dt).max(0.0);
+        if was_damaged && self.pain_sound_cooldown <= 0.0 {
+            self.pain_sound_cooldown = PAIN_SOUND_INTERVAL;
+            self.play_voice(definition.pain_sounds, position);
+        }
+
+        let has_target = self.target.is_some();
+        if has_target && !self.had_target {
+            self.play_voice(definition.aggro_sounds, position);
+        }
+        self.had_target = has_target;
+
+        if has_target {
+            self.idle_sound_time_left = IDLE_SOUND_INTERVAL.end;
+        } else {
+            self.idle_sound_time_left -= dt;
+            if self.idle_sound_time_left <= 0.0 {
+                self.idle_sound_time_left = rand::thread_rng().gen_range(IDLE_SOUND_INTERVAL);
+                self.play_voice(definition.idle_sounds, position);
+            }
+        }
+    }
+
+    // Plays one of given sounds at random, a bot without such sounds stays silent. Sounds that
+    // fail to load are skipped by the sound manager.
+    fn play_voice(&self, sounds: &[&str], position: Vector3<f32>) {
+        if sounds.is_empty() {
+            return;
+        }
+        self.character
+            .sender
+            .as_ref()
+            .unwrap()
+            .send(Message::PlaySound {
+                path: sounds[rand::thread_rng().gen_range(0..sounds.len())].into(),
+                position,
+                gain: 1.0,
+                rolloff_factor: 1.0,
+                radius: 5.0,
+                category: SoundCategory::Voice,
+            })
+            .unwrap();
+    }
+
+    // Horizontal velocity of the body in the space of the model, X is to the right of the bot
+    // and Z is ahead of it. Bot that stands still walks forward.
+    fn local_move_dir(&self, graph: &Graph) -> Vector3<f32> {
+        let velocity = graph[self.character.body].as_rigid_body().lin_vel();
+        let look = graph[self.model].look_vector();
+        let forward = match Vector3::new(look.x, 0.0, look.z).try_normalize(f32::EPSILON) {
+            Some(forward) => forward,
+            None => return Vector3::z(),
+        };
+        let right = forward.cross(&Vector3::y());
+        Vector3::new(velocity.dot(&right), 0.0, velocity.dot(&forward))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z)
+    }
+
+    // Lunging bots bite, they never aim.
+    fn can_aim(&self) -> bool {
+        self.restoration_time <= 0.0 && self.definition().lunge.is_none()
+    }
+
+    // Counts down the pauses before shooting and tells whether the bot may shoot this frame.
+    // Only a hit reaction keeps a bot that can aim from shooting, so chip damage that doesn't
+    // stagger it never makes it hold fire.
+    fn update_fire_readiness(&mut self, dt: f32) -> bool {
+        self.reaction_time_left = (self.reaction_time_left - dt).max(0.0);
+        self.burst_pause_left = (self.burst_pause_left - dt).max(0.0);
+        self.can_aim()
+            && !self.out_of_ammo
+            && self.target.is_some()
+            && self.reaction_time_left <= 0.0
+            && self.burst_pause_left <= 0.0
+    }
+
+    // Shot is taken only when the weapon is ready, so bursts are counted in real shots.
+    fn take_shot(&mut self, elapsed: f64, shoot_interval: f64) -> bool {
+        if elapsed - self.last_shot_time < shoot_interval {
+            return false;
+        }
+        self.last_shot_time = elapsed;
+        self.burst_shots += 1;
+        if self.burst_shots >= self.difficulty.shoot_burst_length {
+            self.burst_shots = 0;
+            self.burst_pause_left = self.difficulty.pause_between_bursts;
+        }
+        true
+    }
+
+    // Bot is staggered when the damage it takes within the cooldown adds up to its threshold,
+    // or by a single heavy hit. Once staggered, it can't be staggered again until the cooldown
+    // is over, so sustained fire doesn't keep it from fighting back.
+    fn update_stagger(&mut self, damage: f32, dt: f32) -> bool {
+        let (hit_reaction_cooldown, stagger_threshold) = match self.phase() {
+            Some(phase) => (phase.hit_reaction_cooldown, phase.stagger_threshold),
+            None => (
+                self.definition().hit_reaction_cooldown,
+                self.definition().stagger_threshold,
+            ),
+        };
+        self.restoration_time = (self.restoration_time - dt).max(0.0);
+        self.stagger_cooldown = (self.stagger_cooldown - dt).max(0.0);
+        if let Some(window) = self.stagger_window.as_mut() {
+            window.time_left -= dt;
+            if window.time_left <= 0.0 {
+                self.stagger_window = None;
+            }
+        }
+        if damage <= 0.0 {
+            return false;
+        }
+        let window = self.stagger_window.get_or_insert(StaggerWindow {
+            damage: 0.0,
+            time_left: hit_reaction_cooldown,
+        });
+        window.damage += damage;
+        if self.stagger_cooldown > 0.0
+            || window.damage < stagger_threshold && damage < HEAVY_HIT_DAMAGE
+        {
+            return false;
+        }
+        self.stagger_window = None;
+        self.stagger_cooldown = hit_reaction_cooldown;
+        self.restoration_time = HIT_REACTION_TIME;
+        true
+    }
+
+    // Leap hits its target at most once, the target and the point of the hit are returned
+    // when it does. Leap at a target that is gone or dead stops in the air.
+    fn update_lunge(
+        &mut self,
+        body: &mut RigidBody,
+        position: Vector3<f32>,
+        targets: &[TargetDescriptor],
+        has_ground_contact: bool,
+        dt: f32,
+    ) -> Option<(Handle<Actor>, Vector3<f32>)> {
+        let (definition, lunge) = match (self.definition().lunge, self.lunge.as_mut()) {
+            (Some(definition), Some(lunge)) => (definition, lunge),
+            _ => return None,
+        };
+        lunge.time += dt;
+        let target = lunge.target;
+        let target_position = match targets
+            .iter()
+            .find(|desc| desc.handle == target && desc.health > 0.0)
+        {
+            Some(desc) => desc.position,
+            None => {
+                self.lunge = None;
+                body.set_lin_vel(Vector3::new(0.0, body.lin_vel().y, 0.0));
+                return None;
+            }
+        };
+        if target_position.metric_distance(&position) <= definition.hit_distance {
+            self.lunge = None;
+            return Some((target, target_position));
+        }
+        if lunge.time >= MAX_LUNGE_TIME || has_ground_contact && lunge.time >= LUNGE_TAKE_OFF_TIME {
+            self.lunge = None;
+        }
+        None
+    }
+
+    // Bot that wants to go somewhere and doesn't get anywhere for a while is wedged on the
+    // geometry. It recalculates its path first, then takes a detour, then jumps.
+    fn update_stuck(
+        &mut self,
+        position: Vector3<f32>,
+        wants_to_move: bool,
+        dt: f32,
+    ) -> Option<Unstuck> {
+        if !wants_to_move {
+            self.stuck_check_position = position;
+            self.stuck_check_time = 0.0;
+            self.unstuck_attempts = 0;
+            return None;
+        }
+        self.stuck_check_time += dt;
+        if self.stuck_check_time < STUCK_TIME {
+            return None;
+        }
+        let moved = position - self.stuck_check_position;
+        self.stuck_check_position = position;
+        self.stuck_check_time = 0.0;
+        if Vector3::new(moved.x, 0.0, moved.z).norm() >= STUCK_DISTANCE {
+            self.unstuck_attempts = 0;
+            return None;
+        }
+        let action =
+            [Unstuck::Repath, Unstuck::Detour, Unstuck::Jump][self.unstuck_attempts as usize % 3];
+        self.unstuck_attempts += 1;
+        self.unstuck_count += 1;
+        Log::writeln(
+            MessageKind::Information,
+            format!(
+                "{} is stuck, trying {:?} (unstuck {} times).",
+                self.character.name, action, self.unstuck_count
+            ),
+        );
+        Some(action)
+    }
+
+    fn update_investigation(&mut self, position: Vector3<f32>, dt: f32) {
+        if let Some(source) = self.damage_source.as_mut() {
+            source.time_left -= dt;
+            let offset = source.position - position;
+            let arrived =
+                Vector3::new(offset.x, 0.0, offset.z).norm() <= INVESTIGATION_ARRIVAL_DISTANCE;
+            if arrived || source.time_left <= 0.0 {
+                self.damage_source = None;
+            }
+        }
+        if let Some(investigation) = self.investigation.as_mut() {
+            investigation.time_left -= dt;
+            let offset = investigation.position - position;
+            let arrived =
+                Vector3::new(offset.x, 0.0, offset.z).norm() <= INVESTIGATION_ARRIVAL_DISTANCE;
+            if arrived || investigation.time_left <= 0.0 {
+                self.investigation = None;
+            }
+        }
+    }
+
+    /// Bot walks given route whenever it has no target, an empty route sends it for items.
+    pub fn set_patrol_route(&mut self, route: PatrolRoute) {
+        self.patrol = route;
+    }
+
+    /// Navmesh agent is not saved, it's placed back at the body of a loaded bot before the
+    /// first update so the bot neither slides nor sprints to where the agent was left.
+    pub fn on_level_loaded(&mut self, graph: &Graph) {
+        let position = self.character.position(graph);
+        let destination = self
+            .target
+            .as_ref()
+            .map(|target| target.position)
+            .or_else(|| self.point_of_interest())
+            .unwrap_or(position);
+        self.navmesh_agent
+            .set_position(position - Vector3::new(0.0, AGENT_HEIGHT_OFFSET, 0.0));
+        self.navmesh_agent.set_target(destination);
+        self.navmesh_agent.set_speed(self.walk_speed());
+    }
+
+    /// Gives the bot a point to walk to for `time_left` seconds. It replaces the point of the
+    /// same priority, points of higher priority are walked to first.
+    pub fn push_point_of_interest(
+        &mut self,
+        poi: Vector3<f32>,
+        priority: PoiPriority,
+        time_left: f32,
+    ) {
+        self.points_of_interest
+            .retain(|point| point.priority != priority);
+        self.points_of_interest.push(PointOfInterest {
+            position: poi,
+            priority,
+            time_left,
+        });
+    }
+
+    fn update_points_of_interest(&mut self, dt: f32) {
+        for point in self.points_of_interest.iter_mut() {
+            point.time_left -= dt;
+        }
+        self.points_of_interest
+            .retain(|point| point.time_left > 0.0);
+    }
+
+    /// The most important point of interest that hasn't expired yet.
+    fn point_of_interest(&self) -> Option<Vector3<f32>> {
+        self.points_of_interest
+            .iter()
+            .max_by_key(|point| point.priority)
+            .map(|point| point.position)
+    }
+
+    fn point_of_interest_above(&self, priority: PoiPriority) -> Option<Vector3<f32>> {
+        self.points_of_interest
+            .iter()
+            .filter(|point| point.priority > priority)
+            .max_by_key(|point| point.priority)
+            .map(|point| point.position)
+    }
+
+    fn item_point_of_interest(&self) -> Option<Vector3<f32>> {
+        self.points_of_interest
+            .iter()
+            .find(|point| point.priority == PoiPriority::Item)
+            .map(|point| point.position)
     }
 }
 
@@ -1190,3 +4186,351 @@ fn clean_machine(machine: &Machine, scene: &mut Scene) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Team;
+    use crate::tests::resource_manager;
+    use fyrox::core::futures::executor::block_on;
+
+    // Bot that has nothing but its body, enough for the parts that don't animate.
+    fn bot_at(graph: &mut Graph, position: Vector3<f32>) -> Bot {
+        let mut bot = Bot::default();
+        bot.character.collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::capsule_y(0.5, bot.definition().body_radius))
+            .build(graph);
+        bot.character.body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_children(&[bot.character.collider])
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                ),
+        )
+        .build(graph);
+        graph.update_hierarchical_data();
+        bot
+    }
+
+    #[test]
+    fn loaded_bot_follows_agent_from_its_body() {
+        let mut graph = Graph::new();
+        let position = Vector3::new(30.0, 1.0, -12.0);
+        let mut bot = bot_at(&mut graph, position);
+        bot.on_level_loaded(&graph);
+
+        let path = std::env::temp_dir().join(format!("wood_bot_{}.bin", std::process::id()));
+        let mut visitor = Visitor::new();
+        bot.visit("Bot", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+        let mut loaded = Bot::default();
+        let mut visitor = block_on(Visitor::load_binary(&path)).unwrap();
+        loaded.visit("Bot", &mut visitor).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.character.body, bot.character.body);
+
+        // Agent isn't saved, the one of a loaded bot is at the origin far away from the body.
+        // Following it would move the body by the whole offset in one frame.
+        let to_agent = loaded.navmesh_agent.position() - position;
+        assert!(to_agent.norm() > MAX_AGENT_OFFSET);
+        assert_eq!(agent_direction(to_agent), None);
+
+        loaded.on_level_loaded(&graph);
+        let to_agent = loaded.navmesh_agent.position() - position;
+        assert!(to_agent.norm() <= AGENT_HEIGHT_OFFSET + f32::EPSILON);
+        assert!(agent_direction(to_agent).is_some());
+    }
+
+    #[test]
+    fn view_ends_at_view_distance() {
+        for kind in [BotKind::Mutant, BotKind::Maw] {
+            let definition = Bot::get_definition(kind);
+            let position = Vector3::new(5.0, 0.0, 5.0);
+            let head = position + Vector3::new(0.0, definition.head_offset, 0.0);
+            let frustum = definition.view_frustum(position, Vector3::z(), Vector3::y());
+
+            let ahead = |distance: f32| head + Vector3::z().scale(distance);
+            assert!(frustum.is_contains_point(ahead(definition.view_distance - 0.1)));
+            assert!(!frustum.is_contains_point(ahead(definition.view_distance + 0.1)));
+            assert!(!frustum.is_contains_point(head - Vector3::z().scale(2.0)));
+
+            // Cone is as wide as it is high.
+            let half_angle = (definition.view_fov_degrees * 0.45).to_radians();
+            let side = Vector3::new(half_angle.sin(), 0.0, half_angle.cos()).scale(5.0);
+            let above = Vector3::new(0.0, half_angle.sin(), half_angle.cos()).scale(5.0);
+            assert!(frustum.is_contains_point(head + side));
+            assert!(frustum.is_contains_point(head + above));
+            assert!(definition.view_radius() > definition.view_distance);
+        }
+    }
+
+    #[test]
+    fn bots_chasing_same_point_keep_apart() {
+        let mut graph = Graph::new();
+        let bots = [
+            bot_at(&mut graph, Vector3::new(6.0, 0.0, 0.0)),
+            bot_at(&mut graph, Vector3::new(6.0, 0.0, 0.3)),
+        ];
+        let handles = [Handle::new(1, 1), Handle::new(2, 1)];
+        let definition = Bot::get_definition(BotKind::Mutant);
+        // Movement of `update` without the navmesh, bots walk straight to the point until
+        // they're close enough to attack.
+        let step = 3.0 / 60.0;
+        let mut grid = SpatialGrid::default();
+        let mut found = GridQuery::default();
+        for _ in 0..600 {
+            let targets = bots
+                .iter()
+                .zip(handles)
+                .map(|(bot, handle)| TargetDescriptor {
+                    handle,
+                    health: 100.0,
+                    position: bot.character.position(&graph),
+                    team: Team::None,
+                    last_damage_dealt_time: 0.0,
+                    aim: None,
+                    velocity: Vector3::default(),
+                    radius: bot.character.radius(&graph),
+                    body: bot.character.body,
+                })
+                .collect::<Vec<_>>();
+            grid.clear();
+            for (index, desc) in targets.iter().enumerate() {
+                grid.insert(index, desc.position);
+            }
+            for (bot, handle) in bots.iter().zip(handles) {
+                let position = bot.character.position(&graph);
+                let to_point = -position;
+                if to_point.norm() <= definition.close_combat_distance {
+                    continue;
+                }
+                let push = bot.separation(handle, &graph, &targets, &grid, &mut found);
+                if let Some(move_dir) = (to_point.normalize() + push).try_normalize(f32::EPSILON) {
+                    graph[bot.character.body]
+                        .local_transform_mut()
+                        .set_position(position + move_dir.scale(step));
+                }
+            }
+            graph.update_hierarchical_data();
+        }
+
+        let [a, b] = bots.map(|bot| bot.character.position(&graph));
+        for position in [a, b] {
+            assert!(position.norm() <= definition.close_combat_distance + step);
+        }
+        assert!(a.metric_distance(&b) > definition.body_radius * 2.0);
+    }
+
+    #[test]
+    fn sustained_light_fire_does_not_stun_lock() {
+        let mut bot = Bot::default();
+        let dt = 1.0 / 60.0;
+        let mut staggers = 0;
+        let mut frames_without_aim = 0;
+        let mut longest_without_aim = 0;
+        // Chip damage every few frames for ten seconds, like an automatic rifle does.
+        for frame in 0..600 {
+            let damage = if frame % 6 == 0 { 4.0 } else { 0.0 };
+            if bot.update_stagger(damage, dt) {
+                staggers += 1;
+            }
+            if bot.restoration_time > 0.0 {
+                frames_without_aim += 1;
+                longest_without_aim = longest_without_aim.max(frames_without_aim);
+            } else {
+                frames_without_aim = 0;
+            }
+        }
+        assert!(staggers > 0);
+        assert!(staggers as f32 <= 10.0 / bot.definition().hit_reaction_cooldown + 1.0);
+        // Bot aims again as soon as the hit reaction is over.
+        assert!(longest_without_aim as f32 * dt <= HIT_REACTION_TIME + dt);
+    }
+
+    #[test]
+    fn bot_keeps_shooting_under_sustained_light_fire() {
+        let mut bot = Bot {
+            target: Some(Target::default()),
+            ..Default::default()
+        };
+        assert!(bot.definition().lunge.is_none());
+        let dt = 1.0 / 60.0;
+        let shoot_interval = 0.1;
+        let mut shots = 0;
+        let mut last_shot = 0.0;
+        let mut longest_gap = 0.0f32;
+        // Same order as in `update`: damage of the frame first, then the shot.
+        for frame in 1..=600 {
+            let damage = if frame % 6 == 0 { 4.0 } else { 0.0 };
+            bot.update_stagger(damage, dt);
+            let elapsed = frame as f64 * dt as f64;
+            if bot.update_fire_readiness(dt) && bot.take_shot(elapsed, shoot_interval) {
+                shots += 1;
+                longest_gap = longest_gap.max((elapsed - last_shot) as f32);
+                last_shot = elapsed;
+            }
+        }
+        assert!(shots > 0);
+        // Nothing but the burst pause and an occasional hit reaction delays a shot.
+        let max_gap =
+            bot.difficulty.pause_between_bursts + HIT_REACTION_TIME + shoot_interval as f32 + dt;
+        assert!(longest_gap <= max_gap);
+        assert!((600.0 * dt - last_shot as f32) <= max_gap);
+    }
+
+    #[test]
+    fn chip_damage_does_not_stagger_but_heavy_hit_does() {
+        let mut bot = Bot::default();
+        let threshold = bot.definition().stagger_threshold;
+        assert!(!bot.update_stagger(threshold * 0.5, 0.1));
+        assert!(!bot.update_stagger(threshold * 0.4, 0.1));
+        // Damage of the window is forgotten once it's over.
+        assert!(!bot.update_stagger(0.0, bot.definition().hit_reaction_cooldown));
+        assert!(!bot.update_stagger(threshold * 0.5, 0.1));
+
+        let mut bot = Bot::default();
+        assert!(bot.update_stagger(HEAVY_HIT_DAMAGE, 0.1));
+        assert!(bot.restoration_time > 0.0);
+        // Next heavy hit within the cooldown doesn't stagger again.
+        assert!(!bot.update_stagger(HEAVY_HIT_DAMAGE, 0.1));
+    }
+
+    #[test]
+    fn wounded_bots_limp_with_matching_steps() {
+        let mut bot = Bot::default();
+        let definition = Bot::get_definition(bot.kind);
+        let threshold = bot.max_health() * definition.limp_health_fraction;
+
+        let mut limp_at = |health: f32| {
+            bot.character.health = health;
+            bot.limp_factor()
+        };
+        assert_eq!(limp_at(threshold * 2.0), 1.0);
+        assert_eq!(limp_at(threshold), 1.0);
+        assert_eq!(limp_at(0.0), definition.min_limp_speed_fraction);
+        let halfway = limp_at(threshold * 0.5);
+        assert!(halfway > definition.min_limp_speed_fraction && halfway < 1.0);
+
+        // Step signals are at fixed times of the walk animation, which `update` plays at the
+        // limp factor, while the agent walks at the agent speed.
+        let step_period = LocomotionMachine::STEP_TIMES[1] - LocomotionMachine::STEP_TIMES[0];
+        for health in [threshold * 2.0, threshold * 0.5, 0.0] {
+            bot.character.health = health;
+            let step_interval = step_period / bot.limp_factor();
+            let stride = bot.agent_speed() * step_interval;
+            assert!((stride - definition.walk_speed * step_period).abs() < 1.0e-4);
+        }
+        // Badly wounded bot walks slower with steps further apart.
+        assert!(bot.agent_speed() < definition.walk_speed);
+        assert!(step_period / bot.limp_factor() > step_period);
+    }
+
+    #[test]
+    fn walk_weights_follow_move_direction() {
+        let weights =
+            |x: f32, z: f32, has_side_walks| walk_weights(Vector3::new(x, 0.0, z), has_side_walks);
+        assert_eq!(weights(0.0, 1.0, true), [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(weights(0.0, -1.0, false), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(weights(-1.0, 0.0, true), [0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(weights(1.0, -1.0, true), [0.0, 0.0, 0.5, 0.5]);
+        // Bot without side walks steps aside with the forward walk.
+        assert_eq!(weights(1.0, -1.0, false), [0.5, 0.0, 0.0, 0.5]);
+        assert_eq!(weights(0.0, 0.0, true), [1.0, 0.0, 0.0, 0.0]);
+        for (x, z) in [(0.3, 0.7), (-0.6, -0.2), (0.5, -0.5)] {
+            for has_side_walks in [false, true] {
+                let total = weights(x, z, has_side_walks).iter().sum::<f32>();
+                assert!((total - 1.0).abs() < 1.0e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn voice_sounds_exist() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        for kind in BotKind::ALL {
+            let definition = Bot::get_definition(kind);
+            for path in definition
+                .idle_sounds
+                .iter()
+                .chain(definition.aggro_sounds)
+                .chain(definition.pain_sounds)
+                .chain(&definition.death_sound)
+            {
+                assert!(root.join(path).exists(), "{:?}: {} is missing", kind, path);
+            }
+        }
+    }
+
+    #[test]
+    fn missing_model_is_reported_instead_of_panicking() {
+        let definition = Bot::get_definition(BotKind::Mutant);
+        assert!(!Path::new(definition.model).exists());
+        let mut scene = Scene::new();
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let result = block_on(Bot::new(
+            BotKind::Mutant,
+            resource_manager(),
+            &mut scene,
+            Vector3::default(),
+            sender,
+            BotDifficulty::default(),
+            &[WeaponKind::M4],
+            &mut WeaponContainer::new(),
+        ));
+        match result {
+            Err(GameError::ResourceLoad { path, .. }) => {
+                assert_eq!(path, Path::new(definition.model))
+            }
+            Ok(_) => panic!("bot spawned without its model"),
+        }
+    }
+
+    #[test]
+    fn missing_animation_falls_back_to_idle() {
+        let resource_manager = resource_manager();
+        let definition = Bot::get_definition(BotKind::Mutant);
+        let aim = block_on(request_model(&resource_manager, definition.aim_animation));
+        assert!(aim.is_err());
+        // Idle animation is missing as well, so the fallback is reported too.
+        match block_on(or_idle(aim, &resource_manager, definition)) {
+            Err(GameError::ResourceLoad { path, .. }) => {
+                assert_eq!(path, Path::new(definition.idle_animation))
+            }
+            Ok(_) => panic!("missing idle animation loaded"),
+        }
+    }
+
+    #[test]
+    fn whip_damage_names_the_bot() {
+        let mut graph = Graph::new();
+        let mut bot = bot_at(&mut graph, Vector3::default());
+        // Body looks along Z, it stands in for the model.
+        bot.model = bot.character.body;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        bot.character.sender = Some(sender);
+        let self_handle = Handle::new(3, 1);
+        let target = Target {
+            position: Vector3::new(0.0, 0.0, 1.0),
+            handle: Handle::new(7, 1),
+            ..Default::default()
+        };
+
+        bot.whip(
+            self_handle,
+            &graph,
+            &[],
+            &SpatialGrid::default(),
+            &mut GridQuery::default(),
+            &target,
+        );
+        match receiver.try_recv() {
+            Ok(Message::DamageActor { actor, who, .. }) => {
+                assert_eq!(actor, target.handle);
+                assert_eq!(who, self_handle);
+            }
+            _ => panic!("whip didn't damage the target"),
+        }
+    }
+}
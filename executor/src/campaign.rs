@@ -0,0 +1,233 @@
+//! Level sequence of the game. Levels are listed in [`LEVEL_LIST_PATH`] in the order they're
+//! played, finishing a level unlocks the next one. Finished levels are kept in
+//! [`PROGRESS_PATH`], so unlocked levels survive restarts of the game.
+
+use crate::{
+    bot::{AiLodOptions, CORPSE_LIFETIME},
+    data_file,
+};
+use fyrox::utils::log::{Log, MessageKind};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// List of levels in the order they're played, see `LevelEntry` for the fields.
+pub const LEVEL_LIST_PATH: &str = "data/levels/levels.ron";
+pub const PROGRESS_PATH: &str = "progress.ron";
+
+#[derive(Clone, Debug)]
+pub struct LevelDefinition {
+    /// Stable identifier, it is stored in progress and saves instead of the index.
+    pub id: String,
+    pub name: String,
+    pub path: PathBuf,
+    /// Track that loops while nobody fights the player.
+    pub ambient_music: Option<PathBuf>,
+    pub combat_music: Option<PathBuf>,
+    /// Played once when a fight begins.
+    pub combat_stinger: Option<PathBuf>,
+    /// When bots far from the player are updated at reduced rate.
+    pub ai_lod: AiLodOptions,
+    /// Actors of one team can hurt each other.
+    pub friendly_fire: bool,
+    /// Dead bots stay on the level for this long, in seconds.
+    pub corpse_lifetime: f32,
+}
+
+/// Level as it is written in the level list, music is optional.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelEntry {
+    id: String,
+    name: String,
+    path: PathBuf,
+    #[serde(default)]
+    ambient_music: Option<PathBuf>,
+    #[serde(default)]
+    combat_music: Option<PathBuf>,
+    #[serde(default)]
+    combat_stinger: Option<PathBuf>,
+}
+
+impl From<LevelEntry> for LevelDefinition {
+    fn from(entry: LevelEntry) -> Self {
+        Self {
+            id: entry.id,
+            name: entry.name,
+            path: entry.path,
+            ambient_music: entry.ambient_music,
+            combat_music: entry.combat_music,
+            combat_stinger: entry.combat_stinger,
+            ai_lod: Default::default(),
+            friendly_fire: true,
+            corpse_lifetime: CORPSE_LIFETIME,
+        }
+    }
+}
+
+pub struct LevelList {
+    pub levels: Vec<LevelDefinition>,
+}
+
+impl Default for LevelList {
+    fn default() -> Self {
+        Self {
+            levels: vec![LevelDefinition {
+                id: "dm6".to_owned(),
+                name: "DM6".to_owned(),
+                path: PathBuf::from("data/levels/dm6.rgs"),
+                ambient_music: Some(PathBuf::from("data/sounds/Fantasy_Ambience.ogg")),
+                combat_music: Some(PathBuf::from("data/sounds/Antonio_Bizarro_Berzerker.ogg")),
+                combat_stinger: None,
+                ai_lod: Default::default(),
+                friendly_fire: true,
+                corpse_lifetime: CORPSE_LIFETIME,
+            }],
+        }
+    }
+}
+
+impl LevelList {
+    /// Loads the list from given file, the built-in list is used if the file is missing,
+    /// malformed or empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        match data_file::read::<Vec<LevelEntry>, _>(path.as_ref()) {
+            Ok(entries) if !entries.is_empty() => Self {
+                levels: entries.into_iter().map(LevelDefinition::from).collect(),
+            },
+            Ok(_) => Self::default(),
+            Err(e) => {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "Unable to load level list from {}, built-in list will be used. Reason: {}",
+                        path.as_ref().display(),
+                        e
+                    ),
+                );
+                Self::default()
+            }
+        }
+    }
+
+    pub fn find(&self, id: &str) -> Option<usize> {
+        self.levels.iter().position(|level| level.id == id)
+    }
+}
+
+#[derive(Default)]
+pub struct Progress {
+    completed: BTreeMap<String, String>,
+}
+
+impl Progress {
+    /// Loads progress from given file, nothing is completed if the file is missing.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        match std::fs::read_to_string(path.as_ref())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                ron::from_str::<BTreeMap<String, String>>(&text).map_err(|e| e.to_string())
+            }) {
+            Ok(completed) => Self { completed },
+            Err(e) => {
+                Log::writeln(
+                    MessageKind::Information,
+                    format!(
+                        "No progress loaded from {}, only the first level is unlocked. Reason: {}",
+                        path.as_ref().display(),
+                        e
+                    ),
+                );
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let text = ron::ser::to_string_pretty(&self.completed, Default::default())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        File::create(path)?.write_all(text.as_bytes())
+    }
+
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.completed
+            .get(id)
+            .map_or(false, |value| value == "true")
+    }
+
+    pub fn complete(&mut self, id: &str) {
+        self.completed.insert(id.to_owned(), true.to_string());
+    }
+
+    /// First level is always unlocked, every other level is unlocked by the previous one.
+    pub fn is_unlocked(&self, list: &LevelList, index: usize) -> bool {
+        index == 0
+            || list
+                .levels
+                .get(index - 1)
+                .map_or(false, |previous| self.is_completed(&previous.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("wood_{}_{}.ron", name, std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn shipped_level_list_parses() {
+        let entries: Vec<LevelEntry> =
+            data_file::parse(include_str!("../../data/levels/levels.ron")).unwrap();
+        let list = LevelList {
+            levels: entries.into_iter().map(LevelDefinition::from).collect(),
+        };
+        assert_eq!(list.find("dm6"), Some(0));
+        assert!(list.levels[0].combat_music.is_some());
+        assert_eq!(list.levels[0].combat_stinger, None);
+    }
+
+    #[test]
+    fn malformed_level_list_falls_back_to_built_in() {
+        for (name, text) in [
+            // Path is missing.
+            ("levels_no_path", r#"[(id: "a", name: "A")]"#),
+            // Unknown column.
+            (
+                "levels_unknown_field",
+                r#"[(id: "a", name: "A", path: "a.rgs", weather: "rain")]"#,
+            ),
+            ("levels_empty", "[]"),
+        ] {
+            let path = temp_file(name, text);
+            let list = LevelList::load(&path);
+            assert_eq!(list.levels.len(), 1);
+            assert_eq!(list.levels[0].id, "dm6");
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn levels_unlock_in_order() {
+        let path = temp_file(
+            "levels_order",
+            r#"[(id: "a", name: "A", path: "a.rgs"), (id: "b", name: "B", path: "b.rgs")]"#,
+        );
+        let list = LevelList::load(&path);
+        std::fs::remove_file(path).unwrap();
+
+        let mut progress = Progress::default();
+        assert!(progress.is_unlocked(&list, 0));
+        assert!(!progress.is_unlocked(&list, 1));
+        progress.complete("a");
+        assert!(progress.is_unlocked(&list, 1));
+    }
+}
@@ -0,0 +1,268 @@
+//! Closed captions of significant sounds. Sounds are captioned only if they're listed in
+//! [`CAPTIONS_PATH`], every other sound is silent for the captions. Level turns captioned
+//! sounds into `Message::ShowCaption` with a hint of where the sound came from, HUD shows
+//! the last few captions until their sounds are over.
+
+use crate::{data_file, localization::tr};
+use fyrox::{
+    core::{algebra::Vector3, color::Color, parking_lot::Mutex, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Map of sound paths to their [`CaptionDefinition`]s.
+pub const CAPTIONS_PATH: &str = "data/sounds/captions.ron";
+const MAX_LINES: usize = 3;
+/// Captions are never shorter than this, even for very short sounds, so they can be read.
+const MIN_DURATION: f32 = 1.5;
+const FONT_PATH: &str = "data/ui/SquaresBold.ttf";
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaptionDefinition {
+    pub text: String,
+    /// Rough duration of the sound, the caption is shown for that long.
+    pub duration: f32,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(transparent)]
+pub struct CaptionList {
+    captions: HashMap<PathBuf, CaptionDefinition>,
+}
+
+impl CaptionList {
+    /// Loads captions from given file, nothing is captioned if the file is missing or
+    /// malformed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        data_file::load(path, "captions")
+    }
+
+    pub fn get(&self, sound_path: &Path) -> Option<&CaptionDefinition> {
+        self.captions.get(sound_path)
+    }
+}
+
+/// Describes where the sound is relative to the listener, like "left" or "behind".
+/// `forward` is the view direction of the listener, only horizontal plane is taken into
+/// account.
+pub fn direction_hint(
+    listener: Vector3<f32>,
+    forward: Vector3<f32>,
+    source: Vector3<f32>,
+) -> String {
+    let to_source = source - listener;
+    let forward = Vector3::new(forward.x, 0.0, forward.z);
+    let right = Vector3::new(-forward.z, 0.0, forward.x);
+    // Clockwise angle, same as `Player::horizontal_angle_to`.
+    let angle = to_source
+        .dot(&right)
+        .atan2(to_source.dot(&forward))
+        .to_degrees();
+    tr(if angle.abs() <= 45.0 {
+        "caption.ahead"
+    } else if angle.abs() >= 135.0 {
+        "caption.behind"
+    } else if angle > 0.0 {
+        "caption.right"
+    } else {
+        "caption.left"
+    })
+}
+
+struct CaptionLine {
+    text: String,
+    time_left: f32,
+}
+
+pub struct CaptionArea {
+    pub root: Handle<UiNode>,
+    rows: Vec<Handle<UiNode>>,
+    texts: Vec<Handle<UiNode>>,
+    lines: VecDeque<CaptionLine>,
+    enabled: bool,
+    font_size: f32,
+    dirty: bool,
+}
+
+fn load_font(size: f32) -> Option<SharedFont> {
+    fyrox::core::futures::executor::block_on(Font::from_file(
+        Path::new(FONT_PATH),
+        size,
+        Font::default_char_set(),
+    ))
+    .ok()
+    .map(|font| SharedFont(Arc::new(Mutex::new(font))))
+}
+
+impl CaptionArea {
+    pub fn new(ctx: &mut BuildContext, font_size: f32, row: usize, column: usize) -> Self {
+        let font = load_font(font_size);
+
+        let mut rows = Vec::new();
+        let mut texts = Vec::new();
+        for _ in 0..MAX_LINES {
+            let mut text = TextBuilder::new(
+                WidgetBuilder::new()
+                    .with_margin(Thickness::uniform(4.0))
+                    .with_foreground(Brush::Solid(Color::WHITE)),
+            )
+            .with_horizontal_text_alignment(HorizontalAlignment::Center);
+            if let Some(font) = font.clone() {
+                text = text.with_font(font);
+            }
+            let text = text.build(ctx);
+            rows.push(
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_margin(Thickness::bottom(2.0))
+                        .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                        .with_child(text),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx),
+            );
+            texts.push(text);
+        }
+
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_hit_test_visibility(false)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_margin(Thickness::bottom(160.0))
+                .with_children(rows.iter().copied()),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            rows,
+            texts,
+            lines: Default::default(),
+            enabled: false,
+            font_size,
+            dirty: false,
+        }
+    }
+
+    /// Applies caption settings, hidden captions are dropped so they don't pop up later.
+    pub fn set_style(&mut self, ui: &mut UserInterface, enabled: bool, font_size: f32) {
+        if enabled != self.enabled {
+            self.enabled = enabled;
+            self.lines.clear();
+            self.dirty = true;
+        }
+
+        // Scroll bar gives fractional sizes, the font is rebuilt only on whole steps.
+        let font_size = font_size.round();
+        if font_size != self.font_size {
+            self.font_size = font_size;
+            if let Some(font) = load_font(font_size) {
+                for text in self.texts.iter() {
+                    ui.send_message(TextMessage::font(
+                        *text,
+                        MessageDirection::ToWidget,
+                        font.clone(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Adds new caption at the bottom, the oldest one is pushed out if there are too many.
+    /// Same caption that is already shown only has its time refreshed.
+    pub fn push(&mut self, text: String, duration: f32) {
+        if !self.enabled {
+            return;
+        }
+
+        let time_left = duration.max(MIN_DURATION);
+        if let Some(line) = self.lines.iter_mut().find(|line| line.text == text) {
+            line.time_left = line.time_left.max(time_left);
+            return;
+        }
+
+        if self.lines.len() == MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(CaptionLine { text, time_left });
+        self.dirty = true;
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        let count = self.lines.len();
+        for line in self.lines.iter_mut() {
+            line.time_left -= dt;
+        }
+        self.lines.retain(|line| line.time_left > 0.0);
+        if self.lines.len() != count {
+            self.dirty = true;
+        }
+
+        if self.dirty {
+            self.dirty = false;
+            for (i, (row, text)) in self.rows.iter().zip(self.texts.iter()).enumerate() {
+                let line = self.lines.get(i);
+                ui.send_message(WidgetMessage::visibility(
+                    *row,
+                    MessageDirection::ToWidget,
+                    line.is_some(),
+                ));
+                if let Some(line) = line {
+                    ui.send_message(TextMessage::text(
+                        *text,
+                        MessageDirection::ToWidget,
+                        line.text.clone(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_captions_parse() {
+        let captions: CaptionList =
+            data_file::parse(include_str!("../../data/sounds/captions.ron")).unwrap();
+        assert_eq!(
+            captions.get(Path::new("data/sounds/explosion.ogg")),
+            Some(&CaptionDefinition {
+                text: "Explosion".to_owned(),
+                duration: 2.0
+            })
+        );
+        assert_eq!(captions.get(Path::new("data/sounds/m4_shot.ogg")), None);
+    }
+
+    #[test]
+    fn malformed_captions_are_rejected() {
+        assert!(data_file::parse::<CaptionList>(r#"{ "a.ogg": (text: "A") }"#).is_err());
+        assert!(
+            data_file::parse::<CaptionList>(r#"{ "a.ogg": (text: "A", duration: "long") }"#)
+                .is_err()
+        );
+    }
+}
@@ -1,15 +1,47 @@
-use crate::{message::Message, weapon::Weapon};
+use crate::{
+    actor::Actor,
+    message::Message,
+    settings::SoundCategory,
+    visit_optional,
+    weapon::{AmmoType, Weapon, WeaponContainer},
+};
 use fyrox::{
     core::{
         algebra::Vector3,
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::{graph::Graph, node::Node, Scene},
+    scene::{collider::ColliderShape, graph::Graph, node::Node, Scene},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::mpsc::Sender,
 };
-use std::sync::mpsc::Sender;
 
-#[derive(Visit)]
+/// Nominal health and armor, health can go above the nominal value up to `MAX_OVERHEAL`.
+pub const MAX_HEALTH: f32 = 100.0;
+pub const MAX_OVERHEAL: f32 = 150.0;
+pub const MAX_ARMOR: f32 = 100.0;
+
+/// Time (in seconds) during which damage event is considered recent.
+pub const RECENT_DAMAGE_TIME: f32 = 1.5;
+const MAX_RECENT_DAMAGE_EVENTS: usize = 4;
+
+/// Steps of every character, one of them is picked at random on each step.
+pub const FOOTSTEP_SOUNDS: [&str; 4] = [
+    "data/sounds/footsteps/FootStep_shoe_stone_step1.wav",
+    "data/sounds/footsteps/FootStep_shoe_stone_step2.wav",
+    "data/sounds/footsteps/FootStep_shoe_stone_step3.wav",
+    "data/sounds/footsteps/FootStep_shoe_stone_step4.wav",
+];
+/// Whoosh of dashes and quick melee.
+pub const SWING_SOUND: &str = "data/sounds/axe-swing-1.ogg";
+
+/// Minimum time between two dashes in seconds.
+const DASH_COOLDOWN: f32 = 2.0;
+const DASH_SPEED: f32 = 10.0;
+const DASH_DURATION: f32 = 0.15;
+
 pub struct Character {
     pub name: String,
     pub body: Handle<Node>,
@@ -18,10 +50,70 @@ pub struct Character {
     pub armor: f32,
     pub weapons: Vec<Handle<Weapon>>,
     pub current_weapon: u32,
+    // Used by quick swap, not important enough to be saved.
+    pub previous_weapon: u32,
     pub weapon_pivot: Handle<Node>,
-    #[visit(skip)]
     pub sender: Option<Sender<Message>>,
     pub team: Team,
+    /// Grenades that can be thrown with quick grenade action.
+    pub grenades: u32,
+    ammo: AmmoPool,
+    // Dash is short and frequent, there is no need to save it.
+    dash_velocity: Vector3<f32>,
+    dash_time_left: f32,
+    dash_cooldown: f32,
+    recent_damage: VecDeque<DamageEvent>,
+    // Game time when the character hurt somebody last time, bots watch out for such actors.
+    last_damage_dealt_time: f64,
+    // Actor that hurt the character last time and level time of the hit.
+    last_attacker: Option<(Handle<Actor>, f32)>,
+}
+
+// Fields that saves made by older versions lack keep their defaults.
+impl Visit for Character {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.name.visit("name", visitor)?;
+        self.body.visit("body", visitor)?;
+        self.collider.visit("collider", visitor)?;
+        self.health.visit("health", visitor)?;
+        self.armor.visit("armor", visitor)?;
+        self.weapons.visit("weapons", visitor)?;
+        self.current_weapon.visit("current_weapon", visitor)?;
+        self.weapon_pivot.visit("weapon_pivot", visitor)?;
+        self.team.visit("team", visitor)?;
+        visit_optional(&mut self.grenades, "grenades", visitor)?;
+        self.ammo.visit("ammo", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Reserve ammo of a character by type, shared by all of its weapons of the type. Magazines
+/// are loaded from it.
+#[derive(Default, Debug)]
+pub struct AmmoPool(HashMap<AmmoType, u32>);
+
+impl Visit for AmmoPool {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let result = self.0.visit(name, visitor);
+        // Saves made before ammo pools keep reserve ammo in weapons, the pool stays empty and
+        // is filled from them by `Level::on_loaded`.
+        if result.is_err() && visitor.is_reading() {
+            self.0.clear();
+            return Ok(());
+        }
+        result
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DamageEvent {
+    /// Position of the damage dealer, `None` if damage has no source (falls, hazards).
+    pub source: Option<Vector3<f32>>,
+    /// Level time when damage was taken.
+    pub time: f32,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit)]
@@ -37,19 +129,36 @@ impl Default for Team {
     }
 }
 
+impl Team {
+    /// Actors of one team don't target each other, actors without a team are enemies of
+    /// everyone.
+    pub fn is_ally_of(self, other: Team) -> bool {
+        self != Team::None && self == other
+    }
+}
+
 impl Default for Character {
     fn default() -> Self {
         Self {
             name: Default::default(),
             body: Default::default(),
             collider: Default::default(),
-            health: 100.0,
-            armor: 100.0,
+            health: MAX_HEALTH,
+            armor: MAX_ARMOR,
             weapons: Vec::new(),
             current_weapon: 0,
+            previous_weapon: 0,
             weapon_pivot: Handle::NONE,
             sender: None,
             team: Team::None,
+            grenades: 3,
+            ammo: Default::default(),
+            dash_velocity: Default::default(),
+            dash_time_left: 0.0,
+            dash_cooldown: 0.0,
+            recent_damage: Default::default(),
+            last_damage_dealt_time: f64::MIN,
+            last_attacker: None,
         }
     }
 }
@@ -97,6 +206,14 @@ impl Character {
         graph[self.body].global_position()
     }
 
+    /// Horizontal radius of the body, zero for bodies that are not capsules.
+    pub fn radius(&self, graph: &Graph) -> f32 {
+        match graph[self.collider].as_collider().shape() {
+            ColliderShape::Capsule(capsule) => capsule.radius,
+            _ => 0.0,
+        }
+    }
+
     pub fn damage(&mut self, amount: f32) {
         let amount = amount.abs();
         if self.armor > 0.0 {
@@ -109,11 +226,43 @@ impl Character {
         }
     }
 
+    /// Remembers damage event, only few most recent events are kept.
+    pub fn record_damage(&mut self, source: Option<Vector3<f32>>, time: f32) {
+        self.recent_damage.push_back(DamageEvent { source, time });
+        while self.recent_damage.len() > MAX_RECENT_DAMAGE_EVENTS {
+            self.recent_damage.pop_front();
+        }
+    }
+
+    pub fn recent_damage(&self) -> impl Iterator<Item = &DamageEvent> {
+        self.recent_damage.iter()
+    }
+
+    pub fn record_damage_dealt(&mut self, time: f64) {
+        self.last_damage_dealt_time = time;
+    }
+
+    pub fn last_damage_dealt_time(&self) -> f64 {
+        self.last_damage_dealt_time
+    }
+
+    pub fn record_attacker(&mut self, attacker: Handle<Actor>, time: f32) {
+        self.last_attacker = Some((attacker, time));
+    }
+
+    /// Actor that damaged the character last time and level time when it happened.
+    pub fn last_attacker(&self) -> Option<(Handle<Actor>, f32)> {
+        self.last_attacker
+    }
+
     pub fn heal(&mut self, amount: f32) {
-        self.health += amount.abs();
+        self.heal_up_to(amount, MAX_OVERHEAL);
+    }
 
-        if self.health > 150.0 {
-            self.health = 150.0;
+    /// Heals without going over `max_health`, health that is already above it is kept.
+    pub fn heal_up_to(&mut self, amount: f32, max_health: f32) {
+        if self.health < max_health {
+            self.health = (self.health + amount.abs()).min(max_health);
         }
     }
 
@@ -121,6 +270,62 @@ impl Character {
         self.health <= 0.0
     }
 
+    pub fn can_dash(&self) -> bool {
+        self.dash_cooldown <= 0.0 && !self.is_dead()
+    }
+
+    /// Starts a dash in given direction, vertical part of the direction is ignored. Returns
+    /// false if dash is not possible right now.
+    pub fn dash(&mut self, direction: Vector3<f32>, graph: &Graph) -> bool {
+        if !self.can_dash() {
+            return false;
+        }
+        let direction =
+            match Vector3::new(direction.x, 0.0, direction.z).try_normalize(std::f32::EPSILON) {
+                Some(direction) => direction,
+                None => return false,
+            };
+
+        self.dash_velocity = direction.scale(DASH_SPEED);
+        self.dash_time_left = DASH_DURATION;
+        self.dash_cooldown = DASH_COOLDOWN;
+
+        if let Some(sender) = self.sender.as_ref() {
+            sender
+                .send(Message::PlaySound {
+                    path: SWING_SOUND.into(),
+                    position: self.position(graph),
+                    gain: 0.7,
+                    rolloff_factor: 2.0,
+                    radius: 3.0,
+                    category: SoundCategory::Sfx,
+                })
+                .unwrap();
+        }
+
+        true
+    }
+
+    /// Applies velocity of active dash to the body. Returns true while dash is active, in this
+    /// case caller must not override horizontal velocity of the body. Dash changes only
+    /// velocity, so physics still does not let the character move through walls.
+    pub fn update_dash(&mut self, graph: &mut Graph, dt: f32) -> bool {
+        self.dash_cooldown = (self.dash_cooldown - dt).max(0.0);
+        if self.dash_time_left <= 0.0 {
+            return false;
+        }
+        self.dash_time_left -= dt;
+
+        let body = graph[self.body].as_rigid_body_mut();
+        let velocity = body.lin_vel();
+        body.set_lin_vel(Vector3::new(
+            self.dash_velocity.x,
+            velocity.y,
+            self.dash_velocity.z,
+        ));
+        true
+    }
+
     pub fn weapon_pivot(&self) -> Handle<Node> {
         self.weapon_pivot
     }
@@ -129,6 +334,41 @@ impl Character {
         &self.weapons
     }
 
+    pub fn reserve_ammo(&self, ammo_type: AmmoType) -> u32 {
+        self.ammo.0.get(&ammo_type).copied().unwrap_or(0)
+    }
+
+    /// Picked up ammo goes to the reserve, it gets to magazines on reload.
+    pub fn give_ammo(&mut self, ammo_type: AmmoType, amount: u32) {
+        *self.ammo.0.entry(ammo_type).or_insert(0) += amount;
+    }
+
+    /// Takes up to `amount` of ammo from the reserve, returns how much was taken.
+    pub fn take_ammo(&mut self, ammo_type: AmmoType, amount: u32) -> u32 {
+        let reserve = self.ammo.0.entry(ammo_type).or_insert(0);
+        let taken = amount.min(*reserve);
+        *reserve -= taken;
+        taken
+    }
+
+    /// Reserve the magazine of the weapon is refilled from, melee weapons have none.
+    pub fn reserve_ammo_for(&self, weapon: &Weapon) -> u32 {
+        weapon
+            .definition()
+            .ammo_type
+            .map_or(0, |ammo_type| self.reserve_ammo(ammo_type))
+    }
+
+    /// Ammo that the weapon can fire, both in its magazine and in the reserve.
+    pub fn weapon_ammo(&self, weapon: &Weapon) -> u32 {
+        weapon.ammo_in_magazine() + self.reserve_ammo_for(weapon)
+    }
+
+    /// Melee weapons always have, they don't use ammo.
+    pub fn has_ammo_for(&self, weapon: &Weapon) -> bool {
+        weapon.is_melee() || self.weapon_ammo(weapon) > 0
+    }
+
     pub fn add_weapon(&mut self, weapon: Handle<Weapon>) {
         if let Some(sender) = self.sender.as_ref() {
             for other_weapon in self.weapons.iter() {
@@ -141,12 +381,42 @@ impl Character {
             }
         }
 
+        self.previous_weapon = self.current_weapon;
         self.current_weapon = self.weapons.len() as u32;
         self.weapons.push(weapon);
 
         self.request_current_weapon_visible(true);
     }
 
+    /// Forgets the weapon, the current weapon stays in hands unless it is the removed one.
+    pub fn remove_weapon(&mut self, weapon: Handle<Weapon>) {
+        if let Some(index) = self.weapons.iter().position(|w| *w == weapon) {
+            let was_current = index == self.current_weapon as usize;
+            self.weapons.remove(index);
+            let last = self.weapons.len().saturating_sub(1) as u32;
+            for slot in [&mut self.current_weapon, &mut self.previous_weapon] {
+                if *slot as usize > index {
+                    *slot -= 1;
+                }
+                *slot = (*slot).min(last);
+            }
+            if was_current {
+                self.request_current_weapon_visible(true);
+            }
+        }
+    }
+
+    /// Drops every weapon handle that does not point to a live weapon anymore and fixes
+    /// current weapon index. Returns amount of dropped handles.
+    pub fn validate_weapons(&mut self, weapons: &WeaponContainer) -> usize {
+        let count = self.weapons.len();
+        self.weapons.retain(|weapon| weapons.contains(*weapon));
+        if self.current_weapon as usize >= self.weapons.len() {
+            self.current_weapon = self.weapons.len().saturating_sub(1) as u32;
+        }
+        count - self.weapons.len()
+    }
+
     pub fn current_weapon(&self) -> Handle<Weapon> {
         if let Some(weapon) = self.weapons.get(self.current_weapon as usize) {
             *weapon
@@ -168,33 +438,61 @@ impl Character {
         }
     }
 
-    pub fn next_weapon(&mut self) {
-        if !self.weapons.is_empty() && (self.current_weapon as usize) < self.weapons.len() - 1 {
+    fn switch_weapon(&mut self, i: u32) {
+        if i != self.current_weapon {
             self.request_current_weapon_visible(false);
 
-            self.current_weapon += 1;
+            self.previous_weapon = self.current_weapon;
+            self.current_weapon = i;
 
             self.request_current_weapon_visible(true);
         }
     }
 
+    pub fn next_weapon(&mut self) {
+        if !self.weapons.is_empty() && (self.current_weapon as usize) < self.weapons.len() - 1 {
+            self.switch_weapon(self.current_weapon + 1);
+        }
+    }
+
     pub fn prev_weapon(&mut self) {
         if self.current_weapon > 0 {
-            self.request_current_weapon_visible(false);
-
-            self.current_weapon -= 1;
-
-            self.request_current_weapon_visible(true);
+            self.switch_weapon(self.current_weapon - 1);
         }
     }
 
     pub fn set_current_weapon(&mut self, i: usize) {
         if i < self.weapons.len() {
-            self.request_current_weapon_visible(false);
+            self.switch_weapon(i as u32);
+        }
+    }
 
-            self.current_weapon = i as u32;
+    /// Selects a weapon from given slot. If there are several weapons in the slot, every
+    /// call selects next one. Does nothing if there is no weapon in the slot.
+    pub fn select_weapon_slot(&mut self, slot: u32, weapons: &WeaponContainer) {
+        let in_slot = self
+            .weapons
+            .iter()
+            .enumerate()
+            .filter(|(_, handle)| weapons[**handle].definition().slot == slot)
+            .map(|(i, _)| i as u32)
+            .collect::<Vec<_>>();
+
+        let next = match in_slot.iter().position(|i| *i == self.current_weapon) {
+            Some(position) => in_slot[(position + 1) % in_slot.len()],
+            None => match in_slot.first() {
+                Some(first) => *first,
+                None => return,
+            },
+        };
+
+        self.switch_weapon(next);
+    }
 
-            self.request_current_weapon_visible(true);
+    /// Switches back to previously selected weapon.
+    pub fn quick_swap_weapon(&mut self) {
+        if (self.previous_weapon as usize) < self.weapons.len() {
+            self.switch_weapon(self.previous_weapon);
         }
     }
 
@@ -202,3 +500,50 @@ impl Character {
         scene.remove_node(self.body);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fyrox::core::futures::executor::block_on;
+
+    // Character as saves made before grenades and ammo pools store it.
+    #[derive(Default, Visit)]
+    struct BaselineCharacter {
+        name: String,
+        body: Handle<Node>,
+        collider: Handle<Node>,
+        health: f32,
+        armor: f32,
+        weapons: Vec<Handle<Weapon>>,
+        current_weapon: u32,
+        weapon_pivot: Handle<Node>,
+        team: Team,
+    }
+
+    #[test]
+    fn baseline_save_loads() {
+        let mut baseline = BaselineCharacter {
+            name: "Bot".to_owned(),
+            health: 40.0,
+            team: Team::Red,
+            weapons: vec![Handle::new(2, 1)],
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join(format!("wood_character_{}.bin", std::process::id()));
+        let mut visitor = Visitor::new();
+        baseline.visit("Character", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+
+        let mut character = Character::default();
+        let mut visitor = block_on(Visitor::load_binary(&path)).unwrap();
+        character.visit("Character", &mut visitor).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(character.name, "Bot");
+        assert_eq!(character.health, 40.0);
+        assert_eq!(character.team, Team::Red);
+        assert_eq!(character.weapons, baseline.weapons);
+        assert_eq!(character.grenades, Character::default().grenades);
+        assert_eq!(character.reserve_ammo(AmmoType::Rifle), 0);
+    }
+}
@@ -1,11 +1,27 @@
-use fyrox::event::VirtualKeyCode;
+use crate::{
+    gamepad::{self, GamepadAxis},
+    localization::tr,
+};
+use fyrox::{
+    event::VirtualKeyCode,
+    utils::log::{Log, MessageKind},
+};
+use ron::{extensions::Extensions, ser::PrettyConfig};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, fs::File, io::Write, path::Path, str::FromStr};
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+/// Default location of the file with user's control scheme.
+pub const CONTROL_SCHEME_PATH: &str = "controls.ron";
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum ControlButton {
     Mouse(u16),
     Key(VirtualKeyCode),
     WheelUp,
     WheelDown,
+    GamepadButton(u32),
+    /// Action has no button assigned, never matches any input.
+    Unbound,
 }
 
 impl ControlButton {
@@ -22,13 +38,224 @@ impl ControlButton {
             ControlButton::Key(code) => fyrox::utils::virtual_key_code_name(code),
             ControlButton::WheelUp => "Wheel Up",
             ControlButton::WheelDown => "Wheel Down",
+            ControlButton::GamepadButton(index) => gamepad::button_name(index),
+            ControlButton::Unbound => "Unbound",
+        }
+    }
+
+    /// Returns stable textual representation of the button which is used in config files.
+    pub fn to_config_string(self) -> String {
+        match self {
+            ControlButton::Mouse(index) => format!("Mouse({})", index),
+            ControlButton::Key(code) => format!("Key({:?})", code),
+            ControlButton::WheelUp => "WheelUp".to_owned(),
+            ControlButton::WheelDown => "WheelDown".to_owned(),
+            ControlButton::GamepadButton(index) => format!("Gamepad({})", index),
+            ControlButton::Unbound => "Unbound".to_owned(),
         }
     }
+
+    pub fn from_config_string(string: &str) -> Option<Self> {
+        match string {
+            "WheelUp" => Some(ControlButton::WheelUp),
+            "WheelDown" => Some(ControlButton::WheelDown),
+            "Unbound" => Some(ControlButton::Unbound),
+            _ => {
+                if let Some(index) = unwrap_call("Mouse", string) {
+                    index.parse().ok().map(ControlButton::Mouse)
+                } else if let Some(index) = unwrap_call("Gamepad", string) {
+                    index.parse().ok().map(ControlButton::GamepadButton)
+                } else if let Some(name) = unwrap_call("Key", string) {
+                    KEY_CODES
+                        .iter()
+                        .find(|code| format!("{:?}", code) == name)
+                        .map(|code| ControlButton::Key(*code))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+// Buttons are stored by their config strings, so names of keys stay the same in files no matter
+// how the key codes are declared.
+impl Serialize for ControlButton {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_config_string())
+    }
+}
+
+fn weapon_slot_definition(slot: u32, key: VirtualKeyCode) -> ControlButtonDefinition {
+    const IDS: [&str; 9] = [
+        "weapon_slot_1",
+        "weapon_slot_2",
+        "weapon_slot_3",
+        "weapon_slot_4",
+        "weapon_slot_5",
+        "weapon_slot_6",
+        "weapon_slot_7",
+        "weapon_slot_8",
+        "weapon_slot_9",
+    ];
+
+    const DESCRIPTIONS: [&str; 9] = [
+        "control.weapon_slot_1",
+        "control.weapon_slot_2",
+        "control.weapon_slot_3",
+        "control.weapon_slot_4",
+        "control.weapon_slot_5",
+        "control.weapon_slot_6",
+        "control.weapon_slot_7",
+        "control.weapon_slot_8",
+        "control.weapon_slot_9",
+    ];
+
+    ControlButtonDefinition {
+        id: IDS[slot as usize - 1],
+        description: DESCRIPTIONS[slot as usize - 1],
+        button: ControlButton::Key(key),
+        alt_button: None,
+        gamepad: None,
+        mode: ActivationMode::Hold,
+        can_toggle: false,
+    }
+}
+
+// Extracts `arg` from `name(arg)`.
+fn unwrap_call<'a>(name: &str, string: &'a str) -> Option<&'a str> {
+    string
+        .strip_prefix(name)
+        .and_then(|s| s.strip_prefix('('))
+        .and_then(|s| s.strip_suffix(')'))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ActivationMode {
+    /// Action is active while button is held.
+    Hold,
+    /// Every press flips active state of the action.
+    Toggle,
+}
+
+impl ActivationMode {
+    pub fn display_name(self) -> String {
+        tr(match self {
+            ActivationMode::Hold => "activation_mode.hold",
+            ActivationMode::Toggle => "activation_mode.toggle",
+        })
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum MouseCurve {
+    /// Mouse motion is used as is.
+    Raw,
+    /// Motion is averaged with previous frames, removes jitter at cost of small latency.
+    Smoothed,
+    /// Fast motion turns camera more than slow one, see `mouse_curve_exponent`.
+    Exponent,
+}
+
+impl MouseCurve {
+    pub const ALL: [MouseCurve; 3] = [MouseCurve::Raw, MouseCurve::Smoothed, MouseCurve::Exponent];
+
+    pub fn display_name(self) -> String {
+        tr(match self {
+            MouseCurve::Raw => "mouse_curve.raw",
+            MouseCurve::Smoothed => "mouse_curve.smoothed",
+            MouseCurve::Exponent => "mouse_curve.exponent",
+        })
+    }
 }
 
 pub struct ControlButtonDefinition {
-    pub description: String,
+    /// Stable name of the action used in config files.
+    pub id: &'static str,
+    /// Localization key of the name shown in the options.
+    pub description: &'static str,
     pub button: ControlButton,
+    /// Secondary keyboard or mouse binding, for example arrow keys for movement.
+    pub alt_button: Option<ControlButton>,
+    /// Separate binding for gamepads, so both devices can be used at the same time.
+    pub gamepad: Option<ControlButton>,
+    pub mode: ActivationMode,
+    /// Whether the action supports toggle mode at all.
+    pub can_toggle: bool,
+}
+
+impl ControlButtonDefinition {
+    pub fn matches(&self, button: ControlButton) -> bool {
+        button != ControlButton::Unbound
+            && (self.button == button
+                || self.alt_button == Some(button)
+                || self.gamepad == Some(button))
+    }
+
+    /// Returns every button bound to the action: primary, alternate and gamepad ones.
+    pub fn bindings(&self) -> impl Iterator<Item = ControlButton> {
+        std::iter::once(self.button)
+            .chain(self.alt_button)
+            .chain(self.gamepad)
+            .filter(|button| *button != ControlButton::Unbound)
+    }
+
+    fn conflicts_with(&self, other: &ControlButtonDefinition) -> bool {
+        self.bindings().any(|button| other.matches(button))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadSettings {
+    pub move_x: GamepadAxis,
+    pub move_y: GamepadAxis,
+    pub look_x: GamepadAxis,
+    pub look_y: GamepadAxis,
+    pub shoot: GamepadAxis,
+    /// Stick values below this threshold are ignored.
+    pub deadzone: f32,
+    /// Exponent of look response curve, values greater than 1 give more precision near center.
+    pub look_exponent: f32,
+    /// Look speed in degrees per second at full stick deflection.
+    pub look_sens: f32,
+    /// Trigger value at which shoot action is activated.
+    pub trigger_threshold: f32,
+    pub aim_assist: bool,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            move_x: GamepadAxis::LeftStickX,
+            move_y: GamepadAxis::LeftStickY,
+            look_x: GamepadAxis::RightStickX,
+            look_y: GamepadAxis::RightStickY,
+            shoot: GamepadAxis::RightTrigger,
+            deadzone: 0.15,
+            look_exponent: 2.0,
+            look_sens: 180.0,
+            trigger_threshold: 0.5,
+            aim_assist: true,
+        }
+    }
+}
+
+impl GamepadSettings {
+    /// Removes deadzone and rescales the rest of the range back to [-1; 1].
+    pub fn apply_deadzone(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.deadzone {
+            0.0
+        } else {
+            value.signum() * ((magnitude - self.deadzone) / (1.0 - self.deadzone)).min(1.0)
+        }
+    }
+
+    pub fn apply_look_curve(&self, value: f32) -> f32 {
+        let value = self.apply_deadzone(value);
+        value.signum() * value.abs().powf(self.look_exponent)
+    }
 }
 
 pub struct ControlScheme {
@@ -39,68 +266,257 @@ pub struct ControlScheme {
     pub jump: ControlButtonDefinition,
     pub crouch: ControlButtonDefinition,
     pub shoot: ControlButtonDefinition,
+    pub aim: ControlButtonDefinition,
     pub next_weapon: ControlButtonDefinition,
     pub prev_weapon: ControlButtonDefinition,
     pub run: ControlButtonDefinition,
-    pub mouse_sens: f32,
+    pub weapon_slot_1: ControlButtonDefinition,
+    pub weapon_slot_2: ControlButtonDefinition,
+    pub weapon_slot_3: ControlButtonDefinition,
+    pub weapon_slot_4: ControlButtonDefinition,
+    pub weapon_slot_5: ControlButtonDefinition,
+    pub weapon_slot_6: ControlButtonDefinition,
+    pub weapon_slot_7: ControlButtonDefinition,
+    pub weapon_slot_8: ControlButtonDefinition,
+    pub weapon_slot_9: ControlButtonDefinition,
+    pub quick_swap: ControlButtonDefinition,
+    /// Shows the weapon wheel while held, a quick tap swaps to the last weapon.
+    pub weapon_wheel: ControlButtonDefinition,
+    pub interact: ControlButtonDefinition,
+    pub dash: ControlButtonDefinition,
+    pub quick_grenade: ControlButtonDefinition,
+    pub quick_melee: ControlButtonDefinition,
+    pub toggle_laser: ControlButtonDefinition,
+    pub show_scores: ControlButtonDefinition,
+    pub mouse_sens_x: f32,
+    pub mouse_sens_y: f32,
+    pub mouse_curve: MouseCurve,
+    /// Exponent of `MouseCurve::Exponent`, 1.0 gives the same response as raw input.
+    pub mouse_curve_exponent: f32,
     pub mouse_y_inverse: bool,
     pub smooth_mouse: bool,
     pub shake_camera: bool,
+    /// Items are picked up only with the "use" key instead of walking over them.
+    pub manual_pickup: bool,
+    /// Dash is also triggered by double tap of any movement key.
+    pub double_tap_dash: bool,
+    /// Maximum time between two taps in seconds.
+    pub double_tap_time: f32,
+    pub show_crosshair: bool,
+    /// Hit markers on the crosshair when damage dealt by the player lands.
+    pub show_hit_markers: bool,
+    /// Arcs around the crosshair pointing to the sources of incoming damage.
+    pub show_damage_indicators: bool,
+    /// Floating numbers above actors damaged by the player.
+    pub show_damage_numbers: bool,
+    pub gamepad: GamepadSettings,
 }
 
 impl Default for ControlScheme {
     fn default() -> Self {
         Self {
             move_forward: ControlButtonDefinition {
-                description: "Move Forward".to_string(),
+                id: "move_forward",
+                description: "control.move_forward",
                 button: ControlButton::Key(VirtualKeyCode::W),
+                alt_button: Some(ControlButton::Key(VirtualKeyCode::Up)),
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             move_backward: ControlButtonDefinition {
-                description: "Move Backward".to_string(),
+                id: "move_backward",
+                description: "control.move_backward",
                 button: ControlButton::Key(VirtualKeyCode::S),
+                alt_button: Some(ControlButton::Key(VirtualKeyCode::Down)),
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             move_left: ControlButtonDefinition {
-                description: "Move Left".to_string(),
+                id: "move_left",
+                description: "control.move_left",
                 button: ControlButton::Key(VirtualKeyCode::A),
+                alt_button: Some(ControlButton::Key(VirtualKeyCode::Left)),
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             move_right: ControlButtonDefinition {
-                description: "Move Right".to_string(),
+                id: "move_right",
+                description: "control.move_right",
                 button: ControlButton::Key(VirtualKeyCode::D),
+                alt_button: Some(ControlButton::Key(VirtualKeyCode::Right)),
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             jump: ControlButtonDefinition {
-                description: "Jump".to_string(),
+                id: "jump",
+                description: "control.jump",
                 button: ControlButton::Key(VirtualKeyCode::Space),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_SOUTH)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             crouch: ControlButtonDefinition {
-                description: "Crouch".to_string(),
+                id: "crouch",
+                description: "control.crouch",
                 button: ControlButton::Key(VirtualKeyCode::C),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_EAST)),
+                mode: ActivationMode::Hold,
+                can_toggle: true,
             },
             shoot: ControlButtonDefinition {
-                description: "Shoot".to_string(),
+                id: "shoot",
+                description: "control.shoot",
                 button: ControlButton::Mouse(1),
+                alt_button: None,
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            aim: ControlButtonDefinition {
+                id: "aim",
+                description: "control.aim",
+                button: ControlButton::Mouse(2),
+                alt_button: None,
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: true,
             },
             next_weapon: ControlButtonDefinition {
-                description: "Next Weapon".to_string(),
+                id: "next_weapon",
+                description: "control.next_weapon",
                 button: ControlButton::WheelUp,
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_RIGHT_BUMPER)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             prev_weapon: ControlButtonDefinition {
-                description: "Previous Weapon".to_string(),
+                id: "prev_weapon",
+                description: "control.previous_weapon",
                 button: ControlButton::WheelDown,
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_LEFT_BUMPER)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
             run: ControlButtonDefinition {
-                description: "Run".to_string(),
+                id: "run",
+                description: "control.run",
                 button: ControlButton::Key(VirtualKeyCode::LShift),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_LEFT_THUMB)),
+                mode: ActivationMode::Hold,
+                can_toggle: true,
+            },
+            weapon_slot_1: weapon_slot_definition(1, VirtualKeyCode::Key1),
+            weapon_slot_2: weapon_slot_definition(2, VirtualKeyCode::Key2),
+            weapon_slot_3: weapon_slot_definition(3, VirtualKeyCode::Key3),
+            weapon_slot_4: weapon_slot_definition(4, VirtualKeyCode::Key4),
+            weapon_slot_5: weapon_slot_definition(5, VirtualKeyCode::Key5),
+            weapon_slot_6: weapon_slot_definition(6, VirtualKeyCode::Key6),
+            weapon_slot_7: weapon_slot_definition(7, VirtualKeyCode::Key7),
+            weapon_slot_8: weapon_slot_definition(8, VirtualKeyCode::Key8),
+            weapon_slot_9: weapon_slot_definition(9, VirtualKeyCode::Key9),
+            quick_swap: ControlButtonDefinition {
+                id: "quick_swap",
+                description: "control.last_weapon",
+                // Tap of the weapon wheel key does the same.
+                button: ControlButton::Unbound,
+                alt_button: None,
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
             },
-            mouse_sens: 0.3,
+            weapon_wheel: ControlButtonDefinition {
+                id: "weapon_wheel",
+                description: "control.weapon_wheel",
+                button: ControlButton::Key(VirtualKeyCode::Q),
+                alt_button: Some(ControlButton::Mouse(3)),
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            interact: ControlButtonDefinition {
+                id: "use",
+                description: "control.use",
+                button: ControlButton::Key(VirtualKeyCode::E),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_WEST)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            dash: ControlButtonDefinition {
+                id: "dash",
+                description: "control.dash",
+                button: ControlButton::Key(VirtualKeyCode::LAlt),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_RIGHT_THUMB)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            quick_grenade: ControlButtonDefinition {
+                id: "quick_grenade",
+                description: "control.throw_grenade",
+                button: ControlButton::Key(VirtualKeyCode::G),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_DPAD_UP)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            quick_melee: ControlButtonDefinition {
+                id: "quick_melee",
+                description: "control.melee_bash",
+                button: ControlButton::Key(VirtualKeyCode::V),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_NORTH)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            toggle_laser: ControlButtonDefinition {
+                id: "toggle_laser",
+                description: "control.toggle_laser",
+                button: ControlButton::Key(VirtualKeyCode::L),
+                alt_button: None,
+                gamepad: None,
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            show_scores: ControlButtonDefinition {
+                id: "show_scores",
+                description: "control.show_scores",
+                button: ControlButton::Key(VirtualKeyCode::Tab),
+                alt_button: None,
+                gamepad: Some(ControlButton::GamepadButton(gamepad::BUTTON_SELECT)),
+                mode: ActivationMode::Hold,
+                can_toggle: false,
+            },
+            mouse_sens_x: 0.3,
+            mouse_sens_y: 0.3,
+            mouse_curve: MouseCurve::Raw,
+            mouse_curve_exponent: 1.5,
             mouse_y_inverse: false,
             smooth_mouse: false,
             shake_camera: true,
+            manual_pickup: false,
+            double_tap_dash: true,
+            double_tap_time: 0.25,
+            show_crosshair: true,
+            show_hit_markers: true,
+            show_damage_indicators: true,
+            show_damage_numbers: false,
+            gamepad: Default::default(),
         }
     }
 }
 
 impl ControlScheme {
-    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 10] {
+    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 28] {
         [
             &mut self.move_forward,
             &mut self.move_backward,
@@ -109,13 +525,31 @@ impl ControlScheme {
             &mut self.jump,
             &mut self.crouch,
             &mut self.shoot,
+            &mut self.aim,
             &mut self.next_weapon,
             &mut self.prev_weapon,
             &mut self.run,
+            &mut self.weapon_slot_1,
+            &mut self.weapon_slot_2,
+            &mut self.weapon_slot_3,
+            &mut self.weapon_slot_4,
+            &mut self.weapon_slot_5,
+            &mut self.weapon_slot_6,
+            &mut self.weapon_slot_7,
+            &mut self.weapon_slot_8,
+            &mut self.weapon_slot_9,
+            &mut self.quick_swap,
+            &mut self.weapon_wheel,
+            &mut self.interact,
+            &mut self.dash,
+            &mut self.quick_grenade,
+            &mut self.quick_melee,
+            &mut self.toggle_laser,
+            &mut self.show_scores,
         ]
     }
 
-    pub fn buttons(&self) -> [&ControlButtonDefinition; 10] {
+    pub fn buttons(&self) -> [&ControlButtonDefinition; 28] {
         [
             &self.move_forward,
             &self.move_backward,
@@ -124,13 +558,548 @@ impl ControlScheme {
             &self.jump,
             &self.crouch,
             &self.shoot,
+            &self.aim,
             &self.next_weapon,
             &self.prev_weapon,
             &self.run,
+            &self.weapon_slot_1,
+            &self.weapon_slot_2,
+            &self.weapon_slot_3,
+            &self.weapon_slot_4,
+            &self.weapon_slot_5,
+            &self.weapon_slot_6,
+            &self.weapon_slot_7,
+            &self.weapon_slot_8,
+            &self.weapon_slot_9,
+            &self.quick_swap,
+            &self.weapon_wheel,
+            &self.interact,
+            &self.dash,
+            &self.quick_grenade,
+            &self.quick_melee,
+            &self.toggle_laser,
+            &self.show_scores,
         ]
     }
 
+    pub fn weapon_slots(&self) -> [&ControlButtonDefinition; 9] {
+        [
+            &self.weapon_slot_1,
+            &self.weapon_slot_2,
+            &self.weapon_slot_3,
+            &self.weapon_slot_4,
+            &self.weapon_slot_5,
+            &self.weapon_slot_6,
+            &self.weapon_slot_7,
+            &self.weapon_slot_8,
+            &self.weapon_slot_9,
+        ]
+    }
+
+    /// Returns pairs of indices (in `buttons()` order) of actions that share a button.
+    pub fn find_conflicts(&self) -> Vec<(usize, usize)> {
+        let buttons = self.buttons();
+        let mut conflicts = Vec::new();
+        for (i, a) in buttons.iter().enumerate() {
+            for (j, b) in buttons.iter().enumerate().skip(i + 1) {
+                if a.conflicts_with(b) {
+                    conflicts.push((i, j));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Unbinds every other action that shares a button with the action at given index, so
+    /// the most recent assignment wins. Returns indices of unbound actions.
+    pub fn resolve_conflicts(&mut self, index: usize) -> Vec<usize> {
+        let mut unbound = Vec::new();
+        for (a, b) in self.find_conflicts() {
+            let other = if a == index {
+                b
+            } else if b == index {
+                a
+            } else {
+                continue;
+            };
+
+            let bindings = self.buttons()[index].bindings().collect::<Vec<_>>();
+            let other_definition = &mut self.buttons_mut()[other];
+            if bindings.contains(&other_definition.button) {
+                other_definition.button = ControlButton::Unbound;
+            }
+            if let Some(alt_button) = other_definition.alt_button {
+                if bindings.contains(&alt_button) {
+                    other_definition.alt_button = None;
+                }
+            }
+            if let Some(gamepad) = other_definition.gamepad {
+                if bindings.contains(&gamepad) {
+                    other_definition.gamepad = None;
+                }
+            }
+            unbound.push(other);
+        }
+        unbound
+    }
+
+    /// Returns sensitivity shown by the simple slider, it is an average of both axes.
+    pub fn mouse_sens(&self) -> f32 {
+        (self.mouse_sens_x + self.mouse_sens_y) * 0.5
+    }
+
+    /// Sets the same sensitivity for both axes.
+    pub fn set_mouse_sens(&mut self, sens: f32) {
+        self.mouse_sens_x = sens;
+        self.mouse_sens_y = sens;
+    }
+
     pub fn reset(&mut self) {
         *self = Default::default();
     }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        // Options are written without `Some`, so the file reads the same as it's written.
+        let pretty = PrettyConfig::default().extensions(Extensions::IMPLICIT_SOME);
+        let text = ron::ser::to_string_pretty(&ControlSchemeConfig::new(self), pretty)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        File::create(path)?.write_all(text.as_bytes())
+    }
+
+    /// Loads control scheme from given file. Every missing entry and every unknown button
+    /// falls back to its default value, a file that can't be parsed at all gives the default
+    /// scheme, so this method never fails.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let mut scheme = Self::default();
+        match std::fs::read_to_string(path.as_ref())
+            .map_err(|e| e.to_string())
+            .and_then(|text| ron::from_str::<ControlSchemeConfig>(&text).map_err(|e| e.to_string()))
+        {
+            Ok(config) => config.apply(&mut scheme),
+            Err(e) => Log::writeln(
+                MessageKind::Warning,
+                format!(
+                    "Unable to load control scheme from {}, defaults will be used. Reason: {}",
+                    path.as_ref().display(),
+                    e
+                ),
+            ),
+        }
+        scheme
+    }
+}
+
+/// Bindings of an action as they're stored in the control scheme file.
+#[derive(Default, Serialize, Deserialize)]
+struct BindingConfig {
+    /// `None` if the button is unknown, the action keeps its default one then.
+    #[serde(default, deserialize_with = "lenient_button")]
+    button: Option<ControlButton>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_button",
+        skip_serializing_if = "Option::is_none"
+    )]
+    alt: Option<ControlButton>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_button",
+        skip_serializing_if = "Option::is_none"
+    )]
+    gamepad: Option<ControlButton>,
+    /// Only stored for actions that can be toggled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<ActivationMode>,
+}
+
+/// Layout of the control scheme file. Missing fields take values of the default scheme.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct ControlSchemeConfig {
+    /// Bindings by the ids of their actions.
+    bindings: BTreeMap<String, BindingConfig>,
+    mouse_sens_x: f32,
+    mouse_sens_y: f32,
+    mouse_curve: MouseCurve,
+    mouse_curve_exponent: f32,
+    mouse_y_inverse: bool,
+    smooth_mouse: bool,
+    shake_camera: bool,
+    manual_pickup: bool,
+    double_tap_dash: bool,
+    double_tap_time: f32,
+    show_crosshair: bool,
+    show_hit_markers: bool,
+    show_damage_indicators: bool,
+    show_damage_numbers: bool,
+    gamepad: GamepadSettings,
+}
+
+impl Default for ControlSchemeConfig {
+    fn default() -> Self {
+        Self::new(&ControlScheme::default())
+    }
+}
+
+impl ControlSchemeConfig {
+    fn new(scheme: &ControlScheme) -> Self {
+        Self {
+            bindings: scheme
+                .buttons()
+                .iter()
+                .map(|definition| {
+                    let binding = BindingConfig {
+                        button: Some(definition.button),
+                        alt: definition.alt_button,
+                        gamepad: definition.gamepad,
+                        mode: Some(definition.mode).filter(|_| definition.can_toggle),
+                    };
+                    (definition.id.to_owned(), binding)
+                })
+                .collect(),
+            mouse_sens_x: scheme.mouse_sens_x,
+            mouse_sens_y: scheme.mouse_sens_y,
+            mouse_curve: scheme.mouse_curve,
+            mouse_curve_exponent: scheme.mouse_curve_exponent,
+            mouse_y_inverse: scheme.mouse_y_inverse,
+            smooth_mouse: scheme.smooth_mouse,
+            shake_camera: scheme.shake_camera,
+            manual_pickup: scheme.manual_pickup,
+            double_tap_dash: scheme.double_tap_dash,
+            double_tap_time: scheme.double_tap_time,
+            show_crosshair: scheme.show_crosshair,
+            show_hit_markers: scheme.show_hit_markers,
+            show_damage_indicators: scheme.show_damage_indicators,
+            show_damage_numbers: scheme.show_damage_numbers,
+            gamepad: scheme.gamepad.clone(),
+        }
+    }
+
+    fn apply(self, scheme: &mut ControlScheme) {
+        for definition in scheme.buttons_mut() {
+            let binding = match self.bindings.get(definition.id) {
+                Some(binding) => binding,
+                None => continue,
+            };
+            if let Some(button) = binding.button {
+                definition.button = button;
+            }
+            definition.alt_button = binding.alt;
+            definition.gamepad = binding.gamepad;
+            match binding.mode {
+                Some(ActivationMode::Toggle) if !definition.can_toggle => Log::writeln(
+                    MessageKind::Warning,
+                    format!("{} can't be toggled, ignored.", definition.id),
+                ),
+                Some(mode) => definition.mode = mode,
+                None => (),
+            }
+        }
+        scheme.mouse_sens_x = self.mouse_sens_x;
+        scheme.mouse_sens_y = self.mouse_sens_y;
+        scheme.mouse_curve = self.mouse_curve;
+        scheme.mouse_curve_exponent = self.mouse_curve_exponent;
+        scheme.mouse_y_inverse = self.mouse_y_inverse;
+        scheme.smooth_mouse = self.smooth_mouse;
+        scheme.shake_camera = self.shake_camera;
+        scheme.manual_pickup = self.manual_pickup;
+        scheme.double_tap_dash = self.double_tap_dash;
+        scheme.double_tap_time = self.double_tap_time;
+        scheme.show_crosshair = self.show_crosshair;
+        scheme.show_hit_markers = self.show_hit_markers;
+        scheme.show_damage_indicators = self.show_damage_indicators;
+        scheme.show_damage_numbers = self.show_damage_numbers;
+        scheme.gamepad = self.gamepad;
+    }
+}
+
+// Unknown button is reported and read as `None` instead of failing the whole file, so one
+// bad binding doesn't reset every other.
+fn lenient_button<'de, D>(deserializer: D) -> Result<Option<ControlButton>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(
+        Option::<String>::deserialize(deserializer)?.and_then(|string| {
+            let button = ControlButton::from_config_string(&string);
+            if button.is_none() {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Invalid binding {}, ignored.", string),
+                );
+            }
+            button
+        }),
+    )
+}
+
+/// Parses value of given entry of a config file, malformed values are reported and ignored.
+pub fn parse_config_value<T: FromStr>(config: &BTreeMap<String, String>, id: &str, value: &mut T) {
+    if let Some(string) = config.get(id) {
+        match string.parse() {
+            Ok(parsed) => *value = parsed,
+            Err(_) => Log::writeln(
+                MessageKind::Warning,
+                format!("Invalid value {} for {}, ignored.", string, id),
+            ),
+        }
+    }
+}
+
+// Every key that can be bound, used to parse key names back from config files.
+#[rustfmt::skip]
+static KEY_CODES: &[VirtualKeyCode] = {
+    use VirtualKeyCode::*;
+    &[
+        Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J,
+        K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8,
+        F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, Snapshot,
+        Scroll, Pause, Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back,
+        Return, Space, Compose, Caret, Numlock, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4,
+        Numpad5, Numpad6, Numpad7, Numpad8, Numpad9, NumpadAdd, NumpadDivide, NumpadDecimal,
+        NumpadComma, NumpadEnter, NumpadEquals, NumpadMultiply, NumpadSubtract, AbntC1, AbntC2,
+        Apostrophe, Apps, Asterisk, At, Ax, Backslash, Calculator, Capital, Colon, Comma, Convert,
+        Equals, Grave, Kana, Kanji, LAlt, LBracket, LControl, LShift, LWin, Mail, MediaSelect,
+        MediaStop, Minus, Mute, MyComputer, NavigateForward, NavigateBackward, NextTrack,
+        NoConvert, OEM102, Period, PlayPause, Plus, Power, PrevTrack, RAlt, RBracket, RControl,
+        RShift, RWin, Semicolon, Slash, Sleep, Stop, Sysrq, Tab, Underline, Unlabeled, VolumeDown,
+        VolumeUp, Wake, WebBack, WebFavorites, WebForward, WebHome, WebRefresh, WebSearch,
+        WebStop, Yen, Copy, Paste, Cut,
+    ]
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("wood_{}_{}.ron", name, std::process::id()))
+    }
+
+    fn assert_same_bindings(a: &ControlScheme, b: &ControlScheme) {
+        for (a, b) in a.buttons().iter().zip(b.buttons().iter()) {
+            assert_eq!(a.button, b.button, "{}", a.id);
+            assert_eq!(a.alt_button, b.alt_button, "{}", a.id);
+            assert_eq!(a.gamepad, b.gamepad, "{}", a.id);
+            assert_eq!(a.mode, b.mode, "{}", a.id);
+        }
+    }
+
+    #[test]
+    fn modified_scheme_round_trips() {
+        let mut scheme = ControlScheme::default();
+        scheme.move_forward.button = ControlButton::Key(VirtualKeyCode::I);
+        scheme.move_forward.alt_button = None;
+        scheme.jump.button = ControlButton::Mouse(4);
+        scheme.jump.gamepad = Some(ControlButton::GamepadButton(gamepad::BUTTON_NORTH));
+        scheme.shoot.alt_button = Some(ControlButton::WheelDown);
+        scheme.next_weapon.button = ControlButton::Unbound;
+        scheme.crouch.mode = ActivationMode::Toggle;
+        scheme.run.gamepad = None;
+        scheme.weapon_slot_9.button = ControlButton::Key(VirtualKeyCode::NumpadEnter);
+        scheme.mouse_sens_x = 0.7;
+        scheme.mouse_sens_y = 0.45;
+        scheme.mouse_curve = MouseCurve::Exponent;
+        scheme.mouse_y_inverse = true;
+        scheme.smooth_mouse = true;
+        scheme.shake_camera = false;
+        scheme.show_damage_numbers = true;
+        scheme.gamepad.look_y = GamepadAxis::LeftStickY;
+        scheme.gamepad.deadzone = 0.3;
+        scheme.gamepad.aim_assist = false;
+
+        let path = temp_path("controls_round_trip");
+        scheme.save(&path).unwrap();
+        let loaded = ControlScheme::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_same_bindings(&scheme, &loaded);
+        assert_eq!(loaded.mouse_sens_x, 0.7);
+        assert_eq!(loaded.mouse_sens_y, 0.45);
+        assert_eq!(loaded.mouse_curve, MouseCurve::Exponent);
+        assert!(loaded.mouse_y_inverse);
+        assert!(loaded.smooth_mouse);
+        assert!(!loaded.shake_camera);
+        assert!(loaded.show_damage_numbers);
+        assert_eq!(loaded.gamepad.look_y, GamepadAxis::LeftStickY);
+        assert_eq!(loaded.gamepad.deadzone, 0.3);
+        assert!(!loaded.gamepad.aim_assist);
+    }
+
+    #[test]
+    fn missing_and_unknown_entries_fall_back_to_defaults() {
+        let path = temp_path("controls_fallback");
+        std::fs::write(
+            &path,
+            r#"(
+                bindings: {
+                    "jump": (button: Some("Key(J)")),
+                    "shoot": (button: Some("Key(NoSuchKey)")),
+                    "run": (button: Some("Key(LShift)"), mode: Some(Toggle)),
+                    "use": (button: Some("Key(F)"), mode: Some(Toggle)),
+                },
+                mouse_y_inverse: true,
+            )"#,
+        )
+        .unwrap();
+        let loaded = ControlScheme::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let default = ControlScheme::default();
+        assert_eq!(loaded.jump.button, ControlButton::Key(VirtualKeyCode::J));
+        assert_eq!(loaded.shoot.button, default.shoot.button);
+        assert_eq!(loaded.run.mode, ActivationMode::Toggle);
+        // Use can't be toggled.
+        assert_eq!(loaded.interact.mode, ActivationMode::Hold);
+        assert_eq!(loaded.move_forward.button, default.move_forward.button);
+        assert_eq!(
+            loaded.move_forward.alt_button,
+            default.move_forward.alt_button
+        );
+        assert!(loaded.mouse_y_inverse);
+        assert_eq!(loaded.mouse_sens_x, default.mouse_sens_x);
+    }
+
+    #[test]
+    fn malformed_file_gives_defaults() {
+        let path = temp_path("controls_malformed");
+        std::fs::write(&path, "(bindings: {").unwrap();
+        let loaded = ControlScheme::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_same_bindings(&loaded, &ControlScheme::default());
+
+        let missing = ControlScheme::load(temp_path("controls_missing"));
+        assert_same_bindings(&missing, &ControlScheme::default());
+    }
+
+    #[test]
+    fn every_key_code_round_trips_through_config_string() {
+        for &code in KEY_CODES {
+            let button = ControlButton::Key(code);
+            assert_eq!(
+                ControlButton::from_config_string(&button.to_config_string()),
+                Some(button)
+            );
+        }
+        for button in [
+            ControlButton::Mouse(5),
+            ControlButton::WheelUp,
+            ControlButton::WheelDown,
+            ControlButton::GamepadButton(3),
+            ControlButton::Unbound,
+        ] {
+            assert_eq!(
+                ControlButton::from_config_string(&button.to_config_string()),
+                Some(button)
+            );
+        }
+    }
+
+    #[test]
+    fn default_scheme_has_no_conflicts() {
+        assert!(ControlScheme::default().find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn conflicts_are_found_between_every_pair_of_actions() {
+        let count = ControlScheme::default().buttons().len();
+        for i in 0..count {
+            for j in (i + 1)..count {
+                let mut scheme = ControlScheme::default();
+                let button = scheme.buttons()[i].button;
+                // Quick swap has no default button, its conflicts come from the other side.
+                let button = if button == ControlButton::Unbound {
+                    let button = scheme.buttons()[j].button;
+                    scheme.buttons_mut()[i].button = button;
+                    button
+                } else {
+                    scheme.buttons_mut()[j].button = button;
+                    button
+                };
+                if button == ControlButton::Unbound {
+                    continue;
+                }
+                assert!(
+                    scheme.find_conflicts().contains(&(i, j)),
+                    "{} and {} share {}",
+                    scheme.buttons()[i].id,
+                    scheme.buttons()[j].id,
+                    button.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn alternate_and_gamepad_bindings_conflict() {
+        let mut scheme = ControlScheme::default();
+        scheme.jump.alt_button = scheme.move_forward.alt_button;
+        scheme.crouch.gamepad = scheme.jump.gamepad;
+        let buttons = scheme.buttons();
+        let index = |id: &str| buttons.iter().position(|b| b.id == id).unwrap();
+        let conflicts = scheme.find_conflicts();
+        assert!(conflicts.contains(&(index("move_forward"), index("jump"))));
+        assert!(conflicts.contains(&(index("jump"), index("crouch"))));
+        assert_eq!(conflicts.len(), 2);
+    }
+
+    #[test]
+    fn unbound_actions_never_conflict() {
+        let mut scheme = ControlScheme::default();
+        scheme.jump.button = ControlButton::Unbound;
+        scheme.crouch.button = ControlButton::Unbound;
+        scheme.crouch.gamepad = None;
+        scheme.jump.gamepad = None;
+        assert!(scheme.find_conflicts().is_empty());
+        assert!(!scheme.jump.matches(ControlButton::Unbound));
+    }
+
+    #[test]
+    fn rebinding_unbinds_the_other_action() {
+        let mut scheme = ControlScheme::default();
+        let shoot = scheme
+            .buttons()
+            .iter()
+            .position(|b| b.id == "shoot")
+            .unwrap();
+        let jump = scheme
+            .buttons()
+            .iter()
+            .position(|b| b.id == "jump")
+            .unwrap();
+        scheme.shoot.button = ControlButton::Key(VirtualKeyCode::Space);
+        scheme.shoot.gamepad = scheme.jump.gamepad;
+
+        assert_eq!(scheme.resolve_conflicts(shoot), vec![jump]);
+        assert_eq!(scheme.jump.button, ControlButton::Unbound);
+        assert_eq!(scheme.jump.gamepad, None);
+        assert_eq!(
+            scheme.shoot.button,
+            ControlButton::Key(VirtualKeyCode::Space)
+        );
+        assert!(scheme.find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn rebinding_keeps_unrelated_bindings_of_the_other_action() {
+        let mut scheme = ControlScheme::default();
+        let crouch = scheme
+            .buttons()
+            .iter()
+            .position(|b| b.id == "crouch")
+            .unwrap();
+        // Only the alternate button of forward movement is taken.
+        scheme.crouch.alt_button = scheme.move_forward.alt_button;
+        scheme.resolve_conflicts(crouch);
+        assert_eq!(
+            scheme.move_forward.button,
+            ControlButton::Key(VirtualKeyCode::W)
+        );
+        assert_eq!(scheme.move_forward.alt_button, None);
+        assert_eq!(
+            scheme.crouch.alt_button,
+            Some(ControlButton::Key(VirtualKeyCode::Up))
+        );
+    }
 }
@@ -0,0 +1,212 @@
+//! Crosshair of the HUD. It consists of four lines around the center of the screen, distance
+//! between lines and the center grows with the spread of current weapon. Hit markers are
+//! shown in the center when damage dealt by the player lands.
+
+use crate::weapon::CrosshairStyle;
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+const LINE_THICKNESS: f32 = 2.0;
+const HIT_MARKER_DURATION: f32 = 0.15;
+const KILL_MARKER_DURATION: f32 = 0.4;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HitMarkerKind {
+    Hit,
+    Headshot,
+    Kill,
+}
+
+impl HitMarkerKind {
+    fn color(self) -> Color {
+        match self {
+            HitMarkerKind::Hit => Color::WHITE,
+            HitMarkerKind::Headshot => Color::opaque(255, 215, 0),
+            HitMarkerKind::Kill => Color::opaque(220, 0, 0),
+        }
+    }
+
+    fn duration(self) -> f32 {
+        match self {
+            HitMarkerKind::Hit | HitMarkerKind::Headshot => HIT_MARKER_DURATION,
+            HitMarkerKind::Kill => KILL_MARKER_DURATION,
+        }
+    }
+}
+
+// Lines in order: top, bottom, left, right.
+pub struct Crosshair {
+    pub root: Handle<UiNode>,
+    lines_root: Handle<UiNode>,
+    lines: [Handle<UiNode>; 4],
+    hit_marker: Handle<UiNode>,
+    is_visible: bool,
+    marker_kind: HitMarkerKind,
+    marker_time: f32,
+    marker_duration: f32,
+}
+
+impl Crosshair {
+    pub fn new(ctx: &mut BuildContext, row: usize, column: usize) -> Self {
+        let mut lines = [Handle::NONE; 4];
+        for line in lines.iter_mut() {
+            *line = BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                    .with_vertical_alignment(VerticalAlignment::Center)
+                    .with_background(Brush::Solid(Color::WHITE)),
+            )
+            .with_stroke_thickness(Thickness::uniform(0.0))
+            .build(ctx);
+        }
+
+        let lines_root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_children(lines),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        let hit_marker = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("X")
+        .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_width(200.0)
+                .with_height(200.0)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_child(lines_root)
+                .with_child(hit_marker),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            root,
+            lines_root,
+            lines,
+            hit_marker,
+            is_visible: false,
+            marker_kind: HitMarkerKind::Hit,
+            marker_time: 0.0,
+            marker_duration: 0.0,
+        }
+    }
+
+    /// Updates the crosshair with the style of current weapon, `None` hides it.
+    pub fn set_style(
+        &mut self,
+        ui: &mut UserInterface,
+        style: Option<CrosshairStyle>,
+        spread: f32,
+    ) {
+        if self.is_visible != style.is_some() {
+            self.is_visible = style.is_some();
+            ui.send_message(WidgetMessage::visibility(
+                self.lines_root,
+                MessageDirection::ToWidget,
+                self.is_visible,
+            ));
+        }
+
+        let style = match style {
+            Some(style) => style,
+            None => return,
+        };
+
+        // Margin on the opposite side of a centered line moves it away from the center.
+        let offset = 2.0 * (style.gap + style.spread_gap * spread.min(1.0) + style.length * 0.5);
+        let [top, bottom, left, right] = self.lines;
+        for (line, vertical, margin) in [
+            (top, true, Thickness::bottom(offset)),
+            (bottom, true, Thickness::top(offset)),
+            (left, false, Thickness::right(offset)),
+            (right, false, Thickness::left(offset)),
+        ] {
+            let (width, height) = if vertical {
+                (LINE_THICKNESS, style.length)
+            } else {
+                (style.length, LINE_THICKNESS)
+            };
+            ui.send_message(WidgetMessage::width(
+                line,
+                MessageDirection::ToWidget,
+                width,
+            ));
+            ui.send_message(WidgetMessage::height(
+                line,
+                MessageDirection::ToWidget,
+                height,
+            ));
+            ui.send_message(WidgetMessage::margin(
+                line,
+                MessageDirection::ToWidget,
+                margin,
+            ));
+        }
+    }
+
+    /// Shows hit marker, marker of a heavy hit stays on screen longer.
+    pub fn show_hit_marker(&mut self, ui: &mut UserInterface, kind: HitMarkerKind, damage: f32) {
+        // Kill marker is more important than any hit that comes right after it.
+        if self.marker_time > 0.0
+            && self.marker_kind == HitMarkerKind::Kill
+            && kind != HitMarkerKind::Kill
+        {
+            return;
+        }
+
+        self.marker_kind = kind;
+        self.marker_duration = kind.duration() * (0.5 + damage / 30.0).min(2.0);
+        self.marker_time = self.marker_duration;
+        ui.send_message(WidgetMessage::visibility(
+            self.hit_marker,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        if self.marker_time <= 0.0 {
+            return;
+        }
+
+        self.marker_time = (self.marker_time - dt).max(0.0);
+        if self.marker_time > 0.0 {
+            let mut color = self.marker_kind.color();
+            color.a = (255.0 * self.marker_time / self.marker_duration) as u8;
+            ui.send_message(WidgetMessage::foreground(
+                self.hit_marker,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        } else {
+            ui.send_message(WidgetMessage::visibility(
+                self.hit_marker,
+                MessageDirection::ToWidget,
+                false,
+            ));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Directional damage indicators of the HUD. Every recent hit is shown as a red arc around the
+//! center of the screen that points to the source of damage, damage without source is shown
+//! as a pulse of the full ring. All segments are built once and only moved and recolored
+//! afterwards, so indicators are cheap to update every frame.
+
+use crate::hud::DamageIndicator;
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+const MAX_ARCS: usize = 4;
+const ARC_SEGMENTS: usize = 7;
+/// Angular length of an arc, in radians.
+const ARC_LENGTH: f32 = 0.7;
+const RING_SEGMENTS: usize = 32;
+const RADIUS: f32 = 90.0;
+/// How much the ring grows while its pulse fades.
+const RING_PULSE_DISTANCE: f32 = 20.0;
+const SEGMENT_SIZE: f32 = 6.0;
+const MAX_ALPHA: f32 = 220.0;
+
+fn segment_color(intensity: f32) -> Brush {
+    Brush::Solid(Color::from_rgba(
+        220,
+        0,
+        0,
+        (MAX_ALPHA * intensity.max(0.0).min(1.0)) as u8,
+    ))
+}
+
+/// Margin on the opposite side of a centered widget moves it away from the center. Angle is
+/// clockwise from the top of the screen.
+pub fn polar_margin(angle: f32, radius: f32) -> Thickness {
+    let dx = 2.0 * radius * angle.sin();
+    let dy = -2.0 * radius * angle.cos();
+    Thickness {
+        left: dx.max(0.0),
+        top: dy.max(0.0),
+        right: (-dx).max(0.0),
+        bottom: (-dy).max(0.0),
+    }
+}
+
+struct SegmentGroup {
+    root: Handle<UiNode>,
+    segments: Vec<Handle<UiNode>>,
+    is_visible: bool,
+}
+
+impl SegmentGroup {
+    fn new(ctx: &mut BuildContext, count: usize) -> Self {
+        let segments = (0..count)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(SEGMENT_SIZE)
+                        .with_height(SEGMENT_SIZE)
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_vertical_alignment(VerticalAlignment::Center)
+                        .with_background(segment_color(0.0)),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_children(segments.iter().copied()),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            root,
+            segments,
+            is_visible: false,
+        }
+    }
+
+    fn set_visible(&mut self, ui: &mut UserInterface, visible: bool) {
+        if self.is_visible != visible {
+            self.is_visible = visible;
+            ui.send_message(WidgetMessage::visibility(
+                self.root,
+                MessageDirection::ToWidget,
+                visible,
+            ));
+        }
+    }
+
+    /// Places segments evenly on a circular arc of given length centered at given angle.
+    fn arrange(
+        &self,
+        ui: &mut UserInterface,
+        center: f32,
+        length: f32,
+        radius: f32,
+        intensity: f32,
+    ) {
+        let count = self.segments.len();
+        let step = length / count as f32;
+        let brush = segment_color(intensity);
+        for (i, &segment) in self.segments.iter().enumerate() {
+            let angle = center + step * (i as f32 - (count - 1) as f32 * 0.5);
+            ui.send_message(WidgetMessage::margin(
+                segment,
+                MessageDirection::ToWidget,
+                polar_margin(angle, radius),
+            ));
+            ui.send_message(WidgetMessage::background(
+                segment,
+                MessageDirection::ToWidget,
+                brush.clone(),
+            ));
+        }
+    }
+}
+
+pub struct DamageIndicators {
+    pub root: Handle<UiNode>,
+    arcs: Vec<SegmentGroup>,
+    ring: SegmentGroup,
+}
+
+impl DamageIndicators {
+    pub fn new(ctx: &mut BuildContext, row: usize, column: usize) -> Self {
+        let arcs = (0..MAX_ARCS)
+            .map(|_| SegmentGroup::new(ctx, ARC_SEGMENTS))
+            .collect::<Vec<_>>();
+        let ring = SegmentGroup::new(ctx, RING_SEGMENTS);
+
+        let size = 2.0 * (RADIUS + RING_PULSE_DISTANCE + SEGMENT_SIZE);
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_width(size)
+                .with_height(size)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_children(arcs.iter().map(|arc| arc.root))
+                .with_child(ring.root),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self { root, arcs, ring }
+    }
+
+    /// Shows given indicators, extra directional indicators beyond the number of arcs are not
+    /// shown. Empty slice hides everything.
+    pub fn set_indicators(&mut self, ui: &mut UserInterface, indicators: &[DamageIndicator]) {
+        let mut arcs = self.arcs.iter_mut();
+        let mut ring_intensity: Option<f32> = None;
+        for indicator in indicators {
+            match indicator.angle {
+                Some(angle) => {
+                    if let Some(arc) = arcs.next() {
+                        arc.set_visible(ui, true);
+                        arc.arrange(ui, angle, ARC_LENGTH, RADIUS, indicator.intensity);
+                    }
+                }
+                None => {
+                    ring_intensity = Some(
+                        ring_intensity.map_or(indicator.intensity, |i| i.max(indicator.intensity)),
+                    );
+                }
+            }
+        }
+        for arc in arcs {
+            arc.set_visible(ui, false);
+        }
+
+        match ring_intensity {
+            Some(intensity) => {
+                self.ring.set_visible(ui, true);
+                let radius = RADIUS + RING_PULSE_DISTANCE * (1.0 - intensity);
+                self.ring
+                    .arrange(ui, 0.0, std::f32::consts::TAU, radius, intensity);
+            }
+            None => self.ring.set_visible(ui, false),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Floating damage numbers of the HUD. Every hit of the player shows the damage above the
+//! damaged actor, the number rises and fades out. Rapid hits on the same actor are summed
+//! into one number, so automatic weapons don't flood the screen. Numbers are anchored in
+//! the world and projected to the screen every frame.
+
+use crate::actor::Actor;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
+        pool::Handle,
+    },
+    gui::{
+        brush::Brush,
+        canvas::CanvasBuilder,
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, UiNode, UserInterface,
+    },
+};
+
+/// Maximum amount of numbers on screen, the oldest one is reused when there are more.
+const MAX_LABELS: usize = 32;
+const LIFETIME: f32 = 0.8;
+/// Hits on the same actor that come faster than this go to the same number.
+const MERGE_TIME: f32 = 0.2;
+/// Height in pixels the number rises during its lifetime.
+const RISE_DISTANCE: f32 = 40.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum DamageNumberKind {
+    /// Damage was taken by armor.
+    Resisted,
+    Normal,
+    Critical,
+}
+
+impl DamageNumberKind {
+    fn color(self) -> Color {
+        match self {
+            DamageNumberKind::Resisted => Color::opaque(160, 160, 160),
+            DamageNumberKind::Normal => Color::opaque(255, 220, 0),
+            DamageNumberKind::Critical => Color::opaque(230, 30, 30),
+        }
+    }
+}
+
+struct Label {
+    text: Handle<UiNode>,
+    target: Handle<Actor>,
+    position: Vector3<f32>,
+    amount: f32,
+    kind: DamageNumberKind,
+    // Time since the last hit merged into the label.
+    age: f32,
+    alive: bool,
+}
+
+pub struct DamageNumbers {
+    pub root: Handle<UiNode>,
+    labels: Vec<Label>,
+}
+
+impl DamageNumbers {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let labels = (0..MAX_LABELS)
+            .map(|_| Label {
+                text: TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_hit_test_visibility(false),
+                )
+                .build(ctx),
+                target: Handle::NONE,
+                position: Default::default(),
+                amount: 0.0,
+                kind: DamageNumberKind::Normal,
+                age: 0.0,
+                alive: false,
+            })
+            .collect::<Vec<_>>();
+
+        let root = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_children(labels.iter().map(|label| label.text)),
+        )
+        .build(ctx);
+
+        Self { root, labels }
+    }
+
+    pub fn push(
+        &mut self,
+        ui: &mut UserInterface,
+        target: Handle<Actor>,
+        position: Vector3<f32>,
+        amount: f32,
+        kind: DamageNumberKind,
+    ) {
+        if let Some(label) = self
+            .labels
+            .iter_mut()
+            .find(|label| label.alive && label.target == target && label.age < MERGE_TIME)
+        {
+            label.amount += amount;
+            label.kind = label.kind.max(kind);
+            label.position = position;
+            label.age = 0.0;
+            Self::sync_text(ui, label);
+            return;
+        }
+
+        // Free label or the oldest one.
+        let label = match self.labels.iter_mut().find(|label| !label.alive) {
+            Some(label) => label,
+            None => self
+                .labels
+                .iter_mut()
+                .max_by(|a, b| a.age.total_cmp(&b.age))
+                .unwrap(),
+        };
+        label.target = target;
+        label.position = position;
+        label.amount = amount;
+        label.kind = kind;
+        label.age = 0.0;
+        label.alive = true;
+        Self::sync_text(ui, label);
+    }
+
+    fn sync_text(ui: &mut UserInterface, label: &Label) {
+        ui.send_message(TextMessage::text(
+            label.text,
+            MessageDirection::ToWidget,
+            format!("{}", label.amount.round() as i32),
+        ));
+    }
+
+    /// Removes every number, used when the player dies or the level ends.
+    pub fn clear(&mut self, ui: &mut UserInterface) {
+        for label in self.labels.iter_mut().filter(|label| label.alive) {
+            label.alive = false;
+            ui.send_message(WidgetMessage::visibility(
+                label.text,
+                MessageDirection::ToWidget,
+                false,
+            ));
+        }
+    }
+
+    /// Moves numbers to their place on screen, `view_projection` is the matrix of the camera
+    /// the player looks through.
+    pub fn update(&mut self, ui: &mut UserInterface, view_projection: &Matrix4<f32>, dt: f32) {
+        let screen_size = ui.screen_size();
+        for label in self.labels.iter_mut().filter(|label| label.alive) {
+            label.age += dt;
+            if label.age >= LIFETIME {
+                label.alive = false;
+                ui.send_message(WidgetMessage::visibility(
+                    label.text,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            }
+
+            let clip = view_projection
+                * Vector4::new(label.position.x, label.position.y, label.position.z, 1.0);
+            // Numbers behind the camera would be mirrored to the front.
+            let visible = clip.w > 0.0;
+            ui.send_message(WidgetMessage::visibility(
+                label.text,
+                MessageDirection::ToWidget,
+                visible,
+            ));
+            if !visible {
+                continue;
+            }
+
+            let t = label.age / LIFETIME;
+            let size = ui.node(label.text).actual_size();
+            let position = Vector2::new(
+                (clip.x / clip.w + 1.0) * 0.5 * screen_size.x - size.x * 0.5,
+                (1.0 - clip.y / clip.w) * 0.5 * screen_size.y - size.y * 0.5 - RISE_DISTANCE * t,
+            );
+            ui.send_message(WidgetMessage::desired_position(
+                label.text,
+                MessageDirection::ToWidget,
+                position,
+            ));
+            let mut color = label.kind.color();
+            color.a = (255.0 * (1.0 - t)) as u8;
+            ui.send_message(WidgetMessage::foreground(
+                label.text,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+    }
+}
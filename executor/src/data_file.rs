@@ -0,0 +1,71 @@
+//! Data files of the game that aren't scenes, like captions or the level list. They're RON
+//! documents read straight into serde types, so every loader shares the same syntax and the
+//! same error reporting.
+
+use fyrox::utils::log::{Log, MessageKind};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// Parses a RON document. Optional values are allowed to omit `Some`.
+pub fn parse<T: DeserializeOwned>(text: &str) -> Result<T, String> {
+    ron::Options::default()
+        .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+        .from_str(text)
+        .map_err(|e| e.to_string())
+}
+
+/// Reads and parses given file.
+pub fn read<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, String> {
+    std::fs::read_to_string(path.as_ref())
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse(&text))
+}
+
+/// Reads given file, a file that is missing or malformed is logged and gives default value.
+/// `what` names the content of the file in the log.
+pub fn load<T: DeserializeOwned + Default, P: AsRef<Path>>(path: P, what: &str) -> T {
+    read(path.as_ref()).unwrap_or_else(|e| {
+        Log::writeln(
+            MessageKind::Warning,
+            format!(
+                "Unable to load {} from {}. Reason: {}",
+                what,
+                path.as_ref().display(),
+                e
+            ),
+        );
+        T::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Default, Debug, PartialEq, Deserialize)]
+    struct Entry {
+        name: String,
+        value: Option<f32>,
+    }
+
+    #[test]
+    fn optional_values_omit_some() {
+        assert_eq!(
+            parse::<Entry>("(name: \"a\", value: 2.0)"),
+            Ok(Entry {
+                name: "a".to_owned(),
+                value: Some(2.0)
+            })
+        );
+    }
+
+    #[test]
+    fn missing_or_malformed_file_gives_default() {
+        assert!(parse::<Entry>("(name: 1)").is_err());
+        assert_eq!(
+            load::<Entry, _>("data/does_not_exist.ron", "entries"),
+            Entry::default()
+        );
+    }
+}
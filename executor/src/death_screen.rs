@@ -0,0 +1,275 @@
+//! Death screen, it is shown when the local player was killed and the death camera has
+//! settled. The level fills `DeathSummary`, the screen only shows it and sends messages.
+
+use crate::{
+    leader_board::format_time,
+    localization::{tr, tr_format},
+    message::Message,
+    save_slot::SaveSlot,
+};
+use fyrox::{
+    core::{color::Color, parking_lot::Mutex, pool::Handle},
+    engine::Engine,
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface,
+    },
+};
+use std::{
+    path::Path,
+    sync::{mpsc::Sender, Arc},
+};
+
+/// Information about the last life of the player.
+pub struct DeathSummary {
+    /// `None` when the player died without a killer (fell, killed themselves).
+    pub killer: Option<String>,
+    /// Weapon the killer was holding.
+    pub weapon: Option<String>,
+    pub kills: u32,
+    pub damage_dealt: f32,
+    pub time_survived: f32,
+    /// Match rules allow the player to come back.
+    pub can_respawn: bool,
+}
+
+pub struct DeathScreen {
+    sender: Sender<Message>,
+    root: Handle<UiNode>,
+    cause: Handle<UiNode>,
+    stats: Handle<UiNode>,
+    btn_respawn: Handle<UiNode>,
+    btn_load_last_save: Handle<UiNode>,
+    btn_quit_to_menu: Handle<UiNode>,
+    can_respawn: bool,
+    // Slot that is loaded by "Load Last Save", it is found when the screen is shown.
+    last_save: Option<usize>,
+}
+
+impl DeathScreen {
+    pub fn new(engine: &mut Engine, sender: Sender<Message>) -> Self {
+        let frame_size = engine.renderer.get_frame_size();
+
+        let font: Font = fyrox::core::futures::executor::block_on(Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            31.0,
+            Font::default_char_set(),
+        ))
+        .unwrap();
+        let font = SharedFont(Arc::new(Mutex::new(font)));
+
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let button = |ctx: &mut _, text: &str| {
+            ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .with_height(60.0)
+                    .with_margin(Thickness::uniform(4.0)),
+            )
+            .with_text_and_font(text, font.clone())
+            .build(ctx)
+        };
+        let btn_respawn = button(ctx, &tr("death.respawn"));
+        let btn_load_last_save = button(ctx, &tr("death.load_last_save"));
+        let btn_quit_to_menu = button(ctx, &tr("death.quit_to_menu"));
+
+        let cause = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_font(font.clone())
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+        let stats = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::from_rgba(20, 0, 0, 190)))
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new().with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .on_column(1)
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(4.0))
+                                                .with_foreground(Brush::Solid(Color::opaque(
+                                                    200, 0, 0,
+                                                ))),
+                                        )
+                                        .with_font(font.clone())
+                                        .with_text(&tr("death.you_died"))
+                                        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                                        .build(ctx),
+                                    )
+                                    .with_child(cause)
+                                    .with_child(stats)
+                                    .with_child(btn_respawn)
+                                    .with_child(btn_load_last_save)
+                                    .with_child(btn_quit_to_menu),
+                            )
+                            .build(ctx),
+                        ),
+                    )
+                    .add_row(Row::stretch())
+                    .add_row(Row::strict(420.0))
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(500.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            sender,
+            root,
+            cause,
+            stats,
+            btn_respawn,
+            btn_load_last_save,
+            btn_quit_to_menu,
+            can_respawn: false,
+            last_save: None,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut UserInterface, summary: &DeathSummary) {
+        let cause = match (summary.killer.as_ref(), summary.weapon.as_ref()) {
+            (Some(killer), Some(weapon)) => tr_format("death.killed_by_with", &[killer, weapon]),
+            (Some(killer), None) => tr_format("death.killed_by", &[killer]),
+            // Falls, death zones and own grenades.
+            (None, _) => tr("death.killed_by_environment"),
+        };
+        ui.send_message(TextMessage::text(
+            self.cause,
+            MessageDirection::ToWidget,
+            cause,
+        ));
+        ui.send_message(TextMessage::text(
+            self.stats,
+            MessageDirection::ToWidget,
+            tr_format(
+                "death.stats",
+                &[
+                    &summary.kills,
+                    &(summary.damage_dealt.round() as u32),
+                    &format_time(summary.time_survived),
+                ],
+            ),
+        ));
+
+        self.can_respawn = summary.can_respawn;
+        self.last_save = SaveSlot::newest();
+        ui.send_message(WidgetMessage::enabled(
+            self.btn_respawn,
+            MessageDirection::ToWidget,
+            self.can_respawn,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.btn_load_last_save,
+            MessageDirection::ToWidget,
+            self.last_save.is_some(),
+        ));
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn hide(&mut self, ui: &mut UserInterface) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    pub fn is_visible(&self, ui: &UserInterface) -> bool {
+        ui.node(self.root).visibility()
+    }
+
+    fn respawn(&self) {
+        if self.can_respawn {
+            self.sender.send(Message::SpawnPlayer).unwrap();
+        }
+    }
+
+    fn load_last_save(&self) {
+        if let Some(slot) = self.last_save {
+            self.sender.send(Message::LoadGame { slot }).unwrap();
+        }
+    }
+
+    /// Handles hotkeys of the screen. Returns true if the event was consumed.
+    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) -> bool {
+        let ui = &mut engine.user_interface;
+
+        if let Event::WindowEvent { event, .. } = event {
+            if let WindowEvent::Resized(new_size) = event {
+                ui.send_message(WidgetMessage::width(
+                    self.root,
+                    MessageDirection::ToWidget,
+                    new_size.width as f32,
+                ));
+                ui.send_message(WidgetMessage::height(
+                    self.root,
+                    MessageDirection::ToWidget,
+                    new_size.height as f32,
+                ));
+            }
+
+            if !self.is_visible(ui) {
+                return false;
+            }
+
+            if let WindowEvent::KeyboardInput { input, .. } = event {
+                if input.state == ElementState::Pressed {
+                    match input.virtual_keycode {
+                        Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::NumpadEnter) => {
+                            self.respawn();
+                            return true;
+                        }
+                        Some(VirtualKeyCode::L) => {
+                            self.load_last_save();
+                            return true;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn handle_ui_event(&mut self, message: &UiMessage) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_respawn {
+                self.respawn();
+            } else if message.destination() == self.btn_load_last_save {
+                self.load_last_save();
+            } else if message.destination() == self.btn_quit_to_menu {
+                self.sender.send(Message::QuitToMenu).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Debug overlay, toggled by F3. The game loop assembles `DebugStats` every frame while the
+//! overlay is visible, the overlay only shows them. It is drawn on top of everything and
+//! ignores the mouse, so it can be left open while playing or navigating menus.
+
+use fyrox::{
+    core::{algebra::Vector3, color::Color, pool::Handle},
+    engine::Engine,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::{collections::VecDeque, fmt::Write, time::Duration};
+
+/// Frames shown by the frame time graph.
+pub const FRAME_HISTORY: usize = 120;
+const BAR_WIDTH: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+/// Frame time that fills the whole height of the graph, longer frames are clamped.
+const GRAPH_MAX_FRAME_TIME: f32 = 50.0;
+
+#[derive(Default)]
+pub struct DebugStats {
+    pub fps: usize,
+    /// Frame time without vsync and frame limiting, in milliseconds.
+    pub frame_time: f32,
+    pub capped_frame_time: f32,
+    pub triangles: usize,
+    pub draw_calls: usize,
+    pub uptime: f64,
+    pub ui_time: Duration,
+    pub bots: usize,
+    /// Living bots by AI level of detail, see `AiLod`.
+    pub full_rate_bots: usize,
+    pub reduced_rate_bots: usize,
+    pub projectiles: usize,
+    pub items: usize,
+    pub animations: usize,
+    pub effects: usize,
+    pub pooled_effects: usize,
+    /// Rough amount of particles of active effects, see `EffectBudget`.
+    pub effect_particles: u32,
+    /// Effects skipped or cut short because of the effect budget.
+    pub skipped_effects: usize,
+    pub evicted_effects: usize,
+    /// `None` while there is no player, in menus or when dead.
+    pub player_position: Option<Vector3<f32>>,
+    /// Subsystem breakdown, only filled when the game is built with the `profiler` feature.
+    pub profiler: Option<String>,
+    /// State labels of bots, only filled while they are drawn, see `BotDebugFlags`.
+    pub bot_labels: Vec<String>,
+}
+
+pub struct DebugOverlay {
+    root: Handle<UiNode>,
+    text: Handle<UiNode>,
+    bars: Vec<Handle<UiNode>>,
+    profiler: Handle<UiNode>,
+    history: VecDeque<f32>,
+    visible: bool,
+    content: String,
+}
+
+fn frame_color(frame_time: f32) -> Color {
+    if frame_time <= 1000.0 / 60.0 {
+        Color::opaque(0, 200, 0)
+    } else if frame_time <= 1000.0 / 30.0 {
+        Color::opaque(220, 200, 0)
+    } else {
+        Color::opaque(220, 0, 0)
+    }
+}
+
+impl DebugOverlay {
+    pub fn new(engine: &mut Engine) -> Self {
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let bars = (0..FRAME_HISTORY)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_hit_test_visibility(false)
+                        .with_width(BAR_WIDTH)
+                        .with_height(0.0)
+                        .with_vertical_alignment(VerticalAlignment::Bottom)
+                        .with_background(Brush::Solid(frame_color(0.0))),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let text =
+            TextBuilder::new(WidgetBuilder::new().with_hit_test_visibility(false)).build(ctx);
+        let profiler = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_hit_test_visibility(false)
+                            .with_margin(Thickness::uniform(4.0))
+                            .with_child(text)
+                            .with_child(
+                                BorderBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_hit_test_visibility(false)
+                                        .with_width(BAR_WIDTH * FRAME_HISTORY as f32)
+                                        .with_height(GRAPH_HEIGHT)
+                                        .with_horizontal_alignment(HorizontalAlignment::Left)
+                                        .with_margin(Thickness::uniform(2.0))
+                                        .with_background(Brush::Solid(Color::from_rgba(
+                                            40, 40, 40, 160,
+                                        )))
+                                        .with_child(
+                                            StackPanelBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_hit_test_visibility(false)
+                                                    .with_children(bars.iter().copied()),
+                                            )
+                                            .with_orientation(Orientation::Horizontal)
+                                            .build(ctx),
+                                        ),
+                                )
+                                .with_stroke_thickness(Thickness::uniform(0.0))
+                                .build(ctx),
+                            )
+                            .with_child(profiler),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            root,
+            text,
+            bars,
+            profiler,
+            history: VecDeque::with_capacity(FRAME_HISTORY),
+            visible: false,
+            content: String::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, ui: &UserInterface, visible: bool) {
+        self.visible = visible;
+        // History is not collected while hidden, old frames would make a gap in the graph.
+        self.history.clear();
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    pub fn update(&mut self, ui: &UserInterface, stats: &DebugStats) {
+        if !self.visible {
+            return;
+        }
+
+        // Windows move themselves to the top when opened, so the overlay is lifted every frame.
+        ui.send_message(WidgetMessage::topmost(
+            self.root,
+            MessageDirection::ToWidget,
+        ));
+
+        self.content.clear();
+        write!(
+            self.content,
+            "FPS: {}\n\
+             Pure frame time: {:.2} ms\n\
+             Capped frame time: {:.2} ms\n\
+             Triangles: {}\n\
+             Draw calls: {}\n\
+             Uptime: {:.2} s\n\
+             UI time: {:?}\n\
+             Bots: {} ({} full rate, {} reduced)\n\
+             Projectiles: {}\n\
+             Items: {}\n\
+             Animations: {}\n\
+             Effects: {} active, {} pooled, ~{} particles\n\
+             Effects over budget: {} skipped, {} evicted",
+            stats.fps,
+            stats.frame_time,
+            stats.capped_frame_time,
+            stats.triangles,
+            stats.draw_calls,
+            stats.uptime,
+            stats.ui_time,
+            stats.bots,
+            stats.full_rate_bots,
+            stats.reduced_rate_bots,
+            stats.projectiles,
+            stats.items,
+            stats.animations,
+            stats.effects,
+            stats.pooled_effects,
+            stats.effect_particles,
+            stats.skipped_effects,
+            stats.evicted_effects,
+        )
+        .unwrap();
+        if let Some(position) = stats.player_position {
+            write!(
+                self.content,
+                "\nPosition: {:.2} {:.2} {:.2}",
+                position.x, position.y, position.z
+            )
+            .unwrap();
+        }
+        for label in stats.bot_labels.iter() {
+            write!(self.content, "\n{}", label).unwrap();
+        }
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            self.content.clone(),
+        ));
+
+        if self.history.len() == FRAME_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(stats.frame_time);
+
+        // Newest frame is on the right, bars without history yet stay empty.
+        let offset = FRAME_HISTORY - self.history.len();
+        for (i, bar) in self.bars.iter().enumerate() {
+            let frame_time = if i >= offset {
+                self.history[i - offset]
+            } else {
+                0.0
+            };
+            ui.send_message(WidgetMessage::height(
+                *bar,
+                MessageDirection::ToWidget,
+                GRAPH_HEIGHT * (frame_time / GRAPH_MAX_FRAME_TIME).min(1.0),
+            ));
+            ui.send_message(WidgetMessage::background(
+                *bar,
+                MessageDirection::ToWidget,
+                Brush::Solid(frame_color(frame_time)),
+            ));
+        }
+
+        ui.send_message(WidgetMessage::visibility(
+            self.profiler,
+            MessageDirection::ToWidget,
+            stats.profiler.is_some(),
+        ));
+        if let Some(profiler) = stats.profiler.as_ref() {
+            ui.send_message(TextMessage::text(
+                self.profiler,
+                MessageDirection::ToWidget,
+                profiler.clone(),
+            ));
+        }
+    }
+}
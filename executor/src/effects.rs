@@ -1,9 +1,20 @@
+//! Particle effects. Short-lived effects are pooled: finished ones are hidden and reused by
+//! next effect of the same kind, so big fights don't spawn a new particle system on every hit.
+//! [`EffectBudget`] limits how many effects and particles may be alive at once, effects of low
+//! priority are skipped when over budget, effects of high priority push out the oldest
+//! low-priority ones.
+
+use crate::{
+    settings::EffectQuality,
+    surface::{self, SurfaceKind},
+};
 use fyrox::{
     core::{
-        algebra::Vector3,
+        algebra::{UnitQuaternion, Vector3},
         color::Color,
         color_gradient::{ColorGradient, GradientPoint},
         pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
     scene::{
@@ -12,34 +23,526 @@ use fyrox::{
         node::Node,
         particle_system::{
             emitter::base::BaseEmitterBuilder, emitter::cylinder::CylinderEmitterBuilder,
-            emitter::sphere::SphereEmitterBuilder, ParticleSystemBuilder,
+            emitter::sphere::SphereEmitterBuilder, ParticleSystem, ParticleSystemBuilder,
         },
         transform::TransformBuilder,
     },
 };
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+/// Particle textures, level decals use them too.
+pub const CIRCLE_TEXTURE: &str = "data/particles/circle_05.png";
+pub const SMOKE_TEXTURE: &str = "data/particles/smoke_04.tga";
+pub const STAR_TEXTURE: &str = "data/particles/star_09.png";
+
+const MAX_DEBRIS_PARTICLES: u32 = 80;
+/// Blood of [`EffectKind::BloodSplash`] created without a color of its own.
+const DEFAULT_BLOOD_COLOR: Color = Color::opaque(140, 0, 0);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit)]
 pub enum EffectKind {
     BulletImpact,
     ItemAppear,
     Smoke,
     Steam,
+    BloodSplash,
+    /// Neutral dust puff, replaces blood when violence is reduced.
+    Puff,
+    /// Explosion on given surface of the level, `None` is an air burst.
+    Explosion(Option<SurfaceKind>),
+    /// Dust rising from a corpse that is removed from the level.
+    CorpseDissolve,
+    /// Dust thrown up by a ground slam.
+    Shockwave,
+    /// Sparks out of the muzzle of a weapon, along the up axis of the effect.
+    MuzzleFlash,
+}
+
+impl Default for EffectKind {
+    fn default() -> Self {
+        EffectKind::BulletImpact
+    }
 }
 
-pub fn create(
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EffectPriority {
+    /// Cosmetic effects, skipped when over budget.
+    Low,
+    /// Effects that tell the player something, they are never skipped while there are
+    /// low-priority effects to push out.
+    High,
+}
+
+impl EffectKind {
+    /// Time the effect is alive, `None` for effects that stay until the level is unloaded.
+    /// Such effects are not pooled and don't count towards the budget.
+    fn duration(self) -> Option<f32> {
+        match self {
+            EffectKind::BulletImpact => Some(1.0),
+            EffectKind::ItemAppear => Some(1.4),
+            EffectKind::Smoke => Some(5.0),
+            EffectKind::Steam => None,
+            EffectKind::BloodSplash => Some(0.8),
+            EffectKind::Puff => Some(0.8),
+            EffectKind::Explosion(_) => Some(1.2),
+            EffectKind::CorpseDissolve => Some(2.0),
+            EffectKind::Shockwave => Some(1.0),
+            EffectKind::MuzzleFlash => Some(0.15),
+        }
+    }
+
+    pub fn priority(self) -> EffectPriority {
+        match self {
+            EffectKind::BulletImpact
+            | EffectKind::Smoke
+            | EffectKind::Puff
+            | EffectKind::Steam
+            | EffectKind::CorpseDissolve
+            | EffectKind::MuzzleFlash => EffectPriority::Low,
+            EffectKind::ItemAppear
+            | EffectKind::BloodSplash
+            | EffectKind::Explosion(_)
+            | EffectKind::Shockwave => EffectPriority::High,
+        }
+    }
+
+    /// Most particles the effect has at once, must match its emitters.
+    fn max_particles(self) -> u32 {
+        match self {
+            EffectKind::BulletImpact => 200,
+            EffectKind::ItemAppear => 100,
+            EffectKind::Smoke => 100,
+            EffectKind::Steam => 0,
+            EffectKind::BloodSplash => 60,
+            EffectKind::Puff => 20,
+            EffectKind::Explosion(None) => 300,
+            EffectKind::Explosion(Some(_)) => 300 + MAX_DEBRIS_PARTICLES,
+            EffectKind::CorpseDissolve => 150,
+            EffectKind::Shockwave => 200,
+            EffectKind::MuzzleFlash => 30,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct EffectBudget {
+    pub max_effects: usize,
+    /// Rough limit, every effect is counted with the most particles it can have.
+    pub max_particles: u32,
+}
+
+impl EffectBudget {
+    pub fn new(quality: EffectQuality) -> Self {
+        match quality {
+            EffectQuality::Low => Self {
+                max_effects: 16,
+                max_particles: 1000,
+            },
+            EffectQuality::Medium => Self {
+                max_effects: 32,
+                max_particles: 2500,
+            },
+            EffectQuality::High => Self {
+                max_effects: 64,
+                max_particles: 6000,
+            },
+        }
+    }
+}
+
+impl Default for EffectBudget {
+    fn default() -> Self {
+        Self::new(EffectQuality::High)
+    }
+}
+
+#[derive(Default, Visit)]
+struct PooledEffect {
+    node: Handle<Node>,
+    kind: EffectKind,
+    age: f32,
+}
+
+#[derive(Default, Visit)]
+pub struct EffectPool {
+    active: Vec<PooledEffect>,
+    // Finished effects, hidden and waiting to be reused.
+    free: Vec<PooledEffect>,
+    #[visit(skip)]
+    budget: EffectBudget,
+    // Effects that were not shown because of the budget, since the level was loaded.
+    #[visit(skip)]
+    skipped: usize,
+    // Low-priority effects that were cut short to make room for high-priority ones.
+    #[visit(skip)]
+    evicted: usize,
+}
+
+impl EffectPool {
+    pub fn set_budget(&mut self, budget: EffectBudget) {
+        self.budget = budget;
+    }
+
+    /// Spawns effect at given position with its up axis along `normal`, effects that spray
+    /// particles spray them away from the surface. Returns `Handle::NONE` if the effect was
+    /// skipped because of the budget.
+    pub fn spawn(
+        &mut self,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        kind: EffectKind,
+        position: Vector3<f32>,
+        normal: Vector3<f32>,
+    ) -> Handle<Node> {
+        let node = self.acquire(graph, resource_manager, kind, DEFAULT_BLOOD_COLOR);
+        if node.is_some() {
+            place(graph, node, position, normal);
+        }
+        node
+    }
+
+    /// Same as [`Self::spawn`] for a blood splash of given color.
+    pub fn spawn_blood(
+        &mut self,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        position: Vector3<f32>,
+        normal: Vector3<f32>,
+        color: Color,
+    ) -> Handle<Node> {
+        let node = self.acquire(graph, resource_manager, EffectKind::BloodSplash, color);
+        if node.is_some() {
+            place(graph, node, position, normal);
+        }
+        node
+    }
+
+    fn acquire(
+        &mut self,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        kind: EffectKind,
+        blood_color: Color,
+    ) -> Handle<Node> {
+        if kind.duration().is_none() {
+            return create(kind, graph, resource_manager, blood_color);
+        }
+
+        if !self.make_room(graph, kind) {
+            self.skipped += 1;
+            return Handle::NONE;
+        }
+
+        let node = match self.free.iter().position(|effect| effect.kind == kind) {
+            Some(index) => {
+                let node = self.free.swap_remove(index).node;
+                graph[node].set_visibility(true);
+                restart(graph, node);
+                if kind == EffectKind::BloodSplash {
+                    graph[node]
+                        .as_particle_system_mut()
+                        .set_color_over_lifetime_gradient(blood_gradient(blood_color));
+                }
+                node
+            }
+            None => create(kind, graph, resource_manager, blood_color),
+        };
+        self.active.push(PooledEffect {
+            node,
+            kind,
+            age: 0.0,
+        });
+        node
+    }
+
+    fn particles(&self) -> u32 {
+        self.active
+            .iter()
+            .map(|effect| effect.kind.max_particles())
+            .sum()
+    }
+
+    fn has_room(&self, kind: EffectKind) -> bool {
+        self.active.len() < self.budget.max_effects
+            && self.particles() + kind.max_particles() <= self.budget.max_particles
+    }
+
+    fn make_room(&mut self, graph: &mut Graph, kind: EffectKind) -> bool {
+        while !self.has_room(kind) {
+            if kind.priority() == EffectPriority::Low {
+                return false;
+            }
+            let oldest = self
+                .active
+                .iter()
+                .enumerate()
+                .filter(|(_, effect)| effect.kind.priority() == EffectPriority::Low)
+                .max_by(|(_, a), (_, b)| a.age.total_cmp(&b.age))
+                .map(|(index, _)| index);
+            match oldest {
+                Some(index) => {
+                    self.release(graph, index);
+                    self.evicted += 1;
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn release(&mut self, graph: &mut Graph, index: usize) {
+        let effect = self.active.swap_remove(index);
+        if graph.is_valid_handle(effect.node) {
+            graph[effect.node].set_visibility(false);
+            self.free.push(effect);
+        }
+    }
+
+    /// Returns finished effects to the pool.
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) {
+        let mut i = 0;
+        while i < self.active.len() {
+            let effect = &mut self.active[i];
+            effect.age += dt;
+            if effect.age >= effect.kind.duration().unwrap_or(f32::MAX) {
+                self.release(graph, i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Removes every effect, active or pooled, from the scene.
+    pub fn clear(&mut self, graph: &mut Graph) {
+        for effect in self.active.drain(..).chain(self.free.drain(..)) {
+            if graph.is_valid_handle(effect.node) {
+                graph.remove_node(effect.node);
+            }
+        }
+        self.skipped = 0;
+        self.evicted = 0;
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn pooled_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Rough amount of particles of active effects.
+    pub fn particle_count(&self) -> u32 {
+        self.particles()
+    }
+
+    pub fn skipped_count(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn evicted_count(&self) -> usize {
+        self.evicted
+    }
+}
+
+fn create(
     kind: EffectKind,
     graph: &mut Graph,
     resource_manager: ResourceManager,
-    pos: Vector3<f32>,
+    blood_color: Color,
 ) -> Handle<Node> {
+    let pos = Vector3::default();
     match kind {
         EffectKind::BulletImpact => create_bullet_impact(graph, resource_manager, pos),
         EffectKind::ItemAppear => create_item_appear(graph, resource_manager, pos),
         EffectKind::Smoke => create_smoke(graph, resource_manager, pos),
         EffectKind::Steam => create_steam(graph, resource_manager, pos),
+        EffectKind::BloodSplash => create_blood_splash(graph, resource_manager, pos, blood_color),
+        EffectKind::Puff => create_puff(graph, resource_manager, pos),
+        EffectKind::Explosion(surface) => create_explosion(graph, resource_manager, pos, surface),
+        EffectKind::CorpseDissolve => create_corpse_dissolve(graph, resource_manager, pos),
+        EffectKind::Shockwave => create_shockwave(graph, resource_manager, pos),
+        EffectKind::MuzzleFlash => create_muzzle_flash(graph, resource_manager, pos),
     }
 }
 
+/// Clears particles of the effect and its child systems, so emitters start over.
+fn restart(graph: &mut Graph, effect: Handle<Node>) {
+    let children = graph[effect].children().to_vec();
+    if let Some(particle_system) = graph[effect].cast_mut::<ParticleSystem>() {
+        particle_system.clear_particles();
+    }
+    for child in children {
+        restart(graph, child);
+    }
+}
+
+fn place(graph: &mut Graph, effect: Handle<Node>, position: Vector3<f32>, normal: Vector3<f32>) {
+    let transform = graph[effect].local_transform_mut();
+    transform.set_position(position);
+    transform
+        .set_rotation(UnitQuaternion::rotation_between(&Vector3::y(), &normal).unwrap_or_default());
+}
+
+fn blood_gradient(color: Color) -> ColorGradient {
+    let with_alpha = |a: u8| Color::from_rgba(color.r, color.g, color.b, a);
+    let mut gradient = ColorGradient::new();
+    gradient.add_point(GradientPoint::new(0.00, with_alpha(0)));
+    gradient.add_point(GradientPoint::new(0.05, with_alpha(255)));
+    gradient.add_point(GradientPoint::new(0.70, with_alpha(220)));
+    gradient.add_point(GradientPoint::new(1.00, with_alpha(0)));
+    gradient
+}
+
+fn create_blood_splash(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+    color: Color,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+    )
+    .with_acceleration(Vector3::new(0.0, -4.0, 0.0))
+    .with_color_over_lifetime_gradient(blood_gradient(color))
+    .with_emitters(vec![SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(60)
+            .with_spawn_rate(600)
+            .with_size_modifier_range(-0.01..-0.015)
+            .with_size_range(0.04..0.08)
+            .with_x_velocity_range(-0.02..0.02)
+            .with_y_velocity_range(0.02..0.05)
+            .with_z_velocity_range(-0.02..0.02)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.02)
+    .build()])
+    .with_texture(resource_manager.request_texture(CIRCLE_TEXTURE))
+    .build(graph)
+}
+
+fn create_explosion(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+    surface: Option<SurfaceKind>,
+) -> Handle<Node> {
+    let fire = create_fire_ball(graph, resource_manager.clone(), pos);
+    // Debris is a child system, so it is pooled and placed together with the fire ball.
+    if let Some(debris) = surface::explosion_definition(surface).debris.as_ref() {
+        let debris = ParticleSystemBuilder::new(BaseBuilder::new())
+            .with_acceleration(Vector3::new(0.0, -9.0, 0.0))
+            .with_color_over_lifetime_gradient({
+                let color = debris.color;
+                let mut gradient = ColorGradient::new();
+                gradient.add_point(GradientPoint::new(
+                    0.00,
+                    Color::from_rgba(color.r, color.g, color.b, 255),
+                ));
+                gradient.add_point(GradientPoint::new(
+                    0.80,
+                    Color::from_rgba(color.r, color.g, color.b, 200),
+                ));
+                gradient.add_point(GradientPoint::new(
+                    1.00,
+                    Color::from_rgba(color.r, color.g, color.b, 0),
+                ));
+                gradient
+            })
+            .with_emitters(vec![SphereEmitterBuilder::new(
+                BaseEmitterBuilder::new()
+                    .with_max_particles(MAX_DEBRIS_PARTICLES)
+                    .with_spawn_rate(1600)
+                    .with_size_range(0.03..0.07)
+                    .with_x_velocity_range(-0.06..0.06)
+                    .with_y_velocity_range(0.05..0.12)
+                    .with_z_velocity_range(-0.06..0.06)
+                    .resurrect_particles(false),
+            )
+            .with_radius(0.1)
+            .build()])
+            .with_texture(resource_manager.request_texture(debris.texture))
+            .build(graph);
+        graph.link_nodes(debris, fire);
+    }
+    fire
+}
+
+fn create_fire_ball(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+    )
+    .with_acceleration(Vector3::new(0.0, -2.0, 0.0))
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 200, 0)));
+        gradient.add_point(GradientPoint::new(
+            0.05,
+            Color::from_rgba(255, 200, 60, 255),
+        ));
+        gradient.add_point(GradientPoint::new(0.50, Color::from_rgba(230, 90, 0, 220)));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(60, 60, 60, 0)));
+        gradient
+    })
+    .with_emitters(vec![SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(300)
+            .with_spawn_rate(3000)
+            .with_size_modifier_range(0.005..0.01)
+            .with_size_range(0.15..0.3)
+            .with_x_velocity_range(-0.08..0.08)
+            .with_y_velocity_range(-0.08..0.08)
+            .with_z_velocity_range(-0.08..0.08)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.2)
+    .build()])
+    .with_texture(resource_manager.request_texture(CIRCLE_TEXTURE))
+    .build(graph)
+}
+
+fn create_puff(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+    )
+    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(180, 170, 150, 0)));
+        gradient.add_point(GradientPoint::new(
+            0.10,
+            Color::from_rgba(180, 170, 150, 160),
+        ));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(200, 200, 200, 0)));
+        gradient
+    })
+    .with_emitters(vec![SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(20)
+            .with_spawn_rate(200)
+            .with_size_range(0.05..0.1)
+            .with_x_velocity_range(-0.01..0.01)
+            .with_y_velocity_range(0.01..0.02)
+            .with_z_velocity_range(-0.01..0.01)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.02)
+    .build()])
+    .with_texture(resource_manager.request_texture(SMOKE_TEXTURE))
+    .build(graph)
+}
+
 fn create_steam(
     graph: &mut Graph,
     resource_manager: ResourceManager,
@@ -68,7 +571,7 @@ fn create_steam(
         .with_height(0.2)
         .with_radius(0.2)
         .build()])
-    .with_texture(resource_manager.request_texture("data/particles/smoke_04.tga"))
+    .with_texture(resource_manager.request_texture(SMOKE_TEXTURE))
     .build(graph)
 }
 
@@ -79,7 +582,6 @@ fn create_bullet_impact(
 ) -> Handle<Node> {
     ParticleSystemBuilder::new(
         BaseBuilder::new()
-            .with_lifetime(1.0)
             .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
     )
     .with_acceleration(Vector3::new(0.0, -10.0, 0.0))
@@ -104,7 +606,7 @@ fn create_bullet_impact(
     )
     .with_radius(0.01)
     .build()])
-    .with_texture(resource_manager.request_texture("data/particles/circle_05.png"))
+    .with_texture(resource_manager.request_texture(CIRCLE_TEXTURE))
     .build(graph)
 }
 
@@ -115,7 +617,6 @@ fn create_smoke(
 ) -> Handle<Node> {
     ParticleSystemBuilder::new(
         BaseBuilder::new()
-            .with_lifetime(5.0)
             .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
     )
     .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
@@ -139,11 +640,124 @@ fn create_smoke(
             .with_spawn_rate(50)
             .with_x_velocity_range(-0.01..0.01)
             .with_y_velocity_range(0.02..0.03)
-            .with_z_velocity_range(-0.01..0.01),
+            .with_z_velocity_range(-0.01..0.01)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.01)
+    .build()])
+    .with_texture(resource_manager.request_texture(SMOKE_TEXTURE))
+    .build(graph)
+}
+
+fn create_corpse_dissolve(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+    )
+    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(90, 80, 70, 0)));
+        gradient.add_point(GradientPoint::new(0.15, Color::from_rgba(90, 80, 70, 200)));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(140, 140, 140, 0)));
+        gradient
+    })
+    .with_emitters(vec![CylinderEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(150)
+            .with_spawn_rate(150)
+            .with_size_range(0.08..0.16)
+            .with_x_velocity_range(-0.005..0.005)
+            .with_y_velocity_range(0.01..0.025)
+            .with_z_velocity_range(-0.005..0.005)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.4)
+    .with_height(0.2)
+    .build()])
+    .with_texture(resource_manager.request_texture(SMOKE_TEXTURE))
+    .build(graph)
+}
+
+fn create_shockwave(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+    )
+    .with_acceleration(Vector3::new(0.0, -0.5, 0.0))
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(
+            0.00,
+            Color::from_rgba(120, 105, 90, 200),
+        ));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(120, 105, 90, 0)));
+        gradient
+    })
+    .with_emitters(vec![CylinderEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(200)
+            .with_spawn_rate(2000)
+            .with_size_modifier_range(0.002..0.004)
+            .with_size_range(0.15..0.3)
+            .with_x_velocity_range(-0.15..0.15)
+            .with_y_velocity_range(0.01..0.03)
+            .with_z_velocity_range(-0.15..0.15)
+            .resurrect_particles(false),
+    )
+    .with_radius(0.6)
+    .with_height(0.1)
+    .build()])
+    .with_texture(resource_manager.request_texture(SMOKE_TEXTURE))
+    .build(graph)
+}
+
+fn create_muzzle_flash(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+    )
+    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(
+            0.00,
+            Color::from_rgba(255, 240, 180, 255),
+        ));
+        gradient.add_point(GradientPoint::new(
+            0.50,
+            Color::from_rgba(255, 160, 40, 200),
+        ));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 80, 0, 0)));
+        gradient
+    })
+    .with_emitters(vec![SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(30)
+            .with_spawn_rate(600)
+            .with_lifetime_range(0.05..0.1)
+            .with_size_modifier_range(-0.2..-0.3)
+            .with_size_range(0.04..0.08)
+            .with_x_velocity_range(-0.01..0.01)
+            .with_y_velocity_range(0.08..0.15)
+            .with_z_velocity_range(-0.01..0.01)
+            .resurrect_particles(false),
     )
     .with_radius(0.01)
     .build()])
-    .with_texture(resource_manager.request_texture("data/particles/smoke_04.tga"))
+    .with_texture(resource_manager.request_texture(STAR_TEXTURE))
     .build(graph)
 }
 
@@ -154,7 +768,6 @@ fn create_item_appear(
 ) -> Handle<Node> {
     ParticleSystemBuilder::new(
         BaseBuilder::new()
-            .with_lifetime(1.4)
             .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
     )
     .with_acceleration(Vector3::new(0.0, -6.0, 0.0))
@@ -179,6 +792,6 @@ fn create_item_appear(
     )
     .with_radius(0.01)
     .build()])
-    .with_texture(resource_manager.request_texture("data/particles/star_09.png"))
+    .with_texture(resource_manager.request_texture(STAR_TEXTURE))
     .build(graph)
 }
@@ -0,0 +1,34 @@
+//! Errors that are reported and survived instead of panicking, like a broken asset that keeps
+//! a single entity from spawning.
+
+use std::{
+    fmt::{Debug, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub enum GameError {
+    /// Resource at given path failed to load, the reason comes from the resource manager.
+    ResourceLoad { path: PathBuf, reason: String },
+}
+
+impl GameError {
+    pub fn resource_load<P: AsRef<Path>>(path: P, reason: impl Debug) -> Self {
+        Self::ResourceLoad {
+            path: path.as_ref().to_owned(),
+            reason: format!("{:?}", reason),
+        }
+    }
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::ResourceLoad { path, reason } => {
+                write!(f, "Unable to load {}. Reason: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
@@ -0,0 +1,273 @@
+//! Event log keeps short history of significant gameplay events (damage, kills, pickups, etc.)
+//! so it is possible to find out what happened right before something went wrong. Entries are
+//! stored in a fixed-size ring buffer and can be viewed in a debug panel or dumped to a file.
+
+use crate::{gui::create_check_box, localization::tr};
+use fyrox::{
+    core::pool::Handle,
+    engine::Engine,
+    gui::{
+        check_box::CheckBoxMessage,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::{collections::VecDeque, fmt::Write as _, fs::File, io::Write, path::Path};
+
+/// Maximum amount of entries in the log, oldest entries are discarded first.
+pub const EVENT_LOG_CAPACITY: usize = 2000;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EventCategory {
+    Damage = 0,
+    Kill = 1,
+    Pickup = 2,
+    WeaponSwitch = 3,
+    BotTarget = 4,
+    Trigger = 5,
+}
+
+impl EventCategory {
+    pub const ALL: [EventCategory; 6] = [
+        EventCategory::Damage,
+        EventCategory::Kill,
+        EventCategory::Pickup,
+        EventCategory::WeaponSwitch,
+        EventCategory::BotTarget,
+        EventCategory::Trigger,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventCategory::Damage => "Damage",
+            EventCategory::Kill => "Kill",
+            EventCategory::Pickup => "Pickup",
+            EventCategory::WeaponSwitch => "Weapon",
+            EventCategory::BotTarget => "Bot Target",
+            EventCategory::Trigger => "Trigger",
+        }
+    }
+
+    pub fn display_name(self) -> String {
+        tr(match self {
+            EventCategory::Damage => "event_log.damage",
+            EventCategory::Kill => "event_log.kill",
+            EventCategory::Pickup => "event_log.pickup",
+            EventCategory::WeaponSwitch => "event_log.weapon",
+            EventCategory::BotTarget => "event_log.bot_target",
+            EventCategory::Trigger => "event_log.trigger",
+        })
+    }
+}
+
+pub struct EventLogEntry {
+    pub time: f32,
+    pub category: EventCategory,
+    pub text: String,
+}
+
+pub struct EventLog {
+    entries: VecDeque<EventLogEntry>,
+    filter: [bool; EventCategory::ALL.len()],
+    // Incremented on every change, used by UI to rebuild its content only when needed.
+    revision: u64,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::with_capacity(EVENT_LOG_CAPACITY),
+            filter: [true; EventCategory::ALL.len()],
+            revision: 0,
+        }
+    }
+}
+
+impl EventLog {
+    pub fn record(&mut self, time: f32, category: EventCategory, text: String) {
+        if self.entries.len() >= EVENT_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(EventLogEntry {
+            time,
+            category,
+            text,
+        });
+        self.revision += 1;
+    }
+
+    pub fn set_category_enabled(&mut self, category: EventCategory, enabled: bool) {
+        self.filter[category as usize] = enabled;
+        self.revision += 1;
+    }
+
+    pub fn is_category_enabled(&self, category: EventCategory) -> bool {
+        self.filter[category as usize]
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns every entry that passes current category filter, oldest first.
+    pub fn filtered(&self) -> impl Iterator<Item = &EventLogEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| self.is_category_enabled(entry.category))
+    }
+
+    /// Writes every entry (ignoring filter) to given file.
+    pub fn dump<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        for entry in self.entries.iter() {
+            writeln!(
+                file,
+                "[{:>8.2}] [{}] {}",
+                entry.time,
+                entry.category.name(),
+                entry.text
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct EventLogPanel {
+    window: Handle<UiNode>,
+    text: Handle<UiNode>,
+    category_check_boxes: Vec<(EventCategory, Handle<UiNode>)>,
+    visible: bool,
+    last_revision: Option<u64>,
+    content: String,
+}
+
+impl EventLogPanel {
+    pub fn new(engine: &mut Engine) -> Self {
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let mut category_check_boxes = Vec::new();
+        let mut filter_children = Vec::new();
+        for (i, category) in EventCategory::ALL.iter().enumerate() {
+            let check_box = create_check_box(ctx, 0, i * 2, true);
+            filter_children.push(check_box);
+            filter_children.push(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(i * 2 + 1)
+                        .with_margin(Thickness::uniform(2.0)),
+                )
+                .with_text(&category.display_name())
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx),
+            );
+            category_check_boxes.push((*category, check_box));
+        }
+        let mut filters = GridBuilder::new(WidgetBuilder::new().with_children(filter_children))
+            .add_row(Row::strict(26.0));
+        for _ in EventCategory::ALL.iter() {
+            filters = filters
+                .add_column(Column::strict(28.0))
+                .add_column(Column::auto());
+        }
+        let filters = filters.build(ctx);
+
+        let text;
+        let content = GridBuilder::new(
+            WidgetBuilder::new().with_child(filters).with_child(
+                ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
+                    .with_content({
+                        text = TextBuilder::new(WidgetBuilder::new()).build(ctx);
+                        text
+                    })
+                    .build(ctx),
+            ),
+        )
+        .add_row(Row::strict(30.0))
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(700.0).with_height(400.0))
+            .with_title(WindowTitle::text(tr("event_log.title")))
+            .open(false)
+            .with_content(content)
+            .build(ctx);
+
+        Self {
+            window,
+            text,
+            category_check_boxes,
+            visible: false,
+            last_revision: None,
+            content: String::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, ui: &UserInterface, visible: bool) {
+        self.visible = visible;
+        if visible {
+            ui.send_message(WindowMessage::open(
+                self.window,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            // Force refresh, log could change a lot while panel was hidden.
+            self.last_revision = None;
+        } else {
+            ui.send_message(WindowMessage::close(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    pub fn sync_to_model(&mut self, ui: &UserInterface, log: &EventLog) {
+        if !self.visible || self.last_revision == Some(log.revision()) {
+            return;
+        }
+        self.last_revision = Some(log.revision());
+
+        self.content.clear();
+        for entry in log.filtered() {
+            writeln!(
+                self.content,
+                "[{:>8.2}] [{}] {}",
+                entry.time,
+                entry.category.name(),
+                entry.text
+            )
+            .unwrap();
+        }
+
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            self.content.clone(),
+        ));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, log: &mut EventLog) {
+        if let Some(CheckBoxMessage::Check(value)) = message.data() {
+            if message.direction() == MessageDirection::FromWidget {
+                for (category, check_box) in self.category_check_boxes.iter() {
+                    if message.destination() == *check_box {
+                        log.set_category_enabled(*category, value.unwrap_or(false));
+                    }
+                }
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            if message.destination() == self.window {
+                self.visible = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Gamepad input. Engine's event loop does not report gamepad events, so gamepads are polled
+//! separately using gilrs and their events are converted into game-specific events which are
+//! then passed to the same places as window events.
+
+use fyrox::utils::log::{Log, MessageKind};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GamepadEvent {
+    Button { index: u32, pressed: bool },
+    Axis { axis: GamepadAxis, value: f32 },
+}
+
+// Index of a button is its position in this table, it is used in config files so the order
+// must not change.
+const BUTTONS: [(Button, &str); 17] = [
+    (Button::South, "Pad A"),
+    (Button::East, "Pad B"),
+    (Button::North, "Pad Y"),
+    (Button::West, "Pad X"),
+    (Button::LeftTrigger, "Pad LB"),
+    (Button::RightTrigger, "Pad RB"),
+    (Button::LeftTrigger2, "Pad LT"),
+    (Button::RightTrigger2, "Pad RT"),
+    (Button::Select, "Pad Back"),
+    (Button::Start, "Pad Start"),
+    (Button::Mode, "Pad Guide"),
+    (Button::LeftThumb, "Pad LS"),
+    (Button::RightThumb, "Pad RS"),
+    (Button::DPadUp, "Pad Up"),
+    (Button::DPadDown, "Pad Down"),
+    (Button::DPadLeft, "Pad Left"),
+    (Button::DPadRight, "Pad Right"),
+];
+
+pub const BUTTON_SOUTH: u32 = 0;
+pub const BUTTON_EAST: u32 = 1;
+pub const BUTTON_NORTH: u32 = 2;
+pub const BUTTON_WEST: u32 = 3;
+pub const BUTTON_LEFT_BUMPER: u32 = 4;
+pub const BUTTON_RIGHT_BUMPER: u32 = 5;
+pub const BUTTON_SELECT: u32 = 8;
+pub const BUTTON_LEFT_THUMB: u32 = 11;
+pub const BUTTON_RIGHT_THUMB: u32 = 12;
+pub const BUTTON_DPAD_UP: u32 = 13;
+
+pub fn button_name(index: u32) -> &'static str {
+    BUTTONS
+        .get(index as usize)
+        .map(|(_, name)| *name)
+        .unwrap_or("Pad Unknown")
+}
+
+fn button_index(button: Button) -> Option<u32> {
+    BUTTONS
+        .iter()
+        .position(|(b, _)| *b == button)
+        .map(|i| i as u32)
+}
+
+pub struct GamepadInput {
+    // Can be None if there is no gamepad support on current platform.
+    gilrs: Option<Gilrs>,
+}
+
+impl GamepadInput {
+    pub fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Gamepad support is unavailable. Reason: {}", e),
+                );
+                None
+            }
+        };
+        Self { gilrs }
+    }
+
+    /// Collects every pending gamepad event. Events from all connected gamepads are merged,
+    /// so it does not matter which one is used.
+    pub fn poll(&mut self, events: &mut Vec<GamepadEvent>) {
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(index) = button_index(button) {
+                        events.push(GamepadEvent::Button {
+                            index,
+                            pressed: true,
+                        });
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(index) = button_index(button) {
+                        events.push(GamepadEvent::Button {
+                            index,
+                            pressed: false,
+                        });
+                    }
+                }
+                // Most of gamepads report triggers as analog buttons.
+                EventType::ButtonChanged(Button::LeftTrigger2, value, _) => {
+                    events.push(GamepadEvent::Axis {
+                        axis: GamepadAxis::LeftTrigger,
+                        value,
+                    });
+                }
+                EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+                    events.push(GamepadEvent::Axis {
+                        axis: GamepadAxis::RightTrigger,
+                        value,
+                    });
+                }
+                EventType::AxisChanged(axis, value, _) => {
+                    let axis = match axis {
+                        Axis::LeftStickX => GamepadAxis::LeftStickX,
+                        Axis::LeftStickY => GamepadAxis::LeftStickY,
+                        Axis::RightStickX => GamepadAxis::RightStickX,
+                        Axis::RightStickY => GamepadAxis::RightStickY,
+                        Axis::LeftZ => GamepadAxis::LeftTrigger,
+                        Axis::RightZ => GamepadAxis::RightTrigger,
+                        _ => continue,
+                    };
+                    events.push(GamepadEvent::Axis { axis, value });
+                }
+                _ => (),
+            }
+        }
+    }
+}
@@ -2,12 +2,35 @@
 //! However most of the styles are used from dark theme of fyrox-ui library so there
 //! is not much.
 
-use fyrox::core::pool::Handle;
+use crate::localization::tr;
+use fyrox::core::{color::Color, pool::Handle};
+use fyrox::engine::resource_manager::ResourceManager;
 use fyrox::gui::{
-    check_box::CheckBoxBuilder, scroll_bar::ScrollBarBuilder, scroll_viewer::ScrollViewerBuilder,
-    widget::WidgetBuilder, BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode,
+    border::BorderBuilder,
+    brush::Brush,
+    button::{ButtonBuilder, ButtonMessage},
+    check_box::CheckBoxBuilder,
+    dropdown_list::DropdownListBuilder,
+    formatted_text::WrapMode,
+    grid::{Column, GridBuilder, Row},
+    image::ImageBuilder,
+    message::{MessageDirection, UiMessage},
+    scroll_bar::ScrollBarBuilder,
+    scroll_viewer::ScrollViewerBuilder,
+    stack_panel::StackPanelBuilder,
+    text::{TextBuilder, TextMessage},
+    ttf::SharedFont,
+    widget::{WidgetBuilder, WidgetMessage},
+    window::{WindowBuilder, WindowMessage, WindowTitle},
+    BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
     VerticalAlignment,
 };
+use fyrox::utils;
+use std::path::Path;
+
+const MENU_BUTTON_HEIGHT: f32 = 60.0;
+const DIALOG_WIDTH: f32 = 320.0;
+const DIALOG_BUTTON_WIDTH: f32 = 100.0;
 
 pub struct ScrollBarData {
     pub min: f32,
@@ -94,3 +117,393 @@ pub fn create_scroll_viewer(ctx: &mut BuildContext) -> Handle<UiNode> {
         ))
         .build(ctx)
 }
+
+/// Creates an image that fills a full screen widget. Background images are optional, so
+/// `None` is returned when there is no such file and the screen keeps its plain color.
+pub fn create_background_image(
+    ctx: &mut BuildContext,
+    resource_manager: &ResourceManager,
+    path: &str,
+) -> Option<Handle<UiNode>> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+    Some(
+        ImageBuilder::new(WidgetBuilder::new())
+            .with_texture(utils::into_gui_texture(
+                resource_manager.request_texture(path),
+            ))
+            .build(ctx),
+    )
+}
+
+/// Scroll bar with a caption on its left. Caption takes `data.column` and the scroll bar the
+/// next column of the same row. Current value is always shown by the scroll bar itself, so it
+/// can't get out of sync.
+pub struct LabeledSlider {
+    pub caption: Handle<UiNode>,
+    /// Sends `ScrollBarMessage::Value` when changed.
+    pub scroll_bar: Handle<UiNode>,
+}
+
+impl LabeledSlider {
+    /// Both widgets, to be added to a grid.
+    pub fn widgets(&self) -> [Handle<UiNode>; 2] {
+        [self.caption, self.scroll_bar]
+    }
+}
+
+pub fn create_labeled_slider(
+    ctx: &mut BuildContext,
+    caption: &str,
+    data: ScrollBarData,
+) -> LabeledSlider {
+    let caption = TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(data.row)
+            .on_column(data.column)
+            .with_margin(data.margin),
+    )
+    .with_text(caption)
+    .with_vertical_text_alignment(VerticalAlignment::Center)
+    .build(ctx);
+    let scroll_bar = create_scroll_bar(
+        ctx,
+        ScrollBarData {
+            column: data.column + 1,
+            show_value: true,
+            ..data
+        },
+    );
+    LabeledSlider {
+        caption,
+        scroll_bar,
+    }
+}
+
+/// Drop-down list of text options, sends `DropdownListMessage::SelectionChanged` with index
+/// of the option.
+pub fn create_dropdown(
+    ctx: &mut BuildContext,
+    row: usize,
+    column: usize,
+    items: &[String],
+    selected: usize,
+) -> Handle<UiNode> {
+    let items = items
+        .iter()
+        .map(|item| {
+            TextBuilder::new(WidgetBuilder::new().with_height(25.0))
+                .with_text(item)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx)
+        })
+        .collect();
+    DropdownListBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(column)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_items(items)
+    .with_selected(selected)
+    .with_close_on_selection(true)
+    .build(ctx)
+}
+
+/// Button of the standard look, `widget` only sets its place and size.
+pub fn create_button(ctx: &mut BuildContext, widget: WidgetBuilder, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(widget.with_margin(Thickness::uniform(2.0)))
+        .with_text(text)
+        .build(ctx)
+}
+
+/// Big button of menus like the pause menu.
+pub fn create_menu_button(ctx: &mut BuildContext, text: &str, font: SharedFont) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_height(MENU_BUTTON_HEIGHT)
+            .with_margin(Thickness::uniform(4.0)),
+    )
+    .with_text_and_font(text, font)
+    .build(ctx)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DialogResult {
+    Ok,
+    Cancel,
+}
+
+/// Modal dialog with OK and Cancel buttons, see [`create_confirmation_dialog`].
+pub struct DialogHandles {
+    /// Dims everything under the dialog, it is removed along with the dialog.
+    pub root: Handle<UiNode>,
+    pub window: Handle<UiNode>,
+    pub text: Handle<UiNode>,
+    pub ok: Handle<UiNode>,
+    pub cancel: Handle<UiNode>,
+}
+
+impl DialogHandles {
+    pub fn open(&self, ui: &UserInterface) {
+        // Screen may have been resized since the dialog was open last time.
+        let screen_size = ui.screen_size();
+        ui.send_message(WidgetMessage::width(
+            self.root,
+            MessageDirection::ToWidget,
+            screen_size.x,
+        ));
+        ui.send_message(WidgetMessage::height(
+            self.root,
+            MessageDirection::ToWidget,
+            screen_size.y,
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        ui.send_message(WidgetMessage::topmost(
+            self.root,
+            MessageDirection::ToWidget,
+        ));
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    /// Opens the dialog with new text.
+    pub fn open_with_text(&self, ui: &UserInterface, text: String) {
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+        self.open(ui);
+    }
+
+    pub fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    pub fn is_open(&self, ui: &UserInterface) -> bool {
+        ui.node(self.root).visibility()
+    }
+
+    /// Returns the answer when one of the buttons is clicked, the dialog is closed then.
+    pub fn handle_ui_message(
+        &self,
+        ui: &UserInterface,
+        message: &UiMessage,
+    ) -> Option<DialogResult> {
+        if let Some(ButtonMessage::Click) = message.data() {
+            let result = if message.destination() == self.ok {
+                DialogResult::Ok
+            } else if message.destination() == self.cancel {
+                DialogResult::Cancel
+            } else {
+                return None;
+            };
+            self.close(ui);
+            return Some(result);
+        }
+        None
+    }
+}
+
+pub fn create_confirmation_dialog(
+    ctx: &mut BuildContext,
+    title: &str,
+    text: &str,
+) -> DialogHandles {
+    let text = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(8.0)))
+        .with_text(text)
+        .with_wrap(WrapMode::Word)
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .build(ctx);
+    let ok = create_button(
+        ctx,
+        WidgetBuilder::new().with_width(DIALOG_BUTTON_WIDTH),
+        &tr("dialog.ok"),
+    );
+    let cancel = create_button(
+        ctx,
+        WidgetBuilder::new().with_width(DIALOG_BUTTON_WIDTH),
+        &tr("dialog.cancel"),
+    );
+
+    let window = WindowBuilder::new(
+        WidgetBuilder::new()
+            .on_row(1)
+            .on_column(1)
+            .with_width(DIALOG_WIDTH),
+    )
+    .can_resize(false)
+    .can_minimize(false)
+    .can_close(false)
+    .open(false)
+    .with_title(WindowTitle::text(title))
+    .with_content(
+        GridBuilder::new(
+            WidgetBuilder::new().with_child(text).with_child(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(1)
+                        .with_margin(Thickness::uniform(4.0))
+                        .with_horizontal_alignment(HorizontalAlignment::Right)
+                        .with_child(ok)
+                        .with_child(cancel),
+                )
+                .with_orientation(Orientation::Horizontal)
+                .build(ctx),
+            ),
+        )
+        .add_row(Row::auto())
+        .add_row(Row::auto())
+        .add_column(Column::stretch())
+        .build(ctx),
+    )
+    .build(ctx);
+
+    let root = BorderBuilder::new(
+        WidgetBuilder::new()
+            .with_visibility(false)
+            .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 140)))
+            .with_child(
+                GridBuilder::new(WidgetBuilder::new().with_child(window))
+                    .add_row(Row::stretch())
+                    .add_row(Row::auto())
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::auto())
+                    .add_column(Column::stretch())
+                    .build(ctx),
+            ),
+    )
+    .with_stroke_thickness(Thickness::uniform(0.0))
+    .build(ctx);
+
+    DialogHandles {
+        root,
+        window,
+        text,
+        ok,
+        cancel,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fyrox::{
+        core::algebra::Vector2,
+        gui::{dropdown_list::DropdownListMessage, scroll_bar::ScrollBarMessage},
+    };
+
+    fn poll_messages(ui: &mut UserInterface) -> Vec<UiMessage> {
+        let mut messages = Vec::new();
+        while let Some(message) = ui.poll_message() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn slider_data() -> ScrollBarData {
+        ScrollBarData {
+            min: 0.0,
+            max: 100.0,
+            value: 50.0,
+            step: 1.0,
+            row: 0,
+            column: 0,
+            margin: Default::default(),
+            show_value: false,
+            orientation: Orientation::Horizontal,
+        }
+    }
+
+    #[test]
+    fn labeled_slider_reports_clamped_value() {
+        let mut ui = UserInterface::new(Vector2::new(800.0, 600.0));
+        let slider = create_labeled_slider(&mut ui.build_ctx(), "Volume", slider_data());
+        poll_messages(&mut ui);
+
+        ui.send_message(ScrollBarMessage::value(
+            slider.scroll_bar,
+            MessageDirection::ToWidget,
+            150.0,
+        ));
+        let values = poll_messages(&mut ui)
+            .iter()
+            .filter(|message| {
+                message.destination() == slider.scroll_bar
+                    && message.direction() == MessageDirection::FromWidget
+            })
+            .filter_map(|message| match message.data() {
+                Some(ScrollBarMessage::Value(value)) => Some(*value),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![100.0]);
+    }
+
+    #[test]
+    fn dropdown_reports_selection() {
+        let mut ui = UserInterface::new(Vector2::new(800.0, 600.0));
+        let items = vec!["Low".to_owned(), "Medium".to_owned(), "High".to_owned()];
+        let dropdown = create_dropdown(&mut ui.build_ctx(), 0, 0, &items, 0);
+        poll_messages(&mut ui);
+
+        ui.send_message(DropdownListMessage::selection(
+            dropdown,
+            MessageDirection::ToWidget,
+            Some(2),
+        ));
+        assert!(poll_messages(&mut ui).iter().any(|message| {
+            message.destination() == dropdown
+                && message.direction() == MessageDirection::FromWidget
+                && matches!(
+                    message.data(),
+                    Some(DropdownListMessage::SelectionChanged(Some(2)))
+                )
+        }));
+    }
+
+    #[test]
+    fn confirmation_dialog_answers_and_closes() {
+        let mut ui = UserInterface::new(Vector2::new(800.0, 600.0));
+        let dialog = create_confirmation_dialog(&mut ui.build_ctx(), "Quit", "Really quit?");
+        poll_messages(&mut ui);
+        assert!(!dialog.is_open(&ui));
+
+        for (button, result) in [
+            (dialog.ok, DialogResult::Ok),
+            (dialog.cancel, DialogResult::Cancel),
+        ] {
+            dialog.open(&ui);
+            poll_messages(&mut ui);
+            assert!(dialog.is_open(&ui));
+
+            let click = ButtonMessage::click(button, MessageDirection::FromWidget);
+            assert_eq!(dialog.handle_ui_message(&ui, &click), Some(result));
+            poll_messages(&mut ui);
+            assert!(!dialog.is_open(&ui));
+        }
+
+        // Clicks of other buttons are not answers.
+        let other = create_button(&mut ui.build_ctx(), WidgetBuilder::new(), "Other");
+        let click = ButtonMessage::click(other, MessageDirection::FromWidget);
+        assert_eq!(dialog.handle_ui_message(&ui, &click), None);
+    }
+}
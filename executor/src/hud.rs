@@ -1,48 +1,176 @@
 use crate::{
+    actor::Actor,
+    ammo_counter::AmmoCounter,
+    boss_bar::BossBar,
+    captions::CaptionArea,
+    control_scheme::ControlScheme,
+    crosshair::{Crosshair, HitMarkerKind},
+    damage_indicator::DamageIndicators,
+    damage_numbers::{DamageNumberKind, DamageNumbers},
+    gamepad::GamepadEvent,
+    kill_feed::{KillFeed, KillFeedEntry},
     leader_board::{LeaderBoard, LeaderBoardUI},
+    localization::tr,
     message::Message,
+    radar::Radar,
+    status_panel::StatusPanel,
+    toast::ToastArea,
+    weapon::{CrosshairStyle, WeaponKind},
+    weapon_wheel::WeaponWheel,
     GameTime, MatchOptions,
 };
 use fyrox::core::parking_lot::Mutex;
 use fyrox::{
-    core::{color::Color, pool::Handle},
+    core::{
+        algebra::{Matrix4, Vector2},
+        color::Color,
+        pool::Handle,
+    },
     engine::Engine,
     event::{Event, WindowEvent},
     gui::{
         border::BorderBuilder,
         brush::Brush,
         grid::{Column, GridBuilder, Row},
-        image::ImageBuilder,
         message::MessageDirection,
-        stack_panel::StackPanelBuilder,
         text::{TextBuilder, TextMessage},
         ttf::{Font, SharedFont},
         widget::{WidgetBuilder, WidgetMessage},
-        HorizontalAlignment, Orientation, Thickness, VerticalAlignment,
+        HorizontalAlignment, Thickness, VerticalAlignment,
     },
     gui::{UiNode, UserInterface},
-    utils,
 };
-use std::{collections::VecDeque, path::Path, sync::Arc};
+use std::{path::Path, sync::Arc};
+
+/// Duration of the screen flash on damage or heal.
+const FLASH_DURATION: f32 = 0.5;
+/// Damage that produces flash of full intensity.
+const FULL_FLASH_DAMAGE: f32 = 50.0;
+const HEAL_FLASH_INTENSITY: f32 = 0.15;
+const MAX_FLASH_ALPHA: f32 = 160.0;
+
+/// State of the player that is shown on HUD. It is filled by the level every frame, so HUD
+/// knows nothing about actors and weapons.
+pub struct HudData {
+    pub health: f32,
+    pub max_health: f32,
+    pub armor: f32,
+    pub max_armor: f32,
+    /// Current weapon, `None` when there is no weapon in hands.
+    pub weapon: Option<WeaponHudData>,
+    /// Recent damage taken by the player, empty if indicators are disabled.
+    pub damage_indicators: Vec<DamageIndicator>,
+    /// `None` while the weapon wheel is closed.
+    pub weapon_wheel: Option<WeaponWheelData>,
+    /// View-projection matrix of the player's camera, world-anchored widgets use it.
+    pub view_projection: Matrix4<f32>,
+    /// `None` when the radar is disabled or not allowed on current difficulty.
+    pub radar: Option<RadarData>,
+}
+
+pub struct RadarData {
+    /// Size of the radar on screen in pixels.
+    pub size: f32,
+    pub blips: Vec<RadarBlip>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RadarBlipKind {
+    Enemy,
+    Ally,
+    /// Key item with the path to its icon.
+    Item(&'static str),
+}
+
+pub struct RadarBlip {
+    pub kind: RadarBlipKind,
+    /// Offset from the player in meters in the player's view, `x` goes to the right and `y`
+    /// goes forward.
+    pub offset: Vector2<f32>,
+}
+
+pub struct WeaponWheelData {
+    /// Weapons in order of the player's inventory.
+    pub items: Vec<WeaponWheelItem>,
+    pub highlighted: usize,
+}
+
+pub struct WeaponWheelItem {
+    pub kind: WeaponKind,
+    pub icon: &'static str,
+    pub ammo: u32,
+    pub is_melee: bool,
+}
+
+pub struct DamageIndicator {
+    /// Clockwise angle (in radians) between view direction and direction to the source of
+    /// damage, `None` for damage without source (falls, hazards).
+    pub angle: Option<f32>,
+    /// Fades from 1.0 to 0.0 as damage gets older.
+    pub intensity: f32,
+}
+
+/// Boss the player fights, it is shown as a health bar at the top of the screen.
+pub struct BossBarData {
+    pub actor: Handle<Actor>,
+    pub name: String,
+    pub health: f32,
+    pub max_health: f32,
+    /// Health fractions where next phase begins, see `BossDefinition::phases`.
+    pub phases: &'static [f32],
+}
+
+pub struct WeaponHudData {
+    pub kind: WeaponKind,
+    pub icon: &'static str,
+    /// Total ammo, both in the magazine and in reserve.
+    pub ammo: u32,
+    pub max_ammo: u32,
+    pub ammo_in_magazine: u32,
+    pub reserve_ammo: u32,
+    pub is_reloading: bool,
+    /// Heat in [0; 1] range, `None` for weapons that never overheat.
+    pub heat: Option<f32>,
+    pub is_overheated: bool,
+    pub is_melee: bool,
+    /// `None` hides the crosshair.
+    pub crosshair: Option<CrosshairStyle>,
+    /// Current inaccuracy in [0; 1] range.
+    pub spread: f32,
+}
 
 pub struct Hud {
     root: Handle<UiNode>,
-    health: Handle<UiNode>,
-    armor: Handle<UiNode>,
-    ammo: Handle<UiNode>,
+    status_panel: StatusPanel,
+    ammo_counter: AmmoCounter,
+    crosshair: Crosshair,
+    damage_indicators: DamageIndicators,
+    damage_numbers: DamageNumbers,
+    boss_bar: BossBar,
+    radar: Radar,
+    weapon_wheel: WeaponWheel,
+    kill_feed: KillFeed,
+    weapons: Handle<UiNode>,
+    use_prompt: Handle<UiNode>,
     time: Handle<UiNode>,
-    message: Handle<UiNode>,
-    message_queue: VecDeque<String>,
-    message_timeout: f32,
+    toasts: ToastArea,
+    captions: CaptionArea,
     leader_board: LeaderBoardUI,
     match_limit: Handle<UiNode>,
     first_score: Handle<UiNode>,
     second_score: Handle<UiNode>,
     died: Handle<UiNode>,
+    // Total of health and armor on previous frame, used to detect damage and heal.
+    last_vitality: Option<f32>,
+    flash_color: Color,
+    flash_intensity: f32,
+    flash_time: f32,
+    // Camera of the player from the last `set_data`, `None` while there is no player.
+    view_projection: Option<Matrix4<f32>>,
 }
 
 impl Hud {
-    pub fn new(engine: &mut Engine) -> Self {
+    pub fn new(engine: &mut Engine, caption_size: f32) -> Self {
         let leader_board = LeaderBoardUI::new(engine);
 
         let frame_size = engine.renderer.get_frame_size();
@@ -57,35 +185,32 @@ impl Hud {
         .unwrap();
         let font = SharedFont(Arc::new(Mutex::new(font)));
 
-        let health;
-        let armor;
-        let ammo;
-        let message;
+        let status_panel = StatusPanel::new(ctx, &resource_manager, font.clone(), 0, 0);
+        let ammo_counter = AmmoCounter::new(ctx, resource_manager.clone(), 0, 2);
+        let weapon_wheel = WeaponWheel::new(ctx, resource_manager.clone(), 0, 1);
+        let toasts = ToastArea::new(ctx, resource_manager.clone(), 0, 1);
+        let radar = Radar::new(ctx, resource_manager.clone(), 0, 0);
+        let captions = CaptionArea::new(ctx, caption_size, 0, 1);
+        let kill_feed = KillFeed::new(ctx, resource_manager, 0, 2);
+        let crosshair = Crosshair::new(ctx, 0, 1);
+        let damage_indicators = DamageIndicators::new(ctx, 0, 1);
+        let damage_numbers = DamageNumbers::new(ctx);
+        let boss_bar = BossBar::new(ctx, 0, 1);
+        let weapons;
+        let use_prompt;
         let time;
         let first_score;
         let second_score;
         let match_limit;
         let died;
-        let root = GridBuilder::new(
+        let grid = GridBuilder::new(
             WidgetBuilder::new()
-                .with_width(frame_size.0 as f32)
-                .with_height(frame_size.1 as f32)
-                .with_visibility(false)
-                .with_child(
-                    ImageBuilder::new(
-                        WidgetBuilder::new()
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_width(33.0)
-                            .with_height(33.0)
-                            .on_row(0)
-                            .on_column(1),
-                    )
-                    .with_texture(utils::into_gui_texture(
-                        resource_manager.request_texture("data/ui/crosshair.tga"),
-                    ))
-                    .build(ctx),
-                )
+                .with_child(status_panel.root)
+                .with_child(crosshair.root)
+                .with_child(damage_indicators.root)
+                .with_child(weapon_wheel.root)
+                .with_child(boss_bar.root)
+                .with_child(radar.root)
                 .with_child({
                     time = TextBuilder::new(
                         WidgetBuilder::new()
@@ -193,144 +318,43 @@ impl Hud {
                     .add_row(Row::strict(33.0))
                     .build(ctx),
                 )
-                .with_child(
-                    StackPanelBuilder::new(
-                        WidgetBuilder::new()
-                            .with_margin(Thickness::bottom(10.0))
-                            .on_column(0)
-                            .with_vertical_alignment(VerticalAlignment::Bottom)
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_child(
-                                ImageBuilder::new(
-                                    WidgetBuilder::new().with_width(35.0).with_height(35.0),
-                                )
-                                .with_texture(utils::into_gui_texture(
-                                    resource_manager.request_texture("data/ui/health_icon.png"),
-                                ))
-                                .build(ctx),
-                            )
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new().with_width(170.0).with_height(35.0),
-                                )
-                                .with_text("Health:")
-                                .with_font(font.clone())
-                                .build(ctx),
-                            )
-                            .with_child({
-                                health = TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_foreground(Brush::Solid(Color::opaque(180, 14, 22)))
-                                        .with_width(170.0)
-                                        .with_height(35.0),
-                                )
-                                .with_text("100")
-                                .with_font(font.clone())
-                                .build(ctx);
-                                health
-                            }),
-                    )
-                    .with_orientation(Orientation::Horizontal)
-                    .build(ctx),
-                )
-                .with_child(
-                    StackPanelBuilder::new(
-                        WidgetBuilder::new()
-                            .with_margin(Thickness::bottom(10.0))
-                            .on_column(1)
-                            .with_vertical_alignment(VerticalAlignment::Bottom)
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_child(
-                                ImageBuilder::new(
-                                    WidgetBuilder::new().with_width(35.0).with_height(35.0),
-                                )
-                                .with_texture(utils::into_gui_texture(
-                                    resource_manager.request_texture("data/ui/ammo_icon.png"),
-                                ))
-                                .build(ctx),
-                            )
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new().with_width(170.0).with_height(35.0),
-                                )
-                                .with_font(font.clone())
-                                .with_text("Ammo:")
-                                .build(ctx),
-                            )
-                            .with_child({
-                                ammo = TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_foreground(Brush::Solid(Color::opaque(79, 79, 255)))
-                                        .with_width(170.0)
-                                        .with_height(35.0),
-                                )
-                                .with_font(font.clone())
-                                .with_text("40")
-                                .build(ctx);
-                                ammo
-                            }),
-                    )
-                    .with_orientation(Orientation::Horizontal)
-                    .build(ctx),
-                )
-                .with_child(
-                    StackPanelBuilder::new(
+                .with_child(ammo_counter.root)
+                .with_child(kill_feed.root)
+                .with_child(toasts.root)
+                .with_child(captions.root)
+                .with_child({
+                    weapons = TextBuilder::new(
                         WidgetBuilder::new()
-                            .with_margin(Thickness::bottom(10.0))
+                            .on_row(0)
                             .on_column(2)
                             .with_vertical_alignment(VerticalAlignment::Bottom)
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_child(
-                                ImageBuilder::new(
-                                    WidgetBuilder::new().with_width(35.0).with_height(35.0),
-                                )
-                                .with_texture(utils::into_gui_texture(
-                                    resource_manager.request_texture("data/ui/shield_icon.png"),
-                                ))
-                                .build(ctx),
-                            )
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new().with_width(170.0).with_height(35.0),
-                                )
-                                .with_font(font.clone())
-                                .with_text("Armor:")
-                                .build(ctx),
-                            )
-                            .with_child({
-                                armor = TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_foreground(Brush::Solid(Color::opaque(255, 100, 26)))
-                                        .with_width(170.0)
-                                        .with_height(35.0),
-                                )
-                                .with_font(font.clone())
-                                .with_text("100")
-                                .build(ctx);
-                                armor
+                            .with_horizontal_alignment(HorizontalAlignment::Right)
+                            .with_margin(Thickness {
+                                left: 0.0,
+                                top: 0.0,
+                                right: 30.0,
+                                bottom: 105.0,
                             }),
                     )
-                    .with_orientation(Orientation::Horizontal)
-                    .build(ctx),
-                )
+                    .build(ctx);
+                    weapons
+                })
                 .with_child({
-                    message = TextBuilder::new(
+                    use_prompt = TextBuilder::new(
                         WidgetBuilder::new()
                             .on_row(0)
-                            .on_column(0)
+                            .on_column(1)
                             .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_horizontal_alignment(HorizontalAlignment::Left)
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
                             .with_margin(Thickness {
-                                left: 45.0,
-                                top: 30.0,
+                                left: 0.0,
+                                top: 120.0,
                                 right: 0.0,
                                 bottom: 0.0,
-                            })
-                            .with_height(40.0)
-                            .with_width(400.0),
+                            }),
                     )
                     .build(ctx);
-                    message
+                    use_prompt
                 })
                 .with_child({
                     died = TextBuilder::new(
@@ -343,7 +367,7 @@ impl Hud {
                             .with_horizontal_alignment(HorizontalAlignment::Center),
                     )
                     .with_font(font)
-                    .with_text("You Died")
+                    .with_text(&tr("death.you_died"))
                     .build(ctx);
                     died
                 }),
@@ -354,44 +378,126 @@ impl Hud {
         .add_row(Row::stretch())
         .build(ctx);
 
+        // Root is also a full-screen overlay that flashes on damage and heal.
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_child(grid)
+                .with_child(damage_numbers.root),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
         Self {
             leader_board,
             root,
-            health,
-            armor,
-            ammo,
-            message,
+            status_panel,
+            ammo_counter,
+            crosshair,
+            damage_indicators,
+            damage_numbers,
+            boss_bar,
+            radar,
+            weapon_wheel,
+            kill_feed,
+            weapons,
+            use_prompt,
+            toasts,
+            captions,
             time,
             first_score,
             second_score,
             match_limit,
             died,
-            message_timeout: 0.0,
-            message_queue: Default::default(),
+            last_vitality: None,
+            flash_color: Color::TRANSPARENT,
+            flash_intensity: 0.0,
+            flash_time: 0.0,
+            view_projection: None,
         }
     }
 
-    pub fn set_health(&mut self, ui: &mut UserInterface, health: f32) {
-        ui.send_message(TextMessage::text(
-            self.health,
-            MessageDirection::ToWidget,
-            format!("{}", health),
-        ));
+    /// Syncs HUD with the state of the player, `None` means that there is no alive player and
+    /// player's widgets must be hidden.
+    pub fn set_data(&mut self, ui: &mut UserInterface, data: Option<&HudData>) {
+        let data = match data {
+            Some(data) => data,
+            None => {
+                self.last_vitality = None;
+                self.status_panel.set_visible(ui, false);
+                self.ammo_counter.set_weapon(ui, None);
+                self.crosshair.set_style(ui, None, 0.0);
+                self.damage_indicators.set_indicators(ui, &[]);
+                self.weapon_wheel.set_data(ui, None);
+                self.damage_numbers.clear(ui);
+                self.radar.set_data(ui, None);
+                self.view_projection = None;
+                return;
+            }
+        };
+
+        let vitality = data.health + data.armor;
+        match self.last_vitality {
+            Some(last) if vitality < last => {
+                let intensity = ((last - vitality) / FULL_FLASH_DAMAGE).min(1.0);
+                self.flash(Color::opaque(200, 0, 0), intensity);
+            }
+            Some(last) if vitality > last => {
+                self.flash(Color::opaque(0, 200, 0), HEAL_FLASH_INTENSITY)
+            }
+            None => self.status_panel.set_visible(ui, true),
+            _ => (),
+        }
+        self.last_vitality = Some(vitality);
+
+        self.status_panel
+            .set_values(ui, data.health, data.max_health, data.armor, data.max_armor);
+        self.ammo_counter.set_weapon(ui, data.weapon.as_ref());
+        match data.weapon.as_ref() {
+            Some(weapon) => self
+                .crosshair
+                .set_style(ui, weapon.crosshair, weapon.spread),
+            None => self.crosshair.set_style(ui, None, 0.0),
+        }
+        self.damage_indicators
+            .set_indicators(ui, &data.damage_indicators);
+        self.weapon_wheel.set_data(ui, data.weapon_wheel.as_ref());
+        self.radar.set_data(ui, data.radar.as_ref());
+        self.view_projection = Some(data.view_projection);
     }
 
-    pub fn set_armor(&mut self, ui: &mut UserInterface, armor: f32) {
+    fn flash(&mut self, color: Color, intensity: f32) {
+        // Stronger flash is not interrupted by a weaker one.
+        if intensity >= self.current_flash_intensity() {
+            self.flash_color = color;
+            self.flash_intensity = intensity;
+            self.flash_time = FLASH_DURATION;
+        }
+    }
+
+    fn current_flash_intensity(&self) -> f32 {
+        self.flash_intensity * self.flash_time / FLASH_DURATION
+    }
+
+    /// Sets list of weapons of the player, every line is prefixed with the slot number of
+    /// the weapon.
+    pub fn set_weapons(&mut self, ui: &mut UserInterface, weapons: String) {
         ui.send_message(TextMessage::text(
-            self.armor,
+            self.weapons,
             MessageDirection::ToWidget,
-            format!("{}", armor),
+            weapons,
         ));
     }
 
-    pub fn set_ammo(&mut self, ui: &mut UserInterface, ammo: u32) {
+    /// Sets prompt for the object that can be used, empty prompt hides it.
+    pub fn set_use_prompt(&mut self, ui: &mut UserInterface, prompt: &str) {
         ui.send_message(TextMessage::text(
-            self.ammo,
+            self.use_prompt,
             MessageDirection::ToWidget,
-            format!("{}", ammo),
+            prompt.to_owned(),
         ));
     }
 
@@ -415,6 +521,15 @@ impl Hud {
         ));
     }
 
+    /// Boss bar is shown separately from the player's data, it stays while the player is dead.
+    pub fn set_boss_bar(&mut self, ui: &mut UserInterface, data: Option<&BossBarData>) {
+        self.boss_bar.set_data(ui, data);
+    }
+
+    pub fn set_caption_style(&mut self, ui: &mut UserInterface, enabled: bool, font_size: f32) {
+        self.captions.set_style(ui, enabled, font_size);
+    }
+
     pub fn set_is_died(&mut self, ui: &mut UserInterface, is_died: bool) {
         ui.send_message(WidgetMessage::visibility(
             self.died,
@@ -423,11 +538,12 @@ impl Hud {
         ));
     }
 
-    pub fn add_message<P: AsRef<str>>(&mut self, message: P) {
-        self.message_queue.push_back(message.as_ref().to_owned())
-    }
-
-    pub fn process_event(&mut self, engine: &mut Engine, event: &Event<()>) {
+    pub fn process_event(
+        &mut self,
+        engine: &mut Engine,
+        event: &Event<()>,
+        control_scheme: &ControlScheme,
+    ) {
         if let Event::WindowEvent { event, .. } = event {
             if let WindowEvent::Resized(new_size) = event {
                 engine.user_interface.send_message(WidgetMessage::width(
@@ -443,31 +559,44 @@ impl Hud {
             }
         }
 
-        self.leader_board.process_input_event(engine, event);
+        self.leader_board
+            .process_input_event(engine, event, control_scheme);
+    }
+
+    pub fn process_gamepad_event(
+        &mut self,
+        ui: &mut UserInterface,
+        event: &GamepadEvent,
+        control_scheme: &ControlScheme,
+    ) {
+        self.leader_board
+            .process_gamepad_event(ui, event, control_scheme);
     }
 
-    pub fn leader_board(&self) -> &LeaderBoardUI {
-        &self.leader_board
+    pub fn leader_board_mut(&mut self) -> &mut LeaderBoardUI {
+        &mut self.leader_board
     }
 
     pub fn update(&mut self, ui: &mut UserInterface, time: &GameTime) {
-        self.message_timeout -= time.delta;
+        self.ammo_counter.update(ui, time.delta);
+        self.crosshair.update(ui, time.delta);
+        self.kill_feed.update(ui, time.delta);
+        self.toasts.update(ui, time.delta);
+        self.captions.update(ui, time.delta);
+        self.boss_bar.update(ui, time.delta);
+        if let Some(view_projection) = self.view_projection.as_ref() {
+            self.damage_numbers.update(ui, view_projection, time.delta);
+        }
 
-        if self.message_timeout <= 0.0 {
-            if let Some(message) = self.message_queue.pop_front() {
-                ui.send_message(TextMessage::text(
-                    self.message,
-                    MessageDirection::ToWidget,
-                    message,
-                ));
-                self.message_timeout = 1.25;
-            } else {
-                ui.send_message(TextMessage::text(
-                    self.message,
-                    MessageDirection::ToWidget,
-                    Default::default(),
-                ));
-            }
+        if self.flash_time > 0.0 {
+            self.flash_time = (self.flash_time - time.delta).max(0.0);
+            let mut color = self.flash_color;
+            color.a = (self.current_flash_intensity() * MAX_FLASH_ALPHA) as u8;
+            ui.send_message(WidgetMessage::background(
+                self.root,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
         }
     }
 
@@ -516,7 +645,58 @@ impl Hud {
         match_options: &MatchOptions,
     ) {
         match message {
-            Message::AddNotification { text } => self.add_message(text),
+            Message::Notify {
+                text,
+                icon,
+                priority,
+            } => self.toasts.push(text.clone(), icon.clone(), *priority),
+            Message::ShowCaption { text, duration } => self.captions.push(text.clone(), *duration),
+            &Message::DamageDealtFeedback {
+                target,
+                position,
+                amount,
+                killed,
+                headshot,
+                resisted,
+                show_hit_marker,
+                show_damage_number,
+            } => {
+                if show_hit_marker {
+                    let kind = if killed {
+                        HitMarkerKind::Kill
+                    } else if headshot {
+                        HitMarkerKind::Headshot
+                    } else {
+                        HitMarkerKind::Hit
+                    };
+                    self.crosshair.show_hit_marker(ui, kind, amount);
+                }
+                if show_damage_number {
+                    let kind = if headshot {
+                        DamageNumberKind::Critical
+                    } else if resisted {
+                        DamageNumberKind::Resisted
+                    } else {
+                        DamageNumberKind::Normal
+                    };
+                    self.damage_numbers.push(ui, target, position, amount, kind);
+                }
+            }
+            Message::ActorKilled {
+                killer,
+                victim,
+                victim_team,
+                weapon,
+                headshot,
+                involves_player,
+            } => self.kill_feed.push(KillFeedEntry::new(
+                killer.clone(),
+                victim.clone(),
+                *victim_team,
+                *weapon,
+                *headshot,
+                *involves_player,
+            )),
             Message::AddBot { .. }
             | Message::RemoveActor { .. }
             | Message::RespawnActor { .. }
@@ -526,8 +706,5 @@ impl Hud {
             }
             _ => (),
         }
-
-        self.leader_board
-            .handle_message(message, ui, leader_board, match_options);
     }
 }
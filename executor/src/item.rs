@@ -1,4 +1,13 @@
-use crate::{effects::EffectKind, fyrox::core::math::Vector3Ext, message::Message, GameTime};
+use crate::{
+    effects::EffectKind,
+    error::GameError,
+    fyrox::core::math::Vector3Ext,
+    message::Message,
+    spatial_grid::{GridQuery, SpatialGrid},
+    tunables,
+    weapon::{AmmoType, WeaponKind},
+    GameTime,
+};
 use fyrox::{
     core::{
         algebra::Vector3,
@@ -11,9 +20,10 @@ use fyrox::{
         transform::TransformBuilder, Scene,
     },
 };
+use serde::Deserialize;
 use std::{path::Path, sync::mpsc::Sender};
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Deserialize)]
 pub enum ItemKind {
     Medkit,
 
@@ -21,6 +31,7 @@ pub enum ItemKind {
     Plasma,
     Ak47Ammo,
     M4Ammo,
+    ShotgunShells,
 
     // Weapons
     PlasmaGun,
@@ -28,6 +39,66 @@ pub enum ItemKind {
     M4,
     RocketLauncher,
     BattleAxe,
+    Shotgun,
+}
+
+impl ItemKind {
+    pub const ALL: [ItemKind; 11] = [
+        ItemKind::Medkit,
+        ItemKind::Plasma,
+        ItemKind::Ak47Ammo,
+        ItemKind::M4Ammo,
+        ItemKind::ShotgunShells,
+        ItemKind::PlasmaGun,
+        ItemKind::Ak47,
+        ItemKind::M4,
+        ItemKind::RocketLauncher,
+        ItemKind::BattleAxe,
+        ItemKind::Shotgun,
+    ];
+
+    /// Items worth going for, they're shown on the radar. Ammo is everywhere so it's not.
+    pub fn is_key_item(self) -> bool {
+        !matches!(
+            self,
+            ItemKind::Plasma | ItemKind::Ak47Ammo | ItemKind::M4Ammo | ItemKind::ShotgunShells
+        )
+    }
+
+    /// Item that a dropped weapon of given kind turns into.
+    pub fn from_weapon(kind: WeaponKind) -> Self {
+        match kind {
+            WeaponKind::M4 => ItemKind::M4,
+            WeaponKind::Ak47 => ItemKind::Ak47,
+            WeaponKind::PlasmaRifle => ItemKind::PlasmaGun,
+            WeaponKind::RocketLauncher => ItemKind::RocketLauncher,
+            WeaponKind::BattleAxe => ItemKind::BattleAxe,
+            WeaponKind::Shotgun => ItemKind::Shotgun,
+        }
+    }
+
+    /// Weapon that the item gives, `None` for items that are not weapons.
+    pub fn weapon(self) -> Option<WeaponKind> {
+        match self {
+            ItemKind::M4 => Some(WeaponKind::M4),
+            ItemKind::Ak47 => Some(WeaponKind::Ak47),
+            ItemKind::PlasmaGun => Some(WeaponKind::PlasmaRifle),
+            ItemKind::RocketLauncher => Some(WeaponKind::RocketLauncher),
+            ItemKind::BattleAxe => Some(WeaponKind::BattleAxe),
+            ItemKind::Shotgun => Some(WeaponKind::Shotgun),
+            _ => None,
+        }
+    }
+
+    /// Ammo that the item gives, `None` for items that are not ammo.
+    pub fn ammo_type(self) -> Option<AmmoType> {
+        match self {
+            ItemKind::Plasma => Some(AmmoType::Plasma),
+            ItemKind::Ak47Ammo | ItemKind::M4Ammo => Some(AmmoType::Rifle),
+            ItemKind::ShotgunShells => Some(AmmoType::Shell),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Visit)]
@@ -43,6 +114,8 @@ pub struct Item {
     #[visit(skip)]
     pub sender: Option<Sender<Message>>,
     lifetime: Option<f32>,
+    // Ammo left in the weapon that was dropped as this item, `None` for items of the level.
+    ammo: Option<u32>,
 }
 
 impl Default for Item {
@@ -58,12 +131,16 @@ impl Default for Item {
             active: true,
             sender: None,
             lifetime: None,
+            ammo: None,
         }
     }
 }
 
 pub struct ItemDefinition {
-    model: &'static str,
+    /// Name shown to the player on pickup.
+    pub name: &'static str,
+    pub icon: &'static str,
+    pub model: &'static str,
     scale: f32,
     reactivation_interval: f32,
 }
@@ -73,6 +150,8 @@ impl Item {
         match kind {
             ItemKind::BattleAxe => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Battle Axe",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/ak47.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
@@ -81,6 +160,8 @@ impl Item {
             }
             ItemKind::Medkit => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Medkit",
+                    icon: "data/ui/health_icon.png",
                     model: "data/models/medkit.fbx",
                     scale: 1.0,
                     reactivation_interval: 20.0,
@@ -89,6 +170,8 @@ impl Item {
             }
             ItemKind::Plasma => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Plasma Cells",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/yellow_box.FBX",
                     scale: 0.25,
                     reactivation_interval: 15.0,
@@ -97,6 +180,8 @@ impl Item {
             }
             ItemKind::Ak47Ammo => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "AK-47 Ammo",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/box_medium.FBX",
                     scale: 0.30,
                     reactivation_interval: 14.0,
@@ -105,14 +190,28 @@ impl Item {
             }
             ItemKind::M4Ammo => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "M4 Ammo",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/box_small.FBX",
                     scale: 0.30,
                     reactivation_interval: 13.0,
                 };
                 &DEFINITION
             }
+            ItemKind::ShotgunShells => {
+                static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Shotgun Shells",
+                    icon: "data/ui/ammo_icon.png",
+                    model: "data/models/box_small.FBX",
+                    scale: 0.30,
+                    reactivation_interval: 14.0,
+                };
+                &DEFINITION
+            }
             ItemKind::PlasmaGun => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Plasma Rifle",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/plasma_rifle.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
@@ -121,6 +220,8 @@ impl Item {
             }
             ItemKind::Ak47 => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "AK-47",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/ak47.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
@@ -129,6 +230,8 @@ impl Item {
             }
             ItemKind::M4 => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "M4",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/m4.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
@@ -137,12 +240,24 @@ impl Item {
             }
             ItemKind::RocketLauncher => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Rocket Launcher",
+                    icon: "data/ui/ammo_icon.png",
                     model: "data/models/Rpg7.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
                 };
                 &DEFINITION
             }
+            ItemKind::Shotgun => {
+                static DEFINITION: ItemDefinition = ItemDefinition {
+                    name: "Shotgun",
+                    icon: "data/ui/ammo_icon.png",
+                    model: "data/models/m4.FBX",
+                    scale: 3.0,
+                    reactivation_interval: 30.0,
+                };
+                &DEFINITION
+            }
         }
     }
 
@@ -152,13 +267,13 @@ impl Item {
         scene: &mut Scene,
         resource_manager: ResourceManager,
         sender: Sender<Message>,
-    ) -> Self {
+    ) -> Result<Self, GameError> {
         let definition = Self::get_definition(kind);
 
         let model = resource_manager
             .request_model(Path::new(definition.model))
             .await
-            .unwrap()
+            .map_err(|e| GameError::resource_load(definition.model, e))?
             .instantiate_geometry(scene);
 
         let pivot = PivotBuilder::new(
@@ -177,13 +292,13 @@ impl Item {
 
         scene.graph.link_nodes(model, pivot);
 
-        Self {
+        Ok(Self {
             pivot,
             kind,
             model,
             sender: Some(sender),
             ..Default::default()
-        }
+        })
     }
 
     pub fn get_pivot(&self) -> Handle<Node> {
@@ -218,6 +333,7 @@ impl Item {
                     .send(Message::CreateEffect {
                         kind: EffectKind::ItemAppear,
                         position,
+                        direction: Vector3::y(),
                     })
                     .unwrap();
             }
@@ -233,7 +349,9 @@ impl Item {
     }
 
     pub fn pick_up(&mut self) {
-        self.reactivation_timer = self.definition().reactivation_interval;
+        self.reactivation_timer = tunables::item(self.kind)
+            .reactivation_interval
+            .unwrap_or(self.definition().reactivation_interval);
         self.active = false;
     }
 
@@ -255,11 +373,23 @@ impl Item {
     pub fn set_lifetime(&mut self, lifetime: Option<f32>) {
         self.lifetime = lifetime;
     }
+
+    pub fn set_ammo(&mut self, ammo: Option<u32>) {
+        self.ammo = ammo;
+    }
+
+    /// Ammo that the picker gets, `None` means the default amount.
+    pub fn ammo(&self) -> Option<u32> {
+        self.ammo
+    }
 }
 
 #[derive(Visit)]
 pub struct ItemContainer {
     pool: Pool<Item>,
+    // Rebuilt on every update, items barely move.
+    #[visit(skip)]
+    grid: SpatialGrid<Handle<Item>>,
 }
 
 impl Default for ItemContainer {
@@ -270,7 +400,10 @@ impl Default for ItemContainer {
 
 impl ItemContainer {
     pub fn new() -> Self {
-        Self { pool: Pool::new() }
+        Self {
+            pool: Pool::new(),
+            grid: Default::default(),
+        }
     }
 
     pub fn add(&mut self, item: Item) -> Handle<Item> {
@@ -308,6 +441,48 @@ impl ItemContainer {
                 item.cleanup(&mut scene.graph);
             }
         }
-        self.pool.retain(|i| !i.can_be_removed())
+        self.pool.retain(|i| !i.can_be_removed());
+
+        self.grid.clear();
+        for (handle, item) in self.pool.pair_iter() {
+            self.grid.insert(handle, item.position(&scene.graph));
+        }
+    }
+
+    /// Items within given radius of the point, including picked up ones, in the same order
+    /// as `pair_iter`.
+    pub fn query(&self, center: Vector3<f32>, radius: f32, result: &mut GridQuery<Handle<Item>>) {
+        self.grid.query(center, radius, result);
+    }
+
+    pub fn get(&self, item: Handle<Item>) -> &Item {
+        self.pool.borrow(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::resource_manager;
+    use fyrox::core::futures::executor::block_on;
+
+    #[test]
+    fn missing_model_is_reported_instead_of_panicking() {
+        let definition = Item::get_definition(ItemKind::Medkit);
+        assert!(!Path::new(definition.model).exists());
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let result = block_on(Item::new(
+            ItemKind::Medkit,
+            Vector3::default(),
+            &mut Scene::new(),
+            resource_manager(),
+            sender,
+        ));
+        match result {
+            Err(GameError::ResourceLoad { path, .. }) => {
+                assert_eq!(path, Path::new(definition.model))
+            }
+            Ok(_) => panic!("item spawned without its model"),
+        }
     }
 }
@@ -0,0 +1,435 @@
+//! Kill feed of the HUD. It lists recent kills in the top-right corner of the screen, every
+//! entry slides in when added and fades out when it gets old. Feed is filled from messages
+//! that HUD receives, so gameplay code knows nothing about it.
+
+use crate::{
+    character::Team,
+    localization::tr_format,
+    weapon::{Weapon, WeaponKind},
+};
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        image::{ImageBuilder, ImageMessage},
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    utils,
+};
+use std::collections::VecDeque;
+
+const MAX_ENTRIES: usize = 5;
+/// Total time that entry stays on screen.
+const ENTRY_LIFETIME: f32 = 5.0;
+const SLIDE_DURATION: f32 = 0.2;
+const SLIDE_DISTANCE: f32 = 40.0;
+/// Entry fades out during last part of its lifetime.
+const FADE_DURATION: f32 = 0.5;
+const ICON_SIZE: f32 = 20.0;
+const SKULL_ICON: &str = "data/ui/skull_icon.png";
+
+fn team_color(team: Team) -> Color {
+    match team {
+        Team::None => Color::WHITE,
+        Team::Red => Color::opaque(220, 50, 50),
+        Team::Blue => Color::opaque(60, 120, 255),
+    }
+}
+
+fn with_alpha(mut color: Color, alpha: f32) -> Brush {
+    color.a = (color.a as f32 * alpha) as u8;
+    Brush::Solid(color)
+}
+
+pub struct KillFeedEntry {
+    /// Name and team of the killer, `None` for suicides.
+    killer: Option<(String, Team)>,
+    victim: String,
+    victim_team: Team,
+    weapon: Option<WeaponKind>,
+    headshot: bool,
+    /// Kill by or of the local player.
+    highlighted: bool,
+    age: f32,
+}
+
+impl KillFeedEntry {
+    pub fn new(
+        killer: Option<(String, Team)>,
+        victim: String,
+        victim_team: Team,
+        weapon: Option<WeaponKind>,
+        headshot: bool,
+        highlighted: bool,
+    ) -> Self {
+        Self {
+            killer,
+            victim,
+            victim_team,
+            weapon,
+            headshot,
+            highlighted,
+            age: 0.0,
+        }
+    }
+
+    /// Offset of the slide-in animation, 1.0 right after entry was added.
+    fn slide(&self) -> f32 {
+        (1.0 - self.age / SLIDE_DURATION).max(0.0)
+    }
+
+    fn alpha(&self) -> f32 {
+        ((ENTRY_LIFETIME - self.age) / FADE_DURATION)
+            .max(0.0)
+            .min(1.0)
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age >= ENTRY_LIFETIME
+    }
+}
+
+struct KillFeedRow {
+    root: Handle<UiNode>,
+    killer: Handle<UiNode>,
+    weapon: Handle<UiNode>,
+    skull: Handle<UiNode>,
+    victim: Handle<UiNode>,
+}
+
+impl KillFeedRow {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let icon = |ctx: &mut BuildContext| {
+            ImageBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(ICON_SIZE)
+                    .with_height(ICON_SIZE)
+                    .with_margin(Thickness::uniform(2.0)),
+            )
+            .build(ctx)
+        };
+        let text = |ctx: &mut BuildContext| {
+            TextBuilder::new(
+                WidgetBuilder::new()
+                    .with_vertical_alignment(VerticalAlignment::Center)
+                    .with_margin(Thickness::uniform(2.0)),
+            )
+            .build(ctx)
+        };
+
+        let killer = text(ctx);
+        let weapon = icon(ctx);
+        let skull = icon(ctx);
+        let victim = text(ctx);
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_margin(Thickness::bottom(2.0))
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(killer)
+                            .with_child(weapon)
+                            .with_child(skull)
+                            .with_child(victim),
+                    )
+                    .with_orientation(Orientation::Horizontal)
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            root,
+            killer,
+            weapon,
+            skull,
+            victim,
+        }
+    }
+
+    fn set_visible(&self, ui: &mut UserInterface, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    /// Fills the row with contents of the entry, done only when entries change.
+    fn fill(
+        &self,
+        ui: &mut UserInterface,
+        resource_manager: &ResourceManager,
+        entry: &KillFeedEntry,
+    ) {
+        let is_suicide = entry.killer.is_none();
+        let (killer, victim) = match entry.killer.as_ref() {
+            Some((killer, _)) => (killer.clone(), entry.victim.clone()),
+            None => (
+                Default::default(),
+                tr_format("kill_feed.died", &[&entry.victim]),
+            ),
+        };
+        ui.send_message(TextMessage::text(
+            self.killer,
+            MessageDirection::ToWidget,
+            killer,
+        ));
+        ui.send_message(TextMessage::text(
+            self.victim,
+            MessageDirection::ToWidget,
+            victim,
+        ));
+
+        let weapon_icon = if is_suicide { None } else { entry.weapon };
+        for (image, icon) in [
+            (
+                self.weapon,
+                weapon_icon.map(|kind| Weapon::get_definition(kind).icon),
+            ),
+            (self.skull, Some(SKULL_ICON).filter(|_| entry.headshot)),
+        ] {
+            ui.send_message(WidgetMessage::visibility(
+                image,
+                MessageDirection::ToWidget,
+                icon.is_some(),
+            ));
+            if let Some(icon) = icon {
+                ui.send_message(ImageMessage::texture(
+                    image,
+                    MessageDirection::ToWidget,
+                    Some(utils::into_gui_texture(
+                        resource_manager.request_texture(icon),
+                    )),
+                ));
+            }
+        }
+        ui.send_message(WidgetMessage::visibility(
+            self.killer,
+            MessageDirection::ToWidget,
+            !is_suicide,
+        ));
+    }
+
+    fn animate(&self, ui: &mut UserInterface, entry: &KillFeedEntry) {
+        let alpha = entry.alpha();
+        ui.send_message(WidgetMessage::margin(
+            self.root,
+            MessageDirection::ToWidget,
+            Thickness {
+                left: 0.0,
+                top: 0.0,
+                right: SLIDE_DISTANCE * entry.slide(),
+                bottom: 2.0,
+            },
+        ));
+        let background = if entry.highlighted {
+            Color::from_rgba(255, 255, 255, 60)
+        } else {
+            Color::from_rgba(0, 0, 0, 100)
+        };
+        ui.send_message(WidgetMessage::background(
+            self.root,
+            MessageDirection::ToWidget,
+            with_alpha(background, alpha),
+        ));
+        if let Some((_, killer_team)) = entry.killer.as_ref() {
+            ui.send_message(WidgetMessage::foreground(
+                self.killer,
+                MessageDirection::ToWidget,
+                with_alpha(team_color(*killer_team), alpha),
+            ));
+        }
+        ui.send_message(WidgetMessage::foreground(
+            self.victim,
+            MessageDirection::ToWidget,
+            with_alpha(team_color(entry.victim_team), alpha),
+        ));
+        // Images are tinted by their background.
+        for image in [self.weapon, self.skull] {
+            ui.send_message(WidgetMessage::background(
+                image,
+                MessageDirection::ToWidget,
+                with_alpha(Color::WHITE, alpha),
+            ));
+        }
+    }
+}
+
+/// Entries of the feed without their widgets. Oldest entry goes first and is shown at the top.
+#[derive(Default)]
+struct KillFeedEntries {
+    entries: VecDeque<KillFeedEntry>,
+}
+
+impl KillFeedEntries {
+    /// Adds new entry, the oldest entry is pushed out if the feed is full.
+    fn push(&mut self, entry: KillFeedEntry) {
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Ages entries and drops expired ones, returns true if any entry was dropped.
+    fn advance(&mut self, dt: f32) -> bool {
+        for entry in self.entries.iter_mut() {
+            entry.age += dt;
+        }
+        let count = self.entries.len();
+        self.entries.retain(|entry| !entry.is_expired());
+        self.entries.len() != count
+    }
+
+    fn get(&self, index: usize) -> Option<&KillFeedEntry> {
+        self.entries.get(index)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &KillFeedEntry> {
+        self.entries.iter()
+    }
+}
+
+pub struct KillFeed {
+    pub root: Handle<UiNode>,
+    rows: Vec<KillFeedRow>,
+    entries: KillFeedEntries,
+    resource_manager: ResourceManager,
+    need_refill: bool,
+}
+
+impl KillFeed {
+    pub fn new(
+        ctx: &mut BuildContext,
+        resource_manager: ResourceManager,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        let rows = (0..MAX_ENTRIES)
+            .map(|_| KillFeedRow::new(ctx))
+            .collect::<Vec<_>>();
+
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_margin(Thickness {
+                    left: 0.0,
+                    top: 30.0,
+                    right: 30.0,
+                    bottom: 0.0,
+                })
+                .with_children(rows.iter().map(|row| row.root)),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            rows,
+            entries: Default::default(),
+            resource_manager,
+            need_refill: false,
+        }
+    }
+
+    /// Adds new entry to the feed, the oldest entry is pushed out if the feed is full.
+    pub fn push(&mut self, entry: KillFeedEntry) {
+        self.entries.push(entry);
+        self.need_refill = true;
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        if self.entries.advance(dt) {
+            self.need_refill = true;
+        }
+
+        if self.need_refill {
+            self.need_refill = false;
+            for (i, row) in self.rows.iter().enumerate() {
+                match self.entries.get(i) {
+                    Some(entry) => {
+                        row.fill(ui, &self.resource_manager, entry);
+                        row.set_visible(ui, true);
+                    }
+                    None => row.set_visible(ui, false),
+                }
+            }
+        }
+
+        for (row, entry) in self.rows.iter().zip(self.entries.iter()) {
+            row.animate(ui, entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kill(killer: &str, victim: &str) -> KillFeedEntry {
+        KillFeedEntry::new(
+            Some((killer.to_owned(), Team::Red)),
+            victim.to_owned(),
+            Team::Blue,
+            Some(WeaponKind::M4),
+            false,
+            false,
+        )
+    }
+
+    fn victims(entries: &KillFeedEntries) -> Vec<&str> {
+        entries.iter().map(|entry| entry.victim.as_str()).collect()
+    }
+
+    #[test]
+    fn entry_lifecycle() {
+        let mut entries = KillFeedEntries::default();
+        entries.push(kill("Alice", "Bot 1"));
+        assert_eq!(entries.get(0).unwrap().slide(), 1.0);
+        assert_eq!(entries.get(0).unwrap().alpha(), 1.0);
+
+        assert!(!entries.advance(SLIDE_DURATION));
+        assert_eq!(entries.get(0).unwrap().slide(), 0.0);
+
+        entries.push(kill("Alice", "Bot 2"));
+        // Both entries are fading out now, the first one is further along.
+        assert!(!entries.advance(ENTRY_LIFETIME - SLIDE_DURATION - FADE_DURATION * 0.5));
+        assert!((entries.get(0).unwrap().alpha() - 0.5).abs() < 1.0e-4);
+        assert!((entries.get(1).unwrap().alpha() - 0.9).abs() < 1.0e-4);
+
+        assert!(entries.advance(FADE_DURATION * 0.5));
+        assert_eq!(victims(&entries), ["Bot 2"]);
+
+        assert!(entries.advance(ENTRY_LIFETIME));
+        assert!(entries.get(0).is_none());
+        assert!(!entries.advance(1.0));
+    }
+
+    #[test]
+    fn oldest_entries_are_pushed_out() {
+        let mut entries = KillFeedEntries::default();
+        for i in 0..MAX_ENTRIES + 2 {
+            entries.push(kill("Alice", &format!("Bot {}", i)));
+            entries.advance(0.1);
+        }
+        assert_eq!(
+            victims(&entries),
+            ["Bot 2", "Bot 3", "Bot 4", "Bot 5", "Bot 6"]
+        );
+        // Survivors keep their own age.
+        assert!(entries.get(0).unwrap().age > entries.get(MAX_ENTRIES - 1).unwrap().age);
+    }
+}
@@ -1,4 +1,11 @@
-use crate::{character::Team, message::Message, MatchOptions};
+use crate::{
+    character::Team,
+    control_scheme::{ControlButton, ControlScheme},
+    gamepad::GamepadEvent,
+    gui::create_scroll_viewer,
+    localization::{tr, tr_format},
+    MatchOptions,
+};
 use fyrox::{
     core::{
         color::Color,
@@ -6,14 +13,17 @@ use fyrox::{
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::Engine,
-    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    event::{DeviceEvent, ElementState, Event, WindowEvent},
     gui::{
+        border::BorderBuilder,
         brush::Brush,
         grid::{Column, GridBuilder, Row},
         message::MessageDirection,
-        text::TextBuilder,
+        scroll_viewer::ScrollViewerMessage,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
         widget::{WidgetBuilder, WidgetMessage},
-        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
 };
 use std::collections::HashMap;
@@ -128,21 +138,183 @@ impl Default for LeaderBoard {
     }
 }
 
+/// Single line of the scoreboard, filled by the level from its frag counters.
+pub struct ScoreboardRow {
+    pub name: String,
+    pub team: Team,
+    pub kills: u32,
+    pub deaths: u32,
+    /// Row of the local player, it is highlighted.
+    pub is_player: bool,
+}
+
+/// Scoreboard is rebuilt at most this often while it is visible.
+const REFRESH_INTERVAL: f32 = 0.5;
+const ROW_HEIGHT: f32 = 30.0;
+
+pub fn format_time(time: f32) -> String {
+    let time = time.max(0.0);
+    let seconds = (time % 60.0) as u32;
+    let minutes = ((time / 60.0) % 60.0) as u32;
+    let hours = (time / 3600.0) as u32;
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+fn build_row(
+    ctx: &mut BuildContext,
+    row: usize,
+    cells: [String; 4],
+    foreground: Color,
+    background: Color,
+) -> Handle<UiNode> {
+    let cells = cells
+        .into_iter()
+        .enumerate()
+        .map(|(column, text)| {
+            TextBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(column)
+                    .with_margin(Thickness::uniform(3.0))
+                    .with_foreground(Brush::Solid(foreground))
+                    .with_vertical_alignment(VerticalAlignment::Center)
+                    .with_horizontal_alignment(if column == 0 {
+                        HorizontalAlignment::Left
+                    } else {
+                        HorizontalAlignment::Center
+                    }),
+            )
+            .with_text(text)
+            .build(ctx)
+        })
+        .collect::<Vec<_>>();
+
+    GridBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_height(ROW_HEIGHT)
+            .with_background(Brush::Solid(background))
+            .with_children(cells),
+    )
+    .add_row(Row::stretch())
+    .add_column(Column::stretch())
+    .add_column(Column::strict(80.0))
+    .add_column(Column::strict(80.0))
+    .add_column(Column::strict(80.0))
+    .build(ctx)
+}
+
+fn team_name(team: Team) -> String {
+    tr(match team {
+        Team::None => "team.unassigned",
+        Team::Red => "team.red_team",
+        Team::Blue => "team.blue_team",
+    })
+}
+
+fn team_color(team: Team) -> Color {
+    match team {
+        Team::None => Color::opaque(120, 120, 120),
+        Team::Red => Color::opaque(220, 50, 50),
+        Team::Blue => Color::opaque(60, 120, 255),
+    }
+}
+
 pub struct LeaderBoardUI {
     root: Handle<UiNode>,
+    header: Handle<UiNode>,
+    summary: Handle<UiNode>,
+    scroll_viewer: Handle<UiNode>,
+    is_visible: bool,
+    // Time left until next refresh of visible scoreboard.
+    refresh_timer: f32,
 }
 
 impl LeaderBoardUI {
     pub fn new(engine: &mut Engine) -> Self {
         let frame_size = engine.renderer.get_frame_size();
 
-        let ui = &mut engine.user_interface;
+        let ctx = &mut engine.user_interface.build_ctx();
 
+        let header;
+        let summary;
+        let scroll_viewer = create_scroll_viewer(ctx);
+        // Scoreboard is shown over the running game, so it must never take the mouse.
         let root: Handle<UiNode> = GridBuilder::new(
             WidgetBuilder::new()
                 .with_visibility(false)
+                .with_hit_test_visibility(false)
                 .with_width(frame_size.0 as f32)
-                .with_height(frame_size.1 as f32),
+                .with_height(frame_size.1 as f32)
+                .with_child(
+                    BorderBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .on_column(1)
+                            .with_hit_test_visibility(false)
+                            .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                            .with_child(
+                                GridBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_child({
+                                            header = TextBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_row(0)
+                                                    .with_margin(Thickness::uniform(5.0))
+                                                    .with_horizontal_alignment(
+                                                        HorizontalAlignment::Center,
+                                                    ),
+                                            )
+                                            .build(ctx);
+                                            header
+                                        })
+                                        .with_child({
+                                            summary = TextBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_row(1)
+                                                    .with_margin(Thickness::uniform(5.0))
+                                                    .with_horizontal_alignment(
+                                                        HorizontalAlignment::Center,
+                                                    ),
+                                            )
+                                            .build(ctx);
+                                            summary
+                                        })
+                                        .with_child(build_row(
+                                            ctx,
+                                            2,
+                                            [
+                                                tr("scores.name"),
+                                                tr("scores.frags"),
+                                                tr("scores.deaths"),
+                                                tr("scores.ping"),
+                                            ],
+                                            Color::opaque(120, 120, 120),
+                                            Color::TRANSPARENT,
+                                        ))
+                                        .with_child(
+                                            BorderBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .on_row(3)
+                                                    .with_background(Brush::Solid(
+                                                        Color::TRANSPARENT,
+                                                    ))
+                                                    .with_child(scroll_viewer),
+                                            )
+                                            .with_stroke_thickness(Thickness::uniform(0.0))
+                                            .build(ctx),
+                                        ),
+                                )
+                                .add_column(Column::stretch())
+                                .add_row(Row::auto())
+                                .add_row(Row::auto())
+                                .add_row(Row::strict(ROW_HEIGHT))
+                                .add_row(Row::stretch())
+                                .build(ctx),
+                            ),
+                    )
+                    .with_stroke_thickness(Thickness::uniform(2.0))
+                    .build(ctx),
+                ),
         )
         .add_row(Row::stretch())
         .add_row(Row::strict(600.0))
@@ -150,271 +322,170 @@ impl LeaderBoardUI {
         .add_column(Column::stretch())
         .add_column(Column::strict(500.0))
         .add_column(Column::stretch())
-        .build(&mut ui.build_ctx());
-        Self { root }
+        .build(ctx);
+
+        Self {
+            root,
+            header,
+            summary,
+            scroll_viewer,
+            is_visible: false,
+            refresh_timer: 0.0,
+        }
     }
 
-    fn sync_to_model(
+    /// Fills the scoreboard with given rows. `elapsed` is the time since the start of the match.
+    pub fn sync_to_model(
         &mut self,
         ui: &mut UserInterface,
+        rows: &[ScoreboardRow],
         leader_board: &LeaderBoard,
         match_options: &MatchOptions,
+        elapsed: f32,
     ) {
-        // Rebuild entire table, this is far from ideal but it is simplest solution.
-        // Shouldn't be a big problem because this method should be called once anything
-        // changes in leader board.
-        // TODO: Remove unnecessary rebuild of table.
-
-        let ctx = &mut ui.build_ctx();
-
-        let row_template = Row::strict(30.0);
-
-        let mut children = Vec::new();
-
-        for (i, (name, score)) in leader_board.values().iter().enumerate() {
-            let row = i + 1;
-
-            children.push(
-                TextBuilder::new(
-                    WidgetBuilder::new()
-                        .with_margin(Thickness::uniform(3.0))
-                        .on_row(row)
-                        .on_column(0),
-                )
-                .with_text(name)
-                .build(ctx),
-            );
-
-            children.push(
-                TextBuilder::new(
-                    WidgetBuilder::new()
-                        .with_margin(Thickness::uniform(3.0))
-                        .on_row(row)
-                        .on_column(1),
-                )
-                .with_text(format!("{}", score.kills))
-                .build(ctx),
-            );
+        let header = match match_options {
+            MatchOptions::DeathMatch(dm) => tr_format(
+                "scores.deathmatch_header",
+                &[&dm.frag_limit, &format_time(dm.time_limit_secs - elapsed)],
+            ),
+            MatchOptions::TeamDeathMatch(tdm) => tr_format(
+                "scores.team_deathmatch_header",
+                &[&format_time(tdm.time_limit_secs)],
+            ),
+            MatchOptions::CaptureTheFlag(ctf) => tr_format(
+                "scores.capture_the_flag_header",
+                &[&format_time(ctf.time_limit_secs)],
+            ),
+        };
+        ui.send_message(TextMessage::text(
+            self.header,
+            MessageDirection::ToWidget,
+            header,
+        ));
 
-            children.push(
-                TextBuilder::new(
-                    WidgetBuilder::new()
-                        .with_margin(Thickness::uniform(3.0))
-                        .on_row(row)
-                        .on_column(2),
+        let summary = match match_options {
+            MatchOptions::DeathMatch(_) => {
+                if let Some((name, kills)) = leader_board.highest_personal_score(None) {
+                    tr_format("scores.leads_with", &[&name, &kills])
+                } else {
+                    tr("scores.draw")
+                }
+            }
+            MatchOptions::TeamDeathMatch(tdm) => {
+                let red_score = leader_board.team_score(Team::Red);
+                let blue_score = leader_board.team_score(Team::Blue);
+                let leader = if red_score > blue_score {
+                    tr("team.red")
+                } else {
+                    tr("team.blue")
+                };
+                tr_format(
+                    "scores.team_deathmatch_summary",
+                    &[&leader, &red_score, &blue_score, &tdm.team_frag_limit],
                 )
-                .with_text(format!("{}", score.deaths))
-                .build(ctx),
-            );
+            }
+            MatchOptions::CaptureTheFlag(ctf) => {
+                // TODO - implement when CTF mode implemented
+                tr_format("scores.capture_the_flag_summary", &[&ctf.flag_limit])
+            }
+        };
+        ui.send_message(TextMessage::text(
+            self.summary,
+            MessageDirection::ToWidget,
+            summary,
+        ));
 
-            let kd = if score.deaths != 0 {
-                format!("{}", score.kills as f32 / score.deaths as f32)
-            } else {
-                "N/A".to_owned()
-            };
-
-            children.push(
-                TextBuilder::new(
-                    WidgetBuilder::new()
-                        .with_margin(Thickness::uniform(3.0))
-                        .on_row(row)
-                        .on_column(3),
-                )
-                .with_text(kd)
-                .build(ctx),
-            );
+        let mut sorted = rows.iter().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| {
+            b.kills
+                .cmp(&a.kills)
+                .then(a.deaths.cmp(&b.deaths))
+                .then(a.name.cmp(&b.name))
+        });
+
+        let has_teams = rows.iter().any(|row| row.team != Team::None);
+        let sections: &[Option<Team>] = if has_teams {
+            &[Some(Team::Red), Some(Team::Blue), Some(Team::None)]
+        } else {
+            &[None]
+        };
+
+        // Only the content of the scroll viewer is rebuilt, it is cheap enough for the rate
+        // at which scoreboard is refreshed.
+        let ctx = &mut ui.build_ctx();
+        let mut children = Vec::new();
+        let mut player_row = Handle::NONE;
+        for section in sections {
+            let section_rows = sorted
+                .iter()
+                .filter(|row| section.map_or(true, |team| row.team == team))
+                .collect::<Vec<_>>();
+            if let Some(team) = section {
+                if section_rows.is_empty() {
+                    continue;
+                }
+                let score = if *team == Team::None {
+                    String::new()
+                } else {
+                    leader_board.team_score(*team).to_string()
+                };
+                children.push(build_row(
+                    ctx,
+                    0,
+                    [team_name(*team), score, String::new(), String::new()],
+                    Color::WHITE,
+                    team_color(*team),
+                ));
+            }
+            for row in section_rows {
+                let background = if row.is_player {
+                    Color::from_rgba(255, 255, 255, 50)
+                } else {
+                    Color::TRANSPARENT
+                };
+                // There is no networking, so ping is just a placeholder.
+                let handle = build_row(
+                    ctx,
+                    0,
+                    [
+                        row.name.clone(),
+                        row.kills.to_string(),
+                        row.deaths.to_string(),
+                        "-".to_owned(),
+                    ],
+                    Color::WHITE,
+                    background,
+                );
+                if row.is_player {
+                    player_row = handle;
+                }
+                children.push(handle);
+            }
         }
 
-        let table = GridBuilder::new(
-            WidgetBuilder::new()
-                .on_row(1)
-                .on_column(1)
-                .with_background(Brush::Solid(Color::BLACK))
-                .with_child(
-                    TextBuilder::new(
-                        WidgetBuilder::new()
-                            .on_column(0)
-                            .on_row(0)
-                            .with_horizontal_alignment(HorizontalAlignment::Center),
-                    )
-                    .with_text({
-                        let time_limit_secs = match match_options {
-                            MatchOptions::DeathMatch(dm) => dm.time_limit_secs,
-                            MatchOptions::TeamDeathMatch(tdm) => tdm.time_limit_secs,
-                            MatchOptions::CaptureTheFlag(ctf) => ctf.time_limit_secs,
-                        };
-
-                        let seconds = (time_limit_secs % 60.0) as u32;
-                        let minutes = (time_limit_secs / 60.0) as u32;
-                        let hours = (time_limit_secs / 3600.0) as u32;
-
-                        match match_options {
-                            MatchOptions::DeathMatch(_) => format!(
-                                "Death Match - Time Limit {:02}:{:02}:{:02}",
-                                hours, minutes, seconds
-                            ),
-                            MatchOptions::TeamDeathMatch(_) => format!(
-                                "Team Death Match - Time Limit {:02}:{:02}:{:02}",
-                                hours, minutes, seconds
-                            ),
-                            MatchOptions::CaptureTheFlag(_) => format!(
-                                "Capture The Flag - Time Limit {:02}:{:02}:{:02}",
-                                hours, minutes, seconds
-                            ),
-                        }
-                    })
-                    .build(ctx),
-                )
-                .with_child({
-                    match match_options {
-                        MatchOptions::DeathMatch(dm) => {
-                            let text = if let Some((name, kills)) =
-                                leader_board.highest_personal_score(None)
-                            {
-                                format!(
-                                    "{} leads with {} frags\nPlaying until {} frags",
-                                    name, kills, dm.frag_limit
-                                )
-                            } else {
-                                format!("Draw\nPlaying until {} frags", dm.frag_limit)
-                            };
-                            TextBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(5.0))
-                                    .with_horizontal_alignment(HorizontalAlignment::Center)
-                                    .on_column(0)
-                                    .on_row(1),
-                            )
-                            .with_text(text)
-                            .build(ctx)
-                        }
-                        MatchOptions::TeamDeathMatch(tdm) => {
-                            let red_score = leader_board.team_score(Team::Red);
-                            let blue_score = leader_board.team_score(Team::Blue);
-
-                            TextBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(5.0))
-                                    .with_horizontal_alignment(HorizontalAlignment::Center)
-                                    .on_column(0)
-                                    .on_row(1),
-                            )
-                            .with_text(format!(
-                                "{} team leads\nRed {} - {} Blue\nPlaying until {} frags",
-                                if red_score > blue_score {
-                                    "Red"
-                                } else {
-                                    "Blue"
-                                },
-                                red_score,
-                                blue_score,
-                                tdm.team_frag_limit
-                            ))
-                            .build(ctx)
-                        }
-                        MatchOptions::CaptureTheFlag(ctf) => {
-                            // TODO - implement when CTF mode implemented
-                            TextBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(5.0))
-                                    .with_horizontal_alignment(HorizontalAlignment::Center)
-                                    .on_column(0)
-                                    .on_row(1),
-                            )
-                            .with_text(format!(
-                                "Red team leads\nRed 0 - 0 Blue\nPlaying until {} flags",
-                                ctf.flag_limit
-                            ))
-                            .build(ctx)
-                        }
-                    }
-                })
-                .with_child(
-                    GridBuilder::new(
-                        WidgetBuilder::new()
-                            .on_column(0)
-                            .on_row(2)
-                            .with_foreground(Brush::Solid(Color::opaque(120, 120, 120)))
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_horizontal_alignment(HorizontalAlignment::Center)
-                                        .with_vertical_alignment(VerticalAlignment::Center)
-                                        .on_column(0)
-                                        .on_row(0),
-                                )
-                                .with_text("Name")
-                                .build(ctx),
-                            )
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_horizontal_alignment(HorizontalAlignment::Center)
-                                        .with_vertical_alignment(VerticalAlignment::Center)
-                                        .on_column(1)
-                                        .on_row(0),
-                                )
-                                .with_text("Kills")
-                                .build(ctx),
-                            )
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_horizontal_alignment(HorizontalAlignment::Center)
-                                        .with_vertical_alignment(VerticalAlignment::Center)
-                                        .on_column(2)
-                                        .on_row(0),
-                                )
-                                .with_text("Deaths")
-                                .build(ctx),
-                            )
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_horizontal_alignment(HorizontalAlignment::Center)
-                                        .with_vertical_alignment(VerticalAlignment::Center)
-                                        .on_column(3)
-                                        .on_row(0),
-                                )
-                                .with_text("K/D")
-                                .build(ctx),
-                            )
-                            .with_children(children),
-                    )
-                    .with_border_thickness(2.0)
-                    .add_row(Row::strict(30.0))
-                    .add_rows(
-                        (0..leader_board.values().len())
-                            .map(|_| row_template)
-                            .collect(),
-                    )
-                    .add_row(Row::stretch())
-                    .add_column(Column::stretch())
-                    .add_column(Column::stretch())
-                    .add_column(Column::stretch())
-                    .add_column(Column::stretch())
-                    .draw_border(true)
-                    .build(ctx),
-                ),
-        )
-        .add_column(Column::auto())
-        .add_row(Row::auto())
-        .add_row(Row::auto())
-        .add_row(Row::stretch())
-        .build(ctx);
-
-        if let Some(table) = ctx[self.root].children().first() {
-            let table = *table;
-            ui.send_message(WidgetMessage::remove(table, MessageDirection::ToWidget));
-        }
-        ui.send_message(WidgetMessage::link(
-            table,
+        let content =
+            StackPanelBuilder::new(WidgetBuilder::new().with_children(children)).build(ctx);
+        ui.send_message(ScrollViewerMessage::content(
+            self.scroll_viewer,
             MessageDirection::ToWidget,
-            self.root,
+            content,
         ));
+        // Mouse is not available while playing, so keep the player in view instead.
+        if player_row.is_some() {
+            ui.send_message(ScrollViewerMessage::bring_into_view(
+                self.scroll_viewer,
+                MessageDirection::ToWidget,
+                player_row,
+            ));
+        }
     }
 
-    pub fn set_visible(&self, visible: bool, ui: &mut UserInterface) {
+    pub fn set_visible(&mut self, visible: bool, ui: &mut UserInterface) {
+        if visible && !self.is_visible {
+            // Data is not refreshed while scoreboard is hidden, so it must be synced right away.
+            self.refresh_timer = 0.0;
+        }
+        self.is_visible = visible;
         ui.send_message(WidgetMessage::visibility(
             self.root,
             MessageDirection::ToWidget,
@@ -422,9 +493,29 @@ impl LeaderBoardUI {
         ));
     }
 
-    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) {
-        if let Event::WindowEvent { event, .. } = event {
-            match event {
+    /// Returns true if visible scoreboard must be refreshed, hidden one is never refreshed.
+    pub fn needs_refresh(&mut self, dt: f32) -> bool {
+        if !self.is_visible {
+            return false;
+        }
+        self.refresh_timer -= dt;
+        if self.refresh_timer <= 0.0 {
+            self.refresh_timer = REFRESH_INTERVAL;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn process_input_event(
+        &mut self,
+        engine: &mut Engine,
+        event: &Event<()>,
+        control_scheme: &ControlScheme,
+    ) {
+        let mut button = None;
+        match event {
+            Event::WindowEvent { event, .. } => match event {
                 WindowEvent::Resized(new_size) => {
                     engine.user_interface.send_message(WidgetMessage::width(
                         self.root,
@@ -438,36 +529,58 @@ impl LeaderBoardUI {
                     ));
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(vk) = input.virtual_keycode {
-                        if vk == VirtualKeyCode::Tab {
-                            let visible = match input.state {
-                                ElementState::Pressed => true,
-                                ElementState::Released => false,
-                            };
-
-                            self.set_visible(visible, &mut engine.user_interface);
-                        }
+                    if let Some(code) = input.virtual_keycode {
+                        button = Some((ControlButton::Key(code), input.state));
                     }
                 }
                 _ => {}
-            }
+            },
+            Event::DeviceEvent {
+                event:
+                    DeviceEvent::Button {
+                        button: index,
+                        state,
+                    },
+                ..
+            } => button = Some((ControlButton::Mouse(*index as u16), *state)),
+            _ => {}
+        }
+
+        if let Some((button, state)) = button {
+            self.process_button(
+                &mut engine.user_interface,
+                control_scheme,
+                button,
+                state == ElementState::Pressed,
+            );
         }
     }
 
-    pub fn handle_message(
+    pub fn process_gamepad_event(
         &mut self,
-        message: &Message,
         ui: &mut UserInterface,
-        leader_board: &LeaderBoard,
-        match_options: &MatchOptions,
+        event: &GamepadEvent,
+        control_scheme: &ControlScheme,
+    ) {
+        if let GamepadEvent::Button { index, pressed } = *event {
+            self.process_button(
+                ui,
+                control_scheme,
+                ControlButton::GamepadButton(index),
+                pressed,
+            );
+        }
+    }
+
+    fn process_button(
+        &mut self,
+        ui: &mut UserInterface,
+        control_scheme: &ControlScheme,
+        button: ControlButton,
+        pressed: bool,
     ) {
-        match message {
-            Message::AddBot { .. } => self.sync_to_model(ui, leader_board, match_options),
-            Message::RemoveActor { .. } => self.sync_to_model(ui, leader_board, match_options),
-            Message::SpawnBot { .. } => self.sync_to_model(ui, leader_board, match_options),
-            Message::SpawnPlayer => self.sync_to_model(ui, leader_board, match_options),
-            Message::RespawnActor { .. } => self.sync_to_model(ui, leader_board, match_options),
-            _ => (),
+        if control_scheme.show_scores.matches(button) {
+            self.set_visible(pressed, ui);
         }
     }
 }
@@ -1,25 +1,55 @@
 use crate::{
     actor::{Actor, ActorContainer},
-    bot::{Bot, BotKind},
+    bot::{
+        AiLod, AiLodOptions, Bot, BotDebugFlags, BotDifficulty, BotKind, PoiPriority,
+        CORPSE_LIFETIME, DAMAGE_POI_TIME,
+    },
+    campaign::LevelDefinition,
+    captions::{self, CaptionList},
+    character::{Team, MAX_ARMOR, MAX_HEALTH, RECENT_DAMAGE_TIME},
     control_scheme::ControlScheme,
-    effects,
+    death_screen::DeathSummary,
+    debug_overlay::DebugStats,
+    effects::{self, EffectBudget, EffectKind, EffectPool},
+    event_log::{EventCategory, EventLog},
+    gamepad::GamepadEvent,
+    hud::{
+        BossBarData, DamageIndicator, HudData, RadarBlip, RadarBlipKind, RadarData, WeaponHudData,
+        WeaponWheelData, WeaponWheelItem,
+    },
     item::{Item, ItemContainer, ItemKind},
     jump_pad::{JumpPad, JumpPadContainer},
-    leader_board::LeaderBoard,
-    message::Message,
+    leader_board::{LeaderBoard, ScoreboardRow},
+    localization::tr_format,
+    message::{DamageHit, Message},
+    music::{self, MusicManager},
+    patrol::{self, PatrolMode, PatrolRoute},
     player::Player,
-    projectile::{Projectile, ProjectileContainer, ProjectileKind},
-    weapon::{Weapon, WeaponContainer, WeaponKind},
+    preload::{PreloadCache, PreloadList},
+    projectile::{Projectile, ProjectileContainer, ProjectileDescriptor, ProjectileKind},
+    radar,
+    reverb::{self, ReverbMixer, ReverbPreset, ReverbZone},
+    settings::{Settings, SoundCategory},
+    spatial_grid::GridQuery,
+    surface::{self, SurfaceKind},
+    toast::NotificationPriority,
+    tracer::TracerPool,
+    tunables::DefinitionKind,
+    usable::{Usable, UsableContainer},
+    weapon::{
+        spread_direction, MeleeSweep, ShootResult, Weapon, WeaponAttack, WeaponContainer,
+        WeaponKind,
+    },
     GameTime, MatchOptions,
 };
 use fyrox::core::algebra::Vector2;
 use fyrox::{
     core::{
         algebra::Point3,
-        algebra::{Matrix3, Vector3},
+        algebra::{Matrix3, Matrix4, UnitQuaternion, Vector3},
         color::Color,
         math::Vector3Ext,
-        math::{aabb::AxisAlignedBoundingBox, ray::Ray, PositionProvider},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, ray::Ray, PositionProvider},
         pool::Handle,
         rand::Rng,
         visitor::{Visit, VisitResult, Visitor},
@@ -30,14 +60,18 @@ use fyrox::{
     scene::{
         self,
         base::BaseBuilder,
-        camera::{Camera, CameraBuilder},
-        collider::InteractionGroups,
-        graph::{physics::RayCastOptions, Graph},
+        camera::{Camera, CameraBuilder, Projection},
+        collider::{ColliderShape, InteractionGroups},
+        decal::DecalBuilder,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
         node::Node,
         sound::{
             context::SoundContext,
             effect::{BaseEffectBuilder, Effect, EffectInput, ReverbEffectBuilder},
-            HrirSphere, HrtfRenderer, Renderer, SoundBuilder, Status, SAMPLE_RATE,
+            HrirSphere, HrtfRenderer, Renderer, Sound, SoundBuilder, Status, SAMPLE_RATE,
         },
         transform::TransformBuilder,
         Scene,
@@ -45,15 +79,123 @@ use fyrox::{
     utils::log::{Log, MessageKind},
 };
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::{mpsc::Sender, Arc, RwLock},
 };
 
 pub const RESPAWN_TIME: f32 = 4.0;
+pub const ITEM_PICKUP_SOUND: &str = "data/sounds/item_pickup.ogg";
+/// Hit marker sounds of kills and headshots, other hits are silent.
+pub const KILL_CONFIRM_SOUND: &str = "data/sounds/bullet_impact_metal.ogg";
+pub const HEADSHOT_CONFIRM_SOUND: &str = "data/sounds/bullet_impact_body.ogg";
+/// Speed of time while the player picks a weapon on the weapon wheel.
+const WEAPON_WHEEL_TIME_SCALE: f32 = 0.2;
+/// Time between death of the player and the death screen, spectator camera drops during it.
+const DEATH_SCREEN_DELAY: f32 = 1.5;
+/// Boss bar is hidden when the player was farther than this from the boss for
+/// `BOSS_DISENGAGE_TIME` seconds.
+const BOSS_COMBAT_RANGE: f32 = 40.0;
+const BOSS_DISENGAGE_TIME: f32 = 10.0;
+/// Deaths without a killer, like falls and own grenades, are credited to the last attacker
+/// if the victim was hurt by them this many seconds ago at most.
+const KILL_CREDIT_TIME: f32 = 5.0;
+/// Weapons dropped by dead actors disappear after this many seconds if nobody picks them up.
+const DROPPED_WEAPON_LIFETIME: f32 = 30.0;
+/// Height of damage numbers above the center of the damaged actor.
+const DAMAGE_NUMBER_HEIGHT: f32 = 1.0;
+/// Sounds that are farther from the camera than this are not captioned.
+const CAPTION_DISTANCE: f32 = 30.0;
+/// Enemies out of sight are shown on the radar for this long after their last shot.
+const RADAR_SHOT_TIME: f32 = 2.0;
+/// Hits that deal at least this much damage leave blood on the wall behind the victim.
+const BLOOD_DECAL_DAMAGE: f32 = 20.0;
+/// Farthest distance behind the victim at which blood still reaches a wall.
+const BLOOD_DECAL_DISTANCE: f32 = 3.0;
+const BLOOD_DECAL_SIZE: f32 = 0.8;
+/// Oldest decal is removed when there are more than this.
+const MAX_DECALS: usize = 64;
+
+/// Sounds quieter than this at full volume are never checked for occlusion.
+const OCCLUSION_MIN_GAIN: f32 = 0.1;
+/// Looping sounds are checked again after this many seconds, sounds that play once are
+/// checked only when they start.
+const OCCLUSION_CHECK_INTERVAL: f32 = 0.5;
+/// Rays cast per frame, sounds that don't fit wait for the next frames.
+const MAX_OCCLUSION_RAYS: usize = 8;
+/// Each wall between the listener and a sound takes this much of its gain.
+const OCCLUSION_GAIN_PER_HIT: f32 = 0.35;
+const MAX_OCCLUSION_HITS: usize = 2;
+
+/// Sound which gain follows volume of its category.
+struct ManagedSound {
+    sound: Handle<Node>,
+    /// Gain at full volume.
+    gain: f32,
+    category: SoundCategory,
+    /// Fraction of the gain left after occlusion, 1.0 when nothing is in the way.
+    occlusion: f32,
+    /// Time until next occlusion check, the sound is checked as soon as possible when it is
+    /// zero.
+    check_timer: f32,
+}
 
-#[derive(Default, Visit)]
+impl ManagedSound {
+    fn new(sound: Handle<Node>, gain: f32, category: SoundCategory) -> Self {
+        Self {
+            sound,
+            gain,
+            category,
+            occlusion: 1.0,
+            check_timer: 0.0,
+        }
+    }
+}
+
+#[derive(Visit)]
 pub struct SoundManager {
     reverb: Handle<Effect>,
+    // Sounds aren't saved, they are collected from the graph on first use instead. This way
+    // sound emitters of the map are found both on a new level and after loading a save.
+    #[visit(skip)]
+    sounds: Vec<ManagedSound>,
+    #[visit(skip)]
+    is_scanned: bool,
+    // Volumes of categories, in order of `SOUND_CATEGORIES`.
+    #[visit(skip)]
+    volumes: [f32; 4],
+    // Index of the sound to start occlusion checks from on next frame.
+    #[visit(skip)]
+    occlusion_cursor: usize,
+    #[visit(skip)]
+    query_buffer: Vec<Intersection>,
+    #[visit(skip)]
+    reverb_mixer: ReverbMixer,
+    // Last preset given to the reverb effect, the effect is only touched when the mix changes.
+    #[visit(skip)]
+    applied_reverb: Option<ReverbPreset>,
+}
+
+const SOUND_CATEGORIES: [SoundCategory; 4] = [
+    SoundCategory::Sfx,
+    SoundCategory::Music,
+    SoundCategory::Ui,
+    SoundCategory::Voice,
+];
+
+impl Default for SoundManager {
+    fn default() -> Self {
+        Self {
+            reverb: Default::default(),
+            sounds: Default::default(),
+            is_scanned: false,
+            volumes: [1.0; 4],
+            occlusion_cursor: 0,
+            query_buffer: Default::default(),
+            reverb_mixer: Default::default(),
+            applied_reverb: None,
+        }
+    }
 }
 
 impl SoundManager {
@@ -68,7 +210,168 @@ impl SoundManager {
 
         context.set_renderer(Renderer::HrtfRenderer(HrtfRenderer::new(hrir_sphere)));
 
-        Self { reverb }
+        Self {
+            reverb,
+            ..Default::default()
+        }
+    }
+
+    fn volume(&self, category: SoundCategory) -> f32 {
+        self.volumes[category as usize]
+    }
+
+    fn scan(&mut self, graph: &mut Graph) {
+        if !self.is_scanned {
+            self.is_scanned = true;
+            // Everything that is already in the graph comes from the map, so it is treated as
+            // sound effects. Music is the only exception, it has a mixer of its own.
+            self.sounds.extend(
+                graph
+                    .pair_iter()
+                    .filter(|(_, node)| node.name() != music::MUSIC_NODE_NAME)
+                    .filter_map(|(handle, node)| {
+                        node.cast::<Sound>().map(|sound| {
+                            ManagedSound::new(handle, sound.gain(), SoundCategory::Sfx)
+                        })
+                    }),
+            );
+            for managed in self.sounds.iter() {
+                self.apply_gain(graph, managed);
+            }
+        }
+    }
+
+    fn apply_gain(&self, graph: &mut Graph, managed: &ManagedSound) {
+        let gain = managed.gain * self.volume(managed.category) * managed.occlusion;
+        graph[managed.sound].as_sound_mut().set_gain(gain);
+    }
+
+    /// Only sounds that come from somewhere in the level can be occluded, music and UI sounds
+    /// are heard the same everywhere.
+    fn is_occludable(managed: &ManagedSound) -> bool {
+        matches!(managed.category, SoundCategory::Sfx | SoundCategory::Voice)
+            && managed.gain >= OCCLUSION_MIN_GAIN
+    }
+
+    /// Counts static geometry between the listener and the sound, every hit against the level
+    /// muffles the sound a bit more.
+    fn occlusion(&mut self, graph: &Graph, listener: Vector3<f32>, source: Vector3<f32>) -> f32 {
+        let to_source = source - listener;
+        let distance = to_source.norm();
+        if distance <= f32::EPSILON {
+            return 1.0;
+        }
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(listener),
+                ray_direction: to_source / distance,
+                groups: InteractionGroups::default(),
+                max_len: distance,
+                sort_results: false,
+            },
+            &mut self.query_buffer,
+        );
+        let hits = self
+            .query_buffer
+            .iter()
+            .filter(|intersection| {
+                matches!(
+                    graph[intersection.collider].as_collider().shape(),
+                    ColliderShape::Trimesh(_)
+                )
+            })
+            .count()
+            .min(MAX_OCCLUSION_HITS);
+        1.0 - OCCLUSION_GAIN_PER_HIT * hits as f32
+    }
+
+    /// Checks sounds for occlusion, a few per frame in round-robin so a lot of sounds at once
+    /// don't stall the frame.
+    fn update_occlusion(&mut self, graph: &mut Graph, listener: Vector3<f32>, dt: f32) {
+        let mut rays = 0;
+        let count = self.sounds.len();
+        for i in 0..count {
+            let index = (self.occlusion_cursor + i) % count;
+            let managed = &mut self.sounds[index];
+            managed.check_timer = (managed.check_timer - dt).max(0.0);
+            if rays == MAX_OCCLUSION_RAYS || managed.check_timer > 0.0 {
+                continue;
+            }
+            if !Self::is_occludable(managed) {
+                managed.check_timer = f32::INFINITY;
+                continue;
+            }
+
+            let sound = graph[managed.sound].as_sound();
+            managed.check_timer = if sound.is_looping() {
+                OCCLUSION_CHECK_INTERVAL
+            } else {
+                f32::INFINITY
+            };
+            let source = sound.global_position();
+            rays += 1;
+            self.occlusion_cursor = index + 1;
+
+            let occlusion = self.occlusion(graph, listener, source);
+            let managed = &mut self.sounds[index];
+            if occlusion != managed.occlusion {
+                managed.occlusion = occlusion;
+                let managed = &self.sounds[index];
+                self.apply_gain(graph, managed);
+            }
+        }
+    }
+
+    /// Applies volumes from settings to every playing sound, so changes are heard immediately,
+    /// even on looping sounds. `listener` is position of the camera the player hears through,
+    /// sounds are checked for occlusion against it.
+    pub fn update(
+        &mut self,
+        graph: &mut Graph,
+        settings: &Settings,
+        listener: Option<Vector3<f32>>,
+        dt: f32,
+    ) {
+        self.scan(graph);
+
+        self.sounds
+            .retain(|managed| graph.is_valid_handle(managed.sound));
+
+        let volumes = SOUND_CATEGORIES.map(|category| settings.volume(category));
+        if volumes != self.volumes {
+            self.volumes = volumes;
+            for managed in self.sounds.iter() {
+                self.apply_gain(graph, managed);
+            }
+        }
+
+        match listener {
+            Some(listener) if settings.sound_occlusion => {
+                self.update_occlusion(graph, listener, dt)
+            }
+            _ => {
+                // Nothing to hear through, sounds are restored and checked again once
+                // occlusion is back.
+                for i in 0..self.sounds.len() {
+                    let managed = &mut self.sounds[i];
+                    managed.check_timer = 0.0;
+                    if managed.occlusion != 1.0 {
+                        managed.occlusion = 1.0;
+                        let managed = &self.sounds[i];
+                        self.apply_gain(graph, managed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fades the reverb towards given preset, it is the preset of the zone the listener is in.
+    pub fn update_reverb(&mut self, graph: &mut Graph, target: ReverbPreset, dt: f32) {
+        let preset = self.reverb_mixer.update(target, dt);
+        if self.reverb.is_some() && self.applied_reverb != Some(preset) {
+            self.applied_reverb = Some(preset);
+            reverb::apply_preset(graph.sound_context.effect_mut(self.reverb), &preset);
+        }
     }
 
     pub async fn handle_message(
@@ -84,7 +387,11 @@ impl SoundManager {
                 gain,
                 rolloff_factor,
                 radius,
+                category,
             } => {
+                // Must be done before the new sound is added, otherwise it'd be found twice.
+                self.scan(graph);
+
                 if let Ok(buffer) = resource_manager.request_sound_buffer(path).await {
                     let sound = SoundBuilder::new(
                         BaseBuilder::new().with_local_transform(
@@ -96,7 +403,7 @@ impl SoundManager {
                     .with_buffer(buffer.into())
                     .with_status(Status::Playing)
                     .with_play_once(true)
-                    .with_gain(*gain)
+                    .with_gain(*gain * self.volume(*category))
                     .with_radius(*radius)
                     .with_rolloff_factor(*rolloff_factor)
                     .build(graph);
@@ -109,9 +416,14 @@ impl SoundManager {
                             sound,
                             filter: None,
                         });
+
+                    // Occlusion of the new sound is checked on next update, before it's heard
+                    // for long.
+                    self.sounds.push(ManagedSound::new(sound, *gain, *category));
                 } else {
+                    // Missing sounds are not fatal, the game goes on silently.
                     Log::writeln(
-                        MessageKind::Error,
+                        MessageKind::Warning,
                         format!("Unable to play sound {:?}", path),
                     );
                 }
@@ -132,11 +444,18 @@ pub struct Level {
     jump_pads: JumpPadContainer,
     items: ItemContainer,
     spawn_points: Vec<SpawnPoint>,
+    usables: UsableContainer,
     #[visit(skip)]
     sender: Option<Sender<Message>>,
     #[visit(skip)]
     pub control_scheme: Option<Arc<RwLock<ControlScheme>>>,
+    #[visit(skip)]
+    pub settings: Option<Arc<RwLock<Settings>>>,
+    #[visit(skip)]
+    pub captions: Option<Arc<CaptionList>>,
     death_zones: Vec<DeathZone>,
+    reverb_zones: Vec<ReverbZone>,
+    patrol_routes: Vec<PatrolRoute>,
     pub options: MatchOptions,
     time: f32,
     pub leader_board: LeaderBoard,
@@ -144,6 +463,42 @@ pub struct Level {
     spectator_camera: Handle<Node>,
     target_spectator_position: Vector3<f32>,
     sound_manager: SoundManager,
+    music: MusicManager,
+    #[visit(skip)]
+    pub event_log: EventLog,
+    player_life: LifeStats,
+    // Filled when the player dies and cleared when the player spawns.
+    player_death: Option<PlayerDeath>,
+    /// Identifier of the level in the level list.
+    pub level_id: String,
+    boss_fight: Option<BossFight>,
+    /// Distances and times at which bots drop to reduced update rate.
+    pub ai_lod: AiLodOptions,
+    friendly_fire: bool,
+    corpse_lifetime: f32,
+    // Level time of the last shot of every actor, enemies reveal themselves on the radar by
+    // shooting.
+    #[visit(skip)]
+    shot_times: HashMap<Handle<Actor>, f32>,
+    // Ring buffer of decals, `next_decal` is the slot that is replaced next when it's full.
+    decals: Vec<Handle<Node>>,
+    next_decal: u32,
+    effect_pool: EffectPool,
+    tracers: TracerPool,
+    // Holds gameplay resources for as long as the level lives.
+    #[visit(skip)]
+    preload: PreloadCache,
+    #[visit(skip)]
+    query_buffer: Vec<Intersection>,
+    #[visit(skip)]
+    actor_query: GridQuery<usize>,
+    #[visit(skip)]
+    item_query: GridQuery<Handle<Item>>,
+    #[visit(skip)]
+    player_positions: Vec<Vector3<f32>>,
+    /// Parts of bots drawn by `debug_draw`.
+    #[visit(skip)]
+    pub bot_debug_flags: BotDebugFlags,
 }
 
 impl Default for Level {
@@ -158,9 +513,14 @@ impl Default for Level {
             jump_pads: JumpPadContainer::new(),
             items: ItemContainer::new(),
             spawn_points: Default::default(),
+            usables: Default::default(),
             sender: None,
             control_scheme: None,
+            settings: None,
+            captions: None,
             death_zones: Default::default(),
+            reverb_zones: Default::default(),
+            patrol_routes: Default::default(),
             options: Default::default(),
             time: 0.0,
             leader_board: Default::default(),
@@ -168,10 +528,53 @@ impl Default for Level {
             spectator_camera: Default::default(),
             target_spectator_position: Default::default(),
             sound_manager: Default::default(),
+            music: Default::default(),
+            event_log: Default::default(),
+            player_life: Default::default(),
+            player_death: None,
+            level_id: Default::default(),
+            boss_fight: None,
+            ai_lod: Default::default(),
+            friendly_fire: true,
+            corpse_lifetime: CORPSE_LIFETIME,
+            shot_times: Default::default(),
+            decals: Default::default(),
+            next_decal: 0,
+            effect_pool: Default::default(),
+            tracers: Default::default(),
+            preload: Default::default(),
+            query_buffer: Default::default(),
+            actor_query: Default::default(),
+            item_query: Default::default(),
+            player_positions: Default::default(),
+            bot_debug_flags: Default::default(),
         }
     }
 }
 
+/// Statistics of the current life of the player.
+#[derive(Default, Visit)]
+struct LifeStats {
+    kills: u32,
+    damage_dealt: f32,
+    spawn_time: f32,
+}
+
+/// Actor whose health bar is shown on the HUD, see `Message::SetBossBar`.
+#[derive(Default, Visit)]
+struct BossFight {
+    actor: Handle<Actor>,
+    // Time the player spent out of combat range of the boss.
+    out_of_range_time: f32,
+}
+
+#[derive(Default, Visit)]
+struct PlayerDeath {
+    killer: Option<String>,
+    weapon: Option<String>,
+    time: f32,
+}
+
 #[derive(Visit)]
 pub struct DeathZone {
     bounds: AxisAlignedBoundingBox,
@@ -191,23 +594,24 @@ pub struct UpdateContext<'a> {
     pub items: &'a ItemContainer,
     pub jump_pads: &'a JumpPadContainer,
     pub weapons: &'a WeaponContainer,
-}
-
-#[derive(Visit)]
-struct PlayerRespawnEntry {
-    time_left: f32,
-}
-
-impl Default for PlayerRespawnEntry {
-    fn default() -> Self {
-        Self { time_left: 0.0 }
-    }
+    pub usables: &'a UsableContainer,
+    /// Projectiles in flight that bots can dodge.
+    pub projectiles: &'a [ProjectileDescriptor],
+    pub ai_lod: AiLodOptions,
+    /// Positions of living players, bots far from all of them are updated at reduced rate.
+    pub player_positions: &'a [Vector3<f32>],
+    /// Scratch buffer for ray casts of actors, it keeps its memory between frames.
+    pub query_buffer: &'a mut Vec<Intersection>,
+    /// Scratch buffers for spatial grid queries of bots, they keep their memory too.
+    pub actor_query: &'a mut GridQuery<usize>,
+    pub item_query: &'a mut GridQuery<Handle<Item>>,
 }
 
 #[derive(Visit)]
 struct BotRespawnEntry {
     name: String,
     kind: BotKind,
+    team: Team,
     time_left: f32,
 }
 
@@ -216,20 +620,21 @@ impl Default for BotRespawnEntry {
         Self {
             name: "".to_string(),
             kind: BotKind::Mutant,
+            team: Team::None,
             time_left: 0.0,
         }
     }
 }
 
+// Player is not respawned automatically, it is done from the death screen.
 #[derive(Visit)]
 enum RespawnEntry {
     Bot(BotRespawnEntry),
-    Player(PlayerRespawnEntry),
 }
 
 impl Default for RespawnEntry {
     fn default() -> Self {
-        RespawnEntry::Player(PlayerRespawnEntry::default())
+        RespawnEntry::Bot(BotRespawnEntry::default())
     }
 }
 
@@ -238,7 +643,10 @@ pub struct AnalysisResult {
     jump_pads: JumpPadContainer,
     items: ItemContainer,
     death_zones: Vec<DeathZone>,
+    reverb_zones: Vec<ReverbZone>,
     spawn_points: Vec<SpawnPoint>,
+    usables: UsableContainer,
+    patrol_routes: Vec<PatrolRoute>,
 }
 
 pub async fn analyze(
@@ -251,6 +659,8 @@ pub async fn analyze(
     let mut items = Vec::new();
     let mut spawn_points = Vec::new();
     let mut death_zones = Vec::new();
+    let mut reverb_zones = Vec::new();
+    let mut buttons = Vec::new();
     for (handle, node) in scene.graph.pair_iter() {
         let position = node.global_position();
         let name = node.name();
@@ -275,24 +685,80 @@ pub async fn analyze(
             items.push((ItemKind::M4Ammo, position));
         } else if name.starts_with("Ammo_Plasma") {
             items.push((ItemKind::Plasma, position));
+        } else if name.starts_with("Ammo_Shotgun") {
+            items.push((ItemKind::ShotgunShells, position));
         } else if name.starts_with("SpawnPoint") {
             spawn_points.push(node.global_position())
         } else if name.starts_with("DeathZone") {
             death_zones.push(handle);
+        } else if let Some(preset) = reverb::zone_preset_name(name) {
+            reverb_zones.push((handle, preset.to_owned()));
+        } else if name.starts_with("Door") {
+            // Door moves to "Open" marker when opened, or just slides up if there is no marker.
+            let open = scene.graph.find_by_name(handle, "Open");
+            let open_offset = if open.is_some() {
+                scene.graph[open].global_position() - position
+            } else {
+                Vector3::new(0.0, 2.5, 0.0)
+            };
+            let collider = scene.graph.find(handle, &mut |n| n.is_collider());
+            result
+                .usables
+                .add(Usable::door(handle, collider, open_offset));
+        } else if name.starts_with(patrol::ROUTE_PREFIX) {
+            let mode = if name.ends_with(patrol::PING_PONG_SUFFIX) {
+                PatrolMode::PingPong
+            } else {
+                PatrolMode::Loop
+            };
+            let points = node
+                .children()
+                .iter()
+                .map(|point| scene.graph[*point].global_position())
+                .collect::<Vec<_>>();
+            if points.is_empty() {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Patrol route {} has no points!", name),
+                );
+            } else {
+                result.patrol_routes.push(PatrolRoute::new(points, mode));
+            }
+        } else if let Some(door_name) = name.strip_prefix("Button_") {
+            // Button_Door1 operates door named Door1.
+            let collider = scene.graph.find(handle, &mut |n| n.is_collider());
+            buttons.push((handle, collider, door_name.to_owned()));
+        }
+    }
+
+    for (handle, collider, door_name) in buttons {
+        let door_node = scene.graph.find_by_name(scene.graph.get_root(), &door_name);
+        let door = result.usables.find_door_by_node(door_node);
+        if door.is_some() {
+            result.usables.add(Usable::button(handle, collider, door));
+        } else {
+            Log::writeln(
+                MessageKind::Warning,
+                format!("There is no door {} for a button!", door_name),
+            );
         }
     }
 
     for (kind, position) in items {
-        result.items.add(
-            Item::new(
-                kind,
-                position,
-                scene,
-                resource_manager.clone(),
-                sender.clone(),
-            )
-            .await,
-        );
+        match Item::new(
+            kind,
+            position,
+            scene,
+            resource_manager.clone(),
+            sender.clone(),
+        )
+        .await
+        {
+            Ok(item) => {
+                result.items.add(item);
+            }
+            Err(e) => Log::writeln(MessageKind::Error, format!("{}. Item is skipped.", e)),
+        }
     }
     for handle in death_zones {
         let node = &mut scene.graph[handle];
@@ -301,6 +767,24 @@ pub async fn analyze(
             bounds: node.world_bounding_box(),
         });
     }
+    for (handle, preset) in reverb_zones {
+        if reverb::preset(&preset).is_none() {
+            Log::writeln(
+                MessageKind::Warning,
+                format!(
+                    "There is no reverb preset {}, zone {} will use the default one!",
+                    preset,
+                    scene.graph[handle].name()
+                ),
+            );
+        }
+        let node = &mut scene.graph[handle];
+        node.set_visibility(false);
+        result.reverb_zones.push(ReverbZone {
+            bounds: node.world_bounding_box(),
+            preset,
+        });
+    }
     result.spawn_points = spawn_points
         .into_iter()
         .map(|p| SpawnPoint { position: p })
@@ -330,13 +814,13 @@ async fn spawn_player(
         .set_position(&mut scene.graph, spawn_position);
 
     let weapons_to_give = [WeaponKind::BattleAxe];
-    for (i, &weapon) in weapons_to_give.iter().enumerate() {
+    for &weapon in weapons_to_give.iter() {
         give_new_weapon(
             weapon,
+            None,
             player,
             sender.clone(),
             resource_manager.clone(),
-            i == weapons_to_give.len() - 1,
             weapons,
             actors,
             scene,
@@ -347,32 +831,68 @@ async fn spawn_player(
     player
 }
 
+/// `ammo` overrides the default amount of ammo the weapon comes with, the magazine is loaded
+/// from it and the rest goes to the reserve of the actor.
 async fn give_new_weapon(
     kind: WeaponKind,
+    ammo: Option<u32>,
     actor: Handle<Actor>,
     sender: Sender<Message>,
     resource_manager: ResourceManager,
-    visible: bool,
     weapons: &mut WeaponContainer,
     actors: &mut ActorContainer,
     scene: &mut Scene,
-) {
-    if actors.contains(actor) {
-        let mut weapon = Weapon::new(kind, resource_manager, scene, sender.clone()).await;
-        weapon.set_owner(actor);
-        let weapon_model = weapon.get_model();
-        scene.graph[weapon_model].set_visibility(visible);
-        let actor = actors.get_mut(actor);
-        let weapon_handle = weapons.add(weapon);
-        actor.add_weapon(weapon_handle);
-        scene.graph.link_nodes(weapon_model, actor.weapon_pivot());
-
-        sender
-            .send(Message::AddNotification {
-                text: format!("Actor picked up weapon {:?}", kind),
-            })
-            .unwrap();
+) -> Option<Handle<Weapon>> {
+    if !actors.contains(actor) {
+        return None;
+    }
+    let mut weapon = match Weapon::new(kind, resource_manager, scene, sender.clone()).await {
+        Ok(weapon) => weapon,
+        Err(e) => {
+            Log::writeln(MessageKind::Error, format!("{}. Weapon is not given.", e));
+            return None;
+        }
+    };
+    weapon.set_owner(actor);
+    let weapon_model = weapon.get_model();
+    // New weapon becomes the current one, it's raised once the weapon in hands is put away.
+    weapon.set_visibility(false, &mut scene.graph);
+    let definition = weapon.definition();
+    let reserve_ammo = weapon.load(ammo.unwrap_or(definition.ammo));
+    let actor = actors.get_mut(actor);
+    if let Some(ammo_type) = definition.ammo_type {
+        actor.give_ammo(ammo_type, reserve_ammo);
     }
+    let weapon_handle = weapons.add(weapon);
+    actor.add_weapon(weapon_handle);
+    scene.graph.link_nodes(weapon_model, actor.weapon_pivot());
+    Some(weapon_handle)
+}
+
+/// Point where a shot from `origin` lands, the end of its range if it hits nothing. The
+/// shooter's own capsule is ignored.
+fn shot_end_point(
+    graph: &Graph,
+    shooter_collider: Handle<Node>,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    range: f32,
+) -> Vector3<f32> {
+    let mut query_buffer = Vec::default();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(origin),
+            ray_direction: direction,
+            max_len: range,
+            groups: InteractionGroups::default(),
+            sort_results: true,
+        },
+        &mut query_buffer,
+    );
+    query_buffer
+        .iter()
+        .find(|hit| hit.collider != shooter_collider)
+        .map_or(origin + direction.scale(range), |hit| hit.position.coords)
 }
 
 fn find_suitable_spawn_point(
@@ -407,7 +927,10 @@ async fn spawn_bot(
     sender: Sender<Message>,
     leader_board: &mut LeaderBoard,
     scene: &mut Scene,
-) -> Handle<Actor> {
+    difficulty: BotDifficulty,
+    team: Team,
+    corpse_lifetime: f32,
+) -> Option<Handle<Actor>> {
     let index = find_suitable_spawn_point(spawn_points, actors, scene);
     let spawn_position = spawn_points
         .get(index)
@@ -423,12 +946,50 @@ async fn spawn_bot(
         sender,
         leader_board,
         scene,
+        difficulty,
+        team,
+        corpse_lifetime,
     )
     .await;
 
     bot
 }
 
+// Bot patrols the route that starts closest to where it has spawned, bots on levels without
+// routes go for items.
+fn assign_patrol_route(
+    routes: &[PatrolRoute],
+    actors: &mut ActorContainer,
+    bot: Handle<Actor>,
+    graph: &Graph,
+) {
+    let position = actors.get(bot).position(graph);
+    let route = routes.iter().min_by(|a, b| {
+        let a = (a.points()[0] - position).norm_squared();
+        let b = (b.points()[0] - position).norm_squared();
+        a.total_cmp(&b)
+    });
+    if let (Some(route), Actor::Bot(bot)) = (route, actors.get_mut(bot)) {
+        bot.set_patrol_route(route.clone());
+    }
+}
+
+/// Team of a bot that joins the match, bots of team matches go to the smaller team and
+/// everyone is on their own in deathmatch.
+fn new_bot_team(options: &MatchOptions, actors: &ActorContainer) -> Team {
+    match options {
+        MatchOptions::DeathMatch(_) => Team::None,
+        MatchOptions::TeamDeathMatch(_) | MatchOptions::CaptureTheFlag(_) => {
+            let count = |team| actors.iter().filter(|actor| actor.team() == team).count();
+            if count(Team::Blue) < count(Team::Red) {
+                Team::Blue
+            } else {
+                Team::Red
+            }
+        }
+    }
+}
+
 async fn add_bot(
     kind: BotKind,
     position: Vector3<f32>,
@@ -439,39 +1000,51 @@ async fn add_bot(
     sender: Sender<Message>,
     leader_board: &mut LeaderBoard,
     scene: &mut Scene,
-) -> Handle<Actor> {
-    let bot = Bot::new(
+    difficulty: BotDifficulty,
+    team: Team,
+    corpse_lifetime: f32,
+) -> Option<Handle<Actor>> {
+    let mut bot = match Bot::new(
         kind,
-        resource_manager.clone(),
+        resource_manager,
         scene,
         position,
-        sender.clone(),
+        sender,
+        difficulty,
+        Bot::get_definition(kind).loadout,
+        weapons,
     )
-    .await;
+    .await
+    {
+        Ok(bot) => bot,
+        Err(e) => {
+            Log::writeln(
+                MessageKind::Error,
+                format!("{}. {:?} is not spawned.", e, kind),
+            );
+            return None;
+        }
+    };
+    bot.set_team(team);
+    bot.corpse_lifetime = corpse_lifetime;
     let name = name.unwrap_or_else(|| format!("Bot {:?} {}", kind, actors.count()));
     leader_board.get_or_add_actor(&name);
-    let bot = actors.add(Actor::Bot(bot));
-    give_new_weapon(
-        WeaponKind::BattleAxe,
-        bot,
-        sender.clone(),
-        resource_manager,
-        true,
-        weapons,
-        actors,
-        scene,
-    )
-    .await;
-    bot
+    let handle = actors.add(Actor::Bot(bot));
+    if let Actor::Bot(bot) = actors.get(handle) {
+        bot.set_weapons_owner(handle, weapons);
+    }
+    Some(handle)
 }
 
 impl Level {
     pub async fn new(
         resource_manager: ResourceManager,
         control_scheme: Arc<RwLock<ControlScheme>>,
+        settings: Arc<RwLock<Settings>>,
         sender: Sender<Message>,
         options: MatchOptions,
-    ) -> (Level, Scene) {
+        definition: LevelDefinition,
+    ) -> Result<(Level, Scene), String> {
         let mut scene = Scene::new();
 
         scene.ambient_lighting_color = Color::opaque(60, 60, 60);
@@ -487,11 +1060,19 @@ impl Level {
 
         // Instantiate map
         let map_root = resource_manager
-            .request_model(Path::new("data/levels/dm6.rgs"))
+            .request_model(&definition.path)
             .await
-            .unwrap()
+            .map_err(|e| format!("unable to load map {} - {:?}", definition.path.display(), e))?
             .instantiate_geometry(&mut scene);
 
+        let music =
+            MusicManager::new(&mut scene.graph, resource_manager.clone(), &definition).await;
+
+        // Everything that can be spawned is loaded before the first spawn, so nothing hitches
+        // mid-match.
+        let preload =
+            PreloadCache::load(&resource_manager, &PreloadList::gameplay(), &sender).await;
+
         // Make sure global coordinates are calculated.
         scene.update(Vector2::new(1.0, 1.0), 0.0);
 
@@ -499,14 +1080,24 @@ impl Level {
             jump_pads,
             items,
             death_zones,
+            reverb_zones,
             spawn_points,
+            usables,
+            patrol_routes,
         } = analyze(&mut scene, resource_manager.clone(), sender.clone()).await;
         let mut actors = ActorContainer::new();
         let mut weapons = WeaponContainer::new();
         let mut leader_board = LeaderBoard::default();
-
-        for &kind in &[BotKind::Maw, BotKind::Mutant, BotKind::Parasite] {
-            spawn_bot(
+        let difficulty = settings.read().unwrap().difficulty.bot_difficulty();
+
+        for &kind in &[
+            BotKind::Maw,
+            BotKind::Mutant,
+            BotKind::Parasite,
+            BotKind::Soldier,
+            BotKind::Hound,
+        ] {
+            let bot = spawn_bot(
                 kind,
                 Some(kind.description().to_owned()),
                 &spawn_points,
@@ -516,8 +1107,14 @@ impl Level {
                 sender.clone(),
                 &mut leader_board,
                 &mut scene,
+                difficulty,
+                new_bot_team(&options, &actors),
+                definition.corpse_lifetime,
             )
             .await;
+            if let Some(bot) = bot {
+                assign_patrol_route(&patrol_routes, &mut actors, bot, &scene.graph);
+            }
         }
 
         let level = Level {
@@ -539,22 +1136,67 @@ impl Level {
             jump_pads,
             items,
             death_zones,
+            reverb_zones,
+            patrol_routes,
             spawn_points,
+            usables,
             leader_board,
             scene: Handle::NONE, // Filled when scene will be moved to engine.
             sender: Some(sender),
             control_scheme: Some(control_scheme),
+            settings: Some(settings),
             time: 0.0,
             respawn_list: Default::default(),
             projectiles: ProjectileContainer::new(),
             target_spectator_position: Default::default(),
             sound_manager,
+            music,
+            event_log: Default::default(),
+            player_life: Default::default(),
+            player_death: None,
+            level_id: definition.id,
+            boss_fight: None,
+            ai_lod: definition.ai_lod,
+            friendly_fire: definition.friendly_fire,
+            corpse_lifetime: definition.corpse_lifetime,
+            shot_times: Default::default(),
+            decals: Default::default(),
+            next_decal: 0,
+            effect_pool: Default::default(),
+            tracers: Default::default(),
+            preload,
+            query_buffer: Default::default(),
+            actor_query: Default::default(),
+            item_query: Default::default(),
+            player_positions: Default::default(),
+            bot_debug_flags: Default::default(),
         };
 
-        (level, scene)
+        Ok((level, scene))
+    }
+
+    /// Gameplay resources are not saved, a loaded level gets them from here.
+    pub fn set_preload(&mut self, preload: PreloadCache) {
+        self.preload = preload;
+    }
+
+    /// Repairs known invariants of the level state, must be called before saving.
+    pub fn validate(&mut self) {
+        self.actors.validate(&self.weapons);
+        if self.player.is_some() && !self.actors.contains(self.player) {
+            Log::writeln(
+                MessageKind::Warning,
+                "Player handle is invalid, dropped.".to_owned(),
+            );
+            self.player = Handle::NONE;
+        }
     }
 
     pub fn destroy(&mut self, engine: &mut Engine) {
+        let graph = &mut engine.scenes[self.scene].graph;
+        self.effect_pool.clear(graph);
+        self.tracers.clear(graph);
+        self.music.stop(graph);
         engine.scenes.remove(self.scene);
     }
 
@@ -563,18 +1205,19 @@ impl Level {
         engine: &mut Engine,
         actor: Handle<Actor>,
         kind: WeaponKind,
-    ) {
+        ammo: Option<u32>,
+    ) -> Option<Handle<Weapon>> {
         give_new_weapon(
             kind,
+            ammo,
             actor,
             self.sender.clone().unwrap(),
             engine.resource_manager.clone(),
-            true,
             &mut self.weapons,
             &mut self.actors,
             &mut engine.scenes[self.scene],
         )
-        .await;
+        .await
     }
 
     async fn spawn_player(&mut self, engine: &mut Engine) -> Handle<Actor> {
@@ -595,6 +1238,12 @@ impl Level {
             spectator_camera.set_enabled(false);
         }
 
+        self.player_death = None;
+        self.player_life = LifeStats {
+            spawn_time: self.time,
+            ..Default::default()
+        };
+
         player
     }
 
@@ -611,150 +1260,548 @@ impl Level {
         false
     }
 
-    pub fn actors(&self) -> &ActorContainer {
-        &self.actors
+    pub fn reset_player_input(&mut self) {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                player.reset_input();
+            }
+        }
     }
 
-    pub fn actors_mut(&mut self) -> &mut ActorContainer {
-        &mut self.actors
+    /// Tells if any bot is after the player right now, music switches to combat on it.
+    fn is_player_targeted(&self) -> bool {
+        self.player.is_some()
+            && self.actors.iter().any(|actor| match actor {
+                Actor::Bot(bot) => bot.target_handle() == Some(self.player),
+                _ => false,
+            })
     }
 
-    pub fn weapons(&self) -> &WeaponContainer {
-        &self.weapons
+    /// Camera the player looks and hears through, the spectator camera while the player is
+    /// dead.
+    fn listener_camera(&self) -> Handle<Node> {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get(self.player) {
+                if !player.is_dead() {
+                    return player.camera();
+                }
+            }
+        }
+        self.spectator_camera
     }
 
-    fn pick(&self, engine: &mut Engine, from: Vector3<f32>, to: Vector3<f32>) -> Vector3<f32> {
-        let scene = &mut engine.scenes[self.scene];
-        let ray = Ray::from_two_points(from, to);
-        let options = RayCastOptions {
-            ray_origin: Point3::from(ray.origin),
-            ray_direction: ray.dir,
-            max_len: f32::MAX,
-            groups: InteractionGroups::default(),
-            sort_results: true,
+    /// Turns a sound into a caption if it is listed in the captions and is close enough to
+    /// be heard. Direction is relative to the camera the player looks through.
+    fn caption_sound(&self, engine: &Engine, path: &Path, position: Vector3<f32>) {
+        let enabled = self
+            .settings
+            .as_ref()
+            .map_or(false, |settings| settings.read().unwrap().captions);
+        if !enabled {
+            return;
+        }
+        let definition = match self
+            .captions
+            .as_ref()
+            .and_then(|captions| captions.get(path))
+        {
+            Some(definition) => definition,
+            None => return,
         };
-        let mut query_buffer = Vec::default();
-        scene.graph.physics.cast_ray(options, &mut query_buffer);
-        if let Some(pt) = query_buffer.first() {
-            pt.position.coords
-        } else {
-            from
+
+        let camera = self.listener_camera();
+        let graph = &engine.scenes[self.scene].graph;
+        if !graph.is_valid_handle(camera) {
+            return;
+        }
+        let camera = &graph[camera];
+        let listener = camera.global_position();
+        if listener.metric_distance(&position) > CAPTION_DISTANCE {
+            return;
+        }
+
+        if let Some(sender) = self.sender.as_ref() {
+            sender
+                .send(Message::ShowCaption {
+                    text: tr_format(
+                        "caption.format",
+                        &[
+                            &definition.text,
+                            &captions::direction_hint(listener, camera.look_vector(), position),
+                        ],
+                    ),
+                    duration: definition.duration,
+                })
+                .unwrap();
         }
     }
 
-    fn remove_weapon(&mut self, engine: &mut Engine, weapon: Handle<Weapon>) {
-        for projectile in self.projectiles.iter_mut() {
-            if projectile.owner == weapon {
-                // Reset owner because handle to weapon will be invalid after weapon freed.
-                projectile.owner = Handle::NONE;
+    /// Returns text of the "use" prompt for the object player is looking at, if any.
+    pub fn use_prompt(&self) -> Option<&str> {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get(self.player) {
+                return player.use_prompt();
             }
         }
-        self.weapons[weapon].clean_up(&mut engine.scenes[self.scene]);
-        self.weapons.free(weapon);
+        None
     }
 
-    async fn add_bot(
-        &mut self,
-        engine: &mut Engine,
-        kind: BotKind,
-        position: Vector3<f32>,
-        name: Option<String>,
-    ) -> Handle<Actor> {
-        add_bot(
-            kind,
-            position,
-            name,
-            &mut self.actors,
-            &mut self.weapons,
-            engine.resource_manager.clone(),
-            self.sender.clone().unwrap(),
-            &mut self.leader_board,
-            &mut engine.scenes[self.scene],
-        )
-        .await
+    /// Returns state of the player for the HUD, `None` if there is no alive player.
+    pub fn hud_data(&self, graph: &Graph) -> Option<HudData> {
+        if self.player.is_none() {
+            return None;
+        }
+        let player = self.actors.get(self.player);
+        if player.is_dead() {
+            return None;
+        }
+
+        let (show_crosshair, show_damage_indicators) =
+            self.control_scheme
+                .as_ref()
+                .map_or((true, true), |control_scheme| {
+                    let control_scheme = control_scheme.read().unwrap();
+                    (
+                        control_scheme.show_crosshair,
+                        control_scheme.show_damage_indicators,
+                    )
+                });
+
+        let mut damage_indicators = Vec::new();
+        let mut weapon_wheel = None;
+        let (spread, is_sprinting) = if let Actor::Player(player) = player {
+            if let Some((weapons, highlighted)) = player.weapon_wheel() {
+                weapon_wheel = Some(WeaponWheelData {
+                    items: weapons
+                        .iter()
+                        .filter(|&&weapon| self.weapons.contains(weapon))
+                        .map(|&weapon| {
+                            let weapon = &self.weapons[weapon];
+                            let definition = weapon.definition();
+                            WeaponWheelItem {
+                                kind: weapon.get_kind(),
+                                icon: definition.icon,
+                                ammo: player.weapon_ammo(weapon),
+                                is_melee: weapon.is_melee(),
+                            }
+                        })
+                        .collect(),
+                    highlighted,
+                });
+            }
+            if show_damage_indicators {
+                for event in player.recent_damage() {
+                    let age = self.time - event.time;
+                    if age < RECENT_DAMAGE_TIME {
+                        damage_indicators.push(DamageIndicator {
+                            // Angle is recalculated every frame, so indicator keeps pointing
+                            // to the source while player turns.
+                            angle: event
+                                .source
+                                .map(|source| player.horizontal_angle_to(source)),
+                            intensity: 1.0 - age / RECENT_DAMAGE_TIME,
+                        });
+                    }
+                }
+            }
+            (player.spread(), player.is_sprinting())
+        } else {
+            (0.0, false)
+        };
+
+        let current_weapon = player.current_weapon();
+        let weapon = if self.weapons.contains(current_weapon) {
+            let weapon = &self.weapons[current_weapon];
+            let definition = weapon.definition();
+            Some(WeaponHudData {
+                kind: weapon.get_kind(),
+                icon: definition.icon,
+                ammo: player.weapon_ammo(weapon),
+                max_ammo: definition.ammo,
+                ammo_in_magazine: weapon.ammo_in_magazine(),
+                reserve_ammo: player.reserve_ammo_for(weapon),
+                is_reloading: weapon.is_reloading(),
+                heat: if definition.overheat_threshold > 0.0 {
+                    Some(weapon.heat_fraction())
+                } else {
+                    None
+                },
+                is_overheated: weapon.is_overheated(),
+                is_melee: weapon.is_melee(),
+                crosshair: if show_crosshair && !is_sprinting {
+                    Some(definition.crosshair)
+                } else {
+                    None
+                },
+                spread,
+            })
+        } else {
+            None
+        };
+
+        let view_projection = match player {
+            Actor::Player(player) => graph[player.camera()].as_camera().view_projection_matrix(),
+            _ => Matrix4::identity(),
+        };
+
+        let radar = match player {
+            Actor::Player(player) => self.settings.as_ref().and_then(|settings| {
+                let settings = settings.read().unwrap();
+                if settings.radar && settings.difficulty.allows_radar() {
+                    Some(RadarData {
+                        size: settings.radar_size,
+                        blips: self.radar_blips(graph, player, &view_projection),
+                    })
+                } else {
+                    None
+                }
+            }),
+            _ => None,
+        };
+
+        Some(HudData {
+            health: player.get_health(),
+            max_health: MAX_HEALTH,
+            armor: player.get_armor(),
+            max_armor: MAX_ARMOR,
+            weapon,
+            damage_indicators,
+            weapon_wheel,
+            view_projection,
+            radar,
+        })
+    }
+
+    /// Collects actors and key items around the player. Allies are always shown, enemies
+    /// only when the player sees them or when they've shot recently.
+    fn radar_blips(
+        &self,
+        graph: &Graph,
+        player: &Player,
+        view_projection: &Matrix4<f32>,
+    ) -> Vec<RadarBlip> {
+        let position = player.position(graph);
+        let offset_to = |point: Vector3<f32>| {
+            let angle = player.horizontal_angle_to(point);
+            let distance = Vector2::new(point.x - position.x, point.z - position.z).norm();
+            Vector2::new(angle.sin(), angle.cos()) * distance
+        };
+
+        let camera_position = graph[player.camera()].global_position();
+        let frustum = Frustum::from(*view_projection);
+        let mut query_buffer = Vec::default();
+        let mut is_visible = |target: &Actor, point: Vector3<f32>| {
+            if !frustum
+                .as_ref()
+                .map_or(false, |frustum| frustum.is_contains_point(point))
+            {
+                return false;
+            }
+            let ray = Ray::from_two_points(camera_position, point);
+            graph.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(ray.origin),
+                    ray_direction: ray.dir,
+                    groups: InteractionGroups::default(),
+                    max_len: ray.dir.norm(),
+                    sort_results: true,
+                },
+                &mut query_buffer,
+            );
+            for hit in query_buffer.iter() {
+                let collider = graph[hit.collider].as_collider();
+                if collider.parent() == target.get_body() {
+                    return true;
+                }
+                if matches!(collider.shape(), ColliderShape::Trimesh(_)) {
+                    // Target is behind a wall.
+                    return false;
+                }
+            }
+            true
+        };
+
+        let mut blips = Vec::new();
+        for (handle, actor) in self.actors.pair_iter() {
+            if handle == self.player || actor.is_dead() {
+                continue;
+            }
+            let actor_position = actor.position(graph);
+            if actor_position.metric_distance(&position) > radar::RADIUS_METERS {
+                continue;
+            }
+            let kind = if actor.team().is_ally_of(player.team()) {
+                RadarBlipKind::Ally
+            } else {
+                let has_shot = self
+                    .shot_times
+                    .get(&handle)
+                    .map_or(false, |time| self.time - time < RADAR_SHOT_TIME);
+                if !has_shot && !is_visible(actor, actor_position) {
+                    continue;
+                }
+                RadarBlipKind::Enemy
+            };
+            blips.push(RadarBlip {
+                kind,
+                offset: offset_to(actor_position),
+            });
+        }
+
+        for item in self.items.iter() {
+            if item.is_picked_up() || !item.get_kind().is_key_item() {
+                continue;
+            }
+            let item_position = item.position(graph);
+            if item_position.metric_distance(&position) > radar::RADIUS_METERS {
+                continue;
+            }
+            blips.push(RadarBlip {
+                kind: RadarBlipKind::Item(Item::get_definition(item.get_kind()).icon),
+                offset: offset_to(item_position),
+            });
+        }
+
+        blips
+    }
+
+    /// Returns scoreboard rows of every actor that has a score or is present on the level.
+    pub fn scoreboard_rows(&self) -> Vec<ScoreboardRow> {
+        let mut rows = self
+            .leader_board
+            .values()
+            .iter()
+            .map(|(name, score)| ScoreboardRow {
+                name: name.clone(),
+                team: Team::None,
+                kills: score.kills,
+                deaths: score.deaths,
+                is_player: false,
+            })
+            .collect::<Vec<_>>();
+
+        let player_name = if self.player.is_some() {
+            Some(self.actors.get(self.player).name.as_str())
+        } else {
+            None
+        };
+        for actor in self.actors.iter() {
+            let is_player = player_name == Some(actor.name.as_str());
+            match rows.iter_mut().find(|row| row.name == actor.name) {
+                Some(row) => {
+                    row.team = actor.team();
+                    row.is_player = is_player;
+                }
+                None => rows.push(ScoreboardRow {
+                    name: actor.name.clone(),
+                    team: actor.team(),
+                    kills: 0,
+                    deaths: 0,
+                    is_player,
+                }),
+            }
+        }
+
+        rows
+    }
+
+    pub fn process_gamepad_event(&mut self, event: &GamepadEvent) {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                player.process_gamepad_event(event);
+            }
+        }
+    }
+
+    pub fn actors(&self) -> &ActorContainer {
+        &self.actors
+    }
+
+    pub fn actors_mut(&mut self) -> &mut ActorContainer {
+        &mut self.actors
+    }
+
+    pub fn weapons(&self) -> &WeaponContainer {
+        &self.weapons
+    }
+
+    fn pick(&self, engine: &mut Engine, from: Vector3<f32>, to: Vector3<f32>) -> Vector3<f32> {
+        let scene = &mut engine.scenes[self.scene];
+        let ray = Ray::from_two_points(from, to);
+        let options = RayCastOptions {
+            ray_origin: Point3::from(ray.origin),
+            ray_direction: ray.dir,
+            max_len: f32::MAX,
+            groups: InteractionGroups::default(),
+            sort_results: true,
+        };
+        let mut query_buffer = Vec::default();
+        scene.graph.physics.cast_ray(options, &mut query_buffer);
+        if let Some(pt) = query_buffer.first() {
+            pt.position.coords
+        } else {
+            from
+        }
+    }
+
+    fn remove_weapon(&mut self, engine: &mut Engine, weapon: Handle<Weapon>) {
+        self.disown_projectiles(weapon);
+        self.weapons
+            .remove(weapon, &mut engine.scenes[self.scene], &mut self.actors);
+    }
+
+    fn disown_projectiles(&mut self, weapon: Handle<Weapon>) {
+        for projectile in self.projectiles.iter_mut() {
+            if projectile.owner == weapon {
+                // Reset owner because handle to weapon will be invalid after weapon freed.
+                projectile.owner = Handle::NONE;
+            }
+        }
+    }
+
+    async fn add_bot(
+        &mut self,
+        engine: &mut Engine,
+        kind: BotKind,
+        position: Vector3<f32>,
+        name: Option<String>,
+    ) -> Option<Handle<Actor>> {
+        let team = new_bot_team(&self.options, &self.actors);
+        add_bot(
+            kind,
+            position,
+            name,
+            &mut self.actors,
+            &mut self.weapons,
+            engine.resource_manager.clone(),
+            self.sender.clone().unwrap(),
+            &mut self.leader_board,
+            &mut engine.scenes[self.scene],
+            self.bot_difficulty(),
+            team,
+            self.corpse_lifetime,
+        )
+        .await
+    }
+
+    // Bots are as good as the difficulty of the settings at the moment they spawn.
+    fn bot_difficulty(&self) -> BotDifficulty {
+        self.settings
+            .as_ref()
+            .map_or_else(BotDifficulty::default, |settings| {
+                settings.read().unwrap().difficulty.bot_difficulty()
+            })
     }
 
     async fn remove_actor(&mut self, engine: &mut Engine, actor: Handle<Actor>) {
         if self.actors.contains(actor) {
-            let scene = &mut engine.scenes[self.scene];
-            let character = self.actors.get(actor);
-
             // Make sure to remove weapons and drop appropriate items (items will be temporary).
-            let drop_position = character.position(&scene.graph);
-            let weapons = character
+            let weapons = self
+                .actors
+                .get(actor)
                 .weapons()
                 .iter()
                 .copied()
                 .collect::<Vec<Handle<Weapon>>>();
             for weapon in weapons {
-                let item_kind = match self.weapons[weapon].get_kind() {
-                    WeaponKind::M4 => ItemKind::M4,
-                    WeaponKind::Ak47 => ItemKind::Ak47,
-                    WeaponKind::PlasmaRifle => ItemKind::PlasmaGun,
-                    WeaponKind::RocketLauncher => ItemKind::RocketLauncher,
-                    WeaponKind::BattleAxe => ItemKind::BattleAxe,
-                };
-                self.spawn_item(engine, item_kind, drop_position, true, Some(20.0))
-                    .await;
-                self.remove_weapon(engine, weapon);
+                self.drop_weapon(engine, weapon).await;
             }
 
             let scene = &mut engine.scenes[self.scene];
             self.actors.get_mut(actor).clean_up(scene);
             self.actors.free(actor);
 
+            // Any weapon that still points at a removed actor would never be cleaned up.
+            for weapon in self.weapons.remove_orphans(scene, &self.actors) {
+                self.disown_projectiles(weapon);
+            }
+
             if self.player == actor {
                 self.player = Handle::NONE;
             }
         }
     }
 
-    async fn give_item(&mut self, engine: &mut Engine, actor: Handle<Actor>, kind: ItemKind) {
+    /// Removes the weapon from its owner and spawns an item with the ammo that was left in it.
+    async fn drop_weapon(&mut self, engine: &mut Engine, weapon: Handle<Weapon>) {
+        if !self.weapons.contains(weapon) {
+            return;
+        }
+        let owner = self.weapons[weapon].owner();
+        let graph = &engine.scenes[self.scene].graph;
+        let position = if self.actors.contains(owner) {
+            self.actors.get(owner).position(graph)
+        } else {
+            self.weapons[weapon].get_shot_position(graph)
+        };
+        let kind = ItemKind::from_weapon(self.weapons[weapon].get_kind());
+        let mut ammo = self.weapons[weapon].ammo_in_magazine();
+        // Only dying actors drop weapons, so the whole reserve goes with the first dropped
+        // weapon of its ammo type.
+        if let (Some(ammo_type), true) = (
+            self.weapons[weapon].definition().ammo_type,
+            self.actors.contains(owner),
+        ) {
+            ammo += self.actors.get_mut(owner).take_ammo(ammo_type, u32::MAX);
+        }
+
+        self.remove_weapon(engine, weapon);
+
+        if let Some(item) = self
+            .spawn_item(engine, kind, position, true, Some(DROPPED_WEAPON_LIFETIME))
+            .await
+        {
+            self.items.get_mut(item).set_ammo(Some(ammo));
+        }
+    }
+
+    /// `ammo` overrides the default amount of ammo the item gives.
+    async fn give_item(
+        &mut self,
+        engine: &mut Engine,
+        actor: Handle<Actor>,
+        kind: ItemKind,
+        ammo: Option<u32>,
+    ) {
         if self.actors.contains(actor) {
             let character = self.actors.get_mut(actor);
             match kind {
-                ItemKind::BattleAxe => {
-                    self.give_new_weapon(engine, actor, WeaponKind::BattleAxe)
-                        .await;
+                ItemKind::Medkit => {
+                    let max_health = character.max_health();
+                    character.heal_up_to(20.0, max_health);
                 }
-                ItemKind::Medkit => character.heal(20.0),
-                ItemKind::Ak47 | ItemKind::PlasmaGun | ItemKind::M4 | ItemKind::RocketLauncher => {
-                    let weapon_kind = match kind {
-                        ItemKind::Ak47 => WeaponKind::Ak47,
-                        ItemKind::PlasmaGun => WeaponKind::PlasmaRifle,
-                        ItemKind::M4 => WeaponKind::M4,
-                        ItemKind::RocketLauncher => WeaponKind::RocketLauncher,
-                        _ => unreachable!(),
+                ItemKind::Ak47
+                | ItemKind::PlasmaGun
+                | ItemKind::M4
+                | ItemKind::RocketLauncher
+                | ItemKind::BattleAxe
+                | ItemKind::Shotgun => {
+                    let weapon_kind = match kind.weapon() {
+                        Some(weapon_kind) => weapon_kind,
+                        None => return,
                     };
 
-                    let mut found = false;
-                    for weapon_handle in character.weapons() {
-                        let weapon = &mut self.weapons[*weapon_handle];
-                        // If actor already has weapon of given kind, then just add ammo to it.
-                        if weapon.get_kind() == weapon_kind {
-                            found = true;
-                            weapon.add_ammo(200);
-                            break;
+                    let found = character
+                        .weapons()
+                        .iter()
+                        .any(|weapon| self.weapons[*weapon].get_kind() == weapon_kind);
+                    if found {
+                        // If actor already has weapon of given kind, then just add its ammo
+                        // to the reserve.
+                        if let Some(ammo_type) = Weapon::get_definition(weapon_kind).ammo_type {
+                            character.give_ammo(ammo_type, ammo.unwrap_or(200));
                         }
-                    }
-                    // Finally if actor does not have such weapon, give new one to him.
-                    if !found {
-                        self.give_new_weapon(engine, actor, weapon_kind).await;
+                    } else {
+                        // Finally if actor does not have such weapon, give new one to him.
+                        self.give_new_weapon(engine, actor, weapon_kind, ammo).await;
                     }
                 }
-                ItemKind::Plasma | ItemKind::Ak47Ammo | ItemKind::M4Ammo => {
-                    for weapon in character.weapons() {
-                        let weapon = &mut self.weapons[*weapon];
-                        let (weapon_kind, ammo) = match kind {
-                            ItemKind::Plasma => (WeaponKind::PlasmaRifle, 200),
-                            ItemKind::Ak47Ammo => (WeaponKind::Ak47, 200),
-                            ItemKind::M4Ammo => (WeaponKind::M4, 200),
-                            _ => continue,
-                        };
-                        if weapon.get_kind() == weapon_kind {
-                            weapon.add_ammo(ammo);
-                            break;
-                        }
+                ItemKind::Plasma
+                | ItemKind::Ak47Ammo
+                | ItemKind::M4Ammo
+                | ItemKind::ShotgunShells => {
+                    if let Some(ammo_type) = kind.ammo_type() {
+                        character.give_ammo(ammo_type, 200);
                     }
                 }
             }
@@ -765,13 +1812,28 @@ impl Level {
         if self.actors.contains(actor) && self.items.contains(item) {
             let item = self.items.get_mut(item);
 
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::AddNotification {
-                    text: format!("Actor picked up item {:?}", item.get_kind()),
-                })
-                .unwrap();
+            self.event_log.record(
+                self.time,
+                EventCategory::Pickup,
+                format!(
+                    "{} picked up {:?}",
+                    self.actors.get(actor).name,
+                    item.get_kind()
+                ),
+            );
+
+            if actor == self.player {
+                let definition = Item::get_definition(item.get_kind());
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::Notify {
+                        text: tr_format("notify.picked_up", &[&definition.name]),
+                        icon: Some(definition.icon.into()),
+                        priority: NotificationPriority::Normal,
+                    })
+                    .unwrap();
+            }
 
             let scene = &mut engine.scenes[self.scene];
             let position = item.position(&scene.graph);
@@ -781,14 +1843,16 @@ impl Level {
                 .as_ref()
                 .unwrap()
                 .send(Message::PlaySound {
-                    path: PathBuf::from("data/sounds/item_pickup.ogg"),
+                    path: PathBuf::from(ITEM_PICKUP_SOUND),
                     position,
                     gain: 1.0,
                     rolloff_factor: 3.0,
                     radius: 2.0,
+                    category: SoundCategory::Sfx,
                 })
                 .unwrap();
-            self.give_item(engine, actor, kind).await;
+            let ammo = item.ammo();
+            self.give_item(engine, actor, kind, ammo).await;
         }
     }
 
@@ -822,37 +1886,220 @@ impl Level {
         &mut self,
         engine: &mut Engine,
         weapon_handle: Handle<Weapon>,
-        initial_velocity: Vector3<f32>,
         time: GameTime,
         direction: Option<Vector3<f32>>,
+        is_burst_shot: bool,
     ) {
         if self.weapons.contains(weapon_handle) {
             let scene = &mut engine.scenes[self.scene];
             let weapon = &mut self.weapons[weapon_handle];
-            if weapon.try_shoot(scene, time) {
-                let kind = weapon.definition().projectile;
+            // Shot goes within the spread the weapon had before it, so a single tap is as
+            // accurate as the weapon gets.
+            let direction = weapon.spread_direction(
+                direction.unwrap_or_else(|| weapon.get_shot_direction(&scene.graph)),
+            );
+            let reserve_ammo = if self.actors.contains(weapon.owner()) {
+                self.actors.get(weapon.owner()).reserve_ammo_for(weapon)
+            } else {
+                0
+            };
+            let result = weapon.try_shoot(reserve_ammo, scene, time, is_burst_shot);
+            if result == ShootResult::NoAmmo {
+                // Bot switches to a loaded weapon right away instead of clicking until its
+                // next think.
+                let owner = weapon.owner();
+                if self.actors.contains(owner) {
+                    if let Actor::Bot(bot) = self.actors.get_mut(owner) {
+                        bot.select_weapon(&self.weapons);
+                    }
+                }
+            } else if result.fired() {
+                self.shot_times.insert(weapon.owner(), self.time);
+                let owner = weapon.owner();
+                let loudness = weapon.definition().loudness;
                 let position = weapon.get_shot_position(&scene.graph);
                 let direction = direction
-                    .unwrap_or_else(|| weapon.get_shot_direction(&scene.graph))
                     .try_normalize(std::f32::EPSILON)
                     .unwrap_or_else(|| Vector3::z());
                 let basis = weapon.world_basis(&scene.graph);
-                self.create_projectile(
-                    engine,
-                    kind,
-                    position,
-                    direction,
-                    initial_velocity,
-                    weapon_handle,
-                    basis,
-                )
-                .await;
+                let definition = weapon.definition();
+                let is_tracer_shot = weapon.is_tracer_shot();
+                match definition.attack {
+                    WeaponAttack::Projectile(kind) => {
+                        let initial_velocity = self.actors.velocity(owner);
+                        if is_tracer_shot {
+                            let shooter_collider = if self.actors.contains(owner) {
+                                self.actors.get(owner).collider
+                            } else {
+                                Handle::NONE
+                            };
+                            let end = shot_end_point(
+                                &scene.graph,
+                                shooter_collider,
+                                position,
+                                direction,
+                                Projectile::get_definition(kind).range(),
+                            );
+                            self.tracers.spawn(&mut scene.graph, position, end);
+                        }
+                        let pellet_damage = definition.pellet_damage();
+                        // Every pellet of a shotgun shot scatters around the shot direction.
+                        for _ in 0..definition.pellets_per_shot {
+                            let direction = if definition.pellets_per_shot > 1 {
+                                spread_direction(direction, definition.pellet_spread_degrees)
+                            } else {
+                                direction
+                            };
+                            let mut projectile = Projectile::new(
+                                kind,
+                                engine.resource_manager.clone(),
+                                &mut engine.scenes[self.scene],
+                                direction,
+                                position,
+                                weapon_handle,
+                                initial_velocity,
+                                self.sender.as_ref().unwrap().clone(),
+                                basis,
+                            )
+                            .await;
+                            projectile.set_damage(pellet_damage, definition.damage_falloff);
+                            self.projectiles.add(projectile);
+                        }
+                    }
+                    WeaponAttack::Melee { sweep, .. } => {
+                        // Swing goes from the owner, not from the end of the weapon.
+                        let origin = if self.actors.contains(owner) {
+                            self.actors.get(owner).position(&scene.graph)
+                        } else {
+                            position
+                        };
+                        let sweep = MeleeSweep {
+                            damage: definition.damage,
+                            ..sweep
+                        };
+                        self.melee_attack(engine, owner, origin, direction, sweep, time);
+                    }
+                }
+                // Shooter knows where it shoots from.
+                self.alert_bots(engine, position, loudness, owner);
             }
         }
     }
 
+    // Bots that can hear the sound go to check it out, `source` doesn't hear itself.
+    fn alert_bots(
+        &mut self,
+        engine: &Engine,
+        position: Vector3<f32>,
+        loudness: f32,
+        source: Handle<Actor>,
+    ) {
+        let graph = &engine.scenes[self.scene].graph;
+        for (handle, actor) in self.actors.pair_iter_mut() {
+            if let Actor::Bot(bot) = actor {
+                if handle != source {
+                    bot.on_sound_heard(graph, position, loudness);
+                }
+            }
+        }
+    }
+
+    fn melee_attack(
+        &mut self,
+        engine: &mut Engine,
+        who: Handle<Actor>,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        sweep: MeleeSweep,
+        time: GameTime,
+    ) {
+        // Height difference between attacker and target that is still within reach.
+        const MAX_HEIGHT_DIFFERENCE: f32 = 1.5;
+
+        let scene = &mut engine.scenes[self.scene];
+        let flat_direction = Vector3::new(direction.x, 0.0, direction.z)
+            .try_normalize(std::f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+
+        let half_angle_cos = (sweep.arc_degrees * 0.5).to_radians().cos();
+        let mut victims = Vec::new();
+        for (handle, actor) in self.actors.pair_iter() {
+            if handle == who || actor.is_dead() {
+                continue;
+            }
+            let to_actor = actor.position(&scene.graph) - origin;
+            let flat = Vector3::new(to_actor.x, 0.0, to_actor.z);
+            let distance = flat.norm();
+            let in_front = flat
+                .try_normalize(std::f32::EPSILON)
+                .map_or(true, |dir| dir.dot(&flat_direction) >= half_angle_cos);
+            if distance <= sweep.range && to_actor.y.abs() <= MAX_HEIGHT_DIFFERENCE && in_front {
+                victims.push((handle, distance));
+            }
+        }
+        if !sweep.cleave {
+            let closest = victims
+                .iter()
+                .copied()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            victims = closest.into_iter().collect();
+        }
+        let victims = victims
+            .into_iter()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        for &victim in victims.iter() {
+            let body = scene.graph[self.actors.get(victim).get_body()].as_rigid_body_mut();
+            let velocity = body.lin_vel() + flat_direction.scale(sweep.knockback);
+            body.set_lin_vel(velocity);
+        }
+
+        for victim in victims {
+            let hit = DamageHit {
+                position: self
+                    .actors
+                    .get(victim)
+                    .position(&engine.scenes[self.scene].graph),
+                direction: flat_direction,
+                normal: -flat_direction,
+            };
+            self.damage_actor(engine, victim, who, sweep.damage, Some(hit), time);
+        }
+    }
+
     fn show_weapon(&mut self, engine: &mut Engine, weapon_handle: Handle<Weapon>, state: bool) {
-        self.weapons[weapon_handle].set_visibility(state, &mut engine.scenes[self.scene].graph)
+        let weapon = &self.weapons[weapon_handle];
+        if state && self.actors.contains(weapon.owner()) {
+            self.event_log.record(
+                self.time,
+                EventCategory::WeaponSwitch,
+                format!(
+                    "{} switched to {:?}",
+                    self.actors.get(weapon.owner()).name,
+                    weapon.get_kind()
+                ),
+            );
+        }
+        let graph = &mut engine.scenes[self.scene].graph;
+        if state {
+            // Weapon comes up once the one that was in hands is put away.
+            let owner = self.weapons[weapon_handle].owner();
+            let delay = if self.actors.contains(owner) {
+                self.actors
+                    .get(owner)
+                    .weapons()
+                    .iter()
+                    .filter(|&&other| other != weapon_handle && self.weapons.contains(other))
+                    .map(|&other| self.weapons[other].lowering_time_left())
+                    .fold(0.0, f32::max)
+            } else {
+                0.0
+            };
+            self.weapons[weapon_handle].raise(delay, graph);
+        } else {
+            self.weapons[weapon_handle].lower(graph);
+        }
     }
 
     async fn spawn_bot(
@@ -860,7 +2107,8 @@ impl Level {
         engine: &mut Engine,
         kind: BotKind,
         name: Option<String>,
-    ) -> Handle<Actor> {
+        team: Team,
+    ) -> Option<Handle<Actor>> {
         let bot = spawn_bot(
             kind,
             name,
@@ -871,31 +2119,69 @@ impl Level {
             self.sender.clone().unwrap(),
             &mut self.leader_board,
             &mut engine.scenes[self.scene],
+            self.bot_difficulty(),
+            team,
+            self.corpse_lifetime,
         )
-        .await;
+        .await?;
+        assign_patrol_route(
+            &self.patrol_routes,
+            &mut self.actors,
+            bot,
+            &engine.scenes[self.scene].graph,
+        );
 
         self.sender
             .as_ref()
             .unwrap()
-            .send(Message::AddNotification {
-                text: format!("Bot {} spawned!", self.actors.get(bot).name),
+            .send(Message::Notify {
+                text: tr_format("notify.bot_spawned", &[&self.actors.get(bot).name]),
+                icon: None,
+                priority: NotificationPriority::Normal,
             })
             .unwrap();
 
-        bot
+        Some(bot)
     }
 
     fn damage_actor(
         &mut self,
-        engine: &Engine,
+        engine: &mut Engine,
         actor: Handle<Actor>,
         who: Handle<Actor>,
         amount: f32,
+        hit: Option<DamageHit>,
         time: GameTime,
     ) {
         if self.actors.contains(actor)
             && (who.is_none() || who.is_some() && self.actors.contains(who))
         {
+            // Self-damage is not friendly fire, own grenades hurt on every level.
+            if !self.friendly_fire
+                && who.is_some()
+                && who != actor
+                && self
+                    .actors
+                    .get(who)
+                    .team()
+                    .is_ally_of(self.actors.get(actor).team())
+            {
+                return;
+            }
+
+            // Difficulty affects only damage that bots deal to the player.
+            let amount = if actor == self.player
+                && who.is_some()
+                && matches!(self.actors.get(who), Actor::Bot(_))
+            {
+                amount
+                    * self.settings.as_ref().map_or(1.0, |settings| {
+                        settings.read().unwrap().difficulty.bot_damage_scale()
+                    })
+            } else {
+                amount
+            };
+
             let mut who_name = Default::default();
             let message = if who.is_some() {
                 who_name = self.actors.get(who).name.clone();
@@ -909,11 +2195,8 @@ impl Level {
                 format!("{} took {} damage!", self.actors.get(actor).name, amount)
             };
 
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::AddNotification { text: message })
-                .unwrap();
+            self.event_log
+                .record(self.time, EventCategory::Damage, message);
 
             let who_position = if who.is_some() {
                 let scene = &engine.scenes[self.scene];
@@ -921,20 +2204,331 @@ impl Level {
             } else {
                 None
             };
-            let actor = self.actors.get_mut(actor);
+            let actor_handle = actor;
+            if who.is_some() && who != actor_handle {
+                self.actors.get_mut(who).record_damage_dealt(time.elapsed);
+            }
+            let actor = self.actors.get_mut(actor_handle);
             if let Actor::Bot(bot) = actor {
                 if let Some(who_position) = who_position {
-                    bot.set_point_of_interest(who_position, time);
+                    bot.push_point_of_interest(who_position, PoiPriority::Sound, DAMAGE_POI_TIME);
+                    if who != actor_handle {
+                        bot.on_damaged(who, who_position, time.elapsed);
+                    }
                 }
             }
             let was_dead = actor.is_dead();
+            let resisted = actor.get_armor() > 0.0;
             actor.damage(amount);
-            if !was_dead && actor.is_dead() && who.is_some() {
-                self.leader_board.add_frag(who_name)
+            // Self-damage (own grenade, for example) has no meaningful direction.
+            actor.record_damage(
+                if who != actor_handle {
+                    who_position
+                } else {
+                    None
+                },
+                self.time,
+            );
+            if who.is_some() && who != actor_handle {
+                actor.record_attacker(who, self.time);
+            }
+            let killed = !was_dead && actor.is_dead();
+
+            if let Some(hit) = hit.filter(|_| !was_dead) {
+                self.spawn_blood(engine, actor_handle, hit, amount);
+            }
+
+            // Bots deal damage too, but only player's hits must be confirmed.
+            if who.is_some() && who == self.player && actor_handle != who && !was_dead {
+                self.player_life.damage_dealt += amount;
+                if killed {
+                    self.player_life.kills += 1;
+                }
+                self.send_damage_feedback(engine, actor_handle, amount, killed, resisted);
+            }
+
+            let actor = self.actors.get(actor_handle);
+            if killed {
+                let killer_handle = if who.is_some() && who != actor_handle {
+                    Some(who)
+                } else {
+                    actor
+                        .last_attacker()
+                        .filter(|&(attacker, time)| {
+                            self.time - time <= KILL_CREDIT_TIME && self.actors.contains(attacker)
+                        })
+                        .map(|(attacker, _)| attacker)
+                };
+                let killer = killer_handle.map(|killer| self.actors.get(killer));
+
+                let text = if let Some(killer) = killer {
+                    format!("{} killed {}", killer.name, actor.name)
+                } else if who.is_some() {
+                    format!("{} killed {}", who_name, actor.name)
+                } else {
+                    format!("{} died", actor.name)
+                };
+                self.event_log.record(self.time, EventCategory::Kill, text);
+
+                // Projectiles do not remember their weapon, so kill is attributed to the weapon
+                // the killer is holding right now.
+                let weapon = killer
+                    .map(|killer| killer.current_weapon())
+                    .filter(|&weapon| self.weapons.contains(weapon))
+                    .map(|weapon| self.weapons[weapon].get_kind());
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::ActorKilled {
+                        killer: killer.map(|killer| (killer.name.clone(), killer.team())),
+                        victim: actor.name.clone(),
+                        victim_team: actor.team(),
+                        weapon,
+                        // There is no hit box system, so kills are never headshots for now.
+                        headshot: false,
+                        involves_player: self.player.is_some()
+                            && (who == self.player
+                                || killer_handle == Some(self.player)
+                                || actor_handle == self.player),
+                    })
+                    .unwrap();
+
+                // Weapon in hands falls to the ground, the rest go with the corpse.
+                let current_weapon = actor.current_weapon();
+                if current_weapon.is_some() {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::DropWeapon {
+                            weapon: current_weapon,
+                        })
+                        .unwrap();
+                }
+
+                if actor_handle == self.player {
+                    self.player_death = Some(PlayerDeath {
+                        killer: killer.map(|killer| killer.name.clone()),
+                        weapon: weapon.map(|weapon| format!("{:?}", weapon)),
+                        time: self.time,
+                    });
+                }
+
+                if let Some(killer) = killer {
+                    self.leader_board.add_frag(&killer.name)
+                } else if who.is_some() {
+                    self.leader_board.add_frag(who_name)
+                }
             }
         }
     }
 
+    /// Splashes blood at the hit point and, for strong hits, leaves a decal on the wall behind
+    /// the victim. Actors that don't bleed only get a puff, so does everybody when violence
+    /// is reduced.
+    fn spawn_blood(
+        &mut self,
+        engine: &mut Engine,
+        victim: Handle<Actor>,
+        hit: DamageHit,
+        amount: f32,
+    ) {
+        let reduce_violence = self
+            .settings
+            .as_ref()
+            .map_or(false, |settings| settings.read().unwrap().reduce_violence);
+        let blood_color = self
+            .actors
+            .get(victim)
+            .blood_color()
+            .filter(|_| !reduce_violence);
+
+        let resource_manager = engine.resource_manager.clone();
+        let scene = &mut engine.scenes[self.scene];
+        let blood_color = match blood_color {
+            Some(blood_color) => blood_color,
+            None => {
+                self.effect_pool.spawn(
+                    &mut scene.graph,
+                    resource_manager,
+                    EffectKind::Puff,
+                    hit.position,
+                    hit.normal,
+                );
+                return;
+            }
+        };
+        self.effect_pool.spawn_blood(
+            &mut scene.graph,
+            resource_manager.clone(),
+            hit.position,
+            hit.normal,
+            blood_color,
+        );
+
+        if amount < BLOOD_DECAL_DAMAGE {
+            return;
+        }
+
+        let mut query_buffer = Vec::default();
+        scene.graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(hit.position),
+                ray_direction: hit.direction,
+                groups: InteractionGroups::default(),
+                max_len: BLOOD_DECAL_DISTANCE,
+                sort_results: true,
+            },
+            &mut query_buffer,
+        );
+        // First wall behind the victim, the victim itself and other actors are skipped.
+        let wall_hit = query_buffer.iter().find(|intersection| {
+            matches!(
+                scene.graph[intersection.collider].as_collider().shape(),
+                ColliderShape::Trimesh(_)
+            )
+        });
+        if let Some(wall_hit) = wall_hit {
+            // Decal projects along its local Y axis, so it's turned to face out of the wall.
+            let rotation = UnitQuaternion::rotation_between(&Vector3::y(), &wall_hit.normal)
+                .unwrap_or_default()
+                * UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    rand::thread_rng().gen_range(0.0..std::f32::consts::TAU),
+                );
+            let decal = DecalBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(wall_hit.position.coords)
+                        .with_local_rotation(rotation)
+                        .with_local_scale(Vector3::new(
+                            BLOOD_DECAL_SIZE,
+                            BLOOD_DECAL_SIZE * 0.5,
+                            BLOOD_DECAL_SIZE,
+                        ))
+                        .build(),
+                ),
+            )
+            .with_diffuse_texture(resource_manager.request_texture(effects::CIRCLE_TEXTURE))
+            .with_color(blood_color)
+            .build(&mut scene.graph);
+            self.add_decal(&mut scene.graph, decal);
+        }
+    }
+
+    /// Leaves a scorch mark of the surface around the explosion. The decal is a cube centered
+    /// at the explosion, so it marks the floor and walls next to it alike.
+    fn add_scorch(
+        &mut self,
+        engine: &mut Engine,
+        position: Vector3<f32>,
+        surface: Option<SurfaceKind>,
+    ) {
+        let scorch = match surface::explosion_definition(surface).scorch.as_ref() {
+            Some(scorch) => scorch,
+            None => return,
+        };
+        let graph = &mut engine.scenes[self.scene].graph;
+        let decal = DecalBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::y_axis(),
+                        rand::thread_rng().gen_range(0.0..std::f32::consts::TAU),
+                    ))
+                    .with_local_scale(Vector3::repeat(scorch.size))
+                    .build(),
+            ),
+        )
+        .with_diffuse_texture(
+            engine
+                .resource_manager
+                .request_texture(effects::SMOKE_TEXTURE),
+        )
+        .with_color(scorch.color)
+        .build(graph);
+        self.add_decal(graph, decal);
+    }
+
+    /// Adds decal to the ring buffer of decals, the oldest one is removed if it's full.
+    fn add_decal(&mut self, graph: &mut Graph, decal: Handle<Node>) {
+        if self.decals.len() < MAX_DECALS {
+            self.decals.push(decal);
+            return;
+        }
+        let slot = self.next_decal as usize % MAX_DECALS;
+        let old = std::mem::replace(&mut self.decals[slot], decal);
+        if graph.is_valid_handle(old) {
+            graph.remove_node(old);
+        }
+        self.next_decal = ((slot + 1) % MAX_DECALS) as u32;
+    }
+
+    fn send_damage_feedback(
+        &self,
+        engine: &Engine,
+        target: Handle<Actor>,
+        amount: f32,
+        killed: bool,
+        resisted: bool,
+    ) {
+        let (show_hit_marker, show_damage_number) =
+            self.control_scheme
+                .as_ref()
+                .map_or((true, false), |control_scheme| {
+                    let control_scheme = control_scheme.read().unwrap();
+                    (
+                        control_scheme.show_hit_markers,
+                        control_scheme.show_damage_numbers,
+                    )
+                });
+        if !show_hit_marker && !show_damage_number {
+            return;
+        }
+
+        // There is no hit box system, so hits are never headshots for now.
+        let headshot = false;
+        let scene = &engine.scenes[self.scene];
+        let sender = self.sender.as_ref().unwrap();
+        sender
+            .send(Message::DamageDealtFeedback {
+                target,
+                position: self.actors.get(target).position(&scene.graph)
+                    + Vector3::new(0.0, DAMAGE_NUMBER_HEIGHT, 0.0),
+                amount,
+                killed,
+                headshot,
+                resisted,
+                show_hit_marker,
+                show_damage_number,
+            })
+            .unwrap();
+
+        if !show_hit_marker {
+            return;
+        }
+
+        let sound = if killed {
+            Some(KILL_CONFIRM_SOUND)
+        } else if headshot {
+            Some(HEADSHOT_CONFIRM_SOUND)
+        } else {
+            None
+        };
+        if let Some(sound) = sound {
+            sender
+                .send(Message::PlaySound {
+                    path: sound.into(),
+                    position: self.actors.get(self.player).position(&scene.graph),
+                    gain: 0.5,
+                    rolloff_factor: 1.0,
+                    radius: 3.0,
+                    category: SoundCategory::Ui,
+                })
+                .unwrap();
+        }
+    }
+
     async fn spawn_item(
         &mut self,
         engine: &mut Engine,
@@ -942,29 +2536,87 @@ impl Level {
         position: Vector3<f32>,
         adjust_height: bool,
         lifetime: Option<f32>,
-    ) {
+    ) -> Option<Handle<Item>> {
         let position = if adjust_height {
             self.pick(engine, position, position - Vector3::new(0.0, 1000.0, 0.0))
         } else {
             position
         };
         let scene = &mut engine.scenes[self.scene];
-        let mut item = Item::new(
+        let mut item = match Item::new(
             kind,
             position,
             scene,
             engine.resource_manager.clone(),
             self.sender.as_ref().unwrap().clone(),
         )
-        .await;
+        .await
+        {
+            Ok(item) => item,
+            Err(e) => {
+                Log::writeln(MessageKind::Error, format!("{}. Item is skipped.", e));
+                return None;
+            }
+        };
         item.set_lifetime(lifetime);
-        self.items.add(item);
+        Some(self.items.add(item))
+    }
+
+    pub fn fill_debug_stats(&self, engine: &Engine, stats: &mut DebugStats) {
+        let scene = &engine.scenes[self.scene];
+        for actor in self.actors.iter() {
+            if let Actor::Bot(bot) = actor {
+                if !bot.is_dead() {
+                    stats.bots += 1;
+                    match bot.lod() {
+                        AiLod::Full => stats.full_rate_bots += 1,
+                        AiLod::Reduced => stats.reduced_rate_bots += 1,
+                    }
+                }
+            }
+        }
+        stats.projectiles = self.projectiles.count() as usize;
+        stats.items = self
+            .items
+            .iter()
+            .filter(|item| !item.is_picked_up())
+            .count();
+        stats.animations = scene
+            .animations
+            .iter()
+            .filter(|animation| animation.is_enabled())
+            .count();
+        stats.effects = self.effect_pool.active_count();
+        stats.pooled_effects = self.effect_pool.pooled_count();
+        stats.effect_particles = self.effect_pool.particle_count();
+        stats.skipped_effects = self.effect_pool.skipped_count();
+        stats.evicted_effects = self.effect_pool.evicted_count();
+        if self.player.is_some() {
+            stats.player_position = Some(self.actors.get(self.player).position(&scene.graph));
+        }
     }
 
     pub fn time(&self) -> f32 {
         self.time
     }
 
+    /// Returns information for the death screen, `None` while the player is alive or the
+    /// death camera is still dropping.
+    pub fn death_summary(&self) -> Option<DeathSummary> {
+        let death = self.player_death.as_ref()?;
+        if self.player.is_some() || self.time - death.time < DEATH_SCREEN_DELAY {
+            return None;
+        }
+        Some(DeathSummary {
+            killer: death.killer.clone(),
+            weapon: death.weapon.clone(),
+            kills: self.player_life.kills,
+            damage_dealt: self.player_life.damage_dealt,
+            time_survived: death.time - self.player_life.spawn_time,
+            can_respawn: !self.leader_board.is_match_over(&self.options),
+        })
+    }
+
     fn update_respawn(&mut self, time: GameTime) {
         // Respawn is done in deferred manner: we just gather all info needed
         // for respawn, wait some time and then re-create actor. Actor is spawned
@@ -981,26 +2633,16 @@ impl Level {
                             .send(Message::SpawnBot {
                                 kind: v.kind,
                                 name: v.name.clone(),
+                                team: Some(v.team),
                             })
                             .unwrap();
                     }
                 }
-                RespawnEntry::Player(v) => {
-                    v.time_left -= time.delta;
-                    if v.time_left <= 0.0 {
-                        self.sender
-                            .as_mut()
-                            .unwrap()
-                            .send(Message::SpawnPlayer)
-                            .unwrap();
-                    }
-                }
             }
         }
 
         self.respawn_list.retain(|entry| match entry {
             RespawnEntry::Bot(v) => v.time_left >= 0.0,
-            RespawnEntry::Player(v) => v.time_left >= 0.0,
         });
     }
 
@@ -1021,6 +2663,11 @@ impl Level {
                     .bounds
                     .is_contains_point(actor.position(&scene.graph))
                 {
+                    self.event_log.record(
+                        self.time,
+                        EventCategory::Trigger,
+                        format!("{} entered death zone", actor.name),
+                    );
                     self.sender
                         .as_ref()
                         .unwrap()
@@ -1041,28 +2688,179 @@ impl Level {
         }
     }
 
+    /// Returns speed of time on the level. There is no multiplayer, so the whole level can be
+    /// slowed down for the player.
+    pub fn time_scale(&self) -> f32 {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get(self.player) {
+                if player.is_weapon_wheel_open() {
+                    return WEAPON_WHEEL_TIME_SCALE;
+                }
+            }
+        }
+        1.0
+    }
+
     pub fn update(&mut self, engine: &mut Engine, time: GameTime) {
+        let time = GameTime {
+            delta: time.delta * self.time_scale(),
+            ..time
+        };
         self.time += time.delta;
         self.update_respawn(time);
+        let listener_camera = self.listener_camera();
+        let player_targeted = self.is_player_targeted();
         let scene = &mut engine.scenes[self.scene];
+        // Physics steps with the same (scaled) delta as the rest of the level.
+        scene.graph.physics.integration_parameters.dt = time.delta;
+        let listener = scene
+            .graph
+            .try_get(listener_camera)
+            .map(|camera| camera.global_position());
+        let reverb = listener
+            .and_then(|listener| reverb::innermost_zone(&self.reverb_zones, listener))
+            .and_then(|zone| reverb::preset(&zone.preset))
+            .unwrap_or_else(reverb::default_preset);
+        self.sound_manager
+            .update_reverb(&mut scene.graph, reverb, time.delta);
+        if let Some(settings) = self.settings.as_ref() {
+            let settings = settings.read().unwrap();
+            self.sound_manager
+                .update(&mut scene.graph, &settings, listener, time.delta);
+            self.music.update(
+                &mut scene.graph,
+                player_targeted,
+                settings.volume(SoundCategory::Music),
+                time.delta,
+            );
+            self.effect_pool
+                .set_budget(EffectBudget::new(settings.effect_quality));
+        }
+        self.effect_pool.update(&mut scene.graph, time.delta);
+        self.tracers.update(&mut scene.graph, time.delta);
         self.update_spectator_camera(scene);
         self.update_death_zones(scene);
-        self.weapons.update(scene, &self.actors);
+        self.weapons
+            .update(scene, &mut self.actors, self.player, time.delta);
         self.projectiles
             .update(scene, &self.actors, &self.weapons, time);
         self.items.update(scene, time);
+        self.usables.update(scene, time.delta);
+        self.player_positions.clear();
+        self.player_positions.extend(
+            self.actors
+                .iter()
+                .filter(|actor| matches!(actor, Actor::Player(_)) && !actor.is_dead())
+                .map(|actor| actor.position(&scene.graph)),
+        );
         let mut ctx = UpdateContext {
             time,
             scene,
             items: &self.items,
             jump_pads: &self.jump_pads,
             weapons: &self.weapons,
+            usables: &self.usables,
+            projectiles: self.projectiles.descriptors(),
+            ai_lod: self.ai_lod,
+            player_positions: &self.player_positions,
+            query_buffer: &mut self.query_buffer,
+            actor_query: &mut self.actor_query,
+            item_query: &mut self.item_query,
         };
         self.actors.update(&mut ctx);
+        self.update_player_fov(ctx.scene);
+        self.update_boss_fight(ctx.scene, time.delta);
 
         self.update_game_ending();
     }
 
+    fn update_boss_fight(&mut self, scene: &Scene, dt: f32) {
+        let fight = match self.boss_fight.as_mut() {
+            Some(fight) => fight,
+            None => return,
+        };
+        if !self.actors.contains(fight.actor) {
+            self.boss_fight = None;
+            return;
+        }
+
+        let boss = self.actors.get(fight.actor);
+        let in_range = self.player.is_some()
+            && self
+                .actors
+                .get(self.player)
+                .position(&scene.graph)
+                .metric_distance(&boss.position(&scene.graph))
+                <= BOSS_COMBAT_RANGE;
+        // Dead boss keeps its bar, so HUD can play the shatter.
+        if in_range || boss.is_dead() {
+            fight.out_of_range_time = 0.0;
+        } else {
+            fight.out_of_range_time += dt;
+            if fight.out_of_range_time >= BOSS_DISENGAGE_TIME {
+                self.boss_fight = None;
+            }
+        }
+    }
+
+    /// Returns health bar of the boss the player fights, if any.
+    pub fn boss_bar(&self) -> Option<BossBarData> {
+        let fight = self.boss_fight.as_ref()?;
+        let actor = self.actors.get(fight.actor);
+        let (name, max_health, phases) = match actor {
+            Actor::Bot(bot) => {
+                let definition = bot.definition();
+                match definition.boss.as_ref() {
+                    Some(boss) => (boss.name.to_owned(), bot.max_health(), boss.phases),
+                    None => (actor.name.clone(), bot.max_health(), &[][..]),
+                }
+            }
+            _ => (actor.name.clone(), MAX_HEALTH, &[][..]),
+        };
+        Some(BossBarData {
+            actor: fight.actor,
+            name,
+            health: actor.get_health(),
+            max_health,
+            phases,
+        })
+    }
+
+    /// Stops physics and sounds of the level. Level must not be updated while it is paused,
+    /// otherwise actors would keep thinking.
+    pub fn set_paused(&self, engine: &mut Engine, paused: bool) {
+        let scene = &mut engine.scenes[self.scene];
+        scene.graph.physics.enabled = !paused;
+        scene.graph.sound_context.pause(paused);
+    }
+
+    fn update_player_fov(&self, scene: &mut Scene) {
+        let fov = match self.settings.as_ref() {
+            Some(settings) => settings.read().unwrap().fov.to_radians(),
+            None => return,
+        };
+        if !self.actors.contains(self.player) {
+            return;
+        }
+        if let Actor::Player(player) = self.actors.get(self.player) {
+            // Weapon that aims down sights zooms the view in.
+            let weapon = player.current_weapon();
+            let fov = if self.weapons.contains(weapon) {
+                fov / self.weapons[weapon].desired_fov_zoom()
+            } else {
+                fov
+            };
+            if let Some(camera) = scene.graph[player.camera()].cast_mut::<Camera>() {
+                if let Projection::Perspective(mut perspective) = camera.projection().clone() {
+                    if perspective.fov != fov {
+                        perspective.fov = fov;
+                        camera.set_projection(Projection::Perspective(perspective));
+                    }
+                }
+            }
+        }
+    }
+
     pub async fn respawn_actor(&mut self, engine: &mut Engine, actor: Handle<Actor>) {
         if self.actors.contains(actor) {
             let name = self.actors.get(actor).name.clone();
@@ -1070,11 +2868,12 @@ impl Level {
             self.leader_board.add_death(&name);
 
             let entry = match self.actors.get(actor) {
-                Actor::Bot(bot) => RespawnEntry::Bot(BotRespawnEntry {
+                Actor::Bot(bot) => Some(RespawnEntry::Bot(BotRespawnEntry {
                     name,
                     kind: bot.definition().kind,
+                    team: bot.team(),
                     time_left: RESPAWN_TIME,
-                }),
+                })),
                 Actor::Player(player) => {
                     // Turn on spectator camera and prepare its target position. Spectator
                     // camera will be used to render world until player is despawned.
@@ -1110,15 +2909,23 @@ impl Level {
                         self.target_spectator_position = position;
                     }
 
-                    RespawnEntry::Player(PlayerRespawnEntry {
-                        time_left: RESPAWN_TIME,
-                    })
+                    // Death zones respawn actors without killing them first.
+                    if self.player_death.is_none() {
+                        self.player_death = Some(PlayerDeath {
+                            time: self.time,
+                            ..Default::default()
+                        });
+                    }
+
+                    None
                 }
             };
 
             self.remove_actor(engine, actor).await;
 
-            self.respawn_list.push(entry);
+            if let Some(entry) = entry {
+                self.respawn_list.push(entry);
+            }
         }
     }
 
@@ -1132,8 +2939,20 @@ impl Level {
             .await;
 
         match message {
+            Message::PlaySound {
+                path,
+                position,
+                gain,
+                category,
+                ..
+            } => {
+                self.caption_sound(engine, path, *position);
+                if matches!(category, SoundCategory::Sfx | SoundCategory::Voice) {
+                    self.alert_bots(engine, *position, *gain, Handle::NONE);
+                }
+            }
             &Message::GiveNewWeapon { actor, kind } => {
-                self.give_new_weapon(engine, actor, kind).await;
+                self.give_new_weapon(engine, actor, kind, None).await;
             }
             Message::AddBot {
                 kind,
@@ -1144,19 +2963,41 @@ impl Level {
             }
             &Message::RemoveActor { actor } => self.remove_actor(engine, actor).await,
             &Message::GiveItem { actor, kind } => {
-                self.give_item(engine, actor, kind).await;
+                self.give_item(engine, actor, kind, None).await;
             }
+            &Message::DropWeapon { weapon } => self.drop_weapon(engine, weapon).await,
             &Message::PickUpItem { actor, item } => {
                 self.pickup_item(engine, actor, item).await;
             }
-            &Message::ShootWeapon {
-                weapon,
-                initial_velocity,
-                direction,
-            } => {
-                self.shoot_weapon(engine, weapon, initial_velocity, time, direction)
+            &Message::SetAiming { weapon, aiming } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].set_aiming(aiming);
+                }
+            }
+            &Message::SetLaserEnabled { weapon, enabled } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon]
+                        .set_laser_enabled(enabled, &mut engine.scenes[self.scene].graph);
+                }
+            }
+            &Message::ReleaseTrigger { weapon } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].release_trigger();
+                }
+            }
+            &Message::ShootWeapon { weapon, direction } => {
+                self.shoot_weapon(engine, weapon, time, direction, false)
                     .await
             }
+            &Message::ShootBurst { weapon } => {
+                self.shoot_weapon(engine, weapon, time, None, true).await
+            }
+            &Message::MeleeAttack {
+                actor,
+                origin,
+                direction,
+                sweep,
+            } => self.melee_attack(engine, actor, origin, direction, sweep, time),
             &Message::CreateProjectile {
                 kind,
                 position,
@@ -1177,22 +3018,43 @@ impl Level {
                 .await
             }
             &Message::ShowWeapon { weapon, state } => self.show_weapon(engine, weapon, state),
-            Message::SpawnBot { kind, name } => {
-                self.spawn_bot(engine, *kind, Some(name.clone())).await;
+            &Message::SpawnBot {
+                kind,
+                ref name,
+                team,
+            } => {
+                let team = team.unwrap_or_else(|| new_bot_team(&self.options, &self.actors));
+                self.spawn_bot(engine, kind, Some(name.clone()), team).await;
             }
-            &Message::DamageActor { actor, who, amount } => {
-                self.damage_actor(engine, actor, who, amount, time);
+            &Message::DamageActor {
+                actor,
+                who,
+                amount,
+                hit,
+            } => {
+                self.damage_actor(engine, actor, who, amount, hit, time);
             }
-            &Message::CreateEffect { kind, position } => {
-                effects::create(
-                    kind,
+            &Message::CreateEffect {
+                kind,
+                position,
+                direction,
+            } => {
+                self.effect_pool.spawn(
                     &mut engine.scenes[self.scene].graph,
                     engine.resource_manager.clone(),
+                    kind,
                     position,
+                    direction,
                 );
+                if let EffectKind::Explosion(surface) = kind {
+                    self.add_scorch(engine, position, surface);
+                }
             }
             Message::SpawnPlayer => {
-                self.player = self.spawn_player(engine).await;
+                // Respawn could be requested a few times before it was handled.
+                if self.player.is_none() {
+                    self.player = self.spawn_player(engine).await;
+                }
             }
             &Message::SpawnItem {
                 kind,
@@ -1201,13 +3063,101 @@ impl Level {
                 lifetime,
             } => {
                 self.spawn_item(engine, kind, position, adjust_height, lifetime)
-                    .await
+                    .await;
             }
             &Message::RespawnActor { actor } => self.respawn_actor(engine, actor).await,
+            &Message::DefinitionsReloaded {
+                kind: DefinitionKind::Bots,
+            } => {
+                for actor in self.actors.iter_mut() {
+                    if let Actor::Bot(bot) = actor {
+                        bot.on_definitions_reloaded();
+                    }
+                }
+            }
+            &Message::TargetAcquired { bot, target } => {
+                if self.actors.contains(bot) && self.actors.contains(target) {
+                    if target == self.player {
+                        self.engage_boss(bot);
+                    }
+                    self.event_log.record(
+                        self.time,
+                        EventCategory::BotTarget,
+                        format!(
+                            "{} acquired target {}",
+                            self.actors.get(bot).name,
+                            self.actors.get(target).name
+                        ),
+                    );
+                }
+            }
+            &Message::UseObject { actor, usable } => {
+                let door = self.usables.activate(usable);
+                if door.is_some() && self.actors.contains(actor) {
+                    self.event_log.record(
+                        self.time,
+                        EventCategory::Trigger,
+                        format!(
+                            "{} used {:?}",
+                            self.actors.get(actor).name,
+                            self.usables.get(usable).kind()
+                        ),
+                    );
+                }
+            }
+            &Message::SetBossBar { actor, visible } => {
+                if visible {
+                    if self.actors.contains(actor) {
+                        self.boss_fight = Some(BossFight {
+                            actor,
+                            out_of_range_time: 0.0,
+                        });
+                    }
+                } else if matches!(self.boss_fight.as_ref(), Some(fight) if fight.actor == actor) {
+                    self.boss_fight = None;
+                }
+            }
+            &Message::TargetLost { bot } => {
+                if self.actors.contains(bot) {
+                    self.event_log.record(
+                        self.time,
+                        EventCategory::BotTarget,
+                        format!("{} lost its target", self.actors.get(bot).name),
+                    );
+                }
+            }
             _ => (),
         }
     }
 
+    // Boss that has noticed the player shows its health bar, unless the fight is going on.
+    fn engage_boss(&self, bot: Handle<Actor>) {
+        let is_boss =
+            matches!(self.actors.get(bot), Actor::Bot(bot) if bot.definition().boss.is_some());
+        let is_engaged = matches!(self.boss_fight.as_ref(), Some(fight) if fight.actor == bot);
+        if is_boss && !is_engaged {
+            if let Some(sender) = self.sender.as_ref() {
+                sender
+                    .send(Message::SetBossBar {
+                        actor: bot,
+                        visible: true,
+                    })
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Restores state of bots that isn't saved and moves reserve ammo of saves made before
+    /// ammo pools to the owners, it's harmless for levels that were never saved.
+    pub fn on_loaded(&mut self, graph: &Graph) {
+        for actor in self.actors.iter_mut() {
+            if let Actor::Bot(bot) = actor {
+                bot.on_level_loaded(graph);
+            }
+        }
+        self.weapons.move_legacy_reserve_ammo(&mut self.actors);
+    }
+
     pub fn set_message_sender(&mut self, sender: Sender<Message>) {
         self.sender = Some(sender.clone());
 
@@ -1226,7 +3176,8 @@ impl Level {
         }
     }
 
-    pub fn debug_draw(&self, engine: &mut Engine) {
+    /// Draws debug geometry of the level, text labels of bots are pushed to `labels`.
+    pub fn debug_draw(&self, engine: &mut Engine, labels: &mut Vec<(Vector3<f32>, String)>) {
         let scene = &mut engine.scenes[self.scene];
 
         let drawing_context = &mut scene.drawing_context;
@@ -1248,7 +3199,7 @@ impl Level {
 
             for actor in self.actors.iter() {
                 if let Actor::Bot(bot) = actor {
-                    bot.debug_draw(drawing_context);
+                    bot.debug_draw(drawing_context, self.bot_debug_flags, labels);
                 }
             }
         }
@@ -1256,6 +3207,10 @@ impl Level {
         for death_zone in self.death_zones.iter() {
             drawing_context.draw_aabb(&death_zone.bounds, Color::opaque(0, 0, 200));
         }
+
+        for reverb_zone in self.reverb_zones.iter() {
+            drawing_context.draw_aabb(&reverb_zone.bounds, Color::opaque(0, 200, 200));
+        }
     }
 }
 
@@ -0,0 +1,131 @@
+use crate::{
+    campaign::{LevelList, Progress, PROGRESS_PATH},
+    localization::{tr, tr_format},
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::sync::Arc;
+
+struct LevelEntry {
+    button: Handle<UiNode>,
+    text: Handle<UiNode>,
+}
+
+pub struct LevelSelect {
+    pub window: Handle<UiNode>,
+    level_list: Arc<LevelList>,
+    entries: Vec<LevelEntry>,
+}
+
+impl LevelSelect {
+    pub fn new(ui: &mut UserInterface, level_list: Arc<LevelList>) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let entries = level_list
+            .levels
+            .iter()
+            .map(|_| {
+                let text = TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_vertical_alignment(VerticalAlignment::Center),
+                )
+                .build(ctx);
+                let button = ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .with_height(40.0)
+                        .with_margin(Thickness::uniform(2.0)),
+                )
+                .with_content(text)
+                .build(ctx);
+                LevelEntry { button, text }
+            })
+            .collect::<Vec<_>>();
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0))
+            .with_title(WindowTitle::text(tr("level_select.title")))
+            .open(false)
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(10.0))
+                        .with_children(entries.iter().map(|entry| entry.button)),
+                )
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            level_list,
+            entries,
+        }
+    }
+
+    /// Opens the window, locks are taken from the progress file every time, so levels finished
+    /// since the last time are shown unlocked.
+    pub fn open(&self, ui: &UserInterface) {
+        let progress = Progress::load(PROGRESS_PATH);
+        for (index, (level, entry)) in self
+            .level_list
+            .levels
+            .iter()
+            .zip(self.entries.iter())
+            .enumerate()
+        {
+            let is_unlocked = progress.is_unlocked(&self.level_list, index);
+            ui.send_message(WidgetMessage::enabled(
+                entry.button,
+                MessageDirection::ToWidget,
+                is_unlocked,
+            ));
+            ui.send_message(TextMessage::text(
+                entry.text,
+                MessageDirection::ToWidget,
+                match (is_unlocked, progress.is_completed(&level.id)) {
+                    (false, _) => tr_format("level_select.locked", &[&level.name]),
+                    (true, true) => tr_format("level_select.completed", &[&level.name]),
+                    (true, false) => level.name.clone(),
+                },
+            ));
+        }
+
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn is_open(&self, ui: &UserInterface) -> bool {
+        ui.node(self.window).visibility()
+    }
+
+    /// Returns index of the level that was picked.
+    pub fn handle_ui_event(&mut self, message: &UiMessage) -> Option<usize> {
+        if let Some(ButtonMessage::Click) = message.data() {
+            return self
+                .entries
+                .iter()
+                .position(|entry| entry.button == message.destination());
+        }
+        None
+    }
+}
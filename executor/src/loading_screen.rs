@@ -0,0 +1,409 @@
+//! Loading screen, it is shown while a level is loaded on a separate thread, so the event
+//! loop keeps running and the screen is redrawn all the time. It shows the name of the level,
+//! loading progress and gameplay tips. When the load fails the progress bar is replaced by
+//! the error and a way back to the main menu.
+
+use crate::{
+    data_file,
+    gui::create_background_image,
+    localization::{tr, tr_format},
+    message::Message,
+};
+use fyrox::{
+    core::{color::Color, parking_lot::Mutex, pool::Handle, rand::Rng},
+    engine::Engine,
+    event::{Event, WindowEvent},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface,
+    },
+    rand,
+};
+use std::{
+    path::Path,
+    sync::{mpsc::Sender, Arc},
+};
+
+/// List of gameplay tips.
+pub const TIPS_PATH: &str = "data/ui/tips.ron";
+/// Background is optional, a plain color is used when there is no image.
+const BACKGROUND_PATH: &str = "data/ui/loading_background.png";
+const TIP_INTERVAL: f32 = 6.0;
+const FADE_DURATION: f32 = 0.4;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum State {
+    Hidden,
+    Loading,
+    Failed,
+    /// Level is running under a fading black screen, the value is time left.
+    FadingOut(f32),
+}
+
+pub struct LoadingScreen {
+    sender: Sender<Message>,
+    root: Handle<UiNode>,
+    fade: Handle<UiNode>,
+    title: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    // What is being loaded right now, under the progress bar.
+    status: Handle<UiNode>,
+    tip: Handle<UiNode>,
+    error: Handle<UiNode>,
+    btn_back_to_menu: Handle<UiNode>,
+    tips: Vec<String>,
+    tip_index: usize,
+    tip_timer: f32,
+    state: State,
+    // Frames drawn since the screen was shown, a level that loads faster than a frame waits
+    // for the first one so the screen never flashes.
+    frames_rendered: usize,
+}
+
+impl LoadingScreen {
+    pub fn new(engine: &mut Engine, sender: Sender<Message>) -> Self {
+        let frame_size = engine.renderer.get_frame_size();
+
+        let font: Font = fyrox::core::futures::executor::block_on(Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            40.0,
+            Font::default_char_set(),
+        ))
+        .unwrap();
+        let font = SharedFont(Arc::new(Mutex::new(font)));
+
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let background = create_background_image(ctx, &engine.resource_manager, BACKGROUND_PATH);
+
+        let title = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_font(font.clone())
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+        let progress_bar = ProgressBarBuilder::new(
+            WidgetBuilder::new()
+                .with_height(32.0)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .build(ctx);
+        let status = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+        let error = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_margin(Thickness::uniform(4.0))
+                .with_foreground(Brush::Solid(Color::opaque(220, 60, 60))),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .build(ctx);
+        let btn_back_to_menu = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_height(50.0)
+                .with_margin(Thickness::uniform(4.0)),
+        )
+        .with_text(&tr("loading.back_to_menu"))
+        .build(ctx);
+        let tip = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(4.0)))
+            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+            .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::opaque(10, 10, 14)))
+                .with_children(background)
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new().with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .on_column(1)
+                                    .with_child(title)
+                                    .with_child(progress_bar)
+                                    .with_child(status)
+                                    .with_child(error)
+                                    .with_child(btn_back_to_menu)
+                                    .with_child(tip),
+                            )
+                            .build(ctx),
+                        ),
+                    )
+                    .add_row(Row::stretch())
+                    .add_row(Row::strict(200.0))
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(600.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        let fade = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_background(Brush::Solid(Color::BLACK)),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            sender,
+            root,
+            fade,
+            title,
+            progress_bar,
+            status,
+            tip,
+            error,
+            btn_back_to_menu,
+            tips: data_file::load(TIPS_PATH, "tips"),
+            tip_index: 0,
+            tip_timer: 0.0,
+            state: State::Hidden,
+            frames_rendered: 0,
+        }
+    }
+
+    pub fn show(&mut self, ui: &UserInterface, title: &str) {
+        self.state = State::Loading;
+        self.frames_rendered = 0;
+        self.tip_timer = TIP_INTERVAL;
+        if !self.tips.is_empty() {
+            self.tip_index = rand::thread_rng().gen_range(0..self.tips.len());
+        }
+        self.sync_tip(ui);
+
+        ui.send_message(TextMessage::text(
+            self.title,
+            MessageDirection::ToWidget,
+            title.to_owned(),
+        ));
+        ui.send_message(ProgressBarMessage::progress(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            0.0,
+        ));
+        self.set_status(ui, "");
+        self.set_failed_widgets_visible(ui, false);
+        ui.send_message(WidgetMessage::visibility(
+            self.fade,
+            MessageDirection::ToWidget,
+            false,
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        ui.send_message(WidgetMessage::topmost(
+            self.root,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn hide(&mut self, ui: &UserInterface) {
+        self.state = State::Hidden;
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            false,
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.fade,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    /// Loading is in progress or has failed, the level is not ready to be played.
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, State::Loading | State::Failed)
+    }
+
+    /// Loaded level can be shown, the screen was on screen at least for one frame.
+    pub fn can_finish(&self) -> bool {
+        self.state == State::Loading && self.frames_rendered > 0
+    }
+
+    pub fn on_frame_rendered(&mut self) {
+        if self.state == State::Loading {
+            self.frames_rendered += 1;
+        }
+    }
+
+    pub fn set_progress(&self, ui: &UserInterface, progress: f32) {
+        ui.send_message(ProgressBarMessage::progress(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            progress,
+        ));
+    }
+
+    pub fn set_status(&self, ui: &UserInterface, status: &str) {
+        ui.send_message(TextMessage::text(
+            self.status,
+            MessageDirection::ToWidget,
+            status.to_owned(),
+        ));
+    }
+
+    /// Swaps the progress bar with the error and the "Back to menu" button.
+    pub fn fail(&mut self, ui: &UserInterface, reason: &str) {
+        self.state = State::Failed;
+        ui.send_message(TextMessage::text(
+            self.error,
+            MessageDirection::ToWidget,
+            tr_format("loading.failed", &[&reason]),
+        ));
+        self.set_failed_widgets_visible(ui, true);
+    }
+
+    /// Starts the fade into gameplay.
+    pub fn finish(&mut self, ui: &UserInterface) {
+        self.state = State::FadingOut(FADE_DURATION);
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            false,
+        ));
+        ui.send_message(WidgetMessage::background(
+            self.fade,
+            MessageDirection::ToWidget,
+            Brush::Solid(Color::BLACK),
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.fade,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        // HUD is shown together with the level, it must fade in with it.
+        ui.send_message(WidgetMessage::topmost(
+            self.fade,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn set_failed_widgets_visible(&self, ui: &UserInterface, failed: bool) {
+        for widget in [self.progress_bar, self.status] {
+            ui.send_message(WidgetMessage::visibility(
+                widget,
+                MessageDirection::ToWidget,
+                !failed,
+            ));
+        }
+        ui.send_message(WidgetMessage::visibility(
+            self.error,
+            MessageDirection::ToWidget,
+            failed,
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.btn_back_to_menu,
+            MessageDirection::ToWidget,
+            failed,
+        ));
+    }
+
+    fn sync_tip(&self, ui: &UserInterface) {
+        ui.send_message(TextMessage::text(
+            self.tip,
+            MessageDirection::ToWidget,
+            self.tips
+                .get(self.tip_index)
+                .map(|tip| tr_format("loading.tip", &[tip]))
+                .unwrap_or_default(),
+        ));
+    }
+
+    pub fn update(&mut self, ui: &UserInterface, dt: f32) {
+        match self.state {
+            State::Hidden => (),
+            State::Loading | State::Failed => {
+                self.tip_timer -= dt;
+                if self.tip_timer <= 0.0 && !self.tips.is_empty() {
+                    self.tip_timer = TIP_INTERVAL;
+                    self.tip_index = (self.tip_index + 1) % self.tips.len();
+                    self.sync_tip(ui);
+                }
+            }
+            State::FadingOut(time_left) => {
+                let time_left = time_left - dt;
+                if time_left <= 0.0 {
+                    self.hide(ui);
+                } else {
+                    self.state = State::FadingOut(time_left);
+                    ui.send_message(WidgetMessage::background(
+                        self.fade,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(Color::from_rgba(
+                            0,
+                            0,
+                            0,
+                            (255.0 * time_left / FADE_DURATION) as u8,
+                        )),
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) {
+        if let Event::WindowEvent {
+            event: WindowEvent::Resized(new_size),
+            ..
+        } = event
+        {
+            let ui = &mut engine.user_interface;
+            for widget in [self.root, self.fade] {
+                ui.send_message(WidgetMessage::width(
+                    widget,
+                    MessageDirection::ToWidget,
+                    new_size.width as f32,
+                ));
+                ui.send_message(WidgetMessage::height(
+                    widget,
+                    MessageDirection::ToWidget,
+                    new_size.height as f32,
+                ));
+            }
+        }
+    }
+
+    pub fn handle_ui_event(&mut self, message: &UiMessage) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_back_to_menu {
+                self.sender.send(Message::QuitToMenu).unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data_file;
+
+    #[test]
+    fn shipped_tips_parse() {
+        let tips: Vec<String> = data_file::parse(include_str!("../../data/ui/tips.ron")).unwrap();
+        assert!(!tips.is_empty());
+        assert!(tips.iter().all(|tip| !tip.trim().is_empty()));
+    }
+}
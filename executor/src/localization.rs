@@ -0,0 +1,214 @@
+//! Localization of UI text. Every string that is shown to the player is looked up by its key
+//! with [`tr`] in the string table of the current language. Tables are loaded from
+//! `data/lang/<language>.ron`, a map of keys to strings. Keys missing in the table are shown as is
+//! and logged once, so untranslated entries are easy to find.
+//!
+//! Widgets take their text when they're built, so menus are rebuilt when the language is
+//! changed, see `Message::LanguageChanged`.
+
+use fyrox::utils::log::{Log, MessageKind};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+pub const LANGUAGES_DIR: &str = "data/lang";
+pub const DEFAULT_LANGUAGE: &str = "en";
+/// Every table has its own name under this key, it is shown in the language list.
+const LANGUAGE_NAME_KEY: &str = "language.name";
+
+struct StringTable {
+    language: String,
+    strings: BTreeMap<String, String>,
+    // Missing keys that were already reported.
+    missing: HashSet<String>,
+}
+
+static TABLE: Mutex<Option<StringTable>> = Mutex::new(None);
+
+fn language_path(language: &str) -> PathBuf {
+    Path::new(LANGUAGES_DIR).join(format!("{}.ron", language))
+}
+
+fn load_table(language: &str) -> Result<BTreeMap<String, String>, String> {
+    let path = language_path(language);
+    std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| {
+            ron::from_str::<BTreeMap<String, String>>(&text).map_err(|e| e.to_string())
+        })
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Switches the string table, default language is used if the table of given language can't
+/// be loaded.
+pub fn set_language(language: &str) {
+    let (language, strings) = match load_table(language) {
+        Ok(strings) => (language.to_owned(), strings),
+        Err(e) => {
+            Log::writeln(
+                MessageKind::Error,
+                format!(
+                    "Unable to load language {}, {} will be used. Reason: {}",
+                    language, DEFAULT_LANGUAGE, e
+                ),
+            );
+            let strings = load_table(DEFAULT_LANGUAGE).unwrap_or_else(|e| {
+                Log::writeln(
+                    MessageKind::Error,
+                    format!("Unable to load default language. Reason: {}", e),
+                );
+                Default::default()
+            });
+            (DEFAULT_LANGUAGE.to_owned(), strings)
+        }
+    };
+
+    *TABLE.lock().unwrap() = Some(StringTable {
+        language,
+        strings,
+        missing: Default::default(),
+    });
+}
+
+/// Returns code of the language in use, it may differ from requested one if its table is
+/// missing.
+pub fn current_language() -> String {
+    TABLE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|table| table.language.clone())
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_owned())
+}
+
+/// Returns text of given key in the current language.
+pub fn tr(key: &str) -> String {
+    let mut table = TABLE.lock().unwrap();
+    let table = table.get_or_insert_with(|| StringTable {
+        language: DEFAULT_LANGUAGE.to_owned(),
+        strings: Default::default(),
+        missing: Default::default(),
+    });
+    match table.strings.get(key) {
+        Some(text) => text.clone(),
+        None => {
+            if table.missing.insert(key.to_owned()) {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Missing localization of {:?}.", key),
+                );
+            }
+            key.to_owned()
+        }
+    }
+}
+
+/// Same as [`tr`], but every `{}` in the text is replaced with next argument.
+pub fn tr_format(key: &str, args: &[&dyn Display]) -> String {
+    let text = tr(key);
+    let mut result = String::with_capacity(text.len());
+    let mut args = args.iter();
+    let mut parts = text.split("{}");
+    if let Some(first) = parts.next() {
+        result.push_str(first);
+    }
+    for part in parts {
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+/// Returns codes and names of every language that has a table, in alphabetical order of codes.
+pub fn available_languages() -> Vec<(String, String)> {
+    let mut languages = std::fs::read_dir(LANGUAGES_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    if path.extension()? != "ron" {
+                        return None;
+                    }
+                    let code = path.file_stem()?.to_str()?.to_owned();
+                    let name = load_table(&code)
+                        .ok()
+                        .and_then(|strings| strings.get(LANGUAGE_NAME_KEY).cloned())
+                        .unwrap_or_else(|| code.clone());
+                    Some((code, name))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    languages.sort();
+    if languages.is_empty() {
+        languages.push((DEFAULT_LANGUAGE.to_owned(), DEFAULT_LANGUAGE.to_owned()));
+    }
+    languages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Extensions of data files, their names look like keys too.
+    const FILE_EXTENSIONS: [&str; 5] = ["json", "log", "ogg", "ron", "txt"];
+
+    fn is_key(literal: &str) -> bool {
+        literal.contains('.')
+            && literal.starts_with(|c: char| c.is_ascii_lowercase())
+            && literal.split('.').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_'))
+            })
+            && !FILE_EXTENSIONS
+                .iter()
+                .any(|ext| literal.ends_with(&format!(".{}", ext)))
+    }
+
+    // Keys are string literals passed to `tr` and `tr_format`, often through a `match`, so
+    // every literal of the sources that looks like a key is collected.
+    fn keys_referenced_in_code() -> BTreeMap<String, PathBuf> {
+        let mut keys = BTreeMap::new();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(true, |ext| ext != "rs") || path.ends_with("localization.rs")
+            {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            for literal in source.split('"').filter(|literal| is_key(literal)) {
+                keys.insert(literal.to_owned(), path.clone());
+            }
+        }
+        keys
+    }
+
+    #[test]
+    fn english_table_covers_every_key() {
+        let strings: BTreeMap<String, String> =
+            ron::from_str(include_str!("../../data/lang/en.ron")).unwrap();
+        let keys = keys_referenced_in_code();
+        assert!(keys.contains_key("options.captions"));
+        let missing = keys
+            .iter()
+            .filter(|(key, _)| !strings.contains_key(*key))
+            .map(|(key, path)| format!("{} ({})", key, path.display()))
+            .collect::<Vec<_>>();
+        assert!(missing.is_empty(), "Missing in en.ron: {:?}", missing);
+    }
+
+    #[test]
+    fn format_fills_placeholders_in_order() {
+        assert_eq!(tr_format("{} of {}", &[&1, &"two"]), "1 of two");
+        assert_eq!(tr_format("{} and {}", &[&1]), "1 and {}");
+    }
+}
@@ -5,46 +5,93 @@ extern crate crossbeam;
 extern crate fyrox;
 
 mod actor;
+mod ammo_counter;
+mod benchmark;
+mod boss_bar;
 mod bot;
+mod campaign;
+mod captions;
 mod character;
 mod control_scheme;
+mod crosshair;
+mod damage_indicator;
+mod damage_numbers;
+mod data_file;
+mod death_screen;
+mod debug_overlay;
 mod effects;
+mod error;
+mod event_log;
+mod gamepad;
 mod gui;
 mod hud;
 mod item;
 mod jump_pad;
+mod kill_feed;
 mod leader_board;
 mod level;
+mod level_select;
+mod loading_screen;
+mod localization;
 mod match_menu;
 mod menu;
 mod message;
+mod music;
 mod options_menu;
+mod patrol;
+mod pause_menu;
 mod player;
+mod preload;
 mod projectile;
+mod radar;
+mod reverb;
+mod save_slot;
+mod settings;
+mod spatial_grid;
+mod status_panel;
+mod surface;
+mod toast;
+mod tracer;
+mod tunables;
+mod usable;
 mod weapon;
+mod weapon_wheel;
 
 use crate::{
-    actor::Actor, control_scheme::ControlScheme, hud::Hud, level::Level, menu::Menu,
+    actor::Actor,
+    benchmark::{Benchmark, BenchmarkOptions},
+    bot::BotDebugFlags,
+    campaign::{LevelList, Progress, LEVEL_LIST_PATH, PROGRESS_PATH},
+    captions::{CaptionList, CAPTIONS_PATH},
+    control_scheme::{ControlScheme, CONTROL_SCHEME_PATH},
+    death_screen::DeathScreen,
+    debug_overlay::{DebugOverlay, DebugStats},
+    event_log::EventLogPanel,
+    gamepad::GamepadInput,
+    hud::Hud,
+    level::Level,
+    loading_screen::LoadingScreen,
+    localization::{self, tr, tr_format},
+    menu::Menu,
     message::Message,
+    pause_menu::PauseMenu,
+    preload::{PreloadCache, PreloadList},
+    save_slot::SaveSlot,
+    settings::{Settings, SoundCategory, SETTINGS_PATH},
+    toast::NotificationPriority,
+    tunables::{Tunables, DEFINITIONS_DIR},
 };
 use fyrox::window::CursorGrabMode;
 use fyrox::{
     core::{
+        algebra::Vector3,
         futures::executor::block_on,
         pool::Handle,
-        visitor::{Visit, VisitResult, Visitor},
+        visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::{resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext},
     event::{ElementState, Event, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    gui::{
-        grid::{Column, GridBuilder, Row},
-        message::MessageDirection,
-        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
-        text::{TextBuilder, TextMessage},
-        widget::{WidgetBuilder, WidgetMessage},
-        BuildContext, HorizontalAlignment, UiNode, VerticalAlignment,
-    },
     scene::{
         base::BaseBuilder,
         node::Node,
@@ -71,13 +118,17 @@ const FIXED_FPS: f32 = 60.0;
 
 pub struct Game {
     menu: Menu,
+    death_screen: DeathScreen,
+    pause_menu: PauseMenu,
     hud: Hud,
     engine: Engine,
     level: Option<Level>,
-    debug_text: Handle<UiNode>,
-    debug_string: String,
+    debug_overlay: DebugOverlay,
     running: bool,
     control_scheme: Arc<RwLock<ControlScheme>>,
+    settings: Arc<RwLock<Settings>>,
+    level_list: Arc<LevelList>,
+    captions: Arc<CaptionList>,
     time: GameTime,
     events_receiver: Receiver<Message>,
     events_sender: Sender<Message>,
@@ -85,55 +136,15 @@ pub struct Game {
     loading_screen: LoadingScreen,
     menu_scene: Handle<Scene>,
     music: Handle<Node>,
-}
-
-struct LoadingScreen {
-    root: Handle<UiNode>,
-    progress_bar: Handle<UiNode>,
-}
-
-impl LoadingScreen {
-    fn new(ctx: &mut BuildContext, width: f32, height: f32) -> Self {
-        let progress_bar;
-        let root = GridBuilder::new(
-            WidgetBuilder::new()
-                .with_width(width)
-                .with_height(height)
-                .with_visibility(false)
-                .with_child(
-                    GridBuilder::new(
-                        WidgetBuilder::new()
-                            .on_row(1)
-                            .on_column(1)
-                            .with_child({
-                                progress_bar =
-                                    ProgressBarBuilder::new(WidgetBuilder::new().on_row(1))
-                                        .build(ctx);
-                                progress_bar
-                            })
-                            .with_child(
-                                TextBuilder::new(WidgetBuilder::new().on_row(0))
-                                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
-                                    .with_vertical_text_alignment(VerticalAlignment::Center)
-                                    .with_text("Loading... Please wait.")
-                                    .build(ctx),
-                            ),
-                    )
-                    .add_row(Row::stretch())
-                    .add_row(Row::strict(32.0))
-                    .add_column(Column::stretch())
-                    .build(ctx),
-                ),
-        )
-        .add_column(Column::stretch())
-        .add_column(Column::strict(400.0))
-        .add_column(Column::stretch())
-        .add_row(Row::stretch())
-        .add_row(Row::strict(100.0))
-        .add_row(Row::stretch())
-        .build(ctx);
-        Self { root, progress_bar }
-    }
+    event_log_panel: EventLogPanel,
+    benchmark: Option<Benchmark>,
+    gamepad: GamepadInput,
+    // Debug geometry of the level is drawn while this is on, F4 toggles it.
+    debug_geometry: bool,
+    // Text labels of bots from the last debug drawing, they are shown by the debug overlay.
+    bot_debug_labels: Vec<(Vector3<f32>, String)>,
+    #[cfg(feature = "hot-reload")]
+    definition_watcher: tunables::DefinitionWatcher,
 }
 
 #[derive(Copy, Clone)]
@@ -184,7 +195,7 @@ impl Default for MatchOptions {
 }
 
 pub struct LoadContext {
-    level: Option<(Level, Scene)>,
+    level: Option<Result<(Level, Scene), String>>,
 }
 
 impl Game {
@@ -212,7 +223,13 @@ impl Game {
         })
         .unwrap();
 
-        let control_scheme = Arc::new(RwLock::new(ControlScheme::default()));
+        let control_scheme = Arc::new(RwLock::new(ControlScheme::load(CONTROL_SCHEME_PATH)));
+        let settings = Arc::new(RwLock::new(Settings::load(SETTINGS_PATH)));
+        // Must be set before any widget is built.
+        localization::set_language(&settings.read().unwrap().language);
+        let level_list = Arc::new(LevelList::load(LEVEL_LIST_PATH));
+        let captions = Arc::new(CaptionList::load(CAPTIONS_PATH));
+        tunables::set(Tunables::load(DEFINITIONS_DIR));
 
         let fixed_timestep = 1.0 / FIXED_FPS;
 
@@ -235,32 +252,49 @@ impl Game {
             .with_buffer(Some(buffer))
             .with_looping(true)
             .with_status(Status::Playing)
-            .with_gain(0.25)
+            .with_gain(settings.read().unwrap().volume(SoundCategory::Music))
             .build(&mut menu_scene.graph);
 
         let mut game = Game {
-            loading_screen: LoadingScreen::new(
-                &mut engine.user_interface.build_ctx(),
-                inner_size.width,
-                inner_size.height,
-            ),
+            loading_screen: LoadingScreen::new(&mut engine, tx.clone()),
             menu_scene: engine.scenes.add(menu_scene),
             music,
-            hud: Hud::new(&mut engine),
+            hud: Hud::new(&mut engine, settings.read().unwrap().caption_size),
+            event_log_panel: EventLogPanel::new(&mut engine),
             running: true,
-            menu: Menu::new(&mut engine, control_scheme.clone(), tx.clone()),
+            menu: Menu::new(
+                &mut engine,
+                control_scheme.clone(),
+                settings.clone(),
+                level_list.clone(),
+                tx.clone(),
+            ),
+            death_screen: DeathScreen::new(&mut engine, tx.clone()),
+            pause_menu: PauseMenu::new(&mut engine, tx.clone()),
             control_scheme,
-            debug_text: Handle::NONE,
+            settings,
+            level_list,
+            captions,
+            // Built last, so it is drawn over every other widget.
+            debug_overlay: DebugOverlay::new(&mut engine),
             engine,
             level: None,
-            debug_string: String::new(),
             time,
             events_receiver: rx,
             events_sender: tx,
             load_context: None,
+            benchmark: None,
+            gamepad: GamepadInput::new(),
+            debug_geometry: false,
+            bot_debug_labels: Default::default(),
+            #[cfg(feature = "hot-reload")]
+            definition_watcher: tunables::DefinitionWatcher::new(DEFINITIONS_DIR),
         };
 
-        game.create_debug_ui();
+        if let Some(options) = BenchmarkOptions::from_args() {
+            game.benchmark = Some(Benchmark::new(options));
+            game.start_new_game(0, MatchOptions::default());
+        }
 
         events_loop.run(move |event, _, control_flow| {
             game.process_input_event(&event);
@@ -276,6 +310,13 @@ impl Game {
 
                         while let Some(ui_event) = game.engine.user_interface.poll_message() {
                             game.menu.handle_ui_event(&mut game.engine, &ui_event);
+                            game.pause_menu.handle_ui_event(&mut game.engine, &ui_event);
+                            game.death_screen.handle_ui_event(&ui_event);
+                            game.loading_screen.handle_ui_event(&ui_event);
+                            if let Some(level) = game.level.as_mut() {
+                                game.event_log_panel
+                                    .handle_ui_message(&ui_event, &mut level.event_log);
+                            }
                         }
                     }
                     if !game.running {
@@ -284,12 +325,9 @@ impl Game {
                     game.engine.get_window().request_redraw();
                 }
                 Event::RedrawRequested(_) => {
-                    game.update_statistics(game.time.elapsed);
-
-                    // <<<<< ENABLE THIS TO SHOW DEBUG GEOMETRY >>>>>
-                    if false {
-                        game.debug_render();
-                    }
+                    game.loading_screen.on_frame_rendered();
+                    game.debug_render();
+                    game.update_debug_overlay();
 
                     // Render at max speed
                     game.engine.render().unwrap();
@@ -317,41 +355,88 @@ impl Game {
     }
 
     fn debug_render(&mut self) {
+        self.bot_debug_labels.clear();
         if let Some(level) = self.level.as_mut() {
-            level.debug_draw(&mut self.engine);
+            if self.debug_geometry {
+                level.debug_draw(&mut self.engine, &mut self.bot_debug_labels);
+            } else {
+                // Lines stay until cleared, so they are gone right after the toggle.
+                self.engine.scenes[level.scene]
+                    .drawing_context
+                    .clear_lines();
+            }
         }
     }
 
-    pub fn create_debug_ui(&mut self) {
-        self.debug_text = TextBuilder::new(WidgetBuilder::new().with_width(400.0))
-            .build(&mut self.engine.user_interface.build_ctx());
+    fn process_gamepad_events(&mut self) {
+        let mut events = Vec::new();
+        self.gamepad.poll(&mut events);
+        for event in events.iter() {
+            if self.is_menu_visible() || self.is_paused() {
+                self.menu.process_gamepad_event(&mut self.engine, event);
+            } else if let Some(level) = self.level.as_mut() {
+                level.process_gamepad_event(event);
+                self.hud.process_gamepad_event(
+                    &mut self.engine.user_interface,
+                    event,
+                    &self.control_scheme.read().unwrap(),
+                );
+            }
+        }
+    }
+
+    fn dump_event_log(&self) {
+        if let Some(level) = self.level.as_ref() {
+            match level.event_log.dump("event_log.txt") {
+                Ok(_) => Log::writeln(
+                    MessageKind::Information,
+                    "Event log saved to event_log.txt".to_owned(),
+                ),
+                Err(e) => Log::writeln(
+                    MessageKind::Error,
+                    format!("Failed to save event log, reason: {}", e),
+                ),
+            }
+        }
     }
 
-    pub fn save_game(&mut self) -> VisitResult {
+    pub fn save_game(&mut self, slot: usize, name: &str) -> VisitResult {
         if let Some(level) = self.level.as_mut() {
+            // Fix dangling handles first, otherwise the save would be unloadable.
+            level.validate();
+
             let mut visitor = Visitor::new();
+            let scene = &mut self.engine.scenes[level.scene];
 
-            self.engine.scenes[level.scene].save("Scene", &mut visitor)?;
-            level.visit("Level", &mut visitor)?;
+            guarded_visit("Scene", &mut visitor, |visitor| {
+                scene.save("Scene", visitor)
+            })?;
+            guarded_visit("Level", &mut visitor, |visitor| {
+                level.visit("Level", visitor)
+            })?;
 
             // Debug output
             if let Ok(mut file) = File::create(Path::new("save.txt")) {
                 file.write_all(visitor.save_text().as_bytes()).unwrap();
             }
 
-            visitor.save_binary(Path::new("save.bin"))
+            SaveSlot::prepare_dir()?;
+            visitor.save_binary(SaveSlot::data_path(slot))?;
+            SaveSlot::new(name.to_owned(), level.time()).save(slot)?;
+
+            Ok(())
         } else {
             Ok(())
         }
     }
 
-    pub fn load_game(&mut self) -> VisitResult {
+    pub fn load_game(&mut self, slot: usize) -> VisitResult {
         Log::writeln(
             MessageKind::Information,
             "Attempting load a save...".to_owned(),
         );
 
-        let mut visitor = block_on(Visitor::load_binary(Path::new("save.bin")))?;
+        let mut visitor = block_on(Visitor::load_binary(SaveSlot::data_path(slot)))?;
 
         // Clean up.
         self.destroy_level();
@@ -362,41 +447,128 @@ impl Game {
             "Trying to load a save file...".to_owned(),
         );
 
-        let scene = block_on(
-            SceneLoader::load(
-                "Scene",
-                self.engine.serialization_context.clone(),
-                &mut visitor,
-            )?
-            .finish(self.engine.resource_manager.clone()),
-        );
+        // Visiting is quick, resources of the scene are what takes time, so only they are
+        // loaded on a separate thread while the loading screen is shown.
+        let serialization_context = self.engine.serialization_context.clone();
+        let result = guarded_visit("Scene", &mut visitor, |visitor| {
+            SceneLoader::load("Scene", serialization_context, visitor)
+        })
+        .and_then(|loader| {
+            let mut level = Level::default();
+            guarded_visit("Level", &mut visitor, |visitor| {
+                level.visit("Level", visitor)
+            })?;
+            Ok((loader, level))
+        });
+
+        let (loader, mut level) = match result {
+            Ok(state) => state,
+            Err(e) => {
+                // Previous level is already gone, so the only sane place to go is main menu.
+                self.set_menu_visible(true);
+                return Err(e);
+            }
+        };
+
+        let title = SaveSlot::load(slot)
+            .map(|save| save.name)
+            .unwrap_or_else(|| format!("Slot {}", slot + 1));
+        let resource_manager = self.engine.resource_manager.clone();
+        let sender = self.events_sender.clone();
+        self.begin_loading(&title, move || {
+            let scene = block_on(loader.finish(resource_manager.clone()));
+            level.set_preload(block_on(PreloadCache::load(
+                &resource_manager,
+                &PreloadList::gameplay(),
+                &sender,
+            )));
+            Ok((level, scene))
+        });
+
+        Ok(())
+    }
+
+    // Shows the loading screen and runs given function on a separate thread, the result is
+    // picked up by `update`. Panics of the function are turned into load errors.
+    fn begin_loading<F>(&mut self, title: &str, load: F)
+    where
+        F: FnOnce() -> Result<(Level, Scene), String> + Send + 'static,
+    {
+        let ctx = Arc::new(Mutex::new(LoadContext { level: None }));
+        self.load_context = Some(ctx.clone());
 
-        let mut level = Level::default();
-        level.visit("Level", &mut visitor)?;
+        let ui = &mut self.engine.user_interface;
+        self.loading_screen.show(ui, title);
+        self.menu.set_visible(ui, false);
+        self.hud.set_visible(ui, false);
+
+        std::thread::spawn(move || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(load))
+                .unwrap_or_else(|payload| Err(panic_reason(payload.as_ref())));
+
+            ctx.lock().unwrap().level = Some(result);
+        });
+    }
+
+    // Puts loaded level into the game, the level could come from a save so everything that
+    // isn't serialized is set here.
+    fn attach_level(&mut self, mut level: Level, scene: Scene) {
         level.scene = self.engine.scenes.add(scene);
+        level.set_message_sender(self.events_sender.clone());
+        level.control_scheme = Some(self.control_scheme.clone());
+        level.settings = Some(self.settings.clone());
+        level.captions = Some(self.captions.clone());
+        level.on_loaded(&self.engine.scenes[level.scene].graph);
+        let player = level.get_player();
+        if let Actor::Player(player) = level.actors_mut().get_mut(player) {
+            player.set_control_scheme(self.control_scheme.clone());
+        }
         self.level = Some(level);
+        // Level has music of its own.
+        self.set_menu_music_playing(false);
 
         Log::writeln(
             MessageKind::Information,
-            "Game state successfully loaded!".to_owned(),
+            "Level successfully loaded!".to_owned(),
         );
 
-        // Hide menu only of we successfully loaded a save.
+        // A save could be made while the level was paused, so it is resumed explicitly.
         self.set_menu_visible(false);
-
-        // Set control scheme for player.
-        if let Some(level) = &mut self.level {
-            level.set_message_sender(self.events_sender.clone());
-            level.control_scheme = Some(self.control_scheme.clone());
-            let player = level.get_player();
-            if let Actor::Player(player) = level.actors_mut().get_mut(player) {
-                player.set_control_scheme(self.control_scheme.clone());
-            }
-        }
+        self.set_paused(false);
+        self.loading_screen.finish(&self.engine.user_interface);
 
         self.time.elapsed = self.time.clock.elapsed().as_secs_f64();
+    }
 
-        Ok(())
+    // Finished level unlocks the next one in the level list.
+    fn complete_level(&self, id: &str) {
+        let mut progress = Progress::load(PROGRESS_PATH);
+        progress.complete(id);
+        if let Err(e) = progress.save(PROGRESS_PATH) {
+            Log::writeln(
+                MessageKind::Error,
+                format!("Failed to save progress, reason: {}", e),
+            );
+        }
+    }
+
+    // Sounds of the interface are played in the menu scene, it exists all the time.
+    fn play_ui_sound(&mut self, path: &Path, gain: f32) {
+        match block_on(self.engine.resource_manager.request_sound_buffer(path)) {
+            Ok(buffer) => {
+                let volume = self.settings.read().unwrap().volume(SoundCategory::Ui);
+                SoundBuilder::new(BaseBuilder::new())
+                    .with_buffer(Some(buffer))
+                    .with_status(Status::Playing)
+                    .with_play_once(true)
+                    .with_gain(gain * volume)
+                    .build(&mut self.engine.scenes[self.menu_scene].graph);
+            }
+            Err(_) => Log::writeln(
+                MessageKind::Error,
+                format!("Unable to play sound {:?}", path),
+            ),
+        }
     }
 
     fn destroy_level(&mut self) {
@@ -407,55 +579,138 @@ impl Game {
                 "Current level destroyed!".to_owned(),
             );
         }
+        self.set_menu_music_playing(true);
     }
 
-    pub fn start_new_game(&mut self, options: MatchOptions) {
-        self.destroy_level();
-
-        let ctx = Arc::new(Mutex::new(LoadContext { level: None }));
+    fn set_menu_music_playing(&mut self, playing: bool) {
+        let music = self.engine.scenes[self.menu_scene].graph[self.music].as_sound_mut();
+        if playing {
+            music.play();
+        } else {
+            music.pause();
+        }
+    }
 
-        self.load_context = Some(ctx.clone());
+    pub fn start_new_game(&mut self, level: usize, options: MatchOptions) {
+        let definition = match self.level_list.levels.get(level) {
+            Some(definition) => definition.clone(),
+            None => {
+                Log::writeln(
+                    MessageKind::Error,
+                    format!("There is no level {} in the level list!", level),
+                );
+                return;
+            }
+        };
 
-        self.engine
-            .user_interface
-            .send_message(WidgetMessage::visibility(
-                self.loading_screen.root,
-                MessageDirection::ToWidget,
-                true,
-            ));
-        self.menu
-            .set_visible(&mut self.engine.user_interface, false);
+        self.destroy_level();
 
         let resource_manager = self.engine.resource_manager.clone();
         let control_scheme = self.control_scheme.clone();
+        let settings = self.settings.clone();
         let sender = self.events_sender.clone();
 
-        std::thread::spawn(move || {
-            let level = fyrox::core::futures::executor::block_on(Level::new(
+        let title = definition.name.clone();
+        self.begin_loading(&title, move || {
+            block_on(Level::new(
                 resource_manager,
                 control_scheme,
+                settings,
                 sender,
                 options,
-            ));
-
-            ctx.lock().unwrap().level = Some(level);
+                definition,
+            ))
         });
     }
 
     pub fn set_menu_visible(&mut self, visible: bool) {
+        if visible {
+            if let Some(level) = self.level.as_mut() {
+                level.reset_player_input();
+            }
+        }
         let ui = &mut self.engine.user_interface;
         self.menu.set_visible(ui, visible);
         self.hud.set_visible(ui, !visible);
     }
 
+    // Widgets take their text only when they're built, so menus are built again with strings
+    // of new language. Other screens pick it up on the next start.
+    fn rebuild_menus(&mut self) {
+        let ui = &self.engine.user_interface;
+        let menu_visible = self.menu.is_visible(ui);
+        let paused = self.is_paused();
+        self.menu.destroy(ui);
+        self.pause_menu.destroy(ui);
+
+        self.menu = Menu::new(
+            &mut self.engine,
+            self.control_scheme.clone(),
+            self.settings.clone(),
+            self.level_list.clone(),
+            self.events_sender.clone(),
+        );
+        self.pause_menu = PauseMenu::new(&mut self.engine, self.events_sender.clone());
+
+        let ui = &mut self.engine.user_interface;
+        self.menu.set_visible(ui, menu_visible);
+        self.pause_menu.set_visible(ui, paused);
+        // Language is changed in the options, they stay open.
+        self.menu.show_options(ui);
+    }
+
     pub fn is_menu_visible(&self) -> bool {
         self.menu.is_visible(&self.engine.user_interface)
     }
 
+    pub fn set_paused(&mut self, paused: bool) {
+        if let Some(level) = self.level.as_mut() {
+            level.set_paused(&mut self.engine, paused);
+            if paused {
+                level.reset_player_input();
+            }
+        }
+        let ui = &mut self.engine.user_interface;
+        self.pause_menu.set_visible(ui, paused);
+        self.hud.set_visible(ui, !paused);
+        if !paused {
+            self.menu.close_options(ui);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_menu.is_visible(&self.engine.user_interface)
+    }
+
+    // Cursor is needed for every menu.
+    fn is_cursor_needed(&self) -> bool {
+        self.is_menu_visible()
+            || self.is_paused()
+            || self.loading_screen.is_loading()
+            || self.death_screen.is_visible(&self.engine.user_interface)
+    }
+
+    // Death screen is hidden under menus and comes back when they're closed.
+    fn sync_death_screen(&mut self) {
+        let summary = if self.is_menu_visible() || self.is_paused() {
+            None
+        } else {
+            self.level.as_ref().and_then(|level| level.death_summary())
+        };
+        let ui = &mut self.engine.user_interface;
+        match summary {
+            Some(summary) if !self.death_screen.is_visible(ui) => {
+                self.death_screen.show(ui, &summary)
+            }
+            None if self.death_screen.is_visible(ui) => self.death_screen.hide(ui),
+            _ => (),
+        }
+    }
+
     pub fn update(&mut self, time: GameTime, control_flow: &mut ControlFlow) {
         let window = self.engine.get_window();
-        window.set_cursor_visible(self.is_menu_visible());
-        let _ = window.set_cursor_grab(if !self.is_menu_visible() {
+        window.set_cursor_visible(self.is_cursor_needed());
+        let _ = window.set_cursor_grab(if !self.is_cursor_needed() {
             CursorGrabMode::Confined
         } else {
             CursorGrabMode::None
@@ -463,97 +718,248 @@ impl Game {
 
         if let Some(ctx) = self.load_context.clone() {
             if let Ok(mut ctx) = ctx.try_lock() {
-                if let Some((mut level, scene)) = ctx.level.take() {
-                    level.scene = self.engine.scenes.add(scene);
-                    self.level = Some(level);
+                // Level that loaded faster than a frame waits until the screen was drawn.
+                let is_ready = match ctx.level.as_ref() {
+                    Some(Ok(_)) => self.loading_screen.can_finish(),
+                    Some(Err(_)) => true,
+                    None => false,
+                };
+                if is_ready {
                     self.load_context = None;
-                    self.set_menu_visible(false);
-                    self.engine
-                        .user_interface
-                        .send_message(WidgetMessage::visibility(
-                            self.loading_screen.root,
-                            MessageDirection::ToWidget,
-                            false,
-                        ));
+                    match ctx.level.take().unwrap() {
+                        Ok((level, scene)) => {
+                            self.attach_level(level, scene);
+                            if let Some(benchmark) = self.benchmark.as_mut() {
+                                for (kind, name) in benchmark.take_bots() {
+                                    self.events_sender
+                                        .send(Message::SpawnBot {
+                                            kind,
+                                            name,
+                                            team: None,
+                                        })
+                                        .unwrap();
+                                }
+                            }
+                        }
+                        Err(reason) => {
+                            Log::writeln(
+                                MessageKind::Error,
+                                format!("Failed to load level, reason: {}", reason),
+                            );
+                            self.loading_screen
+                                .fail(&self.engine.user_interface, &reason);
+                        }
+                    }
                 } else {
-                    self.engine
-                        .user_interface
-                        .send_message(ProgressBarMessage::progress(
-                            self.loading_screen.progress_bar,
-                            MessageDirection::ToWidget,
-                            self.engine.resource_manager.state().loading_progress() as f32 / 100.0,
-                        ));
+                    self.loading_screen.set_progress(
+                        &self.engine.user_interface,
+                        self.engine.resource_manager.state().loading_progress() as f32 / 100.0,
+                    );
                 }
             }
         }
+        self.loading_screen
+            .update(&self.engine.user_interface, time.delta);
 
         let mut lag = 0f32;
         self.engine.update(time.delta, control_flow, &mut lag);
 
+        self.process_gamepad_events();
+
+        let is_paused = self.is_paused();
         if let Some(ref mut level) = self.level {
-            level.update(&mut self.engine, time);
+            let tick_start = Instant::now();
+            if !is_paused {
+                level.update(&mut self.engine, time);
+            }
+            if let Some(benchmark) = self.benchmark.as_mut() {
+                benchmark.record(time.delta, tick_start.elapsed().as_secs_f32() * 1000.0);
+                if benchmark.is_finished() {
+                    let within_budget = benchmark.finish();
+                    self.destroy_level();
+                    std::process::exit(if within_budget { 0 } else { 1 });
+                }
+            }
             let ui = &mut self.engine.user_interface;
             self.hud.set_time(ui, level.time());
+            self.hud.set_boss_bar(ui, level.boss_bar().as_ref());
+            if self.hud.leader_board_mut().needs_refresh(time.delta) {
+                self.hud.leader_board_mut().sync_to_model(
+                    ui,
+                    &level.scoreboard_rows(),
+                    &level.leader_board,
+                    &level.options,
+                    level.time(),
+                );
+            }
             let player = level.get_player();
             if player.is_some() {
                 // Sync hud with player state.
                 let player = level.actors().get(player);
-                self.hud.set_health(ui, player.get_health());
-                self.hud.set_armor(ui, player.get_armor());
+                let graph = &self.engine.scenes[level.scene].graph;
+                self.hud.set_data(ui, level.hud_data(graph).as_ref());
                 let current_weapon = player.current_weapon();
-                if current_weapon.is_some() {
-                    self.hud
-                        .set_ammo(ui, level.weapons()[current_weapon].ammo());
+                let mut weapons = String::new();
+                for &handle in player.weapons() {
+                    let weapon = &level.weapons()[handle];
+                    use std::fmt::Write;
+                    writeln!(
+                        weapons,
+                        "{}[{}] {:?}",
+                        if handle == current_weapon { "> " } else { "" },
+                        weapon.definition().slot,
+                        weapon.get_kind()
+                    )
+                    .unwrap();
                 }
+                self.hud.set_weapons(ui, weapons);
+                self.hud
+                    .set_use_prompt(ui, level.use_prompt().unwrap_or_default());
                 self.hud.set_is_died(ui, false);
             } else {
-                self.hud.set_is_died(ui, true);
+                self.hud.set_use_prompt(ui, "");
+                self.hud.set_data(ui, None);
+                let is_death_screen_visible = self.death_screen.is_visible(ui);
+                self.hud.set_is_died(ui, !is_death_screen_visible);
+            }
+        }
+
+        self.sync_death_screen();
+
+        #[cfg(feature = "hot-reload")]
+        {
+            let watcher = &mut self.definition_watcher;
+            for kind in tunables::modify(|tunables| watcher.update(time.delta, tunables)) {
+                self.events_sender
+                    .send(Message::DefinitionsReloaded { kind })
+                    .unwrap();
             }
         }
 
         self.handle_messages(time);
 
+        {
+            let settings = self.settings.read().unwrap();
+            self.hud.set_caption_style(
+                &mut self.engine.user_interface,
+                settings.captions,
+                settings.caption_size,
+            );
+        }
         self.hud.update(&mut self.engine.user_interface, &self.time);
+
+        if let Some(level) = self.level.as_ref() {
+            self.event_log_panel
+                .sync_to_model(&self.engine.user_interface, &level.event_log);
+        }
     }
 
     fn handle_messages(&mut self, time: GameTime) {
         while let Ok(message) = self.events_receiver.try_recv() {
             match &message {
-                Message::StartNewGame { options } => {
-                    self.start_new_game(*options);
+                Message::StartNewGame { level, options } => {
+                    self.start_new_game(*level, *options);
                 }
-                Message::SaveGame => match self.save_game() {
+                Message::SaveGame { slot, name } => match self.save_game(*slot, name) {
                     Ok(_) => {
-                        Log::writeln(MessageKind::Information, "Successfully saved".to_owned())
+                        Log::writeln(MessageKind::Information, "Successfully saved".to_owned());
+                        self.events_sender
+                            .send(Message::Notify {
+                                text: tr("notify.game_saved"),
+                                icon: Some("data/ui/check_mark.png".into()),
+                                priority: NotificationPriority::Normal,
+                            })
+                            .unwrap();
+                    }
+                    Err(e) => {
+                        Log::writeln(
+                            MessageKind::Error,
+                            format!("Failed to make a save, reason: {}", e),
+                        );
+                        self.events_sender
+                            .send(Message::Notify {
+                                text: tr_format("notify.save_failed", &[&e]),
+                                icon: None,
+                                priority: NotificationPriority::High,
+                            })
+                            .unwrap();
                     }
-                    Err(e) => Log::writeln(
-                        MessageKind::Error,
-                        format!("Failed to make a save, reason: {}", e),
-                    ),
                 },
-                Message::LoadGame => {
-                    if let Err(e) = self.load_game() {
+                Message::LoadGame { slot } => {
+                    if let Err(e) = self.load_game(*slot) {
                         Log::writeln(
                             MessageKind::Error,
-                            format!("Failed to load saved game. Reason: {:?}", e),
+                            format!("Failed to load saved game. Reason: {}", e),
                         );
+                        self.events_sender
+                            .send(Message::Notify {
+                                text: tr_format("notify.load_failed", &[&e]),
+                                icon: None,
+                                priority: NotificationPriority::High,
+                            })
+                            .unwrap();
                     }
                 }
+                Message::ResumeGame => {
+                    self.set_paused(false);
+                }
+                Message::ShowOptions => {
+                    self.menu.show_options(&self.engine.user_interface);
+                }
+                Message::RestartLevel => {
+                    if let Some((index, options)) = self.level.as_ref().and_then(|level| {
+                        self.level_list
+                            .find(&level.level_id)
+                            .map(|index| (index, level.options))
+                    }) {
+                        self.set_paused(false);
+                        self.start_new_game(index, options);
+                    }
+                }
+                Message::QuitToMenu => {
+                    self.loading_screen.hide(&self.engine.user_interface);
+                    self.set_paused(false);
+                    self.destroy_level();
+                    self.set_menu_visible(true);
+                }
                 Message::QuitGame => {
                     self.destroy_level();
                     self.running = false;
                 }
                 Message::EndMatch => {
+                    // Final scores are taken before the level is destroyed.
+                    if let Some(level) = self.level.as_ref() {
+                        self.complete_level(&level.level_id);
+                        self.hud.leader_board_mut().sync_to_model(
+                            &mut self.engine.user_interface,
+                            &level.scoreboard_rows(),
+                            &level.leader_board,
+                            &level.options,
+                            level.time(),
+                        );
+                    }
                     self.destroy_level();
                     self.hud
-                        .leader_board()
+                        .leader_board_mut()
                         .set_visible(true, &mut self.engine.user_interface);
                 }
+                Message::PlayUiSound { path, gain } => {
+                    self.play_ui_sound(path, *gain);
+                }
                 Message::SetMusicVolume { volume } => {
                     self.engine.scenes[self.menu_scene].graph[self.music]
                         .as_sound_mut()
                         .set_gain(*volume);
                 }
+                Message::LanguageChanged => {
+                    self.rebuild_menus();
+                }
+                Message::PreloadProgress { done, total } => {
+                    self.loading_screen.set_status(
+                        &self.engine.user_interface,
+                        &tr_format("loading.preload", &[done, total]),
+                    );
+                }
                 _ => (),
             }
 
@@ -574,34 +980,43 @@ impl Game {
         }
     }
 
-    pub fn update_statistics(&mut self, elapsed: f64) {
-        self.debug_string.clear();
-        use std::fmt::Write;
+    // Nothing is collected while the overlay is hidden.
+    pub fn update_debug_overlay(&mut self) {
+        if !self.debug_overlay.is_visible() {
+            return;
+        }
+
         let statistics = self.engine.renderer.get_statistics();
-        write!(
-            self.debug_string,
-            "Pure frame time: {:.2} ms\n\
-               Capped frame time: {:.2} ms\n\
-               FPS: {}\n\
-               Triangles: {}\n\
-               Draw calls: {}\n\
-               Uptime: {:.2} s\n\
-               UI time: {:?}",
-            statistics.pure_frame_time * 1000.0,
-            statistics.capped_frame_time * 1000.0,
-            statistics.frames_per_second,
-            statistics.geometry.triangles_rendered,
-            statistics.geometry.draw_calls,
-            elapsed,
-            self.engine.ui_time
-        )
-        .unwrap();
+        let mut stats = DebugStats {
+            fps: statistics.frames_per_second,
+            frame_time: statistics.pure_frame_time * 1000.0,
+            capped_frame_time: statistics.capped_frame_time * 1000.0,
+            triangles: statistics.geometry.triangles_rendered,
+            draw_calls: statistics.geometry.draw_calls,
+            uptime: self.time.elapsed,
+            ui_time: self.engine.ui_time,
+            ..Default::default()
+        };
+        if let Some(level) = self.level.as_ref() {
+            level.fill_debug_stats(&self.engine, &mut stats);
+        }
+        stats.bot_labels = self
+            .bot_debug_labels
+            .iter()
+            .map(|(position, text)| {
+                format!(
+                    "{} at {:.1} {:.1} {:.1}",
+                    text, position.x, position.y, position.z
+                )
+            })
+            .collect();
+        #[cfg(feature = "profiler")]
+        {
+            stats.profiler = fyrox::core::profiler::print().ok();
+        }
 
-        self.engine.user_interface.send_message(TextMessage::text(
-            self.debug_text,
-            MessageDirection::ToWidget,
-            self.debug_string.clone(),
-        ));
+        self.debug_overlay
+            .update(&self.engine.user_interface, &stats);
     }
 
     fn process_dispatched_event(&mut self, event: &Event<()>) {
@@ -611,7 +1026,7 @@ impl Game {
             }
         }
 
-        if !self.is_menu_visible() {
+        if !self.is_cursor_needed() {
             if let Some(ref mut level) = self.level {
                 level.process_input_event(event);
             }
@@ -619,25 +1034,248 @@ impl Game {
     }
 
     pub fn process_input_event(&mut self, event: &Event<()>) {
+        // Key capture of the controls menu takes input exclusively.
+        if self.menu.process_input_event(&mut self.engine, event) {
+            return;
+        }
+        self.loading_screen
+            .process_input_event(&mut self.engine, event);
+        if self
+            .death_screen
+            .process_input_event(&mut self.engine, event)
+        {
+            return;
+        }
+        // Options are shown over the pause menu, keys must not reach the menu under them.
+        if !self.menu.is_options_visible(&self.engine.user_interface)
+            && self.pause_menu.process_input_event(&mut self.engine, event)
+        {
+            return;
+        }
+
         self.process_dispatched_event(event);
 
+        if let Event::WindowEvent {
+            event: WindowEvent::Focused(focused),
+            ..
+        } = event
+        {
+            if !focused {
+                // Release events won't come while window is not focused.
+                if let Some(level) = self.level.as_mut() {
+                    level.reset_player_input();
+                }
+            }
+            // Volumes are applied per sound, so the engine's gain is free to be used as a mute
+            // switch.
+            if self.settings.read().unwrap().mute_on_focus_loss {
+                self.engine.set_sound_gain(if *focused { 1.0 } else { 0.0 });
+            }
+        }
+
         if let Event::WindowEvent { event, .. } = event {
             if let WindowEvent::KeyboardInput { input, .. } = event {
                 if let ElementState::Pressed = input.state {
                     if let Some(key) = input.virtual_keycode {
-                        if key == VirtualKeyCode::Escape {
-                            self.set_menu_visible(!self.is_menu_visible());
+                        // Nothing to pause while loading, failed load has its own way out.
+                        if key == VirtualKeyCode::Escape && !self.loading_screen.is_loading() {
+                            // In-game Escape goes through the pause menu, main menu can't be
+                            // closed while there is no level to return to.
+                            if self.level.is_some() && !self.is_menu_visible() {
+                                self.set_paused(!self.is_paused());
+                            } else if self.level.is_some() {
+                                self.set_menu_visible(false);
+                            }
+                        } else if key == VirtualKeyCode::F3 {
+                            self.debug_overlay.set_visible(
+                                &self.engine.user_interface,
+                                !self.debug_overlay.is_visible(),
+                            );
+                        } else if key == VirtualKeyCode::F4 {
+                            self.debug_geometry = !self.debug_geometry;
+                        } else if let Some(flag) =
+                            bot_debug_flag(key).filter(|_| self.debug_geometry)
+                        {
+                            if let Some(level) = self.level.as_mut() {
+                                level.bot_debug_flags.toggle(flag);
+                            }
+                        } else if key == VirtualKeyCode::F8 {
+                            self.event_log_panel.set_visible(
+                                &self.engine.user_interface,
+                                !self.event_log_panel.is_visible(),
+                            );
+                        } else if key == VirtualKeyCode::F9 {
+                            self.dump_event_log();
                         }
                     }
                 }
             }
         }
 
-        self.menu.process_input_event(&mut self.engine, &event);
-        self.hud.process_event(&mut self.engine, &event);
+        self.hud.process_event(
+            &mut self.engine,
+            &event,
+            &self.control_scheme.read().unwrap(),
+        );
+    }
+}
+
+/// Part of the bot debug drawing that is toggled by a numpad key while the debug geometry is
+/// on.
+fn bot_debug_flag(key: VirtualKeyCode) -> Option<BotDebugFlags> {
+    match key {
+        VirtualKeyCode::Numpad1 => Some(BotDebugFlags::PATH),
+        VirtualKeyCode::Numpad2 => Some(BotDebugFlags::FRUSTUM),
+        VirtualKeyCode::Numpad3 => Some(BotDebugFlags::TARGET),
+        VirtualKeyCode::Numpad4 => Some(BotDebugFlags::POINT_OF_INTEREST),
+        VirtualKeyCode::Numpad5 => Some(BotDebugFlags::STATE_LABELS),
+        VirtualKeyCode::Numpad6 => Some(BotDebugFlags::STUCK),
+        VirtualKeyCode::Numpad7 => Some(BotDebugFlags::DODGE),
+        VirtualKeyCode::Numpad8 => Some(BotDebugFlags::PATROL),
+        _ => None,
+    }
+}
+
+/// Runs visiting routine for given region and converts both errors and panics into
+/// a visit error prefixed with the path to the failing field, so a corrupted save can't
+/// crash the game and tells where it's broken.
+fn guarded_visit<T, F>(region: &str, visitor: &mut Visitor, func: F) -> Result<T, VisitError>
+where
+    F: FnOnce(&mut Visitor) -> Result<T, VisitError>,
+{
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| func(visitor)));
+    if let Ok(Ok(value)) = result {
+        return Ok(value);
+    }
+    // Failed visit doesn't leave the regions it entered, the visitor is still where it failed.
+    let path = visitor_path(visitor);
+    let path = if path.is_empty() { region } else { &path };
+    match result {
+        Ok(result) => result.map_err(|e| VisitError::User(format!("{}: {}", path, e))),
+        Err(payload) => Err(VisitError::User(format!(
+            "{}: panicked - {}",
+            path,
+            panic_reason(payload.as_ref())
+        ))),
+    }
+}
+
+// Regions from the root to the current one, separated by slashes. Visitor is left at the
+// root, so it can't be used for visiting after this.
+fn visitor_path(visitor: &mut Visitor) -> String {
+    let mut regions = Vec::new();
+    loop {
+        let name = visitor.current_region().map(str::to_owned);
+        // Root region has no parent to leave to and isn't a part of the path.
+        if visitor.leave_region().is_err() {
+            break;
+        }
+        regions.extend(name);
+    }
+    regions.reverse();
+    regions.join("/")
+}
+
+/// Visits a field that saves made by older versions lack, a missing field keeps the value it
+/// had before reading.
+pub fn visit_optional<T: Visit>(value: &mut T, name: &str, visitor: &mut Visitor) -> VisitResult {
+    match value.visit(name, visitor) {
+        Err(VisitError::FieldDoesNotExist(_) | VisitError::RegionDoesNotExist(_))
+            if visitor.is_reading() =>
+        {
+            Ok(())
+        }
+        result => result,
     }
 }
 
+fn panic_reason(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned())
+}
+
 fn main() {
     Game::run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resource manager for tests of missing resources. Tests run from the crate directory,
+    /// paths of the game data don't resolve there.
+    pub fn resource_manager() -> ResourceManager {
+        ResourceManager::new(Arc::new(SerializationContext::new()))
+    }
+
+    #[derive(Default, Visit)]
+    struct Inner {
+        value: u32,
+    }
+
+    #[derive(Default, Visit)]
+    struct Outer {
+        inner: Inner,
+    }
+
+    // Same as `Outer`, but the inner struct has another field.
+    #[derive(Default, Visit)]
+    struct OldInner {
+        other: u32,
+    }
+
+    #[derive(Default, Visit)]
+    struct OldOuter {
+        inner: OldInner,
+    }
+
+    #[test]
+    fn guarded_visit_names_path_to_failing_field() {
+        let path = std::env::temp_dir().join(format!("wood_guarded_{}.bin", std::process::id()));
+        let mut visitor = Visitor::new();
+        OldOuter::default().visit("Level", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+        let mut visitor = block_on(Visitor::load_binary(&path)).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let mut outer = Outer::default();
+        match guarded_visit("Level", &mut visitor, |visitor| {
+            outer.visit("Level", visitor)
+        }) {
+            Err(VisitError::User(message)) => {
+                assert!(message.starts_with("Level/inner: "), "{}", message);
+                assert!(message.contains("value"), "{}", message);
+            }
+            _ => panic!("missing field was read"),
+        }
+    }
+
+    #[test]
+    fn guarded_visit_catches_panics() {
+        let mut visitor = Visitor::new();
+        let result: Result<(), _> = guarded_visit("Level", &mut visitor, |visitor| {
+            visitor.enter_region("Level")?;
+            visitor.enter_region("Actors")?;
+            panic!("corrupted");
+        });
+        match result {
+            Err(VisitError::User(message)) => {
+                assert_eq!(message, "Level/Actors: panicked - corrupted")
+            }
+            _ => panic!("panic wasn't caught"),
+        }
+
+        // Region that isn't there at all is named by the caller.
+        let mut visitor = Visitor::new();
+        let result = guarded_visit("Scene", &mut visitor, |_| -> VisitResult {
+            Err(VisitError::User("broken".to_owned()))
+        });
+        match result {
+            Err(VisitError::User(message)) => assert_eq!(message, "Scene: broken"),
+            _ => panic!("error was lost"),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     gui::{create_scroll_bar, ScrollBarData},
+    localization::tr,
     message::Message,
     DeathMatch, MatchOptions,
 };
@@ -12,20 +13,33 @@ use fyrox::{
         decorator::DecoratorBuilder,
         dropdown_list::DropdownListBuilder,
         grid::{Column, GridBuilder, Row},
-        message::UiMessage,
+        message::{MessageDirection, UiMessage},
         scroll_bar::ScrollBar,
         text::TextBuilder,
         text_box::TextBoxBuilder,
         widget::WidgetBuilder,
-        window::{WindowBuilder, WindowTitle},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
         HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
 };
 use std::sync::mpsc::Sender;
 
+/// Limits that are preselected in the menu, New Game starts a match with them right away.
+pub const DEFAULT_TIME_LIMIT_MINUTES: f32 = 10.0;
+pub const DEFAULT_FRAG_LIMIT: u32 = 30;
+
+pub fn default_match_options() -> MatchOptions {
+    MatchOptions::DeathMatch(DeathMatch {
+        time_limit_secs: DEFAULT_TIME_LIMIT_MINUTES * 60.0,
+        frag_limit: DEFAULT_FRAG_LIMIT,
+    })
+}
+
 pub struct MatchMenu {
     sender: Sender<Message>,
     pub window: Handle<UiNode>,
+    // Index of the level in the level list the match is played on.
+    level: usize,
     sb_frag_limit: Handle<UiNode>,
     sb_time_limit: Handle<UiNode>,
     start_button: Handle<UiNode>,
@@ -40,22 +54,26 @@ impl MatchMenu {
         let sb_time_limit;
         let start_button;
         let window = WindowBuilder::new(WidgetBuilder::new().with_width(500.0))
-            .with_title(WindowTitle::text("Match Options"))
+            .with_title(WindowTitle::text(tr("match.match_options")))
             .open(false)
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new()
                         .with_child(
                             TextBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
-                                .with_text("Match Type")
+                                .with_text(&tr("match.match_type"))
                                 .build(ctx),
                         )
                         .with_child(
                             DropdownListBuilder::new(WidgetBuilder::new().on_column(1).on_row(0))
                                 .with_items({
                                     let mut items = Vec::new();
-                                    for mode in
-                                        ["Deathmatch", "Team Deathmatch", "Capture The Flag"].iter()
+                                    for mode in [
+                                        "match.deathmatch",
+                                        "match.team_deathmatch",
+                                        "match.capture_the_flag",
+                                    ]
+                                    .iter()
                                     {
                                         let item = DecoratorBuilder::new(BorderBuilder::new(
                                             WidgetBuilder::new().with_height(30.0).with_child(
@@ -68,7 +86,7 @@ impl MatchMenu {
                                                             VerticalAlignment::Center,
                                                         ),
                                                 )
-                                                .with_text(mode)
+                                                .with_text(&tr(mode))
                                                 .build(ctx),
                                             ),
                                         ))
@@ -81,7 +99,7 @@ impl MatchMenu {
                         )
                         .with_child(
                             TextBuilder::new(WidgetBuilder::new().on_row(1).on_column(0))
-                                .with_text("Time Limit (min)")
+                                .with_text(&tr("match.time_limit_min"))
                                 .build(ctx),
                         )
                         .with_child({
@@ -90,7 +108,7 @@ impl MatchMenu {
                                 ScrollBarData {
                                     min: 5.0,
                                     max: 60.0,
-                                    value: 10.0,
+                                    value: DEFAULT_TIME_LIMIT_MINUTES,
                                     step: 1.0,
                                     row: 1,
                                     column: 1,
@@ -103,7 +121,7 @@ impl MatchMenu {
                         })
                         .with_child(
                             TextBuilder::new(WidgetBuilder::new().on_row(2).on_column(0))
-                                .with_text("Frag Limit")
+                                .with_text(&tr("match.frag_limit"))
                                 .build(ctx),
                         )
                         .with_child({
@@ -112,7 +130,7 @@ impl MatchMenu {
                                 ScrollBarData {
                                     min: 10.0,
                                     max: 200.0,
-                                    value: 30.0,
+                                    value: DEFAULT_FRAG_LIMIT as f32,
                                     step: 1.0,
                                     row: 2,
                                     column: 1,
@@ -130,7 +148,7 @@ impl MatchMenu {
                                     .on_column(0)
                                     .with_margin(Thickness::uniform(2.0)),
                             )
-                            .with_text("Player Name")
+                            .with_text(&tr("match.player_name"))
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx),
                         )
@@ -141,13 +159,13 @@ impl MatchMenu {
                                     .on_column(1)
                                     .with_margin(Thickness::uniform(2.0)),
                             )
-                            .with_text("Unnamed Player".to_owned())
+                            .with_text(tr("match.unnamed_player"))
                             .build(ctx),
                         )
                         .with_child({
                             start_button =
                                 ButtonBuilder::new(WidgetBuilder::new().on_row(4).on_column(1))
-                                    .with_text("Start")
+                                    .with_text(&tr("match.start"))
                                     .build(ctx);
                             start_button
                         }),
@@ -166,12 +184,22 @@ impl MatchMenu {
         Self {
             sender,
             window,
+            level: 0,
             sb_frag_limit,
             sb_time_limit,
             start_button,
         }
     }
 
+    pub fn open(&mut self, ui: &UserInterface, level: usize) {
+        self.level = level;
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
     pub fn handle_ui_event(&mut self, engine: &mut Engine, message: &UiMessage) {
         let ui = &mut engine.user_interface;
 
@@ -196,7 +224,12 @@ impl MatchMenu {
                     frag_limit: frag_limit as u32,
                 });
 
-                self.sender.send(Message::StartNewGame { options }).unwrap();
+                self.sender
+                    .send(Message::StartNewGame {
+                        level: self.level,
+                        options,
+                    })
+                    .unwrap();
             }
         }
     }
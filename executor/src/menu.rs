@@ -1,16 +1,29 @@
 use crate::{
-    control_scheme::ControlScheme, match_menu::MatchMenu, message::Message,
+    campaign::LevelList,
+    control_scheme::ControlScheme,
+    gamepad::GamepadEvent,
+    gui::create_background_image,
+    level_select::LevelSelect,
+    localization::tr,
+    match_menu::{default_match_options, MatchMenu},
+    message::Message,
     options_menu::OptionsMenu,
+    save_slot::{SlotWindow, SlotWindowMode},
+    settings::Settings,
 };
 use fyrox::core::parking_lot::Mutex;
 use fyrox::{
-    core::pool::Handle,
+    core::{color::Color, pool::Handle},
     engine::Engine,
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
     gui::{
-        button::{ButtonBuilder, ButtonMessage},
+        border::BorderBuilder,
+        brush::Brush,
+        button::{Button, ButtonBuilder, ButtonMessage},
+        decorator::DecoratorMessage,
         grid::{Column, GridBuilder, Row},
         message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
         ttf::{Font, SharedFont},
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
@@ -18,26 +31,41 @@ use fyrox::{
     },
 };
 use std::{
-    path::Path,
+    path::{Path, PathBuf},
     sync::{mpsc::Sender, Arc, RwLock},
 };
 
+const BACKGROUND_PATH: &str = "data/ui/menu_background.png";
+const HOVER_SOUND: &str = "data/sounds/item_pickup.ogg";
+const HOVER_SOUND_GAIN: f32 = 0.3;
+const CLICK_SOUND: &str = "data/sounds/item_pickup.ogg";
+const CLICK_SOUND_GAIN: f32 = 1.0;
+
 pub struct Menu {
     sender: Sender<Message>,
     root: Handle<UiNode>,
     btn_new_game: Handle<UiNode>,
-    btn_save_game: Handle<UiNode>,
-    btn_settings: Handle<UiNode>,
+    btn_level_select: Handle<UiNode>,
     btn_load_game: Handle<UiNode>,
+    btn_settings: Handle<UiNode>,
     btn_quit_game: Handle<UiNode>,
+    // All buttons top to bottom, used for keyboard navigation.
+    buttons: Vec<Handle<UiNode>>,
+    selection: usize,
+    // Button under the mouse, hover sound is played only when it changes.
+    hovered: Option<usize>,
     options_menu: OptionsMenu,
     match_menu: MatchMenu,
+    level_select: LevelSelect,
+    slot_window: SlotWindow,
 }
 
 impl Menu {
     pub fn new(
         engine: &mut Engine,
         control_scheme: Arc<RwLock<ControlScheme>>,
+        settings: Arc<RwLock<Settings>>,
+        level_list: Arc<LevelList>,
         sender: Sender<Message>,
     ) -> Self {
         let frame_size = engine.renderer.get_frame_size();
@@ -52,110 +80,79 @@ impl Menu {
 
         let ctx = &mut engine.user_interface.build_ctx();
 
-        let btn_new_game;
-        let btn_settings;
-        let btn_save_game;
-        let btn_load_game;
-        let btn_quit_game;
-        let root: Handle<UiNode> = GridBuilder::new(
+        let buttons = [
+            "menu.new_game",
+            "menu.level_select",
+            "menu.load_game",
+            "menu.options",
+            "menu.quit",
+        ]
+        .iter()
+        .map(|key| {
+            ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .with_height(67.0)
+                    .with_margin(Thickness::uniform(4.0)),
+            )
+            .with_text_and_font(&tr(key), font.clone())
+            .build(ctx)
+        })
+        .collect::<Vec<_>>();
+
+        let background = create_background_image(ctx, &engine.resource_manager, BACKGROUND_PATH);
+
+        let root: Handle<UiNode> = BorderBuilder::new(
             WidgetBuilder::new()
                 .with_width(frame_size.0 as f32)
                 .with_height(frame_size.1 as f32)
+                .with_background(Brush::Solid(Color::opaque(10, 10, 14)))
+                .with_children(background)
                 .with_child(
-                    WindowBuilder::new(WidgetBuilder::new().on_row(1).on_column(1))
-                        .can_resize(false)
-                        .can_minimize(false)
-                        .can_close(false)
-                        .with_title(WindowTitle::text("Woodnet 2.0"))
-                        .with_content(
-                            GridBuilder::new(
-                                WidgetBuilder::new()
-                                    .with_margin(Thickness::uniform(20.0))
-                                    .with_child({
-                                        btn_new_game = ButtonBuilder::new(
-                                            WidgetBuilder::new()
-                                                .on_column(0)
-                                                .on_row(0)
-                                                .with_margin(Thickness::uniform(4.0)),
-                                        )
-                                        .with_text_and_font("New Game", font.clone())
-                                        .build(ctx);
-                                        btn_new_game
-                                    })
-                                    .with_child({
-                                        btn_save_game = ButtonBuilder::new(
-                                            WidgetBuilder::new()
-                                                .on_column(0)
-                                                .on_row(1)
-                                                .with_margin(Thickness::uniform(4.0)),
-                                        )
-                                        .with_text_and_font("Save Game", font.clone())
-                                        .build(ctx);
-                                        btn_save_game
-                                    })
-                                    .with_child({
-                                        btn_load_game = ButtonBuilder::new(
-                                            WidgetBuilder::new()
-                                                .on_column(0)
-                                                .on_row(2)
-                                                .with_margin(Thickness::uniform(4.0)),
-                                        )
-                                        .with_text_and_font("Load Game", font.clone())
-                                        .build(ctx);
-                                        btn_load_game
-                                    })
-                                    .with_child({
-                                        btn_settings = ButtonBuilder::new(
-                                            WidgetBuilder::new()
-                                                .on_column(0)
-                                                .on_row(3)
-                                                .with_margin(Thickness::uniform(4.0)),
-                                        )
-                                        .with_text_and_font("Settings", font.clone())
-                                        .build(ctx);
-                                        btn_settings
-                                    })
-                                    .with_child({
-                                        btn_quit_game = ButtonBuilder::new(
-                                            WidgetBuilder::new()
-                                                .on_column(0)
-                                                .on_row(4)
-                                                .with_margin(Thickness::uniform(4.0)),
-                                        )
-                                        .with_text_and_font("Quit", font.clone())
-                                        .build(ctx);
-                                        btn_quit_game
-                                    }),
-                            )
-                            .add_column(Column::stretch())
-                            .add_row(Row::strict(75.0))
-                            .add_row(Row::strict(75.0))
-                            .add_row(Row::strict(75.0))
-                            .add_row(Row::strict(75.0))
-                            .add_row(Row::strict(75.0))
-                            .build(ctx),
-                        )
-                        .build(ctx),
+                    GridBuilder::new(
+                        WidgetBuilder::new().with_child(
+                            WindowBuilder::new(WidgetBuilder::new().on_row(1).on_column(1))
+                                .can_resize(false)
+                                .can_minimize(false)
+                                .can_close(false)
+                                .with_title(WindowTitle::text("Woodnet 2.0"))
+                                .with_content(
+                                    StackPanelBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(20.0))
+                                            .with_children(buttons.iter().copied()),
+                                    )
+                                    .build(ctx),
+                                )
+                                .build(ctx),
+                        ),
+                    )
+                    .add_row(Row::stretch())
+                    .add_row(Row::strict(480.0))
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(400.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
                 ),
         )
-        .add_row(Row::stretch())
-        .add_row(Row::strict(500.0))
-        .add_row(Row::stretch())
-        .add_column(Column::stretch())
-        .add_column(Column::strict(400.0))
-        .add_column(Column::stretch())
+        .with_stroke_thickness(Thickness::uniform(0.0))
         .build(ctx);
 
         Self {
             sender: sender.clone(),
             root,
-            btn_new_game,
-            btn_settings,
-            btn_save_game,
-            btn_load_game,
-            btn_quit_game,
-            options_menu: OptionsMenu::new(engine, control_scheme, sender.clone()),
-            match_menu: MatchMenu::new(&mut engine.user_interface, sender),
+            btn_new_game: buttons[0],
+            btn_level_select: buttons[1],
+            btn_load_game: buttons[2],
+            btn_settings: buttons[3],
+            btn_quit_game: buttons[4],
+            buttons,
+            selection: 0,
+            hovered: None,
+            options_menu: OptionsMenu::new(engine, control_scheme, settings, sender.clone()),
+            match_menu: MatchMenu::new(&mut engine.user_interface, sender.clone()),
+            level_select: LevelSelect::new(&mut engine.user_interface, level_list),
+            slot_window: SlotWindow::new(&mut engine.user_interface, sender),
         }
     }
 
@@ -165,7 +162,9 @@ impl Menu {
             MessageDirection::ToWidget,
             visible,
         ));
-        if !visible {
+        if visible {
+            self.select(ui, 0);
+        } else {
             ui.send_message(WindowMessage::close(
                 self.options_menu.window,
                 MessageDirection::ToWidget,
@@ -174,14 +173,86 @@ impl Menu {
                 self.match_menu.window,
                 MessageDirection::ToWidget,
             ));
+            self.level_select.close(ui);
+            self.slot_window.close(ui);
         }
     }
 
+    /// Options are shared with the pause menu, so they can be opened while this menu is hidden.
+    pub fn show_options(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.options_menu.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn close_options(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.options_menu.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn is_options_visible(&self, ui: &UserInterface) -> bool {
+        ui.node(self.options_menu.window).visibility()
+    }
+
     pub fn is_visible(&self, ui: &UserInterface) -> bool {
         ui.node(self.root).visibility()
     }
 
-    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) {
+    /// Removes every widget of the menu and its windows, used to rebuild it with new language.
+    pub fn destroy(&self, ui: &UserInterface) {
+        self.options_menu.destroy(ui);
+        for widget in [
+            self.root,
+            self.match_menu.window,
+            self.level_select.window,
+            self.slot_window.window,
+        ] {
+            ui.send_message(WidgetMessage::remove(widget, MessageDirection::ToWidget));
+        }
+    }
+
+    fn play_sound(&self, path: &str, gain: f32) {
+        self.sender
+            .send(Message::PlayUiSound {
+                path: PathBuf::from(path),
+                gain,
+            })
+            .unwrap();
+    }
+
+    // Keyboard selection is shown the same way as mouse hover.
+    fn select(&mut self, ui: &UserInterface, selection: usize) {
+        for (i, button) in self.buttons.iter().enumerate() {
+            if let Some(button) = ui.node(*button).cast::<Button>() {
+                ui.send_message(DecoratorMessage::select(
+                    button.decorator,
+                    MessageDirection::ToWidget,
+                    i == selection,
+                ));
+            }
+        }
+        self.selection = selection;
+    }
+
+    // Mouse enter comes from the deepest widget under the cursor, which is usually a part of
+    // the button, so the button is searched up the tree.
+    fn find_button(&self, ui: &UserInterface, mut node: Handle<UiNode>) -> Option<usize> {
+        while node.is_some() {
+            if let Some(index) = self.buttons.iter().position(|button| *button == node) {
+                return Some(index);
+            }
+            node = ui.node(node).parent();
+        }
+        None
+    }
+
+    /// Returns true if the event was consumed by the menu, see
+    /// [`OptionsMenu::process_input_event`].
+    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) -> bool {
         if let Event::WindowEvent { event, .. } = event {
             if let WindowEvent::Resized(new_size) = event {
                 engine.user_interface.send_message(WidgetMessage::width(
@@ -197,33 +268,119 @@ impl Menu {
             }
         }
 
-        self.options_menu.process_input_event(engine, event);
+        if self.options_menu.process_input_event(engine, event) {
+            return true;
+        }
+
+        let ui = &mut engine.user_interface;
+        if !self.is_visible(ui) || self.is_options_visible(ui) {
+            return false;
+        }
+
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { input, .. },
+            ..
+        } = event
+        {
+            let key = match input.virtual_keycode {
+                Some(key) if input.state == ElementState::Pressed => key,
+                _ => return false,
+            };
+
+            let match_menu_open = ui.node(self.match_menu.window).visibility();
+            if match_menu_open || self.level_select.is_open(ui) || self.slot_window.is_open(ui) {
+                // Escape closes the window on top, keys are typed into the windows otherwise.
+                if key == VirtualKeyCode::Escape {
+                    if match_menu_open {
+                        ui.send_message(WindowMessage::close(
+                            self.match_menu.window,
+                            MessageDirection::ToWidget,
+                        ));
+                    } else if self.level_select.is_open(ui) {
+                        self.level_select.close(ui);
+                    } else {
+                        self.slot_window.close(ui);
+                    }
+                    return true;
+                }
+                return false;
+            }
+
+            match key {
+                VirtualKeyCode::Up => {
+                    let count = self.buttons.len();
+                    self.select(ui, (self.selection + count - 1) % count);
+                    self.play_sound(HOVER_SOUND, HOVER_SOUND_GAIN);
+                    return true;
+                }
+                VirtualKeyCode::Down => {
+                    self.select(ui, (self.selection + 1) % self.buttons.len());
+                    self.play_sound(HOVER_SOUND, HOVER_SOUND_GAIN);
+                    return true;
+                }
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                    // Behaves exactly like a click.
+                    ui.send_message(ButtonMessage::click(
+                        self.buttons[self.selection],
+                        MessageDirection::FromWidget,
+                    ));
+                    return true;
+                }
+                _ => (),
+            }
+        }
+
+        false
+    }
+
+    pub fn process_gamepad_event(&mut self, engine: &mut Engine, event: &GamepadEvent) {
+        self.options_menu.process_gamepad_event(engine, event);
     }
 
     pub fn handle_ui_event(&mut self, engine: &mut Engine, message: &UiMessage) {
-        if let Some(ButtonMessage::Click) = message.data() {
+        if let Some(WidgetMessage::MouseEnter) = message.data() {
+            let hovered = self.find_button(&engine.user_interface, message.destination());
+            if hovered != self.hovered {
+                self.hovered = hovered;
+                if let Some(index) = hovered {
+                    self.select(&engine.user_interface, index);
+                    self.play_sound(HOVER_SOUND, HOVER_SOUND_GAIN);
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if self.buttons.contains(&message.destination()) {
+                self.play_sound(CLICK_SOUND, CLICK_SOUND_GAIN);
+            }
+
             if message.destination() == self.btn_new_game {
-                engine.user_interface.send_message(WindowMessage::open(
-                    self.match_menu.window,
-                    MessageDirection::ToWidget,
-                    true,
-                ));
-            } else if message.destination() == self.btn_save_game {
-                self.sender.send(Message::SaveGame).unwrap();
+                // New game always begins from the first level of the sequence.
+                self.sender
+                    .send(Message::StartNewGame {
+                        level: 0,
+                        options: default_match_options(),
+                    })
+                    .unwrap();
+            } else if message.destination() == self.btn_level_select {
+                self.level_select.open(&engine.user_interface);
             } else if message.destination() == self.btn_load_game {
-                self.sender.send(Message::LoadGame).unwrap();
+                self.slot_window
+                    .open(&mut engine.user_interface, SlotWindowMode::Load);
             } else if message.destination() == self.btn_quit_game {
                 self.sender.send(Message::QuitGame).unwrap();
             } else if message.destination() == self.btn_settings {
-                engine.user_interface.send_message(WindowMessage::open(
-                    self.options_menu.window,
-                    MessageDirection::ToWidget,
-                    true,
-                ));
+                self.show_options(&engine.user_interface);
             }
         }
 
+        if let Some(level) = self.level_select.handle_ui_event(message) {
+            self.play_sound(CLICK_SOUND, CLICK_SOUND_GAIN);
+            self.level_select.close(&engine.user_interface);
+            self.match_menu.open(&engine.user_interface, level);
+        }
+
         self.options_menu.handle_ui_event(engine, message);
         self.match_menu.handle_ui_event(engine, message);
+        self.slot_window
+            .handle_ui_event(&mut engine.user_interface, message);
     }
 }
@@ -12,10 +12,15 @@
 use crate::{
     actor::Actor,
     bot::BotKind,
+    character::Team,
     effects::EffectKind,
     item::{Item, ItemKind},
     projectile::ProjectileKind,
-    weapon::{Weapon, WeaponKind},
+    settings::SoundCategory,
+    toast::NotificationPriority,
+    tunables::DefinitionKind,
+    usable::Usable,
+    weapon::{MeleeSweep, Weapon, WeaponKind},
     MatchOptions,
 };
 use fyrox::core::{
@@ -24,6 +29,16 @@ use fyrox::core::{
 };
 use std::path::PathBuf;
 
+/// Where a shot or a blow landed on the damaged actor.
+#[derive(Copy, Clone, Debug)]
+pub struct DamageHit {
+    pub position: Vector3<f32>,
+    /// Normalized direction in which the damage travelled.
+    pub direction: Vector3<f32>,
+    /// Normal of the surface of the actor at the hit point.
+    pub normal: Vector3<f32>,
+}
+
 #[derive(Debug)]
 pub enum Message {
     GiveNewWeapon {
@@ -38,12 +53,18 @@ pub enum Message {
     RemoveActor {
         actor: Handle<Actor>,
     },
+    /// Turns the weapon into an item at the feet of its owner, the item keeps its ammo.
+    DropWeapon {
+        weapon: Handle<Weapon>,
+    },
     /// Spawns new bot at random spawn point. Selection of spawn point can be based on some
     /// particular heuristic, leading to good selection (like do spawn at a point with least
     /// enemies nearby, which will increase survival probability)
     SpawnBot {
         kind: BotKind,
         name: String,
+        /// `None` puts the bot on the team picked by the match.
+        team: Option<Team>,
     },
     /// Gives item of specified kind to a given actor. Basically it means that actor will take
     /// item and consume it immediately (heal itself, add ammo, etc.)
@@ -71,17 +92,36 @@ pub enum Message {
         owner: Handle<Weapon>,
         basis: Matrix3<f32>,
     },
+    /// Projectiles inherit velocity of the owner of the weapon.
     ShootWeapon {
         weapon: Handle<Weapon>,
-        initial_velocity: Vector3<f32>,
         direction: Option<Vector3<f32>>,
     },
+    /// Next shot of a burst, the weapon sends it itself. Unlike `ShootWeapon` it isn't held
+    /// back by the trigger or the shoot interval.
+    ShootBurst {
+        weapon: Handle<Weapon>,
+    },
+    /// See `Weapon::set_aiming`.
+    SetAiming {
+        weapon: Handle<Weapon>,
+        aiming: bool,
+    },
+    SetLaserEnabled {
+        weapon: Handle<Weapon>,
+        enabled: bool,
+    },
+    /// Owner of the weapon stopped shooting, see `Weapon::release_trigger`.
+    ReleaseTrigger {
+        weapon: Handle<Weapon>,
+    },
     PlaySound {
         path: PathBuf,
         position: Vector3<f32>,
         gain: f32,
         rolloff_factor: f32,
         radius: f32,
+        category: SoundCategory,
     },
     ShowWeapon {
         weapon: Handle<Weapon>,
@@ -93,30 +133,126 @@ pub enum Message {
         /// or not from any actor.
         who: Handle<Actor>,
         amount: f32,
+        /// `None` for damage that hits the whole body, like explosions or falls.
+        hit: Option<DamageHit>,
     },
     CreateEffect {
         kind: EffectKind,
         position: Vector3<f32>,
+        /// Up axis of the effect, particles spray along it. Effects on the ground use
+        /// `Vector3::y()`.
+        direction: Vector3<f32>,
     },
     SpawnPlayer,
-    /// HUD listens such events and puts them into queue.
-    AddNotification {
+    /// Shows a toast on the HUD, see `toast` module.
+    Notify {
         text: String,
+        icon: Option<PathBuf>,
+        priority: NotificationPriority,
+    },
+    /// Shows or hides health bar of given actor on the HUD. Boss bots send it when they
+    /// engage the player, level scripts may use it for any actor.
+    SetBossBar {
+        actor: Handle<Actor>,
+        visible: bool,
+    },
+    /// Shows a closed caption on the HUD, see `captions` module.
+    ShowCaption {
+        text: String,
+        duration: f32,
+    },
+    /// Bot has selected new target, used only for debugging purposes.
+    TargetAcquired {
+        bot: Handle<Actor>,
+        target: Handle<Actor>,
+    },
+    /// Bot has lost its target, used only for debugging purposes.
+    TargetLost {
+        bot: Handle<Actor>,
+    },
+    /// Actor has used a door or a button.
+    UseObject {
+        actor: Handle<Actor>,
+        usable: Handle<Usable>,
+    },
+    /// Damage dealt by the player has landed, used by HUD to show hit markers and damage
+    /// numbers.
+    DamageDealtFeedback {
+        target: Handle<Actor>,
+        /// Point above the target where damage number is shown.
+        position: Vector3<f32>,
+        amount: f32,
+        killed: bool,
+        headshot: bool,
+        /// Damage was taken by armor.
+        resisted: bool,
+        show_hit_marker: bool,
+        show_damage_number: bool,
+    },
+    /// Actor has died, used by HUD to show the kill feed.
+    ActorKilled {
+        /// Name and team of the killer, `None` for suicides and deaths with no killer.
+        killer: Option<(String, Team)>,
+        victim: String,
+        victim_team: Team,
+        /// Weapon that killer was holding at the moment of kill.
+        weapon: Option<WeaponKind>,
+        headshot: bool,
+        /// Local player is the killer or the victim.
+        involves_player: bool,
+    },
+    /// Damages and pushes every actor in front of the attacker, see `MeleeSweep`.
+    MeleeAttack {
+        actor: Handle<Actor>,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        sweep: MeleeSweep,
     },
     /// Removes specified actor and creates new one at random spawn point.
     RespawnActor {
         actor: Handle<Actor>,
     },
-    /// Save game state to a file. TODO: Add filename field.
-    SaveGame,
-    /// Loads game state from a file. TODO: Add filename field.
-    LoadGame,
+    /// Save game state to given save slot, see `save_slot` module.
+    SaveGame {
+        slot: usize,
+        name: String,
+    },
+    /// Loads game state from given save slot.
+    LoadGame {
+        slot: usize,
+    },
+    /// Level that is being loaded has preloaded one more of its resources, see `preload`
+    /// module.
+    PreloadProgress {
+        done: usize,
+        total: usize,
+    },
+    /// Starts a match on given level of the level list.
     StartNewGame {
+        level: usize,
         options: MatchOptions,
     },
+    /// Closes the pause menu and continues simulation of the level.
+    ResumeGame,
+    ShowOptions,
+    /// Starts the same match from scratch.
+    RestartLevel,
+    /// Destroys current level and shows the main menu.
+    QuitToMenu,
     QuitGame,
+    /// Plays a non-positional sound of the user interface, it is heard in menus too.
+    PlayUiSound {
+        path: PathBuf,
+        gain: f32,
+    },
     SetMusicVolume {
         volume: f32,
     },
+    /// UI language was changed in the options, menus are rebuilt with new strings.
+    LanguageChanged,
+    /// Definition file was changed on disk and re-read, see `tunables` module.
+    DefinitionsReloaded {
+        kind: DefinitionKind,
+    },
     EndMatch,
 }
@@ -0,0 +1,168 @@
+//! Music of a level. Ambient track of the level loops until some bot targets the player, then
+//! music crossfades to the combat track and stays there until nobody has targeted the player
+//! for [`CALM_DOWN_TIME`]. Tracks are set per level in the level list, a level without tracks
+//! (or with missing files) is silent.
+
+use crate::campaign::LevelDefinition;
+use fyrox::{
+    core::{
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    engine::resource_manager::ResourceManager,
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        node::Node,
+        sound::{SoundBuilder, Status},
+    },
+    utils::log::{Log, MessageKind},
+};
+use std::path::Path;
+
+/// Name of every music node, level sound manager leaves these sounds alone since their gain
+/// is driven by the crossfade.
+pub const MUSIC_NODE_NAME: &str = "__Music";
+/// Music stays in combat for this many seconds after the last bot has lost the player.
+pub const CALM_DOWN_TIME: f32 = 8.0;
+/// Duration of a crossfade between ambient and combat tracks.
+const FADE_DURATION: f32 = 2.0;
+
+#[derive(Default, Visit)]
+pub struct MusicManager {
+    ambient: Handle<Node>,
+    combat: Handle<Node>,
+    stinger: Handle<Node>,
+    /// 0.0 is ambient only, 1.0 is combat only.
+    blend: f32,
+    in_combat: bool,
+    // Time since the last bot lost the player.
+    calm_time: f32,
+}
+
+async fn create_track(
+    graph: &mut Graph,
+    resource_manager: &ResourceManager,
+    path: Option<&Path>,
+    looping: bool,
+) -> Handle<Node> {
+    let path = match path {
+        Some(path) => path,
+        None => return Handle::NONE,
+    };
+    match resource_manager.request_sound_buffer(path).await {
+        Ok(buffer) => SoundBuilder::new(BaseBuilder::new().with_name(MUSIC_NODE_NAME))
+            .with_buffer(Some(buffer))
+            .with_looping(looping)
+            .with_status(if looping {
+                Status::Playing
+            } else {
+                Status::Stopped
+            })
+            // Music is heard the same everywhere on the level.
+            .with_spatial_blend_factor(0.0)
+            .with_gain(0.0)
+            .build(graph),
+        Err(_) => {
+            Log::writeln(
+                MessageKind::Error,
+                format!(
+                    "Unable to load music {}, it will be silent.",
+                    path.display()
+                ),
+            );
+            Handle::NONE
+        }
+    }
+}
+
+impl MusicManager {
+    pub async fn new(
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        definition: &LevelDefinition,
+    ) -> Self {
+        Self {
+            ambient: create_track(
+                graph,
+                &resource_manager,
+                definition.ambient_music.as_deref(),
+                true,
+            )
+            .await,
+            combat: create_track(
+                graph,
+                &resource_manager,
+                definition.combat_music.as_deref(),
+                true,
+            )
+            .await,
+            stinger: create_track(
+                graph,
+                &resource_manager,
+                definition.combat_stinger.as_deref(),
+                false,
+            )
+            .await,
+            ..Default::default()
+        }
+    }
+
+    /// Follows the combat state of the level, `player_targeted` tells if any bot has the
+    /// player as its target right now. `volume` is the volume of music category.
+    pub fn update(&mut self, graph: &mut Graph, player_targeted: bool, volume: f32, dt: f32) {
+        if player_targeted {
+            if !self.in_combat {
+                self.in_combat = true;
+                self.play_stinger(graph, volume);
+            }
+            self.calm_time = 0.0;
+        } else if self.in_combat {
+            self.calm_time += dt;
+            if self.calm_time >= CALM_DOWN_TIME {
+                self.in_combat = false;
+            }
+        }
+
+        // Without combat track ambient keeps playing through the fight.
+        let target = if self.in_combat && graph.is_valid_handle(self.combat) {
+            1.0
+        } else {
+            0.0
+        };
+        let step = dt / FADE_DURATION;
+        if self.blend < target {
+            self.blend = (self.blend + step).min(target);
+        } else {
+            self.blend = (self.blend - step).max(target);
+        }
+
+        for (track, gain) in [
+            (self.ambient, 1.0 - self.blend),
+            (self.combat, self.blend),
+            (self.stinger, 1.0),
+        ] {
+            if let Some(node) = graph.try_get_mut(track) {
+                node.as_sound_mut().set_gain(gain * volume);
+            }
+        }
+    }
+
+    fn play_stinger(&self, graph: &mut Graph, volume: f32) {
+        if let Some(node) = graph.try_get_mut(self.stinger) {
+            let stinger = node.as_sound_mut();
+            stinger.set_gain(volume);
+            stinger.stop();
+            stinger.play();
+        }
+    }
+
+    /// Silences every track, level music must not be heard once the level is gone.
+    pub fn stop(&self, graph: &mut Graph) {
+        for track in [self.ambient, self.combat, self.stinger] {
+            if let Some(node) = graph.try_get_mut(track) {
+                node.as_sound_mut().stop();
+            }
+        }
+    }
+}
@@ -1,26 +1,35 @@
 use crate::{
-    control_scheme::{ControlButton, ControlScheme},
-    gui::{create_check_box, create_scroll_bar, create_scroll_viewer, ScrollBarData},
+    control_scheme::{
+        ActivationMode, ControlButton, ControlScheme, MouseCurve, CONTROL_SCHEME_PATH,
+    },
+    gamepad::GamepadEvent,
+    gui::{
+        create_button, create_check_box, create_confirmation_dialog, create_dropdown,
+        create_labeled_slider, create_scroll_viewer, DialogHandles, DialogResult, ScrollBarData,
+    },
+    localization::{self, tr},
     message::Message,
+    settings::{Difficulty, EffectQuality, Settings, SoundCategory, SETTINGS_PATH},
 };
 use fyrox::{
     core::pool::Handle,
     engine::Engine,
-    event::{Event, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent},
     gui::{
-        border::BorderBuilder,
-        button::{Button, ButtonBuilder, ButtonMessage},
+        button::{Button, ButtonMessage},
         check_box::CheckBoxMessage,
-        decorator::DecoratorBuilder,
+        dropdown_list::DropdownListMessage,
+        expander::ExpanderBuilder,
         grid::{Column, GridBuilder, Row},
-        list_view::{ListViewBuilder, ListViewMessage},
         message::{MessageDirection, UiMessage},
         scroll_bar::ScrollBarMessage,
+        stack_panel::StackPanelBuilder,
         tab_control::{TabControlBuilder, TabDefinition},
         text::{TextBuilder, TextMessage},
-        widget::WidgetBuilder,
-        window::{WindowBuilder, WindowTitle},
-        HorizontalAlignment, Orientation, Thickness, UiNode, VerticalAlignment,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
     },
     monitor::VideoMode,
     utils::log::{Log, MessageKind},
@@ -28,12 +37,89 @@ use fyrox::{
 };
 use std::sync::{mpsc::Sender, Arc, RwLock};
 
+/// Binding of an action that can be changed in the controls tab.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum BindingSlot {
+    Primary,
+    Alternative,
+    Gamepad,
+}
+
+/// Binding that waits for the next key or button press.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct BindingCapture {
+    action: usize,
+    slot: BindingSlot,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum CaptureResult {
+    Bind(ControlButton),
+    Unbind,
+    Cancel,
+    /// Event can't be used for the binding, but capture must still consume it.
+    Ignore,
+}
+
+impl BindingCapture {
+    /// Decides what to do with a window event during capture, returns `None` for events that
+    /// aren't input at all (resize, cursor movement, etc.), such events must not be consumed.
+    fn process_event(&self, event: &WindowEvent) -> Option<CaptureResult> {
+        let button = match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                if input.state != ElementState::Pressed {
+                    return Some(CaptureResult::Ignore);
+                }
+                match input.virtual_keycode {
+                    // Escape is reserved for the menu, so it can't be bound.
+                    Some(VirtualKeyCode::Escape) => return Some(CaptureResult::Cancel),
+                    Some(VirtualKeyCode::Delete) | Some(VirtualKeyCode::Back) => {
+                        return Some(CaptureResult::Unbind)
+                    }
+                    Some(code) => ControlButton::Key(code),
+                    None => return Some(CaptureResult::Ignore),
+                }
+            }
+            WindowEvent::MouseInput { button, state, .. } => {
+                if *state != ElementState::Pressed {
+                    return Some(CaptureResult::Ignore);
+                }
+                ControlButton::Mouse(match button {
+                    MouseButton::Left => 1,
+                    MouseButton::Right => 2,
+                    MouseButton::Middle => 3,
+                    MouseButton::Other(i) => *i,
+                })
+            }
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(_, y) if *y > 0.0 => ControlButton::WheelUp,
+                MouseScrollDelta::LineDelta(_, y) if *y < 0.0 => ControlButton::WheelDown,
+                _ => return Some(CaptureResult::Ignore),
+            },
+            _ => return None,
+        };
+
+        // Gamepad bindings are captured from gamepad events, keyboard and mouse can only
+        // cancel or unbind them.
+        if self.slot == BindingSlot::Gamepad {
+            Some(CaptureResult::Ignore)
+        } else {
+            Some(CaptureResult::Bind(button))
+        }
+    }
+}
+
 pub struct OptionsMenu {
     pub window: Handle<UiNode>,
     sender: Sender<Message>,
-    sb_sound_volume: Handle<UiNode>,
-    pub sb_music_volume: Handle<UiNode>,
-    lb_video_modes: Handle<UiNode>,
+    sb_master_volume: Handle<UiNode>,
+    sb_sfx_volume: Handle<UiNode>,
+    sb_music_volume: Handle<UiNode>,
+    sb_ui_volume: Handle<UiNode>,
+    sb_voice_volume: Handle<UiNode>,
+    cb_mute_on_focus_loss: Handle<UiNode>,
+    cb_sound_occlusion: Handle<UiNode>,
+    dd_video_mode: Handle<UiNode>,
     cb_fullscreen: Handle<UiNode>,
     cb_spot_shadows: Handle<UiNode>,
     cb_soft_spot_shadows: Handle<UiNode>,
@@ -45,20 +131,55 @@ pub struct OptionsMenu {
     video_modes: Vec<VideoMode>,
     control_scheme: Arc<RwLock<ControlScheme>>,
     control_scheme_buttons: Vec<Handle<UiNode>>,
-    active_control_button: Option<usize>,
+    control_scheme_alt_buttons: Vec<Handle<UiNode>>,
+    control_scheme_alt_clear_buttons: Vec<Handle<UiNode>>,
+    control_scheme_gamepad_buttons: Vec<Handle<UiNode>>,
+    capture: Option<BindingCapture>,
+    // Pairs of action index and drop-down list with its activation mode.
+    activation_mode_lists: Vec<(usize, Handle<UiNode>)>,
+    settings: Arc<RwLock<Settings>>,
+    // Changes are applied live, but saved to disk only by "Apply". Closing the window with
+    // unapplied changes reverts everything to the saved state.
+    control_scheme_changed: bool,
+    settings_changed: bool,
     sb_mouse_sens: Handle<UiNode>,
+    sb_mouse_sens_x: Handle<UiNode>,
+    sb_mouse_sens_y: Handle<UiNode>,
+    dd_mouse_curve: Handle<UiNode>,
+    sb_mouse_curve_exponent: Handle<UiNode>,
     cb_mouse_y_inverse: Handle<UiNode>,
     cb_smooth_mouse: Handle<UiNode>,
     cb_shake_camera: Handle<UiNode>,
+    cb_manual_pickup: Handle<UiNode>,
+    cb_double_tap_dash: Handle<UiNode>,
+    cb_show_crosshair: Handle<UiNode>,
+    cb_show_hit_markers: Handle<UiNode>,
+    cb_show_damage_indicators: Handle<UiNode>,
+    cb_show_damage_numbers: Handle<UiNode>,
     btn_reset_control_scheme: Handle<UiNode>,
     cb_use_hrtf: Handle<UiNode>,
     btn_reset_audio_settings: Handle<UiNode>,
+    dd_difficulty: Handle<UiNode>,
+    dd_effect_quality: Handle<UiNode>,
+    sb_fov: Handle<UiNode>,
+    cb_captions: Handle<UiNode>,
+    sb_caption_size: Handle<UiNode>,
+    cb_radar: Handle<UiNode>,
+    sb_radar_size: Handle<UiNode>,
+    cb_reduce_violence: Handle<UiNode>,
+    dd_language: Handle<UiNode>,
+    // Codes and names of languages in the order of the drop-down list.
+    languages: Vec<(String, String)>,
+    btn_apply: Handle<UiNode>,
+    btn_defaults: Handle<UiNode>,
+    discard_dialog: DialogHandles,
 }
 
 impl OptionsMenu {
     pub fn new(
         engine: &mut Engine,
         control_scheme: Arc<RwLock<ControlScheme>>,
+        settings: Arc<RwLock<Settings>>,
         sender: Sender<Message>,
     ) -> Self {
         let video_modes: Vec<VideoMode> = engine
@@ -69,17 +190,24 @@ impl OptionsMenu {
             .filter(|vm| vm.size().width > 800 && vm.size().height > 600 && vm.bit_depth() == 32)
             .collect();
 
+        let languages = localization::available_languages();
+
         let ctx = &mut engine.user_interface.build_ctx();
 
         let common_row = Row::strict(36.0);
 
-        let settings = engine.renderer.get_quality_settings();
+        let quality = engine.renderer.get_quality_settings();
 
         let margin = Thickness::uniform(2.0);
 
-        let sb_sound_volume;
+        let sb_master_volume;
+        let sb_sfx_volume;
         let sb_music_volume;
-        let lb_video_modes;
+        let sb_ui_volume;
+        let sb_voice_volume;
+        let cb_mute_on_focus_loss;
+        let cb_sound_occlusion;
+        let dd_video_mode;
         let cb_fullscreen;
         let cb_spot_shadows;
         let cb_soft_spot_shadows;
@@ -88,19 +216,44 @@ impl OptionsMenu {
         let sb_point_shadow_distance;
         let sb_spot_shadow_distance;
         let sb_mouse_sens;
+        let sb_mouse_sens_x;
+        let sb_mouse_sens_y;
+        let dd_mouse_curve;
+        let sb_mouse_curve_exponent;
         let cb_mouse_y_inverse;
         let cb_smooth_mouse;
         let cb_shake_camera;
+        let cb_manual_pickup;
+        let cb_double_tap_dash;
+        let cb_show_crosshair;
+        let cb_show_hit_markers;
+        let cb_show_damage_indicators;
+        let cb_show_damage_numbers;
         let btn_reset_control_scheme;
         let mut control_scheme_buttons = Vec::new();
+        let mut control_scheme_alt_buttons = Vec::new();
+        let mut control_scheme_alt_clear_buttons = Vec::new();
+        let mut control_scheme_gamepad_buttons = Vec::new();
+        let mut activation_mode_lists = Vec::new();
         let cb_use_hrtf;
         let btn_reset_audio_settings;
         let cb_use_light_scatter;
+        let dd_difficulty;
+        let dd_effect_quality;
+        let sb_fov;
+        let cb_captions;
+        let sb_caption_size;
+        let cb_radar;
+        let sb_radar_size;
+        let cb_reduce_violence;
+        let dd_language;
+        let btn_apply;
+        let btn_defaults;
         let tab_control = TabControlBuilder::new(WidgetBuilder::new())
             .with_tab(TabDefinition {
                 header: {
                     TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
-                        .with_text("Graphics")
+                        .with_text(&tr("options.tab.graphics"))
                         .build(ctx)
                 },
                 content: {
@@ -114,62 +267,30 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Resolution")
+                                .with_text(&tr("options.resolution"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
-                                lb_video_modes = ListViewBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_column(1)
-                                        .on_row(0)
-                                        .with_margin(margin),
-                                )
-                                .with_scroll_viewer(create_scroll_viewer(ctx))
-                                .with_items({
-                                    let mut items = Vec::new();
-                                    for video_mode in video_modes.iter() {
+                                // First item is borderless fullscreen on desktop resolution.
+                                let items = std::iter::once(tr("options.desktop"))
+                                    .chain(video_modes.iter().map(|video_mode| {
                                         let size = video_mode.size();
                                         let rate = video_mode.refresh_rate_millihertz() / 1000;
-                                        let item = DecoratorBuilder::new(
-                                            BorderBuilder::new(
-                                                WidgetBuilder::new().with_child(
-                                                    TextBuilder::new(
-                                                        WidgetBuilder::new()
-                                                            .on_column(0)
-                                                            .with_height(25.0)
-                                                            .with_width(200.0),
-                                                    )
-                                                    .with_text(
-                                                        format!(
-                                                            "{} x {} @ {}Hz",
-                                                            size.width, size.height, rate
-                                                        )
-                                                        .as_str(),
-                                                    )
-                                                    .with_vertical_text_alignment(
-                                                        VerticalAlignment::Center,
-                                                    )
-                                                    .with_horizontal_text_alignment(
-                                                        HorizontalAlignment::Center,
-                                                    )
-                                                    .build(ctx),
-                                                ),
-                                            )
-                                            .with_stroke_thickness(Thickness {
-                                                left: 1.0,
-                                                top: 0.0,
-                                                right: 1.0,
-                                                bottom: 1.0,
-                                            }),
-                                        )
-                                        .build(ctx);
-                                        items.push(item)
-                                    }
-                                    items
-                                })
-                                .build(ctx);
-                                lb_video_modes
+                                        format!("{} x {} @ {}Hz", size.width, size.height, rate)
+                                    }))
+                                    .collect::<Vec<_>>();
+                                dd_video_mode = create_dropdown(
+                                    ctx,
+                                    0,
+                                    1,
+                                    &items,
+                                    video_mode_index(
+                                        &video_modes,
+                                        settings.read().unwrap().video_mode,
+                                    ),
+                                );
+                                dd_video_mode
                             })
                             .with_child(
                                 TextBuilder::new(
@@ -178,12 +299,17 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Fullscreen")
+                                .with_text(&tr("options.fullscreen"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
-                                cb_fullscreen = create_check_box(ctx, 1, 1, false);
+                                cb_fullscreen = create_check_box(
+                                    ctx,
+                                    1,
+                                    1,
+                                    settings.read().unwrap().fullscreen,
+                                );
                                 cb_fullscreen
                             })
                             // Spot Shadows Enabled
@@ -194,13 +320,13 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Spot Shadows")
+                                .with_text(&tr("options.spot_shadows"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
                                 cb_spot_shadows =
-                                    create_check_box(ctx, 2, 1, settings.spot_shadows_enabled);
+                                    create_check_box(ctx, 2, 1, quality.spot_shadows_enabled);
                                 cb_spot_shadows
                             })
                             // Soft Spot Shadows
@@ -211,43 +337,34 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Soft Spot Shadows")
+                                .with_text(&tr("options.soft_spot_shadows"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
                                 cb_soft_spot_shadows =
-                                    create_check_box(ctx, 3, 1, settings.spot_soft_shadows);
+                                    create_check_box(ctx, 3, 1, quality.spot_soft_shadows);
                                 cb_soft_spot_shadows
                             })
                             // Spot Shadows Distance
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_row(4)
-                                        .on_column(0)
-                                        .with_margin(margin),
-                                )
-                                .with_text("Spot Shadows Distance")
-                                .with_vertical_text_alignment(VerticalAlignment::Center)
-                                .build(ctx),
-                            )
-                            .with_child({
-                                sb_spot_shadow_distance = create_scroll_bar(
+                            .with_children({
+                                let slider = create_labeled_slider(
                                     ctx,
+                                    &tr("options.spot_shadows_distance"),
                                     ScrollBarData {
                                         min: 1.0,
                                         max: 15.0,
-                                        value: settings.spot_shadows_distance,
+                                        value: quality.spot_shadows_distance,
                                         step: 0.25,
                                         row: 4,
-                                        column: 1,
+                                        column: 0,
                                         margin,
                                         show_value: true,
                                         orientation: Orientation::Horizontal,
                                     },
                                 );
-                                sb_spot_shadow_distance
+                                sb_spot_shadow_distance = slider.scroll_bar;
+                                slider.widgets()
                             })
                             // Point Shadows Enabled
                             .with_child(
@@ -257,13 +374,13 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Point Shadows")
+                                .with_text(&tr("options.point_shadows"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
                                 cb_point_shadows =
-                                    create_check_box(ctx, 5, 1, settings.point_shadows_enabled);
+                                    create_check_box(ctx, 5, 1, quality.point_shadows_enabled);
                                 cb_point_shadows
                             })
                             // Soft Point Shadows
@@ -274,43 +391,34 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Soft Point Shadows")
+                                .with_text(&tr("options.soft_point_shadows"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
                                 cb_soft_point_shadows =
-                                    create_check_box(ctx, 6, 1, settings.point_soft_shadows);
+                                    create_check_box(ctx, 6, 1, quality.point_soft_shadows);
                                 cb_soft_point_shadows
                             })
                             // Point Shadows Distance
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_row(7)
-                                        .on_column(0)
-                                        .with_margin(margin),
-                                )
-                                .with_text("Point Shadows Distance")
-                                .with_vertical_text_alignment(VerticalAlignment::Center)
-                                .build(ctx),
-                            )
-                            .with_child({
-                                sb_point_shadow_distance = create_scroll_bar(
+                            .with_children({
+                                let slider = create_labeled_slider(
                                     ctx,
+                                    &tr("options.point_shadows_distance"),
                                     ScrollBarData {
                                         min: 1.0,
                                         max: 15.0,
-                                        value: settings.point_shadows_distance,
+                                        value: quality.point_shadows_distance,
                                         step: 0.25,
                                         row: 7,
-                                        column: 1,
+                                        column: 0,
                                         margin,
                                         show_value: true,
                                         orientation: Orientation::Horizontal,
                                     },
                                 );
-                                sb_point_shadow_distance
+                                sb_point_shadow_distance = slider.scroll_bar;
+                                slider.widgets()
                             })
                             .with_child(
                                 TextBuilder::new(
@@ -319,14 +427,38 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Use Light Scatter")
+                                .with_text(&tr("options.use_light_scatter"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
                                 cb_use_light_scatter =
-                                    create_check_box(ctx, 8, 1, settings.light_scatter_enabled);
+                                    create_check_box(ctx, 8, 1, quality.light_scatter_enabled);
                                 cb_use_light_scatter
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(9)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.effect_quality"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                dd_effect_quality = create_dropdown(
+                                    ctx,
+                                    9,
+                                    1,
+                                    &EffectQuality::ALL
+                                        .iter()
+                                        .map(|quality| quality.display_name())
+                                        .collect::<Vec<_>>(),
+                                    effect_quality_index(settings.read().unwrap().effect_quality),
+                                );
+                                dd_effect_quality
                             }),
                     )
                     .add_row(Row::strict(200.0))
@@ -338,6 +470,7 @@ impl OptionsMenu {
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
+                    .add_row(common_row)
                     .add_column(Column::strict(250.0))
                     .add_column(Column::stretch())
                     .build(ctx)
@@ -346,89 +479,168 @@ impl OptionsMenu {
             .with_tab(TabDefinition {
                 header: {
                     TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
-                        .with_text("Sound")
+                        .with_text(&tr("options.tab.sound"))
                         .build(ctx)
                 },
                 content: {
                     GridBuilder::new(
                         WidgetBuilder::new()
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_row(0)
-                                        .on_column(0)
-                                        .with_margin(margin),
-                                )
-                                .with_text("Sound Volume")
-                                .with_vertical_text_alignment(VerticalAlignment::Center)
-                                .build(ctx),
-                            )
-                            .with_child({
-                                sb_sound_volume = create_scroll_bar(
+                            .with_children({
+                                let slider = create_labeled_slider(
                                     ctx,
+                                    &tr("options.master_volume"),
                                     ScrollBarData {
                                         min: 0.0,
                                         max: 1.0,
-                                        value: 1.0,
+                                        value: settings.read().unwrap().master_volume,
                                         step: 0.025,
                                         row: 0,
-                                        column: 1,
+                                        column: 0,
                                         margin,
                                         show_value: true,
                                         orientation: Orientation::Horizontal,
                                     },
                                 );
-                                sb_sound_volume
+                                sb_master_volume = slider.scroll_bar;
+                                slider.widgets()
                             })
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_row(1)
-                                        .on_column(0)
-                                        .with_margin(margin),
-                                )
-                                .with_text("Music Volume")
-                                .with_vertical_text_alignment(VerticalAlignment::Center)
-                                .build(ctx),
-                            )
-                            .with_child({
-                                sb_music_volume = create_scroll_bar(
+                            .with_children({
+                                let slider = create_labeled_slider(
                                     ctx,
+                                    &tr("options.sound_effects_volume"),
                                     ScrollBarData {
                                         min: 0.0,
                                         max: 1.0,
-                                        value: 0.0,
+                                        value: settings.read().unwrap().sfx_volume,
                                         step: 0.025,
                                         row: 1,
-                                        column: 1,
+                                        column: 0,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_sfx_volume = slider.scroll_bar;
+                                slider.widgets()
+                            })
+                            .with_children({
+                                let slider = create_labeled_slider(
+                                    ctx,
+                                    &tr("options.music_volume"),
+                                    ScrollBarData {
+                                        min: 0.0,
+                                        max: 1.0,
+                                        value: settings.read().unwrap().music_volume,
+                                        step: 0.025,
+                                        row: 2,
+                                        column: 0,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_music_volume = slider.scroll_bar;
+                                slider.widgets()
+                            })
+                            .with_children({
+                                let slider = create_labeled_slider(
+                                    ctx,
+                                    &tr("options.ui_volume"),
+                                    ScrollBarData {
+                                        min: 0.0,
+                                        max: 1.0,
+                                        value: settings.read().unwrap().ui_volume,
+                                        step: 0.025,
+                                        row: 3,
+                                        column: 0,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_ui_volume = slider.scroll_bar;
+                                slider.widgets()
+                            })
+                            .with_children({
+                                let slider = create_labeled_slider(
+                                    ctx,
+                                    &tr("options.voice_volume"),
+                                    ScrollBarData {
+                                        min: 0.0,
+                                        max: 1.0,
+                                        value: settings.read().unwrap().voice_volume,
+                                        step: 0.025,
+                                        row: 4,
+                                        column: 0,
                                         margin,
                                         show_value: true,
                                         orientation: Orientation::Horizontal,
                                     },
                                 );
-                                sb_music_volume
+                                sb_voice_volume = slider.scroll_bar;
+                                slider.widgets()
                             })
                             .with_child(
                                 TextBuilder::new(
                                     WidgetBuilder::new()
-                                        .on_row(2)
+                                        .on_row(5)
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Use HRTF")
+                                .with_text(&tr("options.use_hrtf"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
                             .with_child({
-                                cb_use_hrtf = create_check_box(ctx, 2, 1, true);
+                                cb_use_hrtf = create_check_box(ctx, 5, 1, true);
                                 cb_use_hrtf
                             })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(6)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.mute_when_unfocused"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
                             .with_child({
-                                btn_reset_audio_settings = ButtonBuilder::new(
-                                    WidgetBuilder::new().on_row(3).with_margin(margin),
+                                cb_mute_on_focus_loss = create_check_box(
+                                    ctx,
+                                    6,
+                                    1,
+                                    settings.read().unwrap().mute_on_focus_loss,
+                                );
+                                cb_mute_on_focus_loss
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(7)
+                                        .on_column(0)
+                                        .with_margin(margin),
                                 )
-                                .with_text("Reset")
-                                .build(ctx);
+                                .with_text(&tr("options.sound_occlusion"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_sound_occlusion = create_check_box(
+                                    ctx,
+                                    7,
+                                    1,
+                                    settings.read().unwrap().sound_occlusion,
+                                );
+                                cb_sound_occlusion
+                            })
+                            .with_child({
+                                btn_reset_audio_settings = create_button(
+                                    ctx,
+                                    WidgetBuilder::new().on_row(8),
+                                    &tr("options.reset"),
+                                );
                                 btn_reset_audio_settings
                             }),
                     )
@@ -436,6 +648,11 @@ impl OptionsMenu {
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
                     .add_column(Column::strict(250.0))
                     .add_column(Column::stretch())
                     .build(ctx)
@@ -444,16 +661,17 @@ impl OptionsMenu {
             .with_tab(TabDefinition {
                 header: {
                     TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
-                        .with_text("Controls")
+                        .with_text(&tr("options.tab.controls"))
                         .build(ctx)
                 },
                 content: {
                     let mut children = Vec::new();
 
-                    for (row, button) in control_scheme.read().unwrap().buttons().iter().enumerate()
+                    for (index, definition) in
+                        control_scheme.read().unwrap().buttons().iter().enumerate()
                     {
                         // Offset by total amount of rows that goes before
-                        let row = row + 4;
+                        let row = index + 7;
 
                         let text = TextBuilder::new(
                             WidgetBuilder::new()
@@ -461,52 +679,76 @@ impl OptionsMenu {
                                 .on_column(0)
                                 .with_margin(margin),
                         )
-                        .with_text(button.description.as_str())
+                        .with_text(&tr(definition.description))
                         .with_vertical_text_alignment(VerticalAlignment::Center)
                         .build(ctx);
                         children.push(text);
 
-                        let button = ButtonBuilder::new(
-                            WidgetBuilder::new()
-                                .with_margin(margin)
-                                .on_row(row)
-                                .on_column(1),
-                        )
-                        .with_text(button.button.name())
-                        .build(ctx);
+                        let button = create_button(
+                            ctx,
+                            WidgetBuilder::new().on_row(row).on_column(1),
+                            definition.button.name(),
+                        );
                         children.push(button);
                         control_scheme_buttons.push(button);
+
+                        let alt_button = create_button(
+                            ctx,
+                            WidgetBuilder::new().on_row(row).on_column(2),
+                            optional_button_name(definition.alt_button),
+                        );
+                        children.push(alt_button);
+                        control_scheme_alt_buttons.push(alt_button);
+
+                        let alt_clear_button =
+                            create_button(ctx, WidgetBuilder::new().on_row(row).on_column(3), "X");
+                        children.push(alt_clear_button);
+                        control_scheme_alt_clear_buttons.push(alt_clear_button);
+
+                        let gamepad_button = create_button(
+                            ctx,
+                            WidgetBuilder::new().on_row(row).on_column(4),
+                            optional_button_name(definition.gamepad),
+                        );
+                        children.push(gamepad_button);
+                        control_scheme_gamepad_buttons.push(gamepad_button);
+
+                        if definition.can_toggle {
+                            let list = create_dropdown(
+                                ctx,
+                                row,
+                                5,
+                                &[ActivationMode::Hold, ActivationMode::Toggle]
+                                    .iter()
+                                    .map(|mode| mode.display_name())
+                                    .collect::<Vec<_>>(),
+                                activation_mode_index(definition.mode),
+                            );
+                            children.push(list);
+                            activation_mode_lists.push((index, list));
+                        }
                     }
 
                     GridBuilder::new(
                         WidgetBuilder::new()
-                            .with_child(
-                                TextBuilder::new(
-                                    WidgetBuilder::new()
-                                        .on_row(0)
-                                        .on_column(0)
-                                        .with_margin(margin),
-                                )
-                                .with_text("Mouse Sensitivity")
-                                .with_vertical_text_alignment(VerticalAlignment::Center)
-                                .build(ctx),
-                            )
-                            .with_child({
-                                sb_mouse_sens = create_scroll_bar(
+                            .with_children({
+                                let slider = create_labeled_slider(
                                     ctx,
+                                    &tr("options.mouse_sensitivity"),
                                     ScrollBarData {
                                         min: 0.05,
                                         max: 2.0,
-                                        value: control_scheme.read().unwrap().mouse_sens,
+                                        value: control_scheme.read().unwrap().mouse_sens(),
                                         step: 0.05,
                                         row: 0,
-                                        column: 1,
+                                        column: 0,
                                         margin,
                                         show_value: true,
                                         orientation: Orientation::Horizontal,
                                     },
                                 );
-                                sb_mouse_sens
+                                sb_mouse_sens = slider.scroll_bar;
+                                slider.widgets()
                             })
                             .with_child(
                                 TextBuilder::new(
@@ -515,7 +757,7 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Inverse Mouse Y")
+                                .with_text(&tr("options.inverse_mouse_y"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
@@ -535,7 +777,7 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Smooth Mouse")
+                                .with_text(&tr("options.smooth_mouse"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
@@ -555,7 +797,7 @@ impl OptionsMenu {
                                         .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Shake Camera")
+                                .with_text(&tr("options.shake_camera"))
                                 .with_vertical_text_alignment(VerticalAlignment::Center)
                                 .build(ctx),
                             )
@@ -568,24 +810,190 @@ impl OptionsMenu {
                                 );
                                 cb_shake_camera
                             })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(4)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.manual_pickup"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_manual_pickup = create_check_box(
+                                    ctx,
+                                    4,
+                                    1,
+                                    control_scheme.read().unwrap().manual_pickup,
+                                );
+                                cb_manual_pickup
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(5)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.double_tap_dash"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
                             .with_child({
-                                btn_reset_control_scheme = ButtonBuilder::new(
+                                cb_double_tap_dash = create_check_box(
+                                    ctx,
+                                    5,
+                                    1,
+                                    control_scheme.read().unwrap().double_tap_dash,
+                                );
+                                cb_double_tap_dash
+                            })
+                            .with_child(
+                                TextBuilder::new(
                                     WidgetBuilder::new()
-                                        .on_row(4 + control_scheme.read().unwrap().buttons().len())
+                                        .on_row(6)
+                                        .on_column(0)
                                         .with_margin(margin),
                                 )
-                                .with_text("Reset")
+                                .with_text(&tr("options.advanced_mouse_settings"))
+                                .with_vertical_text_alignment(VerticalAlignment::Top)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                let control_scheme = control_scheme.read().unwrap();
+                                let create_label = |ctx: &mut BuildContext, row, text| {
+                                    TextBuilder::new(
+                                        WidgetBuilder::new()
+                                            .on_row(row)
+                                            .on_column(0)
+                                            .with_margin(margin),
+                                    )
+                                    .with_text(text)
+                                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                                    .build(ctx)
+                                };
+                                let content = GridBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_children({
+                                            let slider = create_labeled_slider(
+                                                ctx,
+                                                &tr("options.mouse.horizontal"),
+                                                ScrollBarData {
+                                                    min: 0.05,
+                                                    max: 2.0,
+                                                    value: control_scheme.mouse_sens_x,
+                                                    step: 0.05,
+                                                    row: 0,
+                                                    column: 0,
+                                                    margin,
+                                                    show_value: true,
+                                                    orientation: Orientation::Horizontal,
+                                                },
+                                            );
+                                            sb_mouse_sens_x = slider.scroll_bar;
+                                            slider.widgets()
+                                        })
+                                        .with_children({
+                                            let slider = create_labeled_slider(
+                                                ctx,
+                                                &tr("options.mouse.vertical"),
+                                                ScrollBarData {
+                                                    min: 0.05,
+                                                    max: 2.0,
+                                                    value: control_scheme.mouse_sens_y,
+                                                    step: 0.05,
+                                                    row: 1,
+                                                    column: 0,
+                                                    margin,
+                                                    show_value: true,
+                                                    orientation: Orientation::Horizontal,
+                                                },
+                                            );
+                                            sb_mouse_sens_y = slider.scroll_bar;
+                                            slider.widgets()
+                                        })
+                                        .with_child(create_label(
+                                            ctx,
+                                            2,
+                                            &tr("options.mouse.curve"),
+                                        ))
+                                        .with_child({
+                                            dd_mouse_curve = create_dropdown(
+                                                ctx,
+                                                2,
+                                                1,
+                                                &MouseCurve::ALL
+                                                    .iter()
+                                                    .map(|curve| curve.display_name())
+                                                    .collect::<Vec<_>>(),
+                                                mouse_curve_index(control_scheme.mouse_curve),
+                                            );
+                                            dd_mouse_curve
+                                        })
+                                        .with_children({
+                                            let slider = create_labeled_slider(
+                                                ctx,
+                                                &tr("options.mouse.exponent"),
+                                                ScrollBarData {
+                                                    min: 1.0,
+                                                    max: 3.0,
+                                                    value: control_scheme.mouse_curve_exponent,
+                                                    step: 0.1,
+                                                    row: 3,
+                                                    column: 0,
+                                                    margin,
+                                                    show_value: true,
+                                                    orientation: Orientation::Horizontal,
+                                                },
+                                            );
+                                            sb_mouse_curve_exponent = slider.scroll_bar;
+                                            slider.widgets()
+                                        }),
+                                )
+                                .add_column(Column::strict(90.0))
+                                .add_column(Column::stretch())
+                                .add_row(common_row)
+                                .add_row(common_row)
+                                .add_row(common_row)
+                                .add_row(common_row)
                                 .build(ctx);
+                                ExpanderBuilder::new(WidgetBuilder::new().on_row(6).on_column(1))
+                                    .with_header(
+                                        TextBuilder::new(WidgetBuilder::new().with_margin(margin))
+                                            .with_text(&tr("options.show"))
+                                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                                            .build(ctx),
+                                    )
+                                    .with_content(content)
+                                    .with_expanded(false)
+                                    .build(ctx)
+                            })
+                            .with_child({
+                                btn_reset_control_scheme = create_button(
+                                    ctx,
+                                    WidgetBuilder::new()
+                                        .on_row(7 + control_scheme.read().unwrap().buttons().len()),
+                                    &tr("options.reset"),
+                                );
                                 btn_reset_control_scheme
                             })
                             .with_children(children),
                     )
-                    .add_column(Column::strict(250.0))
+                    .add_column(Column::strict(220.0))
                     .add_column(Column::stretch())
+                    .add_column(Column::strict(120.0))
+                    .add_column(Column::strict(30.0))
+                    .add_column(Column::strict(130.0))
+                    .add_column(Column::strict(90.0))
+                    .add_row(common_row)
+                    .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
                     .add_row(common_row)
+                    .add_row(Row::auto())
                     .add_rows(
                         (0..control_scheme.read().unwrap().buttons().len())
                             .map(|_| common_row)
@@ -595,48 +1003,497 @@ impl OptionsMenu {
                     .build(ctx)
                 },
             })
-            .build(ctx);
-
-        let options_window: Handle<UiNode> =
-            WindowBuilder::new(WidgetBuilder::new().with_width(500.0))
-                .with_title(WindowTitle::text("Options"))
-                .open(false)
-                .with_content(tab_control)
-                .build(ctx);
-
-        Self {
-            sender,
-            window: options_window,
-            sb_sound_volume,
-            sb_music_volume,
-            lb_video_modes,
-            cb_fullscreen,
-            cb_spot_shadows,
-            cb_soft_spot_shadows,
-            cb_point_shadows,
-            cb_soft_point_shadows,
-            sb_point_shadow_distance,
-            sb_spot_shadow_distance,
-            video_modes,
-            control_scheme,
-            control_scheme_buttons,
-            active_control_button: None,
-            sb_mouse_sens,
-            cb_mouse_y_inverse,
-            cb_smooth_mouse,
-            cb_shake_camera,
-            btn_reset_control_scheme,
+            .with_tab(TabDefinition {
+                header: {
+                    TextBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+                        .with_text(&tr("options.tab.gameplay"))
+                        .build(ctx)
+                },
+                content: {
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(0)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.difficulty"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                dd_difficulty = create_dropdown(
+                                    ctx,
+                                    0,
+                                    1,
+                                    &Difficulty::ALL
+                                        .iter()
+                                        .map(|difficulty| difficulty.display_name())
+                                        .collect::<Vec<_>>(),
+                                    difficulty_index(settings.read().unwrap().difficulty),
+                                );
+                                dd_difficulty
+                            })
+                            .with_children({
+                                let slider = create_labeled_slider(
+                                    ctx,
+                                    &tr("options.field_of_view"),
+                                    ScrollBarData {
+                                        min: 60.0,
+                                        max: 110.0,
+                                        value: settings.read().unwrap().fov,
+                                        step: 1.0,
+                                        row: 1,
+                                        column: 0,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_fov = slider.scroll_bar;
+                                slider.widgets()
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(2)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.show_crosshair"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_show_crosshair = create_check_box(
+                                    ctx,
+                                    2,
+                                    1,
+                                    control_scheme.read().unwrap().show_crosshair,
+                                );
+                                cb_show_crosshair
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(3)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.show_hit_markers"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_show_hit_markers = create_check_box(
+                                    ctx,
+                                    3,
+                                    1,
+                                    control_scheme.read().unwrap().show_hit_markers,
+                                );
+                                cb_show_hit_markers
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(4)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.show_damage_indicators"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_show_damage_indicators = create_check_box(
+                                    ctx,
+                                    4,
+                                    1,
+                                    control_scheme.read().unwrap().show_damage_indicators,
+                                );
+                                cb_show_damage_indicators
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(5)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.show_damage_numbers"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_show_damage_numbers = create_check_box(
+                                    ctx,
+                                    5,
+                                    1,
+                                    control_scheme.read().unwrap().show_damage_numbers,
+                                );
+                                cb_show_damage_numbers
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(6)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.captions"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_captions =
+                                    create_check_box(ctx, 6, 1, settings.read().unwrap().captions);
+                                cb_captions
+                            })
+                            .with_children({
+                                let slider = create_labeled_slider(
+                                    ctx,
+                                    &tr("options.caption_size"),
+                                    ScrollBarData {
+                                        min: 14.0,
+                                        max: 40.0,
+                                        value: settings.read().unwrap().caption_size,
+                                        step: 1.0,
+                                        row: 7,
+                                        column: 0,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_caption_size = slider.scroll_bar;
+                                slider.widgets()
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(8)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.radar"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_radar =
+                                    create_check_box(ctx, 8, 1, settings.read().unwrap().radar);
+                                cb_radar
+                            })
+                            .with_children({
+                                let slider = create_labeled_slider(
+                                    ctx,
+                                    &tr("options.radar_size"),
+                                    ScrollBarData {
+                                        min: 100.0,
+                                        max: 300.0,
+                                        value: settings.read().unwrap().radar_size,
+                                        step: 10.0,
+                                        row: 9,
+                                        column: 0,
+                                        margin,
+                                        show_value: true,
+                                        orientation: Orientation::Horizontal,
+                                    },
+                                );
+                                sb_radar_size = slider.scroll_bar;
+                                slider.widgets()
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(10)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.language"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                dd_language = create_dropdown(
+                                    ctx,
+                                    10,
+                                    1,
+                                    &languages
+                                        .iter()
+                                        .map(|(_, name)| name.clone())
+                                        .collect::<Vec<_>>(),
+                                    language_index(&languages, &settings.read().unwrap().language),
+                                );
+                                dd_language
+                            })
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(11)
+                                        .on_column(0)
+                                        .with_margin(margin),
+                                )
+                                .with_text(&tr("options.reduce_violence"))
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx),
+                            )
+                            .with_child({
+                                cb_reduce_violence = create_check_box(
+                                    ctx,
+                                    11,
+                                    1,
+                                    settings.read().unwrap().reduce_violence,
+                                );
+                                cb_reduce_violence
+                            }),
+                    )
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_row(common_row)
+                    .add_column(Column::strict(250.0))
+                    .add_column(Column::stretch())
+                    .build(ctx)
+                },
+            })
+            .build(ctx);
+
+        let options_window: Handle<UiNode> =
+            WindowBuilder::new(WidgetBuilder::new().with_width(870.0))
+                .with_title(WindowTitle::text(tr("options.options")))
+                .open(false)
+                .with_content(
+                    GridBuilder::new(
+                        WidgetBuilder::new().with_child(tab_control).with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        btn_apply = create_button(
+                                            ctx,
+                                            WidgetBuilder::new().with_width(100.0),
+                                            &tr("options.apply"),
+                                        );
+                                        btn_apply
+                                    })
+                                    .with_child({
+                                        btn_defaults = create_button(
+                                            ctx,
+                                            WidgetBuilder::new().with_width(100.0),
+                                            &tr("options.defaults"),
+                                        );
+                                        btn_defaults
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                    )
+                    .add_row(Row::auto())
+                    .add_row(common_row)
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                )
+                .build(ctx);
+
+        let discard_dialog = create_confirmation_dialog(
+            ctx,
+            &tr("options.unapplied_changes"),
+            &tr("options.discard_unapplied_changes"),
+        );
+
+        let menu = Self {
+            sender,
+            window: options_window,
+            sb_master_volume,
+            sb_sfx_volume,
+            sb_music_volume,
+            sb_ui_volume,
+            sb_voice_volume,
+            cb_mute_on_focus_loss,
+            cb_sound_occlusion,
+            dd_video_mode,
+            cb_fullscreen,
+            cb_spot_shadows,
+            cb_soft_spot_shadows,
+            cb_point_shadows,
+            cb_soft_point_shadows,
+            sb_point_shadow_distance,
+            sb_spot_shadow_distance,
+            video_modes,
+            control_scheme,
+            control_scheme_buttons,
+            control_scheme_alt_buttons,
+            control_scheme_alt_clear_buttons,
+            control_scheme_gamepad_buttons,
+            capture: None,
+            activation_mode_lists,
+            settings,
+            control_scheme_changed: false,
+            settings_changed: false,
+            sb_mouse_sens,
+            sb_mouse_sens_x,
+            sb_mouse_sens_y,
+            dd_mouse_curve,
+            sb_mouse_curve_exponent,
+            cb_mouse_y_inverse,
+            cb_smooth_mouse,
+            cb_shake_camera,
+            cb_manual_pickup,
+            cb_double_tap_dash,
+            cb_show_crosshair,
+            cb_show_hit_markers,
+            cb_show_damage_indicators,
+            cb_show_damage_numbers,
+            btn_reset_control_scheme,
             cb_use_hrtf,
             btn_reset_audio_settings,
             cb_use_light_scatter,
+            dd_difficulty,
+            dd_effect_quality,
+            sb_fov,
+            cb_captions,
+            sb_caption_size,
+            cb_radar,
+            sb_radar_size,
+            cb_reduce_violence,
+            dd_language,
+            languages,
+            btn_apply,
+            btn_defaults,
+            discard_dialog,
+        };
+
+        // Saved settings must be in effect from the start, not only after the menu was opened.
+        menu.apply_settings(engine);
+
+        menu
+    }
+
+    pub fn destroy(&self, ui: &UserInterface) {
+        for widget in [self.window, self.discard_dialog.root] {
+            ui.send_message(WidgetMessage::remove(widget, MessageDirection::ToWidget));
+        }
+    }
+
+    fn apply_settings(&self, engine: &mut Engine) {
+        let settings = self.settings.read().unwrap();
+        // Volumes of level sounds are picked up by the level itself.
+        self.send_music_volume(&settings);
+        let quality = settings.quality(engine.renderer.get_quality_settings());
+        if let Err(err) = engine.renderer.set_quality_settings(&quality) {
+            Log::writeln(
+                MessageKind::Error,
+                format!("Failed to set renderer quality settings! Reason: {:?}", err),
+            );
+        }
+        self.apply_video_mode(engine, &settings);
+    }
+
+    fn send_music_volume(&self, settings: &Settings) {
+        self.sender
+            .send(Message::SetMusicVolume {
+                volume: settings.volume(SoundCategory::Music),
+            })
+            .unwrap();
+    }
+
+    fn apply_video_mode(&self, engine: &Engine, settings: &Settings) {
+        let fullscreen = if settings.fullscreen {
+            match settings.video_mode.and_then(|mode| {
+                self.video_modes
+                    .iter()
+                    .find(|video_mode| video_mode_key(video_mode) == mode)
+            }) {
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode.clone())),
+                // Saved mode may be unavailable on current monitor.
+                None => Some(Fullscreen::Borderless(None)),
+            }
+        } else {
+            None
+        };
+        engine.get_window().set_fullscreen(fullscreen);
+    }
+
+    fn save(&mut self) {
+        if self.control_scheme_changed {
+            if let Err(e) = self
+                .control_scheme
+                .read()
+                .unwrap()
+                .save(CONTROL_SCHEME_PATH)
+            {
+                Log::writeln(
+                    MessageKind::Error,
+                    format!("Failed to save control scheme! Reason: {}", e),
+                );
+            }
+        }
+        if self.settings_changed {
+            let settings = self.settings.read().unwrap();
+            if let Err(e) = settings.save(SETTINGS_PATH) {
+                Log::writeln(
+                    MessageKind::Error,
+                    format!("Failed to save settings! Reason: {}", e),
+                );
+            }
+            // Language is switched only on apply, every menu is rebuilt then.
+            if settings.language != localization::current_language() {
+                localization::set_language(&settings.language);
+                self.sender.send(Message::LanguageChanged).unwrap();
+            }
+        }
+        self.control_scheme_changed = false;
+        self.settings_changed = false;
+    }
+
+    // Throws away unapplied changes by reloading both files.
+    fn revert(&mut self, engine: &mut Engine) {
+        if self.control_scheme_changed {
+            *self.control_scheme.write().unwrap() = ControlScheme::load(CONTROL_SCHEME_PATH);
+        }
+        if self.settings_changed {
+            *self.settings.write().unwrap() = Settings::load(SETTINGS_PATH);
+            self.apply_settings(engine);
+        }
+        self.control_scheme_changed = false;
+        self.settings_changed = false;
+        self.sync_to_model(engine);
+    }
+
+    fn has_unapplied_changes(&self) -> bool {
+        self.control_scheme_changed || self.settings_changed
+    }
+
+    // Closes the window, but asks for confirmation first if there is something to lose.
+    fn request_close(&self, engine: &mut Engine) {
+        if self.has_unapplied_changes() {
+            self.discard_dialog.open(&engine.user_interface);
+        } else {
+            engine.user_interface.send_message(WindowMessage::close(
+                self.window,
+                MessageDirection::ToWidget,
+            ));
         }
     }
 
     pub fn sync_to_model(&mut self, engine: &mut Engine) {
-        let sound_gain = engine.sound_gain();
         let ui = &mut engine.user_interface;
         let control_scheme = self.control_scheme.read().unwrap();
-        let settings = engine.renderer.get_quality_settings();
+        let settings = self.settings.read().unwrap();
+        let quality = engine.renderer.get_quality_settings();
 
         let sync_check_box = |handle: Handle<UiNode>, value: bool| {
             ui.send_message(CheckBoxMessage::checked(
@@ -645,14 +1502,32 @@ impl OptionsMenu {
                 Some(value),
             ));
         };
-        sync_check_box(self.cb_spot_shadows, settings.spot_shadows_enabled);
-        sync_check_box(self.cb_soft_spot_shadows, settings.spot_soft_shadows);
-        sync_check_box(self.cb_point_shadows, settings.point_shadows_enabled);
-        sync_check_box(self.cb_soft_point_shadows, settings.point_soft_shadows);
-        sync_check_box(self.cb_use_light_scatter, settings.light_scatter_enabled);
+        sync_check_box(self.cb_spot_shadows, quality.spot_shadows_enabled);
+        sync_check_box(self.cb_soft_spot_shadows, quality.spot_soft_shadows);
+        sync_check_box(self.cb_point_shadows, quality.point_shadows_enabled);
+        sync_check_box(self.cb_soft_point_shadows, quality.point_soft_shadows);
+        sync_check_box(self.cb_use_light_scatter, quality.light_scatter_enabled);
+        sync_check_box(self.cb_fullscreen, settings.fullscreen);
+        sync_check_box(self.cb_mute_on_focus_loss, settings.mute_on_focus_loss);
+        sync_check_box(self.cb_sound_occlusion, settings.sound_occlusion);
+        sync_check_box(self.cb_captions, settings.captions);
+        sync_check_box(self.cb_radar, settings.radar);
+        sync_check_box(self.cb_reduce_violence, settings.reduce_violence);
         sync_check_box(self.cb_mouse_y_inverse, control_scheme.mouse_y_inverse);
         sync_check_box(self.cb_smooth_mouse, control_scheme.smooth_mouse);
         sync_check_box(self.cb_shake_camera, control_scheme.shake_camera);
+        sync_check_box(self.cb_manual_pickup, control_scheme.manual_pickup);
+        sync_check_box(self.cb_double_tap_dash, control_scheme.double_tap_dash);
+        sync_check_box(self.cb_show_crosshair, control_scheme.show_crosshair);
+        sync_check_box(self.cb_show_hit_markers, control_scheme.show_hit_markers);
+        sync_check_box(
+            self.cb_show_damage_indicators,
+            control_scheme.show_damage_indicators,
+        );
+        sync_check_box(
+            self.cb_show_damage_numbers,
+            control_scheme.show_damage_numbers,
+        );
         let is_hrtf = true; /*if let fyrox::sound::renderer::Renderer::HrtfRenderer(_) =
                                 engine.sound_context.lock().unwrap().renderer()
                             {
@@ -671,11 +1546,49 @@ impl OptionsMenu {
         };
         sync_scroll_bar(
             self.sb_point_shadow_distance,
-            settings.point_shadows_distance,
+            quality.point_shadows_distance,
         );
-        sync_scroll_bar(self.sb_spot_shadow_distance, settings.spot_shadows_distance);
-        sync_scroll_bar(self.sb_mouse_sens, control_scheme.mouse_sens);
-        sync_scroll_bar(self.sb_sound_volume, sound_gain);
+        sync_scroll_bar(self.sb_spot_shadow_distance, quality.spot_shadows_distance);
+        sync_scroll_bar(self.sb_mouse_sens, control_scheme.mouse_sens());
+        sync_scroll_bar(self.sb_mouse_sens_x, control_scheme.mouse_sens_x);
+        sync_scroll_bar(self.sb_mouse_sens_y, control_scheme.mouse_sens_y);
+        sync_scroll_bar(
+            self.sb_mouse_curve_exponent,
+            control_scheme.mouse_curve_exponent,
+        );
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_mouse_curve,
+            MessageDirection::ToWidget,
+            Some(mouse_curve_index(control_scheme.mouse_curve)),
+        ));
+        sync_scroll_bar(self.sb_master_volume, settings.master_volume);
+        sync_scroll_bar(self.sb_sfx_volume, settings.sfx_volume);
+        sync_scroll_bar(self.sb_music_volume, settings.music_volume);
+        sync_scroll_bar(self.sb_ui_volume, settings.ui_volume);
+        sync_scroll_bar(self.sb_voice_volume, settings.voice_volume);
+        sync_scroll_bar(self.sb_fov, settings.fov);
+        sync_scroll_bar(self.sb_caption_size, settings.caption_size);
+        sync_scroll_bar(self.sb_radar_size, settings.radar_size);
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_video_mode,
+            MessageDirection::ToWidget,
+            Some(video_mode_index(&self.video_modes, settings.video_mode)),
+        ));
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_difficulty,
+            MessageDirection::ToWidget,
+            Some(difficulty_index(settings.difficulty)),
+        ));
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_effect_quality,
+            MessageDirection::ToWidget,
+            Some(effect_quality_index(settings.effect_quality)),
+        ));
+        ui.send_message(DropdownListMessage::selection(
+            self.dd_language,
+            MessageDirection::ToWidget,
+            Some(language_index(&self.languages, &settings.language)),
+        ));
 
         for (btn, def) in self
             .control_scheme_buttons
@@ -690,141 +1603,506 @@ impl OptionsMenu {
                 ));
             }
         }
+
+        for (index, list) in self.activation_mode_lists.iter() {
+            ui.send_message(DropdownListMessage::selection(
+                *list,
+                MessageDirection::ToWidget,
+                Some(activation_mode_index(
+                    self.control_scheme.read().unwrap().buttons()[*index].mode,
+                )),
+            ));
+        }
+
+        for (btn, def) in self
+            .control_scheme_alt_buttons
+            .iter()
+            .zip(self.control_scheme.read().unwrap().buttons().iter())
+        {
+            if let Some(button) = ui.node(*btn).cast::<Button>() {
+                ui.send_message(TextMessage::text(
+                    button.content,
+                    MessageDirection::ToWidget,
+                    optional_button_name(def.alt_button).to_owned(),
+                ));
+            }
+        }
+
+        for (btn, def) in self
+            .control_scheme_gamepad_buttons
+            .iter()
+            .zip(self.control_scheme.read().unwrap().buttons().iter())
+        {
+            if let Some(button) = ui.node(*btn).cast::<Button>() {
+                ui.send_message(TextMessage::text(
+                    button.content,
+                    MessageDirection::ToWidget,
+                    optional_button_name(def.gamepad).to_owned(),
+                ));
+            }
+        }
     }
 
-    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) {
-        if let Event::WindowEvent { event, .. } = event {
-            let mut control_button = None;
-
-            match event {
-                WindowEvent::MouseWheel { delta, .. } => {
-                    if let MouseScrollDelta::LineDelta(_, y) = delta {
-                        if *y != 0.0 {
-                            control_button = if *y >= 0.0 {
-                                Some(ControlButton::WheelUp)
-                            } else {
-                                Some(ControlButton::WheelDown)
-                            };
-                        }
-                    }
-                }
-                WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(code) = input.virtual_keycode {
-                        control_button = Some(ControlButton::Key(code));
-                    }
-                }
-                WindowEvent::MouseInput { button, .. } => {
-                    let index = match button {
-                        MouseButton::Left => 1,
-                        MouseButton::Right => 2,
-                        MouseButton::Middle => 3,
-                        MouseButton::Other(i) => *i,
-                    };
+    // Most recent assignment wins, every other action that used the same button is unbound.
+    fn resolve_conflicts(&mut self, engine: &mut Engine, index: usize) {
+        let unbound = self
+            .control_scheme
+            .write()
+            .unwrap()
+            .resolve_conflicts(index);
+        if !unbound.is_empty() {
+            let control_scheme = self.control_scheme.read().unwrap();
+            for other in unbound {
+                let definition = control_scheme.buttons()[other];
+                self.sync_binding_row(engine, other, &control_scheme);
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "{} was unbound because its button is now used by {}.",
+                        tr(definition.description),
+                        tr(control_scheme.buttons()[index].description)
+                    ),
+                );
+            }
+        }
+    }
+
+    // Shows current bindings of an action in its row.
+    fn sync_binding_row(&self, engine: &mut Engine, index: usize, control_scheme: &ControlScheme) {
+        let definition = control_scheme.buttons()[index];
+        for (buttons, text) in [
+            (&self.control_scheme_buttons, definition.button.name()),
+            (
+                &self.control_scheme_alt_buttons,
+                optional_button_name(definition.alt_button),
+            ),
+            (
+                &self.control_scheme_gamepad_buttons,
+                optional_button_name(definition.gamepad),
+            ),
+        ] {
+            if let Some(button) = engine.user_interface.node(buttons[index]).cast::<Button>() {
+                engine.user_interface.send_message(TextMessage::text(
+                    button.content,
+                    MessageDirection::ToWidget,
+                    text.to_owned(),
+                ));
+            }
+        }
+    }
+
+    fn start_capture(&mut self, engine: &mut Engine, action: usize, slot: BindingSlot) {
+        // Only one binding can wait for input, previous one keeps its button.
+        self.cancel_capture(engine);
+
+        let (buttons, text) = match slot {
+            BindingSlot::Primary => (&self.control_scheme_buttons, tr("options.waiting_input")),
+            BindingSlot::Alternative => (
+                &self.control_scheme_alt_buttons,
+                tr("options.waiting_input"),
+            ),
+            BindingSlot::Gamepad => (
+                &self.control_scheme_gamepad_buttons,
+                tr("options.press_pad_button"),
+            ),
+        };
+        if let Some(button) = engine.user_interface.node(buttons[action]).cast::<Button>() {
+            engine.user_interface.send_message(TextMessage::text(
+                button.content,
+                MessageDirection::ToWidget,
+                text,
+            ))
+        }
+
+        self.capture = Some(BindingCapture { action, slot });
+    }
+
+    fn cancel_capture(&mut self, engine: &mut Engine) {
+        if let Some(capture) = self.capture.take() {
+            let control_scheme = self.control_scheme.clone();
+            let control_scheme = control_scheme.read().unwrap();
+            self.sync_binding_row(engine, capture.action, &control_scheme);
+        }
+    }
 
-                    control_button = Some(ControlButton::Mouse(index));
+    fn finish_capture(&mut self, engine: &mut Engine, result: CaptureResult) {
+        let capture = match self.capture {
+            Some(capture) => capture,
+            None => return,
+        };
+
+        let button = match result {
+            CaptureResult::Bind(button) => Some(button),
+            CaptureResult::Unbind => None,
+            CaptureResult::Cancel => {
+                self.cancel_capture(engine);
+                return;
+            }
+            CaptureResult::Ignore => return,
+        };
+        self.capture = None;
+
+        {
+            let control_scheme = self.control_scheme.clone();
+            let mut control_scheme = control_scheme.write().unwrap();
+            let definition = &mut control_scheme.buttons_mut()[capture.action];
+            match capture.slot {
+                BindingSlot::Primary => {
+                    definition.button = button.unwrap_or(ControlButton::Unbound)
                 }
-                _ => {}
+                BindingSlot::Alternative => definition.alt_button = button,
+                BindingSlot::Gamepad => definition.gamepad = button,
             }
+            self.sync_binding_row(engine, capture.action, &control_scheme);
+        }
+        self.control_scheme_changed = true;
 
-            if let Some(control_button) = control_button {
-                if let Some(active_control_button) = self.active_control_button {
-                    if let Some(button) = engine
-                        .user_interface
-                        .node(self.control_scheme_buttons[active_control_button])
-                        .cast::<Button>()
-                    {
-                        engine.user_interface.send_message(TextMessage::text(
-                            button.content,
-                            MessageDirection::ToWidget,
-                            control_button.name().to_owned(),
-                        ));
-                    }
+        if button.is_some() {
+            self.resolve_conflicts(engine, capture.action);
+        }
+    }
+
+    pub fn process_gamepad_event(&mut self, engine: &mut Engine, event: &GamepadEvent) {
+        if let GamepadEvent::Button {
+            index,
+            pressed: true,
+        } = *event
+        {
+            if let Some(BindingCapture {
+                slot: BindingSlot::Gamepad,
+                ..
+            }) = self.capture
+            {
+                self.finish_capture(
+                    engine,
+                    CaptureResult::Bind(ControlButton::GamepadButton(index)),
+                );
+            }
+        }
+    }
 
-                    self.control_scheme.write().unwrap().buttons_mut()[active_control_button]
-                        .button = control_button;
+    /// Feeds input to the binding capture and handles Escape while the window is open. Returns
+    /// true if the event was consumed, in this case nobody else must process it (so a click on
+    /// LMB to bind it won't fire a weapon and Escape won't close the whole menu).
+    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) -> bool {
+        if let Event::WindowEvent { event, .. } = event {
+            if let Some(result) = self
+                .capture
+                .and_then(|capture| capture.process_event(event))
+            {
+                self.finish_capture(engine, result);
+                return true;
+            }
 
-                    self.active_control_button = None;
+            if let WindowEvent::KeyboardInput { input, .. } = event {
+                if input.virtual_keycode == Some(VirtualKeyCode::Escape)
+                    && engine.user_interface.node(self.window).visibility()
+                {
+                    // Escape while the dialog is open is ignored, it must be answered.
+                    if input.state == ElementState::Pressed
+                        && !self.discard_dialog.is_open(&engine.user_interface)
+                    {
+                        self.request_close(engine);
+                    }
+                    return true;
                 }
             }
         }
+        false
     }
 
     #[allow(clippy::cognitive_complexity)]
     pub fn handle_ui_event(&mut self, engine: &mut Engine, message: &UiMessage) {
-        let old_settings = engine.renderer.get_quality_settings();
-        let mut settings = old_settings;
+        let old_quality = engine.renderer.get_quality_settings();
+        let mut quality = old_quality;
 
         if let Some(ScrollBarMessage::Value(new_value)) = message.data() {
             if message.direction() == MessageDirection::FromWidget {
-                if message.destination() == self.sb_sound_volume {
-                    engine.set_sound_gain(*new_value)
+                if message.destination() == self.sb_master_volume {
+                    let mut settings = self.settings.write().unwrap();
+                    settings.master_volume = *new_value;
+                    self.settings_changed = true;
+                    self.send_music_volume(&settings);
+                } else if message.destination() == self.sb_sfx_volume {
+                    self.settings.write().unwrap().sfx_volume = *new_value;
+                    self.settings_changed = true;
+                } else if message.destination() == self.sb_ui_volume {
+                    self.settings.write().unwrap().ui_volume = *new_value;
+                    self.settings_changed = true;
+                } else if message.destination() == self.sb_voice_volume {
+                    self.settings.write().unwrap().voice_volume = *new_value;
+                    self.settings_changed = true;
+                } else if message.destination() == self.sb_fov {
+                    self.settings.write().unwrap().fov = *new_value;
+                    self.settings_changed = true;
+                } else if message.destination() == self.sb_caption_size {
+                    self.settings.write().unwrap().caption_size = *new_value;
+                    self.settings_changed = true;
+                } else if message.destination() == self.sb_radar_size {
+                    self.settings.write().unwrap().radar_size = *new_value;
+                    self.settings_changed = true;
                 } else if message.destination() == self.sb_point_shadow_distance {
-                    settings.point_shadows_distance = *new_value;
+                    quality.point_shadows_distance = *new_value;
                 } else if message.destination() == self.sb_spot_shadow_distance {
-                    settings.spot_shadows_distance = *new_value;
+                    quality.spot_shadows_distance = *new_value;
                 } else if message.destination() == self.sb_mouse_sens {
-                    self.control_scheme.write().unwrap().mouse_sens = *new_value;
+                    // Changes are applied immediately, so it is possible to try new
+                    // sensitivity without closing the menu.
+                    self.control_scheme
+                        .write()
+                        .unwrap()
+                        .set_mouse_sens(*new_value);
+                    self.control_scheme_changed = true;
+                    for sb in [self.sb_mouse_sens_x, self.sb_mouse_sens_y] {
+                        engine.user_interface.send_message(ScrollBarMessage::value(
+                            sb,
+                            MessageDirection::ToWidget,
+                            *new_value,
+                        ));
+                    }
+                } else if message.destination() == self.sb_mouse_sens_x {
+                    self.control_scheme.write().unwrap().mouse_sens_x = *new_value;
+                    self.control_scheme_changed = true;
+                } else if message.destination() == self.sb_mouse_sens_y {
+                    self.control_scheme.write().unwrap().mouse_sens_y = *new_value;
+                    self.control_scheme_changed = true;
+                } else if message.destination() == self.sb_mouse_curve_exponent {
+                    self.control_scheme.write().unwrap().mouse_curve_exponent = *new_value;
+                    self.control_scheme_changed = true;
                 } else if message.destination() == self.sb_music_volume {
-                    self.sender
-                        .send(Message::SetMusicVolume { volume: *new_value })
-                        .unwrap();
+                    let mut settings = self.settings.write().unwrap();
+                    settings.music_volume = *new_value;
+                    self.settings_changed = true;
+                    self.send_music_volume(&settings);
                 }
             }
-        } else if let Some(ListViewMessage::SelectionChanged(new_value)) = message.data() {
-            if message.destination() == self.lb_video_modes {
-                if let Some(index) = new_value {
-                    let video_mode = self.video_modes[*index].clone();
-                    engine
-                        .get_window()
-                        .set_fullscreen(Some(Fullscreen::Exclusive(video_mode)))
+        } else if let Some(DropdownListMessage::SelectionChanged(Some(selection))) = message.data()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.dd_mouse_curve {
+                    if let Some(curve) = MouseCurve::ALL.get(*selection) {
+                        let mut control_scheme = self.control_scheme.write().unwrap();
+                        if control_scheme.mouse_curve != *curve {
+                            control_scheme.mouse_curve = *curve;
+                            self.control_scheme_changed = true;
+                        }
+                    }
+                } else if message.destination() == self.dd_difficulty {
+                    if let Some(difficulty) = Difficulty::ALL.get(*selection) {
+                        let mut settings = self.settings.write().unwrap();
+                        if settings.difficulty != *difficulty {
+                            settings.difficulty = *difficulty;
+                            self.settings_changed = true;
+                        }
+                    }
+                } else if message.destination() == self.dd_effect_quality {
+                    if let Some(quality) = EffectQuality::ALL.get(*selection) {
+                        let mut settings = self.settings.write().unwrap();
+                        if settings.effect_quality != *quality {
+                            settings.effect_quality = *quality;
+                            self.settings_changed = true;
+                        }
+                    }
+                } else if message.destination() == self.dd_language {
+                    if let Some((code, _)) = self.languages.get(*selection) {
+                        let mut settings = self.settings.write().unwrap();
+                        if settings.language != *code {
+                            settings.language = code.clone();
+                            self.settings_changed = true;
+                        }
+                    }
+                } else if message.destination() == self.dd_video_mode {
+                    let video_mode = match selection {
+                        0 => None,
+                        _ => self.video_modes.get(*selection - 1).map(video_mode_key),
+                    };
+                    let settings = self.settings.clone();
+                    let mut settings = settings.write().unwrap();
+                    if settings.video_mode != video_mode {
+                        settings.video_mode = video_mode;
+                        self.settings_changed = true;
+                        self.apply_video_mode(engine, &settings);
+                    }
+                }
+                for (index, list) in self.activation_mode_lists.iter() {
+                    if message.destination() == *list {
+                        let mode = if *selection == 1 {
+                            ActivationMode::Toggle
+                        } else {
+                            ActivationMode::Hold
+                        };
+                        let mut control_scheme = self.control_scheme.write().unwrap();
+                        let definition = &mut control_scheme.buttons_mut()[*index];
+                        if definition.mode != mode {
+                            definition.mode = mode;
+                            self.control_scheme_changed = true;
+                        }
+                    }
                 }
             }
         } else if let Some(CheckBoxMessage::Check(value)) = message.data() {
             let value = value.unwrap_or(false);
             let mut control_scheme = self.control_scheme.write().unwrap();
             if message.destination() == self.cb_point_shadows {
-                settings.point_shadows_enabled = value;
+                quality.point_shadows_enabled = value;
             } else if message.destination() == self.cb_spot_shadows {
-                settings.spot_shadows_enabled = value;
+                quality.spot_shadows_enabled = value;
             } else if message.destination() == self.cb_soft_spot_shadows {
-                settings.spot_soft_shadows = value;
+                quality.spot_soft_shadows = value;
             } else if message.destination() == self.cb_soft_point_shadows {
-                settings.point_soft_shadows = value;
+                quality.point_soft_shadows = value;
             } else if message.destination() == self.cb_mouse_y_inverse {
                 control_scheme.mouse_y_inverse = value;
+                self.control_scheme_changed = true;
             } else if message.destination() == self.cb_smooth_mouse {
                 control_scheme.smooth_mouse = value;
+                self.control_scheme_changed = true;
             } else if message.destination() == self.cb_shake_camera {
                 control_scheme.shake_camera = value;
+                self.control_scheme_changed = true;
+            } else if message.destination() == self.cb_manual_pickup {
+                control_scheme.manual_pickup = value;
+                self.control_scheme_changed = true;
+            } else if message.destination() == self.cb_double_tap_dash {
+                control_scheme.double_tap_dash = value;
+                self.control_scheme_changed = true;
+            } else if message.destination() == self.cb_show_crosshair {
+                control_scheme.show_crosshair = value;
+                self.control_scheme_changed = true;
+            } else if message.destination() == self.cb_show_hit_markers {
+                control_scheme.show_hit_markers = value;
+                self.control_scheme_changed = true;
+            } else if message.destination() == self.cb_show_damage_indicators {
+                control_scheme.show_damage_indicators = value;
+                self.control_scheme_changed = true;
+            } else if message.destination() == self.cb_show_damage_numbers {
+                control_scheme.show_damage_numbers = value;
+                self.control_scheme_changed = true;
             } else if message.destination() == self.cb_use_light_scatter {
-                settings.light_scatter_enabled = value;
+                quality.light_scatter_enabled = value;
+            } else if message.destination() == self.cb_mute_on_focus_loss {
+                self.settings.write().unwrap().mute_on_focus_loss = value;
+                self.settings_changed = true;
+            } else if message.destination() == self.cb_sound_occlusion {
+                self.settings.write().unwrap().sound_occlusion = value;
+                self.settings_changed = true;
+            } else if message.destination() == self.cb_captions {
+                self.settings.write().unwrap().captions = value;
+                self.settings_changed = true;
+            } else if message.destination() == self.cb_radar {
+                self.settings.write().unwrap().radar = value;
+                self.settings_changed = true;
+            } else if message.destination() == self.cb_reduce_violence {
+                self.settings.write().unwrap().reduce_violence = value;
+                self.settings_changed = true;
+            } else if message.destination() == self.cb_fullscreen {
+                let settings = self.settings.clone();
+                let mut settings = settings.write().unwrap();
+                settings.fullscreen = value;
+                self.settings_changed = true;
+                self.apply_video_mode(engine, &settings);
             }
         } else if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.btn_reset_control_scheme {
                 self.control_scheme.write().unwrap().reset();
+                self.control_scheme_changed = true;
                 self.sync_to_model(engine);
             } else if message.destination() == self.btn_reset_audio_settings {
-                engine.set_sound_gain(1.0);
+                {
+                    let defaults = Settings::default();
+                    let mut settings = self.settings.write().unwrap();
+                    settings.master_volume = defaults.master_volume;
+                    settings.sfx_volume = defaults.sfx_volume;
+                    settings.music_volume = defaults.music_volume;
+                    settings.ui_volume = defaults.ui_volume;
+                    settings.voice_volume = defaults.voice_volume;
+                    settings.mute_on_focus_loss = defaults.mute_on_focus_loss;
+                    settings.sound_occlusion = defaults.sound_occlusion;
+                }
+                self.settings_changed = true;
+                self.apply_settings(engine);
+                self.sync_to_model(engine);
+            } else if message.destination() == self.btn_apply {
+                self.save();
+            } else if message.destination() == self.btn_defaults {
+                self.control_scheme.write().unwrap().reset();
+                self.settings.write().unwrap().reset();
+                self.control_scheme_changed = true;
+                self.settings_changed = true;
+                self.apply_settings(engine);
                 self.sync_to_model(engine);
             }
 
             for (i, button) in self.control_scheme_buttons.iter().enumerate() {
                 if message.destination() == *button {
-                    if let Some(button) = engine.user_interface.node(*button).cast::<Button>() {
+                    self.start_capture(engine, i, BindingSlot::Primary);
+                }
+            }
+
+            for (i, button) in self.control_scheme_alt_buttons.iter().enumerate() {
+                if message.destination() == *button {
+                    self.start_capture(engine, i, BindingSlot::Alternative);
+                }
+            }
+
+            for (i, button) in self.control_scheme_alt_clear_buttons.iter().enumerate() {
+                if message.destination() == *button {
+                    self.control_scheme.write().unwrap().buttons_mut()[i].alt_button = None;
+                    self.control_scheme_changed = true;
+                    if let Some(button) = engine
+                        .user_interface
+                        .node(self.control_scheme_alt_buttons[i])
+                        .cast::<Button>()
+                    {
                         engine.user_interface.send_message(TextMessage::text(
                             button.content,
                             MessageDirection::ToWidget,
-                            "[WAITING INPUT]".to_owned(),
+                            optional_button_name(None).to_owned(),
                         ))
                     }
+                }
+            }
 
-                    self.active_control_button = Some(i);
+            for (i, button) in self.control_scheme_gamepad_buttons.iter().enumerate() {
+                if message.destination() == *button {
+                    self.start_capture(engine, i, BindingSlot::Gamepad);
                 }
             }
         }
 
-        if settings != old_settings {
-            if let Err(err) = engine.renderer.set_quality_settings(&settings) {
+        if let Some(result) = self
+            .discard_dialog
+            .handle_ui_message(&engine.user_interface, message)
+        {
+            if result == DialogResult::Ok {
+                self.revert(engine);
+                engine.user_interface.send_message(WindowMessage::close(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+            }
+        } else if let Some(WindowMessage::Open { .. }) = message.data() {
+            // Everything matches saved state when window opens, but syncing widgets after
+            // revert could mark some values as changed again.
+            if message.destination() == self.window {
+                self.control_scheme_changed = false;
+                self.settings_changed = false;
+            }
+        } else if let Some(WindowMessage::Close) = message.data() {
+            // Closing the window (by its close button or along with the whole menu) discards
+            // unapplied changes.
+            if message.destination() == self.window {
+                self.cancel_capture(engine);
+                if self.has_unapplied_changes() {
+                    self.revert(engine);
+                }
+            }
+        }
+
+        if quality != old_quality {
+            self.settings.write().unwrap().set_quality(&quality);
+            self.settings_changed = true;
+            if let Err(err) = engine.renderer.set_quality_settings(&quality) {
                 Log::writeln(
                     MessageKind::Error,
                     format!("Failed to set renderer quality settings! Reason: {:?}", err),
@@ -833,3 +2111,168 @@ impl OptionsMenu {
         }
     }
 }
+
+fn optional_button_name(button: Option<ControlButton>) -> &'static str {
+    button.map(|b| b.name()).unwrap_or("-")
+}
+
+fn mouse_curve_index(curve: MouseCurve) -> usize {
+    MouseCurve::ALL
+        .iter()
+        .position(|c| *c == curve)
+        .unwrap_or_default()
+}
+
+fn difficulty_index(difficulty: Difficulty) -> usize {
+    Difficulty::ALL
+        .iter()
+        .position(|d| *d == difficulty)
+        .unwrap_or_default()
+}
+
+fn effect_quality_index(quality: EffectQuality) -> usize {
+    EffectQuality::ALL
+        .iter()
+        .position(|q| *q == quality)
+        .unwrap_or_default()
+}
+
+fn language_index(languages: &[(String, String)], language: &str) -> usize {
+    languages
+        .iter()
+        .position(|(code, _)| code == language)
+        .unwrap_or_default()
+}
+
+fn video_mode_key(video_mode: &VideoMode) -> (u32, u32, u32) {
+    let size = video_mode.size();
+    (
+        size.width,
+        size.height,
+        video_mode.refresh_rate_millihertz(),
+    )
+}
+
+// First item of the list is the desktop resolution.
+fn video_mode_index(video_modes: &[VideoMode], video_mode: Option<(u32, u32, u32)>) -> usize {
+    video_mode
+        .and_then(|mode| {
+            video_modes
+                .iter()
+                .position(|video_mode| video_mode_key(video_mode) == mode)
+        })
+        .map_or(0, |index| index + 1)
+}
+
+fn activation_mode_index(mode: ActivationMode) -> usize {
+    match mode {
+        ActivationMode::Hold => 0,
+        ActivationMode::Toggle => 1,
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use super::*;
+    use fyrox::event::{DeviceId, KeyboardInput, ModifiersState, TouchPhase};
+
+    fn capture(slot: BindingSlot) -> BindingCapture {
+        BindingCapture { action: 0, slot }
+    }
+
+    fn key(code: VirtualKeyCode, state: ElementState) -> WindowEvent<'static> {
+        WindowEvent::KeyboardInput {
+            device_id: unsafe { DeviceId::dummy() },
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(code),
+                modifiers: ModifiersState::empty(),
+            },
+            is_synthetic: false,
+        }
+    }
+
+    fn mouse(button: MouseButton) -> WindowEvent<'static> {
+        WindowEvent::MouseInput {
+            device_id: unsafe { DeviceId::dummy() },
+            state: ElementState::Pressed,
+            button,
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    fn wheel(y: f32) -> WindowEvent<'static> {
+        WindowEvent::MouseWheel {
+            device_id: unsafe { DeviceId::dummy() },
+            delta: MouseScrollDelta::LineDelta(0.0, y),
+            phase: TouchPhase::Moved,
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    #[test]
+    fn escape_cancels() {
+        for slot in [BindingSlot::Primary, BindingSlot::Gamepad] {
+            assert_eq!(
+                capture(slot).process_event(&key(VirtualKeyCode::Escape, ElementState::Pressed)),
+                Some(CaptureResult::Cancel)
+            );
+        }
+    }
+
+    #[test]
+    fn delete_and_backspace_unbind() {
+        for code in [VirtualKeyCode::Delete, VirtualKeyCode::Back] {
+            for slot in [BindingSlot::Alternative, BindingSlot::Gamepad] {
+                assert_eq!(
+                    capture(slot).process_event(&key(code, ElementState::Pressed)),
+                    Some(CaptureResult::Unbind)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn keys_and_mouse_bind() {
+        let capture = capture(BindingSlot::Primary);
+        assert_eq!(
+            capture.process_event(&key(VirtualKeyCode::F, ElementState::Pressed)),
+            Some(CaptureResult::Bind(ControlButton::Key(VirtualKeyCode::F)))
+        );
+        assert_eq!(
+            capture.process_event(&key(VirtualKeyCode::F, ElementState::Released)),
+            Some(CaptureResult::Ignore)
+        );
+        assert_eq!(
+            capture.process_event(&mouse(MouseButton::Right)),
+            Some(CaptureResult::Bind(ControlButton::Mouse(2)))
+        );
+        assert_eq!(
+            capture.process_event(&mouse(MouseButton::Other(5))),
+            Some(CaptureResult::Bind(ControlButton::Mouse(5)))
+        );
+        assert_eq!(
+            capture.process_event(&wheel(1.0)),
+            Some(CaptureResult::Bind(ControlButton::WheelUp))
+        );
+        assert_eq!(
+            capture.process_event(&wheel(-1.0)),
+            Some(CaptureResult::Bind(ControlButton::WheelDown))
+        );
+        assert_eq!(capture.process_event(&WindowEvent::Focused(true)), None);
+    }
+
+    #[test]
+    fn gamepad_slot_ignores_keyboard_and_mouse() {
+        let capture = capture(BindingSlot::Gamepad);
+        for event in [
+            key(VirtualKeyCode::F, ElementState::Pressed),
+            mouse(MouseButton::Left),
+            wheel(1.0),
+        ] {
+            assert_eq!(capture.process_event(&event), Some(CaptureResult::Ignore));
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Patrol routes of bots. A bot with a route walks from point to point while it has no
+//! target, bots without a route go for items instead. Routes come from the map: a node named
+//! `PatrolRoute...` is a route and its children are the points in order, a route whose name
+//! ends with `_PingPong` walks back along itself instead of looping.
+
+use fyrox::core::{
+    algebra::Vector3,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+pub const ROUTE_PREFIX: &str = "PatrolRoute";
+pub const PING_PONG_SUFFIX: &str = "_PingPong";
+/// Bot is at a point when it's this close to it on the horizontal plane, in meters.
+const ARRIVAL_DISTANCE: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
+pub enum PatrolMode {
+    /// Last point is followed by the first one.
+    Loop,
+    /// Route is walked to the end and back.
+    PingPong,
+}
+
+impl Default for PatrolMode {
+    fn default() -> Self {
+        Self::Loop
+    }
+}
+
+#[derive(Clone, Default, Debug, Visit)]
+pub struct PatrolRoute {
+    points: Vec<Vector3<f32>>,
+    mode: PatrolMode,
+    current: u32,
+    // Direction of a ping-pong route, `true` is towards the last point.
+    forward: bool,
+    // Bot has left the route to fight, it comes back to the nearest point.
+    paused: bool,
+}
+
+fn horizontal_distance(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    Vector3::new(a.x - b.x, 0.0, a.z - b.z).norm()
+}
+
+impl PatrolRoute {
+    pub fn new(points: Vec<Vector3<f32>>, mode: PatrolMode) -> Self {
+        Self {
+            points,
+            mode,
+            current: 0,
+            forward: true,
+            paused: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[Vector3<f32>] {
+        &self.points
+    }
+
+    /// Leaves the route, next [`Self::advance`] picks the point nearest to the bot.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Point the bot at `position` should walk to, the route moves on to the next point when
+    /// the bot has reached the current one. `None` for empty routes.
+    pub fn advance(&mut self, position: Vector3<f32>) -> Option<Vector3<f32>> {
+        if self.points.is_empty() {
+            return None;
+        }
+        if self.paused {
+            self.paused = false;
+            self.current = self.nearest(position);
+        }
+        if horizontal_distance(self.points[self.current as usize], position) <= ARRIVAL_DISTANCE {
+            self.step();
+        }
+        Some(self.points[self.current as usize])
+    }
+
+    fn nearest(&self, position: Vector3<f32>) -> u32 {
+        self.points
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                horizontal_distance(**a, position).total_cmp(&horizontal_distance(**b, position))
+            })
+            .map_or(0, |(index, _)| index as u32)
+    }
+
+    fn step(&mut self) {
+        let last = self.points.len() as u32 - 1;
+        if last == 0 {
+            return;
+        }
+        self.current = match self.mode {
+            PatrolMode::Loop => (self.current + 1) % (last + 1),
+            PatrolMode::PingPong => {
+                if self.forward && self.current == last {
+                    self.forward = false;
+                } else if !self.forward && self.current == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.current + 1
+                } else {
+                    self.current - 1
+                }
+            }
+        };
+    }
+}
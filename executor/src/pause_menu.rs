@@ -0,0 +1,306 @@
+//! Pause menu, it is shown by Escape while a level is running. Simulation of the level stops
+//! while the menu is open, see `Level::set_paused`. Actions that affect the whole game are
+//! sent as messages, so the menu itself knows nothing about the game state.
+
+use crate::{
+    gui::{create_confirmation_dialog, create_menu_button, DialogHandles, DialogResult},
+    localization::tr,
+    message::Message,
+    save_slot::{SlotWindow, SlotWindowMode},
+};
+use fyrox::{
+    core::{color::Color, parking_lot::Mutex, pool::Handle},
+    engine::Engine,
+    event::{ElementState, Event, VirtualKeyCode, WindowEvent},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{Button, ButtonMessage},
+        decorator::DecoratorMessage,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        Thickness, UiNode, UserInterface,
+    },
+};
+use std::{
+    path::Path,
+    sync::{mpsc::Sender, Arc},
+};
+
+/// Action that must be confirmed before it is done.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum PendingAction {
+    QuitToMenu,
+    QuitToDesktop,
+}
+
+pub struct PauseMenu {
+    sender: Sender<Message>,
+    root: Handle<UiNode>,
+    btn_resume: Handle<UiNode>,
+    btn_options: Handle<UiNode>,
+    btn_save_game: Handle<UiNode>,
+    btn_load_game: Handle<UiNode>,
+    btn_restart: Handle<UiNode>,
+    btn_quit_to_menu: Handle<UiNode>,
+    btn_quit_to_desktop: Handle<UiNode>,
+    // All buttons top to bottom, used for keyboard navigation.
+    buttons: Vec<Handle<UiNode>>,
+    selection: usize,
+    slot_window: SlotWindow,
+    confirm_dialog: DialogHandles,
+    pending_action: Option<PendingAction>,
+}
+
+impl PauseMenu {
+    pub fn new(engine: &mut Engine, sender: Sender<Message>) -> Self {
+        let frame_size = engine.renderer.get_frame_size();
+
+        let font: Font = fyrox::core::futures::executor::block_on(Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            31.0,
+            Font::default_char_set(),
+        ))
+        .unwrap();
+        let font = SharedFont(Arc::new(Mutex::new(font)));
+
+        let slot_window = SlotWindow::new(&mut engine.user_interface, sender.clone());
+
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let buttons = [
+            "pause.resume",
+            "pause.options",
+            "pause.save_game",
+            "pause.load_game",
+            "pause.restart_level",
+            "pause.quit_to_menu",
+            "pause.quit_to_desktop",
+        ]
+        .iter()
+        .map(|key| create_menu_button(ctx, &tr(key), font.clone()))
+        .collect::<Vec<_>>();
+
+        // Background dims the level and catches every click that misses the buttons.
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(frame_size.0 as f32)
+                .with_height(frame_size.1 as f32)
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 140)))
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new().with_child(
+                            WindowBuilder::new(WidgetBuilder::new().on_row(1).on_column(1))
+                                .can_resize(false)
+                                .can_minimize(false)
+                                .can_close(false)
+                                .with_title(WindowTitle::text(tr("pause.paused")))
+                                .with_content(
+                                    StackPanelBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_margin(Thickness::uniform(20.0))
+                                            .with_children(buttons.iter().copied()),
+                                    )
+                                    .build(ctx),
+                                )
+                                .build(ctx),
+                        ),
+                    )
+                    .add_row(Row::stretch())
+                    .add_row(Row::strict(560.0))
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(400.0))
+                    .add_column(Column::stretch())
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        // Text depends on the action, it is set when the dialog opens.
+        let confirm_dialog = create_confirmation_dialog(ctx, &tr("pause.confirm"), "");
+
+        Self {
+            sender,
+            root,
+            btn_resume: buttons[0],
+            btn_options: buttons[1],
+            btn_save_game: buttons[2],
+            btn_load_game: buttons[3],
+            btn_restart: buttons[4],
+            btn_quit_to_menu: buttons[5],
+            btn_quit_to_desktop: buttons[6],
+            buttons,
+            selection: 0,
+            slot_window,
+            confirm_dialog,
+            pending_action: None,
+        }
+    }
+
+    pub fn set_visible(&mut self, ui: &mut UserInterface, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+        if visible {
+            self.select(ui, 0);
+        } else {
+            self.slot_window.close(ui);
+            self.confirm_dialog.close(ui);
+            self.pending_action = None;
+        }
+    }
+
+    pub fn is_visible(&self, ui: &UserInterface) -> bool {
+        ui.node(self.root).visibility()
+    }
+
+    /// Removes every widget of the menu, used to rebuild it with new language.
+    pub fn destroy(&self, ui: &UserInterface) {
+        for widget in [self.root, self.confirm_dialog.root, self.slot_window.window] {
+            ui.send_message(WidgetMessage::remove(widget, MessageDirection::ToWidget));
+        }
+    }
+
+    // Keyboard selection is shown the same way as mouse hover.
+    fn select(&mut self, ui: &UserInterface, selection: usize) {
+        for (i, button) in self.buttons.iter().enumerate() {
+            if let Some(button) = ui.node(*button).cast::<Button>() {
+                ui.send_message(DecoratorMessage::select(
+                    button.decorator,
+                    MessageDirection::ToWidget,
+                    i == selection,
+                ));
+            }
+        }
+        self.selection = selection;
+    }
+
+    fn confirm(&mut self, ui: &UserInterface, action: PendingAction) {
+        let text = match action {
+            PendingAction::QuitToMenu => tr("pause.confirm_quit_to_menu"),
+            PendingAction::QuitToDesktop => tr("pause.confirm_quit_to_desktop"),
+        };
+        self.confirm_dialog.open_with_text(ui, text);
+        self.pending_action = Some(action);
+    }
+
+    /// Handles keyboard navigation of the menu. Returns true if the event was consumed, so
+    /// Escape that closes a dialog won't close the whole menu.
+    pub fn process_input_event(&mut self, engine: &mut Engine, event: &Event<()>) -> bool {
+        let ui = &mut engine.user_interface;
+
+        if let Event::WindowEvent { event, .. } = event {
+            if let WindowEvent::Resized(new_size) = event {
+                ui.send_message(WidgetMessage::width(
+                    self.root,
+                    MessageDirection::ToWidget,
+                    new_size.width as f32,
+                ));
+                ui.send_message(WidgetMessage::height(
+                    self.root,
+                    MessageDirection::ToWidget,
+                    new_size.height as f32,
+                ));
+            }
+
+            if !self.is_visible(ui) {
+                return false;
+            }
+
+            if let WindowEvent::KeyboardInput { input, .. } = event {
+                let key = match input.virtual_keycode {
+                    Some(key) => key,
+                    None => return false,
+                };
+                if input.state != ElementState::Pressed {
+                    return false;
+                }
+
+                if self.slot_window.is_open(ui) {
+                    if key == VirtualKeyCode::Escape {
+                        self.slot_window.close(ui);
+                        return true;
+                    }
+                    // The name is typed in the window.
+                    return false;
+                }
+
+                if self.confirm_dialog.is_open(ui) {
+                    if key == VirtualKeyCode::Escape {
+                        self.confirm_dialog.close(ui);
+                        self.pending_action = None;
+                        return true;
+                    }
+                    return false;
+                }
+
+                match key {
+                    VirtualKeyCode::Up => {
+                        let count = self.buttons.len();
+                        self.select(ui, (self.selection + count - 1) % count);
+                        return true;
+                    }
+                    VirtualKeyCode::Down => {
+                        self.select(ui, (self.selection + 1) % self.buttons.len());
+                        return true;
+                    }
+                    VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                        // Behaves exactly like a click.
+                        ui.send_message(ButtonMessage::click(
+                            self.buttons[self.selection],
+                            MessageDirection::FromWidget,
+                        ));
+                        return true;
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn handle_ui_event(&mut self, engine: &mut Engine, message: &UiMessage) {
+        let ui = &mut engine.user_interface;
+
+        self.slot_window.handle_ui_event(ui, message);
+
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_resume {
+                self.sender.send(Message::ResumeGame).unwrap();
+            } else if message.destination() == self.btn_options {
+                self.sender.send(Message::ShowOptions).unwrap();
+            } else if message.destination() == self.btn_save_game {
+                self.slot_window.open(ui, SlotWindowMode::Save);
+            } else if message.destination() == self.btn_load_game {
+                self.slot_window.open(ui, SlotWindowMode::Load);
+            } else if message.destination() == self.btn_restart {
+                self.sender.send(Message::RestartLevel).unwrap();
+            } else if message.destination() == self.btn_quit_to_menu {
+                self.confirm(ui, PendingAction::QuitToMenu);
+            } else if message.destination() == self.btn_quit_to_desktop {
+                self.confirm(ui, PendingAction::QuitToDesktop);
+            }
+        }
+
+        if let Some(result) = self.confirm_dialog.handle_ui_message(ui, message) {
+            if let (Some(action), DialogResult::Ok) = (self.pending_action.take(), result) {
+                self.sender
+                    .send(match action {
+                        PendingAction::QuitToMenu => Message::QuitToMenu,
+                        PendingAction::QuitToDesktop => Message::QuitGame,
+                    })
+                    .unwrap();
+            }
+        }
+    }
+}
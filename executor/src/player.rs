@@ -1,12 +1,23 @@
 use crate::{
-    character::Character,
-    control_scheme::{ControlButton, ControlScheme},
+    actor::{Actor, TargetDescriptor},
+    character::{Character, FOOTSTEP_SOUNDS, SWING_SOUND},
+    control_scheme::{
+        ActivationMode, ControlButton, ControlButtonDefinition, ControlScheme, MouseCurve,
+    },
+    gamepad::GamepadEvent,
+    item::Item,
     level::UpdateContext,
+    localization::{tr, tr_format},
     message::Message,
+    projectile::ProjectileKind,
+    settings::SoundCategory,
+    toast::NotificationPriority,
+    weapon::{Weapon, QUICK_MELEE},
+    weapon_wheel::MAX_WHEEL_WEAPONS,
 };
 use fyrox::{
     core::{
-        algebra::{UnitQuaternion, Vector3},
+        algebra::{Matrix3, Point3, UnitQuaternion, Vector2, Vector3},
         math::Vector3Ext,
         pool::Handle,
         rand::Rng,
@@ -17,8 +28,8 @@ use fyrox::{
     scene::{
         base::BaseBuilder,
         camera::CameraBuilder,
-        collider::{ColliderBuilder, ColliderShape},
-        graph::physics::CoefficientCombineRule,
+        collider::{ColliderBuilder, ColliderShape, InteractionGroups},
+        graph::physics::{CoefficientCombineRule, RayCastOptions},
         node::Node,
         pivot::PivotBuilder,
         rigidbody::{RigidBodyBuilder, RigidBodyType},
@@ -30,8 +41,63 @@ use fyrox::{
 use std::{
     ops::{Deref, DerefMut},
     sync::{mpsc::Sender, Arc, RwLock},
+    time::Instant,
 };
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum DashDirection {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    /// Dedicated dash key was pressed, dash in direction of movement.
+    Movement,
+}
+
+/// Weapon wheel key must be held this long (in seconds) to open the wheel, shorter press is a
+/// tap that swaps to the last weapon.
+const WEAPON_WHEEL_DELAY: f32 = 0.2;
+/// Mouse motion (in counts) from the center of the wheel needed to highlight a weapon.
+const WEAPON_WHEEL_DEADZONE: f32 = 40.0;
+/// Cursor of the wheel never goes further, so direction can be changed quickly.
+const WEAPON_WHEEL_CURSOR_LIMIT: f32 = 100.0;
+
+struct WeaponWheel {
+    opened_at: Instant,
+    // Weapons at the moment the wheel was opened, the wheel does not change while open.
+    weapons: Vec<Handle<Weapon>>,
+    // Virtual cursor driven by mouse motion, y axis points down.
+    cursor: Vector2<f32>,
+    highlighted: usize,
+}
+
+impl WeaponWheel {
+    fn is_open(&self) -> bool {
+        self.weapons.len() >= 2 && self.opened_at.elapsed().as_secs_f32() >= WEAPON_WHEEL_DELAY
+    }
+
+    // Highlights the sector in given direction, sectors go clockwise starting from the top.
+    fn point(&mut self, direction: Vector2<f32>) {
+        let sector = std::f32::consts::TAU / self.weapons.len() as f32;
+        let angle = direction
+            .x
+            .atan2(-direction.y)
+            .rem_euclid(std::f32::consts::TAU);
+        self.highlighted = ((angle + sector * 0.5) / sector) as usize % self.weapons.len();
+    }
+
+    fn move_cursor(&mut self, delta: Vector2<f32>) {
+        self.cursor += delta;
+        let distance = self.cursor.norm();
+        if distance > WEAPON_WHEEL_CURSOR_LIMIT {
+            self.cursor = self.cursor.scale(WEAPON_WHEEL_CURSOR_LIMIT / distance);
+        }
+        if distance > WEAPON_WHEEL_DEADZONE {
+            self.point(self.cursor);
+        }
+    }
+}
+
 pub struct Controller {
     move_forward: bool,
     move_backward: bool,
@@ -41,6 +107,25 @@ pub struct Controller {
     jump: bool,
     run: bool,
     shoot: bool,
+    aim: bool,
+    // Mouse motion accumulated since last update.
+    mouse_delta: Vector2<f32>,
+    // Analog input from gamepad, already filtered by deadzone.
+    move_axis: Vector2<f32>,
+    look_axis: Vector2<f32>,
+    trigger_shoot: bool,
+    // Weapon selection requests, handled during update because weapons are needed.
+    selected_slot: Option<u32>,
+    quick_swap: bool,
+    weapon_wheel: Option<WeaponWheel>,
+    wheel_selection: Option<Handle<Weapon>>,
+    interact: bool,
+    quick_grenade: bool,
+    quick_melee: bool,
+    toggle_laser: bool,
+    dash: Option<DashDirection>,
+    // Direction and time of last press of a movement key, used to detect double taps.
+    last_tap: Option<(DashDirection, Instant)>,
 }
 
 impl Default for Controller {
@@ -54,6 +139,21 @@ impl Default for Controller {
             jump: false,
             run: false,
             shoot: false,
+            aim: false,
+            mouse_delta: Default::default(),
+            move_axis: Default::default(),
+            look_axis: Default::default(),
+            trigger_shoot: false,
+            selected_slot: None,
+            quick_swap: false,
+            weapon_wheel: None,
+            wheel_selection: None,
+            interact: false,
+            quick_grenade: false,
+            quick_melee: false,
+            toggle_laser: false,
+            dash: None,
+            last_tap: None,
         }
     }
 }
@@ -87,6 +187,32 @@ pub struct Player {
     stand_up_speed: f32,
     #[visit(skip)]
     control_scheme: Option<Arc<RwLock<ControlScheme>>>,
+    // Multiplier for gamepad look speed, less than 1 when crosshair is near enemy.
+    #[visit(skip)]
+    aim_assist_factor: f32,
+    #[visit(skip)]
+    smoothed_mouse_delta: Vector2<f32>,
+    #[visit(skip)]
+    use_cooldown: f32,
+    // Prompt for the object in front of the camera that can be used.
+    #[visit(skip)]
+    use_prompt: Option<String>,
+    #[visit(skip)]
+    quick_grenade_cooldown: f32,
+    #[visit(skip)]
+    quick_melee_cooldown: f32,
+    // Time left until end of current quick action, normal firing is blocked meanwhile and
+    // weapon is moved to the offset of the action.
+    #[visit(skip)]
+    quick_action_time: f32,
+    #[visit(skip)]
+    quick_action_offset: Vector3<f32>,
+    // Inaccuracy in [0; 1] range caused by movement and continuous fire, widens crosshair.
+    #[visit(skip)]
+    spread: f32,
+    // Weapon in hands was shooting on the last update, its trigger is released once it stops.
+    #[visit(skip)]
+    trigger_pulled: bool,
 }
 
 impl Deref for Player {
@@ -131,6 +257,16 @@ impl Default for Player {
             crouch_speed: 0.15,
             stand_up_speed: 0.12,
             control_scheme: None,
+            aim_assist_factor: 1.0,
+            smoothed_mouse_delta: Default::default(),
+            use_cooldown: 0.0,
+            use_prompt: None,
+            quick_grenade_cooldown: 0.0,
+            quick_melee_cooldown: 0.0,
+            quick_action_time: 0.0,
+            quick_action_offset: Default::default(),
+            spread: 0.0,
+            trigger_pulled: false,
         }
     }
 }
@@ -256,13 +392,8 @@ impl Player {
         self.control_scheme = Some(control_scheme);
     }
 
-    fn update_movement(&mut self, context: &mut UpdateContext) {
-        let has_ground_contact = self.character.has_ground_contact(&context.scene.graph);
-
-        let body = context.scene.graph[self.character.body].as_rigid_body_mut();
-        let look = body.look_vector();
-        let side = body.side_vector();
-
+    // Returns normalized direction of movement requested by input.
+    fn move_direction(&self, look: Vector3<f32>, side: Vector3<f32>) -> Option<Vector3<f32>> {
         let mut velocity = Vector3::default();
         if self.controller.move_forward {
             velocity += look;
@@ -276,19 +407,53 @@ impl Player {
         if self.controller.move_right {
             velocity -= side;
         }
+        velocity += look.scale(self.controller.move_axis.y);
+        velocity -= side.scale(self.controller.move_axis.x);
+        velocity.try_normalize(std::f32::EPSILON)
+    }
+
+    fn update_movement(&mut self, context: &mut UpdateContext) {
+        let has_ground_contact = self.character.has_ground_contact(&context.scene.graph);
+
+        if let Some(direction) = self.controller.dash.take() {
+            let body = &context.scene.graph[self.character.body];
+            let look = body.look_vector();
+            let side = body.side_vector();
+            let direction = match direction {
+                DashDirection::Forward => look,
+                DashDirection::Backward => -look,
+                DashDirection::Left => side,
+                DashDirection::Right => -side,
+                DashDirection::Movement => self.move_direction(look, side).unwrap_or(look),
+            };
+            self.character.dash(direction, &context.scene.graph);
+        }
+        let is_dashing = self
+            .character
+            .update_dash(&mut context.scene.graph, context.time.delta);
 
-        let speed_mult = if self.controller.run {
+        let body = context.scene.graph[self.character.body].as_rigid_body_mut();
+        let look = body.look_vector();
+        let side = body.side_vector();
+
+        let mut speed_mult = if self.controller.run {
             self.run_speed_multiplier
         } else {
             1.0
         };
+        let current_weapon = self.character.current_weapon();
+        if context.weapons.contains(current_weapon) {
+            speed_mult *= context.weapons[current_weapon].move_speed_factor();
+        }
 
-        if let Some(normalized_velocity) = velocity.try_normalize(std::f32::EPSILON) {
-            body.set_lin_vel(Vector3::new(
-                normalized_velocity.x * self.move_speed * speed_mult,
-                body.lin_vel().y,
-                normalized_velocity.z * self.move_speed * speed_mult,
-            ));
+        if let Some(normalized_velocity) = self.move_direction(look, side) {
+            if !is_dashing {
+                body.set_lin_vel(Vector3::new(
+                    normalized_velocity.x * self.move_speed * speed_mult,
+                    body.lin_vel().y,
+                    normalized_velocity.z * self.move_speed * speed_mult,
+                ));
+            }
 
             self.weapon_dest_offset.x = 0.01 * (self.weapon_shake_factor * 0.5).cos();
             self.weapon_dest_offset.y = 0.005 * self.weapon_shake_factor.sin();
@@ -304,6 +469,10 @@ impl Player {
             self.weapon_dest_offset = Vector3::default();
         }
 
+        if self.quick_action_time > 0.0 {
+            self.weapon_dest_offset = self.quick_action_offset;
+        }
+
         self.weapon_offset.follow(&self.weapon_dest_offset, 0.1);
 
         if self.controller.jump {
@@ -341,6 +510,34 @@ impl Player {
             self.camera_offset = Vector3::default();
         }
 
+        {
+            let control_scheme = self.control_scheme.clone().unwrap();
+            let control_scheme = control_scheme.read().unwrap();
+            let delta = self.apply_mouse_curve(&control_scheme);
+            self.dest_yaw -= delta.x * control_scheme.mouse_sens_x;
+            let sens_y = if control_scheme.mouse_y_inverse {
+                -control_scheme.mouse_sens_y
+            } else {
+                control_scheme.mouse_sens_y
+            };
+            self.dest_pitch += delta.y * sens_y;
+
+            let settings = &control_scheme.gamepad;
+            // Right stick picks a weapon while the wheel is open.
+            if !self.is_weapon_wheel_open() {
+                let speed = settings.look_sens * self.aim_assist_factor * context.time.delta;
+                self.dest_yaw -= settings.apply_look_curve(self.controller.look_axis.x) * speed;
+                let pitch_speed = if control_scheme.mouse_y_inverse {
+                    -speed
+                } else {
+                    speed
+                };
+                self.dest_pitch -=
+                    settings.apply_look_curve(self.controller.look_axis.y) * pitch_speed;
+            }
+            self.dest_pitch = self.dest_pitch.max(-90.0).min(90.0);
+        }
+
         if self
             .control_scheme
             .clone()
@@ -383,6 +580,26 @@ impl Player {
         self.up_direction = camera_node.up_vector();
     }
 
+    // Consumes accumulated mouse motion and applies response curve to it.
+    fn apply_mouse_curve(&mut self, control_scheme: &ControlScheme) -> Vector2<f32> {
+        // Motion in counts per frame at which exponent curve gives the same result as raw.
+        const REFERENCE_SPEED: f32 = 10.0;
+
+        let delta = std::mem::take(&mut self.controller.mouse_delta);
+        match control_scheme.mouse_curve {
+            MouseCurve::Raw => delta,
+            MouseCurve::Smoothed => {
+                self.smoothed_mouse_delta += (delta - self.smoothed_mouse_delta).scale(0.5);
+                self.smoothed_mouse_delta
+            }
+            MouseCurve::Exponent => delta.map(|d| {
+                d.signum()
+                    * REFERENCE_SPEED
+                    * (d.abs() / REFERENCE_SPEED).powf(control_scheme.mouse_curve_exponent)
+            }),
+        }
+    }
+
     pub fn can_be_removed(&self) -> bool {
         self.character.is_dead()
     }
@@ -402,19 +619,12 @@ impl Player {
         if let Event::DeviceEvent { event, .. } = event {
             match event {
                 DeviceEvent::MouseMotion { delta } => {
-                    self.dest_yaw -= delta.0 as f32 * control_scheme.mouse_sens;
-
-                    let sens = if control_scheme.mouse_y_inverse {
-                        -control_scheme.mouse_sens
-                    } else {
-                        control_scheme.mouse_sens
-                    };
-
-                    self.dest_pitch += delta.1 as f32 * sens;
-                    if self.dest_pitch > 90.0 {
-                        self.dest_pitch = 90.0;
-                    } else if self.dest_pitch < -90.0 {
-                        self.dest_pitch = -90.0;
+                    let delta = Vector2::new(delta.0 as f32, delta.1 as f32);
+                    match self.controller.weapon_wheel.as_mut() {
+                        // Camera stands still while a weapon is picked.
+                        Some(wheel) if wheel.is_open() => wheel.move_cursor(delta),
+                        // Sensitivity and curve are applied during update.
+                        _ => self.controller.mouse_delta += delta,
                     }
                 }
 
@@ -430,9 +640,11 @@ impl Player {
                 DeviceEvent::MouseWheel { delta } => {
                     if let MouseScrollDelta::LineDelta(_, y) = delta {
                         if *y < 0.0 {
-                            self.prev_weapon();
+                            control_button = Some(ControlButton::WheelDown);
+                            control_button_state = ElementState::Pressed;
                         } else if *y > 0.0 {
-                            self.next_weapon();
+                            control_button = Some(ControlButton::WheelUp);
+                            control_button_state = ElementState::Pressed;
                         }
                     }
                 }
@@ -457,91 +669,603 @@ impl Player {
             None => return false,
         };
 
-        match control_button_state {
+        self.apply_button(&control_scheme, control_button, control_button_state);
+
+        false
+    }
+
+    fn apply_button(
+        &mut self,
+        control_scheme: &ControlScheme,
+        control_button: ControlButton,
+        state: ElementState,
+    ) {
+        fn press(definition: &ControlButtonDefinition, active: &mut bool) {
+            match definition.mode {
+                ActivationMode::Hold => *active = true,
+                ActivationMode::Toggle => *active = !*active,
+            }
+        }
+
+        fn release(definition: &ControlButtonDefinition, active: &mut bool) {
+            if definition.mode == ActivationMode::Hold {
+                *active = false;
+            }
+        }
+
+        match state {
             ElementState::Pressed => {
-                if control_button == control_scheme.shoot.button {
+                if control_scheme.shoot.matches(control_button) {
                     self.controller.shoot = true;
-                } else if control_button == control_scheme.move_forward.button {
+                } else if control_scheme.aim.matches(control_button) {
+                    press(&control_scheme.aim, &mut self.controller.aim);
+                } else if control_scheme.move_forward.matches(control_button) {
+                    self.register_tap(control_scheme, DashDirection::Forward);
                     self.controller.move_forward = true;
-                } else if control_button == control_scheme.move_backward.button {
+                } else if control_scheme.move_backward.matches(control_button) {
+                    self.register_tap(control_scheme, DashDirection::Backward);
                     self.controller.move_backward = true;
-                } else if control_button == control_scheme.move_left.button {
+                } else if control_scheme.move_left.matches(control_button) {
+                    self.register_tap(control_scheme, DashDirection::Left);
                     self.controller.move_left = true;
-                } else if control_button == control_scheme.move_right.button {
+                } else if control_scheme.move_right.matches(control_button) {
+                    self.register_tap(control_scheme, DashDirection::Right);
                     self.controller.move_right = true;
-                } else if control_button == control_scheme.crouch.button {
-                    self.controller.crouch = true;
-                } else if control_button == control_scheme.run.button {
-                    self.controller.run = true;
-                } else if control_button == control_scheme.jump.button {
+                } else if control_scheme.dash.matches(control_button) {
+                    self.controller.dash = Some(DashDirection::Movement);
+                } else if control_scheme.crouch.matches(control_button) {
+                    press(&control_scheme.crouch, &mut self.controller.crouch);
+                } else if control_scheme.run.matches(control_button) {
+                    press(&control_scheme.run, &mut self.controller.run);
+                } else if control_scheme.jump.matches(control_button) {
                     self.controller.jump = true;
+                } else if control_scheme.next_weapon.matches(control_button) {
+                    self.next_weapon();
+                } else if control_scheme.prev_weapon.matches(control_button) {
+                    self.prev_weapon();
+                } else if control_scheme.quick_swap.matches(control_button) {
+                    self.controller.quick_swap = true;
+                } else if control_scheme.weapon_wheel.matches(control_button) {
+                    // Key repeat produces lots of press events.
+                    if self.controller.weapon_wheel.is_none() {
+                        self.open_weapon_wheel();
+                    }
+                } else if control_scheme.interact.matches(control_button) {
+                    self.controller.interact = true;
+                } else if control_scheme.quick_grenade.matches(control_button) {
+                    self.controller.quick_grenade = true;
+                } else if control_scheme.quick_melee.matches(control_button) {
+                    self.controller.quick_melee = true;
+                } else if control_scheme.toggle_laser.matches(control_button) {
+                    self.controller.toggle_laser = true;
+                } else if let Some(slot) = control_scheme
+                    .weapon_slots()
+                    .iter()
+                    .position(|definition| definition.matches(control_button))
+                {
+                    self.controller.selected_slot = Some(slot as u32 + 1);
                 }
             }
             ElementState::Released => {
-                if control_button == control_scheme.shoot.button {
+                if control_scheme.shoot.matches(control_button) {
                     self.controller.shoot = false;
-                } else if control_button == control_scheme.move_forward.button {
+                } else if control_scheme.aim.matches(control_button) {
+                    release(&control_scheme.aim, &mut self.controller.aim);
+                } else if control_scheme.move_forward.matches(control_button) {
                     self.controller.move_forward = false;
-                } else if control_button == control_scheme.move_backward.button {
+                } else if control_scheme.move_backward.matches(control_button) {
                     self.controller.move_backward = false;
-                } else if control_button == control_scheme.move_left.button {
+                } else if control_scheme.move_left.matches(control_button) {
                     self.controller.move_left = false;
-                } else if control_button == control_scheme.move_right.button {
+                } else if control_scheme.move_right.matches(control_button) {
                     self.controller.move_right = false;
-                } else if control_button == control_scheme.crouch.button {
-                    self.controller.crouch = false;
-                } else if control_button == control_scheme.run.button {
-                    self.controller.run = false;
+                } else if control_scheme.crouch.matches(control_button) {
+                    release(&control_scheme.crouch, &mut self.controller.crouch);
+                } else if control_scheme.run.matches(control_button) {
+                    release(&control_scheme.run, &mut self.controller.run);
+                } else if control_scheme.weapon_wheel.matches(control_button) {
+                    if let Some(wheel) = self.controller.weapon_wheel.take() {
+                        if wheel.is_open() {
+                            self.controller.wheel_selection =
+                                wheel.weapons.get(wheel.highlighted).copied();
+                        } else {
+                            self.controller.quick_swap = true;
+                        }
+                    }
                 }
             }
         }
+    }
 
-        false
+    // Wheel is built from current weapons every time it opens, but it is shown only after
+    // the key was held for a while.
+    fn open_weapon_wheel(&mut self) {
+        let weapons = self
+            .character
+            .weapons()
+            .iter()
+            .take(MAX_WHEEL_WEAPONS)
+            .copied()
+            .collect::<Vec<_>>();
+        let highlighted = (self.character.current_weapon as usize).min(weapons.len().max(1) - 1);
+        self.controller.weapon_wheel = Some(WeaponWheel {
+            opened_at: Instant::now(),
+            weapons,
+            cursor: Default::default(),
+            highlighted,
+        });
+    }
+
+    /// Returns weapons of the open weapon wheel and index of the highlighted one, `None`
+    /// while the wheel is closed.
+    pub fn weapon_wheel(&self) -> Option<(&[Handle<Weapon>], usize)> {
+        self.controller
+            .weapon_wheel
+            .as_ref()
+            .filter(|wheel| wheel.is_open())
+            .map(|wheel| (wheel.weapons.as_slice(), wheel.highlighted))
+    }
+
+    pub fn is_weapon_wheel_open(&self) -> bool {
+        self.weapon_wheel().is_some()
+    }
+
+    // Requests a dash if a movement key was pressed twice within short period of time.
+    fn register_tap(&mut self, control_scheme: &ControlScheme, direction: DashDirection) {
+        let is_held = match direction {
+            DashDirection::Forward => self.controller.move_forward,
+            DashDirection::Backward => self.controller.move_backward,
+            DashDirection::Left => self.controller.move_left,
+            DashDirection::Right => self.controller.move_right,
+            DashDirection::Movement => false,
+        };
+        // Ignore key repeat.
+        if is_held || !control_scheme.double_tap_dash {
+            return;
+        }
+
+        let now = Instant::now();
+        match self.controller.last_tap {
+            Some((last_direction, time))
+                if last_direction == direction
+                    && now.duration_since(time).as_secs_f32() <= control_scheme.double_tap_time =>
+            {
+                self.controller.dash = Some(direction);
+                self.controller.last_tap = None;
+            }
+            _ => self.controller.last_tap = Some((direction, now)),
+        }
+    }
+
+    /// Releases every held and toggled action. Must be called whenever player can miss
+    /// release events (menu opened, window lost focus) or must stop acting (death).
+    pub fn reset_input(&mut self) {
+        self.controller = Default::default();
+    }
+
+    pub fn process_gamepad_event(&mut self, event: &GamepadEvent) {
+        let control_scheme = match self.control_scheme.clone() {
+            Some(x) => x,
+            None => return,
+        };
+        let control_scheme = control_scheme.read().unwrap();
+
+        match *event {
+            GamepadEvent::Button { index, pressed } => {
+                let state = if pressed {
+                    ElementState::Pressed
+                } else {
+                    ElementState::Released
+                };
+                self.apply_button(&control_scheme, ControlButton::GamepadButton(index), state);
+            }
+            GamepadEvent::Axis { axis, value } => {
+                let settings = &control_scheme.gamepad;
+                if axis == settings.move_x {
+                    self.controller.move_axis.x = settings.apply_deadzone(value);
+                } else if axis == settings.move_y {
+                    self.controller.move_axis.y = settings.apply_deadzone(value);
+                } else if axis == settings.look_x {
+                    // Curve is applied during update, store raw value.
+                    self.controller.look_axis.x = value;
+                } else if axis == settings.look_y {
+                    self.controller.look_axis.y = value;
+                } else if axis == settings.shoot {
+                    self.controller.trigger_shoot = value >= settings.trigger_threshold;
+                }
+            }
+        }
+    }
+
+    // Aim assist slows down gamepad look when crosshair is within few degrees of an enemy.
+    // It never rotates camera on its own.
+    fn update_aim_assist(&mut self, self_handle: Handle<Actor>, targets: &[TargetDescriptor]) {
+        const ASSIST_ANGLE: f32 = 4.0;
+        const ASSIST_FACTOR: f32 = 0.55;
+
+        self.aim_assist_factor = 1.0;
+
+        if let Some(control_scheme) = self.control_scheme.as_ref() {
+            if !control_scheme.read().unwrap().gamepad.aim_assist {
+                return;
+            }
+        }
+
+        for target in targets {
+            if target.handle == self_handle || target.health <= 0.0 {
+                continue;
+            }
+            // Aim at chest rather than feet.
+            let to_target = target.position + Vector3::new(0.0, 0.5, 0.0) - self.head_position;
+            if let Some(to_target) = to_target.try_normalize(std::f32::EPSILON) {
+                let angle = to_target
+                    .dot(&self.look_direction)
+                    .max(-1.0)
+                    .min(1.0)
+                    .acos()
+                    .to_degrees();
+                if angle < ASSIST_ANGLE {
+                    self.aim_assist_factor = ASSIST_FACTOR;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns clockwise angle (in radians) between view direction and direction to given
+    /// point in horizontal plane, zero means that the point is straight ahead.
+    pub fn horizontal_angle_to(&self, point: Vector3<f32>) -> f32 {
+        let to_point = point - self.feet_position;
+        let forward = Vector3::new(self.look_direction.x, 0.0, self.look_direction.z);
+        let right = Vector3::new(-forward.z, 0.0, forward.x);
+        to_point.dot(&right).atan2(to_point.dot(&forward))
+    }
+
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
+    pub fn is_sprinting(&self) -> bool {
+        self.controller.run
+            && (self.controller.move_forward
+                || self.controller.move_backward
+                || self.controller.move_left
+                || self.controller.move_right
+                || self.controller.move_axis.norm_squared() > 0.0)
+    }
+
+    pub fn use_prompt(&self) -> Option<&str> {
+        self.use_prompt.as_deref()
+    }
+
+    /// Returns true if items must be picked up with the "use" key instead of walking over them.
+    pub fn is_manual_pickup(&self) -> bool {
+        self.control_scheme
+            .as_ref()
+            .map_or(false, |control_scheme| {
+                control_scheme.read().unwrap().manual_pickup
+            })
+    }
+
+    // Returns closest item that is in front of the camera and close enough to be picked up.
+    fn find_item_in_reach(&self, context: &UpdateContext) -> Option<Handle<Item>> {
+        const REACH: f32 = 2.5;
+        const MAX_ANGLE: f32 = 30.0;
+
+        let mut closest = None;
+        let mut closest_distance = REACH;
+        for (handle, item) in context.items.pair_iter() {
+            if item.is_picked_up() {
+                continue;
+            }
+            let to_item = item.position(&context.scene.graph) - self.head_position;
+            let distance = to_item.norm();
+            if distance >= closest_distance {
+                continue;
+            }
+            if let Some(to_item) = to_item.try_normalize(std::f32::EPSILON) {
+                let angle = to_item
+                    .dot(&self.look_direction)
+                    .max(-1.0)
+                    .min(1.0)
+                    .acos()
+                    .to_degrees();
+                if angle < MAX_ANGLE {
+                    closest = Some(handle);
+                    closest_distance = distance;
+                }
+            }
+        }
+        closest
+    }
+
+    // Looks for a door, button or item (if manual pickup is enabled) in front of the camera,
+    // updates use prompt and uses the object if requested.
+    fn update_use(&mut self, self_handle: Handle<Actor>, context: &mut UpdateContext) {
+        const USE_DISTANCE: f32 = 2.0;
+        // Prevents spamming when the key is held, key repeat produces lots of press events.
+        const USE_COOLDOWN: f32 = 0.5;
+
+        self.use_cooldown = (self.use_cooldown - context.time.delta).max(0.0);
+        let interact = std::mem::take(&mut self.controller.interact);
+        self.use_prompt = None;
+
+        let control_scheme = match self.control_scheme.clone() {
+            Some(x) => x,
+            None => return,
+        };
+        let control_scheme = control_scheme.read().unwrap();
+
+        let mut query_buffer = Vec::new();
+        context.scene.graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(self.head_position),
+                ray_direction: self.look_direction,
+                max_len: USE_DISTANCE,
+                groups: InteractionGroups::default(),
+                sort_results: true,
+            },
+            &mut query_buffer,
+        );
+        // Closest obstacle except player itself is the only one that can be used.
+        let usable = query_buffer
+            .iter()
+            .find(|intersection| intersection.collider != self.character.collider)
+            .map(|intersection| context.usables.find_by_collider(intersection.collider))
+            .unwrap_or_default();
+
+        let target = if usable.is_some() {
+            Some((
+                context.usables.get(usable).action(),
+                Message::UseObject {
+                    actor: self_handle,
+                    usable,
+                },
+            ))
+        } else if control_scheme.manual_pickup {
+            self.find_item_in_reach(context).map(|item| {
+                (
+                    "use.pick_up",
+                    Message::PickUpItem {
+                        actor: self_handle,
+                        item,
+                    },
+                )
+            })
+        } else {
+            None
+        };
+
+        if let Some((action, message)) = target {
+            let button = control_scheme
+                .interact
+                .bindings()
+                .next()
+                .unwrap_or(ControlButton::Unbound);
+            self.use_prompt = Some(tr_format("use.prompt", &[&button.name(), &tr(action)]));
+            if interact && self.use_cooldown <= 0.0 {
+                self.use_cooldown = USE_COOLDOWN;
+                self.character
+                    .sender
+                    .as_ref()
+                    .unwrap()
+                    .send(message)
+                    .unwrap();
+            }
+        }
+    }
+
+    // Handles grenade throw and melee bash, both are done without switching current weapon.
+    fn update_quick_actions(&mut self, self_handle: Handle<Actor>, context: &mut UpdateContext) {
+        const QUICK_GRENADE_COOLDOWN: f32 = 1.0;
+        const QUICK_MELEE_COOLDOWN: f32 = 0.6;
+        const QUICK_ACTION_DURATION: f32 = 0.3;
+
+        let dt = context.time.delta;
+        self.quick_grenade_cooldown = (self.quick_grenade_cooldown - dt).max(0.0);
+        self.quick_melee_cooldown = (self.quick_melee_cooldown - dt).max(0.0);
+        self.quick_action_time = (self.quick_action_time - dt).max(0.0);
+
+        let grenade = std::mem::take(&mut self.controller.quick_grenade);
+        let melee = std::mem::take(&mut self.controller.quick_melee);
+        if self.is_dead() || self.quick_action_time > 0.0 {
+            return;
+        }
+
+        let sender = self.character.sender.clone().unwrap();
+        if melee && self.quick_melee_cooldown <= 0.0 {
+            self.quick_melee_cooldown = QUICK_MELEE_COOLDOWN;
+            self.quick_action_time = QUICK_ACTION_DURATION;
+            // Shove weapon forward.
+            self.quick_action_offset = Vector3::new(0.0, 0.02, 0.15);
+
+            sender
+                .send(Message::MeleeAttack {
+                    actor: self_handle,
+                    origin: self.head_position,
+                    direction: self.look_direction,
+                    sweep: QUICK_MELEE,
+                })
+                .unwrap();
+            sender
+                .send(Message::PlaySound {
+                    path: SWING_SOUND.into(),
+                    position: self.head_position,
+                    gain: 1.0,
+                    rolloff_factor: 2.0,
+                    radius: 3.0,
+                    category: SoundCategory::Sfx,
+                })
+                .unwrap();
+        } else if grenade && self.quick_grenade_cooldown <= 0.0 && self.character.grenades > 0 {
+            self.character.grenades -= 1;
+            self.quick_grenade_cooldown = QUICK_GRENADE_COOLDOWN;
+            self.quick_action_time = QUICK_ACTION_DURATION;
+            // Lower weapon while throwing.
+            self.quick_action_offset = Vector3::new(0.0, -0.15, 0.0);
+
+            let initial_velocity = context.scene.graph[self.character.body]
+                .as_rigid_body()
+                .lin_vel();
+            // Throw slightly upwards so grenade flies by an arc.
+            let direction = (self.look_direction + Vector3::new(0.0, 0.2, 0.0))
+                .try_normalize(std::f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+
+            sender
+                .send(Message::CreateProjectile {
+                    kind: ProjectileKind::Grenade,
+                    position: self.head_position + self.look_direction.scale(0.5),
+                    direction,
+                    initial_velocity,
+                    owner: self.character.current_weapon(),
+                    basis: Matrix3::identity(),
+                })
+                .unwrap();
+        }
     }
 
-    pub fn update(&mut self, context: &mut UpdateContext) {
+    pub fn update(
+        &mut self,
+        self_handle: Handle<Actor>,
+        context: &mut UpdateContext,
+        targets: &[TargetDescriptor],
+    ) {
+        if self.is_dead() {
+            self.reset_input();
+        }
+
+        self.update_aim_assist(self_handle, targets);
         self.update_movement(context);
+        self.update_use(self_handle, context);
+        self.update_quick_actions(self_handle, context);
+
+        if let Some(slot) = self.controller.selected_slot.take() {
+            self.character.select_weapon_slot(slot, context.weapons);
+        }
+        if self.controller.quick_swap {
+            self.controller.quick_swap = false;
+            self.character.quick_swap_weapon();
+        }
+        if let Some(weapon) = self.controller.wheel_selection.take() {
+            // Weapon could be lost while the wheel was open.
+            if let Some(index) = self.character.weapons().iter().position(|w| *w == weapon) {
+                self.character.set_current_weapon(index);
+            }
+        }
+        if let Some(wheel) = self.controller.weapon_wheel.as_mut() {
+            // Stick is pushed in y-up convention, wheel cursor uses screen coordinates.
+            let stick = Vector2::new(self.controller.look_axis.x, -self.controller.look_axis.y);
+            if wheel.is_open() && stick.norm() > 0.5 {
+                wheel.point(stick);
+            }
+        }
 
         if let Some(current_weapon_handle) = self
             .character
             .weapons
             .get(self.character.current_weapon as usize)
         {
-            let initial_velocity = context.scene.graph[self.character.body]
+            let velocity = context.scene.graph[self.character.body]
                 .as_rigid_body()
                 .lin_vel();
 
-            if self.controller.shoot {
+            if std::mem::take(&mut self.controller.toggle_laser)
+                && context.weapons.contains(*current_weapon_handle)
+            {
+                self.character
+                    .sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::SetLaserEnabled {
+                        weapon: *current_weapon_handle,
+                        enabled: !context.weapons[*current_weapon_handle].is_laser_enabled(),
+                    })
+                    .unwrap();
+            }
+
+            if context.weapons.contains(*current_weapon_handle) {
+                let weapon = &context.weapons[*current_weapon_handle];
+                // Quick actions take the weapon away from the sights.
+                let aiming = self.controller.aim
+                    && self.quick_action_time <= 0.0
+                    && weapon.definition().ads.is_some();
+                if weapon.is_aiming() != aiming {
+                    self.character
+                        .sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::SetAiming {
+                            weapon: *current_weapon_handle,
+                            aiming,
+                        })
+                        .unwrap();
+                }
+            }
+
+            let is_shooting = self.controller.shoot || self.controller.trigger_shoot;
+
+            let max_speed = self.move_speed * self.run_speed_multiplier;
+            let movement = (Vector2::new(velocity.x, velocity.z).norm() / max_speed).min(1.0);
+            let target_spread = if is_shooting { 1.0 } else { movement };
+            self.spread += (target_spread - self.spread) * (context.time.delta * 8.0).min(1.0);
+
+            let trigger_pulled = is_shooting && self.quick_action_time <= 0.0;
+            // Hint once per pull, the weapon itself clicks on every pull.
+            if trigger_pulled
+                && !self.trigger_pulled
+                && context.weapons.contains(*current_weapon_handle)
+                && !self
+                    .character
+                    .has_ammo_for(&context.weapons[*current_weapon_handle])
+            {
+                self.character
+                    .sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::Notify {
+                        text: tr("notify.no_ammo"),
+                        icon: None,
+                        priority: NotificationPriority::Normal,
+                    })
+                    .unwrap();
+            }
+            if trigger_pulled {
                 self.character
                     .sender
                     .as_ref()
                     .unwrap()
                     .send(Message::ShootWeapon {
                         weapon: *current_weapon_handle,
-                        initial_velocity,
                         direction: None,
                     })
                     .unwrap();
+            } else if self.trigger_pulled {
+                self.character
+                    .sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::ReleaseTrigger {
+                        weapon: *current_weapon_handle,
+                    })
+                    .unwrap();
             }
+            self.trigger_pulled = trigger_pulled;
         }
 
         if self.path_len > 2.0 {
-            let footsteps = [
-                "data/sounds/footsteps/FootStep_shoe_stone_step1.wav",
-                "data/sounds/footsteps/FootStep_shoe_stone_step2.wav",
-                "data/sounds/footsteps/FootStep_shoe_stone_step3.wav",
-                "data/sounds/footsteps/FootStep_shoe_stone_step4.wav",
-            ];
             self.character
                 .sender
                 .as_ref()
                 .unwrap()
                 .send(Message::PlaySound {
-                    path: footsteps[rand::thread_rng().gen_range(0..footsteps.len())].into(),
+                    path: FOOTSTEP_SOUNDS[rand::thread_rng().gen_range(0..FOOTSTEP_SOUNDS.len())]
+                        .into(),
                     position: self.character.position(&context.scene.graph),
                     gain: 1.0,
                     rolloff_factor: 2.0,
                     radius: 3.0,
+                    category: SoundCategory::Sfx,
                 })
                 .unwrap();
 
@@ -0,0 +1,209 @@
+//! Resources of the gameplay are loaded while the level loads. Bots, weapons, items and
+//! projectiles request their models and sounds when they're spawned, the first rocket of a
+//! match would hitch on loading of its model otherwise. [`PreloadCache`] is owned by the level
+//! and holds every resource until the level is gone, so the resource manager keeps them alive
+//! and spawn-time requests get resources that are already loaded.
+
+use crate::{
+    bot::{Bot, BotKind},
+    character::{FOOTSTEP_SOUNDS, SWING_SOUND},
+    effects,
+    item::{Item, ItemKind},
+    level::{HEADSHOT_CONFIRM_SOUND, ITEM_PICKUP_SOUND, KILL_CONFIRM_SOUND},
+    message::Message,
+    projectile::{self, Projectile, ProjectileKind},
+    surface::{self, SurfaceKind},
+    weapon::{Weapon, WeaponKind, VENT_SOUND},
+};
+use fyrox::{
+    engine::resource_manager::ResourceManager,
+    resource::{model::Model, texture::Texture},
+    scene::sound::SoundBufferResource,
+    utils::log::{Log, MessageKind},
+};
+use std::{collections::BTreeSet, sync::mpsc::Sender};
+
+/// Paths of everything that can be spawned on a level, every path is listed once.
+#[derive(Default)]
+pub struct PreloadList {
+    pub models: BTreeSet<&'static str>,
+    pub textures: BTreeSet<&'static str>,
+    pub sounds: BTreeSet<&'static str>,
+}
+
+impl PreloadList {
+    pub fn gameplay() -> Self {
+        let mut list = Self::default();
+
+        // Any kind of bot can be spawned, by respawns, level scripts or the benchmark.
+        for kind in BotKind::ALL {
+            let definition = Bot::get_definition(kind);
+            list.models.extend([
+                definition.model,
+                definition.idle_animation,
+                definition.walk_animation,
+                definition.aim_animation,
+                definition.whip_animation,
+                definition.jump_animation,
+                definition.falling_animation,
+                definition.hit_reaction_animation,
+                definition.dying_animation,
+                definition.dead_animation,
+            ]);
+            list.sounds.extend(
+                definition
+                    .idle_sounds
+                    .iter()
+                    .chain(definition.aggro_sounds)
+                    .chain(definition.pain_sounds)
+                    .chain(&definition.death_sound)
+                    .copied(),
+            );
+            if let Some(strafe) = definition.strafe_animations {
+                list.models.extend([strafe.left, strafe.right]);
+            }
+            if let Some(slam) = definition.second_phase.and_then(|phase| phase.slam) {
+                list.models.insert(slam.animation);
+            }
+        }
+        for kind in WeaponKind::ALL {
+            let definition = Weapon::get_definition(kind);
+            list.models.insert(definition.model);
+            list.sounds.insert(definition.shot_sound);
+            list.sounds.extend(definition.reload_sound);
+            list.sounds.extend(definition.dry_fire_sound);
+            if definition.overheat_threshold > 0.0 {
+                list.sounds.insert(VENT_SOUND);
+            }
+        }
+        for kind in ItemKind::ALL {
+            list.models.insert(Item::get_definition(kind).model);
+        }
+        for kind in ProjectileKind::ALL {
+            list.sounds
+                .insert(Projectile::get_definition(kind).impact_sound);
+        }
+        list.models.insert(projectile::ROCKET_MODEL);
+        list.textures
+            .extend([projectile::LIGHT_TEXTURE, projectile::GRENADE_TEXTURE]);
+
+        let explosions = std::iter::once(None)
+            .chain(SurfaceKind::ALL.into_iter().map(Some))
+            .map(surface::explosion_definition);
+        for explosion in explosions {
+            list.sounds.extend(explosion.sounds.iter().copied());
+            if let Some(debris) = explosion.debris.as_ref() {
+                list.textures.insert(debris.texture);
+            }
+        }
+        list.textures.extend([
+            effects::CIRCLE_TEXTURE,
+            effects::SMOKE_TEXTURE,
+            effects::STAR_TEXTURE,
+        ]);
+
+        list.sounds.extend(FOOTSTEP_SOUNDS);
+        list.sounds.extend([
+            SWING_SOUND,
+            ITEM_PICKUP_SOUND,
+            KILL_CONFIRM_SOUND,
+            HEADSHOT_CONFIRM_SOUND,
+        ]);
+
+        list
+    }
+
+    pub fn count(&self) -> usize {
+        self.models.len() + self.textures.len() + self.sounds.len()
+    }
+}
+
+#[derive(Default)]
+pub struct PreloadCache {
+    models: Vec<Model>,
+    textures: Vec<Texture>,
+    sounds: Vec<SoundBufferResource>,
+}
+
+fn report_failure(path: &str, reason: impl std::fmt::Debug) {
+    Log::writeln(
+        MessageKind::Error,
+        format!("Unable to preload {}. Reason: {:?}", path, reason),
+    );
+}
+
+impl PreloadCache {
+    /// Loads everything in the list. Resources are requested all at once so they load in
+    /// parallel, then each one is awaited and the progress is sent as
+    /// `Message::PreloadProgress`. Resources that fail to load are logged and left out, their
+    /// spawn-time requests will fail the same way.
+    pub async fn load(
+        resource_manager: &ResourceManager,
+        list: &PreloadList,
+        sender: &Sender<Message>,
+    ) -> Self {
+        let models = list
+            .models
+            .iter()
+            .map(|path| (*path, resource_manager.request_model(path)))
+            .collect::<Vec<_>>();
+        let textures = list
+            .textures
+            .iter()
+            .map(|path| (*path, resource_manager.request_texture(path)))
+            .collect::<Vec<_>>();
+        let sounds = list
+            .sounds
+            .iter()
+            .map(|path| (*path, resource_manager.request_sound_buffer(path)))
+            .collect::<Vec<_>>();
+
+        let total = list.count();
+        let mut done = 0;
+        let mut progress = || {
+            done += 1;
+            sender
+                .send(Message::PreloadProgress { done, total })
+                .unwrap();
+        };
+
+        let mut cache = Self::default();
+        for (path, model) in models {
+            match model.await {
+                Ok(model) => cache.models.push(model),
+                Err(e) => report_failure(path, e),
+            }
+            progress();
+        }
+        for (path, texture) in textures {
+            match texture.await {
+                Ok(texture) => cache.textures.push(texture),
+                Err(e) => report_failure(path, e),
+            }
+            progress();
+        }
+        for (path, sound) in sounds {
+            match sound.await {
+                Ok(sound) => cache.sounds.push(sound),
+                Err(e) => report_failure(path, e),
+            }
+            progress();
+        }
+
+        Log::writeln(
+            MessageKind::Information,
+            format!(
+                "Preloaded {} of {} gameplay resources.",
+                cache.count(),
+                total
+            ),
+        );
+
+        cache
+    }
+
+    /// Number of resources that were loaded.
+    pub fn count(&self) -> usize {
+        self.models.len() + self.textures.len() + self.sounds.len()
+    }
+}
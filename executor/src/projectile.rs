@@ -1,9 +1,11 @@
 use crate::{
     actor::{Actor, ActorContainer},
     effects::EffectKind,
-    message::Message,
-    weapon::{Weapon, WeaponContainer},
-    GameTime,
+    message::{DamageHit, Message},
+    settings::SoundCategory,
+    surface,
+    weapon::{DamageFalloff, Weapon, WeaponContainer},
+    GameTime, FIXED_FPS,
 };
 use fyrox::{
     core::{
@@ -28,13 +30,34 @@ use fyrox::{
         Scene,
     },
 };
-use std::{collections::HashSet, path::PathBuf, sync::mpsc::Sender};
+use std::{collections::HashMap, path::PathBuf, sync::mpsc::Sender};
+
+/// Sprite of plasma balls and bullets.
+pub const LIGHT_TEXTURE: &str = "data/particles/light_01.png";
+pub const ROCKET_MODEL: &str = "data/models/rocket.FBX";
+pub const GRENADE_TEXTURE: &str = "data/models/grenade.tga";
+/// Projectile of a moving shooter spawns as far ahead as the shooter moves in this many
+/// seconds, so it can't spawn inside the shooter's capsule.
+const SPAWN_LEAD_TIME: f32 = 0.05;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
 pub enum ProjectileKind {
     Plasma,
     Bullet,
     Rocket,
+    Grenade,
+    /// One of the projectiles of a shotgun shot, weaker and shorter-lived than a bullet.
+    Pellet,
+}
+
+impl ProjectileKind {
+    pub const ALL: [ProjectileKind; 5] = [
+        ProjectileKind::Plasma,
+        ProjectileKind::Bullet,
+        ProjectileKind::Rocket,
+        ProjectileKind::Grenade,
+        ProjectileKind::Pellet,
+    ];
 }
 
 #[derive(Visit)]
@@ -52,13 +75,19 @@ pub struct Projectile {
     /// Handle of weapons from which projectile was fired.
     pub owner: Handle<Weapon>,
     initial_velocity: Vector3<f32>,
+    /// Damage of a hit, comes from the definition of the weapon that fired the projectile.
+    damage: f32,
+    damage_falloff: Option<DamageFalloff>,
+    /// Spawn position of the projectile, damage falls off with distance from it.
+    origin: Vector3<f32>,
     /// Position of projectile on the previous frame, it is used to simulate
     /// continuous intersection detection from fast moving projectiles.
     last_position: Vector3<f32>,
     #[visit(skip)]
     pub sender: Option<Sender<Message>>,
+    // Actors hit on this frame, direct hits know the exact hit point.
     #[visit(skip)]
-    hits: HashSet<Hit>,
+    hits: HashMap<Hit, Option<DamageHit>>,
 }
 
 impl Default for Projectile {
@@ -72,6 +101,9 @@ impl Default for Projectile {
             rotation_angle: 0.0,
             owner: Default::default(),
             initial_velocity: Default::default(),
+            damage: 0.0,
+            damage_falloff: None,
+            origin: Default::default(),
             last_position: Default::default(),
             sender: None,
             hits: Default::default(),
@@ -80,13 +112,41 @@ impl Default for Projectile {
 }
 
 pub struct ProjectileDefinition {
+    /// Damage of projectiles that were not fired from a weapon, like thrown grenades.
     damage: f32,
     speed: f32,
     lifetime: f32,
     /// Means that movement of projectile controlled by code, not physics.
     /// However projectile still could have rigid body to detect collisions.
     is_kinematic: bool,
-    impact_sound: &'static str,
+    pub impact_sound: &'static str,
+    /// Radius of explosion when projectile dies, every actor within it takes full damage.
+    /// Zero means that only actor that was hit directly is damaged.
+    explosion_radius: f32,
+    /// Fraction of the shooter's velocity that is added to the projectile at launch, zero for
+    /// bullets that are too fast for it to matter.
+    velocity_inheritance: f32,
+}
+
+impl ProjectileDefinition {
+    /// Velocity the projectile gets from a shooter that moves with given velocity.
+    pub fn inherited_velocity(&self, shooter_velocity: Vector3<f32>) -> Vector3<f32> {
+        shooter_velocity.scale(self.velocity_inheritance)
+    }
+
+    /// Speed in meters per second, kinematic projectiles move by `speed` every frame.
+    pub fn speed_per_second(&self) -> f32 {
+        if self.is_kinematic {
+            self.speed * FIXED_FPS
+        } else {
+            self.speed
+        }
+    }
+
+    /// Distance in meters that the projectile flies before it dies.
+    pub fn range(&self) -> f32 {
+        self.speed_per_second() * self.lifetime
+    }
 }
 
 impl Projectile {
@@ -99,6 +159,8 @@ impl Projectile {
                     lifetime: 10.0,
                     is_kinematic: true,
                     impact_sound: "data/sounds/bullet_impact_concrete.ogg",
+                    explosion_radius: 0.0,
+                    velocity_inheritance: 1.0,
                 };
                 &DEFINITION
             }
@@ -109,6 +171,8 @@ impl Projectile {
                     lifetime: 10.0,
                     is_kinematic: true,
                     impact_sound: "data/sounds/bullet_impact_concrete.ogg",
+                    explosion_radius: 0.0,
+                    velocity_inheritance: 0.0,
                 };
                 &DEFINITION
             }
@@ -119,6 +183,34 @@ impl Projectile {
                     lifetime: 10.0,
                     is_kinematic: true,
                     impact_sound: "data/sounds/explosion.ogg",
+                    explosion_radius: 0.0,
+                    velocity_inheritance: 1.0,
+                };
+                &DEFINITION
+            }
+            ProjectileKind::Grenade => {
+                static DEFINITION: ProjectileDefinition = ProjectileDefinition {
+                    damage: 60.0,
+                    // Grenade is thrown by physics, speed is in meters per second.
+                    speed: 12.0,
+                    // Fuse time.
+                    lifetime: 2.0,
+                    is_kinematic: false,
+                    impact_sound: "data/sounds/explosion.ogg",
+                    explosion_radius: 3.0,
+                    velocity_inheritance: 1.0,
+                };
+                &DEFINITION
+            }
+            ProjectileKind::Pellet => {
+                static DEFINITION: ProjectileDefinition = ProjectileDefinition {
+                    damage: 8.0,
+                    speed: 0.75,
+                    lifetime: 0.5,
+                    is_kinematic: true,
+                    impact_sound: "data/sounds/bullet_impact_concrete.ogg",
+                    explosion_radius: 0.0,
+                    velocity_inheritance: 0.0,
                 };
                 &DEFINITION
             }
@@ -138,6 +230,8 @@ impl Projectile {
         basis: Matrix3<f32>,
     ) -> Self {
         let definition = Self::get_definition(kind);
+        let initial_velocity = definition.inherited_velocity(initial_velocity);
+        let position = spawn_position(position, dir, initial_velocity);
 
         let (model, body) = {
             match &kind {
@@ -159,9 +253,7 @@ impl Projectile {
                             )
                             .with_size(size)
                             .with_color(color)
-                            .with_texture(
-                                resource_manager.request_texture("data/particles/light_01.png"),
-                            )
+                            .with_texture(resource_manager.request_texture(LIGHT_TEXTURE))
                             .build(&mut scene.graph);
                             model
                         },
@@ -177,7 +269,7 @@ impl Projectile {
 
                     (model, Some(body))
                 }
-                ProjectileKind::Bullet => {
+                ProjectileKind::Bullet | ProjectileKind::Pellet => {
                     let model = SpriteBuilder::new(
                         BaseBuilder::new().with_local_transform(
                             TransformBuilder::new()
@@ -186,16 +278,13 @@ impl Projectile {
                         ),
                     )
                     .with_size(0.05)
-                    .with_texture(resource_manager.request_texture("data/particles/light_01.png"))
+                    .with_texture(resource_manager.request_texture(LIGHT_TEXTURE))
                     .build(&mut scene.graph);
 
                     (model, None)
                 }
                 ProjectileKind::Rocket => {
-                    let resource = resource_manager
-                        .request_model("data/models/rocket.FBX")
-                        .await
-                        .unwrap();
+                    let resource = resource_manager.request_model(ROCKET_MODEL).await.unwrap();
                     let model = resource.instantiate_geometry(scene);
                     scene.graph[model]
                         .local_transform_mut()
@@ -210,6 +299,31 @@ impl Projectile {
                     scene.graph.link_nodes(light, model);
                     (model, None)
                 }
+                ProjectileKind::Grenade => {
+                    let model = SpriteBuilder::new(BaseBuilder::new())
+                        .with_size(0.1)
+                        .with_texture(resource_manager.request_texture(GRENADE_TEXTURE))
+                        .build(&mut scene.graph);
+                    let collider = ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::ball(0.1))
+                        .build(&mut scene.graph);
+                    let body = RigidBodyBuilder::new(
+                        BaseBuilder::new()
+                            .with_local_transform(
+                                TransformBuilder::new()
+                                    .with_local_position(position)
+                                    .build(),
+                            )
+                            .with_children(&[model, collider]),
+                    )
+                    .with_body_type(RigidBodyType::Dynamic)
+                    .build(&mut scene.graph);
+                    scene.graph[body]
+                        .as_rigid_body_mut()
+                        .set_lin_vel(dir.scale(definition.speed) + initial_velocity);
+
+                    (model, Some(body))
+                }
             }
         };
 
@@ -217,6 +331,8 @@ impl Projectile {
             lifetime: definition.lifetime,
             body,
             initial_velocity,
+            damage: definition.damage,
+            origin: position,
             dir: dir.try_normalize(std::f32::EPSILON).unwrap_or(Vector3::y()),
             kind,
             model,
@@ -231,6 +347,12 @@ impl Projectile {
         self.lifetime <= 0.0
     }
 
+    /// Replaces the damage of the definition with the damage of the weapon that fired it.
+    pub fn set_damage(&mut self, damage: f32, falloff: Option<DamageFalloff>) {
+        self.damage = damage;
+        self.damage_falloff = falloff;
+    }
+
     pub fn kill(&mut self) {
         self.lifetime = 0.0;
     }
@@ -254,6 +376,11 @@ impl Projectile {
         };
 
         let mut effect_position = None;
+        // Surface of the level the projectile hit, `None` if it hit an actor or nothing.
+        let mut impact_surface = None;
+        // Impact effects spray away from the surface that was hit.
+        let mut impact_normal = Vector3::y();
+        let mut hit_actor = false;
 
         // Do ray based intersection tests for every kind of projectiles. This will help to handle
         // fast moving projectiles.
@@ -270,6 +397,11 @@ impl Projectile {
             &mut query_buffer,
         );
 
+        // Grenades bounce off everything and explode only when fuse time is out.
+        if self.kind == ProjectileKind::Grenade {
+            query_buffer.clear();
+        }
+
         // List of hits sorted by distance from ray origin.
         'hit_loop: for hit in query_buffer.iter() {
             let collider = scene.graph[hit.collider].as_collider();
@@ -278,6 +410,8 @@ impl Projectile {
             if matches!(collider.shape(), ColliderShape::Trimesh(_)) {
                 self.kill();
                 effect_position = Some(hit.position.coords);
+                impact_surface = surface::surface_at(&scene.graph, hit);
+                impact_normal = hit.normal;
                 break 'hit_loop;
             } else {
                 for (actor_handle, actor) in actors.pair_iter() {
@@ -285,13 +419,24 @@ impl Projectile {
                         let weapon = &weapons[self.owner];
                         // Ignore intersections with owners of weapon.
                         if weapon.owner() != actor_handle {
-                            self.hits.insert(Hit {
-                                actor: actor_handle,
-                                who: weapon.owner(),
-                            });
+                            self.hits.insert(
+                                Hit {
+                                    actor: actor_handle,
+                                    who: weapon.owner(),
+                                },
+                                ray.dir.try_normalize(std::f32::EPSILON).map(|direction| {
+                                    DamageHit {
+                                        position: hit.position.coords,
+                                        direction,
+                                        normal: hit.normal,
+                                    }
+                                }),
+                            );
 
                             self.kill();
                             effect_position = Some(hit.position.coords);
+                            impact_normal = hit.normal;
+                            hit_actor = true;
                             break 'hit_loop;
                         }
                     }
@@ -301,7 +446,9 @@ impl Projectile {
 
         // Movement of kinematic projectiles are controlled explicitly.
         if self.definition().is_kinematic {
-            let total_velocity = self.dir.scale(self.definition().speed);
+            // Speed of kinematic projectiles is per frame, initial velocity is per second.
+            let total_velocity = self.dir.scale(self.definition().speed)
+                + self.initial_velocity.scale(1.0 / FIXED_FPS);
 
             // Special case for projectiles with rigid body.
             if let Some(body) = self.body.as_ref() {
@@ -331,37 +478,83 @@ impl Projectile {
         if self.lifetime <= 0.0 {
             let pos = effect_position.unwrap_or_else(|| self.get_position(&scene.graph));
 
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::CreateEffect {
-                    kind: EffectKind::BulletImpact,
-                    position: pos,
-                })
-                .unwrap();
+            let radius = self.definition().explosion_radius;
+            if radius > 0.0 && weapons.contains(self.owner) {
+                let who = weapons[self.owner].owner();
+                for (actor_handle, actor) in actors.pair_iter() {
+                    if actor.position(&scene.graph).metric_distance(&pos) <= radius {
+                        // Explosion hits the whole body, direct hit is kept if there was one.
+                        self.hits
+                            .entry(Hit {
+                                actor: actor_handle,
+                                who,
+                            })
+                            .or_insert(None);
+                    }
+                }
+            }
+
+            let (kind, sound) = if radius > 0.0 {
+                // Projectiles that explode without hitting the level are either on the ground
+                // (grenades, hits on actors) or in the air.
+                let surface = impact_surface.or_else(|| surface::surface_below(&scene.graph, pos));
+                let sounds = surface::explosion_definition(surface).sounds;
+                (
+                    Some(EffectKind::Explosion(surface)),
+                    sounds[rand::thread_rng().gen_range(0..sounds.len())],
+                )
+            } else if hit_actor {
+                // Hit actor bleeds, the blood splash of its color comes with the damage.
+                (None, self.definition().impact_sound)
+            } else {
+                (
+                    Some(EffectKind::BulletImpact),
+                    self.definition().impact_sound,
+                )
+            };
+
+            if let Some(kind) = kind {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::CreateEffect {
+                        kind,
+                        position: pos,
+                        direction: impact_normal
+                            .try_normalize(std::f32::EPSILON)
+                            .unwrap_or_else(Vector3::y),
+                    })
+                    .unwrap();
+            }
 
             self.sender
                 .as_ref()
                 .unwrap()
                 .send(Message::PlaySound {
-                    path: PathBuf::from(self.definition().impact_sound),
+                    path: PathBuf::from(sound),
                     position: pos,
                     gain: 1.0,
                     rolloff_factor: 4.0,
                     radius: 3.0,
+                    category: SoundCategory::Sfx,
                 })
                 .unwrap();
         }
 
-        let definition = self.definition();
-        for hit in self.hits.drain() {
+        for (hit, damage_hit) in self.hits.drain() {
+            let hit_position = damage_hit.map_or(position, |damage_hit| damage_hit.position);
             self.sender
                 .as_ref()
                 .unwrap()
                 .send(Message::DamageActor {
                     actor: hit.actor,
                     who: hit.who,
-                    amount: definition.damage,
+                    amount: hit_damage(
+                        self.damage,
+                        self.damage_falloff,
+                        hit_position.metric_distance(&self.origin),
+                    ),
+                    hit: damage_hit,
                 })
                 .unwrap();
         }
@@ -373,6 +566,14 @@ impl Projectile {
         graph[self.model].global_position()
     }
 
+    /// Velocity in meters per second.
+    pub fn velocity(&self, graph: &Graph) -> Vector3<f32> {
+        match self.body {
+            Some(body) if !self.definition().is_kinematic => graph[body].as_rigid_body().lin_vel(),
+            _ => self.dir.scale(self.definition().speed_per_second()) + self.initial_velocity,
+        }
+    }
+
     fn clean_up(&mut self, scene: &mut Scene) {
         if let Some(body) = self.body.as_ref() {
             scene.graph.remove_node(*body);
@@ -384,20 +585,62 @@ impl Projectile {
     }
 }
 
+/// Damage of a hit `distance` meters away from the spawn point of the projectile.
+fn hit_damage(damage: f32, falloff: Option<DamageFalloff>, distance: f32) -> f32 {
+    damage * falloff.map_or(1.0, |falloff| falloff.multiplier(distance))
+}
+
+/// Moves the spawn point of a projectile along its direction by the distance the shooter
+/// covers in `SPAWN_LEAD_TIME`, a standing shooter spawns it right at `position`.
+fn spawn_position(
+    position: Vector3<f32>,
+    direction: Vector3<f32>,
+    inherited_velocity: Vector3<f32>,
+) -> Vector3<f32> {
+    match direction.try_normalize(std::f32::EPSILON) {
+        Some(direction) => position + direction.scale(inherited_velocity.norm() * SPAWN_LEAD_TIME),
+        None => position,
+    }
+}
+
 #[derive(Hash, Eq, PartialEq)]
 struct Hit {
     actor: Handle<Actor>,
     who: Handle<Actor>,
 }
 
+/// Projectiles slower than this can be dodged by bots, in meters per second.
+pub const DODGEABLE_SPEED: f32 = 40.0;
+
+/// What bots see of a projectile in flight.
+#[derive(Copy, Clone, Debug)]
+pub struct ProjectileDescriptor {
+    pub position: Vector3<f32>,
+    /// Velocity in meters per second.
+    pub velocity: Vector3<f32>,
+    /// Actor that fired the projectile, `None` if the weapon is gone.
+    pub shooter: Handle<Actor>,
+}
+
 #[derive(Visit)]
 pub struct ProjectileContainer {
     pool: Pool<Projectile>,
+    // Rebuilt every frame, after the projectiles have moved.
+    #[visit(skip)]
+    descriptors: Vec<ProjectileDescriptor>,
 }
 
 impl ProjectileContainer {
     pub fn new() -> Self {
-        Self { pool: Pool::new() }
+        Self {
+            pool: Pool::new(),
+            descriptors: Default::default(),
+        }
+    }
+
+    /// Projectiles in flight that are slow enough to be dodged.
+    pub fn descriptors(&self) -> &[ProjectileDescriptor] {
+        &self.descriptors
     }
 
     pub fn add(&mut self, projectile: Projectile) -> Handle<Projectile> {
@@ -408,6 +651,10 @@ impl ProjectileContainer {
         self.pool.iter_mut()
     }
 
+    pub fn count(&self) -> u32 {
+        self.pool.alive_count()
+    }
+
     pub fn update(
         &mut self,
         scene: &mut Scene,
@@ -423,5 +670,69 @@ impl ProjectileContainer {
         }
 
         self.pool.retain(|proj| !proj.is_dead());
+
+        self.descriptors.clear();
+        for projectile in self.pool.iter() {
+            if projectile.definition().speed_per_second() >= DODGEABLE_SPEED {
+                continue;
+            }
+            self.descriptors.push(ProjectileDescriptor {
+                position: projectile.get_position(&scene.graph),
+                velocity: projectile.velocity(&scene.graph),
+                shooter: if weapons.contains(projectile.owner) {
+                    weapons[projectile.owner].owner()
+                } else {
+                    Handle::NONE
+                },
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapon::{WeaponAttack, WeaponKind};
+
+    #[test]
+    fn weapons_with_same_projectile_deal_own_damage() {
+        let m4 = Weapon::get_definition(WeaponKind::M4);
+        let ak47 = Weapon::get_definition(WeaponKind::Ak47);
+        for definition in [m4, ak47] {
+            assert!(matches!(
+                definition.attack,
+                WeaponAttack::Projectile(ProjectileKind::Bullet)
+            ));
+        }
+        for distance in [1.0, 30.0, 100.0] {
+            let m4_damage = hit_damage(m4.damage, m4.damage_falloff, distance);
+            let ak47_damage = hit_damage(ak47.damage, ak47.damage_falloff, distance);
+            assert!((m4_damage - ak47_damage).abs() > 0.1, "{}", distance);
+        }
+        assert_eq!(hit_damage(m4.damage, m4.damage_falloff, 1.0), m4.damage);
+    }
+
+    #[test]
+    fn standing_shooter_adds_nothing() {
+        let position = Vector3::new(1.0, 2.0, 3.0);
+        for kind in ProjectileKind::ALL {
+            let velocity = Projectile::get_definition(kind).inherited_velocity(Vector3::default());
+            assert_eq!(velocity, Vector3::default());
+            assert_eq!(spawn_position(position, Vector3::z(), velocity), position);
+        }
+    }
+
+    #[test]
+    fn falling_shooter_passes_its_fall_on() {
+        let fall = Vector3::new(0.0, -12.0, 0.0);
+        let grenade = Projectile::get_definition(ProjectileKind::Grenade).inherited_velocity(fall);
+        assert_eq!(grenade, fall);
+        let bullet = Projectile::get_definition(ProjectileKind::Bullet).inherited_velocity(fall);
+        assert_eq!(bullet, Vector3::default());
+
+        // Shot ahead spawns ahead of the shooter by the distance it falls in the lead time.
+        let position = Vector3::new(0.0, 5.0, 0.0);
+        let spawn = spawn_position(position, Vector3::z(), grenade);
+        assert!((spawn - Vector3::new(0.0, 5.0, 12.0 * SPAWN_LEAD_TIME)).norm() < 1.0e-5);
     }
 }
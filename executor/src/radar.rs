@@ -0,0 +1,260 @@
+//! Round radar of the HUD in the top-left corner. It shows actors and key items around the player,
+//! the player is in the middle and looks up. Level decides which actors are shown, so the
+//! radar does not give away enemies that hide and keep quiet.
+
+use crate::hud::{RadarBlipKind, RadarData};
+use fyrox::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        brush::Brush,
+        canvas::CanvasBuilder,
+        grid::{Column, GridBuilder, Row},
+        image::{ImageBuilder, ImageMessage},
+        message::MessageDirection,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    utils,
+};
+
+/// Distance in meters from the player to the edge of the radar.
+pub const RADIUS_METERS: f32 = 30.0;
+/// Most actors and items the radar can show at once, widgets are made up front.
+const MAX_DOTS: usize = 32;
+const MAX_ICONS: usize = 16;
+const DOT_SIZE: f32 = 6.0;
+const ICON_SIZE: f32 = 14.0;
+const ENEMY_COLOR: Color = Color::opaque(220, 30, 30);
+const ALLY_COLOR: Color = Color::opaque(40, 200, 40);
+const CIRCLE_PATH: &str = "data/ui/circle.png";
+
+pub struct Radar {
+    pub root: Handle<UiNode>,
+    center: Handle<UiNode>,
+    dots: Vec<Handle<UiNode>>,
+    icons: Vec<Handle<UiNode>>,
+    // Texture path of every icon, so textures are requested only when they change.
+    icon_paths: Vec<Option<&'static str>>,
+    // Amount of dots and icons shown on previous frame, the rest is hidden.
+    visible_dots: usize,
+    visible_icons: usize,
+    size: f32,
+    visible: bool,
+    resource_manager: ResourceManager,
+}
+
+impl Radar {
+    pub fn new(
+        ctx: &mut BuildContext,
+        resource_manager: ResourceManager,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        let circle = utils::into_gui_texture(resource_manager.request_texture(CIRCLE_PATH));
+
+        // Dots are the circle tinted with the background.
+        let dots = (0..MAX_DOTS)
+            .map(|_| {
+                ImageBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_width(DOT_SIZE)
+                        .with_height(DOT_SIZE),
+                )
+                .with_texture(circle.clone())
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let icons = (0..MAX_ICONS)
+            .map(|_| {
+                ImageBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_width(ICON_SIZE)
+                        .with_height(ICON_SIZE),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let center = ImageBuilder::new(
+            WidgetBuilder::new()
+                .with_width(DOT_SIZE)
+                .with_height(DOT_SIZE)
+                .with_background(Brush::Solid(Color::WHITE)),
+        )
+        .with_texture(circle.clone())
+        .build(ctx);
+
+        let canvas = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_child(center)
+                .with_children(icons.iter().copied())
+                .with_children(dots.iter().copied()),
+        )
+        .build(ctx);
+
+        let background = ImageBuilder::new(
+            WidgetBuilder::new().with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 120))),
+        )
+        .with_texture(circle)
+        .build(ctx);
+
+        let root = GridBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_visibility(false)
+                .with_hit_test_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_margin(Thickness::uniform(20.0))
+                .with_child(background)
+                .with_child(canvas),
+        )
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        Self {
+            root,
+            center,
+            dots,
+            icons,
+            icon_paths: vec![None; MAX_ICONS],
+            visible_dots: 0,
+            visible_icons: 0,
+            size: 0.0,
+            visible: false,
+            resource_manager,
+        }
+    }
+
+    fn set_visibility(ui: &mut UserInterface, widget: Handle<UiNode>, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            widget,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    /// Places a widget of given size centered at the offset from the player.
+    fn place(
+        &self,
+        ui: &mut UserInterface,
+        widget: Handle<UiNode>,
+        offset: Vector2<f32>,
+        size: f32,
+    ) {
+        let half = self.size * 0.5;
+        // Things out of the circle stick to its edge, so the player knows the direction.
+        let offset = if offset.norm() > RADIUS_METERS {
+            offset.normalize() * RADIUS_METERS
+        } else {
+            offset
+        };
+        let scale = (half - size * 0.5) / RADIUS_METERS;
+        ui.send_message(WidgetMessage::desired_position(
+            widget,
+            MessageDirection::ToWidget,
+            Vector2::new(
+                half + offset.x * scale - size * 0.5,
+                half - offset.y * scale - size * 0.5,
+            ),
+        ));
+    }
+
+    /// Syncs the radar with surroundings of the player, `None` hides it.
+    pub fn set_data(&mut self, ui: &mut UserInterface, data: Option<&RadarData>) {
+        let data = match data {
+            Some(data) => data,
+            None => {
+                if self.visible {
+                    self.visible = false;
+                    Self::set_visibility(ui, self.root, false);
+                }
+                return;
+            }
+        };
+
+        if !self.visible {
+            self.visible = true;
+            Self::set_visibility(ui, self.root, true);
+        }
+
+        if data.size != self.size {
+            self.size = data.size;
+            ui.send_message(WidgetMessage::width(
+                self.root,
+                MessageDirection::ToWidget,
+                data.size,
+            ));
+            ui.send_message(WidgetMessage::height(
+                self.root,
+                MessageDirection::ToWidget,
+                data.size,
+            ));
+            self.place(ui, self.center, Vector2::default(), DOT_SIZE);
+        }
+
+        let mut dot_count = 0;
+        let mut icon_count = 0;
+        for blip in data.blips.iter() {
+            match blip.kind {
+                RadarBlipKind::Enemy | RadarBlipKind::Ally => {
+                    if dot_count == MAX_DOTS {
+                        continue;
+                    }
+                    let dot = self.dots[dot_count];
+                    dot_count += 1;
+                    let color = if blip.kind == RadarBlipKind::Enemy {
+                        ENEMY_COLOR
+                    } else {
+                        ALLY_COLOR
+                    };
+                    ui.send_message(WidgetMessage::background(
+                        dot,
+                        MessageDirection::ToWidget,
+                        Brush::Solid(color),
+                    ));
+                    self.place(ui, dot, blip.offset, DOT_SIZE);
+                }
+                RadarBlipKind::Item(icon_path) => {
+                    if icon_count == MAX_ICONS {
+                        continue;
+                    }
+                    let icon = self.icons[icon_count];
+                    if self.icon_paths[icon_count] != Some(icon_path) {
+                        self.icon_paths[icon_count] = Some(icon_path);
+                        ui.send_message(ImageMessage::texture(
+                            icon,
+                            MessageDirection::ToWidget,
+                            Some(utils::into_gui_texture(
+                                self.resource_manager.request_texture(icon_path),
+                            )),
+                        ));
+                    }
+                    icon_count += 1;
+                    self.place(ui, icon, blip.offset, ICON_SIZE);
+                }
+            }
+        }
+
+        for (i, dot) in self.dots.iter().enumerate() {
+            let visible = i < dot_count;
+            if visible != (i < self.visible_dots) {
+                Self::set_visibility(ui, *dot, visible);
+            }
+        }
+        for (i, icon) in self.icons.iter().enumerate() {
+            let visible = i < icon_count;
+            if visible != (i < self.visible_icons) {
+                Self::set_visibility(ui, *icon, visible);
+            }
+        }
+        self.visible_dots = dot_count;
+        self.visible_icons = icon_count;
+    }
+}
@@ -0,0 +1,265 @@
+//! Reverb of the level. Volumes of the map named `ReverbZone_<preset>` make sounds inside of
+//! them echo as described by the preset in [`REVERB_PRESETS_PATH`], the innermost zone that
+//! holds the listener wins. Outside of every zone [`DEFAULT_PRESET`] is used. Reverb follows
+//! the listener with a short crossfade, so walking through a door doesn't snap the echo.
+
+use crate::data_file;
+use fyrox::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::sound::effect::Effect,
+    utils::log::{Log, MessageKind},
+};
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+/// Map of preset names to [`ReverbPreset`]s.
+pub const REVERB_PRESETS_PATH: &str = "data/sounds/reverb_presets.ron";
+pub const DEFAULT_PRESET: &str = "Outdoor";
+pub const ZONE_PREFIX: &str = "ReverbZone_";
+const CROSSFADE_DURATION: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReverbPreset {
+    /// Seconds.
+    pub decay_time: f32,
+    /// Level of the reverberated sound, the rest of the mix is the dry sound.
+    pub wet: f32,
+}
+
+impl Default for ReverbPreset {
+    // Nearly dry, used when even the default preset is missing in the table.
+    fn default() -> Self {
+        Self {
+            decay_time: 0.8,
+            wet: 0.1,
+        }
+    }
+}
+
+impl ReverbPreset {
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let t = t.max(0.0).min(1.0);
+        Self {
+            decay_time: self.decay_time + (other.decay_time - self.decay_time) * t,
+            wet: self.wet + (other.wet - self.wet) * t,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(transparent)]
+pub struct ReverbPresetTable {
+    presets: HashMap<String, ReverbPreset>,
+}
+
+impl ReverbPresetTable {
+    /// Loads presets from given file, every zone is dry if the file is missing or malformed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        data_file::load::<Self, _>(path, "reverb presets").validated()
+    }
+
+    /// Drops presets without decay and keeps wet level within `0..1`.
+    fn validated(mut self) -> Self {
+        self.presets.retain(|name, preset| {
+            if preset.decay_time > 0.0 {
+                preset.wet = preset.wet.max(0.0).min(1.0);
+                true
+            } else {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Reverb preset {} has no decay time, skipped.", name),
+                );
+                false
+            }
+        });
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<ReverbPreset> {
+        self.presets.get(name).copied()
+    }
+}
+
+static TABLE: Mutex<Option<ReverbPresetTable>> = Mutex::new(None);
+
+/// Preset of given name, the table is loaded on first use.
+pub fn preset(name: &str) -> Option<ReverbPreset> {
+    TABLE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| ReverbPresetTable::load(REVERB_PRESETS_PATH))
+        .get(name)
+}
+
+pub fn default_preset() -> ReverbPreset {
+    preset(DEFAULT_PRESET).unwrap_or_default()
+}
+
+/// Preset name of the zone node, numbers at the end are ignored so a map can have
+/// `ReverbZone_Hall1` and `ReverbZone_Hall2`.
+pub fn zone_preset_name(node_name: &str) -> Option<&str> {
+    node_name
+        .strip_prefix(ZONE_PREFIX)
+        .map(|name| name.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .filter(|name| !name.is_empty())
+}
+
+#[derive(Visit, Default)]
+pub struct ReverbZone {
+    pub bounds: AxisAlignedBoundingBox,
+    pub preset: String,
+}
+
+impl ReverbZone {
+    fn volume(&self) -> f32 {
+        let size = self.bounds.max - self.bounds.min;
+        size.x * size.y * size.z
+    }
+}
+
+/// The smallest zone that holds given point, nested zones are always smaller than the one
+/// around them.
+pub fn innermost_zone(zones: &[ReverbZone], point: Vector3<f32>) -> Option<&ReverbZone> {
+    zones
+        .iter()
+        .filter(|zone| zone.bounds.is_contains_point(point))
+        .min_by(|a, b| a.volume().total_cmp(&b.volume()))
+}
+
+/// Crossfades reverb between presets as the listener moves.
+#[derive(Default)]
+pub struct ReverbMixer {
+    from: ReverbPreset,
+    to: ReverbPreset,
+    // Time since the fade to `to` has started, `None` until the first preset is set.
+    elapsed: Option<f32>,
+}
+
+impl ReverbMixer {
+    /// Moves the mix towards given preset and returns the preset to be heard now. Very first
+    /// preset is taken immediately, so a level doesn't start with a fade.
+    pub fn update(&mut self, target: ReverbPreset, dt: f32) -> ReverbPreset {
+        match self.elapsed {
+            None => {
+                self.from = target;
+                self.to = target;
+                self.elapsed = Some(CROSSFADE_DURATION);
+            }
+            Some(elapsed) => {
+                if target != self.to {
+                    // Fade starts from what is heard right now, even if previous fade is not
+                    // over yet.
+                    self.from = self.current();
+                    self.to = target;
+                    self.elapsed = Some(0.0);
+                } else {
+                    self.elapsed = Some((elapsed + dt).min(CROSSFADE_DURATION));
+                }
+            }
+        }
+        self.current()
+    }
+
+    pub fn current(&self) -> ReverbPreset {
+        let t = self
+            .elapsed
+            .map_or(1.0, |elapsed| elapsed / CROSSFADE_DURATION);
+        self.from.lerp(&self.to, t)
+    }
+}
+
+/// Applies the preset to a reverb effect, other effects are left as is.
+#[allow(irrefutable_let_patterns)]
+pub fn apply_preset(effect: &mut Effect, preset: &ReverbPreset) {
+    if let Effect::Reverb(reverb) = effect {
+        reverb.set_decay_time(preset.decay_time);
+        reverb.set_wet(preset.wet);
+        reverb.set_dry(1.0 - preset.wet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<ReverbPresetTable, String> {
+        data_file::parse::<ReverbPresetTable>(text).map(ReverbPresetTable::validated)
+    }
+
+    #[test]
+    fn shipped_presets_parse() {
+        let table = parse(include_str!("../../data/sounds/reverb_presets.ron")).unwrap();
+        assert!(table.get(DEFAULT_PRESET).is_some());
+        assert_eq!(
+            table.get("Cave"),
+            Some(ReverbPreset {
+                decay_time: 5.0,
+                wet: 0.65
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_presets_are_rejected() {
+        assert!(parse(r#"{ "Room": (decay_time: 1.2) }"#).is_err());
+        assert!(parse(r#"{ "Room": (decay_time: 1.2, wet: 0.3, dry: 0.7) }"#).is_err());
+        assert!(parse(r#"{ "Room": 1.2 }"#).is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_fixed_or_skipped() {
+        let table = parse(
+            r#"{ "Silent": (decay_time: 0.0, wet: 0.5), "Loud": (decay_time: 1.0, wet: 2.0) }"#,
+        )
+        .unwrap();
+        assert_eq!(table.get("Silent"), None);
+        assert_eq!(table.get("Loud").map(|preset| preset.wet), Some(1.0));
+    }
+
+    #[test]
+    fn zone_names_strip_numbers() {
+        assert_eq!(zone_preset_name("ReverbZone_Hall2"), Some("Hall"));
+        assert_eq!(zone_preset_name("ReverbZone_Cave"), Some("Cave"));
+        assert_eq!(zone_preset_name("ReverbZone_12"), None);
+        assert_eq!(zone_preset_name("Hall"), None);
+    }
+
+    #[test]
+    fn innermost_zone_wins() {
+        let zone = |size: f32, preset: &str| ReverbZone {
+            bounds: AxisAlignedBoundingBox::from_min_max(
+                Vector3::repeat(-size),
+                Vector3::repeat(size),
+            ),
+            preset: preset.to_owned(),
+        };
+        let zones = [zone(10.0, "Hall"), zone(2.0, "Room")];
+        let preset_at = |point| innermost_zone(&zones, point).map(|zone| zone.preset.as_str());
+        assert_eq!(preset_at(Vector3::zeros()), Some("Room"));
+        assert_eq!(preset_at(Vector3::new(5.0, 0.0, 0.0)), Some("Hall"));
+        assert_eq!(preset_at(Vector3::new(50.0, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn mixer_crossfades_after_first_preset() {
+        let room = ReverbPreset {
+            decay_time: 1.0,
+            wet: 0.2,
+        };
+        let hall = ReverbPreset {
+            decay_time: 3.0,
+            wet: 0.6,
+        };
+        let mut mixer = ReverbMixer::default();
+        assert_eq!(mixer.update(room, 0.1), room);
+        assert_eq!(mixer.update(hall, 0.1), room);
+        let halfway = mixer.update(hall, CROSSFADE_DURATION * 0.5);
+        assert!((halfway.decay_time - 2.0).abs() < 1.0e-4);
+        assert_eq!(mixer.update(hall, CROSSFADE_DURATION), hall);
+    }
+}
@@ -0,0 +1,420 @@
+//! Save slots and the window to pick one. Every slot is a pair of files in `saves` directory:
+//! binary game state and small text file with metadata, so the list of slots can be shown
+//! without loading whole saves.
+
+use crate::{
+    control_scheme::parse_config_value,
+    leader_board::format_time,
+    localization::{tr, tr_format},
+    message::Message,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        border::BorderBuilder,
+        button::{Button, ButtonBuilder, ButtonMessage},
+        decorator::DecoratorBuilder,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write,
+    path::PathBuf,
+    sync::mpsc::Sender,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const SAVE_SLOT_COUNT: usize = 8;
+const SAVES_DIR: &str = "saves";
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn format_age(seconds: u64) -> String {
+    if seconds < 60 {
+        tr("save.just_now")
+    } else if seconds < 3600 {
+        tr_format("save.minutes_ago", &[&(seconds / 60)])
+    } else if seconds < 24 * 3600 {
+        tr_format("save.hours_ago", &[&(seconds / 3600)])
+    } else {
+        tr_format("save.days_ago", &[&(seconds / (24 * 3600))])
+    }
+}
+
+/// Metadata of a save.
+pub struct SaveSlot {
+    pub name: String,
+    /// Seconds since Unix epoch.
+    pub saved_at: u64,
+    /// Time spent on the level.
+    pub level_time: f32,
+}
+
+impl SaveSlot {
+    pub fn new(name: String, level_time: f32) -> Self {
+        Self {
+            name,
+            saved_at: now(),
+            level_time,
+        }
+    }
+
+    /// Path to the game state of given slot.
+    pub fn data_path(slot: usize) -> PathBuf {
+        PathBuf::from(SAVES_DIR).join(format!("slot_{}.bin", slot))
+    }
+
+    fn info_path(slot: usize) -> PathBuf {
+        PathBuf::from(SAVES_DIR).join(format!("slot_{}.ron", slot))
+    }
+
+    /// Returns `None` for empty slots.
+    pub fn load(slot: usize) -> Option<Self> {
+        if !Self::data_path(slot).exists() {
+            return None;
+        }
+        let text = std::fs::read_to_string(Self::info_path(slot)).ok()?;
+        let config = ron::from_str::<BTreeMap<String, String>>(&text).ok()?;
+        let mut info = Self::new(String::new(), 0.0);
+        parse_config_value(&config, "name", &mut info.name);
+        parse_config_value(&config, "saved_at", &mut info.saved_at);
+        parse_config_value(&config, "level_time", &mut info.level_time);
+        Some(info)
+    }
+
+    /// Must be called after the game state was written, so a failed save won't leave
+    /// metadata of a slot without a game state.
+    pub fn save(&self, slot: usize) -> std::io::Result<()> {
+        let mut config = BTreeMap::new();
+        config.insert("name".to_owned(), self.name.clone());
+        config.insert("saved_at".to_owned(), self.saved_at.to_string());
+        config.insert("level_time".to_owned(), self.level_time.to_string());
+
+        let text = ron::ser::to_string_pretty(&config, Default::default())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        File::create(Self::info_path(slot))?.write_all(text.as_bytes())
+    }
+
+    /// Returns the most recently written slot, `None` if there are no saves at all.
+    pub fn newest() -> Option<usize> {
+        (0..SAVE_SLOT_COUNT)
+            .filter_map(|slot| Self::load(slot).map(|info| (slot, info.saved_at)))
+            .max_by_key(|&(_, saved_at)| saved_at)
+            .map(|(slot, _)| slot)
+    }
+
+    /// Creates directory for saves if needed.
+    pub fn prepare_dir() -> std::io::Result<()> {
+        std::fs::create_dir_all(SAVES_DIR)
+    }
+
+    fn description(&self) -> String {
+        tr_format(
+            "save.slot_description",
+            &[
+                &self.name,
+                &format_time(self.level_time),
+                &format_age(now().saturating_sub(self.saved_at)),
+            ],
+        )
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SlotWindowMode {
+    Save,
+    Load,
+}
+
+/// Window with the list of save slots, it is used both to save and to load a game.
+pub struct SlotWindow {
+    pub window: Handle<UiNode>,
+    sender: Sender<Message>,
+    mode: SlotWindowMode,
+    list: Handle<UiNode>,
+    slot_texts: Vec<Handle<UiNode>>,
+    slots: Vec<Option<SaveSlot>>,
+    selection: Option<usize>,
+    name_row: Handle<UiNode>,
+    tb_name: Handle<UiNode>,
+    name: String,
+    btn_confirm: Handle<UiNode>,
+    btn_cancel: Handle<UiNode>,
+}
+
+impl SlotWindow {
+    pub fn new(ui: &mut UserInterface, sender: Sender<Message>) -> Self {
+        let ctx = &mut ui.build_ctx();
+        let margin = Thickness::uniform(2.0);
+
+        let mut slot_texts = Vec::new();
+        let items = (0..SAVE_SLOT_COUNT)
+            .map(|_| {
+                let text = TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_height(30.0)
+                        .with_margin(Thickness::left(5.0)),
+                )
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx);
+                slot_texts.push(text);
+                DecoratorBuilder::new(
+                    BorderBuilder::new(WidgetBuilder::new().with_child(text))
+                        .with_stroke_thickness(Thickness {
+                            left: 1.0,
+                            top: 0.0,
+                            right: 1.0,
+                            bottom: 1.0,
+                        }),
+                )
+                .build(ctx)
+            })
+            .collect();
+
+        let list;
+        let name_row;
+        let tb_name;
+        let btn_confirm;
+        let btn_cancel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(500.0))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            list = ListViewBuilder::new(
+                                WidgetBuilder::new().on_row(0).with_margin(margin),
+                            )
+                            .with_items(items)
+                            .build(ctx);
+                            list
+                        })
+                        .with_child({
+                            name_row = GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new().on_column(0).with_margin(margin),
+                                        )
+                                        .with_text(&tr("save.name"))
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .build(ctx),
+                                    )
+                                    .with_child({
+                                        tb_name = TextBoxBuilder::new(
+                                            WidgetBuilder::new().on_column(1).with_margin(margin),
+                                        )
+                                        .build(ctx);
+                                        tb_name
+                                    }),
+                            )
+                            .add_column(Column::strict(80.0))
+                            .add_column(Column::stretch())
+                            .add_row(Row::stretch())
+                            .build(ctx);
+                            name_row
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        btn_confirm = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(margin),
+                                        )
+                                        .build(ctx);
+                                        btn_confirm
+                                    })
+                                    .with_child({
+                                        btn_cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(margin),
+                                        )
+                                        .with_text(&tr("save.cancel"))
+                                        .build(ctx);
+                                        btn_cancel
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::auto())
+                .add_row(Row::strict(36.0))
+                .add_row(Row::strict(36.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            sender,
+            mode: SlotWindowMode::Load,
+            list,
+            slot_texts,
+            slots: Default::default(),
+            selection: None,
+            name_row,
+            tb_name,
+            name: String::new(),
+            btn_confirm,
+            btn_cancel,
+        }
+    }
+
+    /// Opens the window, metadata of slots is re-read every time so the list is always
+    /// up-to-date.
+    pub fn open(&mut self, ui: &mut UserInterface, mode: SlotWindowMode) {
+        self.mode = mode;
+        self.slots = (0..SAVE_SLOT_COUNT).map(SaveSlot::load).collect();
+        self.selection = None;
+
+        for (i, (text, slot)) in self.slot_texts.iter().zip(self.slots.iter()).enumerate() {
+            let description = match slot {
+                Some(slot) => slot.description(),
+                None => tr("save.empty"),
+            };
+            ui.send_message(TextMessage::text(
+                *text,
+                MessageDirection::ToWidget,
+                format!("{}. {}", i + 1, description),
+            ));
+        }
+        ui.send_message(ListViewMessage::selection(
+            self.list,
+            MessageDirection::ToWidget,
+            None,
+        ));
+
+        let (title, confirm) = match mode {
+            SlotWindowMode::Save => (tr("save.save_game"), tr("save.save")),
+            SlotWindowMode::Load => (tr("save.load_game"), tr("save.load")),
+        };
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text(title),
+        ));
+        if let Some(button) = ui.node(self.btn_confirm).cast::<Button>() {
+            ui.send_message(TextMessage::text(
+                button.content,
+                MessageDirection::ToWidget,
+                confirm,
+            ));
+        }
+        ui.send_message(WidgetMessage::visibility(
+            self.name_row,
+            MessageDirection::ToWidget,
+            mode == SlotWindowMode::Save,
+        ));
+        self.set_name(ui, String::new());
+        self.sync_confirm(ui);
+
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn is_open(&self, ui: &UserInterface) -> bool {
+        ui.node(self.window).visibility()
+    }
+
+    fn set_name(&mut self, ui: &UserInterface, name: String) {
+        self.name = name.clone();
+        ui.send_message(TextMessage::text(
+            self.tb_name,
+            MessageDirection::ToWidget,
+            name,
+        ));
+    }
+
+    // Saving needs any slot and a name, loading needs a slot that isn't empty.
+    fn sync_confirm(&self, ui: &UserInterface) {
+        let enabled = match (self.mode, self.selection) {
+            (SlotWindowMode::Save, Some(_)) => !self.name.trim().is_empty(),
+            (SlotWindowMode::Load, Some(slot)) => self.slots[slot].is_some(),
+            (_, None) => false,
+        };
+        ui.send_message(WidgetMessage::enabled(
+            self.btn_confirm,
+            MessageDirection::ToWidget,
+            enabled,
+        ));
+    }
+
+    pub fn handle_ui_event(&mut self, ui: &mut UserInterface, message: &UiMessage) {
+        if let Some(ListViewMessage::SelectionChanged(selection)) = message.data() {
+            if message.destination() == self.list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.selection = *selection;
+                // Saving over a slot suggests its name, so overwriting is a single click.
+                if self.mode == SlotWindowMode::Save {
+                    if let Some(Some(slot)) = selection.map(|i| &self.slots[i]) {
+                        let name = slot.name.clone();
+                        self.set_name(ui, name);
+                    } else if self.name.is_empty() {
+                        self.set_name(
+                            ui,
+                            tr_format("save.default_name", &[&(selection.unwrap_or(0) + 1)]),
+                        );
+                    }
+                }
+                self.sync_confirm(ui);
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.tb_name
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.name = text.clone();
+                self.sync_confirm(ui);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.btn_confirm {
+                if let Some(slot) = self.selection {
+                    let message = match self.mode {
+                        SlotWindowMode::Save => Message::SaveGame {
+                            slot,
+                            name: self.name.trim().to_owned(),
+                        },
+                        SlotWindowMode::Load => Message::LoadGame { slot },
+                    };
+                    self.sender.send(message).unwrap();
+                    self.close(ui);
+                }
+            } else if message.destination() == self.btn_cancel {
+                self.close(ui);
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! Audio, video and gameplay settings. Controls are stored separately, see `ControlScheme`.
+
+use crate::{
+    bot::BotDifficulty,
+    control_scheme::parse_config_value,
+    localization::{tr, DEFAULT_LANGUAGE},
+};
+use fyrox::{
+    core::visitor::{Visit, VisitResult, Visitor},
+    renderer::QualitySettings,
+    utils::log::{Log, MessageKind},
+};
+use std::{collections::BTreeMap, fs::File, io::Write, path::Path};
+
+/// Default location of the file with user's settings.
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    /// Same as hard, but with tougher bots and without HUD aids like the radar.
+    Hardcore,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Hardcore,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Hardcore => "Hardcore",
+        }
+    }
+
+    pub fn display_name(self) -> String {
+        tr(match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+            Difficulty::Hardcore => "difficulty.hardcore",
+        })
+    }
+
+    /// Multiplier of damage that bots deal to the player.
+    pub fn bot_damage_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Hardcore => 2.0,
+        }
+    }
+
+    /// Shooting skills of bots spawned on this difficulty.
+    pub fn bot_difficulty(self) -> BotDifficulty {
+        match self {
+            Difficulty::Easy => BotDifficulty::EASY,
+            Difficulty::Normal => BotDifficulty::NORMAL,
+            Difficulty::Hard | Difficulty::Hardcore => BotDifficulty::HARD,
+        }
+    }
+
+    /// Whether HUD may show the radar.
+    pub fn allows_radar(self) -> bool {
+        self != Difficulty::Hardcore
+    }
+}
+
+/// Amount of particle effects the game may show at once, see `effects::EffectBudget`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EffectQuality {
+    Low,
+    Medium,
+    High,
+}
+
+impl EffectQuality {
+    pub const ALL: [EffectQuality; 3] = [
+        EffectQuality::Low,
+        EffectQuality::Medium,
+        EffectQuality::High,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EffectQuality::Low => "Low",
+            EffectQuality::Medium => "Medium",
+            EffectQuality::High => "High",
+        }
+    }
+
+    pub fn display_name(self) -> String {
+        tr(match self {
+            EffectQuality::Low => "effect_quality.low",
+            EffectQuality::Medium => "effect_quality.medium",
+            EffectQuality::High => "effect_quality.high",
+        })
+    }
+}
+
+/// Every sound belongs to a category, which has its own volume in settings.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
+pub enum SoundCategory {
+    Sfx,
+    Music,
+    Ui,
+    Voice,
+}
+
+impl Default for SoundCategory {
+    fn default() -> Self {
+        Self::Sfx
+    }
+}
+
+pub struct Settings {
+    /// Gain of every sound in the game, it is multiplied with volume of the sound's category.
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub ui_volume: f32,
+    pub voice_volume: f32,
+    /// Silences the game while its window is not focused.
+    pub mute_on_focus_loss: bool,
+    /// Muffles sounds that are heard through walls of the level.
+    pub sound_occlusion: bool,
+    pub fullscreen: bool,
+    /// Width, height and refresh rate (in millihertz) of exclusive fullscreen mode, `None`
+    /// means borderless fullscreen on the desktop resolution.
+    pub video_mode: Option<(u32, u32, u32)>,
+    pub spot_shadows: bool,
+    pub soft_spot_shadows: bool,
+    pub spot_shadows_distance: f32,
+    pub point_shadows: bool,
+    pub soft_point_shadows: bool,
+    pub point_shadows_distance: f32,
+    pub light_scatter: bool,
+    pub effect_quality: EffectQuality,
+    pub difficulty: Difficulty,
+    /// Vertical field of view of the player's camera in degrees.
+    pub fov: f32,
+    /// Subtitles of significant sounds, see `captions` module.
+    pub captions: bool,
+    pub caption_size: f32,
+    /// Radar with nearby actors and items at the top left of the HUD, see `radar` module.
+    pub radar: bool,
+    /// Diameter of the radar in pixels.
+    pub radar_size: f32,
+    /// Hits produce neutral puffs instead of blood.
+    pub reduce_violence: bool,
+    /// Code of the UI language, see `localization` module.
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let quality = QualitySettings::default();
+        Self {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 0.25,
+            ui_volume: 1.0,
+            voice_volume: 1.0,
+            mute_on_focus_loss: true,
+            sound_occlusion: true,
+            fullscreen: false,
+            video_mode: None,
+            spot_shadows: quality.spot_shadows_enabled,
+            soft_spot_shadows: quality.spot_soft_shadows,
+            spot_shadows_distance: quality.spot_shadows_distance,
+            point_shadows: quality.point_shadows_enabled,
+            soft_point_shadows: quality.point_soft_shadows,
+            point_shadows_distance: quality.point_shadows_distance,
+            light_scatter: quality.light_scatter_enabled,
+            effect_quality: EffectQuality::High,
+            difficulty: Difficulty::Normal,
+            fov: 75.0,
+            captions: false,
+            caption_size: 22.0,
+            radar: false,
+            radar_size: 160.0,
+            reduce_violence: false,
+            language: DEFAULT_LANGUAGE.to_owned(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn reset(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Final volume of sounds of given category, master volume included.
+    pub fn volume(&self, category: SoundCategory) -> f32 {
+        self.master_volume
+            * match category {
+                SoundCategory::Sfx => self.sfx_volume,
+                SoundCategory::Music => self.music_volume,
+                SoundCategory::Ui => self.ui_volume,
+                SoundCategory::Voice => self.voice_volume,
+            }
+    }
+
+    /// Applies quality part of the settings on top of given renderer settings.
+    pub fn quality(&self, mut quality: QualitySettings) -> QualitySettings {
+        quality.spot_shadows_enabled = self.spot_shadows;
+        quality.spot_soft_shadows = self.soft_spot_shadows;
+        quality.spot_shadows_distance = self.spot_shadows_distance;
+        quality.point_shadows_enabled = self.point_shadows;
+        quality.point_soft_shadows = self.soft_point_shadows;
+        quality.point_shadows_distance = self.point_shadows_distance;
+        quality.light_scatter_enabled = self.light_scatter;
+        quality
+    }
+
+    pub fn set_quality(&mut self, quality: &QualitySettings) {
+        self.spot_shadows = quality.spot_shadows_enabled;
+        self.soft_spot_shadows = quality.spot_soft_shadows;
+        self.spot_shadows_distance = quality.spot_shadows_distance;
+        self.point_shadows = quality.point_shadows_enabled;
+        self.soft_point_shadows = quality.point_soft_shadows;
+        self.point_shadows_distance = quality.point_shadows_distance;
+        self.light_scatter = quality.light_scatter_enabled;
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut config = BTreeMap::new();
+        config.insert("master_volume".to_owned(), self.master_volume.to_string());
+        config.insert("sfx_volume".to_owned(), self.sfx_volume.to_string());
+        config.insert("music_volume".to_owned(), self.music_volume.to_string());
+        config.insert("ui_volume".to_owned(), self.ui_volume.to_string());
+        config.insert("voice_volume".to_owned(), self.voice_volume.to_string());
+        config.insert(
+            "mute_on_focus_loss".to_owned(),
+            self.mute_on_focus_loss.to_string(),
+        );
+        config.insert(
+            "sound_occlusion".to_owned(),
+            self.sound_occlusion.to_string(),
+        );
+        config.insert("fullscreen".to_owned(), self.fullscreen.to_string());
+        config.insert(
+            "video_mode".to_owned(),
+            match self.video_mode {
+                Some((width, height, rate)) => format!("{}x{}@{}", width, height, rate),
+                None => "None".to_owned(),
+            },
+        );
+        config.insert("spot_shadows".to_owned(), self.spot_shadows.to_string());
+        config.insert(
+            "soft_spot_shadows".to_owned(),
+            self.soft_spot_shadows.to_string(),
+        );
+        config.insert(
+            "spot_shadows_distance".to_owned(),
+            self.spot_shadows_distance.to_string(),
+        );
+        config.insert("point_shadows".to_owned(), self.point_shadows.to_string());
+        config.insert(
+            "soft_point_shadows".to_owned(),
+            self.soft_point_shadows.to_string(),
+        );
+        config.insert(
+            "point_shadows_distance".to_owned(),
+            self.point_shadows_distance.to_string(),
+        );
+        config.insert("light_scatter".to_owned(), self.light_scatter.to_string());
+        config.insert(
+            "effect_quality".to_owned(),
+            self.effect_quality.name().to_owned(),
+        );
+        config.insert("difficulty".to_owned(), self.difficulty.name().to_owned());
+        config.insert("fov".to_owned(), self.fov.to_string());
+        config.insert("captions".to_owned(), self.captions.to_string());
+        config.insert("caption_size".to_owned(), self.caption_size.to_string());
+        config.insert("radar".to_owned(), self.radar.to_string());
+        config.insert("radar_size".to_owned(), self.radar_size.to_string());
+        config.insert(
+            "reduce_violence".to_owned(),
+            self.reduce_violence.to_string(),
+        );
+        config.insert("language".to_owned(), self.language.clone());
+
+        let text = ron::ser::to_string_pretty(&config, Default::default())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        File::create(path)?.write_all(text.as_bytes())
+    }
+
+    /// Loads settings from given file. Every missing or malformed entry falls back to its
+    /// default value, so this method never fails.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let mut settings = Self::default();
+
+        let config = match std::fs::read_to_string(path.as_ref())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                ron::from_str::<BTreeMap<String, String>>(&text).map_err(|e| e.to_string())
+            }) {
+            Ok(config) => config,
+            Err(e) => {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "Unable to load settings from {}, defaults will be used. Reason: {}",
+                        path.as_ref().display(),
+                        e
+                    ),
+                );
+                return settings;
+            }
+        };
+
+        parse_config_value(&config, "master_volume", &mut settings.master_volume);
+        parse_config_value(&config, "sfx_volume", &mut settings.sfx_volume);
+        parse_config_value(&config, "music_volume", &mut settings.music_volume);
+        parse_config_value(&config, "ui_volume", &mut settings.ui_volume);
+        parse_config_value(&config, "voice_volume", &mut settings.voice_volume);
+        parse_config_value(
+            &config,
+            "mute_on_focus_loss",
+            &mut settings.mute_on_focus_loss,
+        );
+        parse_config_value(&config, "sound_occlusion", &mut settings.sound_occlusion);
+        parse_config_value(&config, "fullscreen", &mut settings.fullscreen);
+        if let Some(value) = config.get("video_mode") {
+            if value == "None" {
+                settings.video_mode = None;
+            } else {
+                match parse_video_mode(value) {
+                    Some(mode) => settings.video_mode = Some(mode),
+                    None => Log::writeln(
+                        MessageKind::Warning,
+                        format!("Invalid video mode {}, ignored.", value),
+                    ),
+                }
+            }
+        }
+        parse_config_value(&config, "spot_shadows", &mut settings.spot_shadows);
+        parse_config_value(
+            &config,
+            "soft_spot_shadows",
+            &mut settings.soft_spot_shadows,
+        );
+        parse_config_value(
+            &config,
+            "spot_shadows_distance",
+            &mut settings.spot_shadows_distance,
+        );
+        parse_config_value(&config, "point_shadows", &mut settings.point_shadows);
+        parse_config_value(
+            &config,
+            "soft_point_shadows",
+            &mut settings.soft_point_shadows,
+        );
+        parse_config_value(
+            &config,
+            "point_shadows_distance",
+            &mut settings.point_shadows_distance,
+        );
+        parse_config_value(&config, "light_scatter", &mut settings.light_scatter);
+        if let Some(value) = config.get("effect_quality") {
+            match EffectQuality::ALL.iter().find(|q| q.name() == value) {
+                Some(quality) => settings.effect_quality = *quality,
+                None => Log::writeln(
+                    MessageKind::Warning,
+                    format!("Invalid effect quality {}, ignored.", value),
+                ),
+            }
+        }
+        if let Some(value) = config.get("difficulty") {
+            match Difficulty::ALL.iter().find(|d| d.name() == value) {
+                Some(difficulty) => settings.difficulty = *difficulty,
+                None => Log::writeln(
+                    MessageKind::Warning,
+                    format!("Invalid difficulty {}, ignored.", value),
+                ),
+            }
+        }
+        parse_config_value(&config, "fov", &mut settings.fov);
+        parse_config_value(&config, "captions", &mut settings.captions);
+        parse_config_value(&config, "caption_size", &mut settings.caption_size);
+        parse_config_value(&config, "radar", &mut settings.radar);
+        parse_config_value(&config, "radar_size", &mut settings.radar_size);
+        parse_config_value(&config, "reduce_violence", &mut settings.reduce_violence);
+        parse_config_value(&config, "language", &mut settings.language);
+
+        settings
+    }
+}
+
+// Parses `WIDTHxHEIGHT@RATE`.
+fn parse_video_mode(string: &str) -> Option<(u32, u32, u32)> {
+    let (size, rate) = string.split_once('@')?;
+    let (width, height) = size.split_once('x')?;
+    Some((
+        width.parse().ok()?,
+        height.parse().ok()?,
+        rate.parse().ok()?,
+    ))
+}
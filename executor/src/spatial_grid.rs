@@ -0,0 +1,148 @@
+//! Uniform grid of points on the horizontal plane, it answers "what is near this point"
+//! without looking at everything on the level. Containers rebuild their grids once per frame
+//! and bots query them for perception.
+
+use fyrox::core::algebra::Vector3;
+use std::collections::HashMap;
+
+/// Size of a cell in meters, a bit more than a room so most queries touch a few cells only.
+pub const CELL_SIZE: f32 = 8.0;
+
+struct Entry<T> {
+    value: T,
+    position: Vector3<f32>,
+    // Order of insertion, results are given in it so they match a plain scan of the source.
+    index: usize,
+}
+
+pub struct SpatialGrid<T> {
+    cells: HashMap<(i32, i32), Vec<Entry<T>>>,
+    count: usize,
+}
+
+impl<T> Default for SpatialGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: Default::default(),
+            count: 0,
+        }
+    }
+}
+
+fn cell_coord(value: f32) -> i32 {
+    (value / CELL_SIZE).floor() as i32
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    /// Empties the grid, cells keep their memory for the next frame.
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.count = 0;
+    }
+
+    pub fn insert(&mut self, value: T, position: Vector3<f32>) {
+        self.cells
+            .entry((cell_coord(position.x), cell_coord(position.z)))
+            .or_default()
+            .push(Entry {
+                value,
+                position,
+                index: self.count,
+            });
+        self.count += 1;
+    }
+
+    /// Collects values within `radius` of `center` into `result`, in the order they were
+    /// inserted. Distance is exact and three-dimensional, cells only narrow the search.
+    pub fn query(&self, center: Vector3<f32>, radius: f32, result: &mut GridQuery<T>) {
+        result.found.clear();
+        let sqr_radius = radius * radius;
+        for x in cell_coord(center.x - radius)..=cell_coord(center.x + radius) {
+            for z in cell_coord(center.z - radius)..=cell_coord(center.z + radius) {
+                if let Some(cell) = self.cells.get(&(x, z)) {
+                    result.found.extend(
+                        cell.iter()
+                            .filter(|entry| (entry.position - center).norm_squared() <= sqr_radius)
+                            .map(|entry| (entry.index, entry.value)),
+                    );
+                }
+            }
+        }
+        result.found.sort_unstable_by_key(|(index, _)| *index);
+    }
+}
+
+/// Result of [`SpatialGrid::query`]. Keep one between queries, it reuses its memory.
+pub struct GridQuery<T> {
+    // Values with their order of insertion.
+    found: Vec<(usize, T)>,
+}
+
+impl<T> Default for GridQuery<T> {
+    fn default() -> Self {
+        Self {
+            found: Default::default(),
+        }
+    }
+}
+
+impl<T: Copy> GridQuery<T> {
+    /// Values found by the last query, in the order they were inserted.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.found.iter().map(|(_, value)| *value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fyrox::rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn query_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let mut random_point = || {
+            Vector3::new(
+                rng.gen_range(-60.0..60.0),
+                rng.gen_range(-5.0..5.0),
+                rng.gen_range(-60.0..60.0),
+            )
+        };
+        let points = (0..300).map(|_| random_point()).collect::<Vec<_>>();
+        let centers = (0..50).map(|_| random_point()).collect::<Vec<_>>();
+
+        let mut grid = SpatialGrid::default();
+        for (index, point) in points.iter().enumerate() {
+            grid.insert(index, *point);
+        }
+
+        let mut result = GridQuery::default();
+        for center in centers {
+            // Within one cell, across a few and wider than the whole set.
+            for radius in [0.5, CELL_SIZE * 0.5, CELL_SIZE * 2.5, 200.0] {
+                grid.query(center, radius, &mut result);
+                let expected = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, point)| (*point - center).norm_squared() <= radius * radius)
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+                assert_eq!(result.iter().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn cleared_grid_finds_nothing() {
+        let mut grid = SpatialGrid::default();
+        grid.insert(1, Vector3::new(1.0, 0.0, 1.0));
+        grid.clear();
+        grid.insert(2, Vector3::new(-1.0, 0.0, -1.0));
+
+        let mut result = GridQuery::default();
+        grid.query(Vector3::zeros(), 5.0, &mut result);
+        assert_eq!(result.iter().collect::<Vec<_>>(), vec![2]);
+    }
+}
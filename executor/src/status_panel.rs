@@ -0,0 +1,210 @@
+//! Health and armor of the player, shown in the bottom-left corner of the HUD. Every value
+//! has numeric representation and a bar, health above nominal maximum (overheal) is shown
+//! as separate segment of the bar.
+
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        image::ImageBuilder,
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::SharedFont,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    utils,
+};
+
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 12.0;
+
+struct StatusBar {
+    value: Handle<UiNode>,
+    fill: Handle<UiNode>,
+    overheal: Handle<UiNode>,
+}
+
+impl StatusBar {
+    fn new(
+        ctx: &mut BuildContext,
+        resource_manager: &ResourceManager,
+        font: SharedFont,
+        icon: &str,
+        color: Color,
+    ) -> (Handle<UiNode>, Self) {
+        let value;
+        let fill;
+        let overheal;
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::bottom(5.0))
+                .with_child(
+                    ImageBuilder::new(WidgetBuilder::new().with_width(35.0).with_height(35.0))
+                        .with_texture(utils::into_gui_texture(
+                            resource_manager.request_texture(icon),
+                        ))
+                        .build(ctx),
+                )
+                .with_child({
+                    value = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_foreground(Brush::Solid(color))
+                            .with_margin(Thickness::left(5.0))
+                            .with_width(80.0)
+                            .with_height(35.0),
+                    )
+                    .with_font(font)
+                    .build(ctx);
+                    value
+                })
+                .with_child(
+                    BorderBuilder::new(
+                        WidgetBuilder::new()
+                            .with_width(BAR_WIDTH)
+                            .with_height(BAR_HEIGHT)
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 120)))
+                            .with_foreground(Brush::Solid(Color::opaque(80, 80, 80)))
+                            .with_child({
+                                fill = BorderBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Left)
+                                        .with_width(BAR_WIDTH)
+                                        .with_background(Brush::Solid(color)),
+                                )
+                                .with_stroke_thickness(Thickness::uniform(0.0))
+                                .build(ctx);
+                                fill
+                            })
+                            .with_child({
+                                overheal = BorderBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Left)
+                                        .with_width(0.0)
+                                        .with_background(Brush::Solid(Color::opaque(255, 215, 0))),
+                                )
+                                .with_stroke_thickness(Thickness::uniform(0.0))
+                                .build(ctx);
+                                overheal
+                            }),
+                    )
+                    .with_stroke_thickness(Thickness::uniform(1.0))
+                    .build(ctx),
+                ),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        (
+            root,
+            Self {
+                value,
+                fill,
+                overheal,
+            },
+        )
+    }
+
+    fn set_value(&self, ui: &mut UserInterface, value: f32, max: f32) {
+        let value = value.max(0.0);
+        ui.send_message(TextMessage::text(
+            self.value,
+            MessageDirection::ToWidget,
+            format!("{}", value.round()),
+        ));
+
+        // Value above maximum is drawn over the normal part of the bar, so the bar never
+        // grows out of its frame.
+        let fill = (value / max).min(1.0);
+        let overheal = ((value - max) / max).max(0.0).min(1.0);
+        ui.send_message(WidgetMessage::width(
+            self.fill,
+            MessageDirection::ToWidget,
+            fill * BAR_WIDTH,
+        ));
+        ui.send_message(WidgetMessage::width(
+            self.overheal,
+            MessageDirection::ToWidget,
+            overheal * BAR_WIDTH,
+        ));
+    }
+}
+
+pub struct StatusPanel {
+    pub root: Handle<UiNode>,
+    health: StatusBar,
+    armor: StatusBar,
+}
+
+impl StatusPanel {
+    pub fn new(
+        ctx: &mut BuildContext,
+        resource_manager: &ResourceManager,
+        font: SharedFont,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        let (health_root, health) = StatusBar::new(
+            ctx,
+            resource_manager,
+            font.clone(),
+            "data/ui/health_icon.png",
+            Color::opaque(180, 14, 22),
+        );
+        let (armor_root, armor) = StatusBar::new(
+            ctx,
+            resource_manager,
+            font,
+            "data/ui/shield_icon.png",
+            Color::opaque(255, 100, 26),
+        );
+
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_margin(Thickness {
+                    left: 50.0,
+                    top: 0.0,
+                    right: 0.0,
+                    bottom: 10.0,
+                })
+                .with_child(health_root)
+                .with_child(armor_root),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            health,
+            armor,
+        }
+    }
+
+    pub fn set_values(
+        &self,
+        ui: &mut UserInterface,
+        health: f32,
+        max_health: f32,
+        armor: f32,
+        max_armor: f32,
+    ) {
+        self.health.set_value(ui, health, max_health);
+        self.armor.set_value(ui, armor, max_armor);
+    }
+
+    pub fn set_visible(&self, ui: &mut UserInterface, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+}
@@ -0,0 +1,297 @@
+//! Surfaces of the world geometry. Every texture of the level is mapped to a kind of surface in
+//! [`SURFACE_MAP_PATH`], hits against the level look up the texture of the hit triangle and
+//! pick visuals and sounds of that surface. Textures that are not listed have no surface.
+
+use crate::data_file;
+use fyrox::{
+    core::{
+        algebra::{Point3, Vector3},
+        color::Color,
+        sstorage::ImmutableString,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    material::PropertyValue,
+    scene::{
+        collider::{ColliderShape, InteractionGroups},
+        graph::{
+            physics::{FeatureId, Intersection, RayCastOptions},
+            Graph,
+        },
+        mesh::Mesh,
+    },
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Map of texture paths to their [`SurfaceKind`]s.
+pub const SURFACE_MAP_PATH: &str = "data/sounds/surfaces.ron";
+/// Explosions that go off this close above the level are on its surface, farther ones are
+/// air bursts.
+const GROUND_PROBE_DISTANCE: f32 = 1.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Deserialize)]
+pub enum SurfaceKind {
+    Stone,
+    Metal,
+    Wood,
+    Grass,
+}
+
+impl Default for SurfaceKind {
+    fn default() -> Self {
+        SurfaceKind::Stone
+    }
+}
+
+impl SurfaceKind {
+    pub const ALL: [SurfaceKind; 4] = [
+        SurfaceKind::Stone,
+        SurfaceKind::Metal,
+        SurfaceKind::Wood,
+        SurfaceKind::Grass,
+    ];
+}
+
+#[derive(Default, Deserialize)]
+#[serde(transparent)]
+pub struct SurfaceTable {
+    textures: HashMap<PathBuf, SurfaceKind>,
+}
+
+impl SurfaceTable {
+    /// Loads the table from given file, nothing has a surface if the file is missing or
+    /// malformed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        data_file::load(path, "surfaces")
+    }
+
+    pub fn get(&self, texture: &Path) -> Option<SurfaceKind> {
+        self.textures.get(texture).copied()
+    }
+}
+
+static TABLE: Mutex<Option<SurfaceTable>> = Mutex::new(None);
+
+/// Surface of given texture, the table is loaded on first use.
+pub fn surface_of_texture(texture: &Path) -> Option<SurfaceKind> {
+    TABLE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| SurfaceTable::load(SURFACE_MAP_PATH))
+        .get(texture)
+}
+
+/// Index of the surface that holds given triangle, `surface_triangles` are triangle counts
+/// of every surface in order they were merged into the trimesh. Trimeshes report back faces
+/// with indices past the last triangle.
+pub fn surface_index(surface_triangles: &[usize], triangle: usize) -> Option<usize> {
+    let total = surface_triangles.iter().sum::<usize>();
+    if total == 0 {
+        return None;
+    }
+    let mut triangle = triangle % total;
+    for (index, count) in surface_triangles.iter().enumerate() {
+        if triangle < *count {
+            return Some(index);
+        }
+        triangle -= count;
+    }
+    None
+}
+
+/// Looks up surface of the level at the hit point, `None` for hits against anything but the
+/// level or against textures without a surface.
+pub fn surface_at(graph: &Graph, intersection: &Intersection) -> Option<SurfaceKind> {
+    let collider = graph[intersection.collider].as_collider();
+    let sources = match collider.shape() {
+        ColliderShape::Trimesh(trimesh) => &trimesh.sources,
+        _ => return None,
+    };
+    let triangle = match intersection.feature {
+        FeatureId::Face(triangle) => triangle as usize,
+        _ => 0,
+    };
+
+    // Trimesh is made of every surface of every source mesh in order.
+    let surfaces = sources
+        .iter()
+        .filter_map(|source| graph.try_get(source.0))
+        .filter_map(|node| node.cast::<Mesh>())
+        .flat_map(|mesh| mesh.surfaces().iter())
+        .collect::<Vec<_>>();
+    let triangles = surfaces
+        .iter()
+        .map(|surface| surface.data().lock().geometry_buffer.len())
+        .collect::<Vec<_>>();
+    let surface = surfaces[surface_index(&triangles, triangle)?];
+
+    let material = surface.material().lock();
+    match material.property_ref(&ImmutableString::new("diffuseTexture")) {
+        Some(PropertyValue::Sampler {
+            value: Some(texture),
+            ..
+        }) => surface_of_texture(texture.state().path()),
+        _ => None,
+    }
+}
+
+/// Surface right below given point, explosions that go off with no hit (grenades, rockets at
+/// the end of their flight) use it to tell if they are on the ground or in the air.
+pub fn surface_below(graph: &Graph, position: Vector3<f32>) -> Option<SurfaceKind> {
+    let mut query_buffer = Vec::default();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(position),
+            ray_direction: -Vector3::y(),
+            groups: InteractionGroups::default(),
+            max_len: GROUND_PROBE_DISTANCE,
+            sort_results: true,
+        },
+        &mut query_buffer,
+    );
+    query_buffer
+        .iter()
+        .find(|intersection| {
+            matches!(
+                graph[intersection.collider].as_collider().shape(),
+                ColliderShape::Trimesh(_)
+            )
+        })
+        .and_then(|intersection| surface_at(graph, intersection))
+}
+
+/// How explosions look and sound on a surface.
+pub struct ExplosionDefinition {
+    /// `None` for explosions without debris, like air bursts.
+    pub debris: Option<DebrisDefinition>,
+    /// `None` for explosions that leave no scorch mark.
+    pub scorch: Option<ScorchDefinition>,
+    /// One of them is picked at random on every explosion.
+    pub sounds: &'static [&'static str],
+}
+
+pub struct DebrisDefinition {
+    pub color: Color,
+    pub texture: &'static str,
+}
+
+pub struct ScorchDefinition {
+    pub color: Color,
+    pub size: f32,
+}
+
+/// Explosion on given surface, `None` is an air burst.
+pub fn explosion_definition(surface: Option<SurfaceKind>) -> &'static ExplosionDefinition {
+    match surface {
+        None => {
+            static DEFINITION: ExplosionDefinition = ExplosionDefinition {
+                debris: None,
+                scorch: None,
+                sounds: &["data/sounds/explosion.ogg"],
+            };
+            &DEFINITION
+        }
+        Some(SurfaceKind::Stone) => {
+            static DEFINITION: ExplosionDefinition = ExplosionDefinition {
+                debris: Some(DebrisDefinition {
+                    color: Color::opaque(150, 145, 135),
+                    texture: "data/particles/smoke_04.tga",
+                }),
+                scorch: Some(ScorchDefinition {
+                    color: Color::from_rgba(20, 20, 20, 220),
+                    size: 2.0,
+                }),
+                sounds: &[
+                    "data/sounds/explosion.ogg",
+                    "data/sounds/bullet_impact_concrete.ogg",
+                ],
+            };
+            &DEFINITION
+        }
+        Some(SurfaceKind::Metal) => {
+            static DEFINITION: ExplosionDefinition = ExplosionDefinition {
+                debris: Some(DebrisDefinition {
+                    color: Color::opaque(255, 210, 120),
+                    texture: "data/particles/star_09.png",
+                }),
+                scorch: Some(ScorchDefinition {
+                    color: Color::from_rgba(30, 25, 20, 160),
+                    size: 1.5,
+                }),
+                sounds: &[
+                    "data/sounds/explosion.ogg",
+                    "data/sounds/bullet_impact_metal.ogg",
+                ],
+            };
+            &DEFINITION
+        }
+        Some(SurfaceKind::Wood) => {
+            static DEFINITION: ExplosionDefinition = ExplosionDefinition {
+                debris: Some(DebrisDefinition {
+                    color: Color::opaque(120, 80, 40),
+                    texture: "data/particles/circle_05.png",
+                }),
+                scorch: Some(ScorchDefinition {
+                    color: Color::from_rgba(15, 10, 5, 230),
+                    size: 1.8,
+                }),
+                sounds: &["data/sounds/explosion.ogg"],
+            };
+            &DEFINITION
+        }
+        Some(SurfaceKind::Grass) => {
+            static DEFINITION: ExplosionDefinition = ExplosionDefinition {
+                debris: Some(DebrisDefinition {
+                    color: Color::opaque(90, 70, 40),
+                    texture: "data/particles/smoke_04.tga",
+                }),
+                scorch: Some(ScorchDefinition {
+                    color: Color::from_rgba(25, 20, 10, 200),
+                    size: 2.2,
+                }),
+                sounds: &["data/sounds/explosion.ogg"],
+            };
+            &DEFINITION
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_table_parses() {
+        let table: SurfaceTable =
+            data_file::parse(include_str!("../../data/sounds/surfaces.ron")).unwrap();
+        assert_eq!(
+            table.get(Path::new("data/textures/brick.tga")),
+            Some(SurfaceKind::Stone)
+        );
+        assert_eq!(
+            table.get(Path::new("data/textures/blocks17floor.tga")),
+            Some(SurfaceKind::Metal)
+        );
+        assert_eq!(table.get(Path::new("data/textures/unknown.tga")), None);
+    }
+
+    #[test]
+    fn unknown_surface_kind_is_rejected() {
+        assert!(data_file::parse::<SurfaceTable>(r#"{ "a.tga": Glass }"#).is_err());
+        assert!(data_file::parse::<SurfaceTable>(r#"{ "a.tga" }"#).is_err());
+    }
+
+    #[test]
+    fn back_faces_map_to_front_surfaces() {
+        let triangles = [2, 3];
+        assert_eq!(surface_index(&triangles, 0), Some(0));
+        assert_eq!(surface_index(&triangles, 2), Some(1));
+        assert_eq!(surface_index(&triangles, 5), Some(0));
+        assert_eq!(surface_index(&triangles, 9), Some(1));
+        assert_eq!(surface_index(&[], 0), None);
+    }
+}
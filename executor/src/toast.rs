@@ -0,0 +1,407 @@
+//! Toasts are short notifications stacked at the bottom-center of the HUD. Any system can show
+//! one with `Message::Notify`. Only a few toasts are visible at once, the rest wait in a queue,
+//! and identical notifications that come in quick succession are merged with a counter.
+
+use fyrox::{
+    core::{color::Color, parking_lot::Mutex, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        image::{ImageBuilder, ImageMessage},
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    utils,
+};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const MAX_VISIBLE_TOASTS: usize = 3;
+/// Time a toast stays on screen, animations included.
+const LIFETIME: f32 = 3.0;
+/// Identical notifications within this time are merged into one toast.
+const COALESCE_TIME: f32 = 1.0;
+const SLIDE_IN_DURATION: f32 = 0.25;
+const SLIDE_DISTANCE: f32 = 20.0;
+const FADE_OUT_DURATION: f32 = 0.5;
+const TOAST_HEIGHT: f32 = 40.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NotificationPriority {
+    Normal,
+    /// Goes ahead of every queued notification, used for objectives and errors.
+    High,
+}
+
+pub struct Toast {
+    pub text: String,
+    pub icon: Option<PathBuf>,
+    /// Number of merged notifications.
+    pub count: u32,
+    // Time of the last merged notification.
+    arrived_at: f32,
+    // Time the toast appeared on screen, meaningless while queued.
+    shown_at: f32,
+}
+
+impl Toast {
+    /// Text with the counter of merged notifications.
+    pub fn label(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{}", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    /// Time since the toast appeared.
+    pub fn age(&self, now: f32) -> f32 {
+        now - self.shown_at
+    }
+}
+
+/// Queueing part of the toasts, it knows nothing about UI and takes time from outside.
+#[derive(Default)]
+pub struct ToastQueue {
+    visible: Vec<Toast>,
+    queued: VecDeque<Toast>,
+}
+
+impl ToastQueue {
+    pub fn push(
+        &mut self,
+        text: String,
+        icon: Option<PathBuf>,
+        priority: NotificationPriority,
+        now: f32,
+    ) {
+        if let Some(toast) = self
+            .visible
+            .iter_mut()
+            .chain(self.queued.iter_mut())
+            .find(|toast| {
+                toast.text == text && toast.icon == icon && now - toast.arrived_at <= COALESCE_TIME
+            })
+        {
+            toast.count += 1;
+            toast.arrived_at = now;
+            return;
+        }
+
+        let toast = Toast {
+            text,
+            icon,
+            count: 1,
+            arrived_at: now,
+            shown_at: now,
+        };
+        match priority {
+            NotificationPriority::Normal => self.queued.push_back(toast),
+            NotificationPriority::High => self.queued.push_front(toast),
+        }
+    }
+
+    /// Removes expired toasts and shows queued ones in their place.
+    pub fn update(&mut self, now: f32) {
+        self.visible.retain(|toast| toast.age(now) < LIFETIME);
+        while self.visible.len() < MAX_VISIBLE_TOASTS {
+            match self.queued.pop_front() {
+                Some(mut toast) => {
+                    toast.shown_at = now;
+                    self.visible.push(toast);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Toasts on screen, oldest first.
+    pub fn visible(&self) -> &[Toast] {
+        &self.visible
+    }
+}
+
+struct ToastView {
+    root: Handle<UiNode>,
+    icon: Handle<UiNode>,
+    text: Handle<UiNode>,
+    // Icon that is currently set to the image, avoids re-requesting the texture every frame.
+    icon_path: Option<PathBuf>,
+}
+
+pub struct ToastArea {
+    pub root: Handle<UiNode>,
+    views: Vec<ToastView>,
+    queue: ToastQueue,
+    resource_manager: ResourceManager,
+    time: f32,
+}
+
+impl ToastArea {
+    pub fn new(
+        ctx: &mut BuildContext,
+        resource_manager: ResourceManager,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        let font = fyrox::core::futures::executor::block_on(Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            22.0,
+            Font::default_char_set(),
+        ))
+        .unwrap();
+        let font = SharedFont(Arc::new(Mutex::new(font)));
+
+        let views = (0..MAX_VISIBLE_TOASTS)
+            .map(|_| {
+                let icon;
+                let text;
+                let root = BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_height(TOAST_HEIGHT)
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 150)))
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(4.0))
+                                    .with_child({
+                                        icon = ImageBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(32.0)
+                                                .with_height(32.0)
+                                                .with_margin(Thickness::right(8.0)),
+                                        )
+                                        .build(ctx);
+                                        icon
+                                    })
+                                    .with_child({
+                                        text = TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_vertical_alignment(VerticalAlignment::Center),
+                                        )
+                                        .with_font(font.clone())
+                                        .build(ctx);
+                                        text
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx);
+                ToastView {
+                    root,
+                    icon,
+                    text,
+                    icon_path: None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let root = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .on_row(row)
+                .on_column(column)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_margin(Thickness::bottom(120.0))
+                .with_children(views.iter().map(|view| view.root)),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            views,
+            queue: Default::default(),
+            resource_manager,
+            time: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, text: String, icon: Option<PathBuf>, priority: NotificationPriority) {
+        self.queue.push(text, icon, priority, self.time);
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        self.time += dt;
+        self.queue.update(self.time);
+
+        let toasts = self.queue.visible();
+        for (i, view) in self.views.iter_mut().enumerate() {
+            let toast = match toasts.get(i) {
+                Some(toast) => toast,
+                None => {
+                    ui.send_message(WidgetMessage::visibility(
+                        view.root,
+                        MessageDirection::ToWidget,
+                        false,
+                    ));
+                    continue;
+                }
+            };
+
+            ui.send_message(WidgetMessage::visibility(
+                view.root,
+                MessageDirection::ToWidget,
+                true,
+            ));
+
+            if view.icon_path != toast.icon {
+                view.icon_path = toast.icon.clone();
+                ui.send_message(ImageMessage::texture(
+                    view.icon,
+                    MessageDirection::ToWidget,
+                    toast.icon.as_ref().map(|icon| {
+                        utils::into_gui_texture(self.resource_manager.request_texture(icon))
+                    }),
+                ));
+                ui.send_message(WidgetMessage::visibility(
+                    view.icon,
+                    MessageDirection::ToWidget,
+                    toast.icon.is_some(),
+                ));
+            }
+
+            ui.send_message(TextMessage::text(
+                view.text,
+                MessageDirection::ToWidget,
+                toast.label(),
+            ));
+
+            // Slides up from below when shown, fades out at the end of its lifetime.
+            let age = toast.age(self.time);
+            let slide = (1.0 - age / SLIDE_IN_DURATION).max(0.0);
+            let alpha = ((LIFETIME - age) / FADE_OUT_DURATION).max(0.0).min(1.0);
+            ui.send_message(WidgetMessage::margin(
+                view.root,
+                MessageDirection::ToWidget,
+                Thickness {
+                    left: 2.0,
+                    top: 2.0 + SLIDE_DISTANCE * slide,
+                    right: 2.0,
+                    bottom: 2.0,
+                },
+            ));
+            ui.send_message(WidgetMessage::background(
+                view.root,
+                MessageDirection::ToWidget,
+                Brush::Solid(Color::from_rgba(0, 0, 0, (150.0 * alpha) as u8)),
+            ));
+            ui.send_message(WidgetMessage::foreground(
+                view.text,
+                MessageDirection::ToWidget,
+                Brush::Solid(Color::from_rgba(255, 255, 255, (255.0 * alpha) as u8)),
+            ));
+            ui.send_message(WidgetMessage::background(
+                view.icon,
+                MessageDirection::ToWidget,
+                Brush::Solid(Color::from_rgba(255, 255, 255, (255.0 * alpha) as u8)),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(queue: &mut ToastQueue, text: &str, priority: NotificationPriority, now: f32) {
+        queue.push(text.to_owned(), None, priority, now);
+    }
+
+    fn labels(queue: &ToastQueue) -> Vec<String> {
+        queue.visible().iter().map(|toast| toast.label()).collect()
+    }
+
+    #[test]
+    fn excess_toasts_wait_in_queue() {
+        let mut queue = ToastQueue::default();
+        for text in ["a", "b", "c", "d"] {
+            push(&mut queue, text, NotificationPriority::Normal, 0.0);
+        }
+        queue.update(0.0);
+        assert_eq!(labels(&queue), ["a", "b", "c"]);
+
+        queue.update(LIFETIME - 0.01);
+        assert_eq!(labels(&queue), ["a", "b", "c"]);
+
+        queue.update(LIFETIME);
+        assert_eq!(labels(&queue), ["d"]);
+        // Queued toast gets full lifetime from the moment it is shown.
+        assert_eq!(queue.visible()[0].age(LIFETIME + 1.0), 1.0);
+
+        queue.update(LIFETIME * 2.0);
+        assert!(queue.visible().is_empty());
+    }
+
+    #[test]
+    fn high_priority_goes_ahead_of_queue() {
+        let mut queue = ToastQueue::default();
+        for text in ["a", "b", "c", "d", "e"] {
+            push(&mut queue, text, NotificationPriority::Normal, 0.0);
+        }
+        queue.update(0.0);
+        push(&mut queue, "objective", NotificationPriority::High, 0.5);
+        queue.update(LIFETIME);
+        assert_eq!(labels(&queue), ["objective", "d", "e"]);
+    }
+
+    #[test]
+    fn identical_toasts_coalesce_within_a_second() {
+        let mut queue = ToastQueue::default();
+        push(&mut queue, "Ammo", NotificationPriority::Normal, 0.0);
+        queue.update(0.0);
+        push(&mut queue, "Ammo", NotificationPriority::Normal, 0.5);
+        // Every merged notification restarts the window.
+        push(
+            &mut queue,
+            "Ammo",
+            NotificationPriority::Normal,
+            0.5 + COALESCE_TIME,
+        );
+        queue.update(1.5);
+        assert_eq!(labels(&queue), ["Ammo x3"]);
+
+        push(&mut queue, "Ammo", NotificationPriority::Normal, 2.6);
+        queue.update(2.6);
+        assert_eq!(labels(&queue), ["Ammo x3", "Ammo"]);
+    }
+
+    #[test]
+    fn toasts_with_different_icons_do_not_coalesce() {
+        let mut queue = ToastQueue::default();
+        queue.push("Saved".to_owned(), None, NotificationPriority::Normal, 0.0);
+        queue.push(
+            "Saved".to_owned(),
+            Some("data/ui/check_mark.png".into()),
+            NotificationPriority::Normal,
+            0.1,
+        );
+        queue.update(0.1);
+        assert_eq!(labels(&queue), ["Saved", "Saved"]);
+    }
+
+    #[test]
+    fn queued_toasts_coalesce_too() {
+        let mut queue = ToastQueue::default();
+        for text in ["a", "b", "c", "d"] {
+            push(&mut queue, text, NotificationPriority::Normal, 0.0);
+        }
+        push(&mut queue, "d", NotificationPriority::Normal, 0.2);
+        queue.update(LIFETIME);
+        assert_eq!(labels(&queue), ["d x2"]);
+    }
+}
@@ -0,0 +1,135 @@
+//! Tracers of bullet weapons, thin glowing lines from the muzzle to the point the shot lands
+//! that fade out quickly. Tracer meshes are pooled like effects, a faded tracer is hidden and
+//! reused by the next shot. Tracers have no colliders, so ray casts never hit them.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    material::{Material, PropertyValue},
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+    },
+};
+use std::sync::Arc;
+
+/// Seconds it takes a tracer to fade out.
+const TRACER_LIFETIME: f32 = 0.1;
+/// Radius of the tracer line, in meters.
+const TRACER_RADIUS: f32 = 0.008;
+const TRACER_COLOR: Color = Color::opaque(255, 220, 150);
+
+#[derive(Default, Visit)]
+struct Tracer {
+    node: Handle<Node>,
+    age: f32,
+}
+
+#[derive(Default, Visit)]
+pub struct TracerPool {
+    active: Vec<Tracer>,
+    // Faded tracers, hidden and waiting to be reused.
+    free: Vec<Handle<Node>>,
+}
+
+impl TracerPool {
+    /// Shows a tracer from `begin` to `end`.
+    pub fn spawn(&mut self, graph: &mut Graph, begin: Vector3<f32>, end: Vector3<f32>) {
+        let length = begin.metric_distance(&end);
+        let direction = match (end - begin).try_normalize(std::f32::EPSILON) {
+            Some(direction) => direction,
+            None => return,
+        };
+
+        let node = self.free.pop().unwrap_or_else(|| create_tracer(graph));
+        // Cylinder of the mesh is of unit height along Y, it's stretched to the tracer length.
+        graph[node].set_visibility(true);
+        graph[node]
+            .local_transform_mut()
+            .set_position(begin)
+            .set_rotation(
+                UnitQuaternion::rotation_between(&Vector3::y(), &direction).unwrap_or_default(),
+            )
+            .set_scale(Vector3::new(1.0, length, 1.0));
+        set_alpha(graph, node, 1.0);
+        self.active.push(Tracer { node, age: 0.0 });
+    }
+
+    /// Fades tracers out and returns faded ones to the pool.
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) {
+        let mut i = 0;
+        while i < self.active.len() {
+            let tracer = &mut self.active[i];
+            tracer.age += dt;
+            let node = tracer.node;
+            if tracer.age >= TRACER_LIFETIME {
+                self.active.swap_remove(i);
+                if graph.is_valid_handle(node) {
+                    graph[node].set_visibility(false);
+                    self.free.push(node);
+                }
+            } else {
+                set_alpha(graph, node, 1.0 - tracer.age / TRACER_LIFETIME);
+                i += 1;
+            }
+        }
+    }
+
+    /// Removes every tracer, active or pooled, from the scene.
+    pub fn clear(&mut self, graph: &mut Graph) {
+        let nodes = self.active.drain(..).map(|tracer| tracer.node);
+        for node in nodes.chain(self.free.drain(..)) {
+            if graph.is_valid_handle(node) {
+                graph.remove_node(node);
+            }
+        }
+    }
+}
+
+fn create_tracer(graph: &mut Graph) -> Handle<Node> {
+    let mut material = Material::standard();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(TRACER_COLOR),
+        )
+        .unwrap();
+
+    // Forward path draws the tracer without lighting and with transparency, so it glows and
+    // can fade.
+    MeshBuilder::new(BaseBuilder::new().with_visibility(false))
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            SurfaceData::make_cylinder(4, TRACER_RADIUS, 1.0, false, &Matrix4::identity()),
+        )))
+        .with_material(Arc::new(Mutex::new(material)))
+        .build()])
+        .with_cast_shadows(false)
+        .with_render_path(RenderPath::Forward)
+        .build(graph)
+}
+
+fn set_alpha(graph: &mut Graph, tracer: Handle<Node>, alpha: f32) {
+    let color = Color::from_rgba(
+        TRACER_COLOR.r,
+        TRACER_COLOR.g,
+        TRACER_COLOR.b,
+        (alpha.clamp(0.0, 1.0) * 255.0) as u8,
+    );
+    for surface in graph[tracer].as_mesh_mut().surfaces() {
+        let _ = surface.material().lock().set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(color),
+        );
+    }
+}
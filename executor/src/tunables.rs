@@ -0,0 +1,298 @@
+//! Tunable values of weapons, bots and items. They're read from data files in
+//! `data/definitions` and override values compiled into the definitions, entries missing in
+//! the files keep the compiled ones. Systems read them when they need them, so weapons pick
+//! up new shoot interval on next shot and items new reactivation interval on next pickup.
+//!
+//! Builds with `hot-reload` feature watch the files and re-read the changed ones while the
+//! game runs, see [`DefinitionWatcher`]. A file that fails to parse keeps previous values.
+
+use crate::{bot::BotKind, data_file, item::ItemKind, weapon::WeaponKind};
+use fyrox::utils::log::{Log, MessageKind};
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path, sync::RwLock};
+
+pub const DEFINITIONS_DIR: &str = "data/definitions";
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DefinitionKind {
+    Weapons,
+    Bots,
+    Items,
+}
+
+impl DefinitionKind {
+    pub const ALL: [DefinitionKind; 3] = [
+        DefinitionKind::Weapons,
+        DefinitionKind::Bots,
+        DefinitionKind::Items,
+    ];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            DefinitionKind::Weapons => "weapons.ron",
+            DefinitionKind::Bots => "bots.ron",
+            DefinitionKind::Items => "items.ron",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeaponTunables {
+    /// Seconds between shots, melee weapons use swing time instead.
+    pub shoot_interval: Option<f64>,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotTunables {
+    /// Meters per second.
+    pub walk_speed: Option<f32>,
+    pub health: Option<f32>,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ItemTunables {
+    /// Seconds until a picked up item is back.
+    pub reactivation_interval: Option<f32>,
+}
+
+#[derive(Default, Debug)]
+pub struct Tunables {
+    weapons: HashMap<WeaponKind, WeaponTunables>,
+    bots: HashMap<BotKind, BotTunables>,
+    items: HashMap<ItemKind, ItemTunables>,
+}
+
+static TUNABLES: RwLock<Option<Tunables>> = RwLock::new(None);
+
+impl Tunables {
+    /// Reads every definition file of given directory, missing files are fine and files
+    /// that fail to parse are logged and skipped.
+    pub fn load<P: AsRef<Path>>(dir: P) -> Self {
+        let mut tunables = Self::default();
+        for kind in DefinitionKind::ALL {
+            if dir.as_ref().join(kind.file_name()).exists() {
+                if let Err(e) = tunables.reload(dir.as_ref(), kind) {
+                    Log::writeln(MessageKind::Error, e);
+                }
+            }
+        }
+        tunables
+    }
+
+    /// Re-reads the file of given kind, previous values are kept if it can't be read.
+    pub fn reload(&mut self, dir: &Path, kind: DefinitionKind) -> Result<(), String> {
+        let path = dir.join(kind.file_name());
+        match kind {
+            DefinitionKind::Weapons => data_file::read(&path).map(|weapons| self.weapons = weapons),
+            DefinitionKind::Bots => data_file::read(&path).map(|bots| self.bots = bots),
+            DefinitionKind::Items => data_file::read(&path).map(|items| self.items = items),
+        }
+        .map_err(|e| format!("Unable to load definitions {}: {}", path.display(), e))
+    }
+
+    pub fn weapon(&self, kind: WeaponKind) -> WeaponTunables {
+        self.weapons.get(&kind).copied().unwrap_or_default()
+    }
+
+    pub fn bot(&self, kind: BotKind) -> BotTunables {
+        self.bots.get(&kind).copied().unwrap_or_default()
+    }
+
+    pub fn item(&self, kind: ItemKind) -> ItemTunables {
+        self.items.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+/// Replaces tunables that are in use.
+pub fn set(tunables: Tunables) {
+    *TUNABLES.write().unwrap() = Some(tunables);
+}
+
+/// Gives mutable access to tunables that are in use, e.g. to reload them.
+#[cfg(feature = "hot-reload")]
+pub fn modify<R>(func: impl FnOnce(&mut Tunables) -> R) -> R {
+    func(
+        TUNABLES
+            .write()
+            .unwrap()
+            .get_or_insert_with(Default::default),
+    )
+}
+
+fn read<R>(func: impl FnOnce(&Tunables) -> R) -> R {
+    match TUNABLES.read().unwrap().as_ref() {
+        Some(tunables) => func(tunables),
+        None => func(&Tunables::default()),
+    }
+}
+
+pub fn weapon(kind: WeaponKind) -> WeaponTunables {
+    read(|tunables| tunables.weapon(kind))
+}
+
+pub fn bot(kind: BotKind) -> BotTunables {
+    read(|tunables| tunables.bot(kind))
+}
+
+pub fn item(kind: ItemKind) -> ItemTunables {
+    read(|tunables| tunables.item(kind))
+}
+
+/// Polls modification time of the definition files, they're re-read when it changes.
+#[cfg(any(test, feature = "hot-reload"))]
+pub struct DefinitionWatcher {
+    dir: std::path::PathBuf,
+    timer: f32,
+    modified: HashMap<DefinitionKind, std::time::SystemTime>,
+}
+
+#[cfg(any(test, feature = "hot-reload"))]
+impl DefinitionWatcher {
+    /// Seconds between checks of the files.
+    pub const POLL_INTERVAL: f32 = 1.0;
+
+    /// Files as they're now are considered loaded already.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        let mut watcher = Self {
+            dir: dir.as_ref().to_owned(),
+            timer: 0.0,
+            modified: Default::default(),
+        };
+        for kind in DefinitionKind::ALL {
+            if let Some(time) = watcher.modified_time(kind) {
+                watcher.modified.insert(kind, time);
+            }
+        }
+        watcher
+    }
+
+    fn modified_time(&self, kind: DefinitionKind) -> Option<std::time::SystemTime> {
+        std::fs::metadata(self.dir.join(kind.file_name()))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Re-reads changed files into given tunables and returns kinds of the reloaded ones.
+    /// Failures are logged, the file is retried only after it's changed again.
+    pub fn update(&mut self, dt: f32, tunables: &mut Tunables) -> Vec<DefinitionKind> {
+        let mut reloaded = Vec::new();
+        self.timer -= dt;
+        if self.timer > 0.0 {
+            return reloaded;
+        }
+        self.timer = Self::POLL_INTERVAL;
+
+        for kind in DefinitionKind::ALL {
+            let time = match self.modified_time(kind) {
+                Some(time) => time,
+                None => continue,
+            };
+            if self.modified.insert(kind, time) == Some(time) {
+                continue;
+            }
+            match tunables.reload(&self.dir, kind) {
+                Ok(()) => {
+                    Log::writeln(
+                        MessageKind::Information,
+                        format!("Reloaded {}", kind.file_name()),
+                    );
+                    reloaded.push(kind);
+                }
+                Err(e) => Log::writeln(MessageKind::Error, e),
+            }
+        }
+        reloaded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs::{self, File},
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wood_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Modification time of a file written twice within a second may not change, so it is
+    // moved forward explicitly.
+    fn write(path: &Path, text: &str, age: u64) {
+        fs::write(path, text).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(age))
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_entries_keep_compiled_values() {
+        let tunables = Tunables::default();
+        assert_eq!(tunables.weapon(WeaponKind::M4), WeaponTunables::default());
+        assert_eq!(tunables.bot(BotKind::Mutant).health, None);
+    }
+
+    #[test]
+    fn parses_definition_files() {
+        let dir = temp_dir("tunables_parse");
+        write(
+            &dir.join("bots.ron"),
+            "{ Mutant: (walk_speed: 3.0), Maw: (health: 250.0) }",
+            0,
+        );
+        write(
+            &dir.join("items.ron"),
+            "{ Medkit: (reactivation_interval: 5.0) }",
+            0,
+        );
+        let tunables = Tunables::load(&dir);
+        assert_eq!(tunables.bot(BotKind::Mutant).walk_speed, Some(3.0));
+        assert_eq!(tunables.bot(BotKind::Mutant).health, None);
+        assert_eq!(tunables.bot(BotKind::Maw).health, Some(250.0));
+        assert_eq!(
+            tunables.item(ItemKind::Medkit).reactivation_interval,
+            Some(5.0)
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn watcher_reloads_changed_file_and_keeps_values_on_error() {
+        let dir = temp_dir("tunables_watch");
+        let path = dir.join(DefinitionKind::Weapons.file_name());
+        write(&path, "{ M4: (shoot_interval: 0.15) }", 0);
+
+        let mut tunables = Tunables::load(&dir);
+        let mut watcher = DefinitionWatcher::new(&dir);
+        assert!(watcher
+            .update(DefinitionWatcher::POLL_INTERVAL, &mut tunables)
+            .is_empty());
+
+        write(&path, "{ M4: (shoot_interval: 0.5) }", 10);
+        // Files are only checked once per poll interval.
+        assert!(watcher.update(0.1, &mut tunables).is_empty());
+        assert_eq!(
+            watcher.update(DefinitionWatcher::POLL_INTERVAL, &mut tunables),
+            vec![DefinitionKind::Weapons]
+        );
+        assert_eq!(tunables.weapon(WeaponKind::M4).shoot_interval, Some(0.5));
+
+        write(&path, "{ M4: (shoot_interval: ", 20);
+        assert!(watcher
+            .update(DefinitionWatcher::POLL_INTERVAL, &mut tunables)
+            .is_empty());
+        assert_eq!(tunables.weapon(WeaponKind::M4).shoot_interval, Some(0.5));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
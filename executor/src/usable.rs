@@ -0,0 +1,163 @@
+//! Usable objects are parts of a level that can be activated with the "use" key: doors that
+//! open and close and buttons that operate doors. Every usable is registered by its collider,
+//! so an object hit by a ray cast can be found quickly.
+//!
+//! Elevators and other moving platforms are doors too - a door is just a node that moves
+//! between its closed position and an open offset.
+
+use fyrox::core::{
+    algebra::Vector3,
+    pool::{Handle, Pool},
+    visitor::{Visit, VisitResult, Visitor},
+};
+use fyrox::scene::{node::Node, Scene};
+
+/// Speed of doors in meters per second.
+const DOOR_SPEED: f32 = 2.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
+pub enum UsableKind {
+    Door,
+    Button,
+}
+
+#[derive(Visit)]
+pub struct Usable {
+    kind: UsableKind,
+    node: Handle<Node>,
+    collider: Handle<Node>,
+    // Offset from closed position of a door when it is fully open and current offset.
+    open_offset: Vector3<f32>,
+    offset: Vector3<f32>,
+    open: bool,
+    // Door that is operated by a button.
+    target: Handle<Usable>,
+}
+
+impl Default for Usable {
+    fn default() -> Self {
+        Self {
+            kind: UsableKind::Door,
+            node: Default::default(),
+            collider: Default::default(),
+            open_offset: Default::default(),
+            offset: Default::default(),
+            open: false,
+            target: Default::default(),
+        }
+    }
+}
+
+impl Usable {
+    pub fn door(node: Handle<Node>, collider: Handle<Node>, open_offset: Vector3<f32>) -> Self {
+        Self {
+            kind: UsableKind::Door,
+            node,
+            collider,
+            open_offset,
+            ..Default::default()
+        }
+    }
+
+    pub fn button(node: Handle<Node>, collider: Handle<Node>, target: Handle<Usable>) -> Self {
+        Self {
+            kind: UsableKind::Button,
+            node,
+            collider,
+            target,
+            ..Default::default()
+        }
+    }
+
+    pub fn kind(&self) -> UsableKind {
+        self.kind
+    }
+
+    /// Returns localization key of the verb that describes what will happen on use, it is
+    /// shown in the HUD prompt.
+    pub fn action(&self) -> &'static str {
+        match self.kind {
+            UsableKind::Door if self.open => "use.close",
+            UsableKind::Door => "use.open",
+            UsableKind::Button => "use.press",
+        }
+    }
+}
+
+#[derive(Default, Visit)]
+pub struct UsableContainer {
+    pool: Pool<Usable>,
+}
+
+impl UsableContainer {
+    pub fn add(&mut self, usable: Usable) -> Handle<Usable> {
+        self.pool.spawn(usable)
+    }
+
+    pub fn get(&self, handle: Handle<Usable>) -> &Usable {
+        self.pool.borrow(handle)
+    }
+
+    /// Returns handle of the usable that is registered with given collider.
+    pub fn find_by_collider(&self, collider: Handle<Node>) -> Handle<Usable> {
+        if collider.is_none() {
+            return Handle::NONE;
+        }
+        self.pool
+            .pair_iter()
+            .find(|(_, usable)| usable.collider == collider)
+            .map(|(handle, _)| handle)
+            .unwrap_or_default()
+    }
+
+    pub fn find_door_by_node(&self, node: Handle<Node>) -> Handle<Usable> {
+        self.pool
+            .pair_iter()
+            .find(|(_, usable)| usable.kind == UsableKind::Door && usable.node == node)
+            .map(|(handle, _)| handle)
+            .unwrap_or_default()
+    }
+
+    /// Activates given usable, button toggles its door. Returns handle of the door that
+    /// changed its state, if any.
+    pub fn activate(&mut self, handle: Handle<Usable>) -> Handle<Usable> {
+        if !self.pool.is_valid_handle(handle) {
+            return Handle::NONE;
+        }
+        let door = match self.pool[handle].kind {
+            UsableKind::Door => handle,
+            UsableKind::Button => self.pool[handle].target,
+        };
+        if self.pool.is_valid_handle(door) {
+            let door_ref = &mut self.pool[door];
+            door_ref.open = !door_ref.open;
+            door
+        } else {
+            Handle::NONE
+        }
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, dt: f32) {
+        for usable in self.pool.iter_mut() {
+            if usable.kind != UsableKind::Door {
+                continue;
+            }
+            let target = if usable.open {
+                usable.open_offset
+            } else {
+                Vector3::default()
+            };
+            let delta = target - usable.offset;
+            let step = DOOR_SPEED * dt;
+            let delta = if delta.norm() <= step {
+                delta
+            } else {
+                delta.normalize().scale(step)
+            };
+            if delta.norm_squared() > 0.0 {
+                usable.offset += delta;
+                scene.graph[usable.node].local_transform_mut().offset(delta);
+            }
+        }
+    }
+}
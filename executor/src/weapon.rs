@@ -1,78 +1,437 @@
 use crate::{
-    actor::Actor, actor::ActorContainer, message::Message, projectile::ProjectileKind, GameTime,
+    actor::Actor, actor::ActorContainer, effects::EffectKind, error::GameError, message::Message,
+    projectile::ProjectileKind, settings::SoundCategory, tunables, visit_optional, GameTime,
 };
 use fyrox::{
     core::{
-        algebra::{Matrix3, Point3, Vector3},
+        algebra::{Matrix3, Point3, UnitQuaternion, Vector3},
         color::Color,
         math::{ray::Ray, Matrix4Ext, Vector3Ext},
         pool::{Handle, Pool},
-        visitor::{Visit, VisitResult, Visitor},
+        rand::{self, Rng},
+        scope_profile,
+        visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
     scene::{
         base::BaseBuilder,
         collider::InteractionGroups,
-        graph::{physics::RayCastOptions, Graph},
-        light::{point::PointLightBuilder, BaseLightBuilder},
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        light::{
+            point::{PointLight, PointLightBuilder},
+            BaseLightBuilder,
+        },
         node::Node,
         Scene,
     },
     utils::log::{Log, MessageKind},
 };
+use serde::Deserialize;
 use std::{
     ops::{Index, IndexMut},
     path::{Path, PathBuf},
     sync::mpsc::Sender,
 };
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Deserialize)]
 pub enum WeaponKind {
     M4,
     Ak47,
     PlasmaRifle,
     RocketLauncher,
     BattleAxe,
+    Shotgun,
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 6] = [
+        WeaponKind::M4,
+        WeaponKind::Ak47,
+        WeaponKind::PlasmaRifle,
+        WeaponKind::RocketLauncher,
+        WeaponKind::BattleAxe,
+        WeaponKind::Shotgun,
+    ];
+}
+
+/// Kind of rounds a weapon fires, weapons of the same ammo type share the reserve of their
+/// owner.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit)]
+pub enum AmmoType {
+    Rifle,
+    Plasma,
+    Rocket,
+    Shell,
+}
+
+impl Default for AmmoType {
+    fn default() -> Self {
+        AmmoType::Rifle
+    }
 }
 
-#[derive(Visit)]
 pub struct Weapon {
     kind: WeaponKind,
     model: Handle<Node>,
+    // None for weapons without a laser sight.
     laser_dot: Handle<Node>,
+    laser_enabled: bool,
+    // Hidden between shots, every shot lights it up for `MUZZLE_FLASH_TIME`.
+    muzzle_flash: Handle<Node>,
+    muzzle_flash_time_left: f32,
     shot_point: Handle<Node>,
     offset: Vector3<f32>,
+    // Rest position of the model, the ADS position while aiming, dipped down while switching.
+    // Recoil kicks from it.
     dest_offset: Vector3<f32>,
+    state: WeaponState,
+    // Seconds until raising or lowering is done, a raise that waits for the previous weapon
+    // to be put away counts the wait too. Not saved, so a save made mid-switch finishes the
+    // switch on the first update.
+    switch_time_left: f32,
+    aiming: bool,
+    // Smoothly goes to 1 while aiming and back to 0, blends ADS zoom, spread and speed.
+    aim_progress: f32,
     last_shot_time: f64,
     shot_position: Vector3<f32>,
     owner: Handle<Actor>,
-    ammo: u32,
-    #[visit(skip)]
+    ammo_in_magazine: u32,
+    // Reserve ammo of saves made before it was moved to the owner's ammo pool, it is moved
+    // there by `WeaponContainer::move_legacy_reserve_ammo`. Always zero otherwise.
+    reserve_ammo: u32,
+    // Seconds until the magazine is refilled, zero when the weapon isn't reloading.
+    reload_time_left: f32,
+    // Spread (in degrees) that is added to the base spread by recent shots.
+    shot_spread: f32,
+    // Shots since the weapon was created or loaded, counts tracer shots.
+    shots_fired: u32,
+    heat: f32,
+    overheated: bool,
+    // Semi-automatic weapons and bursts need the trigger to be released between shots.
+    trigger_released: bool,
+    burst_shots_left: u32,
+    burst_time_left: f32,
+    // Dry-fire click was played for the current pull of the trigger.
+    dry_fired: bool,
     pub sender: Option<Sender<Message>>,
+    // Time left until the next ray cast of the laser sight.
+    laser_timer: f32,
+    // Dot moves from the point of the previous ray cast to the point of the last one in
+    // between the casts.
+    laser_previous: Vector3<f32>,
+    laser_target: Vector3<f32>,
 }
 
+// Fields that saves made by older versions lack keep their defaults.
+impl Visit for Weapon {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.kind.visit("kind", visitor)?;
+        self.model.visit("model", visitor)?;
+        self.laser_dot.visit("laser_dot", visitor)?;
+        visit_optional(&mut self.laser_enabled, "laser_enabled", visitor)?;
+        visit_optional(&mut self.muzzle_flash, "muzzle_flash", visitor)?;
+        self.shot_point.visit("shot_point", visitor)?;
+        self.offset.visit("offset", visitor)?;
+        self.dest_offset.visit("dest_offset", visitor)?;
+        visit_optional(&mut self.state, "state", visitor)?;
+        self.last_shot_time.visit("last_shot_time", visitor)?;
+        self.shot_position.visit("shot_position", visitor)?;
+        self.owner.visit("owner", visitor)?;
+        visit_optional(&mut self.ammo_in_magazine, "ammo_in_magazine", visitor)?;
+        visit_optional(&mut self.reserve_ammo, "reserve_ammo", visitor)?;
+        if visitor.is_reading() {
+            // Saves made before magazines keep every round of the weapon in `ammo`.
+            let mut ammo = 0u32;
+            match ammo.visit("ammo", visitor) {
+                Ok(()) => self.reserve_ammo = self.load(ammo),
+                Err(VisitError::FieldDoesNotExist(_)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        visit_optional(&mut self.reload_time_left, "reload_time_left", visitor)?;
+        visit_optional(&mut self.shot_spread, "shot_spread", visitor)?;
+        visit_optional(&mut self.heat, "heat", visitor)?;
+        visit_optional(&mut self.overheated, "overheated", visitor)?;
+        visit_optional(&mut self.trigger_released, "trigger_released", visitor)?;
+        visit_optional(&mut self.burst_shots_left, "burst_shots_left", visitor)?;
+        visit_optional(&mut self.burst_time_left, "burst_time_left", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Seconds between ray casts of a laser sight, the dot is interpolated in between.
+const LASER_UPDATE_INTERVAL: f32 = 1.0 / 30.0;
+/// Fraction of the distance to the rest position the model covers every frame, for weapons
+/// that can't aim down sights.
+const OFFSET_FOLLOW_SPEED: f32 = 0.2;
+/// Overheated weapon can shoot again once its heat drops to this fraction of the threshold.
+const OVERHEAT_RECOVERY_FRACTION: f32 = 0.4;
+/// How far below the view the model goes when it is put away, in meters.
+const SWITCH_DIP_DEPTH: f32 = 0.25;
+/// Sound of an overheated weapon venting the heat.
+pub const VENT_SOUND: &str = "data/sounds/plasma_vent.wav";
+/// Time (in seconds) the muzzle flash light fades out in.
+const MUZZLE_FLASH_TIME: f32 = 0.1;
+const WARM_FLASH_COLOR: Color = Color {
+    r: 255,
+    g: 190,
+    b: 90,
+    a: 255,
+};
+
 pub struct WeaponDefinition {
     pub model: &'static str,
     pub shot_sound: &'static str,
+    /// How far bots hear the shot, in units of [`crate::bot::HEARING_DISTANCE`]. Sounds of
+    /// the level have loudness of their gain.
+    pub loudness: f32,
+    /// Ammo the weapon comes with, the magazine is loaded from it and the rest is in reserve.
     pub ammo: u32,
-    pub projectile: ProjectileKind,
+    /// Reserve of the owner the magazine is refilled from, `None` for melee weapons.
+    pub ammo_type: Option<AmmoType>,
+    pub magazine_size: u32,
+    /// Seconds it takes to refill the magazine from the reserve.
+    pub reload_time: f32,
+    /// Sound that is played when reload starts, melee weapons never reload.
+    pub reload_sound: Option<&'static str>,
+    /// Click of the trigger when there is no ammo left at all, once per pull.
+    pub dry_fire_sound: Option<&'static str>,
+    /// Seconds it takes to take the weapon in hands, it can't shoot until then.
+    pub raise_time: f32,
+    /// Seconds it takes to put the weapon away, next weapon is raised after it.
+    pub lower_time: f32,
+    pub attack: WeaponAttack,
+    /// Damage of a single shot or swing, a shot of several pellets splits it between them.
+    pub damage: f32,
+    /// Projectiles lose damage with distance they flew, `None` means they never do.
+    pub damage_falloff: Option<DamageFalloff>,
+    /// Seconds between shots, melee weapons swing every `swing_time` instead.
     pub shoot_interval: f64,
+    pub fire_mode: FireMode,
+    /// Projectiles fired by a single shot, all of them for one round of ammo.
+    pub pellets_per_shot: u32,
+    /// Half-angle of the cone pellets scatter in around the shot direction, in degrees.
+    pub pellet_spread_degrees: f32,
+    /// Every this many shots is a tracer, zero means the weapon never fires tracers.
+    pub tracer_interval: u32,
+    /// Half-angle of the cone projectiles fly in, in degrees, when the weapon is at rest.
+    pub base_spread_degrees: f32,
+    /// Degrees of spread that every shot adds.
+    pub spread_per_shot: f32,
+    /// Spread never grows above this many degrees, however long the trigger is held.
+    pub max_spread_degrees: f32,
+    /// Degrees of spread that are recovered every second.
+    pub spread_recovery_per_second: f32,
+    /// How far (in meters) the model is pushed back by every shot.
+    pub recoil_kick: f32,
+    /// Heat that every shot adds, zero for weapons that never overheat.
+    pub heat_per_shot: f32,
+    /// Heat that is lost every second.
+    pub cooling_rate: f32,
+    /// Weapon overheats at this heat and can't shoot until it cools down to
+    /// `OVERHEAT_RECOVERY_FRACTION` of it.
+    pub overheat_threshold: f32,
+    /// Aiming down sights, melee weapons can't aim.
+    pub ads: Option<AimDownSightsDefinition>,
+    /// Light and sparks at the muzzle on every shot, melee weapons have none.
+    pub muzzle_flash: Option<MuzzleFlashDefinition>,
+    /// Slot (1-9) used by direct weapon selection hotkeys.
+    pub slot: u32,
+    /// Whether the weapon projects a laser dot where it aims, the player can toggle it.
+    pub has_laser_sight: bool,
+    /// Texture that is shown next to ammo counter on HUD.
+    pub icon: &'static str,
+    pub crosshair: CrosshairStyle,
 }
 
+impl WeaponDefinition {
+    /// Damage of every pellet of a shot, pellets of a whole shot deal `damage` together.
+    pub fn pellet_damage(&self) -> f32 {
+        self.damage / self.pellets_per_shot.max(1) as f32
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum WeaponAttack {
+    Projectile(ProjectileKind),
+    /// Melee weapons hit in front of the owner instead of shooting, they need no ammo.
+    Melee {
+        /// Damage of the sweep is replaced by the damage of the weapon.
+        sweep: MeleeSweep,
+        /// Seconds between swings.
+        swing_time: f64,
+    },
+}
+
+/// While aiming down sights the weapon is brought to the center of the screen and the view
+/// zooms in. Spread, zoom and speed are blended in as the model moves to the ADS position.
+#[derive(Copy, Clone, Debug)]
+pub struct AimDownSightsDefinition {
+    /// Position of the model relative to the weapon pivot that centers the sights.
+    pub offset: Vector3<f32>,
+    /// Fraction of the distance to the ADS position the model covers every frame.
+    pub transition_speed: f32,
+    /// Spread is multiplied by this while aiming.
+    pub spread_multiplier: f32,
+    /// Field of view of the owner's camera is divided by this while aiming.
+    pub fov_zoom: f32,
+    /// Movement speed of the owner is multiplied by this while aiming.
+    pub move_speed_multiplier: f32,
+}
+
+/// Damage is full up to `start` meters from the muzzle and goes down linearly to
+/// `min_multiplier` of it at `end` meters.
+#[derive(Copy, Clone, Debug, Default, Visit)]
+pub struct DamageFalloff {
+    pub start: f32,
+    pub end: f32,
+    pub min_multiplier: f32,
+}
+
+impl DamageFalloff {
+    pub fn multiplier(&self, distance: f32) -> f32 {
+        if distance <= self.start {
+            1.0
+        } else if distance >= self.end {
+            self.min_multiplier
+        } else {
+            let t = (distance - self.start) / (self.end - self.start);
+            1.0 + (self.min_multiplier - 1.0) * t
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct MuzzleFlashDefinition {
+    pub color: Color,
+    /// Intensity of the light right after the shot, it fades out to zero.
+    pub intensity: f32,
+}
+
+/// Stage of taking the weapon in hands or putting it away.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Visit)]
+pub enum WeaponState {
+    Holstered,
+    Raising,
+    /// In hands, the only state the weapon can shoot in.
+    Ready,
+    Lowering,
+}
+
+impl Default for WeaponState {
+    fn default() -> Self {
+        WeaponState::Ready
+    }
+}
+
+/// Outcome of `Weapon::try_shoot`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShootResult {
+    Shot,
+    /// Previous shot was too recent.
+    Cooldown,
+    /// Semi-automatic weapon or burst waits for the trigger to be released.
+    TriggerHeld,
+    /// Weapon is being raised or lowered.
+    NotReady,
+    /// Both the magazine and the reserve are empty.
+    NoAmmo,
+    /// Magazine is being refilled, an empty magazine starts reloading on a shot attempt.
+    Reloading,
+    Overheated,
+}
+
+impl ShootResult {
+    pub fn fired(self) -> bool {
+        self == ShootResult::Shot
+    }
+}
+
+/// What a single pull of the trigger does.
+#[derive(Copy, Clone, Debug)]
+pub enum FireMode {
+    /// One shot per pull.
+    SemiAuto,
+    /// `count` shots per pull, `interval` seconds apart. Burst goes on when the trigger is
+    /// released.
+    Burst { count: u32, interval: f64 },
+    /// Weapon shoots for as long as the trigger is held.
+    FullAuto,
+}
+
+/// Look of the HUD crosshair while weapon is in hands. Crosshair consists of four lines
+/// around the center of the screen.
+#[derive(Copy, Clone, Debug)]
+pub struct CrosshairStyle {
+    pub length: f32,
+    /// Distance between the center and the lines when weapon is accurate.
+    pub gap: f32,
+    /// Additional distance at full spread.
+    pub spread_gap: f32,
+}
+
+/// Short-range hit in front of an actor, used by melee weapons and by quick melee bash.
+#[derive(Copy, Clone, Debug)]
+pub struct MeleeSweep {
+    pub range: f32,
+    pub damage: f32,
+    /// Speed (m/s) that is added to velocity of every hit actor.
+    pub knockback: f32,
+    /// Full angle of the arc in front of the attacker in which actors are hit.
+    pub arc_degrees: f32,
+    /// Hits everyone in the arc, otherwise only the closest actor is hit.
+    pub cleave: bool,
+}
+
+/// Weapon-butt bash that can be done with any weapon in hands.
+pub const QUICK_MELEE: MeleeSweep = MeleeSweep {
+    range: 1.5,
+    damage: 10.0,
+    knockback: 4.0,
+    arc_degrees: 90.0,
+    cleave: true,
+};
+
 impl Default for Weapon {
     fn default() -> Self {
         Self {
             kind: WeaponKind::M4,
             laser_dot: Handle::NONE,
+            laser_enabled: true,
+            muzzle_flash: Handle::NONE,
+            muzzle_flash_time_left: 0.0,
             model: Handle::NONE,
             offset: Vector3::default(),
             shot_point: Handle::NONE,
             dest_offset: Vector3::default(),
+            state: WeaponState::Ready,
+            switch_time_left: 0.0,
+            aiming: false,
+            aim_progress: 0.0,
             last_shot_time: 0.0,
             shot_position: Vector3::default(),
             owner: Handle::NONE,
-            ammo: 250,
+            ammo_in_magazine: 0,
+            reserve_ammo: 0,
+            reload_time_left: 0.0,
+            shot_spread: 0.0,
+            heat: 0.0,
+            overheated: false,
+            trigger_released: true,
+            burst_shots_left: 0,
+            burst_time_left: 0.0,
+            dry_fired: false,
+            shots_fired: 0,
             sender: None,
+            laser_timer: 0.0,
+            laser_previous: Default::default(),
+            laser_target: Default::default(),
         }
     }
 }
@@ -84,9 +443,51 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/ak47.FBX",
                     shot_sound: "data/sounds/axe-swing-1.ogg",
-                    ammo: 200,
-                    projectile: ProjectileKind::Bullet,
-                    shoot_interval: 0.15,
+                    loudness: 0.5,
+                    ammo: 0,
+                    ammo_type: None,
+                    magazine_size: 0,
+                    reload_time: 0.0,
+                    reload_sound: None,
+                    dry_fire_sound: None,
+                    raise_time: 0.3,
+                    lower_time: 0.25,
+                    attack: WeaponAttack::Melee {
+                        sweep: MeleeSweep {
+                            range: 2.0,
+                            // Damage of the weapon is used.
+                            damage: 0.0,
+                            knockback: 2.0,
+                            arc_degrees: 70.0,
+                            cleave: false,
+                        },
+                        swing_time: 0.6,
+                    },
+                    damage: 35.0,
+                    damage_falloff: None,
+                    shoot_interval: 0.0,
+                    fire_mode: FireMode::FullAuto,
+                    pellets_per_shot: 1,
+                    pellet_spread_degrees: 0.0,
+                    tracer_interval: 0,
+                    base_spread_degrees: 0.0,
+                    spread_per_shot: 0.0,
+                    max_spread_degrees: 0.0,
+                    spread_recovery_per_second: 0.0,
+                    recoil_kick: 0.0,
+                    heat_per_shot: 0.0,
+                    cooling_rate: 0.0,
+                    overheat_threshold: 0.0,
+                    ads: None,
+                    muzzle_flash: None,
+                    slot: 1,
+                    has_laser_sight: false,
+                    icon: "data/ui/ammo_icon.png",
+                    crosshair: CrosshairStyle {
+                        length: 6.0,
+                        gap: 2.0,
+                        spread_gap: 2.0,
+                    },
                 };
                 &DEFINITION
             }
@@ -94,9 +495,57 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/m4.FBX",
                     shot_sound: "data/sounds/m4_shot.ogg",
+                    loudness: 6.0,
                     ammo: 200,
-                    projectile: ProjectileKind::Bullet,
+                    ammo_type: Some(AmmoType::Rifle),
+                    magazine_size: 30,
+                    reload_time: 2.0,
+                    reload_sound: Some("data/sounds/m4_reload.wav"),
+                    dry_fire_sound: Some("data/sounds/dry_fire.wav"),
+                    raise_time: 0.4,
+                    lower_time: 0.3,
+                    attack: WeaponAttack::Projectile(ProjectileKind::Bullet),
+                    damage: 14.0,
+                    damage_falloff: Some(DamageFalloff {
+                        start: 20.0,
+                        end: 60.0,
+                        min_multiplier: 0.6,
+                    }),
                     shoot_interval: 0.15,
+                    fire_mode: FireMode::Burst {
+                        count: 3,
+                        interval: 0.07,
+                    },
+                    pellets_per_shot: 1,
+                    pellet_spread_degrees: 0.0,
+                    tracer_interval: 4,
+                    base_spread_degrees: 0.5,
+                    spread_per_shot: 0.3,
+                    max_spread_degrees: 3.0,
+                    spread_recovery_per_second: 6.0,
+                    recoil_kick: 0.04,
+                    heat_per_shot: 0.0,
+                    cooling_rate: 0.0,
+                    overheat_threshold: 0.0,
+                    ads: Some(AimDownSightsDefinition {
+                        offset: Vector3::new(0.065, 0.025, 0.0),
+                        transition_speed: 0.25,
+                        spread_multiplier: 0.5,
+                        fov_zoom: 1.5,
+                        move_speed_multiplier: 0.6,
+                    }),
+                    muzzle_flash: Some(MuzzleFlashDefinition {
+                        color: WARM_FLASH_COLOR,
+                        intensity: 2.0,
+                    }),
+                    slot: 2,
+                    has_laser_sight: true,
+                    icon: "data/ui/ammo_icon.png",
+                    crosshair: CrosshairStyle {
+                        length: 10.0,
+                        gap: 4.0,
+                        spread_gap: 20.0,
+                    },
                 };
                 &DEFINITION
             }
@@ -104,9 +553,54 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/ak47.FBX",
                     shot_sound: "data/sounds/ak47.ogg",
+                    loudness: 7.0,
                     ammo: 200,
-                    projectile: ProjectileKind::Bullet,
+                    ammo_type: Some(AmmoType::Rifle),
+                    magazine_size: 30,
+                    reload_time: 2.3,
+                    reload_sound: Some("data/sounds/ak47_reload.wav"),
+                    dry_fire_sound: Some("data/sounds/dry_fire.wav"),
+                    raise_time: 0.45,
+                    lower_time: 0.3,
+                    attack: WeaponAttack::Projectile(ProjectileKind::Bullet),
+                    damage: 18.0,
+                    damage_falloff: Some(DamageFalloff {
+                        start: 15.0,
+                        end: 50.0,
+                        min_multiplier: 0.5,
+                    }),
                     shoot_interval: 0.15,
+                    fire_mode: FireMode::FullAuto,
+                    pellets_per_shot: 1,
+                    pellet_spread_degrees: 0.0,
+                    tracer_interval: 2,
+                    base_spread_degrees: 1.0,
+                    spread_per_shot: 0.7,
+                    max_spread_degrees: 5.0,
+                    spread_recovery_per_second: 4.0,
+                    recoil_kick: 0.07,
+                    heat_per_shot: 0.0,
+                    cooling_rate: 0.0,
+                    overheat_threshold: 0.0,
+                    ads: Some(AimDownSightsDefinition {
+                        offset: Vector3::new(0.065, 0.022, 0.0),
+                        transition_speed: 0.2,
+                        spread_multiplier: 0.6,
+                        fov_zoom: 1.4,
+                        move_speed_multiplier: 0.6,
+                    }),
+                    muzzle_flash: Some(MuzzleFlashDefinition {
+                        color: WARM_FLASH_COLOR,
+                        intensity: 2.5,
+                    }),
+                    slot: 2,
+                    has_laser_sight: true,
+                    icon: "data/ui/ammo_icon.png",
+                    crosshair: CrosshairStyle {
+                        length: 10.0,
+                        gap: 4.0,
+                        spread_gap: 20.0,
+                    },
                 };
                 &DEFINITION
             }
@@ -114,9 +608,57 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/plasma_rifle.FBX",
                     shot_sound: "data/sounds/plasma_shot.ogg",
+                    loudness: 4.0,
                     ammo: 100,
-                    projectile: ProjectileKind::Plasma,
+                    ammo_type: Some(AmmoType::Plasma),
+                    // Heat limits the rifle instead of reloads.
+                    magazine_size: 100,
+                    reload_time: 2.5,
+                    reload_sound: Some("data/sounds/plasma_reload.wav"),
+                    dry_fire_sound: Some("data/sounds/dry_fire.wav"),
+                    raise_time: 0.5,
+                    lower_time: 0.35,
+                    attack: WeaponAttack::Projectile(ProjectileKind::Plasma),
+                    damage: 28.0,
+                    damage_falloff: None,
                     shoot_interval: 0.25,
+                    fire_mode: FireMode::FullAuto,
+                    pellets_per_shot: 1,
+                    pellet_spread_degrees: 0.0,
+                    tracer_interval: 0,
+                    base_spread_degrees: 0.3,
+                    spread_per_shot: 0.6,
+                    max_spread_degrees: 2.5,
+                    spread_recovery_per_second: 2.0,
+                    recoil_kick: 0.03,
+                    // Sustained fire overheats the rifle in about three seconds.
+                    heat_per_shot: 0.15,
+                    cooling_rate: 0.3,
+                    overheat_threshold: 1.0,
+                    ads: Some(AimDownSightsDefinition {
+                        offset: Vector3::new(0.065, 0.03, 0.0),
+                        transition_speed: 0.25,
+                        spread_multiplier: 0.5,
+                        fov_zoom: 1.3,
+                        move_speed_multiplier: 0.65,
+                    }),
+                    muzzle_flash: Some(MuzzleFlashDefinition {
+                        color: Color {
+                            r: 60,
+                            g: 255,
+                            b: 200,
+                            a: 255,
+                        },
+                        intensity: 3.0,
+                    }),
+                    slot: 3,
+                    has_laser_sight: true,
+                    icon: "data/ui/ammo_icon.png",
+                    crosshair: CrosshairStyle {
+                        length: 8.0,
+                        gap: 6.0,
+                        spread_gap: 12.0,
+                    },
                 };
                 &DEFINITION
             }
@@ -124,9 +666,106 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/Rpg7.FBX",
                     shot_sound: "data/sounds/grenade_launcher_fire.ogg",
+                    loudness: 10.0,
                     ammo: 100,
-                    projectile: ProjectileKind::Rocket,
+                    ammo_type: Some(AmmoType::Rocket),
+                    magazine_size: 1,
+                    reload_time: 1.8,
+                    reload_sound: Some("data/sounds/rocket_launcher_reload.wav"),
+                    dry_fire_sound: Some("data/sounds/dry_fire.wav"),
+                    raise_time: 0.7,
+                    lower_time: 0.45,
+                    attack: WeaponAttack::Projectile(ProjectileKind::Rocket),
+                    damage: 60.0,
+                    damage_falloff: None,
                     shoot_interval: 1.5,
+                    fire_mode: FireMode::SemiAuto,
+                    pellets_per_shot: 1,
+                    pellet_spread_degrees: 0.0,
+                    tracer_interval: 0,
+                    base_spread_degrees: 0.2,
+                    spread_per_shot: 2.0,
+                    max_spread_degrees: 2.2,
+                    spread_recovery_per_second: 1.5,
+                    recoil_kick: 0.15,
+                    heat_per_shot: 0.0,
+                    cooling_rate: 0.0,
+                    overheat_threshold: 0.0,
+                    ads: Some(AimDownSightsDefinition {
+                        offset: Vector3::new(0.065, 0.01, -0.05),
+                        transition_speed: 0.15,
+                        spread_multiplier: 0.8,
+                        fov_zoom: 1.2,
+                        move_speed_multiplier: 0.5,
+                    }),
+                    muzzle_flash: Some(MuzzleFlashDefinition {
+                        color: WARM_FLASH_COLOR,
+                        intensity: 4.0,
+                    }),
+                    slot: 4,
+                    has_laser_sight: false,
+                    icon: "data/ui/ammo_icon.png",
+                    crosshair: CrosshairStyle {
+                        length: 14.0,
+                        gap: 10.0,
+                        spread_gap: 10.0,
+                    },
+                };
+                &DEFINITION
+            }
+            WeaponKind::Shotgun => {
+                static DEFINITION: WeaponDefinition = WeaponDefinition {
+                    model: "data/models/m4.FBX",
+                    shot_sound: "data/sounds/shotgun_shot.wav",
+                    loudness: 8.0,
+                    ammo: 32,
+                    ammo_type: Some(AmmoType::Shell),
+                    magazine_size: 8,
+                    reload_time: 3.0,
+                    reload_sound: Some("data/sounds/shotgun_reload.wav"),
+                    dry_fire_sound: Some("data/sounds/dry_fire.wav"),
+                    raise_time: 0.5,
+                    lower_time: 0.35,
+                    attack: WeaponAttack::Projectile(ProjectileKind::Pellet),
+                    damage: 64.0,
+                    damage_falloff: Some(DamageFalloff {
+                        start: 5.0,
+                        end: 20.0,
+                        min_multiplier: 0.2,
+                    }),
+                    // Pump action.
+                    shoot_interval: 0.9,
+                    fire_mode: FireMode::SemiAuto,
+                    pellets_per_shot: 8,
+                    pellet_spread_degrees: 6.0,
+                    tracer_interval: 0,
+                    base_spread_degrees: 0.5,
+                    spread_per_shot: 1.5,
+                    max_spread_degrees: 3.0,
+                    spread_recovery_per_second: 2.0,
+                    recoil_kick: 0.12,
+                    heat_per_shot: 0.0,
+                    cooling_rate: 0.0,
+                    overheat_threshold: 0.0,
+                    ads: Some(AimDownSightsDefinition {
+                        offset: Vector3::new(0.065, 0.02, 0.0),
+                        transition_speed: 0.2,
+                        spread_multiplier: 0.8,
+                        fov_zoom: 1.15,
+                        move_speed_multiplier: 0.6,
+                    }),
+                    muzzle_flash: Some(MuzzleFlashDefinition {
+                        color: WARM_FLASH_COLOR,
+                        intensity: 4.0,
+                    }),
+                    slot: 5,
+                    has_laser_sight: false,
+                    icon: "data/ui/ammo_icon.png",
+                    crosshair: CrosshairStyle {
+                        length: 6.0,
+                        gap: 14.0,
+                        spread_gap: 10.0,
+                    },
                 };
                 &DEFINITION
             }
@@ -138,22 +777,39 @@ impl Weapon {
         resource_manager: ResourceManager,
         scene: &mut Scene,
         sender: Sender<Message>,
-    ) -> Weapon {
+    ) -> Result<Weapon, GameError> {
         let definition = Self::get_definition(kind);
 
         let model = resource_manager
             .request_model(Path::new(definition.model))
             .await
-            .unwrap()
+            .map_err(|e| GameError::resource_load(definition.model, e))?
             .instantiate_geometry(scene);
 
-        let laser_dot = PointLightBuilder::new(
-            BaseLightBuilder::new(BaseBuilder::new())
-                .with_color(Color::opaque(255, 0, 0))
+        let laser_dot = if definition.has_laser_sight {
+            PointLightBuilder::new(
+                BaseLightBuilder::new(BaseBuilder::new())
+                    .with_color(Color::opaque(255, 0, 0))
+                    .with_scatter_enabled(false)
+                    .cast_shadows(false),
+            )
+            .with_radius(0.5)
+            .build(&mut scene.graph)
+        } else {
+            Handle::NONE
+        };
+
+        let muzzle_flash = PointLightBuilder::new(
+            BaseLightBuilder::new(BaseBuilder::new().with_visibility(false))
+                .with_color(
+                    definition
+                        .muzzle_flash
+                        .map_or(WARM_FLASH_COLOR, |flash| flash.color),
+                )
                 .with_scatter_enabled(false)
                 .cast_shadows(false),
         )
-        .with_radius(0.5)
+        .with_radius(4.0)
         .build(&mut scene.graph);
 
         let shot_point = scene.graph.find_by_name(model, "Weapon:ShotPoint");
@@ -162,34 +818,199 @@ impl Weapon {
             Log::writeln(MessageKind::Warning, "Shot point not found!".to_owned());
         }
 
-        Weapon {
+        Ok(Weapon {
             kind,
             laser_dot,
+            muzzle_flash,
             model,
             shot_point,
-            ammo: definition.ammo,
+            ammo_in_magazine: definition.ammo.min(definition.magazine_size),
             sender: Some(sender),
             ..Default::default()
-        }
+        })
     }
 
-    pub fn set_visibility(&self, visibility: bool, graph: &mut Graph) {
+    /// Puts the weapon in hands or away instantly, see `raise` and `lower` for switching.
+    pub fn set_visibility(&mut self, visibility: bool, graph: &mut Graph) {
+        self.state = if visibility {
+            WeaponState::Ready
+        } else {
+            WeaponState::Holstered
+        };
+        self.switch_time_left = 0.0;
+        self.show_model(visibility, graph);
+    }
+
+    fn show_model(&self, visibility: bool, graph: &mut Graph) {
         graph[self.model].set_visibility(visibility);
-        graph[self.laser_dot].set_visibility(visibility);
+        if self.laser_dot.is_some() {
+            graph[self.laser_dot].set_visibility(visibility && self.laser_enabled);
+        }
+    }
+
+    /// Starts taking the weapon in hands after `delay` seconds, the time the previous weapon
+    /// needs to be put away. Weapon that is being lowered comes back up from where it is.
+    pub fn raise(&mut self, delay: f32, graph: &mut Graph) {
+        if self.state == WeaponState::Ready {
+            return;
+        }
+        let raise_time = self.definition().raise_time;
+        self.switch_time_left = delay.max(0.0) + self.switch_dip() * raise_time;
+        self.state = WeaponState::Raising;
+        self.show_model(self.switch_time_left <= raise_time, graph);
+    }
+
+    /// Starts putting the weapon away, weapon that is being raised goes back down from where
+    /// it is. Weapon that still waits to be raised is holstered right away.
+    pub fn lower(&mut self, graph: &mut Graph) {
+        let definition = self.definition();
+        match self.state {
+            WeaponState::Holstered | WeaponState::Lowering => (),
+            WeaponState::Raising if self.switch_time_left > definition.raise_time => {
+                self.set_visibility(false, graph);
+            }
+            WeaponState::Raising | WeaponState::Ready => {
+                self.switch_time_left = (1.0 - self.switch_dip()) * definition.lower_time;
+                self.state = WeaponState::Lowering;
+            }
+        }
+    }
+
+    pub fn state(&self) -> WeaponState {
+        self.state
+    }
+
+    /// Time until the weapon is put away, zero if it isn't being lowered.
+    pub fn lowering_time_left(&self) -> f32 {
+        if self.state == WeaponState::Lowering {
+            self.switch_time_left
+        } else {
+            0.0
+        }
+    }
+
+    // How far the model is dipped below the view by switching, from 0 in hands to 1 out of
+    // view.
+    fn switch_dip(&self) -> f32 {
+        let definition = self.definition();
+        match self.state {
+            WeaponState::Ready => 0.0,
+            WeaponState::Holstered => 1.0,
+            WeaponState::Raising => time_fraction(self.switch_time_left, definition.raise_time),
+            WeaponState::Lowering => {
+                1.0 - time_fraction(self.switch_time_left, definition.lower_time)
+            }
+        }
+    }
+
+    // Counts down raising and lowering, the model shows up once a delayed raise starts and
+    // is hidden once the weapon is put away.
+    fn update_switch(&mut self, graph: &mut Graph, dt: f32) {
+        match self.state {
+            WeaponState::Raising => {
+                self.switch_time_left = (self.switch_time_left - dt).max(0.0);
+                if self.switch_time_left <= self.definition().raise_time
+                    && !graph[self.model].visibility()
+                {
+                    self.show_model(true, graph);
+                }
+                if self.switch_time_left <= 0.0 {
+                    self.state = WeaponState::Ready;
+                }
+            }
+            WeaponState::Lowering => {
+                self.switch_time_left = (self.switch_time_left - dt).max(0.0);
+                if self.switch_time_left <= 0.0 {
+                    self.set_visibility(false, graph);
+                }
+            }
+            WeaponState::Holstered | WeaponState::Ready => (),
+        }
+    }
+
+    /// Turns the laser sight on or off, weapons without one ignore it.
+    pub fn set_laser_enabled(&mut self, enabled: bool, graph: &mut Graph) {
+        self.laser_enabled = enabled;
+        if self.laser_dot.is_some() {
+            let visibility = enabled && graph[self.model].visibility();
+            graph[self.laser_dot].set_visibility(visibility);
+        }
+    }
+
+    pub fn is_laser_enabled(&self) -> bool {
+        self.laser_enabled
     }
 
     pub fn get_model(&self) -> Handle<Node> {
         self.model
     }
 
-    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer) {
-        self.offset.follow(&self.dest_offset, 0.2);
+    pub fn update(
+        &mut self,
+        self_handle: Handle<Weapon>,
+        scene: &mut Scene,
+        actors: &ActorContainer,
+        reserve_ammo: u32,
+        show_laser: bool,
+        dt: f32,
+        query_buffer: &mut Vec<Intersection>,
+    ) -> u32 {
+        self.update_switch(&mut scene.graph, dt);
+        let rest_offset = match self.definition().ads {
+            Some(ads) if self.aiming => ads.offset,
+            _ => Vector3::default(),
+        };
+        self.dest_offset = rest_offset - Vector3::y().scale(SWITCH_DIP_DEPTH * self.switch_dip());
+
+        let follow_speed = self
+            .definition()
+            .ads
+            .map_or(OFFSET_FOLLOW_SPEED, |ads| ads.transition_speed);
+        self.offset.follow(&self.dest_offset, follow_speed);
+        let aim_target = if self.aiming { 1.0 } else { 0.0 };
+        self.aim_progress += (aim_target - self.aim_progress) * follow_speed;
 
-        self.update_laser_sight(&mut scene.graph, actors);
+        self.shot_spread =
+            (self.shot_spread - self.definition().spread_recovery_per_second * dt).max(0.0);
+
+        self.cool_down(dt);
+        self.update_burst(self_handle, dt);
+
+        let mut loaded = 0;
+        if self.reload_time_left > 0.0 {
+            self.reload_time_left -= dt;
+            if self.reload_time_left <= 0.0 {
+                self.reload_time_left = 0.0;
+                loaded =
+                    (self.definition().magazine_size - self.ammo_in_magazine).min(reserve_ammo);
+                self.ammo_in_magazine += loaded;
+            }
+        }
+
+        if self.definition().has_laser_sight && self.laser_dot.is_some() {
+            self.update_laser_sight(&mut scene.graph, actors, show_laser, dt, query_buffer);
+        }
 
         let node = &mut scene.graph[self.model];
         node.local_transform_mut().set_position(self.offset);
         self.shot_position = node.global_position();
+
+        // Weapons of old saves have no muzzle flash light.
+        if self.muzzle_flash_time_left > 0.0 && self.muzzle_flash.is_some() {
+            self.muzzle_flash_time_left = (self.muzzle_flash_time_left - dt).max(0.0);
+            let position = self.get_shot_position(&scene.graph);
+            let intensity = self.definition().muzzle_flash.map_or(0.0, |flash| {
+                flash.intensity * self.muzzle_flash_time_left / MUZZLE_FLASH_TIME
+            });
+            let node = &mut scene.graph[self.muzzle_flash];
+            node.set_visibility(self.muzzle_flash_time_left > 0.0);
+            node.local_transform_mut().set_position(position);
+            if let Some(light) = node.cast_mut::<PointLight>() {
+                light.set_intensity(intensity);
+            }
+        }
+
+        loaded
     }
 
     pub fn get_shot_position(&self, graph: &Graph) -> Vector3<f32> {
@@ -213,17 +1034,71 @@ impl Weapon {
         graph[self.model].global_transform().basis()
     }
 
-    pub fn add_ammo(&mut self, amount: u32) {
-        self.ammo += amount;
+    /// Replaces the magazine with up to `ammo` rounds, returns the rest that goes to the
+    /// reserve of the owner.
+    pub fn load(&mut self, ammo: u32) -> u32 {
+        self.ammo_in_magazine = ammo.min(self.definition().magazine_size);
+        ammo - self.ammo_in_magazine
     }
 
-    fn update_laser_sight(&self, graph: &mut Graph, actors: &ActorContainer) {
+    /// Takes the reserve that a weapon from an old save came with.
+    pub fn take_legacy_reserve_ammo(&mut self) -> u32 {
+        std::mem::take(&mut self.reserve_ammo)
+    }
+
+    // Holstered weapons, disabled lasers and lasers that are not shown hide the dot, nothing
+    // needs a ray cast then.
+    fn update_laser_sight(
+        &mut self,
+        graph: &mut Graph,
+        actors: &ActorContainer,
+        show_laser: bool,
+        dt: f32,
+        query_buffer: &mut Vec<Intersection>,
+    ) {
+        let was_visible = graph[self.laser_dot].visibility();
+        let visible = show_laser && self.laser_enabled && graph[self.model].visibility();
+        graph[self.laser_dot].set_visibility(visible);
+        if !visible {
+            // Dot is cast for right away once it's shown again.
+            self.laser_timer = 0.0;
+            return;
+        }
+
+        self.laser_timer -= dt;
+        if self.laser_timer <= 0.0 {
+            self.laser_timer = LASER_UPDATE_INTERVAL;
+            let target = self.cast_laser(graph, actors, query_buffer);
+            // Dot that was just shown has nowhere to move from.
+            self.laser_previous = if was_visible {
+                self.laser_target
+            } else {
+                target
+            };
+            self.laser_target = target;
+        }
+
+        let t = 1.0 - self.laser_timer / LASER_UPDATE_INTERVAL;
+        let position = self
+            .laser_previous
+            .lerp(&self.laser_target, t.clamp(0.0, 1.0));
+        graph[self.laser_dot]
+            .local_transform_mut()
+            .set_position(position);
+    }
+
+    // Point where the laser sight hits something, the owner is ignored.
+    fn cast_laser(
+        &self,
+        graph: &Graph,
+        actors: &ActorContainer,
+        query_buffer: &mut Vec<Intersection>,
+    ) -> Vector3<f32> {
         let mut laser_dot_position = Vector3::default();
         let model = &graph[self.model];
         let begin = model.global_position();
         let end = begin + model.look_vector().scale(100.0);
         let ray = Ray::from_two_points(begin, end);
-        let mut query_buffer = Vec::default();
         graph.physics.cast_ray(
             RayCastOptions {
                 ray_origin: Point3::from(ray.origin),
@@ -232,7 +1107,7 @@ impl Weapon {
                 groups: InteractionGroups::default(),
                 sort_results: true,
             },
-            &mut query_buffer,
+            query_buffer,
         );
         'hit_loop: for hit in query_buffer.iter() {
             // Filter hit with owner capsule
@@ -251,13 +1126,108 @@ impl Weapon {
             break 'hit_loop;
         }
 
-        graph[self.laser_dot]
-            .local_transform_mut()
-            .set_position(laser_dot_position);
+        laser_dot_position
     }
 
-    pub fn ammo(&self) -> u32 {
-        self.ammo
+    pub fn ammo_in_magazine(&self) -> u32 {
+        self.ammo_in_magazine
+    }
+
+    /// Half-angle of the cone projectiles fly in, in degrees.
+    pub fn current_spread(&self) -> f32 {
+        (self.definition().base_spread_degrees + self.shot_spread)
+            * self.ads_factor(|ads| ads.spread_multiplier)
+    }
+
+    /// Brings the weapon to the ADS position or back to rest, weapons that can't aim ignore it.
+    pub fn set_aiming(&mut self, aiming: bool) {
+        self.aiming = aiming && self.definition().ads.is_some();
+    }
+
+    pub fn is_aiming(&self) -> bool {
+        self.aiming
+    }
+
+    /// Field of view of the owner's camera should be divided by this, 1.0 when not aiming.
+    pub fn desired_fov_zoom(&self) -> f32 {
+        self.ads_factor(|ads| ads.fov_zoom)
+    }
+
+    /// Movement speed of the owner should be multiplied by this, 1.0 when not aiming.
+    pub fn move_speed_factor(&self) -> f32 {
+        self.ads_factor(|ads| ads.move_speed_multiplier)
+    }
+
+    // Blends a multiplier from 1.0 at rest to its full value at the ADS position.
+    fn ads_factor(&self, factor: impl FnOnce(&AimDownSightsDefinition) -> f32) -> f32 {
+        self.definition()
+            .ads
+            .as_ref()
+            .map_or(1.0, |ads| 1.0 + (factor(ads) - 1.0) * self.aim_progress)
+    }
+
+    /// Random direction within the spread cone around `direction`.
+    pub fn spread_direction(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        spread_direction(direction, self.current_spread())
+    }
+
+    /// Last shot of the weapon was a tracer, every `tracer_interval`-th shot is.
+    pub fn is_tracer_shot(&self) -> bool {
+        let interval = self.definition().tracer_interval;
+        interval > 0 && self.shots_fired % interval == 0
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_time_left > 0.0
+    }
+
+    /// Heat in [0; 1] range where 1 is the overheat threshold, always 0 for weapons that
+    /// never overheat.
+    pub fn heat_fraction(&self) -> f32 {
+        let threshold = self.definition().overheat_threshold;
+        if threshold > 0.0 {
+            (self.heat / threshold).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    /// Starts refilling the magazine from `reserve_ammo` of the owner, the magazine is full
+    /// after `reload_time` seconds. Returns false if there is nothing to reload or the weapon
+    /// is reloading already.
+    pub fn start_reload(&mut self, reserve_ammo: u32, graph: &Graph) -> bool {
+        let definition = self.definition();
+        let reload_sound = match definition.reload_sound {
+            Some(reload_sound) => reload_sound,
+            None => return false,
+        };
+        if self.is_reloading()
+            || reserve_ammo == 0
+            || self.ammo_in_magazine >= definition.magazine_size
+        {
+            return false;
+        }
+
+        self.reload_time_left = definition.reload_time;
+
+        if let Some(sender) = self.sender.as_ref() {
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(reload_sound),
+                    position: self.get_shot_position(graph),
+                    gain: 1.0,
+                    rolloff_factor: 5.0,
+                    radius: 3.0,
+                    category: SoundCategory::Sfx,
+                })
+                .unwrap();
+        }
+
+        true
     }
 
     pub fn owner(&self) -> Handle<Actor> {
@@ -272,48 +1242,252 @@ impl Weapon {
         Self::get_definition(self.kind)
     }
 
-    pub fn try_shoot(&mut self, scene: &mut Scene, time: GameTime) -> bool {
-        if self.ammo != 0 && time.elapsed - self.last_shot_time >= self.definition().shoot_interval
+    pub fn is_melee(&self) -> bool {
+        matches!(self.definition().attack, WeaponAttack::Melee { .. })
+    }
+
+    /// Seconds between shots or swings.
+    pub fn attack_interval(&self) -> f64 {
+        match self.definition().attack {
+            WeaponAttack::Projectile(_) => tunables::weapon(self.kind)
+                .shoot_interval
+                .unwrap_or(self.definition().shoot_interval),
+            WeaponAttack::Melee { swing_time, .. } => swing_time,
+        }
+    }
+
+    fn update_burst(&mut self, self_handle: Handle<Weapon>, dt: f32) {
+        if self.burst_shots_left > 0 {
+            self.burst_time_left -= dt;
+            if self.burst_time_left <= 0.0 {
+                if let FireMode::Burst { interval, .. } = self.definition().fire_mode {
+                    self.burst_time_left += interval as f32;
+                }
+                self.burst_shots_left -= 1;
+                if let Some(sender) = self.sender.as_ref() {
+                    sender
+                        .send(Message::ShootBurst {
+                            weapon: self_handle,
+                        })
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    fn cool_down(&mut self, dt: f32) {
+        self.heat = (self.heat - self.definition().cooling_rate * dt).max(0.0);
+        if self.overheated
+            && self.heat <= self.definition().overheat_threshold * OVERHEAT_RECOVERY_FRACTION
         {
-            self.ammo -= 1;
+            self.overheated = false;
+        }
+    }
+
+    /// Owner has let go of the trigger, semi-automatic weapon can shoot again.
+    pub fn release_trigger(&mut self) {
+        self.trigger_released = true;
+        self.dry_fired = false;
+    }
 
-            self.offset = Vector3::new(0.0, 0.0, -0.05);
+    /// Weapon with empty magazine starts reloading from `reserve_ammo` of the owner instead
+    /// of shooting. `is_burst_shot` is set for shots requested by `Message::ShootBurst`.
+    pub fn try_shoot(
+        &mut self,
+        reserve_ammo: u32,
+        scene: &mut Scene,
+        time: GameTime,
+        is_burst_shot: bool,
+    ) -> ShootResult {
+        let definition = self.definition();
+        if self.state != WeaponState::Ready {
+            self.burst_shots_left = 0;
+            return ShootResult::NotReady;
+        }
+        if !is_burst_shot {
+            let needs_release = !matches!(definition.fire_mode, FireMode::FullAuto);
+            if needs_release && (!self.trigger_released || self.burst_shots_left > 0) {
+                return ShootResult::TriggerHeld;
+            }
+            if time.elapsed - self.last_shot_time < self.attack_interval() {
+                return ShootResult::Cooldown;
+            }
+        }
+        // Melee weapons swing, and play their sound, whether they hit anything or not.
+        if self.is_melee() {
             self.last_shot_time = time.elapsed;
+            self.play_shot_sound(&scene.graph);
+            return ShootResult::Shot;
+        }
+        if self.is_reloading() {
+            self.burst_shots_left = 0;
+            return ShootResult::Reloading;
+        }
+        if self.overheated {
+            self.burst_shots_left = 0;
+            return ShootResult::Overheated;
+        }
+        if self.ammo_in_magazine == 0 {
+            self.burst_shots_left = 0;
+            if self.start_reload(reserve_ammo, &scene.graph) {
+                return ShootResult::Reloading;
+            }
+            if !std::mem::replace(&mut self.dry_fired, true) {
+                self.play_dry_fire_sound(&scene.graph);
+            }
+            return ShootResult::NoAmmo;
+        }
 
-            let position = self.get_shot_position(&scene.graph);
+        self.ammo_in_magazine -= 1;
+        if self.ammo_in_magazine == 0 {
+            self.burst_shots_left = 0;
+            self.start_reload(reserve_ammo, &scene.graph);
+        }
 
+        self.trigger_released = false;
+        if let (false, FireMode::Burst { count, interval }) = (is_burst_shot, definition.fire_mode)
+        {
+            if self.ammo_in_magazine > 0 {
+                self.burst_shots_left = count.saturating_sub(1);
+                self.burst_time_left = interval as f32;
+            }
+        }
+
+        self.offset = self.dest_offset + Vector3::new(0.0, 0.0, -definition.recoil_kick);
+        self.shot_spread = (self.shot_spread + definition.spread_per_shot)
+            .min(definition.max_spread_degrees - definition.base_spread_degrees)
+            .max(0.0);
+        self.last_shot_time = time.elapsed;
+        self.shots_fired = self.shots_fired.wrapping_add(1);
+
+        if definition.overheat_threshold > 0.0 {
+            self.heat += definition.heat_per_shot;
+            if self.heat >= definition.overheat_threshold {
+                self.overheated = true;
+                self.burst_shots_left = 0;
+                if let Some(sender) = self.sender.as_ref() {
+                    sender
+                        .send(Message::PlaySound {
+                            path: PathBuf::from(VENT_SOUND),
+                            position: self.get_shot_position(&scene.graph),
+                            gain: 1.0,
+                            rolloff_factor: 5.0,
+                            radius: 3.0,
+                            category: SoundCategory::Sfx,
+                        })
+                        .unwrap();
+                }
+            }
+        }
+
+        if definition.muzzle_flash.is_some() {
+            // Light is placed and faded by `update`.
+            self.muzzle_flash_time_left = MUZZLE_FLASH_TIME;
             if let Some(sender) = self.sender.as_ref() {
                 sender
-                    .send(Message::PlaySound {
-                        path: PathBuf::from(self.definition().shot_sound),
-                        position,
-                        gain: 1.0,
-                        rolloff_factor: 5.0,
-                        radius: 3.0,
+                    .send(Message::CreateEffect {
+                        kind: EffectKind::MuzzleFlash,
+                        position: self.get_shot_position(&scene.graph),
+                        direction: self.get_shot_direction(&scene.graph),
                     })
                     .unwrap();
             }
+        }
 
-            true
-        } else {
-            false
+        self.play_shot_sound(&scene.graph);
+
+        ShootResult::Shot
+    }
+
+    fn play_dry_fire_sound(&self, graph: &Graph) {
+        if let (Some(sender), Some(dry_fire_sound)) =
+            (self.sender.as_ref(), self.definition().dry_fire_sound)
+        {
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(dry_fire_sound),
+                    position: self.get_shot_position(graph),
+                    gain: 0.6,
+                    rolloff_factor: 5.0,
+                    radius: 2.0,
+                    category: SoundCategory::Sfx,
+                })
+                .unwrap();
+        }
+    }
+
+    fn play_shot_sound(&self, graph: &Graph) {
+        if let Some(sender) = self.sender.as_ref() {
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(self.definition().shot_sound),
+                    position: self.get_shot_position(graph),
+                    gain: 1.0,
+                    rolloff_factor: 5.0,
+                    radius: 3.0,
+                    category: SoundCategory::Sfx,
+                })
+                .unwrap();
         }
     }
 
     pub fn clean_up(&mut self, scene: &mut Scene) {
         scene.graph.remove_node(self.model);
-        scene.graph.remove_node(self.laser_dot);
+        if self.laser_dot.is_some() {
+            scene.graph.remove_node(self.laser_dot);
+        }
+        if self.muzzle_flash.is_some() {
+            scene.graph.remove_node(self.muzzle_flash);
+        }
     }
 }
 
+// Fraction of `total` that `time` is, zero for instant actions.
+fn time_fraction(time: f32, total: f32) -> f32 {
+    if total > 0.0 {
+        (time / total).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Random direction within a cone of given half-angle around `direction`.
+pub fn spread_direction(direction: Vector3<f32>, max_angle_degrees: f32) -> Vector3<f32> {
+    let direction = match direction.try_normalize(f32::EPSILON) {
+        Some(direction) => direction,
+        None => return direction,
+    };
+    let side = direction
+        .cross(&Vector3::y())
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::x);
+    let up = side.cross(&direction);
+    let mut rng = rand::thread_rng();
+    let around = rng.gen_range(0.0..std::f32::consts::TAU);
+    let axis = side.scale(around.cos()) + up.scale(around.sin());
+    let angle = rng.gen_range(0.0..=max_angle_degrees.max(0.0).to_radians());
+    UnitQuaternion::from_scaled_axis(axis.scale(angle)) * direction
+}
+
 #[derive(Default, Visit)]
 pub struct WeaponContainer {
     pool: Pool<Weapon>,
+    // Results of laser sight ray casts, kept between frames to not allocate on every cast.
+    #[visit(skip)]
+    query_buffer: Vec<Intersection>,
+    /// Laser sights of weapons of other actors than the local player are hidden unless this
+    /// is set, a spectator would want to see them.
+    #[visit(skip)]
+    pub show_all_lasers: bool,
 }
 
 impl WeaponContainer {
     pub fn new() -> Self {
-        Self { pool: Pool::new() }
+        Self {
+            pool: Pool::new(),
+            query_buffer: Default::default(),
+            show_all_lasers: false,
+        }
     }
 
     pub fn add(&mut self, weapon: Weapon) -> Handle<Weapon> {
@@ -324,17 +1498,97 @@ impl WeaponContainer {
         self.pool.is_valid_handle(weapon)
     }
 
-    pub fn free(&mut self, weapon: Handle<Weapon>) {
+    /// Removes nodes of the weapon from the scene and the weapon from its owner's weapon list,
+    /// then frees the weapon.
+    pub fn remove(
+        &mut self,
+        weapon: Handle<Weapon>,
+        scene: &mut Scene,
+        actors: &mut ActorContainer,
+    ) {
+        if !self.contains(weapon) {
+            return;
+        }
+        let owner = self.pool[weapon].owner();
+        if actors.contains(owner) {
+            actors.get_mut(owner).remove_weapon(weapon);
+        }
+        self.pool[weapon].clean_up(scene);
         self.pool.free(weapon);
     }
 
+    /// Removes every weapon whose owner is gone and returns their handles, which are invalid
+    /// now.
+    pub fn remove_orphans(
+        &mut self,
+        scene: &mut Scene,
+        actors: &ActorContainer,
+    ) -> Vec<Handle<Weapon>> {
+        let orphans = self
+            .pool
+            .pair_iter()
+            .filter(|(_, weapon)| !actors.contains(weapon.owner()))
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        for &weapon in orphans.iter() {
+            self.pool[weapon].clean_up(scene);
+            self.pool.free(weapon);
+        }
+        orphans
+    }
+
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Weapon> {
         self.pool.iter_mut()
     }
 
-    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer) {
+    /// Moves reserve ammo that weapons of old saves carry to the pools of their owners, it's
+    /// harmless for weapons that carry none.
+    pub fn move_legacy_reserve_ammo(&mut self, actors: &mut ActorContainer) {
         for weapon in self.pool.iter_mut() {
-            weapon.update(scene, actors)
+            let reserve_ammo = weapon.take_legacy_reserve_ammo();
+            if let (Some(ammo_type), true) = (
+                weapon.definition().ammo_type,
+                actors.contains(weapon.owner()),
+            ) {
+                actors
+                    .get_mut(weapon.owner())
+                    .give_ammo(ammo_type, reserve_ammo);
+            }
+        }
+    }
+
+    /// Updates every weapon, only weapons of `local_player` show their laser sights unless
+    /// `show_all_lasers` is set. Finished reloads take their ammo from the pools of the owners.
+    pub fn update(
+        &mut self,
+        scene: &mut Scene,
+        actors: &mut ActorContainer,
+        local_player: Handle<Actor>,
+        dt: f32,
+    ) {
+        scope_profile!();
+
+        for (handle, weapon) in self.pool.pair_iter_mut() {
+            let owner = weapon.owner();
+            let ammo_type = weapon.definition().ammo_type;
+            let reserve_ammo = match ammo_type {
+                Some(ammo_type) if actors.contains(owner) => {
+                    actors.get(owner).reserve_ammo(ammo_type)
+                }
+                _ => 0,
+            };
+            let loaded = weapon.update(
+                handle,
+                scene,
+                actors,
+                reserve_ammo,
+                self.show_all_lasers || (owner.is_some() && owner == local_player),
+                dt,
+                &mut self.query_buffer,
+            );
+            if let (Some(ammo_type), true) = (ammo_type, loaded > 0) {
+                actors.get_mut(owner).take_ammo(ammo_type, loaded);
+            }
         }
     }
 }
@@ -352,3 +1606,296 @@ impl IndexMut<Handle<Weapon>> for WeaponContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::resource_manager;
+    use fyrox::{core::futures::executor::block_on, scene::pivot::PivotBuilder};
+    use std::path::Path;
+
+    #[test]
+    fn missing_model_is_reported_instead_of_panicking() {
+        let definition = Weapon::get_definition(WeaponKind::M4);
+        assert!(!Path::new(definition.model).exists());
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let result = block_on(Weapon::new(
+            WeaponKind::M4,
+            resource_manager(),
+            &mut Scene::new(),
+            sender,
+        ));
+        match result {
+            Err(GameError::ResourceLoad { path, .. }) => {
+                assert_eq!(path, Path::new(definition.model))
+            }
+            Ok(_) => panic!("weapon spawned without its model"),
+        }
+    }
+
+    #[test]
+    fn weapon_sounds_exist() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        for kind in WeaponKind::ALL {
+            let definition = Weapon::get_definition(kind);
+            let sounds = std::iter::once(definition.shot_sound)
+                .chain(definition.reload_sound)
+                .chain(definition.dry_fire_sound);
+            for path in sounds {
+                assert!(root.join(path).exists(), "{:?}: {} is missing", kind, path);
+            }
+        }
+        assert!(root.join(VENT_SOUND).exists());
+    }
+
+    #[test]
+    fn pellets_split_damage_of_a_shot() {
+        let shotgun = Weapon::get_definition(WeaponKind::Shotgun);
+        assert!(shotgun.pellets_per_shot > 1);
+        let total = shotgun.pellet_damage() * shotgun.pellets_per_shot as f32;
+        assert!((total - shotgun.damage).abs() < 1.0e-4);
+
+        let m4 = Weapon::get_definition(WeaponKind::M4);
+        assert_eq!(m4.pellets_per_shot, 1);
+        assert_eq!(m4.pellet_damage(), m4.damage);
+    }
+
+    #[test]
+    fn pellets_scatter_within_cone() {
+        let shotgun = Weapon::get_definition(WeaponKind::Shotgun);
+        let direction = Vector3::new(1.0, 0.5, -2.0).normalize();
+        for _ in 0..200 {
+            let pellet = spread_direction(direction, shotgun.pellet_spread_degrees);
+            assert!((pellet.norm() - 1.0).abs() < 1.0e-4);
+            let angle = pellet.angle(&direction).to_degrees();
+            assert!(angle <= shotgun.pellet_spread_degrees + 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn sustained_fire_overheats_plasma_rifle() {
+        let definition = Weapon::get_definition(WeaponKind::PlasmaRifle);
+        let mut weapon = Weapon {
+            kind: WeaponKind::PlasmaRifle,
+            ammo_in_magazine: definition.magazine_size,
+            ..Default::default()
+        };
+        let mut scene = Scene::new();
+        let dt = 1.0 / 60.0;
+        let mut time = GameTime {
+            clock: std::time::Instant::now(),
+            elapsed: 0.0,
+            delta: dt,
+        };
+        let mut step = |weapon: &mut Weapon, scene: &mut Scene| {
+            time.elapsed += dt as f64;
+            weapon.cool_down(dt);
+            weapon.try_shoot(0, scene, time, false)
+        };
+
+        // Trigger is held down.
+        let mut frames = 0;
+        while !weapon.is_overheated() {
+            step(&mut weapon, &mut scene);
+            frames += 1;
+            assert!(frames as f32 * dt < 5.0, "rifle never overheats");
+        }
+
+        // Locked out until the heat is down to the recovery fraction.
+        let recovery = definition.overheat_threshold * OVERHEAT_RECOVERY_FRACTION;
+        while weapon.heat > recovery + definition.cooling_rate * dt {
+            assert_eq!(step(&mut weapon, &mut scene), ShootResult::Overheated);
+        }
+        let mut result = step(&mut weapon, &mut scene);
+        while result == ShootResult::Cooldown {
+            result = step(&mut weapon, &mut scene);
+        }
+        assert_eq!(result, ShootResult::Shot);
+        assert!(!weapon.is_overheated());
+    }
+
+    // Weapon as saves made before magazines, ammo pools, switching and heat store it.
+    #[derive(Visit)]
+    struct BaselineWeapon {
+        kind: WeaponKind,
+        model: Handle<Node>,
+        laser_dot: Handle<Node>,
+        shot_point: Handle<Node>,
+        offset: Vector3<f32>,
+        dest_offset: Vector3<f32>,
+        last_shot_time: f64,
+        shot_position: Vector3<f32>,
+        owner: Handle<Actor>,
+        ammo: u32,
+    }
+
+    #[test]
+    fn baseline_save_loads_and_moves_ammo_to_owner() {
+        let mut actors = ActorContainer::new();
+        let owner = actors.add(Actor::default());
+        let mut baseline = BaselineWeapon {
+            kind: WeaponKind::M4,
+            model: Handle::NONE,
+            laser_dot: Handle::NONE,
+            shot_point: Handle::NONE,
+            offset: Vector3::default(),
+            dest_offset: Vector3::default(),
+            last_shot_time: 2.0,
+            shot_position: Vector3::default(),
+            owner,
+            ammo: 250,
+        };
+        let path = std::env::temp_dir().join(format!("wood_weapon_{}.bin", std::process::id()));
+        let mut visitor = Visitor::new();
+        baseline.visit("Weapon", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+
+        let mut weapon = Weapon::default();
+        let mut visitor = block_on(Visitor::load_binary(&path)).unwrap();
+        weapon.visit("Weapon", &mut visitor).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(weapon.owner, owner);
+        assert_eq!(weapon.last_shot_time, 2.0);
+        assert_eq!(weapon.state, WeaponState::Ready);
+        assert!(weapon.trigger_released);
+        assert!(weapon.laser_enabled);
+        assert_eq!(weapon.heat, 0.0);
+        let magazine_size = Weapon::get_definition(WeaponKind::M4).magazine_size;
+        assert_eq!(weapon.ammo_in_magazine, magazine_size);
+
+        let mut weapons = WeaponContainer::new();
+        weapons.add(weapon);
+        weapons.move_legacy_reserve_ammo(&mut actors);
+        assert_eq!(
+            actors.get(owner).reserve_ammo(AmmoType::Rifle),
+            250 - magazine_size
+        );
+        // Second pass has nothing left to move.
+        weapons.move_legacy_reserve_ammo(&mut actors);
+        assert_eq!(
+            actors.get(owner).reserve_ammo(AmmoType::Rifle),
+            250 - magazine_size
+        );
+    }
+
+    fn add_weapon(
+        weapons: &mut WeaponContainer,
+        actors: &mut ActorContainer,
+        owner: Handle<Actor>,
+        scene: &mut Scene,
+    ) -> Handle<Weapon> {
+        let weapon = weapons.add(Weapon {
+            model: PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph),
+            owner,
+            ..Default::default()
+        });
+        actors.get_mut(owner).weapons.push(weapon);
+        weapon
+    }
+
+    #[test]
+    fn removing_weapons_fixes_owner_indices() {
+        let mut scene = Scene::new();
+        let mut weapons = WeaponContainer::new();
+        let mut actors = ActorContainer::new();
+        let owner = actors.add(Actor::default());
+        let [a, b, c] = [(); 3].map(|_| add_weapon(&mut weapons, &mut actors, owner, &mut scene));
+        let character = actors.get_mut(owner);
+        character.current_weapon = 2;
+        character.previous_weapon = 0;
+
+        // Weapons after the removed one shift down, the current one stays in hands.
+        weapons.remove(b, &mut scene, &mut actors);
+        let character = actors.get(owner);
+        assert_eq!(character.weapons, vec![a, c]);
+        assert_eq!(character.current_weapon(), c);
+        assert_eq!(character.previous_weapon, 0);
+        assert!(!weapons.contains(b));
+
+        // Removed weapon in hands is replaced by the last one left.
+        weapons.remove(c, &mut scene, &mut actors);
+        let character = actors.get(owner);
+        assert_eq!(character.weapons, vec![a]);
+        assert_eq!(character.current_weapon(), a);
+        assert_eq!(character.previous_weapon, 0);
+
+        weapons.remove(a, &mut scene, &mut actors);
+        assert_eq!(actors.get(owner).current_weapon(), Handle::NONE);
+    }
+
+    #[test]
+    fn weapons_of_removed_actor_are_orphans() {
+        let mut scene = Scene::new();
+        let mut weapons = WeaponContainer::new();
+        let mut actors = ActorContainer::new();
+        let dead = actors.add(Actor::default());
+        let alive = actors.add(Actor::default());
+        let dead_weapons = [(); 2].map(|_| add_weapon(&mut weapons, &mut actors, dead, &mut scene));
+        let kept = add_weapon(&mut weapons, &mut actors, alive, &mut scene);
+
+        actors.free(dead);
+        let mut orphans = weapons.remove_orphans(&mut scene, &actors);
+        orphans.sort_by_key(|handle| handle.index());
+        assert_eq!(orphans, dead_weapons.to_vec());
+        for weapon in dead_weapons {
+            assert!(!weapons.contains(weapon));
+        }
+        assert!(weapons.contains(kept));
+        assert!(weapons.remove_orphans(&mut scene, &actors).is_empty());
+    }
+
+    #[test]
+    fn burst_keeps_its_cadence_while_trigger_is_held() {
+        let (count, interval) = match Weapon::get_definition(WeaponKind::M4).fire_mode {
+            FireMode::Burst { count, interval } => (count, interval),
+            _ => panic!("M4 doesn't shoot bursts"),
+        };
+        let mut scene = Scene::new();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut weapon = Weapon {
+            kind: WeaponKind::M4,
+            model: PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph),
+            ammo_in_magazine: 30,
+            sender: Some(sender),
+            ..Default::default()
+        };
+        let dt = 1.0 / 60.0;
+        let mut time = GameTime {
+            clock: std::time::Instant::now(),
+            elapsed: 0.0,
+            delta: dt,
+        };
+
+        // Trigger is held down for a second. Owner asks for a shot every frame, before the
+        // burst shots of the weapon are handled.
+        let mut shot_times = Vec::new();
+        for _ in 0..60 {
+            time.elapsed += dt as f64;
+            if weapon.try_shoot(0, &mut scene, time, false) == ShootResult::Shot {
+                shot_times.push(time.elapsed);
+            }
+            weapon.update_burst(Handle::NONE, dt);
+            let bursts = receiver
+                .try_iter()
+                .filter(|message| matches!(message, Message::ShootBurst { .. }))
+                .count();
+            for _ in 0..bursts {
+                if weapon.try_shoot(0, &mut scene, time, true) == ShootResult::Shot {
+                    shot_times.push(time.elapsed);
+                }
+            }
+        }
+        assert_eq!(shot_times.len(), count as usize);
+        for pair in shot_times.windows(2) {
+            assert!((pair[1] - pair[0] - interval).abs() <= dt as f64 + 1.0e-6);
+        }
+
+        // Next pull of the trigger starts a new burst.
+        weapon.release_trigger();
+        assert_eq!(
+            weapon.try_shoot(0, &mut scene, time, false),
+            ShootResult::Shot
+        );
+    }
+}
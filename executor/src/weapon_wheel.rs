@@ -0,0 +1,265 @@
+//! Weapon wheel of the HUD. It is shown while the weapon wheel key is held and lays weapons of
+//! the player out evenly on a ring, the weapon in the direction of the mouse is highlighted.
+//! Cells are built once for the largest possible wheel and only moved afterwards.
+
+use crate::{damage_indicator::polar_margin, hud::WeaponWheelData, weapon::WeaponKind};
+use fyrox::{
+    core::{color::Color, parking_lot::Mutex, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        image::{ImageBuilder, ImageMessage},
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        ttf::{Font, SharedFont},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    utils,
+};
+use std::{path::Path, sync::Arc};
+
+/// Wheel has a cell for every weapon slot.
+pub const MAX_WHEEL_WEAPONS: usize = 9;
+const RADIUS: f32 = 170.0;
+const CELL_SIZE: f32 = 96.0;
+const CELL_COLOR: Color = Color {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 150,
+};
+const HIGHLIGHT_COLOR: Color = Color {
+    r: 79,
+    g: 79,
+    b: 255,
+    a: 200,
+};
+const EMPTY_COLOR: Color = Color {
+    r: 120,
+    g: 120,
+    b: 120,
+    a: 140,
+};
+
+struct Cell {
+    root: Handle<UiNode>,
+    icon: Handle<UiNode>,
+    ammo: Handle<UiNode>,
+    kind: Option<WeaponKind>,
+}
+
+pub struct WeaponWheel {
+    pub root: Handle<UiNode>,
+    cells: Vec<Cell>,
+    name: Handle<UiNode>,
+    resource_manager: ResourceManager,
+    is_visible: bool,
+}
+
+impl WeaponWheel {
+    pub fn new(
+        ctx: &mut BuildContext,
+        resource_manager: ResourceManager,
+        row: usize,
+        column: usize,
+    ) -> Self {
+        // Default char set has no infinity sign which is shown for melee weapons.
+        let font = fyrox::core::futures::executor::block_on(Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            24.0,
+            &[0x20..0x7F, 0x221E..0x221F],
+        ))
+        .unwrap();
+        let font = SharedFont(Arc::new(Mutex::new(font)));
+
+        let cells = (0..MAX_WHEEL_WEAPONS)
+            .map(|_| {
+                let icon;
+                let ammo;
+                let root = BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_width(CELL_SIZE)
+                        .with_height(CELL_SIZE)
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_vertical_alignment(VerticalAlignment::Center)
+                        .with_background(Brush::Solid(CELL_COLOR))
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                    .with_vertical_alignment(VerticalAlignment::Center)
+                                    .with_child({
+                                        icon = ImageBuilder::new(
+                                            WidgetBuilder::new().with_width(48.0).with_height(48.0),
+                                        )
+                                        .build(ctx);
+                                        icon
+                                    })
+                                    .with_child({
+                                        ammo = TextBuilder::new(WidgetBuilder::new())
+                                            .with_font(font.clone())
+                                            .with_horizontal_text_alignment(
+                                                HorizontalAlignment::Center,
+                                            )
+                                            .build(ctx);
+                                        ammo
+                                    }),
+                            )
+                            .build(ctx),
+                        ),
+                )
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx);
+                Cell {
+                    root,
+                    icon,
+                    ammo,
+                    kind: None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let name = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_font(font)
+        .build(ctx);
+
+        let size = 2.0 * (RADIUS + CELL_SIZE);
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .on_row(row)
+                .on_column(column)
+                .with_width(size)
+                .with_height(size)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_background(Brush::Solid(Color::TRANSPARENT))
+                .with_children(cells.iter().map(|cell| cell.root))
+                .with_child(name),
+        )
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx);
+
+        Self {
+            root,
+            cells,
+            name,
+            resource_manager,
+            is_visible: false,
+        }
+    }
+
+    /// Shows given wheel, `None` hides it.
+    pub fn set_data(&mut self, ui: &mut UserInterface, data: Option<&WeaponWheelData>) {
+        let data = match data {
+            Some(data) => data,
+            None => {
+                if self.is_visible {
+                    self.is_visible = false;
+                    ui.send_message(WidgetMessage::visibility(
+                        self.root,
+                        MessageDirection::ToWidget,
+                        false,
+                    ));
+                }
+                return;
+            }
+        };
+
+        if !self.is_visible {
+            self.is_visible = true;
+            ui.send_message(WidgetMessage::visibility(
+                self.root,
+                MessageDirection::ToWidget,
+                true,
+            ));
+        }
+
+        let count = data.items.len().min(MAX_WHEEL_WEAPONS);
+        for (i, cell) in self.cells.iter_mut().enumerate() {
+            let item = match data.items.get(i) {
+                Some(item) if i < count => item,
+                _ => {
+                    ui.send_message(WidgetMessage::visibility(
+                        cell.root,
+                        MessageDirection::ToWidget,
+                        false,
+                    ));
+                    continue;
+                }
+            };
+
+            ui.send_message(WidgetMessage::visibility(
+                cell.root,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            // First weapon is on top, the rest follow clockwise.
+            ui.send_message(WidgetMessage::margin(
+                cell.root,
+                MessageDirection::ToWidget,
+                polar_margin(i as f32 * std::f32::consts::TAU / count as f32, RADIUS),
+            ));
+            ui.send_message(WidgetMessage::background(
+                cell.root,
+                MessageDirection::ToWidget,
+                Brush::Solid(if i == data.highlighted {
+                    HIGHLIGHT_COLOR
+                } else {
+                    CELL_COLOR
+                }),
+            ));
+
+            if cell.kind != Some(item.kind) {
+                cell.kind = Some(item.kind);
+                ui.send_message(ImageMessage::texture(
+                    cell.icon,
+                    MessageDirection::ToWidget,
+                    Some(utils::into_gui_texture(
+                        self.resource_manager.request_texture(item.icon),
+                    )),
+                ));
+            }
+
+            // Empty weapons can be selected anyway, they're just greyed out.
+            let is_empty = !item.is_melee && item.ammo == 0;
+            let tint = if is_empty { EMPTY_COLOR } else { Color::WHITE };
+            ui.send_message(WidgetMessage::background(
+                cell.icon,
+                MessageDirection::ToWidget,
+                Brush::Solid(tint),
+            ));
+            ui.send_message(WidgetMessage::foreground(
+                cell.ammo,
+                MessageDirection::ToWidget,
+                Brush::Solid(tint),
+            ));
+            ui.send_message(TextMessage::text(
+                cell.ammo,
+                MessageDirection::ToWidget,
+                if item.is_melee {
+                    "\u{221E}".to_owned()
+                } else {
+                    item.ammo.to_string()
+                },
+            ));
+        }
+
+        ui.send_message(TextMessage::text(
+            self.name,
+            MessageDirection::ToWidget,
+            data.items
+                .get(data.highlighted)
+                .map(|item| format!("{:?}", item.kind))
+                .unwrap_or_default(),
+        ));
+    }
+}